@@ -0,0 +1,173 @@
+//! 无障碍偏好：减少动态效果 / 降低透明度
+//!
+//! 启动时读取系统设置（Windows：客户区动画与「透明效果」；macOS：辅助功能中的减少动态效果/降低透明度；
+//! GNOME：enable-animations），设置中可按项覆盖为「开启 / 关闭」。
+//! 减少动态效果：关闭 egui 过渡动画与平滑滚动；降低透明度：不画背景点阵，去掉窗口/弹出层的半透明阴影。
+
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+
+/// 单项偏好：跟随系统或手动指定
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum A11yOverride {
+    #[default]
+    System,
+    On,
+    Off,
+}
+
+impl A11yOverride {
+    pub const ALL: [A11yOverride; 3] = [A11yOverride::System, A11yOverride::On, A11yOverride::Off];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            A11yOverride::System => "跟随系统",
+            A11yOverride::On => "开启",
+            A11yOverride::Off => "关闭",
+        }
+    }
+
+    fn resolve(self, system: bool) -> bool {
+        match self {
+            A11yOverride::System => system,
+            A11yOverride::On => true,
+            A11yOverride::Off => false,
+        }
+    }
+}
+
+/// 设置中的无障碍覆盖项（持久化）
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AccessibilitySettings {
+    #[serde(default)]
+    pub reduce_motion: A11yOverride,
+    #[serde(default)]
+    pub reduce_transparency: A11yOverride,
+}
+
+/// 系统当前的无障碍偏好
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SystemPrefs {
+    pub reduce_motion: bool,
+    pub reduce_transparency: bool,
+}
+
+/// 最终生效的偏好（系统 + 覆盖）
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Effective {
+    pub reduce_motion: bool,
+    pub reduce_transparency: bool,
+}
+
+impl AccessibilitySettings {
+    pub fn effective(&self, system: SystemPrefs) -> Effective {
+        Effective {
+            reduce_motion: self.reduce_motion.resolve(system.reduce_motion),
+            reduce_transparency: self.reduce_transparency.resolve(system.reduce_transparency),
+        }
+    }
+}
+
+/// 按生效偏好调整 egui 样式（深浅两套都改）
+pub fn apply(ctx: &egui::Context, prefs: Effective) {
+    let default_style = egui::Style::default();
+    ctx.all_styles_mut(|style| {
+        if prefs.reduce_motion {
+            style.animation_time = 0.0;
+            style.scroll_animation = egui::style::ScrollAnimation::none();
+        } else {
+            style.animation_time = default_style.animation_time;
+            style.scroll_animation = default_style.scroll_animation;
+        }
+        let base = if style.visuals.dark_mode {
+            egui::Visuals::dark()
+        } else {
+            egui::Visuals::light()
+        };
+        if prefs.reduce_transparency {
+            style.visuals.window_shadow = egui::Shadow::NONE;
+            style.visuals.popup_shadow = egui::Shadow::NONE;
+        } else {
+            style.visuals.window_shadow = base.window_shadow;
+            style.visuals.popup_shadow = base.popup_shadow;
+        }
+    });
+}
+
+/// 读取系统偏好（启动时调用一次）
+pub fn detect() -> SystemPrefs {
+    SystemPrefs {
+        reduce_motion: detect_reduce_motion(),
+        reduce_transparency: detect_reduce_transparency(),
+    }
+}
+
+#[cfg(windows)]
+fn detect_reduce_motion() -> bool {
+    use windows_sys::Win32::UI::WindowsAndMessaging::{SPI_GETCLIENTAREAANIMATION, SystemParametersInfoW};
+    let mut enabled: i32 = 1;
+    let ok = unsafe { SystemParametersInfoW(SPI_GETCLIENTAREAANIMATION, 0, &mut enabled as *mut i32 as *mut _, 0) };
+    ok != 0 && enabled == 0
+}
+
+#[cfg(windows)]
+fn detect_reduce_transparency() -> bool {
+    // 「设置 → 个性化 → 颜色 → 透明效果」关闭时 EnableTransparency 为 0
+    command_output(
+        "reg",
+        &[
+            "query",
+            r"HKCU\Software\Microsoft\Windows\CurrentVersion\Themes\Personalize",
+            "/v",
+            "EnableTransparency",
+        ],
+    )
+    .is_some_and(|out| out.split_whitespace().last() == Some("0x0"))
+}
+
+#[cfg(target_os = "macos")]
+fn detect_reduce_motion() -> bool {
+    command_output("defaults", &["read", "com.apple.universalaccess", "reduceMotion"]).is_some_and(|v| v.trim() == "1")
+}
+
+#[cfg(target_os = "macos")]
+fn detect_reduce_transparency() -> bool {
+    command_output("defaults", &["read", "com.apple.universalaccess", "reduceTransparency"])
+        .is_some_and(|v| v.trim() == "1")
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn detect_reduce_motion() -> bool {
+    command_output("gsettings", &["get", "org.gnome.desktop.interface", "enable-animations"])
+        .is_some_and(|v| v.trim() == "false")
+}
+
+/// Linux 桌面没有统一的「降低透明度」设置，只能手动开启
+#[cfg(all(unix, not(target_os = "macos")))]
+fn detect_reduce_transparency() -> bool {
+    false
+}
+
+#[cfg(not(any(windows, unix)))]
+fn detect_reduce_motion() -> bool {
+    false
+}
+
+#[cfg(not(any(windows, unix)))]
+fn detect_reduce_transparency() -> bool {
+    false
+}
+
+#[cfg(any(windows, unix))]
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let mut cmd = std::process::Command::new(program);
+    cmd.args(args);
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    }
+    let out = cmd.output().ok()?;
+    out.status.success().then(|| String::from_utf8_lossy(&out.stdout).into_owned())
+}
@@ -0,0 +1,123 @@
+//! 成就：由专注记录（含归档）汇总出的累计番茄数、时长、单日最多与最长连续天数，对照成就表判定是否解锁
+//!
+//! 成就定义全部在 `ACHIEVEMENTS` 表中，新增一项只需加一行；已解锁的成就与解锁时间保存在数据库的 achievements 表。
+//! 每完成一个番茄检查一次，新解锁的以应用内提示告知，主界面「成就」中查看全部成就与进度。
+
+use chrono::NaiveDate;
+use rusqlite::Connection;
+
+use crate::db;
+
+/// 成就的判定条件
+#[derive(Clone, Copy, Debug)]
+pub enum Rule {
+    /// 累计完成的番茄数
+    TotalSessions(u32),
+    /// 累计专注小时数
+    TotalHours(u32),
+    /// 单日完成的番茄数
+    SessionsInDay(u32),
+    /// 连续有专注的天数
+    DayStreak(u32),
+}
+
+/// 一项成就
+pub struct Achievement {
+    /// 持久化用的键，定义后不要改
+    pub key: &'static str,
+    pub icon: &'static str,
+    pub title: &'static str,
+    pub description: &'static str,
+    pub rule: Rule,
+}
+
+/// 成就表：按展示顺序排列
+pub const ACHIEVEMENTS: &[Achievement] = &[
+    Achievement { key: "first_pomodoro", icon: "🍅", title: "第一个番茄", description: "完成第一个番茄", rule: Rule::TotalSessions(1) },
+    Achievement { key: "day_4", icon: "🌱", title: "渐入佳境", description: "单日完成 4 个番茄", rule: Rule::SessionsInDay(4) },
+    Achievement { key: "day_10", icon: "🔥", title: "单日 10 🍅", description: "单日完成 10 个番茄", rule: Rule::SessionsInDay(10) },
+    Achievement { key: "streak_3", icon: "📅", title: "连续 3 天", description: "连续 3 天都有专注", rule: Rule::DayStreak(3) },
+    Achievement { key: "streak_7", icon: "🗓", title: "连续 7 天", description: "连续 7 天都有专注", rule: Rule::DayStreak(7) },
+    Achievement { key: "streak_30", icon: "🏅", title: "连续 30 天", description: "连续 30 天都有专注", rule: Rule::DayStreak(30) },
+    Achievement { key: "sessions_100", icon: "💯", title: "百个番茄", description: "累计完成 100 个番茄", rule: Rule::TotalSessions(100) },
+    Achievement { key: "hours_100", icon: "⏳", title: "累计 100 小时", description: "累计专注 100 小时", rule: Rule::TotalHours(100) },
+    Achievement { key: "hours_1000", icon: "🏆", title: "累计 1000 小时", description: "累计专注 1000 小时", rule: Rule::TotalHours(1000) },
+];
+
+/// 判定成就用的汇总数据
+#[derive(Clone, Debug, Default)]
+pub struct FocusTotals {
+    pub sessions: u32,
+    pub total_secs: i64,
+    /// 单日最多番茄数
+    pub best_day_sessions: u32,
+    /// 最长连续有专注的天数
+    pub longest_streak: u32,
+}
+
+impl FocusTotals {
+    /// 汇总主库与归档库中全部已完成的专注
+    pub fn load(conn: &Connection) -> Result<Self, rusqlite::Error> {
+        // 完成时间为 RFC3339 字符串，空串与 "9" 之间即全部记录
+        let mut days = db::daily_focus(conn, "", "9")?;
+        if let Some(archive) = db::open_archive()? {
+            days.extend(db::daily_focus(&archive, "", "9")?);
+        }
+        let mut per_day: std::collections::BTreeMap<String, (i64, u32)> = std::collections::BTreeMap::new();
+        for row in days {
+            let day = per_day.entry(row.day).or_default();
+            day.0 += row.total_secs;
+            day.1 += row.sessions;
+        }
+        let mut totals = FocusTotals::default();
+        let mut streak = 0;
+        let mut previous: Option<NaiveDate> = None;
+        for (day, (secs, sessions)) in &per_day {
+            totals.sessions += sessions;
+            totals.total_secs += secs;
+            totals.best_day_sessions = totals.best_day_sessions.max(*sessions);
+            let Ok(date) = NaiveDate::parse_from_str(day, "%Y-%m-%d") else { continue };
+            streak = if previous.is_some_and(|p| date - p == chrono::Duration::days(1)) { streak + 1 } else { 1 };
+            totals.longest_streak = totals.longest_streak.max(streak);
+            previous = Some(date);
+        }
+        Ok(totals)
+    }
+}
+
+impl Achievement {
+    /// 当前进度与目标值
+    pub fn progress(&self, totals: &FocusTotals) -> (u32, u32) {
+        match self.rule {
+            Rule::TotalSessions(n) => (totals.sessions, n),
+            Rule::TotalHours(n) => ((totals.total_secs / 3600) as u32, n),
+            Rule::SessionsInDay(n) => (totals.best_day_sessions, n),
+            Rule::DayStreak(n) => (totals.longest_streak, n),
+        }
+    }
+
+    pub fn reached(&self, totals: &FocusTotals) -> bool {
+        let (value, target) = self.progress(totals);
+        value >= target
+    }
+}
+
+pub fn find(key: &str) -> Option<&'static Achievement> {
+    ACHIEVEMENTS.iter().find(|a| a.key == key)
+}
+
+/// 检查全部成就，把新达成的记为解锁（unlocked_at 为北京时间 RFC3339），返回新解锁的成就
+pub fn check(conn: &Connection, unlocked_at: &str) -> Result<Vec<&'static Achievement>, rusqlite::Error> {
+    let totals = FocusTotals::load(conn)?;
+    let unlocked = db::load_achievements(conn)?;
+    let mut newly = Vec::new();
+    for achievement in ACHIEVEMENTS {
+        if unlocked.iter().any(|(key, _)| key == achievement.key) || !achievement.reached(&totals) {
+            continue;
+        }
+        if db::unlock_achievement(conn, achievement.key, unlocked_at)? {
+            newly.push(achievement);
+        }
+    }
+    Ok(newly)
+}
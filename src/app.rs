@@ -2,122 +2,261 @@
 
 use eframe::egui;
 use egui::emath::NumExt;
-use chrono::{FixedOffset, Utc};
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, Utc};
 use raw_window_handle::HasWindowHandle;
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
 
-use crate::pomodoro::{Phase, PomodoroState, TimerState};
+use crate::a11y::{A11yOverride, AccessibilitySettings};
+use crate::config::ConfigWatcher;
+use crate::audit::DayAudit;
+use crate::bar::{BarOutput, BarState};
+use crate::breathing::{BreathPattern, BreathingSettings};
+use crate::daily_note::DailyNoteConfig;
+use crate::distraction::{DistractionMonitor, DistractionSettings};
+use crate::labels::TaskColors;
+use crate::linux_pin::{self, LinuxPin};
+use crate::eye_rest::{EyeRestSettings, EyeRestTimer};
+use crate::media::{MediaControl, MediaSettings};
+use crate::metronome::{Metronome, MetronomeSettings};
+use crate::data_sync::{DataSync, DataSyncResult, DataSyncSettings, SyncTarget};
+use crate::mqtt::{MqttPublisher, MqttSettings, MqttStatus};
+use crate::obs::{ObsOutput, ObsSettings};
+use crate::planner::{IdleNudgeSettings, WorkingHours};
+use crate::presence::{Activity, DiscordPresence, DiscordSettings};
+use crate::profile::Profiles;
+use crate::push_actions::{PushAction, PushActionListener};
+use crate::quotes::QuoteSettings;
+use crate::rest_guard::{PendingBreak, RestGuardSettings, RestWarning};
+use crate::ring::{ProgressRing, TimerLayout};
+use crate::session::{ActiveSession, SessionJournal};
+use crate::side_timer::SideTimer;
+use crate::slack::{SlackFocus, SlackSettings, SlackStatus};
+use crate::suspend::{SleepAction, SleepGap};
+use crate::fonts::FontManager;
+use crate::git_suggest::{GitSuggestSettings, GitSuggester};
+use crate::history::{FocusRecord, HistoryCache};
+use crate::errors::{ErrorKind, ErrorReports, ReportErr};
+use crate::logging::LogErr;
+use crate::notify::{Channel, NotificationRouting, Notifier, NotifyEvent};
+use crate::policy::Integration;
+use crate::pomodoro::{Command, PauseStats, Phase, PomodoroConfig, PomodoroState, Routine, RoutineStep, TimerState};
+use crate::sync::{SettingsSync, SyncEndpoint, SyncResult, SyncedSettings};
+use crate::taskbar::ThumbBar;
+use crate::tray::{TrayIcon, TrayState};
+use crate::theme::{AccentColors, Palette, ThemeMode};
+use crate::undo::UndoStack;
+use crate::wellness::{WellnessReminder, WellnessSettings, WellnessTimer};
+
+/// 「完成」时进行中的专注至少这么久（秒）才计入时长
+const PARTIAL_FOCUS_MIN_SECS: i64 = 60;
+
+/// 自动保存间隔（秒）：默认值与设置中的可选范围
+const DEFAULT_AUTOSAVE_SECS: u64 = 30;
+const AUTOSAVE_SECS_RANGE: (u64, u64) = (5, 600);
 
 /// 桌面右上角边距（逻辑像素）
 const PIN_MARGIN: f32 = 16.0;
 
-/// White Text 主题色（参考 OnePomodoro WhiteTextView.xaml.cs）
-mod white_text_theme {
-    /// 专注/番茄阶段：红 PointLight
-    pub const FOCUS_RGB: (u8, u8, u8) = (217, 17, 83);
-    /// 休息阶段：蓝 PointLight
-    pub const RELAX_RGB: (u8, u8, u8) = (255, 193, 7); // 黄色
-    /// 深色背景（接近黑）
-    pub const BG_RGB: (u8, u8, u8) = (18, 18, 24);
-    /// 主文字白
-    pub const TEXT_WHITE: (u8, u8, u8) = (255, 255, 255);
-    /// 次要文字
-    pub const TEXT_DIM: (u8, u8, u8) = (200, 200, 210);
-}
-
-/// 紧凑 overlay 尺寸（保证进度条+「开始/暂停」按钮完整显示，留足垂直空间以兼容高 DPI/缩放）
+/// 紧凑 overlay 尺寸（保证进度条+番茄数圆圈+「开始/暂停」按钮完整显示，留足垂直空间以兼容高 DPI/缩放）
 const COMPACT_WIDTH: f32 = 300.0;
-const COMPACT_HEIGHT: f32 = 228.0;
-
-/// 设置中文字体，避免中文乱码。优先使用系统自带字体。
-fn setup_chinese_fonts(ctx: &egui::Context) {
-    let mut fonts = egui::FontDefinitions::default();
-
-    #[cfg(windows)]
-    let system_font_paths = [
-        r"C:\Windows\Fonts\msyh.ttc",   // 微软雅黑
-        r"C:\Windows\Fonts\simhei.ttf", // 黑体
-        r"C:\Windows\Fonts\simsun.ttc",  // 宋体
-    ];
-
-    #[cfg(not(windows))]
-    let system_font_paths: [&str; 0] = [];
-
-    for path in system_font_paths {
-        if let Ok(bytes) = std::fs::read(path) {
-            let leaked: &'static [u8] = Box::leak(bytes.into_boxed_slice());
-            fonts.font_data.insert(
-                "chinese".to_owned(),
-                Arc::new(egui::FontData::from_static(leaked)),
-            );
-            fonts
-                .families
-                .entry(egui::FontFamily::Proportional)
-                .or_default()
-                .insert(0, "chinese".to_owned());
-            fonts
-                .families
-                .entry(egui::FontFamily::Monospace)
-                .or_default()
-                .insert(0, "chinese".to_owned());
-            ctx.set_fonts(fonts);
-            return;
-        }
-    }
-
-    // 非 Windows 或系统字体未找到时，使用内置后备字体（仅基本拉丁字符，中文仍可能方框）
-    // 可后续将 Noto Sans SC 等放入 assets 并 include_bytes 以支持跨平台中文
-    #[allow(unused)]
-    if let Some(embedded) = option_env!("RED_TOMATO_FONT_PATH") {
-        if let Ok(bytes) = std::fs::read(embedded) {
-            let leaked: &'static [u8] = Box::leak(bytes.into_boxed_slice());
-            fonts.font_data.insert(
-                "chinese".to_owned(),
-                Arc::new(egui::FontData::from_static(leaked)),
-            );
-            fonts
-                .families
-                .entry(egui::FontFamily::Proportional)
-                .or_default()
-                .insert(0, "chinese".to_owned());
-            ctx.set_fonts(fonts);
-        }
-    }
-}
+const COMPACT_HEIGHT: f32 = 246.0;
 
 /// 完整模式默认窗口尺寸（高度留足，避免高 DPI/缩放下底部按钮被裁切）
-const FULL_SIZE: (f32, f32) = (380.0, 540.0);
+const FULL_SIZE: (f32, f32) = (380.0, 572.0);
+/// 完整模式可拖动调整到的最小窗口尺寸（矮于内容时可滚动）
+const FULL_MIN_SIZE: (f32, f32) = (340.0, 420.0);
+/// 完整模式计时器字号、圆环随窗口缩放的范围
+const LAYOUT_SCALE_RANGE: (f32, f32) = (0.8, 2.0);
 
 /// 存储键：任务 + 番茄钟状态 + 专注历史（JSON）
 const STORAGE_KEY_STATE: &str = "red_tomato_state";
 
+/// 北京时区 UTC+8（专注记录、日程等时间均按此时区存储与统计）
+pub(crate) fn beijing() -> FixedOffset {
+    FixedOffset::east_opt(8 * 3600).unwrap()
+}
+
+/// 北京时区 UTC+8 的当前时间
+fn beijing_now() -> DateTime<FixedOffset> {
+    Utc::now().with_timezone(&beijing())
+}
+
 /// 北京时区 UTC+8（专注记录完成时间用）
 fn beijing_now_rfc3339() -> String {
-    let beijing = FixedOffset::east_opt(8 * 3600).unwrap();
-    Utc::now().with_timezone(&beijing).to_rfc3339()
+    beijing_now().to_rfc3339()
 }
 
-/// 单条专注记录：用于按时间统计做了哪些任务（与 SQLite focus_records 表一致）
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct FocusRecord {
-    pub task: String,
-    pub duration_secs: i64,
-    /// 完成时间 ISO 8601
-    pub completed_at: String,
-    /// 完成时的番茄数（本周期内）
-    pub completed_pomodoros: u32,
+/// 秒级时间戳 → 北京时区 RFC3339（按时间段查询记录时作区间边界）
+pub(crate) fn beijing_rfc3339(ts: i64) -> String {
+    DateTime::from_timestamp(ts, 0).unwrap().with_timezone(&beijing()).to_rfc3339()
+}
+
+/// 北京时区某天 0 点的时间戳（按天、按周统计与查询的日界）
+pub(crate) fn beijing_day_start(day: NaiveDate) -> i64 {
+    day.and_hms_opt(0, 0, 0).unwrap().and_local_timezone(beijing()).unwrap().timestamp()
+}
+
+/// 北京时区某天 0 点的 RFC3339 字符串（按日期区间查询记录时的边界）
+pub(crate) fn beijing_day_start_rfc3339(day: NaiveDate) -> String {
+    beijing_rfc3339(beijing_day_start(day))
 }
 
 /// 持久化到 eframe storage 的会话状态（专注历史存 SQLite，不在此）
 #[derive(Serialize, Deserialize)]
 struct PersistedState {
     current_task: String,
+    /// 本次专注的承诺（专注契约）
+    #[serde(default)]
+    current_commitment: String,
+    /// 当前任务所属项目 id
+    #[serde(default)]
+    current_project: Option<i64>,
+    /// 进行中的副计时（按截止时间，重启后继续）
+    #[serde(default)]
+    side_timer: Option<SideTimer>,
     phase: String,
     state: String,
     remaining_secs: i64,
     phase_total_secs: i64,
     completed_pomodoros: u32,
+    /// 加时中的已加时秒数（不在加时中为 None）
+    #[serde(default)]
+    overtime_secs: Option<i64>,
+    /// 连续推迟休息的次数；推迟休息的专注结束后要回到的休息阶段
+    #[serde(default)]
+    snoozes: u32,
+    #[serde(default)]
+    snoozed_break: Option<String>,
+    /// 上次退出时是否为紧凑 / 钉住模式，以及窗口外框左上角位置（逻辑像素；尺寸由模式决定）
+    #[serde(default)]
+    compact: bool,
+    #[serde(default)]
+    pinned: bool,
+    #[serde(default)]
+    window_pos: Option<[f32; 2]>,
+    /// 完整模式下调整过的窗口尺寸
+    #[serde(default)]
+    full_window_size: Option<[f32; 2]>,
+    /// 当前阶段的暂停统计
+    #[serde(default)]
+    pauses: PauseStats,
+    /// 用户选择的界面字体名（None 表示自动选择第一个可用中文字体）
+    #[serde(default)]
+    font: Option<String>,
+    /// 用户指定的字体文件路径
+    #[serde(default)]
+    custom_font_path: String,
+    /// 主题模式："System" / "Dark" / "Light"
+    #[serde(default)]
+    theme: Option<String>,
+    /// 自定义阶段强调色
+    #[serde(default)]
+    accents: AccentColors,
+    /// 阶段时长配置（None 表示默认 25/5/15）
+    #[serde(default)]
+    config: Option<PomodoroConfig>,
+    /// 当前例程（None 为经典番茄）与其中的当前步
+    #[serde(default)]
+    routine: Option<Routine>,
+    #[serde(default)]
+    routine_step: usize,
+    /// 用户自定义的例程
+    #[serde(default)]
+    routines: Vec<Routine>,
+    /// 长休息时显示全屏休息画面
+    #[serde(default)]
+    break_screensaver: bool,
+    /// 专注时在屏幕顶部显示「请勿打扰」横幅
+    #[serde(default)]
+    focus_banner: bool,
+    /// 主界面计时器布局（进度条 / 圆环）
+    #[serde(default)]
+    timer_layout: TimerLayout,
+    /// 设置同步端点（URL + token，仅本机）
+    #[serde(default)]
+    sync: SyncEndpoint,
+    /// 专注记录同步（WebDAV / 同步文件夹，仅本机）
+    #[serde(default)]
+    data_sync: DataSyncSettings,
+    /// 工作时间内长时间未计时的提醒
+    #[serde(default)]
+    idle_nudge: IdleNudgeSettings,
+    /// 按星期的工作时间（时间外压下提醒、显示「下班了」）
+    #[serde(default)]
+    working_hours: WorkingHours,
+    /// 护眼提醒（20-20-20）
+    #[serde(default)]
+    eye_rest: EyeRestSettings,
+    /// 专注时计时器下方轮换的语录
+    #[serde(default)]
+    quotes: QuoteSettings,
+    /// 休息时的呼吸引导
+    #[serde(default)]
+    breathing: BreathingSettings,
+    /// 起来活动、喝水等健康提醒
+    #[serde(default)]
+    wellness: WellnessSettings,
+    /// 连续跳过休息、太久没有长休息时的警示
+    #[serde(default)]
+    rest_guard: RestGuardSettings,
+    /// 专注中前台窗口命中黑名单时的分心提醒
+    #[serde(default)]
+    distraction: DistractionSettings,
+    /// 专注时的滴答声
+    #[serde(default)]
+    tick_sound: MetronomeSettings,
+    /// 专注开始时暂停媒体播放、休息时恢复
+    #[serde(default)]
+    media: MediaSettings,
+    /// 通知路由（事件 → 渠道）与 Webhook / 手机推送地址
+    #[serde(default)]
+    notifications: NotificationRouting,
+    /// 日记笔记集成（完成番茄时追加到按日期命名的 Markdown 文件）
+    #[serde(default)]
+    daily_note: DailyNoteConfig,
+    /// Discord Rich Presence（默认关闭）
+    #[serde(default)]
+    discord: DiscordSettings,
+    /// MQTT 发布（默认关闭）
+    #[serde(default)]
+    mqtt: MqttSettings,
+    /// Slack 专注状态（默认关闭）
+    #[serde(default)]
+    slack: SlackSettings,
+    /// 本机 HTTP 状态接口（仅 http-api 特性）
+    #[cfg(feature = "http-api")]
+    #[serde(default)]
+    http_api: crate::server::ServerSettings,
+    /// OBS 文本源输出文件
+    #[serde(default)]
+    obs: ObsSettings,
+    /// 无障碍覆盖项（减少动态效果 / 降低透明度）
+    #[serde(default)]
+    accessibility: AccessibilitySettings,
+    /// 关闭按钮行为："Minimize" / "Compact" / "Quit"
+    #[serde(default)]
+    close_action: Option<String>,
+    /// 从 git 仓库推荐任务名
+    #[serde(default)]
+    git_suggest: GitSuggestSettings,
+    /// 专注中系统休眠的时间如何处理
+    #[serde(default)]
+    sleep_action: SleepAction,
+    /// 托盘图标显示剩余时间（仅 Windows）
+    #[serde(default)]
+    tray_timer: bool,
+    /// 为 Waybar / Polybar / i3blocks 写出状态文件
+    #[serde(default)]
+    bar_output: bool,
+    /// 超过多少个月的记录移入归档库，0 表示不归档
+    #[serde(default)]
+    archive_after_months: u32,
+    /// 自动保存间隔（秒）
+    #[serde(default)]
+    autosave_secs: Option<u64>,
+    /// 命名的配置档（工作 / 学习等）与当前生效的配置档
+    #[serde(default)]
+    profiles: Profiles,
 }
 
 fn phase_to_str(p: Phase) -> &'static str {
@@ -149,17 +288,513 @@ fn state_from_str(s: &str) -> TimerState {
     }
 }
 
+/// 顶栏 × 按钮的行为
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CloseAction {
+    /// 最小化到任务栏（开启托盘计时时托盘中仍显示剩余时间）
+    Minimize,
+    /// 钉成右上角紧凑小窗（已是紧凑模式时最小化）
+    Compact,
+    /// 退出；计时进行中先确认
+    Quit,
+}
+
+impl CloseAction {
+    const ALL: [CloseAction; 3] = [CloseAction::Minimize, CloseAction::Compact, CloseAction::Quit];
+
+    fn label(self) -> &'static str {
+        match self {
+            CloseAction::Minimize => "最小化",
+            CloseAction::Compact => "钉成小窗",
+            CloseAction::Quit => "退出",
+        }
+    }
+}
+
+fn close_action_to_str(a: CloseAction) -> &'static str {
+    match a {
+        CloseAction::Minimize => "Minimize",
+        CloseAction::Compact => "Compact",
+        CloseAction::Quit => "Quit",
+    }
+}
+fn close_action_from_str(s: &str) -> CloseAction {
+    match s {
+        "Minimize" => CloseAction::Minimize,
+        "Compact" => CloseAction::Compact,
+        _ => CloseAction::Quit,
+    }
+}
+
+/// 时间审计窗口的界面状态
+struct AuditView {
+    day: NaiveDate,
+    plan_start: String,
+    plan_end: String,
+    plan_title: String,
+    ics_path: String,
+    message: Option<String>,
+    /// 当天审计结果与计划块行（删除用），None 表示需要重新从 SQLite 加载
+    loaded: Option<(DayAudit, Vec<crate::db::SpanRow>)>,
+}
+
+impl AuditView {
+    fn new(day: NaiveDate) -> Self {
+        Self {
+            day,
+            plan_start: "09:00".to_string(),
+            plan_end: "10:00".to_string(),
+            plan_title: String::new(),
+            ics_path: String::new(),
+            message: None,
+            loaded: None,
+        }
+    }
+}
+
+/// 触发立即保存的会话状态：任一项变化即保存
+#[derive(Clone, Copy, PartialEq)]
+struct SaveMarker {
+    phase: Phase,
+    state: TimerState,
+    completed_pomodoros: u32,
+    step: usize,
+    compact: bool,
+    pinned: bool,
+}
+
+/// 重置 / 完成前的状态，撤销时恢复；其余字段为因此写入、撤销时要删除的数据：
+/// 放弃中断与放弃记录的 id、部分专注记录的 (完成时间, 时长)、任务完成的 id、标记完成的日程
+struct ResetSnapshot {
+    pomo: PomodoroState,
+    task: String,
+    commitment: String,
+    last_focus_record: Option<(String, String)>,
+    abandoned: Option<AbandonRows>,
+    partial: Option<(String, i64)>,
+    task_completion: Option<i64>,
+    schedule_id: Option<i64>,
+}
+
+/// 重置时写入的放弃中断与放弃记录的 id（写入失败的为 None），撤销时按 id 删除
+struct AbandonRows {
+    interruption: Option<i64>,
+    record: Option<i64>,
+}
+
+/// 时间占比环形图的一个扇区；filter 为点击后对记录列表的筛选（「其他」为 None）
+struct ShareSlice {
+    label: String,
+    secs: i64,
+    sessions: u32,
+    filter: Option<ShareFilter>,
+}
+
+#[derive(Clone)]
+enum ShareFilter {
+    Task(String),
+    Project(i64),
+}
+
+/// 时间占比的查询结果及其条件（筛选范围、按项目或按任务）
+struct ShareChart {
+    range: Option<(String, String)>,
+    by_project: bool,
+    slices: Vec<ShareSlice>,
+}
+
+impl ShareChart {
+    /// 按时长倒序取前 `SHARE_MAX_SLICES` 项，其余合并为「其他」；范围为 None 表示全部记录
+    fn load(range: Option<(String, String)>, by_project: bool) -> Self {
+        let (from, to) = range.clone().unwrap_or_else(|| (String::new(), "9999".to_string()));
+        let mut slices: Vec<ShareSlice> = crate::db::open_and_init()
+            .and_then(|conn| {
+                if by_project {
+                    crate::db::project_totals(&conn, &from, &to).map(|rows| {
+                        rows.into_iter()
+                            .map(|r| ShareSlice {
+                                label: r.name,
+                                secs: r.total_secs,
+                                sessions: r.sessions,
+                                filter: Some(ShareFilter::Project(r.project_id)),
+                            })
+                            .collect()
+                    })
+                } else {
+                    crate::db::top_tasks(&conn, &from, &to, 0).map(|rows| {
+                        rows.into_iter()
+                            .map(|r| ShareSlice {
+                                label: if r.task.is_empty() { "(无任务)".to_string() } else { r.task.clone() },
+                                secs: r.total_secs,
+                                sessions: r.sessions,
+                                // 无任务的记录没法用搜索筛出
+                                filter: (!r.task.is_empty()).then_some(ShareFilter::Task(r.task)),
+                            })
+                            .collect()
+                    })
+                }
+            })
+            .unwrap_or_default();
+        if slices.len() > SHARE_MAX_SLICES {
+            let rest = slices.split_off(SHARE_MAX_SLICES);
+            slices.push(ShareSlice {
+                label: "其他".to_string(),
+                secs: rest.iter().map(|s| s.secs).sum(),
+                sessions: rest.iter().map(|s| s.sessions).sum(),
+                filter: None,
+            });
+        }
+        Self {
+            range,
+            by_project,
+            slices,
+        }
+    }
+}
+
+/// 日程任务做满预计番茄数时的庆祝弹窗状态
+struct TaskCelebration {
+    schedule_id: i64,
+    task: String,
+    pomodoros: u32,
+    /// 已标记任务完成
+    marked: bool,
+    /// 接下来可做的日程（任务, 项目）
+    next: Vec<(String, i64)>,
+    /// 弹出时间，用于入场动画
+    shown_at: std::time::Instant,
+}
+
+/// 日程窗口的界面状态
+struct PlannerView {
+    day: NaiveDate,
+    /// 快速输入，如 `09:00 写代码 ×2`
+    entry: String,
+    project_id: i64,
+    message: Option<String>,
+    /// 当天日程，None 表示需要重新从 SQLite 加载
+    rows: Option<Vec<crate::db::ScheduleRow>>,
+}
+
+/// 收集箱窗口的界面状态
+struct InboxView {
+    /// 是否同时列出已处理的
+    include_done: bool,
+    message: Option<String>,
+    /// None 表示需要重新从 SQLite 加载
+    rows: Option<Vec<crate::db::InboxRow>>,
+}
+
+/// 成就窗口的界面状态
+struct AchievementsView {
+    /// 汇总数据与已解锁成就 (成就键, 解锁时间)；None 表示需要重新从 SQLite 加载
+    loaded: Option<(crate::achievements::FocusTotals, Vec<(String, String)>)>,
+    message: Option<String>,
+}
+
+/// 周报窗口的界面状态
+struct ReviewView {
+    /// 周报最后一天（含），默认今天
+    last_day: NaiveDate,
+    loaded: Option<crate::review::WeeklyReview>,
+    message: Option<String>,
+}
+
+/// 每日回顾窗口的界面状态
+struct DailyReviewView {
+    day: NaiveDate,
+    /// 「换一个」提问的偏移
+    prompt_offset: usize,
+    answer: String,
+    search: String,
+    /// 搜索结果（None 表示需重新查询）
+    results: Option<Vec<crate::db::JournalRow>>,
+    /// 当天专注汇总：(番茄数, 总秒数, 平均秒数)
+    summary: Option<(u32, i64, i64)>,
+    /// 当天路由到「摘要」渠道的通知（随 summary 一起加载）
+    digest: Vec<crate::db::DigestRow>,
+    message: Option<String>,
+}
+
+/// 项目管理窗口的界面状态
+#[derive(Default)]
+struct ProjectsView {
+    new_name: String,
+    message: Option<String>,
+}
+
+/// 统计窗口记录列表的时间范围
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum HistoryRange {
+    #[default]
+    All,
+    Today,
+    Week,
+    Month,
+    Custom,
+}
+
+impl HistoryRange {
+    const ALL: [HistoryRange; 5] = [
+        HistoryRange::All,
+        HistoryRange::Today,
+        HistoryRange::Week,
+        HistoryRange::Month,
+        HistoryRange::Custom,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            HistoryRange::All => "全部",
+            HistoryRange::Today => "今日",
+            HistoryRange::Week => "本周",
+            HistoryRange::Month => "本月",
+            HistoryRange::Custom => "自定义",
+        }
+    }
+}
+
+/// 统计窗口记录列表的筛选输入
+#[derive(Default)]
+struct HistoryFilter {
+    /// 任务名包含的文字
+    search: String,
+    range: HistoryRange,
+    /// 自定义范围的起止日期（YYYY-MM-DD，含首尾两天）
+    custom_from: String,
+    custom_to: String,
+    /// 专注时长下限（分钟）
+    min_minutes: u32,
+    project_id: Option<i64>,
+    /// 同时列出归档库中的记录
+    include_archive: bool,
+}
+
+/// 统计窗口记录列表中的一条：记录本身，以及编辑笔记用的 id 与笔记
+struct HistoryEntry {
+    id: i64,
+    note: String,
+    record: FocusRecord,
+}
+
+/// 统计窗口中打开的记录笔记（列表下方的详情面板）
+struct RecordNote {
+    id: i64,
+    /// 面板标题：完成时间与任务
+    title: String,
+    /// 已保存的笔记
+    saved: String,
+    /// 编辑中的文字；None 为只读显示
+    draft: Option<String>,
+}
+
+/// 统计窗口记录列表：按条件分页加载的结果
+struct HistoryPage {
+    query: crate::db::HistoryQuery,
+    records: Vec<HistoryEntry>,
+    /// 已加载的最后一条 (completed_at, id)，作为下一页的起点
+    cursor: Option<(String, i64)>,
+    /// 已加载到最早的记录
+    exhausted: bool,
+}
+
+impl HistoryPage {
+    fn new(query: crate::db::HistoryQuery) -> Self {
+        let mut page = Self {
+            query,
+            records: Vec::new(),
+            cursor: None,
+            exhausted: false,
+        };
+        page.load_more();
+        page
+    }
+
+    /// 加载下一页追加到末尾；不足一页时视为已到底
+    fn load_more(&mut self) {
+        let before = self.cursor.as_ref().map(|(at, id)| (at.as_str(), *id));
+        let rows = crate::db::open_and_init()
+            .and_then(|conn| crate::db::search_focus_records(&conn, &self.query, before, HISTORY_PAGE_SIZE))
+            .db_err("加载专注记录")
+            .unwrap_or_default();
+        self.exhausted = rows.len() < HISTORY_PAGE_SIZE as usize;
+        if let Some(last) = rows.last() {
+            self.cursor = Some((last.completed_at.clone(), last.id));
+        }
+        self.records.extend(rows.into_iter().map(|mut r| HistoryEntry {
+            id: r.id,
+            note: std::mem::take(&mut r.note),
+            record: FocusRecord::from(r),
+        }));
+    }
+}
+
+/// 统计窗口记录列表：按天的小计随筛选条件查询，展开某天时才加载该天的记录（每天分页）
+struct HistoryDays {
+    query: crate::db::HistoryQuery,
+    days: Vec<crate::db::DayTotalRow>,
+    pages: std::collections::HashMap<String, HistoryPage>,
+}
+
+impl HistoryDays {
+    fn new(query: crate::db::HistoryQuery) -> Self {
+        let days = crate::db::open_and_init()
+            .and_then(|conn| crate::db::search_day_totals(&conn, &query))
+            .db_err("按天汇总专注记录")
+            .unwrap_or_default();
+        Self {
+            query,
+            days,
+            pages: std::collections::HashMap::new(),
+        }
+    }
+
+    /// 某天（YYYY-MM-DD）的记录，首次访问时加载第一页
+    fn page(&mut self, day: &str) -> &mut HistoryPage {
+        let query = &self.query;
+        self.pages.entry(day.to_string()).or_insert_with(|| {
+            let mut query = query.clone();
+            if let Ok(date) = NaiveDate::parse_from_str(day, "%Y-%m-%d") {
+                query.range = Some((
+                    beijing_day_start_rfc3339(date),
+                    beijing_day_start_rfc3339(date + chrono::Duration::days(1)),
+                ));
+            }
+            HistoryPage::new(query)
+        })
+    }
+}
+
+/// 记录列表的日期标题：今天 / 昨天 / 今年内「10-12 周一」/ 更早带年份
+fn history_day_label(day: &str, today: NaiveDate) -> String {
+    let Ok(date) = NaiveDate::parse_from_str(day, "%Y-%m-%d") else { return day.to_string() };
+    match (today - date).num_days() {
+        0 => "今天".to_string(),
+        1 => "昨天".to_string(),
+        _ if date.year() == today.year() => {
+            format!("{} {}", date.format("%m-%d"), crate::review::weekday_label(date))
+        }
+        _ => format!("{} {}", date.format("%Y-%m-%d"), crate::review::weekday_label(date)),
+    }
+}
+
+/// 紧凑时长：「2h30m」「45m」
+fn format_compact_hm(secs: i64) -> String {
+    let mins = secs.max(0) / 60;
+    if mins >= 60 {
+        format!("{}h{:02}m", mins / 60, mins % 60)
+    } else {
+        format!("{}m", mins)
+    }
+}
+
+/// 主界面「今日概览」的缓存：按日期、当前任务与统计项目查询，有新记录或三者变化时重新查询
+struct TodayGlance {
+    day: NaiveDate,
+    task: String,
+    project_id: Option<i64>,
+    sessions: u32,
+    total_secs: i64,
+    task_sessions: u32,
+}
+
+impl TodayGlance {
+    fn load(day: NaiveDate, task: String, project_id: Option<i64>) -> Self {
+        let from = beijing_day_start_rfc3339(day);
+        let to = beijing_day_start_rfc3339(day + chrono::Duration::days(1));
+        let (sessions, total_secs, task_sessions) = crate::db::open_and_init()
+            .and_then(|conn| crate::db::day_glance(&conn, &from, &to, &task, project_id))
+            .db_err("读取今日概览")
+            .unwrap_or_default();
+        Self { day, task, project_id, sessions, total_secs, task_sessions }
+    }
+}
+
+/// 启动解锁界面的状态（数据库已加密时）
+#[derive(Default)]
+struct UnlockView {
+    passphrase: String,
+    error: Option<String>,
+}
+
+/// 设置窗口中启用 / 关闭加密的输入
+#[derive(Default)]
+struct EncryptionForm {
+    passphrase: String,
+    confirm: String,
+    message: Option<String>,
+}
+
+/// 设置窗口中移动数据库的输入
+#[derive(Default)]
+struct RelocateForm {
+    folder: String,
+    message: Option<String>,
+}
+
+/// 数据清除窗口的界面状态
+#[derive(Default)]
+struct PurgeView {
+    /// true 为清空全部数据，否则按项目 / 日期区间
+    everything: bool,
+    project_id: Option<i64>,
+    by_date: bool,
+    from: String,
+    to: String,
+    include_reports: bool,
+    /// 用户输入的确认短语
+    confirm: String,
+    /// 上次预览的范围与匹配条数（范围变化时重新查询）
+    preview: Option<(crate::purge::PurgeScope, u32)>,
+    message: Option<String>,
+    /// 要合并的另一台电脑的数据库文件路径
+    merge_path: String,
+    merge_message: Option<String>,
+    /// 任务改名：原任务名与新任务名
+    rename_from: String,
+    rename_to: String,
+    rename_message: Option<String>,
+    /// 合并重复任务：全部任务名及用量（展开时加载）、筛选词、勾选的写法与合并后的名称
+    task_usage: Option<Vec<crate::db::TaskUsageRow>>,
+    merge_filter: String,
+    merge_selected: Vec<String>,
+    merge_target: String,
+    merge_tasks_message: Option<String>,
+}
+
 pub struct RedTomatoApp {
     pub pomo: PomodoroState,
     /// 当前专注任务（本番茄要完成的事），与番茄工作法关联
     pub current_task: String,
-    /// 专注历史：每次完成一个番茄记录一条，用于按时间统计
-    pub focus_history: Vec<FocusRecord>,
+    /// 专注契约：开始专注前写下的一句承诺，专注期间显示在倒计时下方，完成后随记录保存
+    pub current_commitment: String,
+    /// 当前任务所属项目 id（项目 → 任务）
+    pub current_project: i64,
+    /// 全部项目（启动与增删后从 SQLite 重新加载）
+    projects: Vec<crate::db::ProjectRow>,
+    /// 任务与项目的颜色标签（随项目一起加载）
+    task_colors: TaskColors,
+    /// 项目管理窗口（Some 表示打开）
+    projects_view: Option<ProjectsView>,
+    /// 数据清除窗口（Some 表示打开）
+    purge_view: Option<PurgeView>,
+    /// 数据库已加密且尚未解锁时的解锁界面（Some 时只显示该界面）
+    unlock: Option<UnlockView>,
+    /// 设置中的数据加密表单
+    encryption_form: EncryptionForm,
+    relocate_form: RelocateForm,
+    /// 专注历史：每次完成一个番茄记录一条，用于按时间统计（内存中按天窗口 + 上限淘汰）
+    pub focus_history: HistoryCache,
     /// 是否显示「统计」窗口
     show_statistics: bool,
     compact: bool,
     pinned: bool,
     pin_applied: bool,
+    /// 当前窗口外框位置（每帧记录，退出时保存）；启动时待恢复的上次位置
+    window_pos: Option<egui::Pos2>,
+    restore_window_pos: Option<egui::Pos2>,
+    /// 用户调整过的完整模式窗口尺寸（None 为默认尺寸）
+    full_window_size: Option<egui::Vec2>,
     compact_size_applied: bool,
     /// 从紧凑回到完整时，是否已恢复尺寸
     full_restore_applied: bool,
@@ -171,6 +806,222 @@ pub struct RedTomatoApp {
     system_menu_removed: bool,
     /// 是否显示「关于」窗口
     show_about: bool,
+    /// 是否显示「设置」窗口
+    show_settings: bool,
+    /// 时间审计窗口（Some 表示打开）
+    audit: Option<AuditView>,
+    /// 周报窗口（Some 表示打开）
+    review: Option<ReviewView>,
+    /// 日程窗口（Some 表示打开）
+    planner: Option<PlannerView>,
+    /// 收集箱窗口（Some 表示打开）；随手记输入条（Some 表示展开）及展开后是否还需聚焦；本次专注中记下的条数
+    inbox: Option<InboxView>,
+    /// 成就窗口（Some 表示打开）
+    achievements: Option<AchievementsView>,
+    capture: Option<String>,
+    capture_focus: bool,
+    inbox_captured: u32,
+    /// 上次检查到点日程的时间；到点待确认的日程提醒
+    schedule_checked: Option<std::time::Instant>,
+    schedule_prompt: Option<crate::db::ScheduleRow>,
+    /// 空闲提醒设置；空闲起算时间（最近一次检查到计时、上次提醒或启动时间）
+    idle_nudge: IdleNudgeSettings,
+    idle_since: DateTime<Utc>,
+    /// 按星期的工作时间
+    working_hours: WorkingHours,
+    /// 副计时；展开的副计时输入条（名称、分钟数）
+    side_timer: Option<SideTimer>,
+    side_timer_form: Option<(String, u32)>,
+    /// 「专注到」输入的时刻（HH:MM）与解析失败的原因
+    focus_until: String,
+    focus_until_error: Option<String>,
+    /// 护眼提醒设置与计时
+    eye_rest: EyeRestSettings,
+    eye_rest_timer: EyeRestTimer,
+    /// 专注语录设置；设置中新增语录的输入框与错误提示
+    quotes: QuoteSettings,
+    quote_input: String,
+    quote_error: Option<String>,
+    /// 呼吸引导设置；本次引导的开始时间（休息计时运行中才有）；本次休息是否已跳过引导（回到专注时复位）
+    breathing: BreathingSettings,
+    breathing_started: Option<std::time::Instant>,
+    breathing_skipped: bool,
+    /// 健康提醒设置与间隔计时；上一帧的阶段（判断是否刚进入休息，启动首帧为 None）
+    wellness: WellnessSettings,
+    wellness_timer: WellnessTimer,
+    wellness_phase: Option<Phase>,
+    /// 休息守护设置；专注结束后待进行的休息（下次开始专注时判定是否跳过）；当前显示的休息警示
+    rest_guard: RestGuardSettings,
+    pending_break: Option<PendingBreak>,
+    rest_warning: Option<RestWarning>,
+    /// 分心提醒设置与前台窗口检测；设置中新增关键词的输入框
+    distraction: DistractionSettings,
+    distraction_monitor: DistractionMonitor,
+    distraction_input: String,
+    /// 专注滴答声设置与节拍器（音频线程）
+    tick_sound: MetronomeSettings,
+    metronome: Metronome,
+    /// 媒体自动暂停设置与控制
+    media: MediaSettings,
+    media_control: MediaControl,
+    /// 重置 / 完成前的状态快照，底部提示条上可在 `undo::UNDO_WINDOW` 内撤销
+    undo: UndoStack<ResetSnapshot>,
+    /// 顶部错误提示条（数据库与集成失败）
+    error_reports: ErrorReports,
+    /// 进行中专注的会话日志；启动时读到的上次意外中断的专注（等用户选择记录 / 继续 / 忽略）
+    session_journal: SessionJournal,
+    session_checked: bool,
+    recovered_session: Option<ActiveSession>,
+    /// 专注中系统休眠的处理方式（部署策略可固定）；询问时等待选择的休眠
+    sleep_action: SleepAction,
+    sleep_prompt: Option<SleepGap>,
+    /// 托盘计时图标：开关与当前显示的图标
+    tray_timer: bool,
+    tray: TrayIcon,
+    /// 任务栏缩略图按钮（仅 Windows）
+    thumb_bar: ThumbBar,
+    /// 状态栏输出：开关、写入器与写入错误
+    bar_output: bool,
+    bar: BarOutput,
+    bar_error: Option<String>,
+    /// 注册 redtomato:// 链接的结果
+    deeplink_message: Option<String>,
+    /// Linux 钉住时的窗口管理器状态（X11 下置顶 / 所有工作区可见）
+    linux_pin: LinuxPin,
+    /// 刚完成、推迟休息时可延长的专注记录（任务, 完成时间），走完休息后清空
+    last_focus_record: Option<(String, String)>,
+    /// 刚结束、待触发视觉提醒的阶段；正在进行的背景脉冲（开始时间与结束的阶段）
+    visual_alarm_pending: Option<Phase>,
+    visual_pulse: Option<(std::time::Instant, Phase)>,
+    /// 用户自定义的例程（内置预设见 `Routine::presets`）
+    routines: Vec<Routine>,
+    /// 统计窗口中今日 / 本周日程的计划与完成、本周完成的任务缓存，None 表示需重新查询
+    schedule_plan: Option<([Vec<crate::db::SchedulePlanRow>; 2], Vec<crate::db::TaskLeadRow>)>,
+    /// 主界面今日概览的缓存
+    today_glance: Option<TodayGlance>,
+    /// 日程任务做满预计番茄数时的庆祝弹窗
+    task_celebration: Option<TaskCelebration>,
+    /// 统计窗口中的 Markdown 导出：范围（日/周/月）、相对本期的偏移、结果提示
+    export_range: crate::review::ReportRange,
+    export_offset: i32,
+    export_message: Option<String>,
+    /// 统计窗口中今日/本周按项目汇总的缓存，None 表示需重新查询
+    project_totals: Option<[Vec<crate::db::ProjectFocusRow>; 2]>,
+    /// 统计窗口中今日/本周按关键词汇总的分心缓存
+    distraction_totals: Option<[Vec<crate::db::DistractionRow>; 2]>,
+    /// 统计窗口记录列表的筛选条件，以及按上次条件查询的结果（条件变化或有新记录时重新查询）
+    history_filter: HistoryFilter,
+    history_rows: Option<HistoryDays>,
+    /// 统计窗口中打开的记录笔记，及其 Markdown 渲染缓存
+    history_note: Option<RecordNote>,
+    markdown_cache: egui_commonmark::CommonMarkCache,
+    /// 统计窗口中今日/本周的 (完成, 放弃) 次数与暂停合计缓存，None 表示需重新查询
+    status_counts: Option<[((u32, u32), crate::db::PauseTotals, u32); 2]>,
+    /// 统计窗口「时段分布」：统计最近几天（0 为全部）；按小时汇总的缓存，None 表示需重新查询
+    hourly_days: u32,
+    hourly_totals: Option<[(i64, u32); 24]>,
+    /// 统计窗口「平均指标」：统计最近几天（0 为全部）；缓存，None 表示需重新查询
+    metrics_days: u32,
+    session_metrics: Option<crate::stats::SessionMetrics>,
+    /// 统计窗口「时间占比」：按项目（否则按任务）；按筛选范围查询的扇区缓存，范围或维度变化时重新查询
+    share_by_project: bool,
+    share_chart: Option<ShareChart>,
+    /// 每日回顾窗口（Some 表示打开）
+    daily_review: Option<DailyReviewView>,
+    /// 大计时器编辑中的输入内容（仅暂停时可编辑），None 表示未在编辑
+    timer_edit: Option<String>,
+    /// 字体管理（中文字体加载与运行时切换）
+    fonts: FontManager,
+    /// 用户选择的字体名（持久化），None 表示自动
+    font_choice: Option<String>,
+    /// 用户指定的字体文件路径（设置中填写，空表示未指定）
+    custom_font_path: String,
+    /// 主题模式（跟随系统 / 深色 / 浅色）
+    theme_mode: ThemeMode,
+    /// 用户自定义的阶段强调色（覆盖主题默认的绿/黄/红）
+    accents: AccentColors,
+    /// 本帧生效的配色（每帧按主题模式、系统深浅色与自定义强调色解析）
+    palette: Palette,
+    /// 长休息时是否显示全屏屏保式休息画面
+    break_screensaver: bool,
+    /// 本次长休息的屏保已被用户输入关闭（离开长休息后复位）
+    screensaver_dismissed: bool,
+    /// 专注计时中是否在屏幕顶部显示「专注中 · 请勿打扰」横幅
+    focus_banner: bool,
+    /// 主界面计时器布局：数字下方进度条，或数字放在环形进度中间（两种模式通用）
+    timer_layout: TimerLayout,
+    /// 数据保留：超过多少个月的记录移入归档库（0 不归档）；当天是否已检查过；最近一次归档结果
+    archive_after_months: u32,
+    archive_checked_on: Option<NaiveDate>,
+    archive_status: String,
+    /// 自动保存间隔（秒）；上次保存时的会话状态，变化即立即保存
+    autosave_secs: u64,
+    saved_marker: Option<SaveMarker>,
+    /// 配置档；生效中的外观与时长在界面字段上，切换或保存时写回当前配置档。设置中新建配置档的输入与错误提示
+    profiles: Profiles,
+    profile_input: String,
+    profile_error: Option<String>,
+    /// 设置同步端点与后台同步器
+    sync_endpoint: SyncEndpoint,
+    settings_sync: SettingsSync,
+    /// 最近一次与云端一致的设置；None 表示尚未拉取成功（此时不自动推送，避免用本机默认值覆盖云端）
+    last_synced: Option<SyncedSettings>,
+    /// 同步状态文案（设置窗口显示）
+    sync_status: String,
+    /// 专注记录同步设置、后台同步器、上次开始同步的时间与状态文案（设置与「关于」中显示）
+    data_sync: DataSyncSettings,
+    data_sync_runner: DataSync,
+    data_sync_started: Option<std::time::Instant>,
+    data_sync_status: String,
+    /// 监视 config.toml 的变化，最近一次加载结果（设置窗口显示）
+    config_file: ConfigWatcher,
+    config_status: String,
+    /// 随手记快捷键（config.toml 可改）
+    capture_shortcut: egui::KeyboardShortcut,
+    /// 通知路由配置与分发器（应用内提示队列）
+    notifications: NotificationRouting,
+    notifier: Notifier,
+    /// 手机推送按钮的回调接收器，以及「+5 分钟」推迟后下一阶段的自动开始时间
+    push_listener: PushActionListener,
+    delayed_start: Option<DateTime<Utc>>,
+    /// 日记笔记集成配置与最近一次写入结果（设置窗口显示）
+    daily_note: DailyNoteConfig,
+    daily_note_status: String,
+    /// 任务输入框下拉中的最近任务缓存，None 表示需重新查询
+    recent_tasks: Option<Vec<crate::db::RecentTaskRow>>,
+    /// 最近的精力自评缓存（最新在前），None 表示需重新查询
+    energy_recent: Option<Vec<u8>>,
+    /// 任务名建议：仓库设置与读取结果缓存
+    git_suggest: GitSuggestSettings,
+    git_suggester: GitSuggester,
+    /// Discord Rich Presence 设置与后台 IPC 句柄
+    discord: DiscordSettings,
+    presence: DiscordPresence,
+    /// MQTT 设置与后台发布句柄
+    mqtt: MqttSettings,
+    mqtt_publisher: MqttPublisher,
+    /// Slack 设置与后台更新句柄
+    slack: SlackSettings,
+    slack_status: SlackStatus,
+    /// 本机 HTTP 状态接口：设置、运行中的服务、启动失败信息
+    #[cfg(feature = "http-api")]
+    http_api: crate::server::ServerSettings,
+    #[cfg(feature = "http-api")]
+    status_server: Option<crate::server::StatusServer>,
+    #[cfg(feature = "http-api")]
+    http_api_error: Option<String>,
+    /// OBS 文本源输出设置、输出器与写入失败信息
+    obs: ObsSettings,
+    obs_output: ObsOutput,
+    obs_error: Option<String>,
+    /// 无障碍：用户覆盖项、启动时读取的系统偏好、本帧生效值（变化时才改 egui 样式）
+    accessibility: AccessibilitySettings,
+    system_a11y: crate::a11y::SystemPrefs,
+    a11y: Option<crate::a11y::Effective>,
+    /// 关闭按钮行为；计时进行中收到关闭请求时是否正在显示确认框；用户已确认退出（不再拦截）
+    close_action: CloseAction,
+    confirm_quit: bool,
+    quit_confirmed: bool,
 }
 
 impl Default for RedTomatoApp {
@@ -178,10 +1029,22 @@ impl Default for RedTomatoApp {
         Self {
             pomo: PomodoroState::default(),
             current_task: String::new(),
-            focus_history: Vec::new(),
+            current_commitment: String::new(),
+            current_project: crate::db::UNCATEGORIZED_PROJECT_ID,
+            projects: Vec::new(),
+            task_colors: TaskColors::default(),
+            projects_view: None,
+            purge_view: None,
+            unlock: None,
+            encryption_form: EncryptionForm::default(),
+            relocate_form: RelocateForm::default(),
+            focus_history: HistoryCache::default(),
             show_statistics: false,
             compact: false,
             pinned: false,
+            window_pos: None,
+            restore_window_pos: None,
+            full_window_size: None,
             pin_applied: false,
             compact_size_applied: false,
             full_restore_applied: true,
@@ -189,6 +1052,145 @@ impl Default for RedTomatoApp {
             full_no_decorations_applied: false,
             system_menu_removed: false,
             show_about: false,
+            show_settings: false,
+            audit: None,
+            planner: None,
+            inbox: None,
+            achievements: None,
+            capture: None,
+            capture_focus: false,
+            inbox_captured: 0,
+            schedule_checked: None,
+            schedule_prompt: None,
+            idle_nudge: IdleNudgeSettings::default(),
+            working_hours: WorkingHours::default(),
+            side_timer: None,
+            side_timer_form: None,
+            focus_until: String::new(),
+            focus_until_error: None,
+            idle_since: Utc::now(),
+            eye_rest: EyeRestSettings::default(),
+            eye_rest_timer: EyeRestTimer::default(),
+            quotes: QuoteSettings::default(),
+            quote_input: String::new(),
+            quote_error: None,
+            breathing: BreathingSettings::default(),
+            breathing_started: None,
+            breathing_skipped: false,
+            wellness: WellnessSettings::default(),
+            wellness_timer: WellnessTimer::default(),
+            wellness_phase: None,
+            rest_guard: RestGuardSettings::default(),
+            pending_break: None,
+            rest_warning: None,
+            distraction: DistractionSettings::default(),
+            distraction_monitor: DistractionMonitor::default(),
+            distraction_input: String::new(),
+            tick_sound: MetronomeSettings::default(),
+            metronome: Metronome::default(),
+            media: MediaSettings::default(),
+            media_control: MediaControl::default(),
+            undo: UndoStack::default(),
+            error_reports: ErrorReports::default(),
+            session_journal: SessionJournal::default(),
+            session_checked: false,
+            recovered_session: None,
+            sleep_action: SleepAction::default(),
+            sleep_prompt: None,
+            tray_timer: false,
+            tray: TrayIcon::default(),
+            thumb_bar: ThumbBar::default(),
+            bar_output: false,
+            bar: BarOutput::default(),
+            bar_error: None,
+            deeplink_message: None,
+            linux_pin: LinuxPin::default(),
+            last_focus_record: None,
+            visual_alarm_pending: None,
+            visual_pulse: None,
+            routines: Vec::new(),
+            schedule_plan: None,
+            today_glance: None,
+            task_celebration: None,
+            review: None,
+            export_range: crate::review::ReportRange::Day,
+            export_offset: 0,
+            export_message: None,
+            status_counts: None,
+            hourly_days: 30,
+            hourly_totals: None,
+            metrics_days: 30,
+            session_metrics: None,
+            share_by_project: false,
+            share_chart: None,
+            project_totals: None,
+            distraction_totals: None,
+            history_filter: HistoryFilter::default(),
+            history_rows: None,
+            history_note: None,
+            markdown_cache: egui_commonmark::CommonMarkCache::default(),
+            daily_review: None,
+            timer_edit: None,
+            fonts: FontManager::new(None),
+            custom_font_path: String::new(),
+            font_choice: None,
+            theme_mode: ThemeMode::System,
+            accents: AccentColors::default(),
+            palette: crate::theme::DARK,
+            break_screensaver: false,
+            screensaver_dismissed: false,
+            focus_banner: false,
+            timer_layout: TimerLayout::Bar,
+            archive_after_months: 0,
+            autosave_secs: DEFAULT_AUTOSAVE_SECS,
+            saved_marker: None,
+            profiles: Profiles::default(),
+            profile_input: String::new(),
+            profile_error: None,
+            archive_checked_on: None,
+            archive_status: String::new(),
+            sync_endpoint: SyncEndpoint::default(),
+            settings_sync: SettingsSync::default(),
+            last_synced: None,
+            sync_status: String::new(),
+            data_sync: DataSyncSettings::default(),
+            data_sync_runner: DataSync::default(),
+            data_sync_started: None,
+            data_sync_status: String::new(),
+            config_file: ConfigWatcher::default(),
+            config_status: String::new(),
+            capture_shortcut: CAPTURE_SHORTCUT,
+            notifications: NotificationRouting::default(),
+            notifier: Notifier::default(),
+            push_listener: PushActionListener::default(),
+            delayed_start: None,
+            daily_note: DailyNoteConfig::default(),
+            daily_note_status: String::new(),
+            recent_tasks: None,
+            energy_recent: None,
+            git_suggest: GitSuggestSettings::default(),
+            git_suggester: GitSuggester::default(),
+            discord: DiscordSettings::default(),
+            presence: DiscordPresence::default(),
+            mqtt: MqttSettings::default(),
+            mqtt_publisher: MqttPublisher::default(),
+            slack: SlackSettings::default(),
+            slack_status: SlackStatus::default(),
+            #[cfg(feature = "http-api")]
+            http_api: crate::server::ServerSettings::default(),
+            #[cfg(feature = "http-api")]
+            status_server: None,
+            #[cfg(feature = "http-api")]
+            http_api_error: None,
+            obs: ObsSettings::default(),
+            obs_output: ObsOutput::default(),
+            obs_error: None,
+            accessibility: AccessibilitySettings::default(),
+            system_a11y: crate::a11y::SystemPrefs::default(),
+            a11y: None,
+            close_action: CloseAction::Quit,
+            confirm_quit: false,
+            quit_confirmed: false,
         }
     }
 }
@@ -229,8 +1231,12 @@ fn try_remove_system_menu(_frame: &eframe::Frame) -> bool {
     false
 }
 
-/// 计算窗口钉在桌面右上角时的位置
-fn pin_position_top_right(ctx: &egui::Context) -> Option<egui::Pos2> {
+/// 计算窗口钉在桌面右上角时的位置；X11 下按主显示器去掉面板后的可用区域计算
+fn pin_position_top_right(ctx: &egui::Context, frame: &eframe::Frame) -> Option<egui::Pos2> {
+    if let Some(area) = linux_pin::work_area(frame, ctx.pixels_per_point()) {
+        let size = ctx.input(|i| i.viewport().outer_rect)?.size();
+        return Some(egui::pos2(area.max.x - size.x - PIN_MARGIN, area.min.y + PIN_MARGIN));
+    }
     ctx.input(|i| {
         let outer_rect = i.viewport().outer_rect?;
         let size = outer_rect.size();
@@ -245,11 +1251,26 @@ fn pin_position_top_right(ctx: &egui::Context) -> Option<egui::Pos2> {
     })
 }
 
+/// 保存的窗口位置是否仍在屏幕上：以当前显示器尺寸判断，左右各放宽一个屏宽以兼容双屏；无显示器信息时为 None
+fn position_on_screen(ctx: &egui::Context, pos: egui::Pos2) -> Option<bool> {
+    let monitor = ctx.input(|i| i.viewport().monitor_size)?;
+    if monitor.x <= 1.0 || monitor.y <= 1.0 {
+        return None;
+    }
+    let x_ok = -monitor.x < pos.x && pos.x < monitor.x * 2.0 - PIN_MARGIN;
+    let y_ok = -PIN_MARGIN < pos.y && pos.y < monitor.y - PIN_MARGIN;
+    Some(x_ok && y_ok)
+}
+
 /// 应用 pin 状态：置顶 + 移到右上角。返回是否成功应用了位置（用于重试）
-fn apply_pin(ctx: &egui::Context) -> bool {
+fn apply_pin(ctx: &egui::Context, frame: &eframe::Frame) -> bool {
     use egui::viewport::{ViewportCommand, WindowLevel};
     ctx.send_viewport_cmd(ViewportCommand::WindowLevel(WindowLevel::AlwaysOnTop));
-    if let Some(pos) = pin_position_top_right(ctx) {
+    // Wayland 下程序不能摆放窗口，也没有显示器尺寸，位置交给合成器，不再重试
+    if linux_pin::session(frame) == linux_pin::Session::Wayland {
+        return true;
+    }
+    if let Some(pos) = pin_position_top_right(ctx, frame) {
         ctx.send_viewport_cmd(ViewportCommand::OuterPosition(pos));
         true
     } else {
@@ -258,19 +1279,55 @@ fn apply_pin(ctx: &egui::Context) -> bool {
 }
 
 /// 取消 pin：恢复普通窗口层级并立即恢复完整窗口尺寸，避免下一帧仍用紧凑尺寸绘制完整界面
-fn apply_unpin(ctx: &egui::Context) {
+fn apply_unpin(ctx: &egui::Context, full_size: egui::Vec2) {
     use egui::viewport::{ViewportCommand, WindowLevel};
     ctx.send_viewport_cmd(ViewportCommand::WindowLevel(WindowLevel::Normal));
-    ctx.send_viewport_cmd(ViewportCommand::InnerSize(egui::vec2(FULL_SIZE.0, FULL_SIZE.1)));
+    ctx.send_viewport_cmd(ViewportCommand::InnerSize(full_size));
+}
+
+/// 完整模式的缩放系数：窗口相对默认尺寸的较小比例，限制在 LAYOUT_SCALE_RANGE 内
+fn layout_scale(size: egui::Vec2) -> f32 {
+    (size.x / FULL_SIZE.0)
+        .min(size.y / FULL_SIZE.1)
+        .clamp(LAYOUT_SCALE_RANGE.0, LAYOUT_SCALE_RANGE.1)
+}
+
+/// 右下角的调整大小手柄：无系统标题栏（也就没有系统边框）时拖动此处调整窗口大小
+fn window_resize_grip(ui: &mut egui::Ui, color: egui::Color32) {
+    let size = 16.0;
+    let rect = egui::Rect::from_min_size(ui.max_rect().right_bottom() - egui::vec2(size, size), egui::vec2(size, size));
+    let response = ui
+        .interact(rect, ui.id().with("resize_grip"), egui::Sense::DRAG)
+        .on_hover_cursor(egui::CursorIcon::ResizeSouthEast);
+    if response.drag_started_by(egui::PointerButton::Primary) {
+        ui.ctx()
+            .send_viewport_cmd(egui::ViewportCommand::BeginResize(egui::viewport::ResizeDirection::SouthEast));
+    }
+    let stroke = egui::Stroke::new(1.0, color);
+    for offset in [4.0, 8.0, 12.0] {
+        ui.painter().line_segment(
+            [egui::pos2(rect.max.x - offset, rect.max.y - 2.0), egui::pos2(rect.max.x - 2.0, rect.max.y - offset)],
+            stroke,
+        );
+    }
+}
+
+/// 自定义顶栏的拖动区域：无系统标题栏时按住此处即可移动窗口
+fn window_drag_region(ui: &mut egui::Ui, width: f32) {
+    let size = egui::vec2(width.at_least(0.0), 32.0);
+    // 只响应拖动、不参与 Tab 焦点切换
+    let (_, response) = ui.allocate_exact_size(size, egui::Sense::DRAG);
+    if response.drag_started_by(egui::PointerButton::Primary) {
+        ui.ctx().send_viewport_cmd(egui::ViewportCommand::StartDrag);
+    }
 }
 
-/// 绘制 subtle 几何背景（类似 WhiteText 的深色质感）
-fn paint_subtle_pattern(ui: &mut egui::Ui, rect: egui::Rect) {
+/// 绘制 subtle 几何背景（类似 WhiteText 的质感）
+fn paint_subtle_pattern(ui: &mut egui::Ui, rect: egui::Rect, palette: &Palette) {
     let painter = ui.painter();
     let step = 16.0;
     let r = 1.2;
-    let alpha = 12u8;
-    let color = egui::Color32::from_rgba_unmultiplied(255, 255, 255, alpha);
+    let color = palette.pattern;
     let mut y = rect.min.y;
     while y < rect.max.y {
         let mut x = rect.min.x + (step * 0.5 * ((y - rect.min.y) / step).floor() % 2.0);
@@ -282,32 +1339,277 @@ fn paint_subtle_pattern(ui: &mut egui::Ui, rect: egui::Rect) {
     }
 }
 
-/// 番茄数：一排小圆形，已完成的填色（番茄红），未完成的描边
-fn paint_pomodoro_circles(ui: &mut egui::Ui, n: u32, done: u32) {
-    const RADIUS: f32 = 8.0;
-    const SPACING: f32 = 6.0;
+/// 时间审计的三条时间轴（计划 / 专注 / 日历），覆盖全天 0–24 点；
+/// 计划内未执行的时段在计划轴上标红，计划外专注在专注轴上标橙
+fn paint_audit_lanes(ui: &mut egui::Ui, audit: &DayAudit, palette: &Palette) {
+    const LABEL_W: f32 = 40.0;
+    const LANE_H: f32 = 18.0;
+    const GAP: f32 = 4.0;
+    const AXIS_H: f32 = 14.0;
+    let width = ui.available_width().at_least(200.0);
+    let (rect, _) = ui.allocate_exact_size(
+        egui::vec2(width, AXIS_H + 3.0 * (LANE_H + GAP)),
+        egui::Sense::hover(),
+    );
+    let painter = ui.painter();
+    let x0 = rect.min.x + LABEL_W;
+    let span_w = rect.max.x - x0;
+    let day_len = (audit.day_end - audit.day_start) as f32;
+    let x_of = |ts: i64| x0 + span_w * ((ts - audit.day_start) as f32 / day_len).clamp(0.0, 1.0);
+    let dim = palette.text_dim;
+    for h in (0..=24).step_by(3) {
+        let x = x0 + span_w * (h as f32 / 24.0);
+        painter.line_segment(
+            [egui::pos2(x, rect.min.y + AXIS_H), egui::pos2(x, rect.max.y)],
+            egui::Stroke::new(1.0, palette.grid),
+        );
+        painter.text(
+            egui::pos2(x, rect.min.y),
+            egui::Align2::CENTER_TOP,
+            h.to_string(),
+            egui::FontId::proportional(10.0),
+            dim,
+        );
+    }
+    let lanes: [(&str, &[crate::audit::Span], egui::Color32, &[crate::audit::Span], egui::Color32); 3] = [
+        ("计划", &audit.planned, palette.plan, &audit.untracked, palette.untracked),
+        ("专注", &audit.tracked, palette.focus, &audit.unplanned, palette.unplanned),
+        ("日历", &audit.calendar, palette.calendar, &[], egui::Color32::TRANSPARENT),
+    ];
+    for (i, (name, spans, color, gaps, gap_color)) in lanes.into_iter().enumerate() {
+        let top = rect.min.y + AXIS_H + i as f32 * (LANE_H + GAP);
+        painter.text(
+            egui::pos2(rect.min.x, top + LANE_H * 0.5),
+            egui::Align2::LEFT_CENTER,
+            name,
+            egui::FontId::proportional(12.0),
+            dim,
+        );
+        let lane = egui::Rect::from_min_max(egui::pos2(x0, top), egui::pos2(rect.max.x, top + LANE_H));
+        painter.rect_filled(lane, 2.0, palette.lane_bg);
+        for (list, c) in [(spans, color), (gaps, gap_color)] {
+            for span in list {
+                let r = egui::Rect::from_min_max(
+                    egui::pos2(x_of(span.start), top),
+                    egui::pos2(x_of(span.end).max(x_of(span.start) + 1.0), top + LANE_H),
+                );
+                painter.rect_filled(r, 2.0, c);
+            }
+        }
+    }
+}
+
+/// 周报每日柱状图：柱高为专注时长，柱上标番茄数，柱下标星期
+fn paint_daily_bars(ui: &mut egui::Ui, days: &[(NaiveDate, i64, u32)], palette: &Palette) {
+    const CHART_H: f32 = 90.0;
+    const LABEL_H: f32 = 14.0;
+    let width = ui.available_width().at_least(200.0);
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(width, CHART_H + LABEL_H * 2.0), egui::Sense::hover());
+    let painter = ui.painter();
+    let max_secs = days.iter().map(|d| d.1).max().unwrap_or(0).max(1);
+    let slot = width / days.len().max(1) as f32;
+    let bar_w = slot * 0.6;
+    let base_y = rect.min.y + LABEL_H + CHART_H;
+    for (i, (day, secs, n)) in days.iter().enumerate() {
+        let cx = rect.min.x + slot * (i as f32 + 0.5);
+        let h = CHART_H * (*secs as f32 / max_secs as f32);
+        let bar = egui::Rect::from_min_max(egui::pos2(cx - bar_w * 0.5, base_y - h), egui::pos2(cx + bar_w * 0.5, base_y));
+        painter.rect_filled(bar, 2.0, palette.focus);
+        if *n > 0 {
+            painter.text(
+                egui::pos2(cx, base_y - h - 2.0),
+                egui::Align2::CENTER_BOTTOM,
+                n.to_string(),
+                egui::FontId::proportional(11.0),
+                palette.text,
+            );
+        }
+        painter.text(
+            egui::pos2(cx, base_y + 2.0),
+            egui::Align2::CENTER_TOP,
+            crate::review::weekday_label(*day),
+            egui::FontId::proportional(11.0),
+            palette.text_dim,
+        );
+    }
+    painter.line_segment(
+        [egui::pos2(rect.min.x, base_y), egui::pos2(rect.max.x, base_y)],
+        egui::Stroke::new(1.0, palette.grid),
+    );
+}
+
+/// 时段分布的统计范围：(最近天数, 标签)，0 为全部
+const HOURLY_RANGES: [(u32, &str); 4] = [(7, "近 7 天"), (30, "近 30 天"), (90, "近 90 天"), (0, "全部")];
+
+/// 时段分布直方图：0–23 点各一根柱，柱高为该小时完成的专注时长，每 3 小时标一次刻度，悬停显示详情
+fn paint_hourly_bars(ui: &mut egui::Ui, totals: &[(i64, u32); 24], palette: &Palette) {
+    const CHART_H: f32 = 80.0;
+    const LABEL_H: f32 = 14.0;
+    let width = ui.available_width().at_least(200.0);
+    let (rect, response) = ui.allocate_exact_size(egui::vec2(width, CHART_H + LABEL_H), egui::Sense::hover());
+    let painter = ui.painter();
+    let max_secs = totals.iter().map(|t| t.0).max().unwrap_or(0).max(1);
+    let slot = width / 24.0;
+    let base_y = rect.min.y + CHART_H;
+    for (hour, (secs, _)) in totals.iter().enumerate() {
+        let x = rect.min.x + slot * hour as f32;
+        let h = CHART_H * (*secs as f32 / max_secs as f32);
+        let bar = egui::Rect::from_min_max(egui::pos2(x + 1.0, base_y - h), egui::pos2(x + slot - 1.0, base_y));
+        painter.rect_filled(bar, 1.0, palette.focus);
+        if hour % 3 == 0 {
+            painter.text(
+                egui::pos2(x, base_y + 2.0),
+                egui::Align2::LEFT_TOP,
+                hour.to_string(),
+                egui::FontId::proportional(10.0),
+                palette.text_dim,
+            );
+        }
+    }
+    painter.line_segment(
+        [egui::pos2(rect.min.x, base_y), egui::pos2(rect.max.x, base_y)],
+        egui::Stroke::new(1.0, palette.grid),
+    );
+    if let Some(pos) = response.hover_pos() {
+        let hour = (((pos.x - rect.min.x) / slot) as usize).min(23);
+        let (secs, n) = totals[hour];
+        response.on_hover_text(format!(
+            "{:02}:00–{:02}:00 专注 {}（🍅{}）",
+            hour,
+            hour + 1,
+            crate::review::format_hm(secs),
+            n
+        ));
+    }
+}
+
+/// 时间占比最多单独列出的扇区数，其余合并为「其他」
+const SHARE_MAX_SLICES: usize = 6;
+
+/// 时间占比的扇区颜色：取自当前主题的强调色，随主题切换
+fn share_colors(palette: &Palette) -> [egui::Color32; SHARE_MAX_SLICES + 1] {
+    [
+        palette.focus,
+        palette.short_break,
+        palette.long_break,
+        palette.overtime,
+        palette.plan,
+        palette.calendar,
+        palette.grid,
+    ]
+}
+
+/// 环形图：从 12 点方向顺时针按时长画扇区，悬停的扇区外扩并显示详情，中间写总时长；返回被点击的扇区
+fn paint_donut(
+    ui: &mut egui::Ui,
+    slices: &[ShareSlice],
+    total: i64,
+    colors: &[egui::Color32],
+    palette: &Palette,
+) -> Option<usize> {
+    const SIZE: f32 = 140.0;
+    const OUTER: f32 = 60.0;
+    const INNER: f32 = 36.0;
+    use std::f32::consts::{FRAC_PI_2, TAU};
+    let (rect, response) = ui.allocate_exact_size(egui::vec2(SIZE, SIZE), egui::Sense::click());
+    let center = rect.center();
+    let total = total.max(1) as f32;
+    // 悬停位置对应的扇区：半径在环内，角度从 12 点方向顺时针量
+    let hovered = response.hover_pos().and_then(|pos| {
+        let d = pos - center;
+        if !(INNER..=OUTER + 4.0).contains(&d.length()) {
+            return None;
+        }
+        let angle = (d.y.atan2(d.x) + FRAC_PI_2).rem_euclid(TAU);
+        let mut start = 0.0;
+        slices.iter().position(|s| {
+            start += TAU * s.secs as f32 / total;
+            angle < start
+        })
+    });
+    let mut mesh = egui::Mesh::default();
+    let mut start = -FRAC_PI_2;
+    for (i, slice) in slices.iter().enumerate() {
+        let sweep = TAU * slice.secs as f32 / total;
+        let outer = if hovered == Some(i) { OUTER + 4.0 } else { OUTER };
+        let color = colors[i % colors.len()];
+        let steps = ((sweep / TAU * 96.0).ceil() as usize).max(2);
+        let base = mesh.vertices.len() as u32;
+        for k in 0..=steps {
+            let a = start + sweep * k as f32 / steps as f32;
+            let dir = egui::vec2(a.cos(), a.sin());
+            mesh.colored_vertex(center + dir * INNER, color);
+            mesh.colored_vertex(center + dir * outer, color);
+        }
+        for k in 0..steps as u32 {
+            let v = base + k * 2;
+            mesh.add_triangle(v, v + 1, v + 2);
+            mesh.add_triangle(v + 1, v + 3, v + 2);
+        }
+        start += sweep;
+    }
+    let painter = ui.painter();
+    painter.add(egui::Shape::mesh(mesh));
+    painter.text(
+        center,
+        egui::Align2::CENTER_CENTER,
+        crate::review::format_hm(total as i64),
+        egui::FontId::proportional(11.0),
+        palette.text,
+    );
+    let hovered_slice = hovered.and_then(|i| slices.get(i));
+    let clicked = response.clicked();
+    if let Some(slice) = hovered_slice {
+        response.on_hover_text(format!(
+            "{}：{}（🍅{}）",
+            slice.label,
+            crate::review::format_hm(slice.secs),
+            slice.sessions
+        ));
+    }
+    if clicked { hovered.filter(|&i| slices[i].filter.is_some()) } else { None }
+}
+
+/// 番茄数圆圈半径：完整模式 / 紧凑模式（迷你）
+const CIRCLE_RADIUS_FULL: f32 = 8.0;
+const CIRCLE_RADIUS_COMPACT: f32 = 5.0;
+/// 圆环布局的圆环外径：完整模式 / 紧凑模式
+const RING_DIAMETER_FULL: f32 = 240.0;
+const RING_DIAMETER_COMPACT: f32 = 120.0;
+/// 圆环布局比进度条布局多占的窗口高度：完整模式 / 紧凑模式
+const RING_EXTRA_HEIGHT: (f32, f32) = (160.0, 50.0);
+
+/// 番茄数：一排小圆形，已完成的填色（番茄红），未完成的描边；间距随半径缩放
+fn paint_pomodoro_circles(ui: &mut egui::Ui, n: u32, done: u32, radius: f32, palette: &Palette) {
+    let spacing = radius * 0.75;
     let size = egui::vec2(
-        n as f32 * (RADIUS * 2.0 + SPACING) - SPACING,
-        RADIUS * 2.0,
+        n as f32 * (radius * 2.0 + spacing) - spacing,
+        radius * 2.0,
     );
     let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
     let painter = ui.painter();
-    let filled_color = egui::Color32::from_rgb(217, 17, 83); // 番茄红
-    let stroke_color = egui::Color32::from_rgb(80, 80, 90);
-    let stroke = egui::Stroke::new(1.5, stroke_color);
+    let filled_color = palette.circle_fill();
+    let stroke_color = palette.circle_stroke;
+    let stroke = egui::Stroke::new((radius * 0.19).at_least(1.0), stroke_color);
     for i in 0..n {
-        let cx = rect.min.x + RADIUS + i as f32 * (RADIUS * 2.0 + SPACING);
+        let cx = rect.min.x + radius + i as f32 * (radius * 2.0 + spacing);
         let cy = rect.center().y;
         let center = egui::Pos2::new(cx, cy);
         if i < done {
-            painter.circle_filled(center, RADIUS, filled_color);
-            painter.circle_stroke(center, RADIUS, stroke);
+            painter.circle_filled(center, radius, filled_color);
+            painter.circle_stroke(center, radius, stroke);
         } else {
-            painter.circle_stroke(center, RADIUS, stroke);
+            painter.circle_stroke(center, radius, stroke);
         }
     }
 }
 
+/// 只有图标的按钮：给屏幕阅读器一个文字名称（否则只读出图标字符）
+fn icon_button_name(response: egui::Response, name: &str) -> egui::Response {
+    response.widget_info(|| egui::WidgetInfo::labeled(egui::WidgetType::Button, response.enabled(), name));
+    response
+}
+
 /// 带文字居中显示的按钮，返回 Response（与 egui::Button 一致便于 .clicked()）
 fn centered_button(ui: &mut egui::Ui, text: impl Into<egui::WidgetText>, size: egui::Vec2) -> egui::Response {
     let size = size.at_least(egui::vec2(ui.spacing().interact_size.x, ui.spacing().interact_size.y));
@@ -323,6 +1625,8 @@ fn centered_button(ui: &mut egui::Ui, text: impl Into<egui::WidgetText>, size: e
     );
     let widget_text: egui::WidgetText = text.into();
     let galley = widget_text.into_galley(ui, None, rect.width() - 8.0, egui::TextStyle::Button);
+    // 与 egui::Button 一样向屏幕阅读器报告为按钮
+    response.widget_info(|| egui::WidgetInfo::labeled(egui::WidgetType::Button, ui.is_enabled(), galley.text()));
     let pos = rect.center() - galley.size() / 2.0;
     let text_color = if ui.is_enabled() {
         ui.visuals().text_color()
@@ -333,31 +1637,56 @@ fn centered_button(ui: &mut egui::Ui, text: impl Into<egui::WidgetText>, size: e
     response
 }
 
-/// 番茄/休息阶段结束时播放系统提示音
-fn play_phase_finished_sound() {
-    #[cfg(windows)]
-    {
-        use std::os::windows::process::CommandExt;
-        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
-        let _ = std::process::Command::new("powershell")
-            .args(["-NoProfile", "-NonInteractive", "-Command", "[Console]::Beep(800, 300)"])
-            .creation_flags(CREATE_NO_WINDOW)
-            .spawn();
-    }
-    #[cfg(not(windows))]
-    {
-        let _ = std::process::Command::new("echo").arg("\x07").status();
-    }
+/// 「+5」「−5」按钮每次调整的秒数
+const ADJUST_STEP_SECS: i64 = 5 * 60;
+/// 任务下拉中最多列出的历史任务数
+const RECENT_TASKS_LIMIT: u32 = 30;
+/// 统计窗口记录列表中每天每页加载的条数
+const HISTORY_PAGE_SIZE: u32 = 100;
+/// 专注横幅高度，及拿不到显示器尺寸时的宽度
+const FOCUS_BANNER_HEIGHT: f32 = 26.0;
+const FOCUS_BANNER_FALLBACK_WIDTH: f32 = 1920.0;
+/// 随手记输入条的快捷键（窗口内）
+const CAPTURE_SHORTCUT: egui::KeyboardShortcut = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::I);
+
+/// 分心提醒小条的尺寸（逻辑像素）
+const DISTRACTION_NUDGE_SIZE: (f32, f32) = (420.0, 36.0);
+
+/// 解析用户输入的时长：「分:秒」或纯「分」，返回秒数
+fn parse_duration_input(s: &str) -> Option<i64> {
+    let s = s.trim();
+    let secs = match s.split_once(':') {
+        Some((m, sec)) => {
+            let m: i64 = m.trim().parse().ok()?;
+            let sec: i64 = sec.trim().parse().ok()?;
+            if !(0..60).contains(&sec) {
+                return None;
+            }
+            m * 60 + sec
+        }
+        None => s.parse::<i64>().ok()? * 60,
+    };
+    (secs > 0).then_some(secs)
 }
 
 impl RedTomatoApp {
     pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
-        setup_chinese_fonts(&cc.egui_ctx);
         let mut app = Self::default();
+        // 部署策略给出默认时长，用户保存过的时长覆盖其上
+        app.pomo.config = crate::policy::get().default_config();
         if let Some(storage) = cc.storage {
             if let Some(json) = storage.get_string(STORAGE_KEY_STATE) {
                 if let Ok(p) = serde_json::from_str::<PersistedState>(&json) {
                     app.current_task = p.current_task;
+                    app.current_commitment = p.current_commitment;
+                    app.side_timer = p.side_timer;
+                    if let Some(project) = p.current_project {
+                        app.current_project = project;
+                    }
+                    app.compact = p.compact;
+                    app.pinned = p.pinned;
+                    app.restore_window_pos = p.window_pos.map(|[x, y]| egui::pos2(x, y));
+                    app.full_window_size = p.full_window_size.map(|[w, h]| egui::vec2(w, h));
                     app.pomo.phase = phase_from_str(&p.phase);
                     let loaded_state = state_from_str(&p.state);
                     app.pomo.state = if loaded_state == TimerState::Running {
@@ -368,436 +1697,6073 @@ impl RedTomatoApp {
                     app.pomo.remaining_secs = p.remaining_secs;
                     app.pomo.phase_total_secs = p.phase_total_secs;
                     app.pomo.completed_pomodoros = p.completed_pomodoros;
+                    app.pomo.snoozes = p.snoozes;
+                    app.pomo.snoozed_break = p.snoozed_break.as_deref().map(phase_from_str);
+                    if let Some(secs) = p.overtime_secs {
+                        app.pomo.in_overtime = true;
+                        app.pomo.overtime_secs = secs;
+                    }
+                    app.pomo.pauses = p.pauses;
+                    // 退出时运行中的计时恢复为暂停，计一次暂停；程序关闭期间不计入暂停时长
+                    if loaded_state == TimerState::Running {
+                        app.pomo.pauses.count += 1;
+                    }
+                    if app.pomo.state == TimerState::Paused {
+                        app.pomo.paused_at = Some(Utc::now());
+                    }
+                    app.font_choice = p.font;
+                    app.custom_font_path = p.custom_font_path;
+                    if let Some(theme) = p.theme.as_deref() {
+                        app.theme_mode = crate::theme::mode_from_str(theme);
+                    }
+                    app.accents = p.accents;
+                    if let Some(config) = p.config {
+                        app.pomo.config = config;
+                    }
+                    if let Some(routine) = p.routine.filter(|r| p.routine_step < r.steps.len()) {
+                        app.pomo.routine = Some(routine);
+                        app.pomo.step = p.routine_step;
+                    }
+                    app.routines = p.routines;
+                    app.break_screensaver = p.break_screensaver;
+                    app.focus_banner = p.focus_banner;
+                    app.timer_layout = p.timer_layout;
+                    app.archive_after_months = p.archive_after_months;
+                    if let Some(secs) = p.autosave_secs {
+                        app.autosave_secs = secs.clamp(AUTOSAVE_SECS_RANGE.0, AUTOSAVE_SECS_RANGE.1);
+                    }
+                    app.sync_endpoint = p.sync;
+                    app.data_sync = p.data_sync;
+                    app.idle_nudge = p.idle_nudge;
+                    app.working_hours = p.working_hours;
+                    app.eye_rest = p.eye_rest;
+                    app.quotes = p.quotes;
+                    app.breathing = p.breathing;
+                    app.wellness = p.wellness;
+                    app.rest_guard = p.rest_guard;
+                    app.distraction = p.distraction;
+                    app.profiles = p.profiles;
+                    app.tick_sound = p.tick_sound;
+                    app.media = p.media;
+                    app.notifications = p.notifications;
+                    app.daily_note = p.daily_note;
+                    app.git_suggest = p.git_suggest;
+                    app.sleep_action = p.sleep_action;
+                    app.tray_timer = p.tray_timer;
+                    app.bar_output = p.bar_output;
+                    app.discord = p.discord;
+                    app.mqtt = p.mqtt;
+                    app.slack = p.slack;
+                    app.obs = p.obs;
+                    app.accessibility = p.accessibility;
+                    if let Some(action) = p.close_action.as_deref() {
+                        app.close_action = close_action_from_str(action);
+                    }
+                    #[cfg(feature = "http-api")]
+                    {
+                        app.http_api = p.http_api;
+                    }
                 }
             }
         }
-        app.load_focus_history_from_db();
+        let settings = app.synced_settings();
+        app.profiles.ensure_default(settings, 0);
+        // 设置中文字体，避免中文乱码。优先使用用户选择的字体，其次系统自带字体
+        app.fonts.set_custom_path(Some(&app.custom_font_path));
+        app.fonts.apply(&cc.egui_ctx, app.font_choice.as_deref());
+        app.system_a11y = crate::a11y::detect();
+        crate::suspend::start();
+        crate::deeplink::listen(&cc.egui_ctx);
+        app.enforce_policy();
+        // 配置了同步端点时，启动即拉取云端设置
+        if app.sync_enabled() {
+            app.settings_sync.pull(&app.sync_endpoint);
+            app.sync_status = "正在拉取云端设置…".to_string();
+        }
+        // 数据库已加密时先显示解锁界面，输入口令后再加载数据
+        crate::db::open_and_init().db_err("打开数据库");
+        if crate::crypto::is_locked() {
+            app.unlock = Some(UnlockView::default());
+        } else {
+            app.load_data();
+        }
         app
     }
 
-    /// 从 SQLite 加载专注历史（启动时与统计窗口刷新时用）
-    fn load_focus_history_from_db(&mut self) {
-        if let Ok(conn) = crate::db::open_and_init() {
-            if let Ok(rows) = crate::db::load_focus_records(&conn, 0) {
-                self.focus_history = rows
-                    .into_iter()
-                    .map(|r| FocusRecord {
-                        task: r.task,
-                        duration_secs: r.duration_secs,
-                        completed_at: r.completed_at,
-                        completed_pomodoros: r.completed_pomodoros,
-                    })
-                    .collect();
-            }
+    /// 加载数据库中的数据：上次未能写入 SQLite 的记录（插入失败或写入途中退出）先补上，再加载历史与项目
+    fn load_data(&mut self) {
+        if let Some(mut conn) = crate::db::open_and_init().db_err("打开数据库") {
+            crate::wal::replay(&mut conn).db_err("回放预写日志");
         }
+        self.load_focus_history_from_db();
+        self.load_projects();
     }
 
-    fn phase_label(phase: Phase) -> &'static str {
-        match phase {
-            Phase::Focus => "专注",
-            Phase::ShortBreak => "短休息",
-            Phase::LongBreak => "长休息",
+    /// 解锁界面：输入口令解锁加密的数据库，成功后加载数据
+    fn ui_unlock(&mut self, ctx: &egui::Context) {
+        let Some(view) = &mut self.unlock else { return };
+        let mut unlocked = false;
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.add_space(120.0);
+                ui.label(egui::RichText::new("🔒 数据已加密").size(20.0));
+                ui.add_space(12.0);
+                let resp = ui.add(
+                    egui::TextEdit::singleline(&mut view.passphrase)
+                        .password(true)
+                        .desired_width(220.0)
+                        .hint_text("输入口令"),
+                );
+                let enter = resp.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                ui.add_space(8.0);
+                if ui.button("解锁").clicked() || enter {
+                    match crate::db::unlock_encryption(&view.passphrase) {
+                        Ok(true) => unlocked = true,
+                        Ok(false) => view.error = Some("口令不正确".to_string()),
+                        Err(e) => view.error = Some(format!("无法打开数据库：{}", e)),
+                    }
+                    view.passphrase.clear();
+                }
+                if let Some(error) = &view.error {
+                    ui.add_space(6.0);
+                    ui.label(egui::RichText::new(error).color(self.palette.text_dim));
+                }
+            });
+        });
+        if unlocked {
+            self.unlock = None;
+            self.load_data();
         }
     }
-}
 
-impl eframe::App for RedTomatoApp {
-    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
-        self.pomo.tick(Utc::now());
-        if self.pomo.take_finished_phase() == Some(Phase::Focus) {
-            play_phase_finished_sound();
-            if let Some(duration_secs) = self.pomo.take_last_completed_focus_duration() {
-                let completed_at = beijing_now_rfc3339();
-                let completed_pomodoros = self.pomo.completed_pomodoros;
-                let task = self.current_task.clone();
-                if let Ok(conn) = crate::db::open_and_init() {
-                    let _ = crate::db::insert_focus_record(
-                        &conn,
-                        &task,
-                        duration_secs,
-                        &completed_at,
-                        completed_pomodoros,
-                    );
-                }
-                self.focus_history.insert(
-                    0,
-                    FocusRecord {
-                        task,
-                        duration_secs,
-                        completed_at,
-                        completed_pomodoros,
-                    },
-                );
-            }
+    /// 从 SQLite 加载项目列表与任务颜色；当前项目已被删除时回到「未分类」
+    fn load_projects(&mut self) {
+        if let Ok(projects) = crate::db::open_and_init().and_then(|conn| crate::db::load_projects(&conn)) {
+            self.projects = projects;
         }
-        ctx.request_repaint();
-
-        // 应用 pin：默认钉在右上角并置顶（首帧可能无 monitor 信息，会下一帧重试）
-        if self.pinned && !self.pin_applied {
-            self.pin_applied = apply_pin(ctx);
+        if let Some(colors) = crate::db::open_and_init().and_then(|conn| TaskColors::load(&conn)).db_err("读取任务颜色") {
+            self.task_colors = colors;
         }
-
-        // 启动时若为完整模式：强制设一次窗口尺寸，避免 eframe 持久化恢复成小窗口导致界面被裁切
-        if !self.compact && !self.initial_full_size_applied {
-            ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(egui::vec2(
-                FULL_SIZE.0,
-                FULL_SIZE.1,
-            )));
-            self.initial_full_size_applied = true;
+        if !self.projects.iter().any(|p| p.id == self.current_project) {
+            self.current_project = crate::db::UNCATEGORIZED_PROJECT_ID;
         }
+    }
 
-        // 紧凑模式（钉到右上角）：小窗 + 无标题栏
-        if self.compact && !self.compact_size_applied {
-            ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(egui::vec2(
-                COMPACT_WIDTH,
-                COMPACT_HEIGHT,
-            )));
-            ctx.send_viewport_cmd(egui::ViewportCommand::Decorations(false));
-            self.compact_size_applied = true;
-            self.full_no_decorations_applied = false;
-            self.system_menu_removed = false;
-        }
+    /// 项目名（找不到时按「未分类」显示）
+    fn project_name(&self, id: i64) -> &str {
+        self.projects
+            .iter()
+            .find(|p| p.id == id)
+            .map_or("未分类", |p| p.name.as_str())
+    }
 
-        // 非钉住模式：也去掉系统标题栏，只保留自定义顶栏（钉子+关闭）
-        if !self.compact && !self.full_no_decorations_applied {
-            ctx.send_viewport_cmd(egui::ViewportCommand::Decorations(false));
-            self.full_no_decorations_applied = true;
-        }
+    /// 统计缓存失效（有新记录写入时）
+    fn invalidate_stats(&mut self) {
+        self.status_counts = None;
+        self.hourly_totals = None;
+        self.session_metrics = None;
+        self.share_chart = None;
+        self.project_totals = None;
+        self.distraction_totals = None;
+        self.history_rows = None;
+        self.schedule_plan = None;
+        self.today_glance = None;
+    }
 
-        // 从紧凑回到完整模式：恢复窗口尺寸（不恢复系统标题栏）
-        if !self.compact && !self.full_restore_applied {
-            ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(egui::vec2(
-                FULL_SIZE.0,
-                FULL_SIZE.1,
-            )));
-            self.full_restore_applied = true;
-            self.full_no_decorations_applied = false; // 下一帧会再次发 Decorations(false)
-            self.system_menu_removed = false;
+    /// 按统计窗口的筛选输入生成查询；自定义日期无效时为 None
+    fn history_query(&self) -> Option<crate::db::HistoryQuery> {
+        use crate::review::ReportRange;
+        let f = &self.history_filter;
+        let today = beijing_now().date_naive();
+        let days = match f.range {
+            HistoryRange::All => None,
+            HistoryRange::Today => Some(ReportRange::Day.period(today, 0)),
+            HistoryRange::Week => Some(ReportRange::Week.period(today, 0)),
+            HistoryRange::Month => Some(ReportRange::Month.period(today, 0)),
+            HistoryRange::Custom => {
+                let parse = |s: &str| NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d").ok();
+                let (first, last) = (parse(&f.custom_from)?, parse(&f.custom_to)?);
+                if first > last {
+                    return None;
+                }
+                Some((first, last))
+            }
+        };
+        Some(crate::db::HistoryQuery {
+            search: f.search.trim().to_string(),
+            range: days.map(|(first, last)| {
+                (beijing_day_start_rfc3339(first), beijing_day_start_rfc3339(last + chrono::Duration::days(1)))
+            }),
+            min_duration_secs: f.min_minutes as i64 * 60,
+            project_id: f.project_id,
+            include_archive: f.include_archive,
+        })
+    }
+
+    /// 从 SQLite 加载专注历史（启动时与统计窗口刷新时用），仅加载缓存窗口内、不超过上限的记录
+    fn load_focus_history_from_db(&mut self) {
+        let now = beijing_now();
+        let since = self.focus_history.window_start(now);
+        let limit = self.focus_history.max_records() as u32;
+        if let Some(conn) = crate::db::open_and_init().db_err("打开数据库") {
+            if let Some(rows) = crate::db::load_focus_records_since(&conn, &since, limit).db_err("加载专注记录") {
+                let records = rows.into_iter().map(FocusRecord::from).collect();
+                self.focus_history.replace(records, now);
+            }
         }
+    }
 
-        // 非紧凑模式下去掉标题栏左上角系统菜单（仅 Windows，只执行一次）
-        if !self.compact && !self.system_menu_removed && try_remove_system_menu(frame) {
-            self.system_menu_removed = true;
+    /// 暂停 / 继续；专注中暂停记一次中断
+    fn toggle_pause(&mut self) {
+        if self.pomo.phase == Phase::Focus && self.pomo.state == TimerState::Running {
+            Self::record_interruption(crate::db::INTERRUPTION_PAUSE);
+        }
+        self.pomo.toggle_pause();
+    }
+
+    /// 专注进行中（未完成）被重置时记一次「放弃」中断，并把已专注的秒数记为一条放弃的专注记录；
+    /// 加时中被重置或退出时专注已满，按完成记录
+    fn record_abandon_if_focusing(&mut self) -> Option<AbandonRows> {
+        // 加时中专注时间已满：按完成记录（含加时），不算放弃
+        if self.pomo.in_overtime {
+            self.pomo.finish_overtime();
+            self.handle_finished_phase();
+            return None;
+        }
+        if self.pomo.phase == Phase::Focus && self.pomo.state != TimerState::Idle {
+            let now = beijing_now_rfc3339();
+            let mut rows = AbandonRows { interruption: None, record: None };
+            if let Some(conn) = crate::db::open_and_init().db_err("打开数据库") {
+                rows.interruption =
+                    crate::db::insert_interruption(&conn, &now, crate::db::INTERRUPTION_ABANDON).db_err("记录放弃");
+            }
+            let elapsed_secs = (self.pomo.phase_total_secs - self.pomo.remaining_secs).max(0);
+            if elapsed_secs > 0 {
+                let record = FocusRecord {
+                    task: self.current_task.clone(),
+                    duration_secs: elapsed_secs,
+                    completed_at: now,
+                    completed_pomodoros: self.pomo.completed_pomodoros,
+                    commitment: self.current_commitment.clone(),
+                    pauses: self.pomo.pause_stats(Utc::now()),
+                    project_id: self.current_project,
+                    overtime_secs: 0,
+                };
+                if let Some(conn) = crate::db::open_and_init().db_err("打开数据库") {
+                    rows.record = crate::db::insert_abandoned_record(&conn, &record).db_err("写入放弃的专注");
+                }
+                self.invalidate_stats();
+            }
+            let message = if self.current_task.is_empty() {
+                "专注未完成即被重置".to_string()
+            } else {
+                format!("专注未完成即被重置：{}", self.current_task)
+            };
+            self.notify(NotifyEvent::FocusAbandoned, &message);
+            return Some(rows);
         }
+        None
+    }
 
-        if self.compact {
-            self.ui_compact(ctx);
+    /// 「重置」/「完成」：清空当前任务并重置番茄数，先保存快照以便撤销。加时先按完成记录（撤销后保留该记录）。
+    /// 重置：专注中按放弃记录；完成：记一次任务完成（`db::insert_task_completion`，并把今天同名日程标记完成），
+    /// 专注中已满 `PARTIAL_FOCUS_MIN_SECS` 的部分按完成记录计入时长（不加番茄数）。撤销时删除这些记录
+    fn reset_with_undo(&mut self, complete: bool) {
+        if self.pomo.in_overtime {
+            self.pomo.finish_overtime();
+            self.handle_finished_phase();
+        }
+        let mut snapshot = ResetSnapshot {
+            pomo: self.pomo.clone(),
+            task: self.current_task.clone(),
+            commitment: self.current_commitment.clone(),
+            last_focus_record: self.last_focus_record.clone(),
+            abandoned: None,
+            partial: None,
+            task_completion: None,
+            schedule_id: None,
+        };
+        let label = if complete {
+            let now = beijing_now_rfc3339();
+            snapshot.partial = self.record_partial_focus(&now);
+            let task = self.current_task.trim().to_string();
+            if !task.is_empty() {
+                snapshot.schedule_id = Self::complete_today_schedule(&task, &now);
+                if let Some(conn) = crate::db::open_and_init().db_err("打开数据库") {
+                    let pomodoros = self.pomo.completed_pomodoros;
+                    snapshot.task_completion = crate::db::insert_task_completion(&conn, &task, self.current_project, pomodoros, &now)
+                        .db_err("记录任务完成");
+                }
+            }
+            self.invalidate_stats();
+            if task.is_empty() {
+                "已完成".to_string()
+            } else {
+                format!("已完成「{}」", task)
+            }
         } else {
-            self.ui_full(ctx);
+            snapshot.abandoned = self.record_abandon_if_focusing();
+            "已重置".to_string()
+        };
+        self.undo.push(&label, snapshot);
+        self.current_task.clear();
+        self.current_commitment.clear();
+        self.last_focus_record = None;
+        self.pomo.reset_pomodoros_and_stop();
+    }
+
+    /// 「完成」时正在专注：已专注满 `PARTIAL_FOCUS_MIN_SECS` 则按完成记录写入（不足一个番茄，不加番茄数），
+    /// 返回写入的 (完成时间, 时长)；更短的直接丢弃，不算放弃
+    fn record_partial_focus(&mut self, now: &str) -> Option<(String, i64)> {
+        if self.pomo.phase != Phase::Focus || self.pomo.state == TimerState::Idle {
+            return None;
         }
+        let elapsed_secs = (self.pomo.phase_total_secs - self.pomo.remaining_secs).max(0);
+        if elapsed_secs < PARTIAL_FOCUS_MIN_SECS {
+            return None;
+        }
+        let record = FocusRecord {
+            task: self.current_task.clone(),
+            duration_secs: elapsed_secs,
+            completed_at: now.to_string(),
+            completed_pomodoros: self.pomo.completed_pomodoros,
+            commitment: self.current_commitment.clone(),
+            pauses: self.pomo.pause_stats(Utc::now()),
+            project_id: self.current_project,
+            overtime_secs: 0,
+        };
+        let conn = crate::db::open_and_init().db_err("打开数据库")?;
+        crate::db::insert_focus_record(&conn, &record).db_err("写入部分专注记录")?;
+        self.load_focus_history_from_db();
+        Some((record.completed_at, elapsed_secs))
+    }
 
-        // 关于窗口（点击导航栏「关于」后展示）
-        if self.show_about {
-            self.ui_about(ctx);
+    /// 把今天同名、尚未完成的日程标记为完成，返回其 id（撤销时恢复）
+    fn complete_today_schedule(task: &str, now: &str) -> Option<i64> {
+        let (today, _) = crate::stats::today_and_week_ranges(beijing_now());
+        let conn = crate::db::open_and_init().db_err("打开数据库")?;
+        let rows = crate::db::load_schedule(&conn, &beijing_rfc3339(today.0), &beijing_rfc3339(today.1)).db_err("读取今日日程")?;
+        let row = rows.iter().find(|r| r.task == task && r.task_completed_at.is_empty())?;
+        crate::db::complete_scheduled_task(&conn, row.id, now).db_err("标记日程完成")?;
+        Some(row.id)
+    }
+
+    /// 撤销最近一次重置 / 完成：恢复计时状态（设置仍用当前的）与任务，删除因此写入的放弃记录、
+    /// 部分专注记录与任务完成，恢复日程为未完成
+    fn undo_reset(&mut self) {
+        let Some(snapshot) = self.undo.pop() else { return };
+        if let Some(conn) = crate::db::open_and_init().db_err("打开数据库") {
+            if let Some(rows) = &snapshot.abandoned {
+                crate::db::delete_abandon(&conn, rows.interruption, rows.record).db_err("撤销放弃记录");
+            }
+            if let Some((at, secs)) = &snapshot.partial {
+                crate::db::delete_partial_focus(&conn, at, *secs).db_err("撤销部分专注记录");
+            }
+            if let Some(id) = snapshot.task_completion {
+                crate::db::delete_task_completion(&conn, id).db_err("撤销任务完成");
+            }
+            if let Some(id) = snapshot.schedule_id {
+                crate::db::complete_scheduled_task(&conn, id, "").db_err("恢复日程为未完成");
+            }
         }
-        // 统计窗口：按时间列出做了哪些任务、专注时长
-        if self.show_statistics {
-            self.ui_statistics(ctx);
+        if snapshot.partial.is_some() {
+            self.load_focus_history_from_db();
+        }
+        self.invalidate_stats();
+        let config = self.pomo.config.clone();
+        self.pomo = snapshot.pomo;
+        self.pomo.config = config;
+        self.current_task = snapshot.task;
+        self.current_commitment = snapshot.commitment;
+        self.last_focus_record = snapshot.last_focus_record;
+    }
+
+    /// 阶段结束后的通知与记录：专注完成时写日记、预写日志与 SQLite，并放入内存历史
+    fn handle_finished_phase(&mut self) {
+        if let Some(secs) = self.pomo.take_snooze_finished() {
+            // 推迟休息后回到原来的休息：休息从现在才开始算
+            if let Some(pending) = self.pending_break.as_mut() {
+                pending.since = Utc::now();
+            }
+            self.extend_last_focus(secs);
+            self.visual_alarm_pending = Some(Phase::Focus);
+            self.notify(NotifyEvent::FocusFinished, &format!("又专注了 {} 分钟，休息一下", secs / 60));
+        }
+        let finished = self.pomo.take_finished_phase();
+        if let Some(phase) = finished {
+            tracing::info!("{}结束，下一阶段：{}", Self::phase_label(phase), Self::phase_label(self.pomo.phase));
+            self.visual_alarm_pending = finished;
+        }
+        match finished {
+            // 专注结束进入休息：记下待进行的休息，下次开始专注时判定是否跳过
+            Some(Phase::Focus) if self.pomo.phase != Phase::Focus => {
+                self.pending_break = Some(PendingBreak {
+                    phase: self.pomo.phase,
+                    secs: self.pomo.phase_secs(),
+                    since: Utc::now(),
+                });
+            }
+            Some(phase @ (Phase::ShortBreak | Phase::LongBreak)) => {
+                self.pending_break = None;
+                self.record_break(phase, false);
+            }
+            _ => {}
+        }
+        if let Some(phase @ (Phase::ShortBreak | Phase::LongBreak)) = finished {
+            self.last_focus_record = None;
+            let message = format!("{}结束，开始专注吧", Self::phase_label(phase));
+            self.notify(NotifyEvent::BreakFinished, &message);
+        }
+        if finished == Some(Phase::Focus) {
+            let mut message = if self.current_task.is_empty() {
+                "完成一个番茄，休息一下".to_string()
+            } else {
+                format!("完成一个番茄：{}", self.current_task)
+            };
+            if self.pomo.overtime_secs >= 60 {
+                message.push_str(&format!("（加时 {} 分钟）", self.pomo.overtime_secs / 60));
+            }
+            if self.inbox_captured > 0 {
+                message.push_str(&format!("；收集箱新增 {} 条，休息时处理", std::mem::take(&mut self.inbox_captured)));
+            }
+            self.notify(NotifyEvent::FocusFinished, &message);
+            if let Some(duration_secs) = self.pomo.take_last_completed_focus_duration() {
+                let now = beijing_now();
+                let pauses = self.pomo.pause_stats(Utc::now());
+                if self.daily_note.is_active() {
+                    // 日记中的起止为墙钟时间，含暂停
+                    let start = now - chrono::Duration::seconds(duration_secs + pauses.secs);
+                    self.daily_note_status =
+                        match crate::daily_note::append_pomodoro(&self.daily_note, start, now, &self.current_task) {
+                            Ok(path) => format!("已写入：{}", path.display()),
+                            Err(e) => {
+                                crate::errors::report(ErrorKind::Integration, format!("写入日记失败：{}", e));
+                                format!("写入日记失败：{}", e)
+                            }
+                        };
+                }
+                let completed_at = now.to_rfc3339();
+                let completed_pomodoros = self.pomo.completed_pomodoros;
+                let task = self.current_task.clone();
+                // 专注契约只对本次专注有效：随记录保存后清空
+                let commitment = std::mem::take(&mut self.current_commitment);
+                let record = FocusRecord {
+                    task,
+                    duration_secs,
+                    completed_at,
+                    completed_pomodoros,
+                    commitment,
+                    pauses,
+                    project_id: self.current_project,
+                    overtime_secs: self.pomo.overtime_secs,
+                };
+                // 先写预写日志再插入 SQLite；插入失败或中途退出时由下次启动回放补上
+                let journaled = crate::wal::append(&record).log_err("写入预写日志").is_some();
+                self.invalidate_stats();
+                // 精力自评有时间窗口，每完成一次专注重新读取
+                self.energy_recent = None;
+                self.recent_tasks = None;
+                if let Some(mut conn) = crate::db::open_and_init().db_err("打开数据库") {
+                    if journaled {
+                        crate::wal::replay(&mut conn).db_err("回放预写日志");
+                    } else {
+                        crate::db::insert_focus_record(&conn, &record).db_err("写入专注记录");
+                    }
+                }
+                self.last_focus_record = Some((record.task.clone(), record.completed_at.clone()));
+                self.focus_history.push_front(record, beijing_now());
+                self.check_task_target();
+                self.check_achievements();
+                self.check_rest_guard();
+            }
+        }
+    }
+
+    /// 每帧：有待进行的休息时开始了专注（推迟休息除外），按离开专注的时长记为休息或跳过
+    fn drive_rest_guard(&mut self) {
+        let focusing = self.pomo.phase == Phase::Focus && self.pomo.state == TimerState::Running;
+        if !focusing || self.pomo.snoozed_break.is_some() {
+            return;
+        }
+        let Some(pending) = self.pending_break.take() else { return };
+        self.record_break(pending.phase, pending.skipped_at(Utc::now()));
+    }
+
+    /// 写入一条休息事件并重新检查休息警示
+    fn record_break(&mut self, phase: Phase, skipped: bool) {
+        let Some(conn) = crate::db::open_and_init().db_err("打开数据库") else { return };
+        let phase_key = crate::rest_guard::phase_key(phase);
+        crate::db::insert_break_event(&conn, &beijing_now_rfc3339(), phase_key, skipped).db_err("写入休息事件");
+        self.session_metrics = None;
+        self.check_rest_guard();
+    }
+
+    /// 按休息守护设置检查是否该警示；新出现警示时按通知路由发出「休息警示」，条件解除时收起
+    fn check_rest_guard(&mut self) {
+        let Some(conn) = crate::db::open_and_init().db_err("打开数据库") else { return };
+        let warning = crate::rest_guard::evaluate(&self.rest_guard, &conn, beijing_now()).db_err("检查休息警示").flatten();
+        if let Some(warning) = warning.as_ref().filter(|_| self.rest_warning.is_none()) {
+            self.notify(NotifyEvent::RestWarning, &warning.message());
+        }
+        self.rest_warning = warning;
+    }
+
+    /// 休息警示横幅：醒目地建议休息，空闲时可直接开始长休息
+    fn ui_rest_warning(&mut self, ui: &mut egui::Ui) {
+        let Some(warning) = &self.rest_warning else { return };
+        let message = warning.message();
+        let accent = self.palette.long_break;
+        let idle = self.pomo.state == TimerState::Idle;
+        egui::Frame::NONE
+            .fill(accent.gamma_multiply(0.18))
+            .stroke(egui::Stroke::new(1.0, accent))
+            .corner_radius(6.0)
+            .inner_margin(8.0)
+            .show(ui, |ui| {
+                ui.set_width(ui.available_width());
+                ui.label(egui::RichText::new(format!("☕ {}", message)).size(15.0).color(self.palette.text));
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(idle, egui::Button::new("开始长休息"))
+                        .on_disabled_hover_text("计时空闲时可用")
+                        .clicked()
+                    {
+                        self.pomo.set_phase(Phase::LongBreak);
+                        self.pomo.start();
+                        self.rest_warning = None;
+                    }
+                    if ui.button("知道了").clicked() {
+                        self.rest_warning = None;
+                    }
+                });
+            });
+        ui.add_space(8.0);
+    }
+
+    /// 刚完成的番茄让今天某条日程做满了预计番茄数时，弹出庆祝并列出接下来可做的日程
+    fn check_task_target(&mut self) {
+        let task = self.current_task.trim();
+        if task.is_empty() {
+            return;
+        }
+        let (today, _) = crate::stats::today_and_week_ranges(beijing_now());
+        let Ok(rows) = crate::db::open_and_init()
+            .and_then(|conn| crate::db::schedule_progress(&conn, &beijing_rfc3339(today.0), &beijing_rfc3339(today.1)))
+        else {
+            return;
+        };
+        let Some(reached) = crate::planner::target_reached(&rows, task) else { return };
+        self.task_celebration = Some(TaskCelebration {
+            schedule_id: reached.id,
+            task: reached.task.clone(),
+            pomodoros: reached.planned,
+            marked: false,
+            next: crate::planner::next_candidates(&rows, task)
+                .into_iter()
+                .map(|r| (r.task.clone(), r.project_id))
+                .collect(),
+            shown_at: std::time::Instant::now(),
+        });
+    }
+
+    /// 每完成一个番茄检查成就，新解锁的合并成一条应用内提示
+    fn check_achievements(&mut self) {
+        let Some(conn) = crate::db::open_and_init().db_err("打开数据库") else { return };
+        let Some(newly) = crate::achievements::check(&conn, &beijing_now_rfc3339()).db_err("检查成就") else { return };
+        if newly.is_empty() {
+            return;
+        }
+        let titles: Vec<String> = newly.iter().map(|a| format!("{} {}", a.icon, a.title)).collect();
+        self.notifier.toast(&format!("🏆 解锁成就：{}", titles.join("、")));
+        if let Some(view) = self.achievements.as_mut() {
+            view.loaded = None;
+        }
+    }
+
+    /// 推迟休息的专注结束：时长并入刚完成的那条专注记录（不新增记录，不计番茄数）
+    fn extend_last_focus(&mut self, secs: i64) {
+        let Some((task, completed_at)) = self.last_focus_record.take() else { return };
+        let now = beijing_now_rfc3339();
+        if let Some(mut conn) = crate::db::open_and_init().db_err("打开数据库") {
+            // 记录可能还在预写日志中，先补写再延长
+            crate::wal::replay(&mut conn).db_err("回放预写日志");
+            crate::db::extend_focus_record(&conn, &task, &completed_at, secs, &now).db_err("延长专注记录");
+        }
+        self.focus_history.extend(&task, &completed_at, secs, &now);
+        self.invalidate_stats();
+        self.last_focus_record = Some((task, now));
+    }
+
+    /// 现在能否「再专注 5 分钟」：休息阶段需有可延长的刚完成记录
+    fn snooze_available(&self) -> bool {
+        self.pomo.can_snooze() && (self.pomo.phase == Phase::Focus || self.last_focus_record.is_some())
+    }
+
+    /// 「再专注 5 分钟」按钮（可推迟时才显示）
+    fn ui_snooze_button(&mut self, ui: &mut egui::Ui, size: egui::Vec2) {
+        if !self.snooze_available() {
+            return;
+        }
+        let left = self.pomo.config.max_snoozes - self.pomo.snoozes;
+        ui.horizontal(|ui| {
+            ui.add_space((ui.available_width() - size.x) * 0.5);
+            let hint = format!("推迟休息，不计番茄数（还可连续推迟 {} 次）", left);
+            if centered_button(ui, "再专注 5 分钟", size).on_hover_text(hint).clicked() {
+                self.pomo.snooze();
+            }
+        });
+    }
+
+    /// 按通知路由把事件发往各渠道（部署策略禁用的网络渠道不发送）
+    fn notify(&mut self, event: NotifyEvent, message: &str) {
+        if event.is_reminder() && self.working_hours.suppresses_reminders(beijing_now().naive_local()) {
+            tracing::debug!("工作时间外，不发送{}：{}", event.label(), message);
+            return;
+        }
+        let policy = crate::policy::get();
+        let mut routing = self.notifications.clone();
+        if !policy.allows(Integration::Webhook) {
+            routing.webhook_url.clear();
+        }
+        if !policy.allows(Integration::PhonePush) {
+            routing.push_url.clear();
+            routing.push_token.clear();
+            routing.telegram_token.clear();
+        }
+        self.notifier
+            .dispatch(&routing, event, message, &beijing_now_rfc3339());
+    }
+
+    /// 每帧：关闭部署策略禁用的集成（用户在设置中勾选也会被立即撤销）
+    fn enforce_policy(&mut self) {
+        let policy = crate::policy::get();
+        if !policy.allows(Integration::PhonePush) {
+            self.notifications.push_actions = false;
+        }
+        if !policy.allows(Integration::Discord) {
+            self.discord.enabled = false;
+        }
+        if !policy.allows(Integration::Obs) {
+            self.obs.enabled = false;
+        }
+        #[cfg(feature = "http-api")]
+        if !policy.allows(Integration::HttpApi) {
+            self.http_api.enabled = false;
+        }
+        if !policy.allows(Integration::DailyNote) {
+            self.daily_note.enabled = false;
+        }
+        if !policy.allows(Integration::DataSync) {
+            self.data_sync.target = SyncTarget::Off;
+        }
+        if !policy.allows(Integration::GitSuggest) {
+            self.git_suggest.enabled = false;
+        }
+        if !policy.allows(Integration::Mqtt) {
+            self.mqtt.enabled = false;
+        }
+        if !policy.allows(Integration::Slack) {
+            self.slack.enabled = false;
+        }
+    }
+
+    /// 设置同步是否可用：已配置端点且未被部署策略禁用
+    fn sync_enabled(&self) -> bool {
+        crate::policy::get().allows(Integration::Sync) && self.sync_endpoint.is_configured()
+    }
+
+    fn record_interruption(kind: &str) {
+        if let Some(conn) = crate::db::open_and_init().db_err("打开数据库") {
+            crate::db::insert_interruption(&conn, &beijing_now_rfc3339(), kind).db_err("记录中断");
+        }
+    }
+
+    /// 当前参与同步的设置快照
+    fn synced_settings(&self) -> SyncedSettings {
+        SyncedSettings {
+            theme: crate::theme::mode_to_str(self.theme_mode).to_string(),
+            font: self.font_choice.clone(),
+            focus_secs: self.pomo.config.focus_secs,
+            short_break_secs: self.pomo.config.short_break_secs,
+            long_break_secs: self.pomo.config.long_break_secs,
+            pomodoros_before_long: self.pomo.config.pomodoros_before_long,
+            accents: self.accents,
+        }
+    }
+
+    /// 配置档列表，当前配置档的外观与时长换成界面上正在生效的
+    fn profiles_snapshot(&self) -> Profiles {
+        let mut profiles = self.profiles.clone();
+        if let Some(active) = profiles.active_mut() {
+            active.settings = self.synced_settings();
+        }
+        profiles
+    }
+
+    /// 切换配置档：当前设置存回原配置档，再应用目标配置档的外观与时长（下次开始阶段时生效）；
+    /// 目标配置档限定了统计项目时选中该项目，记录列表也只看该项目
+    fn switch_profile(&mut self, ctx: &egui::Context, index: usize) {
+        if index == self.profiles.active || index >= self.profiles.list.len() {
+            return;
+        }
+        self.profiles = self.profiles_snapshot();
+        self.profiles.active = index;
+        let profile = self.profiles.list[index].clone();
+        self.apply_synced_settings(ctx, &profile.settings);
+        if let Some(project) = profile.stats_project {
+            self.current_project = project;
+        }
+        self.history_filter.project_id = profile.stats_project;
+        self.invalidate_stats();
+    }
+
+    /// 今日完成的番茄数：当前配置档限定了统计项目时只计该项目
+    fn today_pomodoros(&self) -> usize {
+        let today = beijing_now().format("%Y-%m-%d").to_string();
+        let scope = self.profiles.active().and_then(|p| p.stats_project);
+        self.focus_history
+            .records()
+            .iter()
+            .filter(|r| r.completed_at.starts_with(&today) && scope.is_none_or(|id| r.project_id == id))
+            .count()
+    }
+
+    /// 今日概览：今天完成的番茄数、专注时长与当前任务的番茄数，不必打开统计窗口
+    fn ui_today_glance(&mut self, ui: &mut egui::Ui) {
+        let day = beijing_now().date_naive();
+        let task = self.current_task.trim().to_string();
+        let project_id = self.profiles.active().and_then(|p| p.stats_project);
+        let stale = self
+            .today_glance
+            .as_ref()
+            .is_none_or(|g| g.day != day || g.task != task || g.project_id != project_id);
+        if stale {
+            self.today_glance = Some(TodayGlance::load(day, task, project_id));
+        }
+        let Some(glance) = &self.today_glance else { return };
+        let mut text = format!("今日 🍅{} · {}", glance.sessions, format_compact_hm(glance.total_secs));
+        if !glance.task.is_empty() {
+            text.push_str(&format!(" · 当前任务 🍅{}", glance.task_sessions));
+        }
+        ui.label(egui::RichText::new(text).color(self.palette.text_dim));
+    }
+
+    /// 顶栏的配置档下拉（只有一个配置档时不显示；计时中不能切换）
+    fn ui_profile_picker(&mut self, ui: &mut egui::Ui) {
+        if self.profiles.list.len() < 2 {
+            return;
+        }
+        let mut picked = None;
+        let idle = self.pomo.state == TimerState::Idle;
+        let current = self.profiles.active().map(|p| p.name.clone()).unwrap_or_default();
+        ui.add_enabled_ui(idle, |ui| {
+            egui::ComboBox::from_id_salt("profile_picker")
+                .width(72.0)
+                .selected_text(current)
+                .show_ui(ui, |ui| {
+                    for (i, profile) in self.profiles.list.iter().enumerate() {
+                        if ui.selectable_label(i == self.profiles.active, &profile.name).clicked() {
+                            picked = Some(i);
+                        }
+                    }
+                })
+                .response
+                .on_hover_text("切换配置档")
+                .on_disabled_hover_text("计时中不能切换配置档");
+        });
+        if let Some(i) = picked {
+            self.switch_profile(ui.ctx(), i);
+        }
+    }
+
+    /// 设置中的配置档：切换、新建（复制当前）、删除，当前配置档的每日目标与统计项目
+    fn ui_profile_settings(&mut self, ui: &mut egui::Ui) {
+        let dim = self.palette.text_dim;
+        egui::CollapsingHeader::new("配置档").show(ui, |ui| {
+            let idle = self.pomo.state == TimerState::Idle;
+            let mut switch = None;
+            let mut remove = None;
+            for (i, profile) in self.profiles.list.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    if i == self.profiles.active {
+                        ui.label(egui::RichText::new(format!("● {}", profile.name)).strong());
+                        ui.label(egui::RichText::new("使用中").color(dim));
+                    } else {
+                        ui.label(format!("○ {}", profile.name));
+                        if ui.add_enabled(idle, egui::Button::new("切换").small()).clicked() {
+                            switch = Some(i);
+                        }
+                        if ui.small_button("删除").clicked() {
+                            remove = Some(i);
+                        }
+                    }
+                });
+            }
+            if let Some(i) = switch {
+                self.switch_profile(ui.ctx(), i);
+            }
+            if let Some(i) = remove {
+                self.profile_error = self.profiles.remove(i).err();
+            }
+            let projects = &self.projects;
+            if let Some(active) = self.profiles.active_mut() {
+                ui.horizontal(|ui| {
+                    ui.label("每日目标");
+                    ui.add(egui::DragValue::new(&mut active.daily_goal).range(0..=30).suffix(" 个番茄"))
+                        .on_hover_text("0 表示不设目标；主界面显示今日进度");
+                });
+                ui.horizontal(|ui| {
+                    ui.label("统计范围");
+                    let selected = active
+                        .stats_project
+                        .and_then(|id| projects.iter().find(|p| p.id == id))
+                        .map_or("全部项目", |p| p.name.as_str());
+                    egui::ComboBox::from_id_salt("profile_stats_project")
+                        .selected_text(selected)
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut active.stats_project, None, "全部项目");
+                            for project in projects {
+                                ui.selectable_value(&mut active.stats_project, Some(project.id), &project.name);
+                            }
+                        });
+                });
+            }
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.profile_input)
+                        .hint_text("如 学习")
+                        .char_limit(crate::profile::MAX_NAME_CHARS)
+                        .desired_width(120.0),
+                );
+                if ui.button("新建（复制当前设置）").clicked() {
+                    self.profiles = self.profiles_snapshot();
+                    match self.profiles.add(&self.profile_input) {
+                        Ok(_) => {
+                            self.profile_input.clear();
+                            self.profile_error = None;
+                        }
+                        Err(e) => self.profile_error = Some(e),
+                    }
+                }
+            });
+            if let Some(e) = &self.profile_error {
+                ui.label(egui::RichText::new(e).color(dim));
+            }
+            ui.label(
+                egui::RichText::new("每个配置档有自己的主题、字体、时长、强调色与每日目标；有两个以上时可在主界面顶栏切换。")
+                    .color(dim),
+            );
+        });
+    }
+
+    /// 应用从云端拉取的设置
+    fn apply_synced_settings(&mut self, ctx: &egui::Context, s: &SyncedSettings) {
+        self.theme_mode = crate::theme::mode_from_str(&s.theme);
+        if s.font != self.font_choice {
+            self.font_choice = s.font.clone();
+            self.fonts.apply(ctx, self.font_choice.as_deref());
+        }
+        // 远端的值不可信：不大于 0 的忽略，其余限制在设置界面允许的范围内（1–180 分钟、1–12 个番茄）
+        let config = &mut self.pomo.config;
+        for (remote, secs) in [
+            (s.focus_secs, &mut config.focus_secs),
+            (s.short_break_secs, &mut config.short_break_secs),
+            (s.long_break_secs, &mut config.long_break_secs),
+        ] {
+            if remote > 0 {
+                *secs = remote.clamp(60, 180 * 60);
+            }
+        }
+        if s.pomodoros_before_long > 0 {
+            config.pomodoros_before_long = s.pomodoros_before_long.min(12);
+        }
+        self.accents = s.accents;
+    }
+
+    /// 每帧：config.toml 首次读到或修改后，把其中写了的项覆盖到当前设置
+    fn drive_config_file(&mut self) {
+        let Some(result) = self.config_file.poll() else { return };
+        let c = match result {
+            Ok(c) => c,
+            Err(e) => {
+                tracing::warn!("{}", e);
+                self.config_status = e;
+                return;
+            }
+        };
+        if let Some(mode) = c.theme_mode() {
+            self.theme_mode = mode;
+        }
+        let d = &c.durations;
+        let config = &mut self.pomo.config;
+        for (minutes, secs) in [
+            (d.focus_minutes, &mut config.focus_secs),
+            (d.short_break_minutes, &mut config.short_break_secs),
+            (d.long_break_minutes, &mut config.long_break_secs),
+        ] {
+            if let Some(m) = minutes.filter(|m| *m > 0) {
+                *secs = m * 60;
+            }
+        }
+        if let Some(n) = d.pomodoros_before_long.filter(|n| *n > 0) {
+            config.pomodoros_before_long = n;
+        }
+        if let Some(shortcut) = c.hotkeys.quick_capture.as_deref().and_then(crate::config::parse_shortcut) {
+            self.capture_shortcut = shortcut;
+        }
+        if let Some(tick) = c.sound.tick {
+            self.tick_sound.enabled = tick;
+        }
+        if let Some(volume) = c.sound.tick_volume {
+            self.tick_sound.volume = volume.min(100);
+        }
+        let hooks = c.hooks;
+        let routing = &mut self.notifications;
+        for (value, field) in [
+            (hooks.webhook_url, &mut routing.webhook_url),
+            (hooks.push_url, &mut routing.push_url),
+            (hooks.push_token, &mut routing.push_token),
+            (hooks.telegram_token, &mut routing.telegram_token),
+            (hooks.telegram_chat_id, &mut routing.telegram_chat_id),
+        ] {
+            if let Some(value) = value {
+                *field = value;
+            }
+        }
+        let sync = c.sync;
+        for (value, field) in [
+            (sync.settings_url, &mut self.sync_endpoint.url),
+            (sync.settings_token, &mut self.sync_endpoint.token),
+            (sync.folder, &mut self.data_sync.folder),
+            (sync.url, &mut self.data_sync.url),
+            (sync.username, &mut self.data_sync.username),
+            (sync.password, &mut self.data_sync.password),
+        ] {
+            if let Some(value) = value {
+                *field = value;
+            }
+        }
+        if let Some(target) = sync.target {
+            self.data_sync.target = target;
+        }
+        if let Some(minutes) = sync.interval_minutes.filter(|m| *m > 0) {
+            self.data_sync.interval_minutes = minutes;
+        }
+        self.config_status = format!("已应用 {}（{}）", crate::config::CONFIG_FILENAME, beijing_now().format("%H:%M:%S"));
+    }
+
+    /// 每帧：取回同步结果；本机设置变化且已与云端对齐过时自动推送
+    fn drive_settings_sync(&mut self, ctx: &egui::Context) {
+        if let Some(result) = self.settings_sync.poll() {
+            match result {
+                SyncResult::Pulled(Some(remote)) => {
+                    self.error_reports.dismiss(ErrorKind::SettingsSync);
+                    self.apply_synced_settings(ctx, &remote);
+                    self.last_synced = Some(remote);
+                    self.sync_status = "已拉取云端设置".to_string();
+                }
+                SyncResult::Pulled(None) => {
+                    // 云端还没有设置：以本机为准推送上去
+                    let local = self.synced_settings();
+                    self.settings_sync.push(&self.sync_endpoint, local);
+                    self.sync_status = "云端无设置，正在上传本机设置…".to_string();
+                }
+                SyncResult::Pushed(pushed) => {
+                    self.error_reports.dismiss(ErrorKind::SettingsSync);
+                    self.last_synced = Some(pushed);
+                    self.sync_status = "已同步到云端".to_string();
+                }
+                SyncResult::Failed(e) => {
+                    crate::errors::report(ErrorKind::SettingsSync, format!("设置同步失败：{}", e));
+                    // 推送失败也记下快照，避免每帧重试；可在设置中手动同步
+                    if self.last_synced.is_some() {
+                        self.last_synced = Some(self.synced_settings());
+                    }
+                    self.notify(NotifyEvent::SyncFailed, &e);
+                    self.sync_status = e;
+                }
+            }
+        }
+        if !self.sync_enabled() || self.settings_sync.is_busy() {
+            return;
+        }
+        let local = self.synced_settings();
+        if self.last_synced.as_ref().is_some_and(|s| *s != local) {
+            self.settings_sync.push(&self.sync_endpoint, local);
+            self.sync_status = "正在同步…".to_string();
+        }
+    }
+
+    /// 阶段切换、开始 / 暂停 / 停止、番茄数变化或切换紧凑 / 钉住时立即保存会话状态并写盘，不等自动保存
+    /// （专注记录完成时已同步写入数据库，这里补上内存中的番茄数与计时状态）
+    fn save_on_transition(&mut self, frame: &mut eframe::Frame) {
+        let marker = SaveMarker {
+            phase: self.pomo.phase,
+            state: self.pomo.state,
+            completed_pomodoros: self.pomo.completed_pomodoros,
+            step: self.pomo.step,
+            compact: self.compact,
+            pinned: self.pinned,
+        };
+        // 启动后的第一帧只记下当前状态
+        let Some(previous) = self.saved_marker.replace(marker) else { return };
+        if previous == marker {
+            return;
+        }
+        if let Some(storage) = frame.storage_mut() {
+            eframe::App::save(self, storage);
+            storage.flush();
+        }
+    }
+
+    /// 钉到桌面右上角并切换为紧凑模式
+    fn pin_to_compact(&mut self) {
+        tracing::info!("钉住并切换为紧凑模式");
+        self.pinned = true;
+        self.compact = true;
+        self.compact_size_applied = false;
+        self.pin_applied = false;
+    }
+
+    /// 顶栏 × 按钮：按设置最小化、钉成小窗或退出（计时进行中退出先确认）
+    fn on_close_clicked(&mut self, ctx: &egui::Context) {
+        match self.close_action {
+            CloseAction::Compact if !self.compact => self.pin_to_compact(),
+            CloseAction::Minimize | CloseAction::Compact => {
+                ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true));
+            }
+            // 计时进行中由 intercept_close 拦下并弹确认框
+            CloseAction::Quit => ctx.send_viewport_cmd(egui::ViewportCommand::Close),
+        }
+    }
+
+    /// 拦截窗口关闭请求（× 按钮、Alt+F4、任务栏关闭等）：计时进行中先取消关闭并弹确认框
+    fn intercept_close(&mut self, ctx: &egui::Context) {
+        if ctx.input(|i| i.viewport().close_requested())
+            && self.pomo.state != TimerState::Idle
+            && !self.quit_confirmed
+        {
+            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+            self.confirm_quit = true;
+        }
+    }
+
+    /// 退出确认：最小化（计时继续）、放弃本次并退出（专注中记为放弃）、取消
+    fn ui_confirm_quit(&mut self, ctx: &egui::Context) {
+        egui::Window::new("退出红番茄？")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "{}还剩 {}，退出后本次计时将中断。",
+                    Self::phase_label(self.pomo.phase),
+                    self.pomo.remaining_display()
+                ));
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button("最小化").on_hover_text("计时继续").clicked() {
+                        self.confirm_quit = false;
+                        ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true));
+                    }
+                    if ui.button("放弃本次并退出").clicked() {
+                        self.confirm_quit = false;
+                        self.quit_confirmed = true;
+                        self.record_abandon_if_focusing();
+                        // 放弃的计时不再随会话状态保存，下次启动为空闲
+                        self.pomo.stop();
+                        ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                    }
+                    if ui.button("取消").clicked() {
+                        self.confirm_quit = false;
+                    }
+                });
+            });
+    }
+
+    /// 每帧：按设置启停本机 HTTP 接口，发布状态快照并执行收到的控制命令
+    #[cfg(feature = "http-api")]
+    fn drive_status_server(&mut self) {
+        use crate::server::{StatusServer, StatusSnapshot};
+        if !self.http_api.enabled {
+            self.status_server = None;
+            self.http_api_error = None;
+            return;
+        }
+        if self.status_server.as_ref().is_some_and(|s| s.port() != self.http_api.port) {
+            self.status_server = None;
+            self.http_api_error = None;
+        }
+        if self.status_server.is_none() && self.http_api_error.is_none() {
+            match StatusServer::start(self.http_api.port) {
+                Ok(server) => self.status_server = Some(server),
+                Err(e) => {
+                    crate::errors::report(
+                        ErrorKind::Integration,
+                        format!("HTTP 接口端口 {} 启动失败：{}", self.http_api.port, e),
+                    );
+                    self.http_api_error = Some(format!("端口 {} 启动失败：{}", self.http_api.port, e));
+                }
+            }
+        }
+        let Some(server) = &self.status_server else { return };
+        server.publish(StatusSnapshot {
+            phase: phase_to_str(self.pomo.phase).to_string(),
+            state: state_to_str(self.pomo.state).to_string(),
+            remaining_secs: self.pomo.remaining_secs,
+            remaining: self.pomo.remaining_display(),
+            task: self.current_task.clone(),
+            tomatoes: self.pomo.completed_pomodoros,
+            pomodoros_before_long: self.pomo.cycle_length(),
+        });
+        for command in server.take_commands() {
+            self.apply_command(command);
+        }
+    }
+
+    /// 执行外部控制命令（HTTP 接口、任务栏缩略图按钮）；与当前状态不符的命令忽略
+    fn apply_command(&mut self, command: Command) {
+        match (command, self.pomo.state) {
+            (Command::Start, TimerState::Idle) => self.pomo.start(),
+            (Command::Start, TimerState::Paused) | (Command::Pause, TimerState::Running) => self.toggle_pause(),
+            // 加时中跳过即结束加时（专注已满，按完成记录）并进入休息
+            (Command::Skip, _) if self.pomo.in_overtime => self.pomo.finish_overtime(),
+            // 跳过当前阶段（不计为完成）：例程进入下一步；经典番茄专注跳到短休息，休息跳回专注
+            (Command::Skip, _) => {
+                self.record_abandon_if_focusing();
+                match self.pomo.routine.as_ref().map(|r| (self.pomo.step + 1) % r.steps.len()) {
+                    Some(next) => self.pomo.set_step(next),
+                    None => {
+                        let next = if self.pomo.phase == Phase::Focus { Phase::ShortBreak } else { Phase::Focus };
+                        self.pomo.set_phase(next);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// 每帧：更新任务栏缩略图按钮（开始 / 暂停随状态切换）并执行点击
+    fn drive_thumb_bar(&mut self, ctx: &egui::Context, frame: &eframe::Frame) {
+        let running = self.pomo.state == TimerState::Running;
+        for command in self.thumb_bar.sync(ctx, frame, running) {
+            self.apply_command(command);
+        }
+    }
+
+    /// 每帧：执行手机推送按钮的回调；「+5 分钟」推迟的下一阶段到点后自动开始
+    fn drive_push_actions(&mut self) {
+        for action in self.push_listener.sync(&self.notifications) {
+            match (action, self.pomo.state) {
+                (PushAction::StartNext, TimerState::Idle) => {
+                    self.delayed_start = None;
+                    self.pomo.start();
+                }
+                (PushAction::AddFiveMinutes, TimerState::Idle) => {
+                    self.delayed_start = Some(Utc::now() + chrono::Duration::seconds(ADJUST_STEP_SECS));
+                }
+                (PushAction::AddFiveMinutes, _) => {
+                    self.pomo.adjust_remaining(ADJUST_STEP_SECS);
+                }
+                // 已在计时（如已在电脑上开始）时忽略过期的「开始」
+                (PushAction::StartNext, _) => {}
+            }
+        }
+        if self.pomo.state != TimerState::Idle {
+            self.delayed_start = None;
+        } else if self.delayed_start.is_some_and(|at| Utc::now() >= at) {
+            self.delayed_start = None;
+            self.pomo.start();
+        }
+    }
+
+    /// OBS 文本源内容：`🍅 24:59 · 任务名`（休息用 ☕，暂停时注明）
+    fn obs_text(&self) -> String {
+        let icon = if self.pomo.phase == Phase::Focus { "🍅" } else { "☕" };
+        let time = match self.pomo.state {
+            TimerState::Idle => "未开始".to_string(),
+            TimerState::Running => self.pomo.remaining_display(),
+            TimerState::Paused => format!("{}（已暂停）", self.pomo.remaining_display()),
+        };
+        let label = if self.pomo.phase == Phase::Focus {
+            self.current_task.trim()
+        } else {
+            Self::phase_label(self.pomo.phase)
+        };
+        if label.is_empty() {
+            format!("{} {}", icon, time)
+        } else {
+            format!("{} {} · {}", icon, time, label)
+        }
+    }
+
+    /// 托盘图标内容：剩余分钟数（不足一分钟按一分钟计）与按阶段着色的剩余进度；加时中为已加时分钟数
+    fn tray_state(&self) -> TrayState {
+        let pomo = &self.pomo;
+        let phase = Self::phase_label(pomo.phase);
+        let color = if pomo.in_overtime { self.palette.overtime } else { self.palette.phase_accent(pomo.phase) };
+        let (minutes, remaining, tip) = if pomo.in_overtime {
+            let minutes = (pomo.overtime_secs / 60) as u32;
+            (minutes, 0.0, format!("红番茄 · {} · 加时 {} 分钟", phase, minutes))
+        } else {
+            let minutes = ((pomo.remaining_secs.max(0) + 59) / 60) as u32;
+            let state = match pomo.state {
+                TimerState::Idle => "（未开始）",
+                TimerState::Running => "",
+                TimerState::Paused => "（已暂停）",
+            };
+            (minutes, 1.0 - pomo.progress(), format!("红番茄 · {}{} · 剩余 {} 分钟", phase, state, minutes))
+        };
+        TrayState {
+            minutes,
+            remaining: (remaining * crate::tray::PIE_STEPS).round() / crate::tray::PIE_STEPS,
+            color: [color.r(), color.g(), color.b()],
+            tip,
+        }
+    }
+
+    /// 每帧：开启托盘计时时同步托盘图标（内容变化才重画），关闭时移除
+    fn drive_tray(&mut self, frame: &eframe::Frame) {
+        let state = self.tray_timer.then(|| self.tray_state());
+        self.tray.sync(frame, state);
+    }
+
+    /// 每帧：开启数据保留时每天检查一次（启动后首帧即检查），把超过保留期的记录移入归档库。
+    /// 当天关闭状态下也记为已检查，拖动设置中的月数不会立刻按中间值归档
+    fn drive_archive(&mut self) {
+        let today = beijing_now().date_naive();
+        if self.archive_checked_on == Some(today) {
+            return;
+        }
+        self.archive_checked_on = Some(today);
+        if self.archive_after_months == 0 {
+            return;
+        }
+        let Some(cutoff) = beijing_now().checked_sub_months(chrono::Months::new(self.archive_after_months)) else {
+            return;
+        };
+        let result = crate::db::open_and_init()
+            .and_then(|conn| crate::db::archive_records_before(&conn, &cutoff.to_rfc3339()));
+        self.archive_status = match result {
+            Ok(0) => format!("{} 已检查，没有需要归档的记录", today.format("%Y-%m-%d")),
+            Ok(moved) => {
+                self.load_focus_history_from_db();
+                self.recent_tasks = None;
+                self.invalidate_stats();
+                format!("{} 已归档 {} 条记录", today.format("%Y-%m-%d"), moved)
+            }
+            Err(e) => {
+                crate::errors::report(ErrorKind::Database, format!("归档失败：{}", e));
+                format!("归档失败：{}", e)
+            }
+        };
+    }
+
+    /// 专注记录同步是否可用：已配置位置且未被部署策略禁用
+    fn data_sync_enabled(&self) -> bool {
+        crate::policy::get().allows(Integration::DataSync) && self.data_sync.is_configured()
+    }
+
+    /// 开始一次后台数据同步（首次同步时生成本机标识）
+    fn start_data_sync(&mut self) {
+        if self.data_sync.device.is_empty() {
+            self.data_sync.device = crate::data_sync::new_device_id();
+        }
+        self.data_sync_runner.start(&self.data_sync);
+        self.data_sync_started = Some(std::time::Instant::now());
+        self.data_sync_status = "正在同步…".to_string();
+    }
+
+    /// 每帧：取回数据同步结果（导入了记录时重新加载历史与项目），到达间隔时开始下一次同步（启动后立即同步一次）
+    fn drive_data_sync(&mut self) {
+        if let Some(result) = self.data_sync_runner.poll() {
+            let time = beijing_now().format("%H:%M");
+            self.data_sync_status = match result {
+                DataSyncResult::Done(report) => {
+                    self.data_sync.last_synced_at = Some(report.synced_at.clone());
+                    if report.changed_local() {
+                        self.load_focus_history_from_db();
+                        self.load_projects();
+                        self.recent_tasks = None;
+                        self.invalidate_stats();
+                    }
+                    self.error_reports.dismiss(ErrorKind::DataSync);
+                    format!("{} 同步成功：{}", time, report.describe())
+                }
+                DataSyncResult::Failed(e) => {
+                    crate::errors::report(ErrorKind::DataSync, format!("数据同步失败：{}", e));
+                    format!("{} 同步失败：{}", time, e)
+                }
+            };
+        }
+        if !self.data_sync_enabled() || self.data_sync_runner.is_busy() {
+            return;
+        }
+        let interval = std::time::Duration::from_secs(self.data_sync.interval_minutes.max(1) as u64 * 60);
+        if self.data_sync_started.is_none_or(|t| t.elapsed() >= interval) {
+            self.start_data_sync();
+        }
+    }
+
+    /// 每隔 `planner::CHECK_INTERVAL_SECS` 检查到点的日程：发出「日程提醒」通知并弹出一键开始提示
+    /// （开始时间已过 `REMIND_GRACE_MINUTES` 以上的不再提醒）；工作时间内空闲过久时发出「空闲提醒」；
+    /// 到点发送 Telegram 每日摘要
+    fn drive_schedule(&mut self) {
+        let interval = std::time::Duration::from_secs(crate::planner::CHECK_INTERVAL_SECS);
+        if self.schedule_checked.is_some_and(|t| t.elapsed() < interval) {
+            return;
+        }
+        self.schedule_checked = Some(std::time::Instant::now());
+        let now = beijing_now();
+        self.send_telegram_digest(now.naive_local());
+        let since = now - chrono::Duration::minutes(crate::planner::REMIND_GRACE_MINUTES);
+        let due = crate::db::open_and_init()
+            .and_then(|conn| crate::db::take_due_schedule(&conn, &since.to_rfc3339(), &now.to_rfc3339()))
+            .db_err("检查日程提醒")
+            .unwrap_or_default();
+        for row in due {
+            let message = format!(
+                "{} 日程：{} ×{}",
+                row.start_at.get(11..16).unwrap_or(""),
+                row.task,
+                row.pomodoros
+            );
+            self.notify(NotifyEvent::ScheduledSession, &message);
+            self.schedule_prompt = Some(row);
+        }
+        if self.pomo.state == TimerState::Running {
+            self.idle_since = Utc::now();
+        } else if let Some(idle) = self.idle_nudge.due(now, self.idle_since.with_timezone(now.offset())) {
+            self.notify(NotifyEvent::IdleNudge, &format!("已经 {} 分钟没有专注了", idle));
+            // 之后每隔设定分钟数再提醒一次
+            self.idle_since = Utc::now();
+        }
+    }
+
+    /// 到了设定时刻且当天未发送时，把当天的摘要（与日报相同的汇总）发到 Telegram
+    fn send_telegram_digest(&mut self, now: chrono::NaiveDateTime) {
+        if !crate::policy::get().allows(Integration::PhonePush) || !self.notifications.digest_due(now) {
+            return;
+        }
+        let Some(text) = crate::db::open_and_init()
+            .and_then(|conn| crate::review::daily_digest(&conn, now.date()))
+            .db_err("生成每日摘要")
+        else {
+            return;
+        };
+        crate::notify::send_telegram(&self.notifications, &text);
+        self.notifications.telegram_digest_sent_on = Some(now.date());
+    }
+
+    /// 每帧：专注计时运行时累计护眼计时，满间隔时按通知路由发出「护眼提醒」
+    fn drive_eye_rest(&mut self) {
+        let focusing = self.pomo.phase == Phase::Focus && self.pomo.state == TimerState::Running;
+        if self.eye_rest_timer.tick(&self.eye_rest, focusing, std::time::Instant::now()) {
+            let message = self.eye_rest.message();
+            self.notify(NotifyEvent::EyeRest, &message);
+        }
+    }
+
+    /// 阶段结束后触发一次视觉提醒：任务栏闪烁、窗口弹到最前、开始背景脉冲（见 `alarm_bg`）
+    fn drive_visual_alarm(&mut self, ctx: &egui::Context) {
+        let Some(phase) = self.visual_alarm_pending.take() else { return };
+        let visual = self.notifications.visual;
+        if visual.flash_taskbar {
+            ctx.send_viewport_cmd(egui::ViewportCommand::RequestUserAttention(egui::UserAttentionType::Critical));
+        }
+        if visual.raise_window {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+        }
+        if visual.pulse {
+            self.visual_pulse = Some((std::time::Instant::now(), phase));
+        }
+    }
+
+    /// 窗口背景色：视觉提醒脉冲期间向刚结束阶段的强调色过渡
+    fn alarm_bg(&mut self) -> egui::Color32 {
+        let bg = self.palette.bg;
+        let Some((at, phase)) = self.visual_pulse else { return bg };
+        let steady = self.a11y.is_some_and(|a| a.reduce_motion);
+        match crate::notify::pulse_intensity(at.elapsed(), steady) {
+            Some(t) => bg.lerp_to_gamma(self.palette.phase_accent(phase), t),
+            None => {
+                self.visual_pulse = None;
+                bg
+            }
+        }
+    }
+
+    /// 每帧：专注计时运行中检测前台窗口，一次超过设定秒数的分心结束时写入数据库
+    fn drive_distraction(&mut self) {
+        let focusing = self.pomo.phase == Phase::Focus && self.pomo.state == TimerState::Running;
+        let Some(d) = self.distraction_monitor.tick(&self.distraction, focusing, std::time::Instant::now()) else {
+            return;
+        };
+        let started_at = d.started_at.with_timezone(beijing_now().offset()).to_rfc3339();
+        let saved = crate::db::open_and_init()
+            .and_then(|conn| crate::db::insert_distraction(&conn, &d.keyword, &d.title, &started_at, d.duration_secs))
+            .db_err("记录分心");
+        if saved.is_some() {
+            self.distraction_totals = None;
+        }
+    }
+
+    /// 每帧：从专注进入休息时发出勾选了「休息开始」的健康提醒，并检查按间隔的提醒；同时到期的合并为一条
+    fn drive_wellness(&mut self) {
+        let time = beijing_now().time();
+        let mut messages: Vec<String> = Vec::new();
+        let previous = self.wellness_phase.replace(self.pomo.phase);
+        if previous == Some(Phase::Focus) && self.pomo.phase != Phase::Focus {
+            messages.extend(self.wellness.at_break_start(time).into_iter().map(str::to_string));
+        }
+        for message in self.wellness_timer.due(&self.wellness, time, std::time::Instant::now()) {
+            if !messages.iter().any(|m| m == message) {
+                messages.push(message.to_string());
+            }
+        }
+        if !messages.is_empty() {
+            self.notify(NotifyEvent::Wellness, &messages.join(" · "));
+        }
+    }
+
+    /// 每帧：专注计时运行时随计时器每秒响一声滴答（暂停、休息时静音）
+    fn drive_metronome(&mut self) {
+        let focusing = self.pomo.phase == Phase::Focus && self.pomo.state == TimerState::Running;
+        let second = (self.pomo.remaining_secs, self.pomo.overtime_secs);
+        self.metronome.sync(&self.tick_sound, focusing, second);
+    }
+
+    /// 每帧：专注开始时暂停正在播放的媒体，进入休息时恢复
+    fn drive_media(&mut self) {
+        let focusing = self.pomo.phase == Phase::Focus && self.pomo.state == TimerState::Running;
+        self.media_control.sync(&self.media, focusing, self.pomo.phase != Phase::Focus);
+    }
+
+    /// 护眼提醒浮层：窗口中央显示「看远处」与倒计时，可跳过；不影响番茄计时
+    fn ui_eye_rest_overlay(&mut self, ctx: &egui::Context) {
+        if !self.eye_rest.overlay {
+            return;
+        }
+        let Some(left) = self.eye_rest_timer.resting_secs_left(std::time::Instant::now()) else { return };
+        let palette = self.palette;
+        egui::Area::new(egui::Id::new("eye_rest_overlay"))
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).inner_margin(16.0).show(ui, |ui| {
+                    ui.vertical_centered(|ui| {
+                        ui.label(egui::RichText::new("👀 看远处，放松眼睛").size(16.0).color(palette.text));
+                        ui.label(egui::RichText::new(format!("{} 秒", left)).size(28.0).color(palette.focus));
+                        if ui.small_button("跳过").clicked() {
+                            self.eye_rest_timer.dismiss();
+                        }
+                    });
+                });
+            });
+    }
+
+    /// 主界面的例程选择（仅 Idle 时可切换）；选中例程时列出各步，点击可跳到该步
+    fn ui_routine_picker(&mut self, ui: &mut egui::Ui) {
+        let idle = self.pomo.state == TimerState::Idle;
+        let current = self.pomo.routine.as_ref().map(|r| r.name.clone());
+        let mut chosen = None;
+        ui.horizontal(|ui| {
+            ui.label("例程：");
+            ui.add_enabled_ui(idle, |ui| {
+                egui::ComboBox::from_id_salt("routine_picker")
+                    .selected_text(current.as_deref().unwrap_or("经典番茄"))
+                    .show_ui(ui, |ui| {
+                        if ui.selectable_label(current.is_none(), "经典番茄").clicked() {
+                            chosen = Some(None);
+                        }
+                        for routine in Routine::presets().into_iter().chain(self.routines.iter().cloned()) {
+                            let selected = current.as_deref() == Some(routine.name.as_str());
+                            let resp = ui.selectable_label(selected, &routine.name).on_hover_text(routine.summary());
+                            if resp.clicked() {
+                                chosen = Some(Some(routine));
+                            }
+                        }
+                    });
+            });
+        });
+        if let Some(routine) = chosen {
+            self.pomo.set_routine(routine);
+        }
+        let Some(routine) = self.pomo.routine.clone() else { return };
+        ui.horizontal_wrapped(|ui| {
+            for (i, step) in routine.steps.iter().enumerate() {
+                let text = format!("{} {}", Self::phase_label(step.phase), step.minutes);
+                let resp = ui.add_enabled(idle, egui::Button::new(text).small());
+                if resp.clicked() {
+                    self.pomo.set_step(i);
+                }
+                if i == self.pomo.step {
+                    resp.highlight();
+                }
+            }
+        });
+    }
+
+    /// 设置中的自定义例程：名称与阶段序列（每步阶段类型 + 分钟数），修改当前例程后下次开始阶段时生效
+    fn ui_routine_settings(&mut self, ui: &mut egui::Ui) {
+        let dim = self.palette.text_dim;
+        egui::CollapsingHeader::new(format!("自定义例程（{} 个）", self.routines.len())).show(ui, |ui| {
+            let mut remove = None;
+            for (i, routine) in self.routines.iter_mut().enumerate() {
+                ui.push_id(i, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.add(egui::TextEdit::singleline(&mut routine.name).desired_width(140.0));
+                        if ui.small_button("删除例程").clicked() {
+                            remove = Some(i);
+                        }
+                    });
+                    let mut remove_step = None;
+                    let removable = routine.steps.len() > 1;
+                    for (j, step) in routine.steps.iter_mut().enumerate() {
+                        ui.horizontal(|ui| {
+                            ui.label(format!("{}.", j + 1));
+                            egui::ComboBox::from_id_salt(("routine_step", j))
+                                .selected_text(Self::phase_label(step.phase))
+                                .show_ui(ui, |ui| {
+                                    for phase in [Phase::Focus, Phase::ShortBreak, Phase::LongBreak] {
+                                        ui.selectable_value(&mut step.phase, phase, Self::phase_label(phase));
+                                    }
+                                });
+                            ui.add(egui::DragValue::new(&mut step.minutes).range(1..=180).suffix(" 分"));
+                            let remove = icon_button_name(ui.add_enabled(removable, egui::Button::new("✕").small()), "删除这一步");
+                            if remove.clicked() {
+                                remove_step = Some(j);
+                            }
+                        });
+                    }
+                    if let Some(j) = remove_step {
+                        routine.steps.remove(j);
+                    }
+                    let room = routine.steps.len() < crate::pomodoro::MAX_ROUTINE_STEPS;
+                    if ui.add_enabled(room, egui::Button::new("添加一步").small()).clicked() {
+                        let phase = match routine.steps.last().map(|s| s.phase) {
+                            Some(Phase::Focus) => Phase::ShortBreak,
+                            _ => Phase::Focus,
+                        };
+                        let minutes = if phase == Phase::Focus { 25 } else { 5 };
+                        routine.steps.push(RoutineStep { phase, minutes });
+                    }
+                    ui.separator();
+                });
+            }
+            if let Some(i) = remove {
+                let removed = self.routines.remove(i);
+                if self.pomo.routine.as_ref().is_some_and(|r| r.name == removed.name) {
+                    self.pomo.set_routine(None);
+                }
+            }
+            for routine in &self.routines {
+                self.pomo.refresh_routine(routine);
+            }
+            if ui.button("新建例程").clicked() {
+                let taken = |name: &str| {
+                    Routine::presets().iter().chain(self.routines.iter()).any(|r| r.name == name)
+                };
+                let name = (1..).map(|n| format!("我的例程 {}", n)).find(|n| !taken(n)).unwrap_or_default();
+                self.routines.push(Routine {
+                    name,
+                    steps: vec![
+                        RoutineStep { phase: Phase::Focus, minutes: 25 },
+                        RoutineStep { phase: Phase::ShortBreak, minutes: 5 },
+                    ],
+                });
+            }
+            ui.label(
+                egui::RichText::new("在主界面的「例程」中选择；走完最后一步后从头循环。内置预设：深度工作 50/10、超昼夜节律 90/20、晨间例程。")
+                    .color(dim),
+            );
+        });
+    }
+
+    /// 设置中的护眼提醒：间隔、时长与是否显示浮层
+    fn ui_eye_rest_settings(&mut self, ui: &mut egui::Ui) {
+        let eye = &mut self.eye_rest;
+        egui::CollapsingHeader::new("护眼提醒（20-20-20）").show(ui, |ui| {
+            ui.checkbox(&mut eye.enabled, "专注时定时提醒看远处");
+            ui.add_enabled_ui(eye.enabled, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("每累计专注");
+                    ui.add(egui::DragValue::new(&mut eye.interval_minutes).range(5..=120));
+                    ui.label("分钟，看远处");
+                    ui.add(egui::DragValue::new(&mut eye.rest_secs).range(5..=120));
+                    ui.label("秒");
+                });
+                ui.checkbox(&mut eye.overlay, "在窗口上显示倒计时浮层");
+            });
+            ui.label(
+                egui::RichText::new("暂停或休息时停止累计，跨番茄继续计算。通知渠道在「通知路由」中设置。")
+                    .color(self.palette.text_dim),
+            );
+        });
+    }
+
+    /// 设置中的呼吸引导：开关与呼吸节奏
+    fn ui_breathing_settings(&mut self, ui: &mut egui::Ui) {
+        let breathing = &mut self.breathing;
+        egui::CollapsingHeader::new("呼吸引导").show(ui, |ui| {
+            ui.checkbox(&mut breathing.enabled, "休息时显示呼吸引导圆圈");
+            ui.add_enabled_ui(breathing.enabled, |ui| {
+                for pattern in BreathPattern::ALL {
+                    ui.radio_value(&mut breathing.pattern, pattern, pattern.label());
+                }
+            });
+            ui.label(
+                egui::RichText::new("短 / 长休息计时中显示在主界面，可随时跳过；减少动态效果时圆圈不缩放。")
+                    .color(self.palette.text_dim),
+            );
+        });
+    }
+
+    /// 设置中的专注语录：开关、轮换间隔与语录列表的增删
+    fn ui_quote_settings(&mut self, ui: &mut egui::Ui) {
+        let dim = self.palette.text_dim;
+        egui::CollapsingHeader::new("专注语录").show(ui, |ui| {
+            let quotes = &mut self.quotes;
+            ui.checkbox(&mut quotes.enabled, "专注时在计时器下方显示语录");
+            ui.add_enabled_ui(quotes.enabled, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("每");
+                    ui.add(egui::DragValue::new(&mut quotes.rotate_minutes).range(0..=60));
+                    ui.label("分钟换一句").on_hover_text("0 表示每次专注只显示一句");
+                });
+                let mut remove = None;
+                for (i, quote) in quotes.quotes.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        if ui.small_button("✕").on_hover_text("删除").clicked() {
+                            remove = Some(i);
+                        }
+                        ui.label(quote);
+                    });
+                }
+                if let Some(i) = remove {
+                    quotes.quotes.remove(i);
+                }
+                ui.horizontal(|ui| {
+                    let edit = ui.add(
+                        egui::TextEdit::singleline(&mut self.quote_input)
+                            .hint_text("新增语录或座右铭")
+                            .char_limit(crate::quotes::MAX_QUOTE_CHARS),
+                    );
+                    let submitted = edit.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    if ui.button("添加").clicked() || submitted {
+                        match quotes.add(&self.quote_input) {
+                            Ok(()) => {
+                                self.quote_input.clear();
+                                self.quote_error = None;
+                            }
+                            Err(e) => self.quote_error = Some(e),
+                        }
+                    }
+                    if ui.button("恢复默认").clicked() {
+                        quotes.quotes = crate::quotes::DEFAULT_QUOTES.iter().map(|q| q.to_string()).collect();
+                    }
+                });
+                if let Some(e) = &self.quote_error {
+                    ui.label(egui::RichText::new(e).color(dim));
+                }
+            });
+        });
+    }
+
+    /// 设置中的空闲提醒：空闲分钟数，以及每天的工作时间与免打扰时段
+    fn ui_idle_nudge_settings(&mut self, ui: &mut egui::Ui) {
+        let dim = self.palette.text_dim;
+        let nudge = &mut self.idle_nudge;
+        egui::CollapsingHeader::new("空闲提醒").show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut nudge.enabled, "工作时间内超过");
+                ui.add_enabled(nudge.enabled, egui::DragValue::new(&mut nudge.idle_minutes).range(10..=480));
+                ui.label("分钟没有计时则提醒");
+            });
+            ui.add_enabled_ui(nudge.enabled, |ui| {
+                egui::Grid::new("idle_nudge_days").show(ui, |ui| {
+                    ui.label("");
+                    ui.label("工作时间");
+                    ui.label("免打扰");
+                    ui.end_row();
+                    for (day, label) in nudge.days.iter_mut().zip(crate::planner::WEEKDAY_LABELS) {
+                        ui.checkbox(&mut day.enabled, label);
+                        for (text, optional) in [(&mut day.work, false), (&mut day.quiet, true)] {
+                            let valid = crate::planner::parse_range(text).is_some() || (optional && text.trim().is_empty());
+                            let edit = egui::TextEdit::singleline(text).desired_width(90.0).hint_text("09:00-18:00");
+                            let edit = if valid { edit } else { edit.text_color(ui.visuals().error_fg_color) };
+                            ui.add_enabled(day.enabled, edit);
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+            ui.label(egui::RichText::new("时间格式为 HH:MM-HH:MM；免打扰可留空。提醒渠道在「通知路由」中设置。").color(dim));
+        });
+    }
+
+    /// 设置中的工作时间：每天是否上班与时段，时间外是否压下提醒、显示「下班了」
+    fn ui_working_hours_settings(&mut self, ui: &mut egui::Ui) {
+        let dim = self.palette.text_dim;
+        let hours = &mut self.working_hours;
+        let before = hours.clone();
+        egui::CollapsingHeader::new("工作时间").show(ui, |ui| {
+            ui.checkbox(&mut hours.enabled, "按星期设定工作时间");
+            ui.add_enabled_ui(hours.enabled, |ui| {
+                egui::Grid::new("working_hours_days").show(ui, |ui| {
+                    for (day, label) in hours.days.iter_mut().zip(crate::planner::WEEKDAY_LABELS) {
+                        ui.checkbox(&mut day.enabled, label);
+                        let valid = crate::planner::parse_range(&day.hours).is_some();
+                        let edit = egui::TextEdit::singleline(&mut day.hours).desired_width(90.0).hint_text("09:00-18:00");
+                        let edit = if valid { edit } else { edit.text_color(ui.visuals().error_fg_color) };
+                        ui.add_enabled(day.enabled, edit);
+                        ui.end_row();
+                    }
+                });
+                ui.checkbox(&mut hours.quiet_after_hours, "工作时间外不发提醒");
+                ui.checkbox(&mut hours.show_off_duty, "工作时间外空闲时显示「下班了」");
+            });
+            ui.label(
+                egui::RichText::new(
+                    "压下的是日程、空闲、护眼、健康提醒与休息警示，阶段结束的通知照常。统计窗口「平均指标」区分工作时间内外的专注。",
+                )
+                .color(dim),
+            );
+        });
+        if self.working_hours != before {
+            self.session_metrics = None;
+        }
+    }
+
+    /// 设置中的分心提醒：开关、提醒前的停留秒数与窗口标题黑名单
+    fn ui_distraction_settings(&mut self, ui: &mut egui::Ui) {
+        let dim = self.palette.text_dim;
+        egui::CollapsingHeader::new("分心提醒").show(ui, |ui| {
+            let settings = &mut self.distraction;
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut settings.enabled, "专注时前台窗口标题含以下关键词超过");
+                ui.add_enabled(settings.enabled, egui::DragValue::new(&mut settings.grace_secs).range(3..=600));
+                ui.label("秒则提醒");
+            });
+            ui.add_enabled_ui(settings.enabled, |ui| {
+                let mut remove = None;
+                ui.horizontal_wrapped(|ui| {
+                    for (i, keyword) in settings.blacklist.iter().enumerate() {
+                        if ui.small_button(format!("{} ✕", keyword)).on_hover_text("删除").clicked() {
+                            remove = Some(i);
+                        }
+                    }
+                });
+                if let Some(i) = remove {
+                    settings.blacklist.remove(i);
+                }
+                ui.horizontal(|ui| {
+                    let edit = ui.add(egui::TextEdit::singleline(&mut self.distraction_input).hint_text("如 YouTube"));
+                    let submitted = edit.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    let keyword = self.distraction_input.trim().to_string();
+                    if (ui.button("添加").clicked() || submitted) && !keyword.is_empty() {
+                        if !settings.blacklist.contains(&keyword) {
+                            settings.blacklist.push(keyword);
+                        }
+                        self.distraction_input.clear();
+                    }
+                });
+            });
+            let note = if crate::distraction::SUPPORTED {
+                "不区分大小写匹配窗口标题；离开该窗口后记录本次分心，统计窗口按关键词汇总。"
+            } else {
+                "当前系统暂不支持检测前台窗口（仅 Windows）"
+            };
+            ui.label(egui::RichText::new(note).color(dim));
+        });
+    }
+
+    /// 设置中的健康提醒：每条的开关、内容、是否在休息开始时提醒、间隔与免打扰时段，可增删
+    fn ui_wellness_settings(&mut self, ui: &mut egui::Ui) {
+        let dim = self.palette.text_dim;
+        egui::CollapsingHeader::new("健康提醒").show(ui, |ui| {
+            let mut remove = None;
+            egui::Grid::new("wellness_reminders").show(ui, |ui| {
+                ui.label("");
+                ui.label("内容");
+                ui.label("休息开始");
+                ui.label("间隔（分钟）");
+                ui.label("免打扰");
+                ui.end_row();
+                for (i, reminder) in self.wellness.reminders.iter_mut().enumerate() {
+                    ui.checkbox(&mut reminder.enabled, "");
+                    ui.add(egui::TextEdit::singleline(&mut reminder.message).desired_width(110.0).hint_text("喝水"));
+                    ui.checkbox(&mut reminder.at_break_start, "");
+                    ui.add(egui::DragValue::new(&mut reminder.interval_minutes).range(0..=480))
+                        .on_hover_text("0 表示不按间隔提醒");
+                    let valid =
+                        crate::wellness::parse_quiet(&reminder.quiet).is_some() || reminder.quiet.trim().is_empty();
+                    let edit = egui::TextEdit::singleline(&mut reminder.quiet).desired_width(90.0).hint_text("22:00-08:00");
+                    ui.add(if valid { edit } else { edit.text_color(ui.visuals().error_fg_color) });
+                    if ui.small_button("✕").on_hover_text("删除").clicked() {
+                        remove = Some(i);
+                    }
+                    ui.end_row();
+                }
+            });
+            if let Some(i) = remove {
+                self.wellness.reminders.remove(i);
+                self.wellness_timer.remove(i);
+            }
+            if ui.button("添加提醒").clicked() {
+                self.wellness.reminders.push(WellnessReminder {
+                    enabled: true,
+                    message: String::new(),
+                    at_break_start: false,
+                    interval_minutes: 60,
+                    quiet: String::new(),
+                });
+            }
+            ui.label(
+                egui::RichText::new("免打扰可跨午夜（如 22:00-08:00），可留空。提醒渠道在「通知路由」中设置。").color(dim),
+            );
+        });
+    }
+
+    /// 设置中的休息守护：连续跳过休息次数与未长休息的专注小时数
+    fn ui_rest_guard_settings(&mut self, ui: &mut egui::Ui) {
+        let guard = &mut self.rest_guard;
+        let before = guard.clone();
+        egui::CollapsingHeader::new("休息守护").show(ui, |ui| {
+            ui.checkbox(&mut guard.enabled, "休息不足时醒目地提醒休息");
+            ui.add_enabled_ui(guard.enabled, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("连续跳过");
+                    ui.add(egui::DragValue::new(&mut guard.max_skipped_breaks).range(0..=10));
+                    ui.label("次休息时提醒");
+                });
+                ui.horizontal(|ui| {
+                    ui.label("累计专注");
+                    ui.add(egui::DragValue::new(&mut guard.max_hours_without_long_break).range(0..=12));
+                    ui.label("小时没有长休息时提醒");
+                });
+            });
+            ui.label(
+                egui::RichText::new(
+                    "设为 0 表示不检查该项。离开专注不到休息时长就开始下一次专注记为跳过，推迟休息不算；统计窗口「平均指标」中可查看跳过次数。",
+                )
+                .color(self.palette.text_dim),
+            );
+        });
+        if self.rest_guard != before {
+            self.check_rest_guard();
+        }
+    }
+
+    /// 每帧（计时推进前）：计时中电脑休眠过时，先计到休眠开始的时刻并暂停；
+    /// 休息中休眠的时间直接计入休息，专注中再按设置计入专注、丢弃、记为暂停，或等待用户选择
+    fn drive_sleep_gap(&mut self) {
+        let Some(gap) = crate::suspend::take_gap() else { return };
+        if !self.pomo.hold_at(gap.from) {
+            return;
+        }
+        if self.pomo.phase != Phase::Focus {
+            tracing::info!("休息中的休眠计入休息");
+            self.pomo.release_hold(true);
+            return;
+        }
+        match crate::policy::get().sleep_action.unwrap_or(self.sleep_action) {
+            SleepAction::Ask => self.sleep_prompt = Some(gap),
+            action => self.resolve_sleep_gap(action),
+        }
+    }
+
+    fn resolve_sleep_gap(&mut self, action: SleepAction) {
+        tracing::info!("专注中的休眠处理为：{}", action.label());
+        match action {
+            SleepAction::Count => self.pomo.release_hold(true),
+            SleepAction::Discard => self.pomo.release_hold(false),
+            // 保持暂停，休眠时间在继续时计入暂停时长
+            SleepAction::Pause | SleepAction::Ask => self.pomo.pauses.count += 1,
+        }
+    }
+
+    /// 询问专注中休眠的时间如何计算；计时在此期间保持暂停
+    fn ui_sleep_prompt(&mut self, ctx: &egui::Context) {
+        let Some(gap) = self.sleep_prompt else { return };
+        let mut chosen = None;
+        egui::Window::new("电脑休眠过")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(format!("专注中电脑休眠了 {} 分钟，这段时间：", (gap.secs() + 59) / 60));
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    for action in [SleepAction::Count, SleepAction::Discard, SleepAction::Pause] {
+                        if ui.button(action.label()).clicked() {
+                            chosen = Some(action);
+                        }
+                    }
+                });
+            });
+        if let Some(action) = chosen {
+            self.sleep_prompt = None;
+            self.resolve_sleep_gap(action);
+        }
+    }
+
+    /// 每帧：首次（解锁后）读取上次意外中断留下的会话，之后专注进行中定时写入会话日志
+    fn drive_session_journal(&mut self) {
+        if !self.session_checked {
+            self.session_checked = true;
+            self.recovered_session = crate::session::load_stale().filter(|s| s.focused_secs > 0);
+            if let Some(session) = &self.recovered_session {
+                tracing::info!("发现意外中断的专注：已专注 {} 秒，最后更新 {}", session.focused_secs, session.updated_at);
+            }
+        }
+        let pomo = &self.pomo;
+        let focusing = pomo.phase == Phase::Focus && pomo.state != TimerState::Idle;
+        self.session_journal.sync(focusing, || ActiveSession {
+            task: self.current_task.clone(),
+            commitment: self.current_commitment.clone(),
+            project_id: self.current_project,
+            completed_pomodoros: pomo.completed_pomodoros,
+            phase_total_secs: pomo.phase_total_secs,
+            focused_secs: (pomo.phase_total_secs - pomo.remaining_secs).max(0) + pomo.overtime_secs,
+            updated_at: beijing_now_rfc3339(),
+        });
+    }
+
+    /// 上次专注意外中断（崩溃、断电）：把已专注的部分记为专注记录，或恢复为暂停继续，或忽略
+    fn ui_recovered_session(&mut self, ctx: &egui::Context) {
+        let Some(session) = self.recovered_session.clone() else { return };
+        let mut close = false;
+        egui::Window::new("上次专注意外中断")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                let task = if session.task.is_empty() { "（未填写任务）" } else { session.task.as_str() };
+                ui.label(format!(
+                    "{}：已专注 {} 分 {} 秒（{} 时最后记录）",
+                    task,
+                    session.focused_secs / 60,
+                    session.focused_secs % 60,
+                    session.updated_at.get(11..16).unwrap_or("")
+                ));
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button("记为专注记录").on_hover_text("按已专注的时长记录，不加番茄数").clicked() {
+                        self.record_recovered_session(&session);
+                        close = true;
+                    }
+                    if ui.button("继续专注").on_hover_text("恢复为暂停状态，点开始继续").clicked() {
+                        self.resume_recovered_session(&session);
+                        close = true;
+                    }
+                    if ui.button("忽略").clicked() {
+                        close = true;
+                    }
+                });
+            });
+        if close {
+            self.recovered_session = None;
+            if self.pomo.phase != Phase::Focus || self.pomo.state == TimerState::Idle {
+                crate::session::clear();
+            }
+        }
+    }
+
+    fn record_recovered_session(&mut self, session: &ActiveSession) {
+        let record = FocusRecord {
+            task: session.task.clone(),
+            duration_secs: session.focused_secs,
+            completed_at: session.updated_at.clone(),
+            completed_pomodoros: session.completed_pomodoros,
+            commitment: session.commitment.clone(),
+            pauses: PauseStats::default(),
+            project_id: session.project_id,
+            overtime_secs: 0,
+        };
+        if let Some(conn) = crate::db::open_and_init().db_err("打开数据库") {
+            crate::db::insert_focus_record(&conn, &record).db_err("记录中断的专注");
+        }
+        // 启动时恢复的暂停计时就是这次专注，已记录后停止，避免重复计时
+        if self.pomo.phase == Phase::Focus && self.pomo.state != TimerState::Idle {
+            self.pomo.stop();
+        }
+        self.load_focus_history_from_db();
+        self.recent_tasks = None;
+        self.invalidate_stats();
+    }
+
+    fn resume_recovered_session(&mut self, session: &ActiveSession) {
+        self.current_task = session.task.clone();
+        self.current_commitment = session.commitment.clone();
+        if self.projects.iter().any(|p| p.id == session.project_id) {
+            self.current_project = session.project_id;
+        }
+        let pomo = &mut self.pomo;
+        pomo.stop();
+        pomo.phase = Phase::Focus;
+        pomo.completed_pomodoros = session.completed_pomodoros;
+        pomo.phase_total_secs = session.phase_total_secs.max(1);
+        pomo.remaining_secs = (session.phase_total_secs - session.focused_secs).max(1);
+        pomo.state = TimerState::Paused;
+        pomo.paused_at = Some(Utc::now());
+    }
+
+    /// 日程提醒：显示到点的日程，空闲时可一键以该任务与项目开始专注
+    fn ui_schedule_prompt(&mut self, ctx: &egui::Context) {
+        let Some(row) = self.schedule_prompt.clone() else { return };
+        let mut close = false;
+        egui::Window::new("日程提醒")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_TOP, [0.0, 12.0])
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "⏰ {} {} ×{}",
+                    row.start_at.get(11..16).unwrap_or(""),
+                    row.task,
+                    row.pomodoros
+                ));
+                ui.horizontal(|ui| {
+                    let idle = self.pomo.state == TimerState::Idle;
+                    let start = ui
+                        .add_enabled(idle, egui::Button::new("开始专注"))
+                        .on_disabled_hover_text("当前计时进行中");
+                    if start.clicked() {
+                        self.current_task = row.task.clone();
+                        if self.projects.iter().any(|p| p.id == row.project_id) {
+                            self.current_project = row.project_id;
+                        }
+                        self.delayed_start = None;
+                        self.pomo.set_phase(Phase::Focus);
+                        self.pomo.start();
+                        close = true;
+                    }
+                    if ui.button("忽略").clicked() {
+                        close = true;
+                    }
+                });
+            });
+        if close {
+            self.schedule_prompt = None;
+        }
+    }
+
+    /// 庆祝弹窗：🎉 放大弹出，可把日程任务标记为完成（记录完成时间，用于统计交付时长），
+    /// 并从今天其余未做完的日程中选下一个任务
+    fn ui_task_celebration(&mut self, ctx: &egui::Context) {
+        let Some(mut view) = self.task_celebration.take() else { return };
+        let mut open = true;
+        let mut close = false;
+        // 入场动画：0.6 秒内从小放大并略微回弹（ease-out-back）
+        let t = (view.shown_at.elapsed().as_secs_f32() / 0.6).min(1.0);
+        let scale = 1.0 + 2.7 * (t - 1.0).powi(3) + 1.7 * (t - 1.0).powi(2);
+        if t < 1.0 {
+            ctx.request_repaint();
+        }
+        egui::Window::new("任务完成")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.vertical_centered(|ui| {
+                    ui.label(egui::RichText::new("🎉").size(48.0 * scale.max(0.05)));
+                    ui.label(format!("「{}」已完成预计的 {} 个番茄", view.task, view.pomodoros));
+                });
+                ui.add_space(4.0);
+                if view.marked {
+                    ui.label(egui::RichText::new("✓ 已标记完成").color(self.palette.text_dim));
+                } else {
+                    ui.horizontal(|ui| {
+                        if ui.button("标记任务完成").clicked() {
+                            let now = beijing_now_rfc3339();
+                            if let Some(conn) = crate::db::open_and_init().db_err("打开数据库") {
+                                crate::db::complete_scheduled_task(&conn, view.schedule_id, &now).db_err("标记日程完成");
+                            }
+                            view.marked = true;
+                            self.schedule_plan = None;
+                        }
+                        if ui.button("还没做完").clicked() {
+                            close = true;
+                        }
+                    });
+                }
+                if !view.next.is_empty() {
+                    ui.separator();
+                    ui.label("接下来做：");
+                    for (task, project_id) in &view.next {
+                        if ui.button(task.as_str()).clicked() {
+                            self.current_task = task.clone();
+                            if self.projects.iter().any(|p| p.id == *project_id) {
+                                self.current_project = *project_id;
+                            }
+                            close = true;
+                        }
+                    }
+                }
+            });
+        if open && !close {
+            self.task_celebration = Some(view);
+        }
+    }
+
+    /// 顶栏「✎」：展开随手记输入条（快捷键默认 Ctrl+I）
+    fn ui_capture_button(&mut self, ui: &mut egui::Ui) {
+        let hint = format!("随手记：把冒出的念头放进收集箱（{}）", ui.ctx().format_shortcut(&self.capture_shortcut));
+        if icon_button_name(ui.add(egui::Button::new("✎").frame(false)), "随手记").on_hover_text(hint).clicked() {
+            self.open_capture();
+        }
+    }
+
+    fn open_capture(&mut self) {
+        if self.capture.is_none() {
+            self.capture = Some(String::new());
+        }
+        self.capture_focus = true;
+    }
+
+    /// 随手记输入条：回车放入收集箱并收起，Esc 或 ✕ 收起（已输入的内容丢弃）
+    fn ui_capture_bar(&mut self, ui: &mut egui::Ui) {
+        let Some(text) = self.capture.as_mut() else { return };
+        let mut close = false;
+        let mut submit = false;
+        ui.horizontal(|ui| {
+            let edit = ui.add(
+                egui::TextEdit::singleline(text)
+                    .hint_text("记下念头，回车放入收集箱")
+                    .desired_width(ui.available_width() - 28.0),
+            );
+            if std::mem::take(&mut self.capture_focus) {
+                edit.request_focus();
+            }
+            if edit.lost_focus() {
+                let (enter, escape) = ui.input(|i| (i.key_pressed(egui::Key::Enter), i.key_pressed(egui::Key::Escape)));
+                submit = enter;
+                close = escape;
+            }
+            if icon_button_name(ui.add(egui::Button::new("✕").frame(false)), "收起").on_hover_text("收起").clicked() {
+                close = true;
+            }
+        });
+        if submit {
+            let item = text.trim().to_string();
+            if item.is_empty() {
+                close = true;
+            } else if crate::db::open_and_init()
+                .and_then(|conn| crate::db::insert_inbox_item(&conn, &item, &beijing_now_rfc3339()))
+                .db_err("放入收集箱")
+                .is_some()
+            {
+                self.inbox_captured += 1;
+                if let Some(view) = self.inbox.as_mut() {
+                    view.rows = None;
+                }
+                close = true;
+            }
+        }
+        if close {
+            self.capture = None;
+        }
+    }
+
+    /// 成就窗口：已解锁的显示解锁日期，未解锁的显示进度
+    fn ui_achievements(&mut self, ctx: &egui::Context) {
+        let Some(mut view) = self.achievements.take() else { return };
+        let mut open = true;
+        let dim = self.palette.text_dim;
+        if view.loaded.is_none() {
+            let loaded = crate::db::open_and_init().and_then(|conn| {
+                Ok((crate::achievements::FocusTotals::load(&conn)?, crate::db::load_achievements(&conn)?))
+            });
+            match loaded {
+                Ok(loaded) => view.loaded = Some(loaded),
+                Err(e) => {
+                    view.loaded = Some(Default::default());
+                    view.message = Some(format!("加载失败：{}", e));
+                }
+            }
+        }
+        egui::Window::new("成就")
+            .open(&mut open)
+            .default_width(360.0)
+            .show(ctx, |ui| {
+                let Some((totals, unlocked)) = view.loaded.as_ref() else { return };
+                ui.label(
+                    egui::RichText::new(format!(
+                        "已解锁 {} / {}",
+                        unlocked.iter().filter(|(key, _)| crate::achievements::find(key).is_some()).count(),
+                        crate::achievements::ACHIEVEMENTS.len()
+                    ))
+                    .color(dim),
+                );
+                ui.add_space(4.0);
+                egui::ScrollArea::vertical().max_height(360.0).show(ui, |ui| {
+                    for achievement in crate::achievements::ACHIEVEMENTS {
+                        let unlocked_at = unlocked.iter().find(|(key, _)| key == achievement.key).map(|(_, at)| at);
+                        ui.horizontal(|ui| {
+                            let icon = egui::RichText::new(achievement.icon).size(20.0);
+                            ui.label(if unlocked_at.is_some() { icon } else { icon.color(dim) });
+                            ui.vertical(|ui| {
+                                let title = egui::RichText::new(achievement.title).strong();
+                                ui.label(if unlocked_at.is_some() { title } else { title.color(dim) });
+                                let status = match unlocked_at {
+                                    Some(at) => format!("{} · {} 解锁", achievement.description, at.get(..10).unwrap_or(at)),
+                                    None => {
+                                        let (value, target) = achievement.progress(totals);
+                                        format!("{} · {} / {}", achievement.description, value.min(target), target)
+                                    }
+                                };
+                                ui.label(egui::RichText::new(status).small().color(dim));
+                            });
+                        });
+                        ui.add_space(2.0);
+                    }
+                });
+                if let Some(msg) = &view.message {
+                    ui.label(egui::RichText::new(msg).color(dim));
+                }
+            });
+        if open {
+            self.achievements = Some(view);
+        }
+    }
+
+    fn ui_side_timer_button(&mut self, ui: &mut egui::Ui) {
+        let button = icon_button_name(ui.add(egui::Button::new("⏲").frame(false)), "副计时");
+        if button.on_hover_text("副计时：与番茄钟并行的倒计时，如「烤箱 12 分钟」").clicked() {
+            self.side_timer_form.get_or_insert_with(|| (String::new(), 10));
+        }
+    }
+
+    /// 副计时输入条：名称与分钟数，开始后替换进行中的副计时；✕ 收起
+    fn ui_side_timer_form(&mut self, ui: &mut egui::Ui) {
+        let Some((label, minutes)) = self.side_timer_form.as_mut() else { return };
+        let mut close = false;
+        let mut start = false;
+        ui.horizontal(|ui| {
+            ui.label("⏲");
+            let edit = ui.add(egui::TextEdit::singleline(label).hint_text("烤箱").desired_width(90.0));
+            start = edit.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            ui.add(egui::DragValue::new(minutes).range(1..=crate::side_timer::MAX_MINUTES).suffix(" 分钟"));
+            start |= ui.button("开始").clicked();
+            if icon_button_name(ui.add(egui::Button::new("✕").frame(false)), "收起").on_hover_text("收起").clicked() {
+                close = true;
+            }
+        });
+        if start {
+            self.side_timer = Some(SideTimer::start(label, *minutes, Utc::now()));
+            close = true;
+        }
+        if close {
+            self.side_timer_form = None;
+        }
+    }
+
+    /// 计时器下方的副计时一行；点击取消（到点后为关闭）
+    fn ui_side_timer_line(&mut self, ui: &mut egui::Ui, size: f32) {
+        let Some(timer) = &self.side_timer else { return };
+        let color = if timer.notified { self.palette.overtime } else { self.palette.text_dim };
+        let text = egui::RichText::new(timer.display(Utc::now())).color(color).size(size).monospace();
+        let hint = if timer.notified { "点击关闭" } else { "点击取消副计时" };
+        if ui.add(egui::Label::new(text).sense(egui::Sense::click())).on_hover_text(hint).clicked() {
+            self.side_timer = None;
+        }
+        ui.add_space(4.0);
+    }
+
+    /// 每帧：副计时到点时按通知路由发出「副计时到点」
+    fn drive_side_timer(&mut self) {
+        let Some(timer) = self.side_timer.as_mut() else { return };
+        if timer.take_due(Utc::now()) {
+            let message = format!("{} 到点了", timer.name());
+            self.notify(NotifyEvent::SideTimer, &message);
+        }
+    }
+
+    /// 收集箱窗口：专注中随手记下的念头 / 待办，可标记已处理、设为当前任务或删除
+    fn ui_inbox(&mut self, ctx: &egui::Context) {
+        let Some(mut view) = self.inbox.take() else { return };
+        let mut open = true;
+        let dim = self.palette.text_dim;
+        if view.rows.is_none() {
+            match crate::db::open_and_init().and_then(|conn| crate::db::load_inbox(&conn, view.include_done)) {
+                Ok(rows) => view.rows = Some(rows),
+                Err(e) => {
+                    view.rows = Some(Vec::new());
+                    view.message = Some(format!("加载失败：{}", e));
+                }
+            }
+        }
+        egui::Window::new("收集箱")
+            .open(&mut open)
+            .default_width(360.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.checkbox(&mut view.include_done, "显示已处理").changed() {
+                        view.rows = None;
+                    }
+                    if ui.button("✎ 记一条").clicked() {
+                        self.open_capture();
+                    }
+                });
+                ui.add_space(4.0);
+                let mut toggle = None;
+                let mut delete_id = None;
+                let idle = self.pomo.state == TimerState::Idle;
+                match view.rows.as_deref() {
+                    Some([]) | None => {
+                        ui.label(egui::RichText::new("收集箱是空的").color(dim));
+                    }
+                    Some(rows) => {
+                        egui::ScrollArea::vertical().max_height(320.0).show(ui, |ui| {
+                            for row in rows {
+                                let done = !row.done_at.is_empty();
+                                ui.horizontal(|ui| {
+                                    let mark = if done { "↺" } else { "✓" };
+                                    let hint = if done { "恢复为未处理" } else { "标记已处理" };
+                                    if ui.small_button(mark).on_hover_text(hint).clicked() {
+                                        toggle = Some((row.id, done));
+                                    }
+                                    ui.label(egui::RichText::new(row.captured_at.get(5..16).unwrap_or("").replace('T', " ")).color(dim));
+                                    let text = egui::RichText::new(&row.text);
+                                    ui.label(if done { text.strikethrough().color(dim) } else { text });
+                                    if !done
+                                        && ui
+                                            .add_enabled(idle, egui::Button::new("设为任务").small())
+                                            .on_hover_text("填入当前任务（计时空闲时）")
+                                            .clicked()
+                                    {
+                                        self.current_task = row.text.clone();
+                                        toggle = Some((row.id, false));
+                                    }
+                                    if ui.small_button("🗑").on_hover_text("删除").clicked() {
+                                        delete_id = Some(row.id);
+                                    }
+                                });
+                            }
+                        });
+                    }
+                }
+                if let Some(msg) = &view.message {
+                    ui.label(egui::RichText::new(msg).color(dim));
+                }
+                if toggle.is_some() || delete_id.is_some() {
+                    let result = crate::db::open_and_init().and_then(|conn| {
+                        if let Some((id, done)) = toggle {
+                            let done_at = if done { String::new() } else { beijing_now_rfc3339() };
+                            crate::db::set_inbox_done(&conn, id, &done_at)?;
+                        }
+                        if let Some(id) = delete_id {
+                            crate::db::delete_inbox_item(&conn, id)?;
+                        }
+                        Ok(())
+                    });
+                    view.message = result.err().map(|e| format!("保存失败：{}", e));
+                    view.rows = None;
+                }
+            });
+        if open {
+            self.inbox = Some(view);
+        }
+    }
+
+    /// 日程窗口：按天列出预定的番茄块，输入 `09:00 写代码 ×2` 添加（所属项目默认为当前项目）
+    fn ui_planner(&mut self, ctx: &egui::Context) {
+        let Some(mut view) = self.planner.take() else { return };
+        let mut open = true;
+        let dim = self.palette.text_dim;
+        if view.rows.is_none() {
+            let from = beijing_day_start_rfc3339(view.day);
+            let to = beijing_day_start_rfc3339(view.day + chrono::Duration::days(1));
+            match crate::db::open_and_init().and_then(|conn| crate::db::load_schedule(&conn, &from, &to)) {
+                Ok(rows) => view.rows = Some(rows),
+                Err(e) => {
+                    view.rows = Some(Vec::new());
+                    view.message = Some(format!("加载失败：{}", e));
+                }
+            }
+        }
+        egui::Window::new("日程 · 预定专注")
+            .open(&mut open)
+            .default_width(360.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button("◀").clicked() {
+                        view.day = view.day.pred_opt().unwrap_or(view.day);
+                        view.rows = None;
+                    }
+                    ui.label(view.day.format("%Y-%m-%d").to_string());
+                    if ui.button("▶").clicked() {
+                        view.day = view.day.succ_opt().unwrap_or(view.day);
+                        view.rows = None;
+                    }
+                    if ui.button("今天").clicked() {
+                        view.day = beijing_now().date_naive();
+                        view.rows = None;
+                    }
+                });
+                ui.add_space(4.0);
+                let mut delete_id = None;
+                let mut complete_id = None;
+                match view.rows.as_deref() {
+                    Some([]) | None => {
+                        ui.label(egui::RichText::new("当天没有日程").color(dim));
+                    }
+                    Some(rows) => {
+                        for row in rows {
+                            ui.horizontal(|ui| {
+                                ui.label(format!(
+                                    "{} {} ×{} · {}",
+                                    row.start_at.get(11..16).unwrap_or(""),
+                                    row.task,
+                                    row.pomodoros,
+                                    self.project_name(row.project_id)
+                                ));
+                                if !row.task_completed_at.is_empty() {
+                                    ui.label(egui::RichText::new("✓ 已完成").color(dim));
+                                } else if ui.small_button("完成").clicked() {
+                                    complete_id = Some(row.id);
+                                }
+                                if ui.small_button("删除").clicked() {
+                                    delete_id = Some(row.id);
+                                }
+                            });
+                        }
+                    }
+                }
+                if let Some(id) = delete_id {
+                    if let Some(conn) = crate::db::open_and_init().db_err("打开数据库") {
+                        crate::db::delete_scheduled_session(&conn, id).db_err("删除日程");
+                    }
+                    view.rows = None;
+                    self.schedule_plan = None;
+                }
+                if let Some(id) = complete_id {
+                    if let Some(conn) = crate::db::open_and_init().db_err("打开数据库") {
+                        crate::db::complete_scheduled_task(&conn, id, &beijing_now_rfc3339()).db_err("标记日程完成");
+                    }
+                    view.rows = None;
+                    self.schedule_plan = None;
+                }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    let resp = ui.add(
+                        egui::TextEdit::singleline(&mut view.entry)
+                            .desired_width(200.0)
+                            .hint_text("09:00 写代码 ×2"),
+                    );
+                    egui::ComboBox::from_id_salt("planner_project")
+                        .selected_text(self.project_name(view.project_id).to_string())
+                        .show_ui(ui, |ui| {
+                            for project in &self.projects {
+                                ui.selectable_value(&mut view.project_id, project.id, project.name.as_str());
+                            }
+                        });
+                    let enter = resp.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    if ui.button("添加").clicked() || enter {
+                        let result = crate::planner::parse_entry(view.day, &view.entry).and_then(|entry| {
+                            crate::db::open_and_init()
+                                .and_then(|conn| {
+                                    crate::db::insert_scheduled_session(
+                                        &conn,
+                                        &entry.start_at,
+                                        &entry.task,
+                                        entry.pomodoros,
+                                        view.project_id,
+                                        &beijing_now_rfc3339(),
+                                    )
+                                })
+                                .map_err(|e| format!("保存失败：{}", e))
+                        });
+                        match result {
+                            Ok(()) => {
+                                view.entry.clear();
+                                view.message = None;
+                                view.rows = None;
+                                self.schedule_plan = None;
+                            }
+                            Err(e) => view.message = Some(e),
+                        }
+                    }
+                });
+                ui.label(
+                    egui::RichText::new(format!(
+                        "到点时提醒（可在通知设置中选择渠道），错过 {} 分钟以上不再提醒。",
+                        crate::planner::REMIND_GRACE_MINUTES
+                    ))
+                    .color(dim),
+                );
+                if let Some(msg) = &view.message {
+                    ui.label(egui::RichText::new(msg).color(dim));
+                }
+            });
+        if open {
+            self.planner = Some(view);
+        }
+    }
+
+    /// 每帧：开启 OBS 输出时把状态写入文件（内容变化才写盘）
+    fn drive_obs_output(&mut self) {
+        if !self.obs.enabled {
+            self.obs_output.reset();
+            self.obs_error = None;
+            return;
+        }
+        let text = self.obs_text();
+        self.obs_error = self.obs_output.update(&self.obs, &text).err().map(|e| format!("写入失败：{}", e));
+    }
+
+    /// 每帧：执行 redtomato:// 链接（启动时带的或其他进程转来的），并把窗口带到前台。
+    /// start 带任务或时长时，仅在计时空闲时切到专注、填入任务并按给定分钟数开始；其余同外部控制命令
+    fn drive_deep_links(&mut self, ctx: &egui::Context) {
+        for link in crate::deeplink::take() {
+            tracing::info!("执行链接：{:?}", link);
+            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+            let Some(command) = link.command else { continue };
+            let custom = link.task.is_some() || link.minutes.is_some();
+            if command == Command::Start && custom {
+                if self.pomo.state != TimerState::Idle {
+                    tracing::info!("计时进行中，忽略链接中的任务与时长");
+                    continue;
+                }
+                if let Some(task) = link.task {
+                    self.current_task = task;
+                }
+                if self.pomo.phase != Phase::Focus {
+                    self.pomo.set_phase(Phase::Focus);
+                }
+                self.pomo.start();
+                if let Some(minutes) = link.minutes {
+                    self.pomo.adjust_remaining(minutes as i64 * 60 - self.pomo.remaining_secs);
+                }
+            } else {
+                self.apply_command(command);
+            }
+        }
+    }
+
+    /// 每帧：开启状态栏输出时写出计时状态（变化才写盘），关闭时删除文件
+    fn drive_bar_output(&mut self) {
+        if !self.bar_output {
+            self.bar.clear();
+            self.bar_error = None;
+            return;
+        }
+        let pomo = &self.pomo;
+        let remaining = if pomo.in_overtime { -pomo.overtime_secs } else { pomo.remaining_secs };
+        let state = BarState {
+            phase: pomo.phase,
+            phase_label: Self::phase_label(pomo.phase).to_string(),
+            task: self.current_task.trim().to_string(),
+            ends_at: (pomo.state == TimerState::Running).then(|| Utc::now().timestamp() + remaining),
+            remaining_secs: remaining,
+            total_secs: pomo.phase_total_secs,
+            paused: pomo.state == TimerState::Paused,
+        };
+        self.bar_error = self.bar.update(state).err().map(|e| format!("写入失败：{}", e));
+    }
+
+    /// 每帧：开启 MQTT 时把计时状态交给后台线程（阶段 / 状态变化或跨分钟时才发布）
+    fn drive_mqtt(&mut self) {
+        let pomo = &self.pomo;
+        let color = self.palette.phase_accent(pomo.phase);
+        let status = MqttStatus {
+            phase: pomo.phase,
+            state: pomo.state,
+            remaining_secs: pomo.remaining_secs,
+            task: self.current_task.trim().to_string(),
+            color: [color.r(), color.g(), color.b()],
+        };
+        self.mqtt_publisher.sync(&self.mqtt, status);
+    }
+
+    /// 每帧：开启 Slack 时，专注中（运行或暂停）设置专注状态，阶段结束或重置后恢复原状态
+    fn drive_slack(&mut self) {
+        let focus = (self.pomo.phase == Phase::Focus && self.pomo.state != TimerState::Idle).then(|| SlackFocus {
+            until: (self.pomo.state == TimerState::Running && !self.pomo.in_overtime)
+                .then(|| Utc::now().timestamp() + self.pomo.remaining_secs),
+        });
+        self.slack_status.sync(&self.slack, focus);
+    }
+
+    /// Discord 上应显示的状态：仅专注中（运行或暂停）有内容，休息与空闲时清除
+    fn presence_activity(&self) -> Option<Activity> {
+        if self.pomo.phase != Phase::Focus || self.pomo.state == TimerState::Idle {
+            return None;
+        }
+        let task = self.current_task.trim();
+        let details = if task.is_empty() {
+            "Focusing".to_string()
+        } else {
+            format!("Focusing: {}", task)
+        };
+        Some(if self.pomo.in_overtime {
+            // 加时为正计时，不给 Discord 倒计时
+            Activity {
+                details,
+                state: Some(if self.pomo.state == TimerState::Paused { "Overtime (paused)" } else { "Overtime" }.to_string()),
+                end: None,
+            }
+        } else if self.pomo.state == TimerState::Running {
+            Activity {
+                details,
+                state: None,
+                end: Some(Utc::now().timestamp() + self.pomo.remaining_secs),
+            }
+        } else {
+            Activity {
+                details,
+                state: Some(format!("Paused ({} left)", self.pomo.remaining_display())),
+                end: None,
+            }
+        })
+    }
+
+    fn phase_label(phase: Phase) -> &'static str {
+        crate::pomodoro::phase_name(phase)
+    }
+}
+
+impl eframe::App for RedTomatoApp {
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        self.palette = crate::theme::apply(ctx, self.theme_mode, &self.accents);
+        let a11y = self.accessibility.effective(self.system_a11y);
+        if self.a11y != Some(a11y) {
+            crate::a11y::apply(ctx, a11y);
+            self.a11y = Some(a11y);
+        }
+        self.intercept_close(ctx);
+        if self.unlock.is_some() {
+            self.ui_unlock(ctx);
+            return;
+        }
+        self.drive_config_file();
+        self.enforce_policy();
+        self.drive_settings_sync(ctx);
+        self.drive_data_sync();
+        self.drive_archive();
+        self.drive_schedule();
+        self.drive_sleep_gap();
+        self.pomo.tick(Utc::now());
+        self.drive_eye_rest();
+        self.drive_wellness();
+        self.drive_rest_guard();
+        self.drive_side_timer();
+        self.drive_distraction();
+        self.drive_metronome();
+        self.drive_media();
+        #[cfg(feature = "http-api")]
+        self.drive_status_server();
+        self.drive_push_actions();
+        self.drive_obs_output();
+        self.drive_bar_output();
+        self.drive_deep_links(ctx);
+        self.drive_tray(frame);
+        self.drive_thumb_bar(ctx, frame);
+        let activity = self.presence_activity();
+        self.presence.sync(&self.discord, activity);
+        self.drive_mqtt();
+        self.drive_slack();
+        self.handle_finished_phase();
+        self.drive_session_journal();
+        self.drive_visual_alarm(ctx);
+        // 长期运行时随时间推移淘汰窗口外的旧记录（常态下只比较最旧一条）
+        self.focus_history.evict(beijing_now());
+        ctx.request_repaint();
+
+        // 应用 pin：默认钉在右上角并置顶（首帧可能无 monitor 信息，会下一帧重试）
+        self.linux_pin.sync(frame, self.pinned);
+        if self.pinned && !self.pin_applied {
+            self.pin_applied = apply_pin(ctx, frame);
+            if self.pin_applied {
+                tracing::info!("已置顶并移到右上角");
+            }
+        }
+
+        // 启动时恢复上次的窗口位置：钉住时在移到右上角之后覆盖；位置已不在屏幕上（如拔掉了副屏）则放弃
+        if let Some(pos) = self.restore_window_pos {
+            if !self.pinned || self.pin_applied {
+                match position_on_screen(ctx, pos) {
+                    Some(true) => {
+                        ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(pos));
+                        self.restore_window_pos = None;
+                    }
+                    Some(false) => {
+                        tracing::info!("上次的窗口位置 ({:.0}, {:.0}) 已不在屏幕上，不恢复", pos.x, pos.y);
+                        self.restore_window_pos = None;
+                    }
+                    // 还没有显示器信息，下一帧再试
+                    None => {}
+                }
+            }
+        }
+        if let Some(rect) = ctx.input(|i| i.viewport().outer_rect) {
+            self.window_pos = Some(rect.min);
+        }
+
+        // 启动时若为完整模式：强制设一次窗口尺寸（上次调整过的或默认尺寸），避免 eframe 持久化恢复成小窗口导致界面被裁切
+        if !self.compact && !self.initial_full_size_applied {
+            ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(self.full_size()));
+            ctx.send_viewport_cmd(egui::ViewportCommand::MinInnerSize(egui::vec2(FULL_MIN_SIZE.0, FULL_MIN_SIZE.1)));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Resizable(true));
+            self.initial_full_size_applied = true;
+        }
+
+        // 紧凑模式（钉到右上角）：固定大小的小窗 + 无标题栏
+        if self.compact && !self.compact_size_applied {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Resizable(false));
+            ctx.send_viewport_cmd(egui::ViewportCommand::MinInnerSize(self.compact_size()));
+            ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(self.compact_size()));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Decorations(false));
+            self.compact_size_applied = true;
+            self.full_no_decorations_applied = false;
+            self.system_menu_removed = false;
+        }
+
+        // 非钉住模式：也去掉系统标题栏，只保留自定义顶栏（钉子+关闭）
+        if !self.compact && !self.full_no_decorations_applied {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Decorations(false));
+            self.full_no_decorations_applied = true;
+        }
+
+        // 从紧凑回到完整模式：恢复窗口尺寸（不恢复系统标题栏）
+        if !self.compact && !self.full_restore_applied {
+            ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(self.full_size()));
+            ctx.send_viewport_cmd(egui::ViewportCommand::MinInnerSize(egui::vec2(FULL_MIN_SIZE.0, FULL_MIN_SIZE.1)));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Resizable(true));
+            self.full_restore_applied = true;
+            self.full_no_decorations_applied = false; // 下一帧会再次发 Decorations(false)
+            self.system_menu_removed = false;
+        }
+
+        // 记下完整模式下调整后的窗口尺寸；小于最小尺寸的是切换模式途中的紧凑尺寸，不记
+        if !self.compact && self.full_restore_applied {
+            if let Some(rect) = ctx.input(|i| i.viewport().inner_rect) {
+                let size = rect.size();
+                if size.x >= FULL_MIN_SIZE.0 && size.y >= FULL_MIN_SIZE.1 && Some(size) != self.full_window_size {
+                    self.full_window_size = Some(size).filter(|s| *s != self.default_full_size());
+                }
+            }
+        }
+
+        // 非紧凑模式下去掉标题栏左上角系统菜单（仅 Windows，只执行一次）
+        if !self.compact && !self.system_menu_removed && try_remove_system_menu(frame) {
+            self.system_menu_removed = true;
+        }
+
+        // 随手记：任何模式下按快捷键展开输入条
+        if ctx.input_mut(|i| i.consume_shortcut(&self.capture_shortcut)) {
+            self.open_capture();
+        }
+        if self.compact {
+            self.ui_compact(ctx);
+        } else {
+            self.ui_full(ctx);
+        }
+
+        // 关于窗口（点击导航栏「关于」后展示）
+        if self.show_about {
+            self.ui_about(ctx);
+        }
+        // 统计窗口：按时间列出做了哪些任务、专注时长
+        if self.show_statistics {
+            self.ui_statistics(ctx);
+        }
+        // 项目管理窗口
+        if self.projects_view.is_some() {
+            self.ui_projects(ctx);
+        }
+        // 数据清除窗口
+        if self.purge_view.is_some() {
+            self.ui_purge(ctx);
+        }
+        // 设置窗口：字体等
+        if self.show_settings {
+            self.ui_settings(ctx);
+        }
+        // 长休息屏保：全屏无边框视口显示时钟与休息倒计时，任意输入关闭
+        let in_long_break = self.pomo.phase == Phase::LongBreak && self.pomo.state == TimerState::Running;
+        if !in_long_break {
+            self.screensaver_dismissed = false;
+        } else if self.break_screensaver && !self.screensaver_dismissed {
+            self.ui_break_screensaver(ctx);
+        }
+        // 专注横幅：专注计时中在屏幕顶部显示请勿打扰提示
+        if self.focus_banner && self.pomo.phase == Phase::Focus && self.pomo.state == TimerState::Running {
+            self.ui_focus_banner(ctx);
+        }
+        // 分心提醒：前台窗口命中黑名单超过设定秒数时在屏幕顶部居中提示
+        let nudge = self
+            .distraction_monitor
+            .nudge(&self.distraction, std::time::Instant::now())
+            .map(|(keyword, secs)| (keyword.to_string(), secs));
+        if let Some((keyword, secs)) = nudge {
+            self.ui_distraction_nudge(ctx, &keyword, secs);
+        }
+        // 时间审计窗口：计划 / 实际专注 / 日历 三条时间轴
+        if self.audit.is_some() {
+            self.ui_audit(ctx);
+        }
+        // 周报窗口：最近 7 天汇总
+        if self.review.is_some() {
+            self.ui_weekly_review(ctx);
+        }
+        // 每日回顾：当天汇总 + 轮换反思提问（微日志）
+        if self.daily_review.is_some() {
+            self.ui_daily_review(ctx);
+        }
+        // 日程：按天预定番茄块；到点提醒可一键开始
+        if self.planner.is_some() {
+            self.ui_planner(ctx);
+        }
+        if self.inbox.is_some() {
+            self.ui_inbox(ctx);
+        }
+        if self.achievements.is_some() {
+            self.ui_achievements(ctx);
+        }
+        if self.schedule_prompt.is_some() {
+            self.ui_schedule_prompt(ctx);
+        }
+        if self.recovered_session.is_some() {
+            self.ui_recovered_session(ctx);
+        }
+        if self.sleep_prompt.is_some() {
+            self.ui_sleep_prompt(ctx);
+        }
+        if self.task_celebration.is_some() {
+            self.ui_task_celebration(ctx);
+        }
+        self.ui_eye_rest_overlay(ctx);
+        if self.confirm_quit {
+            self.ui_confirm_quit(ctx);
+        }
+        // 应用内提示：路由到「提示」渠道的通知，浮在窗口底部数秒后消失
+        self.ui_toasts(ctx);
+        self.ui_undo_bar(ctx);
+        self.ui_error_banner(ctx);
+        self.save_on_transition(frame);
+    }
+
+    /// 正常退出：界面状态已保存（运行中的计时下次恢复为暂停），不需要会话日志
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        crate::session::clear();
+    }
+
+    fn auto_save_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.autosave_secs)
+    }
+
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        let p = PersistedState {
+            current_task: self.current_task.clone(),
+            current_commitment: self.current_commitment.clone(),
+            current_project: Some(self.current_project),
+            side_timer: self.side_timer.clone(),
+            phase: phase_to_str(self.pomo.phase).to_string(),
+            state: state_to_str(self.pomo.state).to_string(),
+            remaining_secs: self.pomo.remaining_secs,
+            phase_total_secs: self.pomo.phase_total_secs,
+            completed_pomodoros: self.pomo.completed_pomodoros,
+            overtime_secs: self.pomo.in_overtime.then_some(self.pomo.overtime_secs),
+            snoozes: self.pomo.snoozes,
+            snoozed_break: self.pomo.snoozed_break.map(|p| phase_to_str(p).to_string()),
+            pauses: self.pomo.pause_stats(Utc::now()),
+            font: self.font_choice.clone(),
+            custom_font_path: self.custom_font_path.clone(),
+            theme: Some(crate::theme::mode_to_str(self.theme_mode).to_string()),
+            accents: self.accents,
+            config: Some(self.pomo.config.clone()),
+            compact: self.compact,
+            pinned: self.pinned,
+            window_pos: self.window_pos.map(|p| [p.x, p.y]),
+            full_window_size: self.full_window_size.map(|s| [s.x, s.y]),
+            routine: self.pomo.routine.clone(),
+            routine_step: self.pomo.step,
+            routines: self.routines.clone(),
+            break_screensaver: self.break_screensaver,
+            focus_banner: self.focus_banner,
+            timer_layout: self.timer_layout,
+            archive_after_months: self.archive_after_months,
+            autosave_secs: Some(self.autosave_secs),
+            profiles: self.profiles_snapshot(),
+            sync: self.sync_endpoint.clone(),
+            data_sync: self.data_sync.clone(),
+            idle_nudge: self.idle_nudge.clone(),
+            working_hours: self.working_hours.clone(),
+            eye_rest: self.eye_rest.clone(),
+            quotes: self.quotes.clone(),
+            breathing: self.breathing.clone(),
+            wellness: self.wellness.clone(),
+            rest_guard: self.rest_guard.clone(),
+            distraction: self.distraction.clone(),
+            tick_sound: self.tick_sound.clone(),
+            media: self.media.clone(),
+            notifications: self.notifications.clone(),
+            daily_note: self.daily_note.clone(),
+            discord: self.discord.clone(),
+            mqtt: self.mqtt.clone(),
+            slack: self.slack.clone(),
+            #[cfg(feature = "http-api")]
+            http_api: self.http_api.clone(),
+            obs: self.obs.clone(),
+            accessibility: self.accessibility,
+            close_action: Some(close_action_to_str(self.close_action).to_string()),
+            git_suggest: self.git_suggest.clone(),
+            sleep_action: self.sleep_action,
+            tray_timer: self.tray_timer,
+            bar_output: self.bar_output,
+        };
+        if let Ok(json) = serde_json::to_string(&p) {
+            storage.set_string(STORAGE_KEY_STATE, json);
+        }
+    }
+}
+
+impl RedTomatoApp {
+    /// 关于窗口
+    fn ui_about(&mut self, ctx: &egui::Context) {
+        let palette = self.palette;
+        egui::Window::new("关于")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.vertical_centered(|ui| {
+                    ui.add_space(12.0);
+                    ui.label(
+                        egui::RichText::new("Red Tomato 红番茄")
+                            .size(18.0)
+                            .color(palette.text_dim),
+                    );
+                    ui.label(
+                        egui::RichText::new("科学工作法")
+                            .size(14.0)
+                            .color(palette.text_dim),
+                    );
+                    ui.add_space(8.0);
+                    let db_path = crate::db::db_path();
+                    let data_label = if crate::db::portable_dir().is_some() {
+                        "数据 (SQLite，便携模式)："
+                    } else {
+                        "数据 (SQLite)："
+                    };
+                    ui.label(
+                        egui::RichText::new(data_label)
+                            .size(12.0)
+                            .color(palette.text_dim),
+                    );
+                    ui.label(
+                        egui::RichText::new(db_path.to_string_lossy().as_ref())
+                            .size(11.0)
+                            .color(palette.text_dim),
+                    );
+                    let loaded = crate::policy::loaded();
+                    let policy_note = match (&loaded.path, &loaded.error) {
+                        (Some(path), _) => Some(format!("已应用部署策略：{}", path.display())),
+                        (None, Some(error)) => Some(error.clone()),
+                        (None, None) => None,
+                    };
+                    if let Some(note) = policy_note {
+                        ui.add_space(4.0);
+                        ui.label(egui::RichText::new(note).size(11.0).color(palette.text_dim));
+                    }
+                    ui.add_space(4.0);
+                    if ui.link("打开日志").on_hover_text(crate::logging::log_dir().display().to_string()).clicked() {
+                        crate::logging::open_log_dir().log_err("打开日志文件夹");
+                    }
+                    if self.data_sync_enabled() {
+                        let status = if self.data_sync_status.is_empty() {
+                            "数据同步：等待首次同步".to_string()
+                        } else {
+                            format!("数据同步：{}", self.data_sync_status)
+                        };
+                        ui.add_space(4.0);
+                        ui.label(egui::RichText::new(status).size(11.0).color(palette.text_dim));
+                    }
+                    ui.add_space(16.0);
+                    if ui.button("确定").clicked() {
+                        self.show_about = false;
+                    }
+                });
+            });
+    }
+
+    /// 完整模式窗口尺寸：用户调整过的尺寸，否则为默认尺寸
+    fn full_size(&self) -> egui::Vec2 {
+        self.full_window_size.unwrap_or_else(|| self.default_full_size())
+    }
+
+    /// 完整模式默认窗口尺寸：圆环布局时加高
+    fn default_full_size(&self) -> egui::Vec2 {
+        let extra = if self.timer_layout == TimerLayout::Ring { RING_EXTRA_HEIGHT.0 } else { 0.0 };
+        egui::vec2(FULL_SIZE.0, FULL_SIZE.1 + extra)
+    }
+
+    /// 紧凑模式窗口尺寸：圆环布局时加高
+    fn compact_size(&self) -> egui::Vec2 {
+        let extra = if self.timer_layout == TimerLayout::Ring { RING_EXTRA_HEIGHT.1 } else { 0.0 };
+        egui::vec2(COMPACT_WIDTH, COMPACT_HEIGHT + extra)
+    }
+
+    /// 当前阶段的强调色，阶段切换时平滑过渡（减少动态效果时立即切换）
+    fn animated_accent(&self, ctx: &egui::Context) -> egui::Color32 {
+        let reduce_motion = self.a11y.is_some_and(|a| a.reduce_motion);
+        let target = self.palette.phase_accent(self.pomo.phase);
+        crate::motion::animated_color(ctx, egui::Id::new("phase_accent"), target, reduce_motion)
+    }
+
+    /// 计时运行中在大计时器背后画起伏的光晕；slot 为画计时器之前预留的位置，使光晕位于文字下面
+    fn paint_timer_glow(&self, ui: &egui::Ui, slot: egui::layers::ShapeIdx, rect: egui::Rect, accent: egui::Color32) {
+        let a11y = self.a11y.unwrap_or_default();
+        if a11y.reduce_transparency {
+            return;
+        }
+        let running = self.pomo.state == TimerState::Running;
+        let level = crate::motion::glow_level(running, a11y.reduce_motion, ui.input(|i| i.time));
+        let color = if self.pomo.in_overtime { self.palette.overtime } else { accent };
+        let radius = rect.width().max(rect.height()) * 0.7;
+        ui.painter().set(slot, crate::motion::glow_shape(rect.center(), radius, color, level));
+    }
+
+    /// 完整模式的大计时器：暂停时点击进入编辑（输入「分:秒」或「分」，回车确认、Esc 取消）；字号按 scale 随窗口缩放
+    fn ui_timer_text(&mut self, ui: &mut egui::Ui, scale: f32) {
+        if self.pomo.state != TimerState::Paused {
+            self.timer_edit = None;
+        }
+        if let Some(buf) = self.timer_edit.as_mut() {
+            let resp = ui.add(
+                egui::TextEdit::singleline(buf)
+                    .font(egui::FontId::monospace(40.0 * scale))
+                    .desired_width(160.0 * scale)
+                    .horizontal_align(egui::Align::Center),
+            );
+            resp.request_focus();
+            let (enter, escape) = ui.input(|i| (i.key_pressed(egui::Key::Enter), i.key_pressed(egui::Key::Escape)));
+            if escape {
+                self.timer_edit = None;
+            } else if enter || resp.lost_focus() {
+                if let Some(secs) = parse_duration_input(buf) {
+                    self.pomo.adjust_remaining(secs - self.pomo.remaining_secs);
+                }
+                self.timer_edit = None;
+            }
+            return;
+        }
+        if self.pomo.state == TimerState::Idle && self.working_hours.off_duty(beijing_now().naive_local()) {
+            ui.label(egui::RichText::new("🌙 下班了").color(self.palette.text_dim).size(44.0 * scale))
+                .on_hover_text("现在是工作时间外；点「开始」仍可照常计时");
+            return;
+        }
+        // 加时中不可编辑剩余时间
+        let paused = self.pomo.state == TimerState::Paused && !self.pomo.in_overtime;
+        let color = if self.pomo.in_overtime { self.palette.overtime } else { self.palette.text };
+        let label = egui::Label::new(
+            egui::RichText::new(self.pomo.remaining_display())
+                .color(color)
+                .size(56.0 * scale)
+                .monospace(),
+        )
+        .sense(if paused { egui::Sense::click() } else { egui::Sense::hover() });
+        let resp = ui.add(label);
+        if paused && resp.on_hover_text("点击修改剩余时间").clicked() {
+            self.timer_edit = Some(self.pomo.remaining_display());
+        }
+    }
+
+    /// 「专注到 11:30」：空闲时输入时刻开始一次专注到该时刻的番茄；计时中显示截止时刻
+    fn ui_focus_until(&mut self, ui: &mut egui::Ui) {
+        let dim = self.palette.text_dim;
+        if let Some(deadline) = self.pomo.deadline {
+            let at = deadline.with_timezone(beijing_now().offset());
+            ui.label(egui::RichText::new(format!("专注到 {}", at.format("%H:%M"))).color(dim));
+            return;
+        }
+        if self.pomo.state != TimerState::Idle {
+            return;
+        }
+        ui.add_space(6.0);
+        ui.horizontal(|ui| {
+            ui.label("专注到");
+            let resp = ui.add(egui::TextEdit::singleline(&mut self.focus_until).desired_width(56.0).hint_text("11:30"));
+            let enter = resp.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            if ui.button("开始").on_hover_text("按到该时刻的时长开始一次专注，已过的时刻视为明天").clicked() || enter {
+                match crate::pomodoro::focus_deadline(&self.focus_until, beijing_now()) {
+                    Ok(deadline) => {
+                        self.pomo.start_until(deadline, Utc::now());
+                        self.focus_until_error = None;
+                    }
+                    Err(e) => self.focus_until_error = Some(e),
+                }
+            }
+        });
+        if let Some(e) = &self.focus_until_error {
+            ui.label(egui::RichText::new(e).color(ui.visuals().error_fg_color));
+        }
+    }
+
+    /// 从 SQLite 加载某天的计划块、专注记录、日历事件并生成审计结果
+    fn load_day_audit(day: NaiveDate) -> Result<(DayAudit, Vec<crate::db::SpanRow>), rusqlite::Error> {
+        let (lo, hi) = crate::audit::day_bounds(day);
+        let (from, to) = (beijing_rfc3339(lo), beijing_rfc3339(hi));
+        let conn = crate::db::open_and_init()?;
+        let plan_rows = crate::db::load_plan_blocks(&conn, &from, &to)?;
+        let calendar_rows = crate::db::load_calendar_events(&conn, &from, &to)?;
+        let focus_rows = crate::db::load_focus_records_since(&conn, &from, 0)?;
+        let to_spans = |rows: &[crate::db::SpanRow]| -> Vec<crate::audit::Span> {
+            rows.iter()
+                .filter_map(|r| crate::audit::span_from_rfc3339(&r.start_at, &r.end_at, &r.title))
+                .collect()
+        };
+        let records: Vec<FocusRecord> = focus_rows.into_iter().map(FocusRecord::from).collect();
+        let audit = crate::audit::build_day_audit(
+            day,
+            &to_spans(&plan_rows),
+            &crate::audit::tracked_spans(&records),
+            &to_spans(&calendar_rows),
+        );
+        Ok((audit, plan_rows))
+    }
+
+    /// 时间审计窗口：按天对照计划、实际专注与日历事件，标出计划外与计划内未执行的时段
+    fn ui_audit(&mut self, ctx: &egui::Context) {
+        let palette = self.palette;
+        let mut open = true;
+        let Some(view) = self.audit.as_mut() else { return };
+        if view.loaded.is_none() {
+            match Self::load_day_audit(view.day) {
+                Ok(loaded) => view.loaded = Some(loaded),
+                Err(e) => view.message = Some(format!("加载失败：{}", e)),
+            }
+        }
+        egui::Window::new("时间审计 · 我的一天去哪了")
+            .open(&mut open)
+            .default_width(520.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button("◀").clicked() {
+                        view.day = view.day.pred_opt().unwrap_or(view.day);
+                        view.loaded = None;
+                    }
+                    ui.label(view.day.format("%Y-%m-%d").to_string());
+                    if ui.button("▶").clicked() {
+                        view.day = view.day.succ_opt().unwrap_or(view.day);
+                        view.loaded = None;
+                    }
+                    if ui.button("今天").clicked() {
+                        view.day = beijing_now().date_naive();
+                        view.loaded = None;
+                    }
+                });
+                ui.add_space(6.0);
+
+                let mut delete_id = None;
+                if let Some((audit, plan_rows)) = view.loaded.as_ref() {
+                    paint_audit_lanes(ui, audit, &palette);
+                    ui.add_space(6.0);
+                    let mins = |spans: &[crate::audit::Span]| DayAudit::total(spans) / 60;
+                    ui.label(format!(
+                        "计划 {} 分钟 · 专注 {} 分钟 · 日历 {} 分钟",
+                        mins(&audit.planned),
+                        mins(&audit.tracked),
+                        mins(&audit.calendar)
+                    ));
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "计划外专注 {} 分钟 · 计划内未执行 {} 分钟",
+                            mins(&audit.unplanned),
+                            mins(&audit.untracked)
+                        ))
+                        .color(palette.text_dim),
+                    );
+                    let hm = |ts: i64| {
+                        DateTime::from_timestamp(ts, 0)
+                            .map(|t| t.with_timezone(&beijing()).format("%H:%M").to_string())
+                            .unwrap_or_default()
+                    };
+                    if !audit.untracked.is_empty() || !audit.unplanned.is_empty() {
+                        ui.add_space(4.0);
+                        egui::ScrollArea::vertical().id_salt("audit_gaps").max_height(100.0).show(ui, |ui| {
+                            for span in &audit.untracked {
+                                ui.label(format!("未执行 {}–{} {}", hm(span.start), hm(span.end), span.label));
+                            }
+                            for span in &audit.unplanned {
+                                let task = if span.label.is_empty() { "(无任务)" } else { span.label.as_str() };
+                                ui.label(format!("计划外 {}–{} {}", hm(span.start), hm(span.end), task));
+                            }
+                        });
+                    }
+                    ui.separator();
+                    ui.label("当天计划：");
+                    for row in plan_rows {
+                        ui.horizontal(|ui| {
+                            let start = row.start_at.get(11..16).unwrap_or("");
+                            let end = row.end_at.get(11..16).unwrap_or("");
+                            ui.label(format!("{}–{} {}", start, end, row.title));
+                            if ui.small_button("删除").clicked() {
+                                delete_id = Some(row.id);
+                            }
+                        });
+                    }
+                }
+                if let Some(id) = delete_id {
+                    if let Some(conn) = crate::db::open_and_init().db_err("打开数据库") {
+                        crate::db::delete_plan_block(&conn, id).db_err("删除计划块");
+                    }
+                    view.loaded = None;
+                }
+
+                ui.horizontal(|ui| {
+                    ui.add(egui::TextEdit::singleline(&mut view.plan_start).desired_width(44.0));
+                    ui.label("–");
+                    ui.add(egui::TextEdit::singleline(&mut view.plan_end).desired_width(44.0));
+                    ui.add(
+                        egui::TextEdit::singleline(&mut view.plan_title)
+                            .desired_width(180.0)
+                            .hint_text("计划做什么…"),
+                    );
+                    if ui.button("添加计划").clicked() {
+                        let start = crate::audit::day_time_rfc3339(view.day, &view.plan_start);
+                        let end = crate::audit::day_time_rfc3339(view.day, &view.plan_end);
+                        match (start, end) {
+                            (Some(start), Some(end)) if end > start => {
+                                if let Some(conn) = crate::db::open_and_init().db_err("打开数据库") {
+                                    crate::db::insert_plan_block(&conn, view.plan_title.trim(), &start, &end)
+                                        .db_err("添加计划块");
+                                }
+                                view.plan_title.clear();
+                                view.message = None;
+                                view.loaded = None;
+                            }
+                            _ => view.message = Some("时间格式应为 HH:MM，且结束晚于开始".to_string()),
+                        }
+                    }
+                });
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label("导入日历 (.ics)：");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut view.ics_path)
+                            .desired_width(240.0)
+                            .hint_text("文件路径"),
+                    );
+                    if ui.button("导入").clicked() {
+                        view.message = Some(match std::fs::read_to_string(view.ics_path.trim()) {
+                            Ok(text) => {
+                                let events = crate::audit::parse_ics(&text);
+                                match crate::db::open_and_init() {
+                                    Ok(conn) => {
+                                        let n = events
+                                            .iter()
+                                            .filter(|e| {
+                                                crate::db::upsert_calendar_event(&conn, &e.uid, &e.summary, &e.start_at, &e.end_at)
+                                                    .is_ok()
+                                            })
+                                            .count();
+                                        view.loaded = None;
+                                        format!("已导入 {} 个日历事件", n)
+                                    }
+                                    Err(e) => format!("打开数据库失败：{}", e),
+                                }
+                            }
+                            Err(e) => format!("读取文件失败：{}", e),
+                        });
+                    }
+                });
+                if let Some(msg) = &view.message {
+                    ui.label(egui::RichText::new(msg).color(palette.text_dim));
+                }
+            });
+        if !open {
+            self.audit = None;
+        }
+    }
+
+    /// 长休息屏保：无边框全屏、置顶的独立视口，显示当前时间与休息剩余时间。
+    /// 按键、点击或明显移动鼠标即关闭（本次长休息内不再弹出）。
+    /// egui 无法枚举显示器，只覆盖窗口当前所在的显示器。
+    fn ui_break_screensaver(&mut self, ctx: &egui::Context) {
+        let palette = self.palette;
+        let remaining = self.pomo.remaining_display();
+        let mut dismissed = false;
+        ctx.show_viewport_immediate(
+            egui::ViewportId::from_hash_of("break_screensaver"),
+            egui::ViewportBuilder::default()
+                .with_title("休息一下")
+                .with_decorations(false)
+                .with_fullscreen(true)
+                .with_taskbar(false)
+                .with_always_on_top(),
+            |ctx, _class| {
+                dismissed = ctx.input(|i| {
+                    i.viewport().close_requested()
+                        || i.events.iter().any(|e| {
+                            matches!(
+                                e,
+                                egui::Event::Key { pressed: true, .. }
+                                    | egui::Event::PointerButton { pressed: true, .. }
+                            )
+                        })
+                        || i.pointer.delta().length() > 4.0
+                });
+                egui::CentralPanel::default()
+                    .frame(egui::Frame::NONE.fill(palette.bg))
+                    .show(ctx, |ui| {
+                        ui.vertical_centered(|ui| {
+                            ui.add_space((ui.available_height() * 0.5 - 120.0).at_least(0.0));
+                            ui.label(
+                                egui::RichText::new(chrono::Local::now().format("%H:%M").to_string())
+                                    .color(palette.text)
+                                    .size(120.0)
+                                    .monospace(),
+                            );
+                            ui.add_space(12.0);
+                            ui.label(
+                                egui::RichText::new(format!("长休息 · 剩余 {}", remaining))
+                                    .color(palette.long_break)
+                                    .size(28.0),
+                            );
+                            ui.add_space(24.0);
+                            ui.label(
+                                egui::RichText::new("离开屏幕，活动一下。按任意键返回")
+                                    .color(palette.text_dim)
+                                    .size(16.0),
+                            );
+                        });
+                    });
+            },
+        );
+        if dismissed {
+            self.screensaver_dismissed = true;
+        }
+    }
+
+    /// 专注横幅：贴屏幕顶边、置顶、鼠标穿透的细长无边框视口，显示「专注中 · 请勿打扰」。
+    /// 与屏保相同，只覆盖窗口当前所在的显示器；降低透明度时改为不透明底色。
+    fn ui_focus_banner(&mut self, ctx: &egui::Context) {
+        let palette = self.palette;
+        let opaque = self.a11y.is_some_and(|a| a.reduce_transparency);
+        let width = ctx
+            .input(|i| i.viewport().monitor_size)
+            .map_or(FOCUS_BANNER_FALLBACK_WIDTH, |size| size.x);
+        ctx.show_viewport_immediate(
+            egui::ViewportId::from_hash_of("focus_banner"),
+            egui::ViewportBuilder::default()
+                .with_title("专注中")
+                .with_decorations(false)
+                .with_taskbar(false)
+                .with_resizable(false)
+                .with_transparent(!opaque)
+                .with_mouse_passthrough(true)
+                .with_always_on_top()
+                .with_position(egui::pos2(0.0, 0.0))
+                .with_inner_size(egui::vec2(width, FOCUS_BANNER_HEIGHT)),
+            |ctx, _class| {
+                let fill = if opaque {
+                    palette.focus
+                } else {
+                    palette.focus.gamma_multiply(0.55)
+                };
+                egui::CentralPanel::default()
+                    .frame(egui::Frame::NONE.fill(fill))
+                    .show(ctx, |ui| {
+                        ui.centered_and_justified(|ui| {
+                            ui.label(
+                                egui::RichText::new("专注中 · 请勿打扰")
+                                    .color(egui::Color32::WHITE)
+                                    .size(14.0),
+                            );
+                        });
+                    });
+            },
+        );
+    }
+
+    /// 分心提醒：屏幕顶部居中的置顶小条，不抢焦点、鼠标可穿透
+    fn ui_distraction_nudge(&mut self, ctx: &egui::Context, keyword: &str, secs: u64) {
+        let palette = self.palette;
+        let opaque = self.a11y.is_some_and(|a| a.reduce_transparency);
+        let screen_width = ctx
+            .input(|i| i.viewport().monitor_size)
+            .map_or(FOCUS_BANNER_FALLBACK_WIDTH, |size| size.x);
+        let task = self.current_task.trim();
+        let back = if task.is_empty() { "回到专注吧".to_string() } else { format!("回到「{}」吧", task) };
+        let text = format!("「{}」已经 {} 秒了 · {}", keyword, secs, back);
+        let offset = if self.focus_banner { FOCUS_BANNER_HEIGHT } else { 0.0 };
+        ctx.show_viewport_immediate(
+            egui::ViewportId::from_hash_of("distraction_nudge"),
+            egui::ViewportBuilder::default()
+                .with_title("分心提醒")
+                .with_decorations(false)
+                .with_taskbar(false)
+                .with_resizable(false)
+                .with_transparent(!opaque)
+                .with_mouse_passthrough(true)
+                .with_always_on_top()
+                .with_position(egui::pos2((screen_width - DISTRACTION_NUDGE_SIZE.0) * 0.5, offset + 8.0))
+                .with_inner_size(egui::vec2(DISTRACTION_NUDGE_SIZE.0, DISTRACTION_NUDGE_SIZE.1)),
+            |ctx, _class| {
+                let fill = if opaque { palette.bg } else { palette.bg.gamma_multiply(0.85) };
+                egui::CentralPanel::default()
+                    .frame(egui::Frame::NONE.fill(fill).stroke(egui::Stroke::new(1.0, palette.focus)))
+                    .show(ctx, |ui| {
+                        ui.centered_and_justified(|ui| {
+                            ui.label(egui::RichText::new(&text).color(palette.text).size(14.0));
+                        });
+                    });
+            },
+        );
+        ctx.request_repaint_after(std::time::Duration::from_millis(500));
+    }
+
+    /// 任务输入框旁的色块：为当前任务选颜色（未输入任务时不显示）
+    fn ui_task_color_menu(&mut self, ui: &mut egui::Ui) {
+        let task = self.current_task.trim().to_string();
+        if task.is_empty() {
+            return;
+        }
+        let current = self.task_colors.color(&task, Some(self.current_project));
+        let Some(picked) = crate::labels::color_menu(ui, current, "任务颜色") else { return };
+        let hex = picked.map(crate::labels::to_hex).unwrap_or_default();
+        if crate::db::open_and_init().and_then(|conn| crate::db::set_task_color(&conn, &task, &hex)).db_err("保存任务颜色").is_some() {
+            self.task_colors.set_task(&task, picked);
+        }
+    }
+
+    /// 任务输入框旁的下拉：最近做过的任务（按输入内容过滤），点一下填入
+    fn ui_recent_tasks_menu(&mut self, ui: &mut egui::Ui) {
+        ui.menu_button("▾", |ui| {
+            let recent = self.recent_tasks.get_or_insert_with(|| {
+                crate::db::open_and_init()
+                    .and_then(|conn| crate::db::recent_tasks(&conn, RECENT_TASKS_LIMIT))
+                    .unwrap_or_default()
+            });
+            let filter = self.current_task.trim().to_lowercase();
+            let mut picked = None;
+            let mut shown = 0;
+            egui::ScrollArea::vertical().max_height(260.0).show(ui, |ui| {
+                for row in recent.iter().filter(|r| filter.is_empty() || r.task.to_lowercase().contains(&filter)) {
+                    shown += 1;
+                    let day = row.last_at.get(5..10).unwrap_or("");
+                    let resp = ui
+                        .button(row.task.as_str())
+                        .on_hover_text(format!("最近 {} · 共 {} 个番茄", day, row.sessions));
+                    if resp.clicked() {
+                        picked = Some((row.task.clone(), row.project_id));
+                    }
+                }
+            });
+            if shown == 0 {
+                ui.label(egui::RichText::new("没有匹配的历史任务").color(self.palette.text_dim));
+            }
+            if let Some((task, project)) = picked {
+                self.current_task = task;
+                // 沿用该任务上次所属的项目（项目已删除则保持当前选择）
+                if self.projects.iter().any(|p| p.id == project) {
+                    self.current_project = project;
+                }
+                ui.close();
+            }
+        })
+        .response
+        .on_hover_text("最近做过的任务");
+    }
+
+    /// 开始专注前：精力自评（1–5）与据此给出的时长建议，建议热身时可直接开始短专注
+    fn ui_energy_row(&mut self, ui: &mut egui::Ui) {
+        use crate::energy::{MAX_ENERGY, MIN_ENERGY, Suggestion, WARMUP_SECS};
+        let recent = self.energy_recent.get_or_insert_with(|| {
+            let since = (beijing_now() - chrono::Duration::hours(crate::energy::RECENT_HOURS)).to_rfc3339();
+            crate::db::open_and_init()
+                .and_then(|conn| crate::db::load_energy_ratings_since(&conn, &since, crate::energy::RECENT_COUNT))
+                .map(|rows| rows.into_iter().map(|(_, energy)| energy).collect())
+                .unwrap_or_default()
+        });
+        let latest = recent.first().copied();
+        let recommendation = crate::energy::recommend(recent, self.pomo.config.focus_secs);
+        ui.horizontal(|ui| {
+            ui.label("精力：");
+            for energy in MIN_ENERGY..=MAX_ENERGY {
+                if ui.selectable_label(latest == Some(energy), energy.to_string()).clicked() {
+                    if let Some(conn) = crate::db::open_and_init().db_err("打开数据库") {
+                        crate::db::insert_energy_rating(&conn, &beijing_now_rfc3339(), energy).db_err("记录精力自评");
+                    }
+                    self.energy_recent = None;
+                }
+            }
+        });
+        match recommendation {
+            None => {
+                ui.label(egui::RichText::new("给当前精力打分，按最近状态建议专注时长").color(self.palette.text_dim));
+            }
+            Some(r) => {
+                ui.label(egui::RichText::new(format!("建议：{}", r.reason)).color(self.palette.text_dim));
+                if r.suggestion == Suggestion::WarmUp
+                    && ui.small_button(format!("开始 {} 分钟热身", WARMUP_SECS / 60)).clicked()
+                {
+                    self.pomo.start();
+                    self.pomo.adjust_remaining(WARMUP_SECS - self.pomo.phase_total_secs);
+                    self.delayed_start = None;
+                }
+            }
+        }
+    }
+
+    /// 专注进行中（运行/暂停）时在倒计时下方醒目显示本次承诺
+    /// 应用内提示：窗口底部居中的浮层，按到达顺序堆叠
+    fn ui_toasts(&mut self, ctx: &egui::Context) {
+        let palette = self.palette;
+        let toasts: Vec<String> = self.notifier.active_toasts().map(str::to_string).collect();
+        if toasts.is_empty() {
+            return;
+        }
+        egui::Area::new(egui::Id::new("toasts"))
+            .anchor(egui::Align2::CENTER_BOTTOM, [0.0, -12.0])
+            .order(egui::Order::Foreground)
+            .interactable(false)
+            .show(ctx, |ui| {
+                for message in &toasts {
+                    egui::Frame::popup(ui.style()).show(ui, |ui| {
+                        ui.label(egui::RichText::new(message).color(palette.text));
+                    });
+                }
+            });
+        // 提示到期后需要重绘才能消失
+        ctx.request_repaint_after(std::time::Duration::from_millis(500));
+    }
+
+    /// 错误提示条：窗口顶部列出未关闭的错误，可重试、打开日志或关闭
+    fn ui_error_banner(&mut self, ctx: &egui::Context) {
+        self.error_reports.collect();
+        if self.error_reports.items().is_empty() {
+            return;
+        }
+        let palette = self.palette;
+        let error_color = ctx.style().visuals.error_fg_color;
+        let mut retry = None;
+        let mut dismiss = None;
+        egui::Area::new(egui::Id::new("error_banner"))
+            .anchor(egui::Align2::CENTER_TOP, [0.0, 36.0])
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                for report in self.error_reports.items() {
+                    egui::Frame::popup(ui.style()).show(ui, |ui| {
+                        ui.set_max_width(320.0);
+                        let text = if report.count > 1 {
+                            format!("{}（{} 次）", report.message, report.count)
+                        } else {
+                            report.message.clone()
+                        };
+                        ui.label(egui::RichText::new(text).color(error_color));
+                        ui.horizontal(|ui| {
+                            if report.kind.retryable() && ui.button("重试").clicked() {
+                                retry = Some(report.kind);
+                            }
+                            if ui.link("查看日志").clicked() {
+                                crate::logging::open_log_dir().log_err("打开日志文件夹");
+                            }
+                            if ui.button(egui::RichText::new("关闭").color(palette.text_dim)).clicked() {
+                                dismiss = Some(report.kind);
+                            }
+                        });
+                    });
+                }
+            });
+        if let Some(kind) = dismiss {
+            self.error_reports.dismiss(kind);
+        }
+        if let Some(kind) = retry {
+            // 先关闭，重试仍失败时会再报告
+            self.error_reports.dismiss(kind);
+            match kind {
+                ErrorKind::Database => {
+                    self.load_data();
+                    self.recent_tasks = None;
+                    self.invalidate_stats();
+                }
+                ErrorKind::DataSync => {
+                    if self.data_sync_enabled() && !self.data_sync_runner.is_busy() {
+                        self.start_data_sync();
+                    }
+                }
+                ErrorKind::SettingsSync => {
+                    if self.sync_enabled() && !self.settings_sync.is_busy() {
+                        self.settings_sync.pull(&self.sync_endpoint);
+                        self.sync_status = "正在拉取云端设置…".to_string();
+                    }
+                }
+                ErrorKind::Integration => {}
+            }
+        }
+    }
+
+    /// 撤销提示条：重置 / 完成后在窗口底部显示「撤销」，`undo::UNDO_WINDOW` 后消失
+    fn ui_undo_bar(&mut self, ctx: &egui::Context) {
+        let Some((label, left)) = self.undo.latest() else { return };
+        let text = format!("{}（{} 秒）", label, left.as_secs() + 1);
+        let mut undo = false;
+        egui::Area::new(egui::Id::new("undo_bar"))
+            .anchor(egui::Align2::CENTER_BOTTOM, [0.0, -56.0])
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(egui::RichText::new(text).color(self.palette.text));
+                        undo = ui.button("撤销").clicked();
+                    });
+                });
+            });
+        if undo {
+            self.undo_reset();
+        }
+        ctx.request_repaint_after(std::time::Duration::from_millis(500));
+    }
+
+    fn ui_commitment_line(&self, ui: &mut egui::Ui, size: f32) {
+        if self.pomo.phase != Phase::Focus
+            || self.pomo.state == TimerState::Idle
+            || self.current_commitment.trim().is_empty()
+        {
+            return;
+        }
+        ui.label(
+            egui::RichText::new(format!("「{}」", self.current_commitment.trim()))
+                .color(self.palette.focus)
+                .size(size)
+                .strong(),
+        );
+        ui.add_space(4.0);
+    }
+
+    /// 专注计时中（不含空闲）在计时器下方显示当前语录
+    fn ui_quote_line(&self, ui: &mut egui::Ui) {
+        if self.pomo.phase != Phase::Focus || self.pomo.state == TimerState::Idle {
+            return;
+        }
+        let elapsed = self.pomo.phase_total_secs - self.pomo.remaining_secs.max(0) + self.pomo.overtime_secs;
+        let Some(quote) = self.quotes.pick(self.pomo.completed_pomodoros, elapsed) else { return };
+        ui.label(egui::RichText::new(quote).color(self.palette.text_dim).size(13.0).italics());
+        ui.add_space(4.0);
+    }
+
+    /// 休息计时运行中显示呼吸引导圆圈与「跳过」；暂停时停止、继续后从吸气重新开始
+    fn ui_breathing_guide(&mut self, ui: &mut egui::Ui, accent: egui::Color32) {
+        if self.pomo.phase == Phase::Focus {
+            self.breathing_skipped = false;
+        }
+        if !self.breathing.enabled
+            || self.breathing_skipped
+            || self.pomo.phase == Phase::Focus
+            || self.pomo.state != TimerState::Running
+        {
+            self.breathing_started = None;
+            return;
+        }
+        let started = *self.breathing_started.get_or_insert_with(std::time::Instant::now);
+        let frame = crate::breathing::frame_at(self.breathing.pattern, started.elapsed().as_secs_f32());
+        let steady = self.a11y.is_some_and(|a| a.reduce_motion);
+        crate::breathing::paint(ui, frame, 96.0, accent, self.palette.text, steady);
+        if ui.small_button("跳过呼吸引导").clicked() {
+            self.breathing_skipped = true;
+        }
+        ui.add_space(4.0);
+        ui.ctx().request_repaint();
+    }
+
+    /// 每日回顾窗口：当天专注汇总、按日期轮换的反思提问与回答（存入 journal_entries），以及日志搜索
+    fn ui_daily_review(&mut self, ctx: &egui::Context) {
+        let palette = self.palette;
+        let Some(view) = self.daily_review.as_mut() else { return };
+        let day_key = view.day.format("%Y-%m-%d").to_string();
+        if view.summary.is_none() || view.results.is_none() {
+            let start = beijing_day_start(view.day);
+            let (from, to) = (beijing_rfc3339(start), beijing_rfc3339(start + 24 * 3600));
+            let loaded = crate::db::open_and_init().and_then(|conn| {
+                let summary = crate::db::session_summary(&conn, &from, &to)?;
+                let digest = crate::db::load_digest(&conn, &from, &to)?;
+                let results = crate::db::search_journal(&conn, &view.search, 50)?;
+                Ok((summary, digest, results))
+            });
+            match loaded {
+                Ok((summary, digest, results)) => {
+                    view.summary = Some(summary);
+                    view.digest = digest;
+                    view.results = Some(results);
+                }
+                Err(e) => view.message = Some(format!("加载失败：{}", e)),
+            }
+        }
+        let mut open = true;
+        egui::Window::new("每日回顾")
+            .open(&mut open)
+            .default_width(400.0)
+            .show(ctx, |ui| {
+                ui.label(egui::RichText::new(&day_key).size(16.0).color(palette.text));
+                if let Some((n, total, _)) = view.summary {
+                    ui.label(format!("今天完成 {} 个番茄，专注 {}", n, crate::review::format_hm(total)));
+                }
+                if !view.digest.is_empty() {
+                    egui::CollapsingHeader::new(format!("通知摘要（{} 条）", view.digest.len()))
+                        .id_salt("notification_digest")
+                        .show(ui, |ui| {
+                            for d in &view.digest {
+                                let event = crate::notify::NotifyEvent::from_key(&d.event).map(|e| e.label()).unwrap_or(&d.event);
+                                ui.label(format!("{} [{}] {}", d.occurred_at.get(11..16).unwrap_or(""), event, d.message));
+                            }
+                        });
+                }
+                ui.add_space(8.0);
+                let prompt = crate::journal::prompt_for(view.day, view.prompt_offset);
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new(prompt).size(15.0).color(palette.focus));
+                    if ui.small_button("换一个").clicked() {
+                        view.prompt_offset += 1;
+                    }
+                });
+                ui.add(
+                    egui::TextEdit::multiline(&mut view.answer)
+                        .desired_rows(3)
+                        .desired_width(f32::INFINITY)
+                        .hint_text("写下一两句…"),
+                );
+                if ui.button("保存").clicked() && !view.answer.trim().is_empty() {
+                    let saved = crate::db::open_and_init().and_then(|conn| {
+                        crate::db::insert_journal_entry(&conn, &day_key, prompt, view.answer.trim(), &beijing_now_rfc3339())
+                    });
+                    view.message = Some(match saved {
+                        Ok(()) => {
+                            view.answer.clear();
+                            view.prompt_offset += 1;
+                            view.results = None;
+                            "已保存".to_string()
+                        }
+                        Err(e) => format!("保存失败：{}", e),
+                    });
+                }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label("搜索日志：");
+                    if ui
+                        .add(egui::TextEdit::singleline(&mut view.search).desired_width(200.0))
+                        .changed()
+                    {
+                        view.results = None;
+                    }
+                });
+                if let Some(results) = &view.results {
+                    egui::ScrollArea::vertical().id_salt("journal_results").max_height(180.0).show(ui, |ui| {
+                        if results.is_empty() {
+                            ui.label(egui::RichText::new("没有匹配的日志").color(palette.text_dim));
+                        }
+                        for r in results {
+                            ui.label(egui::RichText::new(format!("{} {} · {}", r.day, r.created_at.get(11..16).unwrap_or(""), r.prompt)).size(12.0).color(palette.text_dim));
+                            ui.label(&r.answer);
+                            ui.add_space(4.0);
+                        }
+                    });
+                }
+                if let Some(msg) = &view.message {
+                    ui.label(egui::RichText::new(msg).color(palette.text_dim));
+                }
+            });
+        if !open {
+            self.daily_review = None;
+        }
+    }
+
+    /// 周报窗口：最近 7 天专注总时长、每日番茄数（柱状图）、任务 Top 5（条形图）、深度块与连续天数、平均时长、中断；可导出 Markdown
+    fn ui_weekly_review(&mut self, ctx: &egui::Context) {
+        let palette = self.palette;
+        let colors = &self.task_colors;
+        let max_gap = self.pomo.config.short_break_secs + crate::stats::DEEP_BLOCK_SLACK_SECS;
+        let Some(view) = self.review.as_mut() else { return };
+        if view.loaded.is_none() {
+            match crate::db::open_and_init()
+                .and_then(|conn| crate::review::build_weekly_review(&conn, view.last_day, max_gap))
+            {
+                Ok(r) => view.loaded = Some(r),
+                Err(e) => view.message = Some(format!("加载失败：{}", e)),
+            }
+        }
+        let mut open = true;
+        egui::Window::new("周报")
+            .open(&mut open)
+            .default_width(420.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button("◀").on_hover_text("上一周").clicked() {
+                        view.last_day -= chrono::Duration::days(7);
+                        view.loaded = None;
+                    }
+                    if let Some(r) = &view.loaded {
+                        ui.label(format!("{} ~ {}", r.first_day.format("%m-%d"), r.last_day.format("%m-%d")));
+                    }
+                    if ui.button("▶").on_hover_text("下一周").clicked() {
+                        view.last_day += chrono::Duration::days(7);
+                        view.loaded = None;
+                    }
+                });
+                ui.add_space(6.0);
+                if let Some(r) = &view.loaded {
+                    use crate::review::format_hm;
+                    ui.label(
+                        egui::RichText::new(format!("专注 {} · {} 个番茄", format_hm(r.total_secs), r.sessions))
+                            .size(16.0)
+                            .color(palette.text),
+                    );
+                    ui.add_space(4.0);
+                    paint_daily_bars(ui, &r.days, &palette);
+                    ui.add_space(6.0);
+                    ui.label(format!("平均每次专注：{}", format_hm(r.avg_session_secs)));
+                    ui.label(format!(
+                        "最长深度块：{} 个番茄（{}） · 最长连续专注 {} 天",
+                        r.longest_block_sessions,
+                        format_hm(r.longest_block_secs),
+                        r.longest_day_streak
+                    ));
+                    ui.label(format!("中断：暂停 {} 次 · 未完成即重置 {} 次", r.pauses, r.abandons));
+                    ui.separator();
+                    ui.label("任务 Top 5");
+                    let max_secs = r.top_tasks.first().map(|t| t.total_secs).unwrap_or(0).max(1);
+                    for t in &r.top_tasks {
+                        let name = if t.task.is_empty() { "(无任务)" } else { t.task.as_str() };
+                        ui.horizontal(|ui| {
+                            let bar_w = 120.0 * t.total_secs as f32 / max_secs as f32;
+                            let (rect, _) = ui.allocate_exact_size(egui::vec2(120.0, 12.0), egui::Sense::hover());
+                            ui.painter().rect_filled(rect, 2.0, palette.lane_bg);
+                            ui.painter().rect_filled(
+                                egui::Rect::from_min_size(rect.min, egui::vec2(bar_w.at_least(1.0), rect.height())),
+                                2.0,
+                                if t.task.is_empty() { palette.focus } else { colors.color(&t.task, None) },
+                            );
+                            ui.label(format!("{} · {} · 🍅{}", name, format_hm(t.total_secs), t.sessions));
+                        });
+                    }
+                    ui.separator();
+                    if ui.button("导出 Markdown").clicked() {
+                        let file_name = format!("weekly-{}.md", r.last_day.format("%Y-%m-%d"));
+                        view.message = Some(match crate::review::write_report(&file_name, &r.to_markdown()) {
+                            Ok(path) => format!("已导出：{}", path.display()),
+                            Err(e) => format!("导出失败：{}", e),
+                        });
+                    }
+                }
+                if let Some(msg) = &view.message {
+                    ui.label(egui::RichText::new(msg).color(palette.text_dim));
+                }
+            });
+        if !open {
+            self.review = None;
+        }
+    }
+
+    /// 设置窗口：界面字体（运行时切换，立即生效）、主题
+    fn ui_settings(&mut self, ctx: &egui::Context) {
+        egui::Window::new("设置")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                let policy = crate::policy::get();
+                if !policy.disabled_integrations.is_empty() {
+                    let names: Vec<&str> = policy.disabled_integrations.iter().map(|i| i.label()).collect();
+                    ui.label(
+                        egui::RichText::new(format!("管理员策略已禁用：{}", names.join("、"))).color(self.palette.text_dim),
+                    );
+                }
+                let mut selected = self.font_choice.clone();
+                ui.horizontal(|ui| {
+                    ui.label("字体：");
+                    let current_text = self.fonts.current().unwrap_or("内置字体").to_string();
+                    egui::ComboBox::from_id_salt("font_choice")
+                        .selected_text(current_text)
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut selected, None, "自动");
+                            for choice in self.fonts.choices() {
+                                ui.selectable_value(&mut selected, Some(choice.name.clone()), &choice.name);
+                            }
+                        });
+                });
+                if self.fonts.choices().is_empty() {
+                    ui.label("未找到可用的中文字体，使用内置字体。可在下方指定字体文件。");
+                }
+                // 自定义字体文件（.ttf / .ttc / .otf），非 Windows 未找到系统中文字体时使用
+                ui.horizontal(|ui| {
+                    ui.label("字体文件：");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.custom_font_path)
+                            .desired_width(180.0)
+                            .hint_text("如 /usr/share/fonts/xxx.ttc"),
+                    );
+                    if ui.button("使用").clicked() {
+                        self.fonts.set_custom_path(Some(&self.custom_font_path));
+                        selected = Some(crate::fonts::CUSTOM_FONT_NAME.to_string());
+                        self.font_choice = None; // 强制下方重新应用
+                    }
+                });
+                if selected != self.font_choice {
+                    self.font_choice = selected;
+                    self.fonts.apply(ctx, self.font_choice.as_deref());
+                }
+                if self.font_choice.is_some() && self.fonts.current() != self.font_choice.as_deref() {
+                    ui.label(egui::RichText::new("所选字体无法读取，已改用其他字体。").color(self.palette.text_dim));
+                }
+                ui.horizontal(|ui| {
+                    ui.label("主题：");
+                    for mode in ThemeMode::ALL {
+                        ui.selectable_value(&mut self.theme_mode, mode, mode.label());
+                    }
+                });
+                // 阶段强调色：进度条、阶段文案、番茄数圆圈统一使用
+                ui.horizontal(|ui| {
+                    ui.label("强调色：");
+                    for phase in [Phase::Focus, Phase::ShortBreak, Phase::LongBreak] {
+                        ui.label(Self::phase_label(phase));
+                        let current = self.palette.phase_accent(phase);
+                        let mut rgb = [current.r(), current.g(), current.b()];
+                        if ui.color_edit_button_srgb(&mut rgb).changed() {
+                            *self.accents.get_mut(phase) = Some(rgb);
+                        }
+                    }
+                    if ui.small_button("恢复默认").clicked() {
+                        self.accents = AccentColors::default();
+                    }
+                });
+                let layout_before = self.timer_layout;
+                ui.horizontal(|ui| {
+                    ui.label("计时器样式：");
+                    for layout in TimerLayout::ALL {
+                        ui.selectable_value(&mut self.timer_layout, layout, layout.label());
+                    }
+                })
+                .response
+                .on_hover_text("圆环：倒计时放在按阶段强调色填满的大圆环中间，代替下方的进度条；紧凑模式同样适用");
+                if self.timer_layout != layout_before {
+                    // 按新布局的高度重设窗口尺寸
+                    self.initial_full_size_applied = false;
+                    self.compact_size_applied = false;
+                }
+                ui.horizontal(|ui| {
+                    ui.label("关闭按钮：");
+                    for action in CloseAction::ALL {
+                        ui.selectable_value(&mut self.close_action, action, action.label());
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("专注中电脑休眠：");
+                    match crate::policy::get().sleep_action {
+                        Some(action) => {
+                            ui.label(format!("{}（已由部署策略固定）", action.label()));
+                        }
+                        None => {
+                            for action in SleepAction::ALL {
+                                ui.selectable_value(&mut self.sleep_action, action, action.label());
+                            }
+                        }
+                    }
+                });
+                ui.checkbox(&mut self.tray_timer, "托盘图标显示剩余时间")
+                    .on_hover_text("系统托盘中按阶段着色的进度饼图与剩余分钟数，窗口最小化时也能看到（仅 Windows）");
+                ui.checkbox(&mut self.break_screensaver, "长休息时显示全屏休息画面（任意输入关闭）");
+                ui.checkbox(&mut self.focus_banner, "专注时在屏幕顶部显示「专注中 · 请勿打扰」横幅")
+                    .on_hover_text("同事路过或远程查看屏幕时可见；横幅不接收鼠标，不影响操作");
+                // 无障碍：默认跟随系统设置，可手动覆盖
+                let system = self.system_a11y;
+                for (label, hint, value, detected) in [
+                    (
+                        "减少动态效果：",
+                        "开启后不做界面过渡动画：阶段切换时强调色立即切换，计时光晕不再起伏",
+                        &mut self.accessibility.reduce_motion,
+                        system.reduce_motion,
+                    ),
+                    (
+                        "降低透明度：",
+                        "开启后不画背景点阵、计时光晕与半透明阴影",
+                        &mut self.accessibility.reduce_transparency,
+                        system.reduce_transparency,
+                    ),
+                ] {
+                    ui.horizontal(|ui| {
+                        ui.label(label).on_hover_text(hint);
+                        for o in A11yOverride::ALL {
+                            let text = if o == A11yOverride::System {
+                                format!("{}（{}）", o.label(), if detected { "开" } else { "关" })
+                            } else {
+                                o.label().to_string()
+                            };
+                            ui.selectable_value(value, o, text);
+                        }
+                    });
+                }
+                ui.add_space(6.0);
+                // 时长（分钟），修改后下次开始阶段时生效
+                let config = &mut self.pomo.config;
+                let mut minutes = [config.focus_secs / 60, config.short_break_secs / 60, config.long_break_secs / 60];
+                ui.horizontal(|ui| {
+                    for (label, m) in ["专注", "短休息", "长休息"].into_iter().zip(minutes.iter_mut()) {
+                        ui.label(label);
+                        ui.add(egui::DragValue::new(m).range(1..=180).suffix(" 分"));
+                    }
+                });
+                config.focus_secs = minutes[0] * 60;
+                config.short_break_secs = minutes[1] * 60;
+                config.long_break_secs = minutes[2] * 60;
+                ui.horizontal(|ui| {
+                    ui.label("长休息间隔：");
+                    ui.add(egui::DragValue::new(&mut config.pomodoros_before_long).range(1..=12).suffix(" 个番茄"));
+                });
+                ui.horizontal(|ui| {
+                    ui.label("连续推迟休息最多：");
+                    ui.add(egui::DragValue::new(&mut config.max_snoozes).range(0..=5).suffix(" 次"))
+                        .on_hover_text("专注快结束或休息刚开始时可「再专注 5 分钟」，不计番茄数；0 表示不允许推迟");
+                });
+                ui.checkbox(&mut config.overtime, "加时模式：专注到点后继续正计时，手动结束后再休息")
+                    .on_hover_text("适合正在状态中不想被打断；超出的分钟计入本次专注");
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.tick_sound.enabled, "专注时播放滴答声");
+                    ui.add_enabled(
+                        self.tick_sound.enabled,
+                        egui::Slider::new(&mut self.tick_sound.volume, 0..=100).text("音量"),
+                    );
+                });
+                if self.tick_sound.enabled && !crate::metronome::SUPPORTED {
+                    ui.label(egui::RichText::new("当前系统暂不支持播放滴答声（仅 Windows）").color(self.palette.text_dim));
+                }
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.media.pause_on_focus, "专注开始时暂停音乐 / 播客")
+                        .on_hover_text("只暂停正在播放的播放器；Linux 需安装 playerctl");
+                    ui.add_enabled(
+                        self.media.pause_on_focus,
+                        egui::Checkbox::new(&mut self.media.resume_on_break, "休息时恢复播放"),
+                    );
+                });
+                if self.media.pause_on_focus && !crate::media::SUPPORTED {
+                    ui.label(egui::RichText::new("当前系统暂不支持控制媒体播放（仅 Windows、Linux）").color(self.palette.text_dim));
+                }
+                // 数据保留：旧记录移入归档库，主库保持小巧；统计窗口可勾选「包含归档」查看
+                ui.horizontal(|ui| {
+                    ui.label("归档早于");
+                    ui.add(egui::DragValue::new(&mut self.archive_after_months).range(0..=120).suffix(" 个月"))
+                        .on_hover_text("0 表示不归档；每天启动或跨天时自动执行");
+                    ui.label("的记录");
+                    if ui.add_enabled(self.archive_after_months > 0, egui::Button::new("立即归档")).clicked() {
+                        self.archive_checked_on = None;
+                    }
+                });
+                if !self.archive_status.is_empty() {
+                    ui.label(egui::RichText::new(&self.archive_status).color(self.palette.text_dim));
+                }
+                ui.horizontal(|ui| {
+                    ui.label("自动保存间隔");
+                    ui.add(
+                        egui::DragValue::new(&mut self.autosave_secs)
+                            .range(AUTOSAVE_SECS_RANGE.0..=AUTOSAVE_SECS_RANGE.1)
+                            .suffix(" 秒"),
+                    )
+                    .on_hover_text("阶段切换、开始 / 暂停及切换紧凑 / 钉住时另会立即保存");
+                });
+                self.ui_encryption_settings(ui);
+                self.ui_db_location_settings(ui);
+                ui.label(
+                    egui::RichText::new(format!("配置文件：{}", crate::config::config_path().display()))
+                        .color(self.palette.text_dim),
+                )
+                .on_hover_text("可手工编辑的 config.toml：写了的项覆盖界面设置，修改后自动重新加载");
+                if !self.config_status.is_empty() {
+                    ui.label(egui::RichText::new(&self.config_status).color(self.palette.text_dim));
+                }
+
+                ui.separator();
+                self.ui_profile_settings(ui);
+                self.ui_routine_settings(ui);
+                self.ui_working_hours_settings(ui);
+                self.ui_idle_nudge_settings(ui);
+                self.ui_eye_rest_settings(ui);
+                self.ui_wellness_settings(ui);
+                self.ui_rest_guard_settings(ui);
+                self.ui_distraction_settings(ui);
+                self.ui_breathing_settings(ui);
+                self.ui_quote_settings(ui);
+                egui::CollapsingHeader::new("通知路由").show(ui, |ui| {
+                    egui::Grid::new("notification_routes").striped(true).show(ui, |ui| {
+                        ui.label("");
+                        for channel in Channel::ALL {
+                            ui.label(channel.label());
+                        }
+                        ui.end_row();
+                        for event in NotifyEvent::ALL {
+                            ui.label(event.label());
+                            for channel in Channel::ALL {
+                                let mut on = self.notifications.is_routed(event, channel);
+                                if ui.checkbox(&mut on, "").changed() {
+                                    self.notifications.set_routed(event, channel, on);
+                                }
+                            }
+                            ui.end_row();
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Webhook：");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.notifications.webhook_url)
+                                .desired_width(220.0)
+                                .hint_text("https://example.com/hook"),
+                        );
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("手机推送：");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.notifications.push_url)
+                                .desired_width(220.0)
+                                .hint_text("https://ntfy.sh/我的主题"),
+                        );
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("推送令牌：");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.notifications.push_token)
+                                .desired_width(220.0)
+                                .password(true)
+                                .hint_text("受保护的 ntfy 主题或自建接口的令牌，可留空"),
+                        )
+                        .on_hover_text("以 Authorization: Bearer 头发送");
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Telegram：");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.notifications.telegram_token)
+                                .desired_width(140.0)
+                                .password(true)
+                                .hint_text("机器人 token"),
+                        );
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.notifications.telegram_chat_id)
+                                .desired_width(76.0)
+                                .hint_text("chat_id"),
+                        );
+                    });
+                    ui.horizontal(|ui| {
+                        ui.add_enabled(
+                            self.notifications.telegram_active(),
+                            egui::Checkbox::new(&mut self.notifications.telegram_digest, "每晚发送当天摘要"),
+                        )
+                        .on_hover_text("当天的番茄数、专注时长与任务排行；到点时未运行则当天稍后启动时补发")
+                        .on_disabled_hover_text("需先填写 Telegram 机器人 token 与 chat_id");
+                        ui.label("时间：");
+                        ui.add_enabled(
+                            self.notifications.telegram_digest,
+                            egui::TextEdit::singleline(&mut self.notifications.telegram_digest_time)
+                                .desired_width(48.0)
+                                .hint_text(crate::notify::DEFAULT_DIGEST_TIME),
+                        );
+                    });
+                    ui.checkbox(
+                        &mut self.notifications.push_actions,
+                        "手机推送附带「开始下一阶段」「+5 分钟」按钮",
+                    )
+                    .on_hover_text("阶段结束的推送可在手机 / 手表上直接操作；+5 分钟在空闲时推迟 5 分钟再开始下一阶段");
+                    // 视觉提醒：静音工作时阶段结束也能注意到
+                    let visual = &mut self.notifications.visual;
+                    ui.horizontal(|ui| {
+                        ui.label("阶段结束时：");
+                        ui.checkbox(&mut visual.flash_taskbar, "任务栏闪烁");
+                        ui.checkbox(&mut visual.pulse, "背景脉冲");
+                        ui.checkbox(&mut visual.raise_window, "弹到最前");
+                    });
+                    ui.label(
+                        egui::RichText::new("摘要渠道不即时提醒，在「回顾」中按天查看").color(self.palette.text_dim),
+                    );
+                });
+
+                ui.separator();
+                ui.checkbox(&mut self.daily_note.enabled, "完成番茄时追加到日记笔记（如 Obsidian）");
+                ui.horizontal(|ui| {
+                    ui.label("文件：");
+                    ui.add_enabled(
+                        self.daily_note.enabled,
+                        egui::TextEdit::singleline(&mut self.daily_note.path_template)
+                            .desired_width(220.0)
+                            .hint_text("~/Obsidian/Daily/{date}.md"),
+                    );
+                });
+                if self.daily_note.enabled {
+                    let hint = if self.daily_note_status.is_empty() {
+                        "{date} → YYYY-MM-DD，{year} / {month} / {day} 分别替换".to_string()
+                    } else {
+                        self.daily_note_status.clone()
+                    };
+                    ui.label(egui::RichText::new(hint).color(self.palette.text_dim));
+                }
+
+                ui.separator();
+                ui.checkbox(&mut self.git_suggest.enabled, "从 git 仓库推荐任务名（当前分支 / 最近提交）");
+                ui.horizontal(|ui| {
+                    ui.label("仓库：");
+                    ui.add_enabled(
+                        self.git_suggest.enabled,
+                        egui::TextEdit::singleline(&mut self.git_suggest.repo_path)
+                            .desired_width(220.0)
+                            .hint_text("~/code/my-project"),
+                    );
+                });
+                if self.git_suggest.is_active() && self.git_suggester.suggestions(&self.git_suggest).is_empty() {
+                    ui.label(
+                        egui::RichText::new("未读取到分支或提交（需安装 git，主干分支名不作建议）")
+                            .color(self.palette.text_dim),
+                    );
+                }
+
+                ui.separator();
+                ui.checkbox(&mut self.discord.enabled, "在 Discord 状态中显示专注任务与剩余时间");
+                if self.discord.enabled {
+                    ui.horizontal(|ui| {
+                        ui.label("应用 ID：");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.discord.client_id)
+                                .desired_width(200.0)
+                                .hint_text("Discord 开发者后台的 Application ID"),
+                        );
+                    });
+                    ui.label(
+                        egui::RichText::new("任务名会对 Discord 好友可见；休息与空闲时自动清除").color(self.palette.text_dim),
+                    );
+                }
+
+                ui.separator();
+                ui.checkbox(&mut self.slack.enabled, "专注时设置 Slack 状态（🍅 Focusing until …）");
+                if self.slack.enabled {
+                    ui.horizontal(|ui| {
+                        ui.label("令牌：");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.slack.token)
+                                .password(true)
+                                .desired_width(200.0)
+                                .hint_text("xoxp-…（用户 OAuth 令牌）"),
+                        );
+                    });
+                    ui.checkbox(&mut self.slack.dnd, "同时开启勿扰，专注结束后关闭");
+                    let state = self.slack_status.state();
+                    let hint = if state.is_empty() { "填写令牌后生效".to_string() } else { state };
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "{}；需 users.profile:read、users.profile:write、dnd:write 权限，结束后恢复原状态",
+                            hint
+                        ))
+                        .color(self.palette.text_dim),
+                    );
+                }
+
+                ui.separator();
+                ui.checkbox(&mut self.mqtt.enabled, "发布计时状态到 MQTT（Home Assistant 等）");
+                if self.mqtt.enabled {
+                    egui::Grid::new("mqtt_settings").num_columns(2).show(ui, |ui| {
+                        ui.label("服务器：");
+                        ui.horizontal(|ui| {
+                            ui.add(egui::TextEdit::singleline(&mut self.mqtt.host).desired_width(150.0).hint_text("192.168.1.10"));
+                            ui.add(egui::DragValue::new(&mut self.mqtt.port).range(1..=65535));
+                            if ui.checkbox(&mut self.mqtt.tls, "TLS").changed() {
+                                // 端口仍为默认值时随 TLS 切换
+                                let (from, to) = if self.mqtt.tls {
+                                    (crate::mqtt::DEFAULT_PORT, crate::mqtt::DEFAULT_TLS_PORT)
+                                } else {
+                                    (crate::mqtt::DEFAULT_TLS_PORT, crate::mqtt::DEFAULT_PORT)
+                                };
+                                if self.mqtt.port == from {
+                                    self.mqtt.port = to;
+                                }
+                            }
+                        });
+                        ui.end_row();
+                        ui.label("用户名：");
+                        ui.add(egui::TextEdit::singleline(&mut self.mqtt.username).desired_width(150.0).hint_text("可留空"));
+                        ui.end_row();
+                        ui.label("密码：");
+                        ui.add(egui::TextEdit::singleline(&mut self.mqtt.password).password(true).desired_width(150.0));
+                        ui.end_row();
+                        ui.label("主题前缀：");
+                        ui.add(egui::TextEdit::singleline(&mut self.mqtt.topic).desired_width(150.0).hint_text("red-tomato"));
+                        ui.end_row();
+                    });
+                    let state = self.mqtt_publisher.state();
+                    let hint = if state.is_empty() { "填写服务器地址后开始发布".to_string() } else { state };
+                    ui.label(
+                        egui::RichText::new(format!("{}；发布 <前缀>/phase、<前缀>/status（保留消息）", hint))
+                            .color(self.palette.text_dim),
+                    );
+                }
+
+                ui.separator();
+                ui.checkbox(&mut self.obs.enabled, "输出计时状态到文本文件（OBS 文本源「从文件读取」）");
+                if self.obs.enabled {
+                    let default_path = crate::obs::default_path().display().to_string();
+                    ui.horizontal(|ui| {
+                        ui.label("文件：");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.obs.path)
+                                .desired_width(220.0)
+                                .hint_text(&default_path),
+                        );
+                    });
+                    let hint = self
+                        .obs_error
+                        .clone()
+                        .unwrap_or_else(|| format!("正在写入：{}", self.obs.output_path().display()));
+                    ui.label(egui::RichText::new(hint).color(self.palette.text_dim));
+                }
+
+                ui.separator();
+                ui.checkbox(&mut self.bar_output, "状态栏输出（Waybar / Polybar / i3blocks）")
+                    .on_hover_text("状态栏中运行 red-tomato bar，每秒输出一行计时状态");
+                if self.bar_output {
+                    let hint = self.bar_error.clone().unwrap_or_else(|| {
+                        format!("状态栏命令：red-tomato bar [--format waybar|i3blocks]，状态文件 {}", crate::bar::state_path().display())
+                    });
+                    ui.label(egui::RichText::new(hint).color(self.palette.text_dim));
+                }
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui
+                        .button("注册 redtomato:// 链接")
+                        .on_hover_text("之后可在启动器、笔记中用 redtomato://start?task=写周报&mins=25 开始专注")
+                        .clicked()
+                    {
+                        self.deeplink_message = Some(crate::deeplink::register().unwrap_or_else(|e| e));
+                    }
+                });
+                if let Some(msg) = &self.deeplink_message {
+                    ui.label(egui::RichText::new(msg).color(self.palette.text_dim));
+                }
+
+                #[cfg(feature = "http-api")]
+                {
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut self.http_api.enabled, "本机 HTTP 接口，端口");
+                        let port = ui.add(egui::DragValue::new(&mut self.http_api.port).range(1024..=65535));
+                        if port.changed() {
+                            self.http_api_error = None;
+                        }
+                    });
+                    if let Some(err) = &self.http_api_error {
+                        ui.label(egui::RichText::new(err).color(self.palette.text_dim));
+                    } else if self.http_api.enabled {
+                        let hint = format!("GET http://127.0.0.1:{}/status，POST /start、/pause、/skip", self.http_api.port);
+                        ui.label(egui::RichText::new(hint).color(self.palette.text_dim));
+                    }
+                }
+
+                ui.separator();
+                ui.label("设置同步（主题、字体、时长；不含历史）");
+                ui.horizontal(|ui| {
+                    ui.label("地址：");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.sync_endpoint.url)
+                            .desired_width(220.0)
+                            .hint_text("https://kv.example.com/v1"),
+                    );
+                });
+                ui.horizontal(|ui| {
+                    ui.label("令牌：");
+                    ui.add(egui::TextEdit::singleline(&mut self.sync_endpoint.token).password(true).desired_width(220.0));
+                });
+                ui.horizontal(|ui| {
+                    let enabled = self.sync_enabled() && !self.settings_sync.is_busy();
+                    if ui.add_enabled(enabled, egui::Button::new("从云端拉取")).clicked() {
+                        self.settings_sync.pull(&self.sync_endpoint);
+                        self.sync_status = "正在拉取云端设置…".to_string();
+                    }
+                    if ui.add_enabled(enabled, egui::Button::new("上传本机设置")).clicked() {
+                        self.settings_sync.push(&self.sync_endpoint, self.synced_settings());
+                        self.sync_status = "正在同步…".to_string();
+                    }
+                });
+                if !self.sync_status.is_empty() {
+                    ui.label(egui::RichText::new(&self.sync_status).color(self.palette.text_dim));
+                }
+
+                ui.separator();
+                self.ui_data_sync_settings(ui);
+                ui.add_space(8.0);
+                if ui.button("关闭").clicked() {
+                    self.show_settings = false;
+                }
+            });
+    }
+
+    /// 统计窗口：按完成时间逆序、同任务番茄数累计、番茄数从 1 开始
+    fn ui_statistics(&mut self, ctx: &egui::Context) {
+        let palette = self.palette;
+        egui::Window::new("统计 · 专注记录")
+            .default_width(460.0)
+            .default_height(320.0)
+            .show(ctx, |ui| {
+                ui.label("数据保存在 SQLite，路径见「关于」；复制该目录即可迁移。");
+                ui.label(format!(
+                    "深度块按最近 {} 天统计；下方列表按筛选条件查询，滚动到底部时继续加载更早的记录。",
+                    self.focus_history.window_days()
+                ));
+                ui.add_space(4.0);
+                let [today_counts, week_counts] = *self.status_counts.get_or_insert_with(|| {
+                    let (today, week) = crate::stats::today_and_week_ranges(beijing_now());
+                    let count = |(from, to): (i64, i64)| {
+                        crate::db::open_and_init()
+                            .and_then(|conn| {
+                                let (from, to) = (beijing_rfc3339(from), beijing_rfc3339(to));
+                                Ok((
+                                    crate::db::count_by_status(&conn, &from, &to)?,
+                                    crate::db::pause_totals(&conn, &from, &to)?,
+                                    crate::db::count_task_completions(&conn, &from, &to)?,
+                                ))
+                            })
+                            .unwrap_or_default()
+                    };
+                    [count(today), count(week)]
+                });
+                // 放弃率 = 放弃 / (完成 + 放弃)
+                let describe_abandon = |label: &str, (completed, abandoned): (u32, u32)| {
+                    let total = completed + abandoned;
+                    if total == 0 {
+                        format!("{}放弃率 —", label)
+                    } else {
+                        format!(
+                            "{}放弃率 {:.0}%（完成 {}，放弃 {}）",
+                            label,
+                            abandoned as f64 * 100.0 / total as f64,
+                            completed,
+                            abandoned
+                        )
+                    }
+                };
+                // 纯专注率 = 实际专注 ÷（专注 + 暂停），越低说明番茄被打断得越碎
+                let describe_pauses = |label: &str, totals: crate::db::PauseTotals| match totals.pure_focus_ratio() {
+                    None => format!("{}纯专注率 —", label),
+                    Some(ratio) => format!(
+                        "{}纯专注率 {:.0}%（暂停 {} 次，共 {} 分钟）",
+                        label,
+                        ratio * 100.0,
+                        totals.pauses,
+                        totals.paused_secs / 60
+                    ),
+                };
+                ui.label(describe_abandon("今日", today_counts.0));
+                ui.label(describe_abandon("本周", week_counts.0));
+                ui.label(describe_pauses("今日", today_counts.1));
+                ui.label(describe_pauses("本周", week_counts.1));
+                ui.label(format!("完成任务：今日 {} 项，本周 {} 项", today_counts.2, week_counts.2));
+                // 分心：今日 / 本周按关键词汇总的次数与时长
+                let [today_distractions, week_distractions] = self.distraction_totals.get_or_insert_with(|| {
+                    let (today, week) = crate::stats::today_and_week_ranges(beijing_now());
+                    let totals = |(from, to): (i64, i64)| {
+                        crate::db::open_and_init()
+                            .and_then(|conn| crate::db::distraction_totals(&conn, &beijing_rfc3339(from), &beijing_rfc3339(to)))
+                            .unwrap_or_default()
+                    };
+                    [totals(today), totals(week)]
+                });
+                for (label, rows) in [("今日", &*today_distractions), ("本周", &*week_distractions)] {
+                    if rows.is_empty() {
+                        continue;
+                    }
+                    let parts: Vec<String> = rows
+                        .iter()
+                        .map(|r| format!("{} {} 次 / {}", r.keyword, r.count, crate::review::format_hm(r.total_secs)))
+                        .collect();
+                    ui.label(format!("{}分心：{}", label, parts.join("，")));
+                }
+                // 按项目汇总：今日 / 本周各项目的专注时长与番茄数
+                let [today_projects, week_projects] = self.project_totals.get_or_insert_with(|| {
+                    let (today, week) = crate::stats::today_and_week_ranges(beijing_now());
+                    let totals = |(from, to): (i64, i64)| {
+                        crate::db::open_and_init()
+                            .and_then(|conn| crate::db::project_totals(&conn, &beijing_rfc3339(from), &beijing_rfc3339(to)))
+                            .unwrap_or_default()
+                    };
+                    [totals(today), totals(week)]
+                });
+                for (label, rows) in [("今日", &*today_projects), ("本周", &*week_projects)] {
+                    if rows.is_empty() {
+                        continue;
+                    }
+                    let parts: Vec<String> = rows
+                        .iter()
+                        .map(|r| format!("{} {} 分钟 / 🍅{}", r.name, r.total_secs / 60, r.sessions))
+                        .collect();
+                    ui.label(format!("{}按项目：{}", label, parts.join("，")));
+                }
+                // 日程：今日逐条列出计划与完成的番茄数，本周汇总
+                let ([today_plan, week_plan], week_done) = self.schedule_plan.get_or_insert_with(|| {
+                    let (today, week) = crate::stats::today_and_week_ranges(beijing_now());
+                    let progress = |(from, to): (i64, i64)| {
+                        crate::db::open_and_init()
+                            .and_then(|conn| crate::db::schedule_progress(&conn, &beijing_rfc3339(from), &beijing_rfc3339(to)))
+                            .unwrap_or_default()
+                    };
+                    let done = crate::db::open_and_init()
+                        .and_then(|conn| crate::db::completed_tasks(&conn, &beijing_rfc3339(week.0), &beijing_rfc3339(week.1)))
+                        .unwrap_or_default();
+                    ([progress(today), progress(week)], done)
+                });
+                if !today_plan.is_empty() {
+                    let parts: Vec<String> = today_plan
+                        .iter()
+                        .map(|r| {
+                            let mark = if r.done >= r.planned { "✓" } else { "" };
+                            format!(
+                                "{} {} {}/{}{}",
+                                r.start_at.get(11..16).unwrap_or(""),
+                                r.task,
+                                r.done.min(r.planned),
+                                r.planned,
+                                mark
+                            )
+                        })
+                        .collect();
+                    ui.label(format!("今日日程：{}", parts.join("，")));
+                }
+                if !week_plan.is_empty() {
+                    let planned: u32 = week_plan.iter().map(|r| r.planned).sum();
+                    let done: u32 = week_plan.iter().map(|r| r.done.min(r.planned)).sum();
+                    let kept = week_plan.iter().filter(|r| r.done >= r.planned).count();
+                    ui.label(format!(
+                        "本周日程：计划 {} 个番茄，按计划完成 {} 个（{}/{} 项日程完成）",
+                        planned,
+                        done,
+                        kept,
+                        week_plan.len()
+                    ));
+                }
+                if let Some(lead) = crate::planner::average_lead_secs(week_done) {
+                    ui.label(format!(
+                        "本周完成任务 {} 项，从添加日程到完成平均 {}",
+                        week_done.len(),
+                        crate::review::format_hm(lead)
+                    ));
+                }
+                self.ui_session_metrics(ui);
+                self.ui_hourly_distribution(ui);
+                ui.add_space(4.0);
+                if self.focus_history.is_empty() {
+                    ui.label("暂无记录。完成专注后这里会按时间显示任务、时长与番茄数。");
+                } else {
+                    // 深度块：连续专注（间隔不超过短休息 + 宽限）的汇总
+                    let max_gap = self.pomo.config.short_break_secs + crate::stats::DEEP_BLOCK_SLACK_SECS;
+                    let blocks = crate::stats::deep_blocks(self.focus_history.records(), max_gap);
+                    let (today, week) = crate::stats::today_and_week_ranges(beijing_now());
+                    let describe = |label: &str, (from, to): (i64, i64)| {
+                        let s = crate::stats::summarize_deep_blocks(&blocks, from, to);
+                        if s.count == 0 {
+                            format!("{}深度块 0 个", label)
+                        } else {
+                            format!(
+                                "{}深度块 {} 个，最长 {} 个番茄（{} 分钟）",
+                                label,
+                                s.count,
+                                s.longest_sessions,
+                                s.longest_focus_secs / 60
+                            )
+                        }
+                    };
+                    ui.label(describe("今日", today));
+                    ui.label(describe("本周", week));
+                    ui.add_space(6.0);
+                    self.ui_history_filter(ui);
+                    self.ui_time_share(ui);
+                    ui.label("按天分组，展开查看：完成时间 · 专注时长 · 番茄数(当天同任务累计) · 任务");
+                    ui.add_space(6.0);
+                    let query = self.history_query();
+                    let stale = self.history_rows.as_ref().map(|d| &d.query) != query.as_ref();
+                    if stale {
+                        self.history_rows = query.map(HistoryDays::new);
+                    }
+                    match &mut self.history_rows {
+                        Some(history) if !history.days.is_empty() => {
+                            let today = beijing_now().date_naive();
+                            egui::ScrollArea::vertical().max_height(280.0).show(ui, |ui| {
+                                Self::ui_history_days(
+                                    ui,
+                                    &palette,
+                                    &self.task_colors,
+                                    history,
+                                    today,
+                                    &mut self.history_note,
+                                );
+                            });
+                        }
+                        _ => {
+                            ui.label(egui::RichText::new("没有符合条件的记录").color(palette.text_dim));
+                        }
+                    }
+                    self.ui_record_note(ui);
+                }
+                ui.add_space(8.0);
+                self.ui_markdown_export(ui);
+                ui.add_space(4.0);
+                ui.horizontal(|ui| {
+                    if ui.button("刷新").clicked() {
+                        self.load_focus_history_from_db();
+                        self.invalidate_stats();
+                    }
+                    if ui.button("关闭").clicked() {
+                        self.show_statistics = false;
+                    }
+                });
+            });
+    }
+
+    /// 设置中的专注记录同步：选择同步文件夹或 WebDAV 地址与同步间隔
+    fn ui_data_sync_settings(&mut self, ui: &mut egui::Ui) {
+        ui.label("数据同步（专注记录，多台电脑共用一个快照文件）");
+        if !crate::policy::get().allows(Integration::DataSync) {
+            ui.label(egui::RichText::new("已被部署策略禁用").color(self.palette.text_dim));
+            return;
+        }
+        ui.horizontal(|ui| {
+            ui.label("位置：");
+            egui::ComboBox::from_id_salt("data_sync_target")
+                .selected_text(self.data_sync.target.label())
+                .show_ui(ui, |ui| {
+                    for target in [SyncTarget::Off, SyncTarget::Folder, SyncTarget::WebDav] {
+                        ui.selectable_value(&mut self.data_sync.target, target, target.label());
+                    }
+                });
+        });
+        match self.data_sync.target {
+            SyncTarget::Off => return,
+            SyncTarget::Folder => {
+                ui.horizontal(|ui| {
+                    ui.label("文件夹：");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.data_sync.folder)
+                            .desired_width(220.0)
+                            .hint_text("Dropbox / OneDrive 中的文件夹"),
+                    );
+                });
+            }
+            SyncTarget::WebDav => {
+                ui.horizontal(|ui| {
+                    ui.label("地址：");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.data_sync.url)
+                            .desired_width(220.0)
+                            .hint_text("https://dav.example.com/red-tomato"),
+                    );
+                });
+                ui.horizontal(|ui| {
+                    ui.label("用户：");
+                    ui.add(egui::TextEdit::singleline(&mut self.data_sync.username).desired_width(100.0));
+                    ui.label("密码：");
+                    ui.add(egui::TextEdit::singleline(&mut self.data_sync.password).password(true).desired_width(100.0));
+                });
+            }
+        }
+        ui.horizontal(|ui| {
+            ui.label("每");
+            ui.add(egui::DragValue::new(&mut self.data_sync.interval_minutes).range(1..=1440));
+            ui.label("分钟同步一次");
+            let enabled = self.data_sync_enabled() && !self.data_sync_runner.is_busy();
+            if ui.add_enabled(enabled, egui::Button::new("立即同步")).clicked() {
+                self.start_data_sync();
+            }
+        });
+        ui.label(
+            egui::RichText::new("同一条记录两边项目不同时以最后写入者为准；删除不会同步；数据库加密时不同步。")
+                .color(self.palette.text_dim),
+        );
+        if !self.data_sync_status.is_empty() {
+            ui.label(egui::RichText::new(&self.data_sync_status).color(self.palette.text_dim));
+        }
+    }
+
+    /// 设置中的数据加密：输入两次口令启用（已有记录改写为密文），或关闭加密恢复明文
+    fn ui_encryption_settings(&mut self, ui: &mut egui::Ui) {
+        let dim = self.palette.text_dim;
+        let form = &mut self.encryption_form;
+        egui::CollapsingHeader::new("数据加密").show(ui, |ui| {
+            if crate::crypto::is_enabled() {
+                ui.label("已启用：任务名与专注承诺以密文保存，启动时需输入口令。");
+                if ui.button("关闭加密").clicked() {
+                    form.message = Some(match crate::db::disable_encryption() {
+                        Ok(n) => format!("已关闭加密，{} 条记录恢复为明文", n),
+                        Err(e) => format!("关闭失败：{}", e),
+                    });
+                }
+            } else {
+                ui.label(
+                    egui::RichText::new("加密任务名与专注承诺（时间、时长不加密）。口令遗忘后数据无法恢复。").color(dim),
+                );
+                ui.horizontal(|ui| {
+                    ui.label("口令：");
+                    ui.add(egui::TextEdit::singleline(&mut form.passphrase).password(true).desired_width(140.0));
+                });
+                ui.horizontal(|ui| {
+                    ui.label("确认：");
+                    ui.add(egui::TextEdit::singleline(&mut form.confirm).password(true).desired_width(140.0));
+                });
+                let ready = !form.passphrase.is_empty() && form.passphrase == form.confirm;
+                if ui.add_enabled(ready, egui::Button::new("启用加密")).clicked() {
+                    form.message = Some(match crate::db::enable_encryption(&form.passphrase) {
+                        Ok(n) => format!("已启用加密，{} 条已有记录改写为密文", n),
+                        Err(e) => format!("启用失败：{}", e),
+                    });
+                    form.passphrase.clear();
+                    form.confirm.clear();
+                }
+            }
+            if let Some(msg) = &form.message {
+                ui.label(egui::RichText::new(msg).color(dim));
+            }
+        });
+    }
+
+    /// 设置中的数据库位置：把数据库移动到其他文件夹（如同步盘），或改用该文件夹中已有的数据库
+    fn ui_db_location_settings(&mut self, ui: &mut egui::Ui) {
+        let dim = self.palette.text_dim;
+        let form = &mut self.relocate_form;
+        egui::CollapsingHeader::new("数据库位置").show(ui, |ui| {
+            ui.label(egui::RichText::new(crate::db::db_path().to_string_lossy()).color(dim));
+            if let Some(reason) = crate::relocate::locked_reason() {
+                ui.label(egui::RichText::new(reason).color(dim));
+                return;
+            }
+            ui.horizontal(|ui| {
+                ui.label("文件夹：");
+                ui.add(
+                    egui::TextEdit::singleline(&mut form.folder)
+                        .desired_width(220.0)
+                        .hint_text("Dropbox / OneDrive 中的文件夹"),
+                );
+            });
+            ui.horizontal(|ui| {
+                let ready = !form.folder.trim().is_empty();
+                if ui.add_enabled(ready, egui::Button::new("移动数据库")).clicked() {
+                    form.message = Some(match crate::relocate::move_to(&form.folder) {
+                        Ok(path) => format!("已移动到 {}，原文件保留在原处", path.display()),
+                        Err(e) => e,
+                    });
+                }
+                if ui.add_enabled(ready, egui::Button::new("改用该位置的数据库")).clicked() {
+                    form.message = Some(match crate::relocate::use_existing(&form.folder) {
+                        Ok(path) => format!("已改用 {}", path.display()),
+                        Err(e) => e,
+                    });
+                }
+                if crate::db::db_location().1 == crate::db::DbLocation::Custom && ui.button("恢复默认位置").clicked() {
+                    form.message = Some(match crate::db::set_custom_db_path(None) {
+                        Ok(()) => format!("已恢复为 {}（不复制数据）", crate::db::db_path().display()),
+                        Err(e) => format!("恢复失败：{}", e),
+                    });
+                }
+            });
+            ui.label(
+                egui::RichText::new(format!(
+                    "也可用环境变量 {} 指定数据库文件。同步盘上请勿在两台电脑同时运行。",
+                    crate::db::DB_PATH_ENV
+                ))
+                .color(dim),
+            );
+            if let Some(msg) = &form.message {
+                ui.label(egui::RichText::new(msg).color(dim));
+            }
+        });
+    }
+
+    /// 统计窗口记录列表的筛选：任务搜索、时间范围、最短时长、项目
+    /// 统计窗口「平均指标」：每个工作日的番茄数、平均每次专注时长（对比设定时长）、每天第一个番茄开始时刻的中位数与完成率，范围可选
+    fn ui_session_metrics(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("平均指标").id_salt("session_metrics").show(ui, |ui| {
+            let before = self.metrics_days;
+            ui.horizontal(|ui| {
+                ui.label("范围");
+                for (days, label) in HOURLY_RANGES {
+                    ui.selectable_value(&mut self.metrics_days, days, label);
+                }
+            });
+            if self.metrics_days != before {
+                self.session_metrics = None;
+            }
+            let days = self.metrics_days;
+            let m = self.session_metrics.get_or_insert_with(|| {
+                let today = beijing_now().date_naive();
+                let to = beijing_day_start_rfc3339(today + chrono::Duration::days(1));
+                // 「全部」以空字符串为下界（RFC3339 字符串比较）
+                let from = if days > 0 {
+                    beijing_day_start_rfc3339(today - chrono::Duration::days(days as i64 - 1))
+                } else {
+                    String::new()
+                };
+                crate::db::open_and_init()
+                    .and_then(|conn| {
+                        let (sessions, _, avg_session_secs) = crate::db::session_summary(&conn, &from, &to)?;
+                        let (completed, abandoned) = crate::db::count_by_status(&conn, &from, &to)?;
+                        let firsts = crate::db::first_focus_per_day(&conn, &from, &to)?;
+                        let (breaks_taken, breaks_skipped) = crate::db::count_breaks(&conn, &from, &to)?;
+                        let hours_split = if self.working_hours.enabled {
+                            let spans = crate::db::focus_spans(&conn, &from, &to)?;
+                            Some(crate::stats::split_by_working_hours(&spans, &self.working_hours))
+                        } else {
+                            None
+                        };
+                        Ok(crate::stats::SessionMetrics {
+                            working_days: crate::db::count_focus_days(&conn, &from, &to)?,
+                            sessions,
+                            avg_session_secs,
+                            median_first_start: crate::stats::median_start_minute(&firsts),
+                            completed,
+                            abandoned,
+                            breaks_taken,
+                            breaks_skipped,
+                            hours_split,
+                        })
+                    })
+                    .unwrap_or_default()
+            });
+            let dim = self.palette.text_dim;
+            let Some(per_day) = m.pomodoros_per_day() else {
+                ui.label(egui::RichText::new("该范围内没有完成的专注").color(dim));
+                return;
+            };
+            egui::Grid::new("session_metrics_grid").num_columns(2).show(ui, |ui| {
+                ui.label("每个工作日");
+                ui.label(format!("🍅{:.1}（{} 天共 {} 个）", per_day, m.working_days, m.sessions));
+                ui.end_row();
+                ui.label("平均每次专注");
+                let planned = self.pomo.config.focus_secs;
+                ui.label(format!(
+                    "{}，设定 {}（{:.0}%）",
+                    crate::review::format_hm(m.avg_session_secs),
+                    crate::review::format_hm(planned),
+                    m.avg_session_secs as f64 * 100.0 / planned.max(1) as f64
+                ))
+                .on_hover_text("实际时长含加时与推迟休息；设定为当前的专注时长");
+                ui.end_row();
+                ui.label("第一个番茄开始");
+                ui.label(match m.median_first_start {
+                    Some(minute) => format!("{:02}:{:02}（中位数）", minute / 60, minute % 60),
+                    None => "—".to_string(),
+                });
+                ui.end_row();
+                ui.label("完成率");
+                ui.label(match m.completion_rate() {
+                    Some(rate) => format!("{:.0}%（完成 {}，放弃 {}）", rate * 100.0, m.completed, m.abandoned),
+                    None => "—".to_string(),
+                });
+                ui.end_row();
+                ui.label("跳过休息");
+                ui.label(match m.break_skip_rate() {
+                    Some(rate) => format!("{:.0}%（休息 {}，跳过 {}）", rate * 100.0, m.breaks_taken, m.breaks_skipped),
+                    None => "—".to_string(),
+                });
+                ui.end_row();
+                if let Some((inside, outside)) = m.hours_split {
+                    ui.label("工作时间内 / 外");
+                    ui.label(format!(
+                        "{} / {}（时间外占 {:.0}%）",
+                        crate::review::format_hm(inside),
+                        crate::review::format_hm(outside),
+                        outside as f64 * 100.0 / (inside + outside).max(1) as f64
+                    ))
+                    .on_hover_text("按设置中的工作时间拆分每次专注，跨越上下班时刻的专注分别计入");
+                    ui.end_row();
+                }
+            });
+        });
+    }
+
+    /// 统计窗口「时段分布」：按一天中的小时汇总完成的专注时长（`db::hourly_totals`），范围可选，标出高峰时段
+    fn ui_hourly_distribution(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("时段分布").id_salt("hourly_distribution").show(ui, |ui| {
+            let before = self.hourly_days;
+            ui.horizontal(|ui| {
+                ui.label("范围");
+                for (days, label) in HOURLY_RANGES {
+                    ui.selectable_value(&mut self.hourly_days, days, label);
+                }
+            });
+            if self.hourly_days != before {
+                self.hourly_totals = None;
+            }
+            let days = self.hourly_days;
+            let totals = self.hourly_totals.get_or_insert_with(|| {
+                let today = beijing_now().date_naive();
+                let to = beijing_day_start_rfc3339(today + chrono::Duration::days(1));
+                let from = (days > 0).then(|| beijing_day_start_rfc3339(today - chrono::Duration::days(days as i64 - 1)));
+                crate::db::open_and_init()
+                    .and_then(|conn| crate::db::hourly_totals(&conn, from.as_deref(), &to))
+                    .unwrap_or([(0, 0); 24])
+            });
+            if totals.iter().all(|(secs, _)| *secs == 0) {
+                ui.label(egui::RichText::new("该范围内没有完成的专注").color(self.palette.text_dim));
+                return;
+            }
+            paint_hourly_bars(ui, totals, &self.palette);
+            let peak = (0..24).max_by_key(|&h| totals[h].0).unwrap_or(0);
+            ui.label(format!(
+                "高峰时段 {:02}:00–{:02}:00，共专注 {}（🍅{}）；按完成时间所在小时统计",
+                peak,
+                peak + 1,
+                crate::review::format_hm(totals[peak].0),
+                totals[peak].1
+            ));
+        });
+    }
+
+    /// 统计窗口「时间占比」：筛选范围内各任务 / 项目的专注时长占比环形图与图例，
+    /// 点击扇区或图例把记录列表筛选到该任务（搜索）或项目
+    fn ui_time_share(&mut self, ui: &mut egui::Ui) {
+        let Some(query) = self.history_query() else { return };
+        egui::CollapsingHeader::new("时间占比").id_salt("time_share").show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut self.share_by_project, false, "按任务");
+                ui.selectable_value(&mut self.share_by_project, true, "按项目");
+            });
+            let by_project = self.share_by_project;
+            let stale = self
+                .share_chart
+                .as_ref()
+                .is_none_or(|c| c.range != query.range || c.by_project != by_project);
+            if stale {
+                self.share_chart = Some(ShareChart::load(query.range.clone(), by_project));
+            }
+            let Some(chart) = self.share_chart.as_ref() else { return };
+            if chart.slices.is_empty() {
+                ui.label(egui::RichText::new("该范围内没有完成的专注").color(self.palette.text_dim));
+                return;
+            }
+            // 任务按任务颜色、已指定颜色的项目按项目颜色，其余（含「其他」）按序取主题色
+            let fallback = share_colors(&self.palette);
+            let colors: Vec<egui::Color32> = chart
+                .slices
+                .iter()
+                .enumerate()
+                .map(|(i, slice)| match &slice.filter {
+                    Some(ShareFilter::Task(task)) => self.task_colors.color(task, None),
+                    Some(ShareFilter::Project(id)) => self.task_colors.project(*id).unwrap_or(fallback[i % fallback.len()]),
+                    None => fallback[i % fallback.len()],
+                })
+                .collect();
+            let total: i64 = chart.slices.iter().map(|s| s.secs).sum();
+            let mut picked = None;
+            ui.horizontal(|ui| {
+                picked = paint_donut(ui, &chart.slices, total, &colors, &self.palette);
+                ui.vertical(|ui| {
+                    for (i, slice) in chart.slices.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            let (rect, _) = ui.allocate_exact_size(egui::vec2(10.0, 10.0), egui::Sense::hover());
+                            ui.painter().rect_filled(rect, 2.0, colors[i % colors.len()]);
+                            let text = format!(
+                                "{} {:.0}% · {}",
+                                slice.label,
+                                slice.secs as f64 * 100.0 / total.max(1) as f64,
+                                crate::review::format_hm(slice.secs)
+                            );
+                            let clickable = slice.filter.is_some();
+                            if ui.add_enabled(clickable, egui::Button::new(text).frame(false)).clicked() {
+                                picked = Some(i);
+                            }
+                        });
+                    }
+                });
+            });
+            let filter = picked.and_then(|i| chart.slices.get(i)).and_then(|s| s.filter.clone());
+            match filter {
+                Some(ShareFilter::Task(task)) => self.history_filter.search = task,
+                Some(ShareFilter::Project(id)) => self.history_filter.project_id = Some(id),
+                None => {}
+            }
+        });
+    }
+
+    fn ui_history_filter(&mut self, ui: &mut egui::Ui) {
+        let project_label = match self.history_filter.project_id {
+            None => "全部".to_string(),
+            Some(id) => self.project_name(id).to_string(),
+        };
+        let f = &mut self.history_filter;
+        ui.horizontal(|ui| {
+            ui.label("搜索：");
+            ui.add(
+                egui::TextEdit::singleline(&mut f.search)
+                    .desired_width(160.0)
+                    .hint_text("任务名包含…"),
+            );
+            ui.label("至少");
+            ui.add(egui::DragValue::new(&mut f.min_minutes).range(0..=180).suffix(" 分钟"));
+            ui.checkbox(&mut f.include_archive, "包含归档");
+        });
+        ui.horizontal(|ui| {
+            ui.label("范围：");
+            for range in HistoryRange::ALL {
+                if ui.selectable_label(f.range == range, range.label()).clicked() {
+                    f.range = range;
+                    if range == HistoryRange::Custom && f.custom_from.is_empty() {
+                        let today = beijing_now().format("%Y-%m-%d").to_string();
+                        f.custom_from = today.clone();
+                        f.custom_to = today;
+                    }
+                }
+            }
+        });
+        if f.range == HistoryRange::Custom {
+            ui.horizontal(|ui| {
+                ui.add(egui::TextEdit::singleline(&mut f.custom_from).desired_width(90.0));
+                ui.label("至");
+                ui.add(egui::TextEdit::singleline(&mut f.custom_to).desired_width(90.0));
+                ui.label(egui::RichText::new("YYYY-MM-DD").color(self.palette.text_dim));
+            });
+        }
+        ui.horizontal(|ui| {
+            ui.label("项目：");
+            egui::ComboBox::from_id_salt("history_project_filter")
+                .selected_text(project_label)
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut f.project_id, None, "全部");
+                    for project in &self.projects {
+                        ui.selectable_value(&mut f.project_id, Some(project.id), project.name.as_str());
+                    }
+                });
+        });
+    }
+
+    /// 项目管理窗口：新建项目、设置项目颜色、删除项目（其记录归入「未分类」）
+    fn ui_projects(&mut self, ctx: &egui::Context) {
+        let Some(mut view) = self.projects_view.take() else { return };
+        let mut open = true;
+        let mut changed = false;
+        egui::Window::new("项目")
+            .default_width(300.0)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut view.new_name)
+                            .desired_width(180.0)
+                            .hint_text("新项目名称"),
+                    );
+                    let name = view.new_name.trim().to_string();
+                    if ui.add_enabled(!name.is_empty(), egui::Button::new("新建")).clicked() {
+                        match crate::db::open_and_init().and_then(|conn| crate::db::insert_project(&conn, &name)) {
+                            Ok(id) => {
+                                self.current_project = id;
+                                view.new_name.clear();
+                                view.message = None;
+                                changed = true;
+                            }
+                            Err(_) => view.message = Some(format!("无法新建「{}」：名称已存在？", name)),
+                        }
+                    }
+                });
+                ui.add_space(6.0);
+                egui::ScrollArea::vertical().max_height(240.0).show(ui, |ui| {
+                    for project in &self.projects {
+                        ui.horizontal(|ui| {
+                            let color = self.task_colors.project(project.id).unwrap_or(self.palette.text_dim);
+                            if let Some(picked) = crate::labels::color_menu(ui, color, "项目颜色：该项目中未单独指定颜色的任务使用") {
+                                let hex = picked.map(crate::labels::to_hex).unwrap_or_default();
+                                match crate::db::open_and_init().and_then(|conn| crate::db::set_project_color(&conn, project.id, &hex)) {
+                                    Ok(()) => changed = true,
+                                    Err(e) => view.message = Some(format!("保存颜色失败：{}", e)),
+                                }
+                            }
+                            ui.label(project.name.as_str());
+                            if project.id == crate::db::UNCATEGORIZED_PROJECT_ID {
+                                return;
+                            }
+                            if ui.small_button("删除").on_hover_text("该项目的记录将归入「未分类」").clicked() {
+                                let result = crate::db::open_and_init()
+                                    .and_then(|mut conn| crate::db::delete_project(&mut conn, project.id));
+                                match result {
+                                    Ok(()) => changed = true,
+                                    Err(e) => view.message = Some(format!("删除失败：{}", e)),
+                                }
+                            }
+                        });
+                    }
+                });
+                if let Some(msg) = &view.message {
+                    ui.label(egui::RichText::new(msg).color(self.palette.text_dim));
+                }
+            });
+        if changed {
+            self.load_projects();
+            // 删除项目会改写历史记录的归属
+            self.load_focus_history_from_db();
+            self.recent_tasks = None;
+            self.invalidate_stats();
         }
-    }
-
-    fn save(&mut self, storage: &mut dyn eframe::Storage) {
-        let p = PersistedState {
-            current_task: self.current_task.clone(),
-            phase: phase_to_str(self.pomo.phase).to_string(),
-            state: state_to_str(self.pomo.state).to_string(),
-            remaining_secs: self.pomo.remaining_secs,
-            phase_total_secs: self.pomo.phase_total_secs,
-            completed_pomodoros: self.pomo.completed_pomodoros,
-        };
-        if let Ok(json) = serde_json::to_string(&p) {
-            storage.set_string(STORAGE_KEY_STATE, json);
+        if open {
+            self.projects_view = Some(view);
         }
     }
-}
 
-impl RedTomatoApp {
-    /// 关于窗口
-    fn ui_about(&mut self, ctx: &egui::Context) {
-        use white_text_theme::TEXT_DIM;
-        egui::Window::new("关于")
-            .collapsible(false)
-            .resizable(false)
-            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+    /// 数据清除窗口：按项目 / 日期区间删除记录，或清空全部数据；需输入确认短语才能执行
+    fn ui_purge(&mut self, ctx: &egui::Context) {
+        use crate::purge::{CONFIRM_PHRASE, PurgeScope};
+        let Some(mut view) = self.purge_view.take() else { return };
+        let mut open = true;
+        let mut changed = false;
+        let mut purged_everything = false;
+        let mut renamed = None;
+        let dim = self.palette.text_dim;
+        egui::Window::new("数据管理")
+            .default_width(380.0)
+            .open(&mut open)
             .show(ctx, |ui| {
-                ui.vertical_centered(|ui| {
-                    ui.add_space(12.0);
-                    ui.label(
-                        egui::RichText::new("Red Tomato 红番茄")
-                            .size(18.0)
-                            .color(egui::Color32::from_rgb(TEXT_DIM.0, TEXT_DIM.1, TEXT_DIM.2)),
-                    );
+                egui::CollapsingHeader::new("合并另一台电脑的数据").show(ui, |ui| {
                     ui.label(
-                        egui::RichText::new("科学工作法")
-                            .size(14.0)
-                            .color(egui::Color32::from_rgb(TEXT_DIM.0, TEXT_DIM.1, TEXT_DIM.2)),
+                        egui::RichText::new("选择另一台电脑的 red_tomato.db（或归档库），按任务、完成时间与时长去重后并入本机；项目按名称对应。")
+                            .color(dim),
                     );
-                    ui.add_space(8.0);
-                    let db_path = crate::db::db_path();
+                    ui.horizontal(|ui| {
+                        ui.label("文件：");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut view.merge_path)
+                                .desired_width(220.0)
+                                .hint_text("/path/to/red_tomato.db"),
+                        );
+                        let ready = !view.merge_path.trim().is_empty();
+                        if ui.add_enabled(ready, egui::Button::new("合并")).clicked() {
+                            view.merge_message = Some(
+                                match crate::merge::merge_database(std::path::Path::new(view.merge_path.trim())) {
+                                    Ok(outcome) => {
+                                        changed |= outcome.merged > 0 || outcome.projects > 0;
+                                        outcome.describe()
+                                    }
+                                    Err(e) => format!("合并失败：{}", e),
+                                },
+                            );
+                        }
+                    });
+                    if let Some(msg) = &view.merge_message {
+                        ui.label(egui::RichText::new(msg).color(dim));
+                    }
+                });
+                egui::CollapsingHeader::new("重命名任务").show(ui, |ui| {
                     ui.label(
-                        egui::RichText::new("数据 (SQLite)：")
-                            .size(12.0)
-                            .color(egui::Color32::from_rgb(TEXT_DIM.0, TEXT_DIM.1, TEXT_DIM.2)),
+                        egui::RichText::new("把全部专注记录（含归档）、日程与任务完成中的任务名改为新名称；新名称已有记录时两者的统计合并。")
+                            .color(dim),
                     );
+                    egui::Grid::new("rename_task").num_columns(2).show(ui, |ui| {
+                        ui.label("原任务名：");
+                        ui.add(egui::TextEdit::singleline(&mut view.rename_from).desired_width(220.0));
+                        ui.end_row();
+                        ui.label("新任务名：");
+                        ui.add(egui::TextEdit::singleline(&mut view.rename_to).desired_width(220.0));
+                        ui.end_row();
+                    });
+                    let from = view.rename_from.trim().to_string();
+                    let to = view.rename_to.trim().to_string();
+                    let ready = !from.is_empty() && !to.is_empty() && from != to;
+                    if ui.add_enabled(ready, egui::Button::new("重命名")).clicked() {
+                        let result = crate::db::open_and_init().and_then(|conn| crate::db::rename_task(&conn, &from, &to));
+                        view.rename_message = Some(match result {
+                            Ok(outcome) if outcome.renamed == 0 => format!("没有任务名为「{}」的记录", from),
+                            Ok(outcome) => {
+                                changed = true;
+                                renamed = Some((from.clone(), to.clone()));
+                                view.rename_from.clear();
+                                view.rename_to.clear();
+                                let merged = if outcome.merged { format!("，已与「{}」合并", to) } else { String::new() };
+                                format!("已将「{}」改为「{}」，共 {} 条{}", from, to, outcome.renamed, merged)
+                            }
+                            Err(e) => format!("重命名失败：{}", e),
+                        });
+                    }
+                    if let Some(msg) = &view.rename_message {
+                        ui.label(egui::RichText::new(msg).color(dim));
+                    }
+                });
+                egui::CollapsingHeader::new("合并重复任务").show(ui, |ui| {
                     ui.label(
-                        egui::RichText::new(db_path.to_string_lossy().as_ref())
-                            .size(11.0)
-                            .color(egui::Color32::from_rgb(TEXT_DIM.0, TEXT_DIM.1, TEXT_DIM.2)),
+                        egui::RichText::new("勾选同一任务的几种写法（如「写周报」「周报」），统一改为一个名称，统计随之合并。")
+                            .color(dim),
                     );
-                    ui.add_space(16.0);
-                    if ui.button("确定").clicked() {
-                        self.show_about = false;
+                    let usage = view.task_usage.get_or_insert_with(|| {
+                        crate::db::open_and_init()
+                            .and_then(|conn| crate::db::task_usage(&conn))
+                            .db_err("读取任务列表")
+                            .unwrap_or_default()
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("筛选：");
+                        ui.add(egui::TextEdit::singleline(&mut view.merge_filter).desired_width(160.0));
+                    });
+                    let filter = view.merge_filter.trim().to_lowercase();
+                    egui::ScrollArea::vertical().id_salt("merge_tasks").max_height(160.0).show(ui, |ui| {
+                        for row in usage.iter().filter(|r| filter.is_empty() || r.task.to_lowercase().contains(&filter)) {
+                            let mut checked = view.merge_selected.contains(&row.task);
+                            let text = format!("{}（{} 条，{}）", row.task, row.records, format_compact_hm(row.total_secs));
+                            if ui.checkbox(&mut checked, text).changed() {
+                                if checked {
+                                    view.merge_selected.push(row.task.clone());
+                                } else {
+                                    view.merge_selected.retain(|t| *t != row.task);
+                                }
+                                // 合并后的名称默认取勾选中记录最多的写法
+                                view.merge_target = usage
+                                    .iter()
+                                    .find(|r| view.merge_selected.contains(&r.task))
+                                    .map(|r| r.task.clone())
+                                    .unwrap_or_default();
+                            }
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("合并为：");
+                        ui.add(egui::TextEdit::singleline(&mut view.merge_target).desired_width(200.0));
+                    });
+                    let target = view.merge_target.trim().to_string();
+                    let selected: Vec<&crate::db::TaskUsageRow> =
+                        usage.iter().filter(|r| view.merge_selected.contains(&r.task)).collect();
+                    let changing: Vec<&&crate::db::TaskUsageRow> = selected.iter().filter(|r| r.task != target).collect();
+                    let ready = selected.len() >= 2 && !target.is_empty();
+                    if ready {
+                        let rows: u32 = changing.iter().map(|r| r.rows()).sum();
+                        let records: u32 = changing.iter().map(|r| r.records).sum();
+                        let total_records: u32 = selected.iter().map(|r| r.records).sum();
+                        let total_secs: i64 = selected.iter().map(|r| r.total_secs).sum();
+                        ui.label(format!(
+                            "将改写 {} 行（专注记录 {} 条，日程与任务完成 {} 条）；合并后「{}」共 {} 条记录，{}",
+                            rows,
+                            records,
+                            rows - records,
+                            target,
+                            total_records,
+                            format_compact_hm(total_secs)
+                        ));
+                    } else {
+                        ui.label(egui::RichText::new("请勾选至少两个任务名并填写合并后的名称").color(dim));
+                    }
+                    if ui.add_enabled(ready, egui::Button::new("合并任务")).clicked() {
+                        let from: Vec<String> = changing.iter().map(|r| r.task.clone()).collect();
+                        let result = crate::db::open_and_init().and_then(|conn| crate::db::merge_tasks(&conn, &from, &target));
+                        view.merge_tasks_message = Some(match result {
+                            Ok(outcome) => {
+                                changed = true;
+                                if from.contains(&self.current_task) {
+                                    renamed = Some((self.current_task.clone(), target.clone()));
+                                }
+                                format!("已将 {} 个写法合并为「{}」，共改写 {} 行", from.len(), target, outcome.renamed)
+                            }
+                            Err(e) => format!("合并失败：{}", e),
+                        });
+                        view.task_usage = None;
+                        view.merge_selected.clear();
+                        view.merge_target.clear();
+                    }
+                    if let Some(msg) = &view.merge_tasks_message {
+                        ui.label(egui::RichText::new(msg).color(dim));
                     }
                 });
-            });
-    }
-
-    /// 统计窗口：按完成时间逆序、同任务番茄数累计、番茄数从 1 开始
-    fn ui_statistics(&mut self, ctx: &egui::Context) {
-        use white_text_theme::TEXT_DIM;
-        egui::Window::new("统计 · 专注记录")
-            .default_width(460.0)
-            .default_height(320.0)
-            .show(ctx, |ui| {
-                ui.label("数据保存在 SQLite，路径见「关于」；复制该目录即可迁移。");
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.selectable_value(&mut view.everything, false, "按范围删除");
+                    ui.selectable_value(&mut view.everything, true, "清空全部数据");
+                });
                 ui.add_space(4.0);
-                if self.focus_history.is_empty() {
-                    ui.label("暂无记录。完成专注后这里会按时间显示任务、时长与番茄数。");
+                let scope = if view.everything {
+                    ui.label("将删除数据库与归档库（全部专注记录、项目、回顾日志、计划、日历、通知摘要、精力自评）、未写入的预写日志、进行中专注的会话日志、导出的报告与 OBS 输出文件。设置不受影响。");
+                    None
                 } else {
-                    ui.label("完成时间 · 专注时长 · 番茄数(同任务累计) · 任务");
-                    ui.add_space(6.0);
-                    let rows = Self::focus_rows_sorted_with_cumulative_tomatoes(&self.focus_history);
-                    egui::ScrollArea::vertical()
-                        .max_height(280.0)
-                        .show(ui, |ui| {
-                        for (r, tomato_display) in rows {
-                            let mins = r.duration_secs / 60;
-                            let secs = r.duration_secs % 60;
-                            let duration = format!("{:02}:{:02}", mins, secs);
-                            let completed = r.completed_at.chars().take(19).collect::<String>();
-                            ui.horizontal(|ui| {
-                                ui.label(
-                                    egui::RichText::new(completed.as_str())
-                                        .color(egui::Color32::from_rgb(TEXT_DIM.0, TEXT_DIM.1, TEXT_DIM.2))
-                                        .size(12.0),
-                                );
-                                ui.label(" · ");
-                                ui.label(duration);
-                                ui.label(" · ");
-                                ui.label(format!("🍅{}", tomato_display));
-                                ui.label(" · ");
-                                ui.label(if r.task.is_empty() { "(无任务)" } else { r.task.as_str() });
+                    ui.horizontal(|ui| {
+                        ui.label("项目：");
+                        let selected = match view.project_id {
+                            None => "不限".to_string(),
+                            Some(id) => self.project_name(id).to_string(),
+                        };
+                        egui::ComboBox::from_id_salt("purge_project")
+                            .selected_text(selected)
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut view.project_id, None, "不限");
+                                for project in &self.projects {
+                                    ui.selectable_value(&mut view.project_id, Some(project.id), project.name.as_str());
+                                }
                             });
+                    });
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut view.by_date, "日期：");
+                        ui.add_enabled(view.by_date, egui::TextEdit::singleline(&mut view.from).desired_width(90.0));
+                        ui.label("至");
+                        ui.add_enabled(view.by_date, egui::TextEdit::singleline(&mut view.to).desired_width(90.0));
+                    });
+                    ui.checkbox(&mut view.include_reports, "同时删除导出的全部报告");
+                    let parse = |s: &str| NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d").ok();
+                    let days = if view.by_date {
+                        match (parse(&view.from), parse(&view.to)) {
+                            (Some(first), Some(last)) if first <= last => Some(Some((first, last))),
+                            _ => None,
+                        }
+                    } else {
+                        Some(None)
+                    };
+                    match days {
+                        None => {
+                            ui.label(egui::RichText::new("日期格式为 YYYY-MM-DD，且开始不晚于结束").color(dim));
+                            None
+                        }
+                        Some(days) => {
+                            let scope = PurgeScope {
+                                project_id: view.project_id,
+                                days,
+                                include_reports: view.include_reports,
+                            };
+                            if scope.is_empty() {
+                                ui.label(egui::RichText::new("请选择项目或日期区间").color(dim));
+                                None
+                            } else {
+                                let stale = view.preview.as_ref().is_none_or(|(s, _)| *s != scope);
+                                if stale {
+                                    let count = crate::purge::preview(&scope).unwrap_or(0);
+                                    view.preview = Some((scope.clone(), count));
+                                }
+                                let count = view.preview.as_ref().map_or(0, |(_, n)| *n);
+                                let mut note = format!("将删除 {} 条专注记录（含放弃记录）", count);
+                                match (scope.project_id, scope.days) {
+                                    (None, Some(_)) => note.push_str("，以及区间内的中断、回顾日志、计划、日历、通知摘要与精力自评"),
+                                    (Some(id), None) if id != crate::db::UNCATEGORIZED_PROJECT_ID => note.push_str("，并删除该项目"),
+                                    _ => {}
+                                }
+                                ui.label(note);
+                                Some(scope)
+                            }
+                        }
+                    }
+                };
+                ui.label(egui::RichText::new("写入外部日记笔记的内容不会被删除。删除后无法恢复。").color(dim));
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    ui.label(format!("输入「{}」以确认：", CONFIRM_PHRASE));
+                    ui.add(egui::TextEdit::singleline(&mut view.confirm).desired_width(100.0));
+                });
+                let ready = view.confirm.trim() == CONFIRM_PHRASE && (view.everything || scope.is_some());
+                if ui.add_enabled(ready, egui::Button::new("删除")).clicked() {
+                    let result = match &scope {
+                        Some(scope) => crate::purge::purge(scope),
+                        None => crate::purge::purge_everything(),
+                    };
+                    view.message = Some(match result {
+                        Ok(outcome) => {
+                            changed = true;
+                            purged_everything = view.everything;
+                            outcome.describe()
                         }
+                        Err(e) => format!("删除失败：{}", e),
                     });
+                    view.confirm.clear();
+                    view.preview = None;
                 }
-                ui.add_space(8.0);
-                ui.horizontal(|ui| {
-                    if ui.button("刷新").clicked() {
-                        self.load_focus_history_from_db();
+                if let Some(msg) = &view.message {
+                    ui.label(egui::RichText::new(msg).color(dim));
+                }
+            });
+        if let Some((from, to)) = renamed.filter(|(from, _)| *from == self.current_task) {
+            self.current_task = to;
+        }
+        if changed {
+            self.after_data_change(purged_everything);
+        }
+        if open {
+            self.purge_view = Some(view);
+        }
+    }
+
+    /// 清除或合并数据后重新加载历史与项目并丢弃各类缓存；清空全部时一并清掉当前任务与承诺
+    fn after_data_change(&mut self, everything: bool) {
+        if everything {
+            self.current_task.clear();
+            self.current_commitment.clear();
+            self.focus_history.replace(Vec::new(), beijing_now());
+            self.recovered_session = None;
+        }
+        self.load_projects();
+        self.load_focus_history_from_db();
+        self.history_filter.project_id = None;
+        self.recent_tasks = None;
+        self.energy_recent = None;
+        self.invalidate_stats();
+        self.audit = None;
+        self.review = None;
+        self.daily_review = None;
+        self.planner = None;
+    }
+
+    /// 统计窗口中的 Markdown 导出：选择日/周/月与具体哪一期，复制到剪贴板或保存到报告目录
+    fn ui_markdown_export(&mut self, ui: &mut egui::Ui) {
+        use crate::review::ReportRange;
+        let today = beijing_now().date_naive();
+        let (first, last) = self.export_range.period(today, self.export_offset);
+        ui.horizontal(|ui| {
+            ui.label("导出：");
+            for range in ReportRange::ALL {
+                if ui.selectable_label(self.export_range == range, range.label()).clicked() {
+                    self.export_range = range;
+                    self.export_offset = 0;
+                }
+            }
+            if ui.small_button("◀").clicked() {
+                self.export_offset -= 1;
+            }
+            let period = if first == last {
+                first.format("%Y-%m-%d").to_string()
+            } else {
+                format!("{} ~ {}", first.format("%m-%d"), last.format("%m-%d"))
+            };
+            ui.label(period);
+            if ui.small_button("▶").clicked() {
+                self.export_offset += 1;
+            }
+        });
+        ui.horizontal(|ui| {
+            let copy = ui.button("复制 Markdown").clicked();
+            let save = ui.button("保存为文件").clicked();
+            if copy || save {
+                let md = crate::db::open_and_init().and_then(|conn| crate::review::period_markdown(&conn, first, last));
+                self.export_message = Some(match md {
+                    Ok(md) if copy => {
+                        ui.ctx().copy_text(md);
+                        "已复制到剪贴板".to_string()
                     }
-                    if ui.button("关闭").clicked() {
-                        self.show_statistics = false;
+                    Ok(md) => {
+                        let file_name = format!("report-{}-{}.md", first.format("%Y%m%d"), last.format("%Y%m%d"));
+                        match crate::review::write_report(&file_name, &md) {
+                            Ok(path) => format!("已保存：{}", path.display()),
+                            Err(e) => format!("保存失败：{}", e),
+                        }
                     }
+                    Err(e) => format!("生成失败：{}", e),
                 });
-            });
+            }
+        });
+        ui.horizontal(|ui| {
+            let save = ui.button("生成分享图").clicked();
+            let copy = ui.button("复制分享图").clicked();
+            if save || copy {
+                let title = match self.export_range {
+                    crate::review::ReportRange::Day => "专注日报",
+                    crate::review::ReportRange::Week => "专注周报",
+                    crate::review::ReportRange::Month => "专注月报",
+                };
+                let image = crate::db::open_and_init()
+                    .and_then(|conn| crate::share_card::ShareCard::load(&conn, title, first, last))
+                    .map_err(|e| e.to_string())
+                    .and_then(|card| card.render(self.fonts.current_path(), &self.palette));
+                self.export_message = Some(match image {
+                    Ok(image) if copy => {
+                        ui.ctx().copy_image(image);
+                        "分享图已复制到剪贴板".to_string()
+                    }
+                    Ok(image) => {
+                        let file_name = format!("share-{}-{}.png", first.format("%Y%m%d"), last.format("%Y%m%d"));
+                        match crate::share_card::encode_png(&image)
+                            .and_then(|png| crate::review::write_report(&file_name, png).map_err(|e| e.to_string()))
+                        {
+                            Ok(path) => format!("已保存：{}", path.display()),
+                            Err(e) => format!("保存失败：{}", e),
+                        }
+                    }
+                    Err(e) => format!("生成失败：{}", e),
+                });
+            }
+        });
+        if let Some(msg) = &self.export_message {
+            ui.label(egui::RichText::new(msg).color(self.palette.text_dim));
+        }
     }
 
     /// 按完成时间逆序排列，并计算同任务番茄数累计（番茄数从 1 开始，0 按 1 计）
+    /// 统计窗口记录列表：每天一个折叠区（标题为当天小计），展开时才加载该天的记录，最近一天默认展开
+    fn ui_history_days(
+        ui: &mut egui::Ui,
+        palette: &Palette,
+        colors: &TaskColors,
+        history: &mut HistoryDays,
+        today: NaiveDate,
+        note: &mut Option<RecordNote>,
+    ) {
+        for i in 0..history.days.len() {
+            let total = &history.days[i];
+            let day = total.day.clone();
+            let header = format!(
+                "{} · {}🍅 · {}",
+                history_day_label(&day, today),
+                total.sessions,
+                format_compact_hm(total.total_secs)
+            );
+            egui::CollapsingHeader::new(header)
+                .id_salt(("history_day", &day))
+                .default_open(i == 0)
+                .show(ui, |ui| {
+                    let page = history.page(&day);
+                    for (entry, tomato_display) in Self::focus_rows_sorted_with_cumulative_tomatoes(&page.records) {
+                        Self::ui_history_row(ui, palette, colors, entry, tomato_display, note);
+                    }
+                    // 「加载更多」进入可视区域时取该天的下一页
+                    if !page.exhausted {
+                        let more = ui.label(egui::RichText::new("加载更多…").color(palette.text_dim));
+                        if ui.is_rect_visible(more.rect) {
+                            page.load_more();
+                            ui.ctx().request_repaint();
+                        }
+                    }
+                });
+        }
+    }
+
+    fn ui_history_row(
+        ui: &mut egui::Ui,
+        palette: &Palette,
+        colors: &TaskColors,
+        entry: &HistoryEntry,
+        tomato_display: u32,
+        note: &mut Option<RecordNote>,
+    ) {
+        let r = &entry.record;
+        let mins = r.duration_secs / 60;
+        let secs = r.duration_secs % 60;
+        let duration = format!("{:02}:{:02}", mins, secs);
+        // 同一天内只显示时分秒
+        let completed = r.completed_at.get(11..19).unwrap_or(&r.completed_at).to_string();
+        ui.horizontal(|ui| {
+            ui.label(
+                egui::RichText::new(completed.as_str())
+                    .color(palette.text_dim)
+                    .size(12.0),
+            );
+            ui.label(" · ");
+            ui.label(duration);
+            ui.label(" · ");
+            ui.label(format!("🍅{}", tomato_display));
+            ui.label(" · ");
+            if !r.task.is_empty() {
+                crate::labels::swatch(ui, colors.color(&r.task, Some(r.project_id)));
+            }
+            ui.label(if r.task.is_empty() { "(无任务)" } else { r.task.as_str() });
+            if r.pauses.count > 0 {
+                ui.label(
+                    egui::RichText::new(format!(
+                        "⏸{}次 {:02}:{:02}",
+                        r.pauses.count,
+                        r.pauses.secs / 60,
+                        r.pauses.secs % 60
+                    ))
+                    .color(palette.text_dim)
+                    .size(12.0),
+                );
+            }
+            if r.overtime_secs > 0 {
+                ui.label(
+                    egui::RichText::new(format!(
+                        "加时 {:02}:{:02}",
+                        r.overtime_secs / 60,
+                        r.overtime_secs % 60
+                    ))
+                    .color(palette.overtime)
+                    .size(12.0),
+                );
+            }
+            if !r.commitment.is_empty() {
+                ui.label(
+                    egui::RichText::new(format!("「{}」", r.commitment))
+                        .color(palette.text_dim)
+                        .size(12.0),
+                );
+            }
+            // 有笔记时图标高亮；点击在列表下方打开笔记
+            let open = note.as_ref().is_some_and(|n| n.id == entry.id);
+            let color = if entry.note.is_empty() && !open { palette.text_dim } else { palette.focus };
+            let button = egui::Button::new(egui::RichText::new("📝").color(color).size(12.0)).frame(false);
+            let hover = if entry.note.is_empty() { "添加笔记" } else { "查看笔记" };
+            if icon_button_name(ui.add(button), hover).on_hover_text(hover).clicked() {
+                *note = (!open).then(|| RecordNote {
+                    id: entry.id,
+                    title: format!(
+                        "{} · {}",
+                        r.completed_at.get(..16).unwrap_or(&r.completed_at).replace('T', " "),
+                        if r.task.is_empty() { "(无任务)" } else { r.task.as_str() }
+                    ),
+                    saved: entry.note.clone(),
+                    draft: entry.note.is_empty().then(String::new),
+                });
+            }
+        });
+    }
+
+    /// 记录笔记详情面板：渲染 Markdown，可切换到编辑；保存后同步到已加载的记录列表
+    fn ui_record_note(&mut self, ui: &mut egui::Ui) {
+        let Some(note) = &mut self.history_note else { return };
+        let mut save = None;
+        let mut close = false;
+        ui.add_space(6.0);
+        egui::Frame::group(ui.style()).show(ui, |ui| {
+            ui.set_width(ui.available_width());
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new(format!("📝 {}", note.title)).strong());
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    close = icon_button_name(ui.add(egui::Button::new("×").frame(false)), "关闭笔记").on_hover_text("关闭").clicked();
+                });
+            });
+            match &mut note.draft {
+                Some(draft) => {
+                    ui.add(
+                        egui::TextEdit::multiline(draft)
+                            .desired_rows(6)
+                            .desired_width(f32::INFINITY)
+                            .hint_text("完成了什么、卡在哪里……支持 Markdown"),
+                    );
+                    ui.horizontal(|ui| {
+                        if ui.button("保存").clicked() {
+                            save = Some(draft.clone());
+                        }
+                        if !note.saved.is_empty() && ui.button("取消").clicked() {
+                            note.draft = None;
+                        }
+                    });
+                }
+                None => {
+                    egui::ScrollArea::vertical().id_salt("record_note").max_height(200.0).show(ui, |ui| {
+                        egui_commonmark::CommonMarkViewer::new().show(ui, &mut self.markdown_cache, &note.saved);
+                    });
+                    if ui.button("编辑").clicked() {
+                        note.draft = Some(note.saved.clone());
+                    }
+                }
+            }
+        });
+        if let Some(text) = save {
+            let text = text.trim_end().to_string();
+            let id = note.id;
+            let saved = crate::db::open_and_init()
+                .and_then(|conn| crate::db::set_record_note(&conn, id, &text))
+                .db_err("保存记录笔记");
+            match saved {
+                Some(true) => {
+                    note.saved = text.clone();
+                    note.draft = text.is_empty().then(String::new);
+                    let pages = self.history_rows.iter_mut().flat_map(|h| h.pages.values_mut());
+                    for entry in pages.flat_map(|p| p.records.iter_mut()).filter(|e| e.id == id) {
+                        entry.note = text.clone();
+                    }
+                }
+                // 记录已被删除
+                Some(false) => close = true,
+                None => {}
+            }
+        }
+        if close {
+            self.history_note = None;
+        }
+    }
+
     fn focus_rows_sorted_with_cumulative_tomatoes(
-        history: &[FocusRecord],
-    ) -> Vec<(&FocusRecord, u32)> {
-        let mut list: Vec<_> = history.iter().map(|r| (r, r.completed_at.as_str())).collect();
+        history: &[HistoryEntry],
+    ) -> Vec<(&HistoryEntry, u32)> {
+        let mut list: Vec<_> = history.iter().map(|e| (e, e.record.completed_at.as_str())).collect();
         list.sort_by(|a, b| a.1.cmp(b.1)); // 时间正序（最旧在前）
         let mut task_cumulative: std::collections::HashMap<String, u32> = std::collections::HashMap::new();
-        let mut with_sum: Vec<(&FocusRecord, u32)> = Vec::with_capacity(list.len());
-        for (r, _) in list {
+        let mut with_sum: Vec<(&HistoryEntry, u32)> = Vec::with_capacity(list.len());
+        for (e, _) in list {
+            let r = &e.record;
             let add = if r.completed_pomodoros == 0 { 1 } else { r.completed_pomodoros };
             let sum = task_cumulative.entry(r.task.clone()).or_insert(0);
             *sum += add;
-            with_sum.push((r, *sum));
+            with_sum.push((e, *sum));
         }
-        with_sum.sort_by(|a, b| b.0.completed_at.cmp(&a.0.completed_at)); // 时间逆序（最新在前）
+        with_sum.sort_by(|a, b| b.0.record.completed_at.cmp(&a.0.record.completed_at)); // 时间逆序（最新在前）
         with_sum
     }
 
     fn ui_full(&mut self, ctx: &egui::Context) {
-        use white_text_theme::BG_RGB;
+        let palette = self.palette;
 
-        // 进度条颜色：专注绿、短休息黄、长休息红
-        let (r, g, b) = match self.pomo.phase {
-            Phase::Focus => (100, 220, 130),       // 绿色
-            Phase::ShortBreak => (255, 193, 7),    // 黄色
-            Phase::LongBreak => (217, 17, 83),     // 红色
-        };
+        // 进度条颜色：专注绿、短休息黄、长休息红；阶段切换时平滑过渡
+        let accent = self.animated_accent(ctx);
 
+        let bg = self.alarm_bg();
         egui::CentralPanel::default()
-            .frame(egui::Frame::NONE.fill(egui::Color32::from_rgb(BG_RGB.0, BG_RGB.1, BG_RGB.2)))
+            .frame(egui::Frame::NONE.fill(bg))
             .show(ctx, |ui| {
-                // 顶栏单独占满宽度，关闭按钮固定右上角
+                let scale = layout_scale(ui.max_rect().size());
+                // 顶栏单独占满宽度，关闭按钮固定右上角，中间空白处可拖动窗口
                 ui.horizontal(|ui| {
-                    if ui
-                        .add(egui::Button::new("📌").frame(false))
+                    if icon_button_name(ui.add(egui::Button::new("📌").frame(false)), "钉到桌面右上角")
                         .on_hover_text("钉到桌面右上角")
                         .clicked()
                     {
-                        self.pinned = true;
-                        self.compact = true;
-                        self.compact_size_applied = false;
-                        self.pin_applied = false;
+                        self.pin_to_compact();
                     }
-                    ui.add_space(ui.available_width() - 32.0);
+                    self.ui_capture_button(ui);
+                    self.ui_side_timer_button(ui);
+                    self.ui_profile_picker(ui);
+                    window_drag_region(ui, ui.available_width() - 32.0);
                     let close_btn = egui::Button::new(egui::RichText::new("×").size(18.0)).frame(false);
-                    if ui.add_sized(egui::vec2(32.0, 32.0), close_btn).on_hover_text("关闭").clicked() {
-                        ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                    if icon_button_name(ui.add_sized(egui::vec2(32.0, 32.0), close_btn), "关闭窗口")
+                        .on_hover_text(self.close_action.label())
+                        .clicked()
+                    {
+                        self.on_close_clicked(ctx);
                     }
                 });
+                self.ui_capture_bar(ui);
+                self.ui_side_timer_form(ui);
                 ui.add_space(12.0);
+                self.ui_rest_warning(ui);
 
-                ui.vertical_centered(|ui| {
-
-                    // 当前任务：与番茄钟关联，专注时明确「在做哪件事」
-                    ui.horizontal(|ui| {
-                        ui.label("当前任务：");
-                        ui.add(
-                            egui::TextEdit::singleline(&mut self.current_task)
-                                .desired_width(240.0)
-                                .hint_text("输入本番茄要完成的事…"),
-                        );
-                    });
-                    ui.add_space(8.0);
-
-                    // 所处阶段文案，颜色与进度条一致（随阶段切换：绿/蓝/红）
-                    ui.label(
-                        egui::RichText::new(Self::phase_label(self.pomo.phase))
-                            .color(egui::Color32::from_rgb(r, g, b))
-                            .size(18.0),
-                    );
-                    ui.add_space(8.0);
+                // 窗口矮于内容时可滚动；计时器字号、圆环与输入框宽度随窗口大小缩放
+                egui::ScrollArea::vertical().auto_shrink(false).show(ui, |ui| {
+                    ui.vertical_centered(|ui| {
 
-                    // 大计时器（白字 + 红/蓝 accent 风格）
-                    ui.label(
-                        egui::RichText::new(self.pomo.remaining_display())
-                            .color(egui::Color32::from_rgb(255, 255, 255))
-                            .size(56.0)
-                            .monospace(),
-                    );
-                    ui.add_space(4.0);
+                        // 当前项目：任务归属的项目，统计按项目汇总
+                        ui.horizontal(|ui| {
+                            let label = ui.label("所属项目：");
+                            let selected = self.project_name(self.current_project).to_string();
+                            egui::ComboBox::from_id_salt("current_project")
+                                .selected_text(selected)
+                                .width(200.0)
+                                .show_ui(ui, |ui| {
+                                    for project in &self.projects {
+                                        ui.selectable_value(&mut self.current_project, project.id, project.name.as_str());
+                                    }
+                                })
+                                .response
+                                .labelled_by(label.id);
+                            if ui.small_button("管理").on_hover_text("新建或删除项目").clicked() {
+                                self.projects_view.get_or_insert_with(ProjectsView::default);
+                            }
+                        });
+                        // 当前任务：与番茄钟关联，专注时明确「在做哪件事」；输入框随窗口宽度伸缩
+                        let field_width = (ui.available_width() - 140.0).clamp(140.0, 360.0);
+                        ui.horizontal(|ui| {
+                            // 输入框以左侧文字为屏幕阅读器名称
+                            let label = ui.label("当前任务：");
+                            ui.add(
+                                egui::TextEdit::singleline(&mut self.current_task)
+                                    .desired_width(field_width)
+                                    .hint_text("输入本番茄要完成的事…"),
+                            )
+                            .labelled_by(label.id);
+                            self.ui_recent_tasks_menu(ui);
+                            self.ui_task_color_menu(ui);
+                        });
+                        // 任务名建议：开始专注前列出仓库当前分支与最近提交说明，点击填入
+                        if self.pomo.phase == Phase::Focus && self.pomo.state == TimerState::Idle {
+                            let suggestions = self.git_suggester.suggestions(&self.git_suggest).to_vec();
+                            let current = self.current_task.trim().to_string();
+                            let suggestions: Vec<String> = suggestions.into_iter().filter(|s| *s != current).collect();
+                            if !suggestions.is_empty() {
+                                ui.horizontal_wrapped(|ui| {
+                                    ui.label(egui::RichText::new("建议：").color(self.palette.text_dim));
+                                    for suggestion in suggestions {
+                                        let shown: String = if suggestion.chars().count() > 40 {
+                                            suggestion.chars().take(40).chain(std::iter::once('…')).collect()
+                                        } else {
+                                            suggestion.clone()
+                                        };
+                                        if ui.small_button(shown).on_hover_text(suggestion.as_str()).clicked() {
+                                            self.current_task = suggestion;
+                                        }
+                                    }
+                                });
+                            }
+                        }
+                        // 专注契约：开始专注前写下一句承诺（实施意图），专注期间显示在倒计时下方
+                        if self.pomo.phase == Phase::Focus && self.pomo.state == TimerState::Idle {
+                            ui.horizontal(|ui| {
+                                let label = ui.label("我承诺：");
+                                ui.add(
+                                    egui::TextEdit::singleline(&mut self.current_commitment)
+                                        .desired_width(field_width)
+                                        .hint_text("如：只写第 3 节，不开微信"),
+                                )
+                                .labelled_by(label.id);
+                            });
+                            self.ui_energy_row(ui);
+                        }
+                        ui.add_space(8.0);
 
-                    // 进度条（红/蓝）
-                    let progress = self.pomo.progress();
-                    let bar = egui::ProgressBar::new(progress)
-                        .desired_width(280.0)
-                        .fill(egui::Color32::from_rgb(r, g, b));
-                    ui.add(bar);
-                    ui.add_space(20.0);
+                        // 所处阶段文案，颜色与进度条一致（随阶段切换：绿/蓝/红）；加时中换用加时色
+                        let phase_text = if self.pomo.in_overtime { "专注 · 加时" } else { Self::phase_label(self.pomo.phase) };
+                        let phase_color = if self.pomo.in_overtime { self.palette.overtime } else { accent };
+                        ui.label(egui::RichText::new(phase_text).color(phase_color).size(18.0));
+                        if let Some(at) = self.delayed_start {
+                            let secs = (at - Utc::now()).num_seconds().max(0);
+                            ui.label(
+                                egui::RichText::new(format!("{:02}:{:02} 后自动开始", secs / 60, secs % 60))
+                                    .color(self.palette.text_dim),
+                            );
+                        }
+                        ui.add_space(8.0);
 
-                    // 开始/暂停、重置、完成 同一行（文字居中）
-                    let btn_size = egui::vec2(88.0, 36.0);
-                    ui.horizontal(|ui| {
-                        let (label, action) = match self.pomo.state {
-                            TimerState::Idle => ("开始", 0u8),
-                            TimerState::Running => ("暂停", 1u8),
-                            TimerState::Paused => ("继续", 2u8),
-                        };
-                        if centered_button(ui, label, btn_size).on_hover_text(match action {
-                            0 => "开始计时",
-                            1 => "暂停",
-                            _ => "继续",
-                        }).clicked() {
-                            match action {
-                                0 => self.pomo.start(),
-                                1 | 2 => self.pomo.toggle_pause(),
-                                _ => {}
+                        // 大计时器（白字 + 红/蓝 accent 风格）；暂停时点击可直接编辑剩余时间；计时中背后有起伏的光晕
+                        // 圆环布局时计时器放在环形进度中间，不再画下方的进度条
+                        let glow_slot = ui.painter().add(egui::Shape::Noop);
+                        let timer_rect = match self.timer_layout {
+                            TimerLayout::Bar => ui.scope(|ui| self.ui_timer_text(ui, scale)).response.rect,
+                            TimerLayout::Ring => {
+                                ProgressRing::new(self.pomo.progress())
+                                    .diameter((RING_DIAMETER_FULL * scale).min(ui.available_width() - 24.0))
+                                    .thickness(10.0 * scale)
+                                    .fill(accent)
+                                    .show(ui, |ui| self.ui_timer_text(ui, scale))
+                                    .response
+                                    .rect
                             }
+                        };
+                        self.paint_timer_glow(ui, glow_slot, timer_rect, accent);
+                        ui.add_space(4.0);
+                        self.ui_commitment_line(ui, 15.0);
+                        self.ui_side_timer_line(ui, 14.0);
+                        self.ui_quote_line(ui);
+
+                        // 加时中：手动结束本次专注（含加时）并进入休息
+                        if self.pomo.in_overtime {
+                            ui.horizontal(|ui| {
+                                let size = egui::vec2(96.0, 22.0);
+                                ui.add_space((ui.available_width() - size.x) * 0.5);
+                                if centered_button(ui, "结束加时", size).on_hover_text("记录本次专注（含加时）并进入休息").clicked() {
+                                    self.pomo.finish_overtime();
+                                }
+                            });
+                            ui.add_space(4.0);
+                        } else if self.pomo.state != TimerState::Idle {
+                            // 运行/暂停时可临时延长或缩短当前阶段（如会议超时）
+                            ui.horizontal(|ui| {
+                                let small = egui::vec2(44.0, 22.0);
+                                ui.add_space((ui.available_width() - small.x * 2.0 - ui.spacing().item_spacing.x) * 0.5);
+                                if centered_button(ui, "−5", small).on_hover_text("减少 5 分钟").clicked() {
+                                    self.pomo.adjust_remaining(-ADJUST_STEP_SECS);
+                                }
+                                if centered_button(ui, "+5", small).on_hover_text("增加 5 分钟").clicked() {
+                                    self.pomo.adjust_remaining(ADJUST_STEP_SECS);
+                                }
+                            });
+                            ui.add_space(4.0);
                         }
-                        if centered_button(ui, "重置", btn_size).on_hover_text("清空当前任务并重置番茄数").clicked() {
-                            self.current_task.clear();
-                            self.pomo.reset_pomodoros_and_stop();
-                        }
-                        if centered_button(ui, "完成", btn_size).on_hover_text("完成当前任务并重置，开始下一项").clicked() {
-                            self.current_task.clear();
-                            self.pomo.reset_pomodoros_and_stop();
+                        // 专注快结束或休息刚开始时可推迟休息
+                        self.ui_snooze_button(ui, egui::vec2(120.0, 22.0));
+                        self.ui_breathing_guide(ui, accent);
+
+                        // 进度条（红/蓝）
+                        if self.timer_layout == TimerLayout::Bar {
+                            let progress = self.pomo.progress();
+                            let bar = egui::ProgressBar::new(progress)
+                                .desired_width((ui.available_width() - 100.0).at_least(200.0))
+                                .fill(accent);
+                            ui.add(bar);
                         }
-                    });
-                    ui.add_space(24.0);
+                        ui.add_space(20.0);
 
-                    // 阶段选择（仅 Idle 时可切换）
-                    ui.horizontal(|ui| {
-                        ui.label("阶段：");
-                        for phase in [Phase::Focus, Phase::ShortBreak, Phase::LongBreak] {
-                            let selected = self.pomo.phase == phase && self.pomo.state == TimerState::Idle;
-                            let label = Self::phase_label(phase);
-                            let btn = egui::Button::new(label);
-                            let resp = ui.add_enabled(self.pomo.state == TimerState::Idle, btn);
-                            if resp.clicked() {
-                                self.pomo.set_phase(phase);
+                        // 开始/暂停、重置、完成 同一行（文字居中）；窗口太窄放不下一行时上下排列
+                        let btn_size = egui::vec2(88.0, 36.0);
+                        let row_width = btn_size.x * 3.0 + ui.spacing().item_spacing.x * 2.0;
+                        let mut buttons = |ui: &mut egui::Ui| {
+                            let (label, action) = match self.pomo.state {
+                                TimerState::Idle => ("开始", 0u8),
+                                TimerState::Running => ("暂停", 1u8),
+                                TimerState::Paused => ("继续", 2u8),
+                            };
+                            if centered_button(ui, label, btn_size).on_hover_text(match action {
+                                0 => "开始计时",
+                                1 => "暂停",
+                                _ => "继续",
+                            }).clicked() {
+                                match action {
+                                    0 => self.pomo.start(),
+                                    1 | 2 => self.toggle_pause(),
+                                    _ => {}
+                                }
+                            }
+                            if centered_button(ui, "重置", btn_size)
+                                .on_hover_text("放弃当前任务：清空任务并重置番茄数，进行中的专注记为放弃")
+                                .clicked()
+                            {
+                                self.reset_with_undo(false);
                             }
-                            if selected {
-                                resp.highlight();
+                            if centered_button(ui, "完成", btn_size)
+                                .on_hover_text("完成当前任务：计入统计，进行中的专注计入时长，然后开始下一项")
+                                .clicked()
+                            {
+                                self.reset_with_undo(true);
                             }
+                        };
+                        if ui.available_width() >= row_width {
+                            ui.horizontal(|ui| {
+                                ui.add_space((ui.available_width() - row_width) * 0.5);
+                                buttons(ui);
+                            });
+                        } else {
+                            buttons(ui);
                         }
-                    });
-                    ui.add_space(12.0);
+                        self.ui_focus_until(ui);
+                        ui.add_space(24.0);
 
-                    // 番茄数：与「阶段：」相同字体格式（普通 label）
-                    ui.horizontal(|ui| {
-                        ui.label("番茄数 ");
-                        let n = self.pomo.config.pomodoros_before_long;
-                        let done = self.pomo.completed_pomodoros;
-                        paint_pomodoro_circles(ui, n, done);
-                    });
-                    ui.add_space(8.0);
-                    ui.horizontal(|ui| {
-                        if ui.link("关于").clicked() {
-                            self.show_about = true;
-                        }
-                        ui.label(" ");
-                        if ui.link("统计").clicked() {
-                            self.show_statistics = true;
+                        // 阶段选择（仅 Idle 时可切换）
+                        ui.horizontal(|ui| {
+                            ui.label("阶段：");
+                            for phase in [Phase::Focus, Phase::ShortBreak, Phase::LongBreak] {
+                                let selected = self.pomo.phase == phase && self.pomo.state == TimerState::Idle;
+                                let label = Self::phase_label(phase);
+                                let btn = egui::Button::new(label);
+                                let resp = ui.add_enabled(self.pomo.state == TimerState::Idle, btn);
+                                // 屏幕阅读器读出「专注 阶段，已选中」；计时中按钮禁用但仍报告当前阶段
+                                let current = self.pomo.phase == phase;
+                                resp.widget_info(|| {
+                                    egui::WidgetInfo::selected(
+                                        egui::WidgetType::Button,
+                                        resp.enabled(),
+                                        current,
+                                        format!("{} 阶段", label),
+                                    )
+                                });
+                                if resp.clicked() {
+                                    self.pomo.set_phase(phase);
+                                }
+                                if selected {
+                                    resp.highlight();
+                                }
+                            }
+                        });
+                        self.ui_routine_picker(ui);
+                        ui.add_space(12.0);
+
+                        // 番茄数：与「阶段：」相同字体格式（普通 label）
+                        ui.horizontal(|ui| {
+                            ui.label("番茄数 ");
+                            let n = self.pomo.cycle_length();
+                            let done = self.pomo.completed_pomodoros;
+                            paint_pomodoro_circles(ui, n, done, CIRCLE_RADIUS_FULL, &self.palette);
+                        });
+                        let goal = self.profiles.active().map_or(0, |p| p.daily_goal);
+                        if goal > 0 {
+                            let today = self.today_pomodoros();
+                            let color = if today >= goal as usize { self.palette.focus } else { self.palette.text_dim };
+                            ui.label(egui::RichText::new(format!("今日目标 {} / {}", today, goal)).color(color));
                         }
+                        self.ui_today_glance(ui);
+                        ui.add_space(8.0);
+                        // 底部链接：窗口窄时自动换行
+                        ui.horizontal_wrapped(|ui| {
+                            if ui.link("关于").clicked() {
+                                self.show_about = true;
+                            }
+                            ui.label(" ");
+                            if ui.link("统计").clicked() {
+                                self.show_statistics = true;
+                                self.invalidate_stats();
+                            }
+                            ui.label(" ");
+                            if ui.link("回顾").clicked() {
+                                self.daily_review = Some(DailyReviewView {
+                                    day: beijing_now().date_naive(),
+                                    prompt_offset: 0,
+                                    answer: String::new(),
+                                    search: String::new(),
+                                    results: None,
+                                    summary: None,
+                                    digest: Vec::new(),
+                                    message: None,
+                                });
+                            }
+                            ui.label(" ");
+                            if ui.link("周报").clicked() {
+                                self.review = Some(ReviewView {
+                                    last_day: beijing_now().date_naive(),
+                                    loaded: None,
+                                    message: None,
+                                });
+                            }
+                            ui.label(" ");
+                            if ui.link("日程").clicked() {
+                                self.planner = Some(PlannerView {
+                                    day: beijing_now().date_naive(),
+                                    entry: String::new(),
+                                    project_id: self.current_project,
+                                    message: None,
+                                    rows: None,
+                                });
+                            }
+                            ui.label(" ");
+                            if ui.link("收集箱").clicked() {
+                                self.inbox = Some(InboxView {
+                                    include_done: false,
+                                    message: None,
+                                    rows: None,
+                                });
+                            }
+                            ui.label(" ");
+                            if ui.link("成就").clicked() {
+                                self.achievements = Some(AchievementsView { loaded: None, message: None });
+                            }
+                            ui.label(" ");
+                            if ui.link("审计").clicked() {
+                                self.audit = Some(AuditView::new(beijing_now().date_naive()));
+                            }
+                            ui.label(" ");
+                            if ui.link("设置").clicked() {
+                                self.show_settings = true;
+                            }
+                            ui.label(" ");
+                            if ui.link("数据").clicked() {
+                                let today = beijing_now().format("%Y-%m-%d").to_string();
+                                self.purge_view = Some(PurgeView {
+                                    from: today.clone(),
+                                    to: today,
+                                    ..Default::default()
+                                });
+                            }
+                        });
+                        ui.add_space(12.0);
                     });
-                    ui.add_space(12.0);
                 });
+                window_resize_grip(ui, palette.text_dim);
             });
     }
 
     fn ui_compact(&mut self, ctx: &egui::Context) {
-        use white_text_theme::{BG_RGB, TEXT_WHITE};
+        let palette = self.palette;
 
-        // 进度条颜色：专注绿、短休息黄、长休息红
-        let (accent_r, accent_g, accent_b) = match self.pomo.phase {
-            Phase::Focus => (100, 220, 130),       // 绿色
-            Phase::ShortBreak => (255, 193, 7),    // 黄色
-            Phase::LongBreak => (217, 17, 83),     // 红色
-        };
+        // 进度条颜色：专注绿、短休息黄、长休息红；阶段切换时平滑过渡
+        let accent = self.animated_accent(ctx);
 
+        let bg = self.alarm_bg();
         egui::CentralPanel::default()
-            .frame(egui::Frame::NONE.fill(egui::Color32::from_rgb(BG_RGB.0, BG_RGB.1, BG_RGB.2)))
+            .frame(egui::Frame::NONE.fill(bg))
             .show(ctx, |ui| {
                 let rect = ui.available_rect_before_wrap();
-                // 背景几何图案（类似 WhiteText 的质感）
-                paint_subtle_pattern(ui, rect);
+                // 背景几何图案（类似 WhiteText 的质感）；降低透明度时不画
+                if !self.a11y.is_some_and(|a| a.reduce_transparency) {
+                    paint_subtle_pattern(ui, rect, &palette);
+                }
 
-                // 顶栏：取消钉住（左）+ 关闭固定右上角（右）
+                // 顶栏：取消钉住（左）+ 拖动区域（中）+ 关闭固定右上角（右）
                 ui.horizontal(|ui| {
-                    if ui
-                        .add(egui::Button::new("📌").frame(false))
+                    if icon_button_name(ui.add(egui::Button::new("📌").frame(false)), "取消钉住")
                         .on_hover_text("取消钉住，恢复完整窗口")
                         .clicked()
                     {
+                        tracing::info!("取消钉住，恢复完整窗口");
                         self.pinned = false;
                         self.compact = false;
                         self.compact_size_applied = false;
                         self.full_restore_applied = true; // apply_unpin 内已发 InnerSize，避免下一帧重复
-                        apply_unpin(ctx);
+                        apply_unpin(ctx, self.full_size());
                     }
-                    ui.add_space(ui.available_width() - 32.0);
+                    self.ui_capture_button(ui);
+                    self.ui_side_timer_button(ui);
+                    window_drag_region(ui, ui.available_width() - 32.0);
                     let close_btn = egui::Button::new(egui::RichText::new("×").size(18.0)).frame(false);
-                    if ui.add_sized(egui::vec2(32.0, 32.0), close_btn).on_hover_text("关闭").clicked() {
-                        ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                    if icon_button_name(ui.add_sized(egui::vec2(32.0, 32.0), close_btn), "关闭窗口")
+                        .on_hover_text(self.close_action.label())
+                        .clicked()
+                    {
+                        self.on_close_clicked(ctx);
                     }
                 });
+                self.ui_capture_bar(ui);
+                self.ui_side_timer_form(ui);
                 ui.add_space(2.0);
 
                 ui.vertical_centered(|ui| {
@@ -810,49 +7776,79 @@ impl RedTomatoApp {
                         } else {
                             self.current_task.clone()
                         };
-                        ui.label(
-                            egui::RichText::new(display)
-                                .color(egui::Color32::from_rgb(TEXT_WHITE.0, TEXT_WHITE.1, TEXT_WHITE.2))
-                                .size(12.0),
-                        );
+                        // 色块与任务名排成一段文字，保持居中
+                        let format = |color| egui::TextFormat::simple(egui::FontId::proportional(12.0), color);
+                        let mut job = egui::text::LayoutJob::default();
+                        let color = self.task_colors.color(&self.current_task, Some(self.current_project));
+                        job.append("■ ", 0.0, format(color));
+                        job.append(&display, 0.0, format(palette.text));
+                        ui.label(job);
                         ui.add_space(2.0);
                     }
 
-                    // 大号白字计时（White Text 风格）
-                    ui.label(
-                        egui::RichText::new(self.pomo.remaining_display())
-                            .color(egui::Color32::from_rgb(TEXT_WHITE.0, TEXT_WHITE.1, TEXT_WHITE.2))
-                            .size(42.0)
-                            .monospace(),
-                    );
+                    // 大号白字计时（White Text 风格），加时中换用加时色
+                    let timer_color = if self.pomo.in_overtime { palette.overtime } else { palette.text };
+                    let timer_text = egui::RichText::new(self.pomo.remaining_display())
+                        .color(timer_color)
+                        .size(if self.timer_layout == TimerLayout::Ring { 30.0 } else { 42.0 })
+                        .monospace();
+                    let glow_slot = ui.painter().add(egui::Shape::Noop);
+                    let timer_rect = match self.timer_layout {
+                        TimerLayout::Bar => ui.label(timer_text).rect,
+                        TimerLayout::Ring => {
+                            ProgressRing::new(self.pomo.progress())
+                                .diameter(RING_DIAMETER_COMPACT)
+                                .thickness(6.0)
+                                .fill(accent)
+                                .show(ui, |ui| ui.label(timer_text))
+                                .response
+                                .rect
+                        }
+                    };
+                    self.paint_timer_glow(ui, glow_slot, timer_rect, accent);
                     ui.add_space(2.0);
+                    self.ui_commitment_line(ui, 12.0);
+                    self.ui_side_timer_line(ui, 12.0);
 
                     // 所处阶段文案，颜色与进度条一致（随阶段切换：绿/蓝/红）
-                    let phase_text = match self.pomo.phase {
-                        Phase::Focus => "专注",
-                        Phase::ShortBreak => "短休息",
-                        Phase::LongBreak => "长休息",
+                    let (phase_text, phase_color) = if self.pomo.in_overtime {
+                        ("专注 · 加时", palette.overtime)
+                    } else {
+                        (Self::phase_label(self.pomo.phase), accent)
                     };
                     ui.label(
                         egui::RichText::new(phase_text)
-                            .color(egui::Color32::from_rgb(accent_r, accent_g, accent_b))
+                            .color(phase_color)
                             .size(14.0),
                     );
                     ui.add_space(8.0);
 
                     // 进度条（红/蓝 accent），宽度略小于窗口以留出边距
-                    let progress = self.pomo.progress();
-                    let bar_width = (ui.available_width() - 24.0).at_least(200.0);
-                    let bar = egui::ProgressBar::new(progress)
-                        .desired_width(bar_width)
-                        .fill(egui::Color32::from_rgb(accent_r, accent_g, accent_b));
-                    ui.add(bar);
+                    if self.timer_layout == TimerLayout::Bar {
+                        let progress = self.pomo.progress();
+                        let bar_width = (ui.available_width() - 24.0).at_least(200.0);
+                        let bar = egui::ProgressBar::new(progress)
+                            .desired_width(bar_width)
+                            .fill(accent);
+                        ui.add(bar);
+                        ui.add_space(6.0);
+                    }
+
+                    // 迷你番茄数圆圈：钉住时也能看到当前处于 4 番茄周期的哪一步
+                    paint_pomodoro_circles(
+                        ui,
+                        self.pomo.cycle_length(),
+                        self.pomo.completed_pomodoros,
+                        CIRCLE_RADIUS_COMPACT,
+                        &self.palette,
+                    );
                     ui.add_space(6.0);
 
-                    // 开始/暂停（一个按钮）：整行居中，避免钉住后偏左显得尴尬
+                    // 开始/暂停（一个按钮）：整行居中，避免钉住后偏左显得尴尬；加时中为「结束加时」
                     let compact_btn = egui::vec2(88.0, 30.0);
                     ui.horizontal(|ui| {
                         let (label, action) = match self.pomo.state {
+                            _ if self.pomo.in_overtime => ("结束加时", 3u8),
                             TimerState::Idle => ("开始", 0u8),
                             TimerState::Running => ("暂停", 1u8),
                             TimerState::Paused => ("继续", 2u8),
@@ -860,13 +7856,14 @@ impl RedTomatoApp {
                         let full_width = ui.available_width();
                         ui.add_space((full_width - compact_btn.x) * 0.5);
                         if centered_button(ui, label, compact_btn).clicked() {
-                            if action == 0 {
-                                self.pomo.start();
-                            } else {
-                                self.pomo.toggle_pause();
+                            match action {
+                                0 => self.pomo.start(),
+                                3 => self.pomo.finish_overtime(),
+                                _ => self.toggle_pause(),
                             }
                         }
                     });
+                    self.ui_snooze_button(ui, egui::vec2(110.0, 22.0));
                 });
             });
     }
@@ -0,0 +1,230 @@
+//! 时间审计：把当天计划、实际专注、日历事件放到三条并列时间轴上，找出「计划外」与「计划内未执行」的时段
+
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, TimeZone};
+
+use crate::app::{beijing, beijing_day_start};
+use crate::history::FocusRecord;
+
+/// 时间段（秒级时间戳，左闭右开）+ 标签
+#[derive(Clone, Debug)]
+pub struct Span {
+    pub start: i64,
+    pub end: i64,
+    pub label: String,
+}
+
+impl Span {
+    pub fn secs(&self) -> i64 {
+        (self.end - self.start).max(0)
+    }
+}
+
+/// 一天的审计结果：三条时间轴 + 两类差异
+pub struct DayAudit {
+    pub day_start: i64,
+    pub day_end: i64,
+    pub planned: Vec<Span>,
+    pub tracked: Vec<Span>,
+    pub calendar: Vec<Span>,
+    /// 有专注记录但不在任何计划块内（计划外）
+    pub unplanned: Vec<Span>,
+    /// 计划了但既没有专注也没有日历事件（计划内未执行）
+    pub untracked: Vec<Span>,
+}
+
+impl DayAudit {
+    pub fn total(spans: &[Span]) -> i64 {
+        spans.iter().map(Span::secs).sum()
+    }
+}
+
+/// 某天（北京时区）的起止时间戳
+pub fn day_bounds(day: NaiveDate) -> (i64, i64) {
+    let start = beijing_day_start(day);
+    (start, start + 24 * 3600)
+}
+
+/// 专注记录 → 时间段：completed_at 为结束时间，往前推 duration_secs
+pub fn tracked_spans(records: &[FocusRecord]) -> Vec<Span> {
+    records
+        .iter()
+        .filter_map(|r| {
+            let end = DateTime::parse_from_rfc3339(&r.completed_at).ok()?.timestamp();
+            Some(Span {
+                start: end - r.duration_secs.max(0),
+                end,
+                label: r.task.clone(),
+            })
+        })
+        .collect()
+}
+
+/// 把时间段裁剪到 [lo, hi) 内，丢弃完全在外的
+fn clip(spans: &[Span], lo: i64, hi: i64) -> Vec<Span> {
+    let mut out: Vec<Span> = spans
+        .iter()
+        .filter(|s| s.end > lo && s.start < hi)
+        .map(|s| Span {
+            start: s.start.max(lo),
+            end: s.end.min(hi),
+            label: s.label.clone(),
+        })
+        .collect();
+    out.sort_by_key(|s| s.start);
+    out
+}
+
+/// 合并重叠区间（只保留时间，不含标签）
+fn union(spans: &[Span]) -> Vec<(i64, i64)> {
+    let mut v: Vec<(i64, i64)> = spans.iter().map(|s| (s.start, s.end)).collect();
+    v.sort();
+    let mut out: Vec<(i64, i64)> = Vec::with_capacity(v.len());
+    for (s, e) in v {
+        match out.last_mut() {
+            Some(last) if s <= last.1 => last.1 = last.1.max(e),
+            _ => out.push((s, e)),
+        }
+    }
+    out
+}
+
+/// a 中不被 b 覆盖的部分（保留 a 的标签）
+fn subtract(a: &[Span], b: &[Span]) -> Vec<Span> {
+    let cover = union(b);
+    let mut out = Vec::new();
+    for span in a {
+        let mut cur = span.start;
+        for &(s, e) in &cover {
+            if e <= cur || s >= span.end {
+                continue;
+            }
+            if s > cur {
+                out.push(Span { start: cur, end: s, label: span.label.clone() });
+            }
+            cur = cur.max(e);
+        }
+        if cur < span.end {
+            out.push(Span { start: cur, end: span.end, label: span.label.clone() });
+        }
+    }
+    out
+}
+
+/// 生成某天的审计：各时间轴先裁剪到当天，再计算计划外 / 计划内未执行
+pub fn build_day_audit(day: NaiveDate, planned: &[Span], tracked: &[Span], calendar: &[Span]) -> DayAudit {
+    let (lo, hi) = day_bounds(day);
+    let planned = clip(planned, lo, hi);
+    let tracked = clip(tracked, lo, hi);
+    let calendar = clip(calendar, lo, hi);
+    let unplanned = subtract(&tracked, &planned);
+    let mut busy = tracked.clone();
+    busy.extend(calendar.iter().cloned());
+    let untracked = subtract(&planned, &busy);
+    DayAudit {
+        day_start: lo,
+        day_end: hi,
+        planned,
+        tracked,
+        calendar,
+        unplanned,
+        untracked,
+    }
+}
+
+/// 从 .ics 文本解析出的日历事件（时间为 RFC3339 北京时区）
+#[derive(Clone, Debug)]
+pub struct CalendarEvent {
+    pub uid: String,
+    pub start_at: String,
+    pub end_at: String,
+    pub summary: String,
+}
+
+/// 解析 iCalendar 日期时间：UTC（…Z）、带 TZID 或浮动时间均按北京时区处理；全天事件（仅日期）返回 None
+fn parse_ics_datetime(value: &str) -> Option<DateTime<FixedOffset>> {
+    let tz = beijing();
+    if let Some(utc) = value.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some(chrono::Utc.from_utc_datetime(&naive).with_timezone(&tz));
+    }
+    let naive = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    tz.from_local_datetime(&naive).single()
+}
+
+/// 最小化的 .ics 解析：展开折行，读取 VEVENT 的 UID / DTSTART / DTEND / SUMMARY，跳过全天事件
+pub fn parse_ics(text: &str) -> Vec<CalendarEvent> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in text.lines() {
+        let raw = raw.trim_end_matches('\r');
+        if let Some(cont) = raw.strip_prefix(' ').or_else(|| raw.strip_prefix('\t')) {
+            if let Some(last) = lines.last_mut() {
+                last.push_str(cont);
+                continue;
+            }
+        }
+        lines.push(raw.to_string());
+    }
+
+    let mut events = Vec::new();
+    let mut in_event = false;
+    let mut uid = String::new();
+    let mut summary = String::new();
+    let mut start: Option<DateTime<FixedOffset>> = None;
+    let mut end: Option<DateTime<FixedOffset>> = None;
+    for line in &lines {
+        if line == "BEGIN:VEVENT" {
+            in_event = true;
+            uid.clear();
+            summary.clear();
+            start = None;
+            end = None;
+            continue;
+        }
+        if line == "END:VEVENT" {
+            in_event = false;
+            if let (Some(s), Some(e)) = (start, end) {
+                if e > s {
+                    let start_at = s.to_rfc3339();
+                    events.push(CalendarEvent {
+                        uid: if uid.is_empty() { format!("{}|{}", start_at, summary) } else { uid.clone() },
+                        start_at,
+                        end_at: e.to_rfc3339(),
+                        summary: summary.clone(),
+                    });
+                }
+            }
+            continue;
+        }
+        if !in_event {
+            continue;
+        }
+        let Some((key, value)) = line.split_once(':') else { continue };
+        let name = key.split(';').next().unwrap_or(key);
+        match name {
+            "UID" => uid = value.to_string(),
+            "SUMMARY" => summary = value.replace("\\,", ",").replace("\\;", ";").replace("\\n", " "),
+            "DTSTART" => start = parse_ics_datetime(value),
+            "DTEND" => end = parse_ics_datetime(value),
+            _ => {}
+        }
+    }
+    events
+}
+
+/// RFC3339 起止字符串 → 时间段（解析失败的丢弃）
+pub fn span_from_rfc3339(start_at: &str, end_at: &str, label: &str) -> Option<Span> {
+    let start = DateTime::parse_from_rfc3339(start_at).ok()?.timestamp();
+    let end = DateTime::parse_from_rfc3339(end_at).ok()?.timestamp();
+    (end > start).then(|| Span {
+        start,
+        end,
+        label: label.to_string(),
+    })
+}
+
+/// 某天 HH:MM → 北京时区 RFC3339（计划块录入用）
+pub fn day_time_rfc3339(day: NaiveDate, hhmm: &str) -> Option<String> {
+    let time = chrono::NaiveTime::parse_from_str(hhmm.trim(), "%H:%M").ok()?;
+    let dt = beijing().from_local_datetime(&day.and_time(time)).single()?;
+    Some(dt.to_rfc3339())
+}
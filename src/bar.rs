@@ -0,0 +1,245 @@
+//! 状态栏输出：供 Waybar / Polybar / i3blocks 等平铺窗口管理器的状态栏显示计时，不需要钉住的小窗
+//!
+//! 界面开启后把计时状态写入数据目录下的 `bar.json`（只在状态变化时写，计时中记下阶段结束的时刻）；
+//! 状态栏运行 `red-tomato bar`，它每秒读一次该文件，按结束时刻算出剩余时间，输出一行到标准输出或 FIFO。
+//! 界面可以一直最小化。界面退出或关闭该设置时删除文件，状态栏随之显示为空。
+
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::pomodoro::Phase;
+
+const STATE_FILENAME: &str = "bar.json";
+/// 命令行读取状态的间隔
+const REFRESH: Duration = Duration::from_secs(1);
+/// 计时中结束时刻的抖动（帧与秒的边界不对齐）在这个秒数内不重写文件
+const ENDS_AT_TOLERANCE_SECS: i64 = 2;
+
+const USAGE: &str = "用法：red-tomato bar [--format text|waybar|i3blocks] [--output <FIFO 或文件>] [--once]
+
+  --format  text      一行文字（默认；Polybar 的 tail = true、i3blocks 的 interval = persist）
+            waybar    Waybar 自定义模块的 JSON（return-type = \"json\"，class 为阶段或 paused / idle）
+            i3blocks  i3blocks 的 JSON（format = json，带阶段颜色）
+  --output  写到指定的 FIFO 或文件，而不是标准输出
+  --once    只输出一次后退出（用于按间隔执行的状态栏模块）
+
+需在界面设置中开启「状态栏输出」；界面未运行时输出为空。";
+
+/// 写给状态栏的计时状态
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BarState {
+    pub phase: Phase,
+    /// 阶段名，如「专注」
+    pub phase_label: String,
+    pub task: String,
+    /// 计时中：阶段结束的时刻（Unix 秒；加时中已过去）。暂停或未开始时为 None
+    pub ends_at: Option<i64>,
+    /// 暂停或未开始时的剩余秒数（加时中为负的已加时秒数）
+    pub remaining_secs: i64,
+    pub total_secs: i64,
+    pub paused: bool,
+}
+
+impl BarState {
+    /// 除结束时刻的小幅抖动外是否相同
+    fn same_as(&self, other: &BarState) -> bool {
+        let ends_close = match (self.ends_at, other.ends_at) {
+            (Some(a), Some(b)) => (a - b).abs() <= ENDS_AT_TOLERANCE_SECS,
+            (a, b) => a == b,
+        };
+        ends_close && Self { ends_at: other.ends_at, ..self.clone() } == *other
+    }
+
+    /// 此刻的剩余秒数，加时中为负
+    fn remaining_at(&self, now: i64) -> i64 {
+        self.ends_at.map_or(self.remaining_secs, |end| end - now)
+    }
+}
+
+pub fn state_path() -> PathBuf {
+    crate::db::data_dir().join(STATE_FILENAME)
+}
+
+/// 界面一侧的写入器：记住上次写入的状态，避免每帧写盘
+#[derive(Default)]
+pub struct BarOutput {
+    last: Option<BarState>,
+}
+
+impl BarOutput {
+    /// 状态变化时写入；返回写入错误（未变化时为 Ok）
+    pub fn update(&mut self, state: BarState) -> std::io::Result<()> {
+        if self.last.as_ref().is_some_and(|last| last.same_as(&state)) {
+            return Ok(());
+        }
+        let path = state_path();
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_vec(&state)?)?;
+        std::fs::rename(&tmp, &path)?;
+        self.last = Some(state);
+        Ok(())
+    }
+
+    /// 关闭输出时删除文件，状态栏显示为空
+    pub fn clear(&mut self) {
+        if self.last.take().is_some() {
+            let _ = std::fs::remove_file(state_path());
+        }
+    }
+}
+
+impl Drop for BarOutput {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Text,
+    Waybar,
+    I3blocks,
+}
+
+struct BarOptions {
+    format: Format,
+    output: Option<PathBuf>,
+    once: bool,
+}
+
+/// 入口：args 为 `bar` 之后的参数，返回进程退出码
+pub fn run(args: &[String]) -> i32 {
+    if args.iter().any(|a| a == "--help" || a == "-h") {
+        println!("{}", USAGE);
+        return 0;
+    }
+    let options = match parse_args(args) {
+        Ok(o) => o,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            return 2;
+        }
+    };
+    let mut output: Option<Box<dyn Write>> = None;
+    let mut last_line = None;
+    loop {
+        let line = render(read_state().as_ref(), Utc::now().timestamp(), options.format);
+        if last_line.as_ref() != Some(&line) {
+            if output.is_none() {
+                output = match open_output(&options) {
+                    Ok(out) => Some(out),
+                    Err(e) => {
+                        eprintln!("打开输出失败：{}", e);
+                        return 1;
+                    }
+                };
+            }
+            let written = output.as_mut().map(|out| writeln!(out, "{}", line).and_then(|_| out.flush()));
+            match written {
+                Some(Ok(())) => last_line = Some(line),
+                // 读端关闭：标准输出说明状态栏已退出；FIFO 则等下一个读端
+                Some(Err(_)) if options.output.is_none() => return 0,
+                _ => {
+                    output = None;
+                    last_line = None;
+                }
+            }
+        }
+        if options.once {
+            return 0;
+        }
+        std::thread::sleep(REFRESH);
+    }
+}
+
+fn parse_args(args: &[String]) -> Result<BarOptions, String> {
+    let mut options = BarOptions { format: Format::Text, output: None, once: false };
+    let mut it = args.iter();
+    while let Some(arg) = it.next() {
+        match arg.as_str() {
+            "--format" => {
+                options.format = match it.next().ok_or("--format 缺少格式")?.as_str() {
+                    "text" => Format::Text,
+                    "waybar" => Format::Waybar,
+                    "i3blocks" => Format::I3blocks,
+                    other => return Err(format!("无法识别的格式：{}", other)),
+                }
+            }
+            "--output" => options.output = Some(PathBuf::from(it.next().ok_or("--output 缺少路径")?)),
+            "--once" => options.once = true,
+            other => return Err(format!("无法识别的参数：{}", other)),
+        }
+    }
+    Ok(options)
+}
+
+/// 打开 FIFO 会等到有读端为止
+fn open_output(options: &BarOptions) -> std::io::Result<Box<dyn Write>> {
+    match &options.output {
+        Some(path) => Ok(Box::new(std::fs::OpenOptions::new().append(true).create(true).open(path)?)),
+        None => Ok(Box::new(std::io::stdout())),
+    }
+}
+
+/// 文件不存在（界面未运行或未开启）或内容不完整时为 None
+fn read_state() -> Option<BarState> {
+    serde_json::from_slice(&std::fs::read(state_path()).ok()?).ok()
+}
+
+/// 按格式生成一行输出；无状态时为空内容
+fn render(state: Option<&BarState>, now: i64, format: Format) -> String {
+    let Some(state) = state else {
+        return match format {
+            Format::Text => String::new(),
+            Format::Waybar => serde_json::json!({ "text": "", "class": "stopped" }).to_string(),
+            Format::I3blocks => serde_json::json!({ "full_text": "" }).to_string(),
+        };
+    };
+    let remaining = state.remaining_at(now);
+    let icon = if state.phase == Phase::Focus { "🍅" } else { "☕" };
+    let time = if remaining < 0 {
+        format!("+{:02}:{:02}", -remaining / 60, -remaining % 60)
+    } else {
+        format!("{:02}:{:02}", remaining / 60, remaining % 60)
+    };
+    let idle = state.ends_at.is_none() && !state.paused;
+    let pause_mark = if state.paused { " ⏸" } else { "" };
+    let label = if state.phase == Phase::Focus && !state.task.trim().is_empty() {
+        state.task.trim()
+    } else {
+        state.phase_label.as_str()
+    };
+    let text = format!("{} {}{} · {}", icon, time, pause_mark, label);
+    let class = match state.phase {
+        _ if idle => "idle",
+        _ if state.paused => "paused",
+        Phase::Focus => "focus",
+        Phase::ShortBreak => "short_break",
+        Phase::LongBreak => "long_break",
+    };
+    match format {
+        Format::Text => text,
+        Format::Waybar => {
+            let elapsed = (state.total_secs - remaining.max(0)).max(0);
+            let percentage = if state.total_secs > 0 { (elapsed * 100 / state.total_secs).min(100) } else { 0 };
+            let tooltip = if state.task.trim().is_empty() {
+                format!("红番茄 · {}", state.phase_label)
+            } else {
+                format!("红番茄 · {} · {}", state.phase_label, state.task.trim())
+            };
+            serde_json::json!({ "text": text, "tooltip": tooltip, "class": class, "percentage": percentage }).to_string()
+        }
+        Format::I3blocks => {
+            let color = match state.phase {
+                Phase::Focus => "#D91153",
+                Phase::ShortBreak | Phase::LongBreak => "#2EA88F",
+            };
+            let short = format!("{} {}", icon, time);
+            serde_json::json!({ "full_text": text, "short_text": short, "color": color }).to_string()
+        }
+    }
+}
@@ -0,0 +1,147 @@
+//! 休息时的呼吸引导：短 / 长休息中在主界面显示随呼吸节奏缩放的圆圈（4-7-8 或方块呼吸），引导真正放松
+//!
+//! 节奏只由引导开始后经过的时间决定；减少动态效果时圆圈不缩放，只显示当前步骤与倒计时。
+
+use egui::{Color32, Sense};
+use serde::{Deserialize, Serialize};
+
+/// 呼吸节奏
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BreathPattern {
+    /// 吸气 4 秒、屏息 7 秒、呼气 8 秒
+    FourSevenEight,
+    /// 方块呼吸：吸气、屏息、呼气、屏息各 4 秒
+    Box,
+}
+
+impl BreathPattern {
+    pub const ALL: [BreathPattern; 2] = [BreathPattern::FourSevenEight, BreathPattern::Box];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            BreathPattern::FourSevenEight => "4-7-8 呼吸",
+            BreathPattern::Box => "方块呼吸（4-4-4-4）",
+        }
+    }
+
+    /// 一轮的各步与秒数
+    fn steps(self) -> &'static [(BreathStep, u32)] {
+        match self {
+            BreathPattern::FourSevenEight => &[(BreathStep::Inhale, 4), (BreathStep::Hold, 7), (BreathStep::Exhale, 8)],
+            BreathPattern::Box => &[
+                (BreathStep::Inhale, 4),
+                (BreathStep::Hold, 4),
+                (BreathStep::Exhale, 4),
+                (BreathStep::HoldEmpty, 4),
+            ],
+        }
+    }
+}
+
+/// 一轮中的步骤
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BreathStep {
+    Inhale,
+    Hold,
+    Exhale,
+    /// 呼气后屏息
+    HoldEmpty,
+}
+
+impl BreathStep {
+    pub fn label(self) -> &'static str {
+        match self {
+            BreathStep::Inhale => "吸气",
+            BreathStep::Hold | BreathStep::HoldEmpty => "屏息",
+            BreathStep::Exhale => "呼气",
+        }
+    }
+}
+
+/// 呼吸引导设置（仅本机）
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BreathingSettings {
+    pub enabled: bool,
+    pub pattern: BreathPattern,
+}
+
+impl Default for BreathingSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            pattern: BreathPattern::FourSevenEight,
+        }
+    }
+}
+
+/// 某一时刻的引导状态
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BreathFrame {
+    pub step: BreathStep,
+    /// 本步剩余整秒数（至少 1）
+    pub secs_left: u32,
+    /// 圆圈大小 0.0（呼尽）..=1.0（吸满）
+    pub fullness: f32,
+}
+
+/// 引导开始 `elapsed_secs` 秒后的步骤与圆圈大小
+pub fn frame_at(pattern: BreathPattern, elapsed_secs: f32) -> BreathFrame {
+    let steps = pattern.steps();
+    let cycle: u32 = steps.iter().map(|(_, secs)| secs).sum();
+    let mut t = elapsed_secs.max(0.0) % cycle as f32;
+    for &(step, secs) in steps {
+        if t < secs as f32 {
+            let progress = ease_in_out(t / secs as f32);
+            let fullness = match step {
+                BreathStep::Inhale => progress,
+                BreathStep::Hold => 1.0,
+                BreathStep::Exhale => 1.0 - progress,
+                BreathStep::HoldEmpty => 0.0,
+            };
+            return BreathFrame {
+                step,
+                secs_left: (secs as f32 - t).ceil().max(1.0) as u32,
+                fullness,
+            };
+        }
+        t -= secs as f32;
+    }
+    // 浮点误差落在一轮末尾时视为新一轮开始
+    BreathFrame {
+        step: steps[0].0,
+        secs_left: steps[0].1,
+        fullness: 0.0,
+    }
+}
+
+fn ease_in_out(t: f32) -> f32 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// 绘制呼吸圆圈：外圈为吸满时的大小，内圆随呼吸缩放，中间写步骤与倒计时；`steady` 时内圆保持中等大小
+pub fn paint(ui: &mut egui::Ui, frame: BreathFrame, diameter: f32, color: Color32, text: Color32, steady: bool) {
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(diameter, diameter), Sense::hover());
+    let painter = ui.painter_at(rect);
+    let center = rect.center();
+    let max_r = diameter * 0.5 - 1.0;
+    let min_r = max_r * 0.45;
+    painter.circle_stroke(center, max_r, egui::Stroke::new(1.0, color.gamma_multiply(0.5)));
+    let fullness = if steady { 0.6 } else { frame.fullness };
+    let r = min_r + (max_r - min_r) * fullness;
+    painter.circle_filled(center, r, color.gamma_multiply(0.35));
+    painter.text(
+        center - egui::vec2(0.0, 8.0),
+        egui::Align2::CENTER_CENTER,
+        frame.step.label(),
+        egui::FontId::proportional(16.0),
+        text,
+    );
+    painter.text(
+        center + egui::vec2(0.0, 12.0),
+        egui::Align2::CENTER_CENTER,
+        frame.secs_left.to_string(),
+        egui::FontId::proportional(13.0),
+        text,
+    );
+}
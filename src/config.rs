@@ -0,0 +1,191 @@
+//! 配置文件：数据目录下可手工编辑的 `config.toml`（便于放进 dotfiles 管理）
+//!
+//! 可写时长、主题、快捷键、滴答声、Webhook / 手机推送与同步设置，各项均可省略，未写的项保持界面中的设置。
+//! 启动时读取，运行中每隔几秒比较修改时间，文件变化后重新加载并覆盖界面中的对应设置；部署策略禁用的集成仍然关闭。
+//! 文件格式错误时不应用，在设置中显示原因。
+//!
+//! ```toml
+//! theme = "dark"
+//!
+//! [durations]
+//! focus_minutes = 50
+//! short_break_minutes = 10
+//!
+//! [hotkeys]
+//! quick_capture = "Ctrl+Shift+I"
+//!
+//! [sound]
+//! tick = true
+//! tick_volume = 40
+//!
+//! [hooks]
+//! webhook_url = "https://example.com/hook"
+//!
+//! [sync]
+//! target = "folder"
+//! folder = "D:/Dropbox/red-tomato"
+//!
+//! [linux]
+//! x11 = false
+//! ```
+
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
+
+use eframe::egui;
+use serde::Deserialize;
+
+use crate::data_sync::SyncTarget;
+use crate::theme::ThemeMode;
+
+pub const CONFIG_FILENAME: &str = "config.toml";
+/// 检查文件修改时间的间隔
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// config.toml 的内容
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FileConfig {
+    /// "system" / "dark" / "light"
+    pub theme: Option<String>,
+    pub durations: Durations,
+    pub hotkeys: Hotkeys,
+    pub sound: Sound,
+    pub hooks: Hooks,
+    pub sync: SyncConfig,
+    pub linux: LinuxConfig,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Durations {
+    pub focus_minutes: Option<i64>,
+    pub short_break_minutes: Option<i64>,
+    pub long_break_minutes: Option<i64>,
+    pub pomodoros_before_long: Option<u32>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Hotkeys {
+    /// 随手记，如 "Ctrl+I"、"Ctrl+Shift+Space"（Ctrl 在 macOS 上为 ⌘）
+    pub quick_capture: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Sound {
+    /// 专注中的滴答声
+    pub tick: Option<bool>,
+    /// 滴答声音量 0–100
+    pub tick_volume: Option<u8>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Hooks {
+    pub webhook_url: Option<String>,
+    pub push_url: Option<String>,
+    /// 推送访问令牌（Bearer）
+    pub push_token: Option<String>,
+    pub telegram_token: Option<String>,
+    pub telegram_chat_id: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SyncConfig {
+    /// 设置同步端点与令牌
+    pub settings_url: Option<String>,
+    pub settings_token: Option<String>,
+    /// 专注记录同步位置
+    pub target: Option<SyncTarget>,
+    pub folder: Option<String>,
+    pub url: Option<String>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub interval_minutes: Option<u32>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LinuxConfig {
+    /// Wayland 会话下是否改用 XWayland 运行（钉住的小窗才能固定到右上角并置顶）；未设置时有 XWayland 就改用，
+    /// false 时保持原生 Wayland。启动时读取，修改后需重启
+    pub x11: Option<bool>,
+}
+
+impl FileConfig {
+    /// 主题名（不区分大小写）；无法识别时为 None
+    pub fn theme_mode(&self) -> Option<ThemeMode> {
+        match self.theme.as_deref()?.to_ascii_lowercase().as_str() {
+            "system" => Some(ThemeMode::System),
+            "dark" => Some(ThemeMode::Dark),
+            "light" => Some(ThemeMode::Light),
+            _ => None,
+        }
+    }
+}
+
+/// 解析 "Ctrl+Shift+I" 形式的快捷键；Ctrl / Cmd 均映射为平台的命令键
+pub fn parse_shortcut(s: &str) -> Option<egui::KeyboardShortcut> {
+    let mut modifiers = egui::Modifiers::NONE;
+    let mut key = None;
+    for part in s.split('+').map(str::trim) {
+        match part.to_ascii_lowercase().as_str() {
+            "ctrl" | "control" | "cmd" | "command" => modifiers |= egui::Modifiers::COMMAND,
+            "shift" => modifiers |= egui::Modifiers::SHIFT,
+            "alt" | "option" => modifiers |= egui::Modifiers::ALT,
+            _ if key.is_none() => key = Some(egui::Key::from_name(part)?),
+            _ => return None,
+        }
+    }
+    Some(egui::KeyboardShortcut::new(modifiers, key?))
+}
+
+pub fn config_path() -> PathBuf {
+    crate::db::data_dir().join(CONFIG_FILENAME)
+}
+
+fn load() -> Result<FileConfig, String> {
+    let text = std::fs::read_to_string(config_path()).map_err(|e| format!("读取 {} 失败：{}", CONFIG_FILENAME, e))?;
+    let config: FileConfig = toml::from_str(&text).map_err(|e| format!("{} 格式错误，未应用：{}", CONFIG_FILENAME, e))?;
+    if let Some(theme) = config.theme.as_ref().filter(|_| config.theme_mode().is_none()) {
+        return Err(format!("{} 中的主题 \"{}\" 无法识别，未应用", CONFIG_FILENAME, theme));
+    }
+    if let Some(shortcut) = config.hotkeys.quick_capture.as_ref().filter(|s| parse_shortcut(s).is_none()) {
+        return Err(format!("{} 中的快捷键 \"{}\" 无法识别，未应用", CONFIG_FILENAME, shortcut));
+    }
+    Ok(config)
+}
+
+/// 启动时读取创建窗口前就要用的项；文件不存在或有错误时为 None（错误由界面中的监视报告）
+pub fn load_at_startup() -> Option<FileConfig> {
+    load().ok()
+}
+
+/// 监视 config.toml：首次调用及文件修改后返回新内容
+#[derive(Default)]
+pub struct ConfigWatcher {
+    last_check: Option<Instant>,
+    modified: Option<SystemTime>,
+}
+
+impl ConfigWatcher {
+    /// 每帧调用；到检查间隔且文件存在并有变化时读取。文件被删除时不改动已应用的设置
+    pub fn poll(&mut self) -> Option<Result<FileConfig, String>> {
+        if self.last_check.is_some_and(|t| t.elapsed() < POLL_INTERVAL) {
+            return None;
+        }
+        self.last_check = Some(Instant::now());
+        let Ok(modified) = std::fs::metadata(config_path()).and_then(|m| m.modified()) else {
+            self.modified = None;
+            return None;
+        };
+        if self.modified == Some(modified) {
+            return None;
+        }
+        self.modified = Some(modified);
+        Some(load())
+    }
+}
@@ -0,0 +1,181 @@
+//! 数据库加密（可选）：在共用电脑上保护任务名与专注承诺
+//!
+//! 应用层加密：由口令经 PBKDF2-HMAC-SHA256 派生两把密钥，用 AES-256-GCM 加密字段，
+//! nonce 取明文的 HMAC（合成 IV），同一明文得到同一密文，按任务分组、去重与 `rt_text()` 搜索照常可用；
+//! 代价是能看出哪些记录的任务名相同。时间、时长等数值列不加密。
+//!
+//! 密文以 `enc1:` 开头（base64），明文与密文可在同一列共存，便于从明文库迁移。
+//! 口令只保存在内存中，丢失后无法恢复数据。
+
+use std::num::NonZeroU32;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use ring::aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey};
+use ring::rand::{SecureRandom, SystemRandom};
+use ring::{hmac, pbkdf2};
+
+/// 密文前缀（含格式版本）
+pub const PREFIX: &str = "enc1:";
+/// PBKDF2 迭代次数
+const PBKDF2_ITERATIONS: u32 = 210_000;
+/// 盐长度（字节）
+pub const SALT_LEN: usize = 16;
+/// 用来校验口令的固定明文：加密后存入数据库，解锁时能解开即口令正确
+pub const VERIFIER: &str = "red-tomato";
+
+/// 由口令派生的密钥：加密用 AES 密钥 + 生成 nonce 用的 HMAC 密钥
+pub struct Key {
+    aead: LessSafeKey,
+    nonce_mac: hmac::Key,
+}
+
+impl Key {
+    pub fn derive(passphrase: &str, salt: &[u8]) -> Self {
+        let mut material = [0u8; 64];
+        pbkdf2::derive(
+            pbkdf2::PBKDF2_HMAC_SHA256,
+            NonZeroU32::new(PBKDF2_ITERATIONS).unwrap(),
+            salt,
+            passphrase.as_bytes(),
+            &mut material,
+        );
+        let aead = LessSafeKey::new(UnboundKey::new(&AES_256_GCM, &material[..32]).unwrap());
+        let nonce_mac = hmac::Key::new(hmac::HMAC_SHA256, &material[32..]);
+        Self { aead, nonce_mac }
+    }
+
+    /// 加密为 `enc1:<base64(nonce || 密文 || tag)>`
+    pub fn seal(&self, plain: &str) -> String {
+        let tag = hmac::sign(&self.nonce_mac, plain.as_bytes());
+        let mut nonce = [0u8; NONCE_LEN];
+        nonce.copy_from_slice(&tag.as_ref()[..NONCE_LEN]);
+        let mut buf = plain.as_bytes().to_vec();
+        self.aead
+            .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut buf)
+            .expect("明文长度远小于 AES-GCM 上限");
+        let mut out = nonce.to_vec();
+        out.extend_from_slice(&buf);
+        format!("{}{}", PREFIX, BASE64.encode(out))
+    }
+
+    /// 加密尚未加密的内容：空串与本密钥的密文原样保留
+    pub fn protect(&self, plain: &str) -> String {
+        if plain.is_empty() || self.open(plain).is_some() {
+            return plain.to_string();
+        }
+        self.seal(plain)
+    }
+
+    /// 解密；不是本密钥加密的内容返回 None
+    pub fn open(&self, stored: &str) -> Option<String> {
+        let raw = BASE64.decode(stored.strip_prefix(PREFIX)?).ok()?;
+        if raw.len() < NONCE_LEN {
+            return None;
+        }
+        let (nonce, body) = raw.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce).ok()?;
+        let mut body = body.to_vec();
+        let plain = self.aead.open_in_place(nonce, Aad::empty(), &mut body).ok()?;
+        String::from_utf8(plain.to_vec()).ok()
+    }
+}
+
+/// 新的随机盐
+pub fn new_salt() -> [u8; SALT_LEN] {
+    let mut salt = [0u8; SALT_LEN];
+    SystemRandom::new().fill(&mut salt).expect("系统随机数不可用");
+    salt
+}
+
+pub fn encode_salt(salt: &[u8]) -> String {
+    BASE64.encode(salt)
+}
+
+pub fn decode_salt(text: &str) -> Option<Vec<u8>> {
+    BASE64.decode(text).ok()
+}
+
+/// 最近一次打开的数据库是否已启用加密（每次打开数据库时刷新）
+static ENABLED: AtomicBool = AtomicBool::new(false);
+/// 解锁后的密钥
+static KEY: RwLock<Option<Arc<Key>>> = RwLock::new(None);
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// 已启用加密但尚未输入口令
+pub fn is_locked() -> bool {
+    is_enabled() && key().is_none()
+}
+
+pub fn set_key(key: Option<Key>) {
+    *KEY.write().unwrap() = key.map(Arc::new);
+}
+
+fn key() -> Option<Arc<Key>> {
+    KEY.read().unwrap().clone()
+}
+
+/// 写入前：启用加密时加密（空串与已加密的内容原样保留）；已启用但未解锁时为 None，调用方不得写入明文
+pub fn protect(plain: &str) -> Option<String> {
+    if !is_enabled() {
+        return Some(plain.to_string());
+    }
+    Some(key()?.protect(plain))
+}
+
+/// 读取后：能用当前密钥解开的密文解密，其余原样返回
+pub fn reveal(stored: &str) -> String {
+    if !stored.starts_with(PREFIX) {
+        return stored.to_string();
+    }
+    key()
+        .and_then(|k| k.open(stored))
+        .unwrap_or_else(|| stored.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seal_and_open_round_trip() {
+        let salt = new_salt();
+        let key = Key::derive("正确的口令", &salt);
+        let sealed = key.seal("写周报");
+        assert!(sealed.starts_with(PREFIX));
+        assert_ne!(sealed, "写周报");
+        assert_eq!(key.open(&sealed).as_deref(), Some("写周报"));
+        // nonce 由明文决定：同一明文的密文相同，便于按值比较
+        assert_eq!(key.seal("写周报"), sealed);
+        assert_eq!(key.open(&key.seal("")).as_deref(), Some(""));
+    }
+
+    #[test]
+    fn wrong_key_cannot_open() {
+        let salt = new_salt();
+        let key = Key::derive("正确的口令", &salt);
+        let sealed = key.seal("写周报");
+        assert_eq!(Key::derive("错误的口令", &salt).open(&sealed), None);
+        assert_eq!(Key::derive("正确的口令", &new_salt()).open(&sealed), None);
+        assert_eq!(key.open("写周报"), None);
+        assert_eq!(key.open("enc1:不是base64"), None);
+    }
+
+    #[test]
+    fn protect_keeps_empty_and_sealed_text() {
+        let key = Key::derive("正确的口令", &new_salt());
+        assert_eq!(key.protect(""), "");
+        let sealed = key.protect("写周报");
+        assert_eq!(key.protect(&sealed), sealed);
+        assert_eq!(key.open(&sealed).as_deref(), Some("写周报"));
+    }
+}
@@ -0,0 +1,67 @@
+//! 日记笔记集成：每完成一个番茄，向按日期命名的 Markdown 文件（如 Obsidian 日记）追加一行
+//!
+//! 路径模板中的 `{date}` 替换为 YYYY-MM-DD，`{year}` / `{month}` / `{day}` 分别替换为年、两位月、两位日；
+//! 开头的 `~` 展开为用户主目录。文件或目录不存在时自动创建。
+
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::PathBuf;
+
+/// 日记集成配置（仅本机）
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DailyNoteConfig {
+    pub enabled: bool,
+    /// 文件路径模板，如 `~/Obsidian/Daily/{date}.md`
+    pub path_template: String,
+}
+
+impl DailyNoteConfig {
+    pub fn is_active(&self) -> bool {
+        self.enabled && !self.path_template.trim().is_empty()
+    }
+}
+
+/// 按日期展开路径模板
+pub fn expand_path(template: &str, day: DateTime<FixedOffset>) -> PathBuf {
+    let path = template
+        .trim()
+        .replace("{date}", &day.format("%Y-%m-%d").to_string())
+        .replace("{year}", &day.format("%Y").to_string())
+        .replace("{month}", &day.format("%m").to_string())
+        .replace("{day}", &day.format("%d").to_string());
+    match path.strip_prefix("~/").zip(dirs::home_dir()) {
+        Some((rest, home)) => home.join(rest),
+        None => PathBuf::from(path),
+    }
+}
+
+/// 一个番茄对应的列表项：`- 🍅 14:00–14:25 写周报`
+pub fn pomodoro_bullet(start: DateTime<FixedOffset>, end: DateTime<FixedOffset>, task: &str) -> String {
+    let task = task.trim();
+    let task = if task.is_empty() { "(无任务)" } else { task };
+    format!("- 🍅 {}–{} {}", start.format("%H:%M"), end.format("%H:%M"), task)
+}
+
+/// 把一个完成的番茄追加到当天的笔记文件，返回写入的文件路径
+pub fn append_pomodoro(
+    config: &DailyNoteConfig,
+    start: DateTime<FixedOffset>,
+    end: DateTime<FixedOffset>,
+    task: &str,
+) -> std::io::Result<PathBuf> {
+    let path = expand_path(&config.path_template, end);
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    // 已有内容且不以换行结尾时先补一个换行，避免接在用户最后一行后面
+    let needs_newline = std::fs::read(&path)
+        .map(|b| b.last().is_some_and(|c| *c != b'\n'))
+        .unwrap_or(false);
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(&path)?;
+    if needs_newline {
+        writeln!(file)?;
+    }
+    writeln!(file, "{}", pomodoro_bullet(start, end, task))?;
+    Ok(path)
+}
@@ -0,0 +1,390 @@
+//! 数据同步：定期把专注记录导出到 WebDAV 地址或同步盘文件夹（Dropbox / OneDrive 等），并导入其他电脑导出的记录
+//!
+//! 所有电脑共用一个快照文件 `red-tomato-sync.json`。每次同步先读取快照并并入本机（按任务 + 完成时间 + 时长去重，
+//! 见 `merge::merge_records`），本机记录与快照不一致时再写回完整快照。同一条记录两边项目不同时以最后写入者为准：
+//! 快照由其他电脑在本机上次同步之后写入，则采用快照中的项目；否则保留本机的项目并覆盖快照。
+//! 删除不会同步（清除数据后其他电脑的记录会在下次同步时补回）；数据库加密时不同步（快照为明文）。
+//! 文件读写与网络请求在后台线程执行，结果通过 channel 交回 UI 线程。
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver};
+
+use crate::db::{self, PortableRecord};
+use crate::history::FocusRecord;
+use crate::pomodoro::PauseStats;
+
+/// 快照文件名（同步文件夹中或 WebDAV 地址下）
+pub const SNAPSHOT_FILENAME: &str = "red-tomato-sync.json";
+/// 默认同步间隔（分钟）
+pub const DEFAULT_INTERVAL_MINUTES: u32 = 15;
+/// 请求超时（秒）
+const TIMEOUT_SECS: u64 = 30;
+
+/// 同步位置
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncTarget {
+    #[default]
+    Off,
+    /// 本机上由同步盘客户端同步的文件夹
+    Folder,
+    WebDav,
+}
+
+impl SyncTarget {
+    pub fn label(self) -> &'static str {
+        match self {
+            SyncTarget::Off => "关闭",
+            SyncTarget::Folder => "同步文件夹",
+            SyncTarget::WebDav => "WebDAV",
+        }
+    }
+}
+
+/// 数据同步设置（仅本机，不参与设置同步）
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DataSyncSettings {
+    pub target: SyncTarget,
+    pub folder: String,
+    /// WebDAV 目录地址，快照存放在其下
+    pub url: String,
+    pub username: String,
+    pub password: String,
+    pub interval_minutes: u32,
+    /// 本机标识，写入快照以区分由哪台电脑导出
+    pub device: String,
+    /// 本机上次与快照一致时快照的导出时间（RFC3339），用于判断快照是否由其他电脑更新过
+    pub last_synced_at: Option<String>,
+}
+
+impl Default for DataSyncSettings {
+    fn default() -> Self {
+        Self {
+            target: SyncTarget::Off,
+            folder: String::new(),
+            url: String::new(),
+            username: String::new(),
+            password: String::new(),
+            interval_minutes: DEFAULT_INTERVAL_MINUTES,
+            device: String::new(),
+            last_synced_at: None,
+        }
+    }
+}
+
+impl DataSyncSettings {
+    pub fn is_configured(&self) -> bool {
+        match self.target {
+            SyncTarget::Off => false,
+            SyncTarget::Folder => !self.folder.trim().is_empty(),
+            SyncTarget::WebDav => !self.url.trim().is_empty(),
+        }
+    }
+
+    fn folder_path(&self) -> PathBuf {
+        PathBuf::from(self.folder.trim()).join(SNAPSHOT_FILENAME)
+    }
+
+    fn webdav_url(&self) -> String {
+        format!("{}/{}", self.url.trim().trim_end_matches('/'), SNAPSHOT_FILENAME)
+    }
+}
+
+/// 快照文件内容
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    exported_at: String,
+    device: String,
+    records: Vec<SnapshotRecord>,
+}
+
+/// 快照中的一条记录（项目以名称表示）
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+struct SnapshotRecord {
+    task: String,
+    duration_secs: i64,
+    completed_at: String,
+    completed_pomodoros: u32,
+    #[serde(default)]
+    commitment: String,
+    status: String,
+    #[serde(default)]
+    pause_count: u32,
+    #[serde(default)]
+    paused_secs: i64,
+    #[serde(default)]
+    overtime_secs: i64,
+    #[serde(default)]
+    project: Option<String>,
+    #[serde(default)]
+    note: String,
+}
+
+impl From<PortableRecord> for SnapshotRecord {
+    fn from(p: PortableRecord) -> Self {
+        Self {
+            task: p.record.task,
+            duration_secs: p.record.duration_secs,
+            completed_at: p.record.completed_at,
+            completed_pomodoros: p.record.completed_pomodoros,
+            commitment: p.record.commitment,
+            status: p.status,
+            pause_count: p.record.pauses.count,
+            paused_secs: p.record.pauses.secs,
+            overtime_secs: p.record.overtime_secs,
+            project: p.project_name,
+            note: p.note,
+        }
+    }
+}
+
+impl From<SnapshotRecord> for PortableRecord {
+    fn from(s: SnapshotRecord) -> Self {
+        Self {
+            record: FocusRecord {
+                task: s.task,
+                duration_secs: s.duration_secs,
+                completed_at: s.completed_at,
+                completed_pomodoros: s.completed_pomodoros,
+                commitment: s.commitment,
+                pauses: PauseStats {
+                    count: s.pause_count,
+                    secs: s.paused_secs,
+                },
+                project_id: db::UNCATEGORIZED_PROJECT_ID,
+                overtime_secs: s.overtime_secs,
+            },
+            status: s.status,
+            project_name: s.project,
+            note: s.note,
+        }
+    }
+}
+
+/// 一次同步的结果
+#[derive(Clone, Debug, Default)]
+pub struct SyncReport {
+    /// 从快照并入的新记录数
+    pub pulled: usize,
+    /// 按快照改了项目归属的记录数
+    pub reassigned: usize,
+    /// 是否写回了快照
+    pub pushed: bool,
+    /// 同步后本机与快照一致时快照的导出时间
+    pub synced_at: String,
+}
+
+impl SyncReport {
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if self.pulled > 0 {
+            parts.push(format!("导入 {} 条", self.pulled));
+        }
+        if self.reassigned > 0 {
+            parts.push(format!("{} 条改为其他电脑的项目", self.reassigned));
+        }
+        if self.pushed {
+            parts.push("已上传本机记录".to_string());
+        }
+        if parts.is_empty() {
+            "已是最新".to_string()
+        } else {
+            parts.join("，")
+        }
+    }
+
+    /// 本机数据库是否有变化（需重新加载历史）
+    pub fn changed_local(&self) -> bool {
+        self.pulled > 0 || self.reassigned > 0
+    }
+}
+
+/// 后台同步结果
+pub enum DataSyncResult {
+    Done(SyncReport),
+    Failed(String),
+}
+
+/// 数据同步器：同一时间只有一次同步在进行
+#[derive(Default)]
+pub struct DataSync {
+    pending: Option<Receiver<DataSyncResult>>,
+}
+
+impl DataSync {
+    pub fn is_busy(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// 后台执行一次同步
+    pub fn start(&mut self, settings: &DataSyncSettings) {
+        if self.pending.is_some() {
+            return;
+        }
+        let settings = settings.clone();
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let result = match run(&settings) {
+                Ok(report) => DataSyncResult::Done(report),
+                Err(e) => DataSyncResult::Failed(e),
+            };
+            let _ = tx.send(result);
+        });
+        self.pending = Some(rx);
+    }
+
+    /// 每帧调用：取回已完成的同步结果
+    pub fn poll(&mut self) -> Option<DataSyncResult> {
+        let rx = self.pending.as_ref()?;
+        match rx.try_recv() {
+            Ok(result) => {
+                self.pending = None;
+                Some(result)
+            }
+            Err(mpsc::TryRecvError::Empty) => None,
+            Err(mpsc::TryRecvError::Disconnected) => {
+                self.pending = None;
+                Some(DataSyncResult::Failed("同步线程异常退出".to_string()))
+            }
+        }
+    }
+}
+
+/// 新的本机标识（首次同步时生成并保存）
+pub fn new_device_id() -> String {
+    format!("{:x}", chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default())
+}
+
+fn run(settings: &DataSyncSettings) -> Result<SyncReport, String> {
+    if crate::crypto::is_enabled() {
+        return Err("数据库已加密，不同步".to_string());
+    }
+    let remote = read_snapshot(settings)?;
+    let mut report = SyncReport::default();
+    if let Some(snapshot) = &remote {
+        let newer = settings
+            .last_synced_at
+            .as_deref()
+            .is_none_or(|last| snapshot.exported_at.as_str() > last);
+        let prefer_incoming = snapshot.device != settings.device && newer;
+        let records = snapshot.records.iter().cloned().map(PortableRecord::from).collect();
+        let outcome = crate::merge::merge_records(records, prefer_incoming)?;
+        report.pulled = outcome.merged;
+        report.reassigned = outcome.reassigned;
+    }
+    let local = local_records()?;
+    let unchanged = remote.as_ref().is_some_and(|snapshot| {
+        let remote_set: HashSet<&SnapshotRecord> = snapshot.records.iter().collect();
+        remote_set.len() == local.len() && local.iter().all(|r| remote_set.contains(r))
+    });
+    match remote {
+        Some(snapshot) if unchanged => report.synced_at = snapshot.exported_at,
+        _ => {
+            let snapshot = Snapshot {
+                exported_at: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+                device: settings.device.clone(),
+                records: local,
+            };
+            write_snapshot(settings, &snapshot)?;
+            report.pushed = true;
+            report.synced_at = snapshot.exported_at;
+        }
+    }
+    Ok(report)
+}
+
+/// 本机主库与归档库中的全部记录（归档过的记录也要保留在快照中，否则会被反复上传和删除）
+fn local_records() -> Result<Vec<SnapshotRecord>, String> {
+    let conn = db::open_and_init().map_err(|e| e.to_string())?;
+    let mut records = db::load_portable_records(&conn).map_err(|e| e.to_string())?.unwrap_or_default();
+    if let Some(archive) = db::open_archive().map_err(|e| e.to_string())? {
+        records.extend(db::load_portable_records(&archive).map_err(|e| e.to_string())?.unwrap_or_default());
+    }
+    Ok(records.into_iter().map(SnapshotRecord::from).collect())
+}
+
+fn read_snapshot(settings: &DataSyncSettings) -> Result<Option<Snapshot>, String> {
+    let text = match settings.target {
+        SyncTarget::Off => return Ok(None),
+        SyncTarget::Folder => match std::fs::read_to_string(settings.folder_path()) {
+            Ok(text) => Some(text),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(format!("读取快照失败：{}", e)),
+        },
+        SyncTarget::WebDav => webdav_get(settings)?,
+    };
+    text.map(|t| serde_json::from_str(&t).map_err(|e| format!("快照格式错误：{}", e)))
+        .transpose()
+}
+
+fn write_snapshot(settings: &DataSyncSettings, snapshot: &Snapshot) -> Result<(), String> {
+    let body = serde_json::to_string(snapshot).map_err(|e| e.to_string())?;
+    match settings.target {
+        SyncTarget::Off => Ok(()),
+        SyncTarget::Folder => {
+            // 先写临时文件再改名，同步盘客户端不会上传半截内容
+            let path = settings.folder_path();
+            let tmp = path.with_extension("tmp");
+            std::fs::write(&tmp, body)
+                .and_then(|_| std::fs::rename(&tmp, &path))
+                .map_err(|e| format!("写入快照失败：{}", e))
+        }
+        SyncTarget::WebDav => webdav_put(settings, &body),
+    }
+}
+
+fn agent() -> ureq::Agent {
+    ureq::Agent::config_builder()
+        .timeout_global(Some(std::time::Duration::from_secs(TIMEOUT_SECS)))
+        .http_status_as_error(false)
+        .build()
+        .into()
+}
+
+/// Basic 认证头；未填用户名时不发送
+fn authorization(settings: &DataSyncSettings) -> Option<String> {
+    if settings.username.is_empty() {
+        return None;
+    }
+    let credentials = format!("{}:{}", settings.username, settings.password);
+    Some(format!("Basic {}", BASE64.encode(credentials)))
+}
+
+/// GET 快照；404 视为尚无快照
+fn webdav_get(settings: &DataSyncSettings) -> Result<Option<String>, String> {
+    let mut request = agent().get(&settings.webdav_url());
+    if let Some(auth) = authorization(settings) {
+        request = request.header("Authorization", &auth);
+    }
+    let mut resp = request.call().map_err(|e| format!("下载快照失败：{}", e))?;
+    match resp.status().as_u16() {
+        404 => Ok(None),
+        200..=299 => resp
+            .body_mut()
+            .with_config()
+            .limit(u64::MAX)
+            .read_to_string()
+            .map(Some)
+            .map_err(|e| format!("读取响应失败：{}", e)),
+        code => Err(format!("下载快照失败：HTTP {}", code)),
+    }
+}
+
+fn webdav_put(settings: &DataSyncSettings, body: &str) -> Result<(), String> {
+    let mut request = agent()
+        .put(&settings.webdav_url())
+        .header("Content-Type", "application/json");
+    if let Some(auth) = authorization(settings) {
+        request = request.header("Authorization", &auth);
+    }
+    let resp = request.send(body).map_err(|e| format!("上传快照失败：{}", e))?;
+    match resp.status().as_u16() {
+        200..=299 => Ok(()),
+        code => Err(format!("上传快照失败：HTTP {}", code)),
+    }
+}
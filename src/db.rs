@@ -1,33 +1,158 @@
 //! SQLite 持久化：任务与专注记录，便于迁移与长期保存
 
 use rusqlite::Connection;
+use rusqlite::functions::FunctionFlags;
+
+use crate::history::FocusRecord;
+use crate::pomodoro::PauseStats;
 
 /// 数据库文件名（放在应用数据目录下）
 pub const DB_FILENAME: &str = "red_tomato.db";
 
-/// 应用数据目录（可迁移：复制此目录下的 .db 即可）
+/// 应用数据目录（可迁移：复制此目录下的 .db 即可）；便携模式下为可执行文件所在目录
 pub fn data_dir() -> std::path::PathBuf {
+    if let Some(dir) = portable_dir() {
+        return dir.to_path_buf();
+    }
     dirs::data_local_dir()
         .unwrap_or_else(|| std::path::PathBuf::from("."))
         .join("red-tomato")
 }
 
+/// 便携模式标记文件：与可执行文件放在同一目录即启用便携模式
+pub const PORTABLE_FLAG: &str = "portable.flag";
+
+static PORTABLE_DIR: std::sync::OnceLock<Option<std::path::PathBuf>> = std::sync::OnceLock::new();
+
+/// 启动时确定是否为便携模式：传了 `--portable` 或可执行文件旁有 `portable.flag`。只有第一次调用生效，
+/// 之后（含未调用过时的 `portable_dir`）沿用第一次的结果
+pub fn init_portable(arg: bool) -> Option<&'static std::path::Path> {
+    PORTABLE_DIR
+        .get_or_init(|| {
+            let dir = std::env::current_exe().ok()?.parent()?.to_path_buf();
+            (arg || dir.join(PORTABLE_FLAG).exists()).then_some(dir)
+        })
+        .as_deref()
+}
+
+/// 便携模式下的数据目录（可执行文件所在目录），非便携模式为 None
+pub fn portable_dir() -> Option<&'static std::path::Path> {
+    init_portable(false)
+}
+
+/// 数据库路径：依次取部署策略固定的路径、环境变量 `RED_TOMATO_DB_PATH`、设置中选择的位置，否则在数据目录下
 pub fn db_path() -> std::path::PathBuf {
-    data_dir().join(DB_FILENAME)
+    db_location().0
+}
+
+/// 数据库路径由谁决定
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DbLocation {
+    Policy,
+    Env,
+    Custom,
+    Default,
+}
+
+/// 指定数据库文件路径的环境变量，优先于设置中选择的位置
+pub const DB_PATH_ENV: &str = "RED_TOMATO_DB_PATH";
+/// 设置中选择的数据库位置保存在数据目录下的这个文件中（一行路径）；不放在数据库或界面状态里，命令行导入也能读到
+pub const DB_LOCATION_FILENAME: &str = "db_location.txt";
+
+/// 数据库路径及其来源
+pub fn db_location() -> (std::path::PathBuf, DbLocation) {
+    if let Some(path) = crate::policy::get().db_path.clone() {
+        return (path, DbLocation::Policy);
+    }
+    if let Some(path) = std::env::var_os(DB_PATH_ENV).filter(|v| !v.is_empty()) {
+        return (path.into(), DbLocation::Env);
+    }
+    if let Some(path) = custom_db_path() {
+        return (path, DbLocation::Custom);
+    }
+    (data_dir().join(DB_FILENAME), DbLocation::Default)
+}
+
+fn custom_location() -> &'static std::sync::RwLock<Option<std::path::PathBuf>> {
+    static CUSTOM: std::sync::OnceLock<std::sync::RwLock<Option<std::path::PathBuf>>> = std::sync::OnceLock::new();
+    CUSTOM.get_or_init(|| {
+        let saved = std::fs::read_to_string(data_dir().join(DB_LOCATION_FILENAME)).unwrap_or_default();
+        let saved = saved.trim();
+        std::sync::RwLock::new((!saved.is_empty()).then(|| saved.into()))
+    })
+}
+
+/// 设置中选择的数据库文件路径
+pub fn custom_db_path() -> Option<std::path::PathBuf> {
+    custom_location().read().unwrap().clone()
+}
+
+/// 记住设置中选择的数据库文件路径（None 恢复为数据目录下的默认位置）
+pub fn set_custom_db_path(path: Option<&std::path::Path>) -> std::io::Result<()> {
+    let file = data_dir().join(DB_LOCATION_FILENAME);
+    match path {
+        Some(path) => {
+            std::fs::create_dir_all(data_dir())?;
+            std::fs::write(&file, path.to_string_lossy().as_bytes())?;
+        }
+        None => {
+            if file.exists() {
+                std::fs::remove_file(&file)?;
+            }
+        }
+    }
+    *custom_location().write().unwrap() = path.map(std::path::Path::to_path_buf);
+    Ok(())
+}
+
+/// 归档库路径：与主库同目录，保存超过保留期、从主库移出的专注记录
+pub fn archive_path() -> std::path::PathBuf {
+    db_path().with_file_name(ARCHIVE_FILENAME)
 }
 
-/// 打开数据库并创建表（若不存在）
+/// 归档库文件名
+pub const ARCHIVE_FILENAME: &str = "red_tomato_archive.db";
+
+/// 其他连接（界面线程、后台线程、命令行）持有写锁时的等待上限
+const BUSY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// 打开数据库、设置连接参数并创建表（若不存在）
 pub fn open_and_init() -> Result<Connection, rusqlite::Error> {
     let path = db_path();
     if let Some(parent) = path.parent() {
         let _ = std::fs::create_dir_all(parent);
     }
     let conn = Connection::open(&path)?;
+    configure(&conn)?;
+    init_schema(&conn)?;
+    crate::crypto::set_enabled(encryption_params(&conn)?.is_some());
+    Ok(conn)
+}
+
+/// 测试用：建好表的内存数据库
+#[cfg(test)]
+pub(crate) fn open_in_memory() -> Result<Connection, rusqlite::Error> {
+    let conn = Connection::open_in_memory()?;
     init_schema(&conn)?;
     Ok(conn)
 }
 
-/// 创建 focus_records 表
+/// 连接参数：忙等待，本地库启用 WAL（读写互不阻塞，崩溃时未提交的事务不会损坏数据库）。
+/// 部署策略把库固定到其他路径时多为网络盘，WAL 依赖共享内存无法在网络文件系统上使用；用户自选的位置多为同步盘，
+/// WAL 中尚未检查点的内容不在 .db 文件里、会被单独同步，这两种情况都保持默认的回滚日志
+fn configure(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.busy_timeout(BUSY_TIMEOUT)?;
+    // rt_text(x)：读取任务名 / 承诺时解密（未加密的内容原样返回），查询中统一用它包一层
+    conn.create_scalar_function("rt_text", 1, FunctionFlags::SQLITE_UTF8, |ctx| {
+        Ok(ctx.get::<Option<String>>(0)?.map(|s| crate::crypto::reveal(&s)))
+    })?;
+    if db_location().1 == DbLocation::Default {
+        conn.pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get::<_, String>(0))?;
+    }
+    Ok(())
+}
+
+/// 创建 focus_records、interruptions（专注中断）、journal_entries（每日回顾日志）、plan_blocks（当天计划）、calendar_events（导入的日历事件）表
 fn init_schema(conn: &Connection) -> Result<(), rusqlite::Error> {
     conn.execute_batch(
         r#"
@@ -38,11 +163,227 @@ fn init_schema(conn: &Connection) -> Result<(), rusqlite::Error> {
             completed_at TEXT NOT NULL,
             completed_pomodoros INTEGER NOT NULL
         );
+        CREATE TABLE IF NOT EXISTS plan_blocks (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            title TEXT NOT NULL,
+            start_at TEXT NOT NULL,
+            end_at TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS interruptions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            occurred_at TEXT NOT NULL,
+            kind TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS journal_entries (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            day TEXT NOT NULL,
+            prompt TEXT NOT NULL,
+            answer TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS calendar_events (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            uid TEXT NOT NULL UNIQUE,
+            summary TEXT NOT NULL,
+            start_at TEXT NOT NULL,
+            end_at TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS notification_digest (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            occurred_at TEXT NOT NULL,
+            event TEXT NOT NULL,
+            message TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS projects (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE
+        );
+        INSERT OR IGNORE INTO projects (id, name) VALUES (1, '未分类');
+        CREATE TABLE IF NOT EXISTS energy_ratings (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            rated_at TEXT NOT NULL,
+            energy INTEGER NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS schedule (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            start_at TEXT NOT NULL,
+            task TEXT NOT NULL,
+            pomodoros INTEGER NOT NULL,
+            project_id INTEGER NOT NULL DEFAULT 1,
+            reminded INTEGER NOT NULL DEFAULT 0
+        );
+        CREATE TABLE IF NOT EXISTS task_completions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            task TEXT NOT NULL,
+            project_id INTEGER NOT NULL DEFAULT 1,
+            pomodoros INTEGER NOT NULL,
+            completed_at TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS distractions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            keyword TEXT NOT NULL,
+            title TEXT NOT NULL,
+            started_at TEXT NOT NULL,
+            duration_secs INTEGER NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS inbox (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            text TEXT NOT NULL,
+            captured_at TEXT NOT NULL,
+            done_at TEXT NOT NULL DEFAULT ''
+        );
+        CREATE TABLE IF NOT EXISTS encryption (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            salt TEXT NOT NULL,
+            verifier TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS task_colors (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            task TEXT NOT NULL,
+            color TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS achievements (
+            key TEXT PRIMARY KEY,
+            unlocked_at TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS break_events (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            occurred_at TEXT NOT NULL,
+            phase TEXT NOT NULL,
+            skipped INTEGER NOT NULL
+        );
+        "#,
+    )?;
+    // 旧库迁移：后续版本新增的列
+    ensure_column(conn, "focus_records", "commitment", "TEXT NOT NULL DEFAULT ''")?;
+    ensure_column(conn, "focus_records", "status", "TEXT NOT NULL DEFAULT 'completed'")?;
+    ensure_column(conn, "focus_records", "pause_count", "INTEGER NOT NULL DEFAULT 0")?;
+    ensure_column(conn, "focus_records", "paused_secs", "INTEGER NOT NULL DEFAULT 0")?;
+    // 引入项目前的记录归入「未分类」
+    ensure_column(conn, "focus_records", "project_id", "INTEGER NOT NULL DEFAULT 1")?;
+    ensure_column(conn, "focus_records", "overtime_secs", "INTEGER NOT NULL DEFAULT 0")?;
+    // 记录的 Markdown 笔记（完成了什么、卡在哪里），在统计窗口中编辑
+    ensure_column(conn, "focus_records", "note", "TEXT NOT NULL DEFAULT ''")?;
+    // 日程的添加时间与任务完成时间（为空表示旧数据 / 未完成），用于统计任务交付时长
+    ensure_column(conn, "schedule", "created_at", "TEXT NOT NULL DEFAULT ''")?;
+    ensure_column(conn, "schedule", "task_completed_at", "TEXT NOT NULL DEFAULT ''")?;
+    // 项目颜色（"#rrggbb"，为空表示未指定）
+    ensure_column(conn, "projects", "color", "TEXT NOT NULL DEFAULT ''")?;
+    // 索引在补列之后创建（引用的列此时一定存在）
+    for (name, table, columns) in INDEXES {
+        conn.execute_batch(&format!("CREATE INDEX IF NOT EXISTS {name} ON {table} ({columns});"))?;
+    }
+    Ok(())
+}
+
+/// 查询索引：(索引名, 表, 列)
+const INDEXES: [(&str, &str, &str); 5] = [
+    // 按完成时间区间统计、聚合，以及记录列表倒序分页
+    ("idx_focus_records_completed", "focus_records", "completed_at, id"),
+    // 按任务聚合（最近任务、任务排行）与导入 / 回放去重
+    ("idx_focus_records_task", "focus_records", "task, completed_at"),
+    // 按项目筛选与汇总
+    ("idx_focus_records_project", "focus_records", "project_id, completed_at"),
+    // 周报中按区间统计中断
+    ("idx_interruptions_occurred", "interruptions", "occurred_at"),
+    // 计划提醒检查与按天列出日程
+    ("idx_schedule_start", "schedule", "start_at"),
+];
+
+/// 打开归档库；尚未归档过（文件不存在）时为 None
+pub fn open_archive() -> Result<Option<Connection>, rusqlite::Error> {
+    let path = archive_path();
+    if !path.exists() {
+        return Ok(None);
+    }
+    let conn = Connection::open(&path)?;
+    configure(&conn)?;
+    init_archive_schema(&conn)?;
+    Ok(Some(conn))
+}
+
+/// 归档库只有 focus_records 一张表，列与主库一致（id 沿用主库，两库之间不重复）
+fn init_archive_schema(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS focus_records (
+            id INTEGER PRIMARY KEY,
+            task TEXT NOT NULL,
+            duration_secs INTEGER NOT NULL,
+            completed_at TEXT NOT NULL,
+            completed_pomodoros INTEGER NOT NULL,
+            commitment TEXT NOT NULL DEFAULT '',
+            status TEXT NOT NULL DEFAULT 'completed',
+            pause_count INTEGER NOT NULL DEFAULT 0,
+            paused_secs INTEGER NOT NULL DEFAULT 0,
+            project_id INTEGER NOT NULL DEFAULT 1,
+            overtime_secs INTEGER NOT NULL DEFAULT 0,
+            note TEXT NOT NULL DEFAULT ''
+        );
+        CREATE INDEX IF NOT EXISTS idx_focus_records_completed ON focus_records (completed_at, id);
         "#,
     )?;
+    // 旧版本建的归档库补列，与主库的整行复制保持一致
+    ensure_column(conn, "focus_records", "overtime_secs", "INTEGER NOT NULL DEFAULT 0")?;
+    ensure_column(conn, "focus_records", "note", "TEXT NOT NULL DEFAULT ''")
+}
+
+/// 把 completed_at < cutoff 的记录（含放弃记录）移到归档库，返回移动条数
+///
+/// 先复制再只删除归档库中已有的 id：两库分别提交，中途退出最多留下重复，下次归档时跳过已复制的记录后补删。
+pub fn archive_records_before(conn: &Connection, cutoff: &str) -> Result<usize, rusqlite::Error> {
+    let path = archive_path();
+    init_archive_schema(&Connection::open(&path)?)?;
+    conn.execute("ATTACH DATABASE ?1 AS archive", rusqlite::params![path.to_string_lossy()])?;
+    let moved = (|| {
+        let tx = conn.unchecked_transaction()?;
+        tx.execute(
+            &format!(
+                "INSERT OR IGNORE INTO archive.focus_records ({FOCUS_RECORD_COLUMNS})
+                 SELECT {FOCUS_RECORD_COLUMNS} FROM main.focus_records WHERE completed_at < ?1"
+            ),
+            rusqlite::params![cutoff],
+        )?;
+        let moved = tx.execute(
+            "DELETE FROM main.focus_records WHERE completed_at < ?1 AND id IN (SELECT id FROM archive.focus_records)",
+            rusqlite::params![cutoff],
+        )?;
+        tx.commit()?;
+        Ok(moved)
+    })();
+    conn.execute_batch("DETACH DATABASE archive")?;
+    moved
+}
+
+/// 归档时整行复制的列
+const FOCUS_RECORD_COLUMNS: &str =
+    "id, task, duration_secs, completed_at, completed_pomodoros, commitment, status, pause_count, paused_secs, project_id, overtime_secs, note";
+
+/// 若表中缺少某列则 ALTER TABLE 添加（旧版本数据库升级用）
+fn ensure_column(conn: &Connection, table: &str, column: &str, decl: &str) -> Result<(), rusqlite::Error> {
+    if !has_column(conn, table, column)? {
+        conn.execute_batch(&format!("ALTER TABLE {table} ADD COLUMN {column} {decl};"))?;
+    }
     Ok(())
 }
 
+fn has_column(conn: &Connection, table: &str, column: &str) -> Result<bool, rusqlite::Error> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({table})"))?;
+    let exists = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .filter_map(Result::ok)
+        .any(|name| name == column);
+    Ok(exists)
+}
+
+fn has_table(conn: &Connection, table: &str) -> Result<bool, rusqlite::Error> {
+    conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1)",
+        rusqlite::params![table],
+        |row| row.get(0),
+    )
+}
+
 /// 单条专注记录（与表结构一致）
 pub struct FocusRow {
     pub id: i64,
@@ -50,37 +391,1597 @@ pub struct FocusRow {
     pub duration_secs: i64,
     pub completed_at: String,
     pub completed_pomodoros: u32,
+    /// 开始专注时写下的承诺（专注契约），可为空
+    pub commitment: String,
+    /// 本次专注的暂停次数与累计暂停秒数
+    pub pauses: PauseStats,
+    /// 所属项目
+    pub project_id: i64,
+    /// 加时秒数（已计入 duration_secs）
+    pub overtime_secs: i64,
+    /// Markdown 笔记，可为空
+    pub note: String,
 }
 
 /// 插入一条专注记录
-pub fn insert_focus_record(
-    conn: &Connection,
-    task: &str,
-    duration_secs: i64,
-    completed_at: &str,
-    completed_pomodoros: u32,
-) -> Result<(), rusqlite::Error> {
-    conn.execute(
-        "INSERT INTO focus_records (task, duration_secs, completed_at, completed_pomodoros) VALUES (?1, ?2, ?3, ?4)",
-        rusqlite::params![task, duration_secs, completed_at, completed_pomodoros as i64],
-    )?;
+pub fn insert_focus_record(conn: &Connection, record: &FocusRecord) -> Result<(), rusqlite::Error> {
+    insert_record_with_status(conn, record, STATUS_COMPLETED)
+}
+
+/// 专注记录状态：正常完成 / 中途重置或关闭而放弃
+pub const STATUS_COMPLETED: &str = "completed";
+pub const STATUS_ABANDONED: &str = "abandoned";
+
+/// 插入一条放弃的专注记录（duration_secs 为放弃前已专注的秒数，completed_at 为放弃时间），返回新记录的 id
+pub fn insert_abandoned_record(conn: &Connection, record: &FocusRecord) -> Result<i64, rusqlite::Error> {
+    insert_record_with_status(conn, record, STATUS_ABANDONED)?;
+    Ok(conn.last_insert_rowid())
+}
+
+fn insert_record_with_status(conn: &Connection, r: &FocusRecord, status: &str) -> Result<(), rusqlite::Error> {
+    insert_record_with_note(conn, r, status, "")
+}
+
+/// 插入一条从其他数据库或同步快照搬来的记录（含笔记）
+pub fn insert_portable_record(conn: &Connection, p: &PortableRecord) -> Result<(), rusqlite::Error> {
+    let status = if p.status == STATUS_ABANDONED { STATUS_ABANDONED } else { STATUS_COMPLETED };
+    insert_record_with_note(conn, &p.record, status, &p.note)
+}
+
+fn insert_record_with_note(conn: &Connection, r: &FocusRecord, status: &str, note: &str) -> Result<(), rusqlite::Error> {
+    let task = protect(&r.task)?;
+    let commitment = protect(&r.commitment)?;
+    let note = protect(note)?;
+    // 导入与回放时逐条插入，复用同一条预编译语句
+    conn.prepare_cached(
+        "INSERT INTO focus_records (task, duration_secs, completed_at, completed_pomodoros, commitment, status, pause_count, paused_secs, project_id, overtime_secs, note)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+    )?
+    .execute(rusqlite::params![
+        task,
+        r.duration_secs,
+        r.completed_at,
+        r.completed_pomodoros as i64,
+        commitment,
+        status,
+        r.pauses.count as i64,
+        r.pauses.secs,
+        r.project_id,
+        r.overtime_secs,
+        note
+    ])?;
     Ok(())
 }
 
-/// 按完成时间倒序加载记录（最新在前），limit 0 表示全部
-pub fn load_focus_records(conn: &Connection, limit: u32) -> Result<Vec<FocusRow>, rusqlite::Error> {
+/// 是否已有同任务、同完成时间的记录（批量导入时去重）
+pub fn focus_record_exists(conn: &Connection, task: &str, completed_at: &str) -> Result<bool, rusqlite::Error> {
+    conn.prepare_cached(
+        "SELECT EXISTS(SELECT 1 FROM focus_records WHERE task = ?1 AND completed_at = ?2)",
+    )?
+    .query_row(
+        // 加密是确定性的，同一任务名的密文相同，可直接比较
+        rusqlite::params![protect(task)?, completed_at],
+        |row| row.get(0),
+    )
+}
+
+/// 加载 completed_at >= since 的已完成记录（按完成时间倒序），limit 0 表示全部；供内存历史缓存按天窗口加载
+pub fn load_focus_records_since(
+    conn: &Connection,
+    since: &str,
+    limit: u32,
+) -> Result<Vec<FocusRow>, rusqlite::Error> {
     let limit_val = if limit > 0 { limit as i64 } else { 1_000_000 };
-    let mut stmt = conn.prepare(
-        "SELECT id, task, duration_secs, completed_at, completed_pomodoros FROM focus_records ORDER BY completed_at DESC LIMIT ?1",
+    let mut stmt = conn.prepare_cached(
+        "SELECT id, rt_text(task), duration_secs, completed_at, completed_pomodoros, rt_text(commitment), pause_count, paused_secs, project_id, overtime_secs, rt_text(note) FROM focus_records WHERE completed_at >= ?1 AND status = ?3 ORDER BY completed_at DESC LIMIT ?2",
     )?;
-    let rows = stmt.query_map(rusqlite::params![limit_val], |row| {
+    let rows = stmt.query_map(rusqlite::params![since, limit_val, STATUS_COMPLETED], |row| {
+        Ok(FocusRow {
+            id: row.get(0)?,
+            task: row.get(1)?,
+            duration_secs: row.get(2)?,
+            completed_at: row.get(3)?,
+            completed_pomodoros: row.get(4)?,
+            commitment: row.get(5)?,
+            pauses: PauseStats {
+                count: row.get(6)?,
+                secs: row.get(7)?,
+            },
+            project_id: row.get(8)?,
+            overtime_secs: row.get(9)?,
+            note: row.get(10)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// 统计窗口记录列表的筛选条件（均可省略）
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HistoryQuery {
+    /// 任务名包含的文字（不区分大小写）
+    pub search: String,
+    /// [from, to) 完成时间区间
+    pub range: Option<(String, String)>,
+    /// 专注时长下限（秒）
+    pub min_duration_secs: i64,
+    pub project_id: Option<i64>,
+    /// 同时查询归档库
+    pub include_archive: bool,
+}
+
+/// 子串匹配的 LIKE 模式：转义通配符后按字面匹配，配合 `ESCAPE '\'` 使用
+fn like_contains(search: &str) -> String {
+    let escaped = search.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
+/// 按筛选条件分页查询已完成的记录（按完成时间、id 倒序），条件均以参数绑定。
+/// before 为上一页最后一条的 (completed_at, id)，None 取第一页；按键集翻页，深翻页也不需扫过前面的行。
+/// 包含归档时两库各取一页后合并，再截取前 limit 条（id 两库不重复，游标在两库中含义一致）
+pub fn search_focus_records(
+    conn: &Connection,
+    query: &HistoryQuery,
+    before: Option<(&str, i64)>,
+    limit: u32,
+) -> Result<Vec<FocusRow>, rusqlite::Error> {
+    let mut rows = query_focus_page(conn, query, before, limit)?;
+    if query.include_archive {
+        if let Some(archive) = open_archive()? {
+            rows.extend(query_focus_page(&archive, query, before, limit)?);
+            rows.sort_by(|a, b| (&b.completed_at, b.id).cmp(&(&a.completed_at, a.id)));
+            rows.truncate(limit as usize);
+        }
+    }
+    Ok(rows)
+}
+
+/// 筛选条件的 WHERE 子句（不含 WHERE）与绑定参数
+fn history_filter_sql(query: &HistoryQuery) -> (String, Vec<rusqlite::types::Value>) {
+    let mut sql = String::from("status = ?");
+    let mut params: Vec<rusqlite::types::Value> = vec![STATUS_COMPLETED.to_string().into()];
+    let search = query.search.trim();
+    if !search.is_empty() {
+        sql.push_str(" AND rt_text(task) LIKE ? ESCAPE '\\'");
+        params.push(like_contains(search).into());
+    }
+    if let Some((from, to)) = &query.range {
+        sql.push_str(" AND completed_at >= ? AND completed_at < ?");
+        params.push(from.clone().into());
+        params.push(to.clone().into());
+    }
+    if query.min_duration_secs > 0 {
+        sql.push_str(" AND duration_secs >= ?");
+        params.push(query.min_duration_secs.into());
+    }
+    if let Some(id) = query.project_id {
+        sql.push_str(" AND project_id = ?");
+        params.push(id.into());
+    }
+    (sql, params)
+}
+
+fn query_focus_page(
+    conn: &Connection,
+    query: &HistoryQuery,
+    before: Option<(&str, i64)>,
+    limit: u32,
+) -> Result<Vec<FocusRow>, rusqlite::Error> {
+    let (filter, mut params) = history_filter_sql(query);
+    let mut sql = format!(
+        "SELECT id, rt_text(task), duration_secs, completed_at, completed_pomodoros, rt_text(commitment), pause_count, paused_secs, project_id, overtime_secs, rt_text(note) FROM focus_records WHERE {}",
+        filter
+    );
+    if let Some((completed_at, id)) = before {
+        sql.push_str(" AND (completed_at < ? OR (completed_at = ? AND id < ?))");
+        params.push(completed_at.to_string().into());
+        params.push(completed_at.to_string().into());
+        params.push(id.into());
+    }
+    sql.push_str(" ORDER BY completed_at DESC, id DESC LIMIT ?");
+    params.push((limit as i64).into());
+    let mut stmt = conn.prepare_cached(&sql)?;
+    let rows = stmt.query_map(rusqlite::params_from_iter(params), |row| {
         Ok(FocusRow {
             id: row.get(0)?,
             task: row.get(1)?,
             duration_secs: row.get(2)?,
             completed_at: row.get(3)?,
             completed_pomodoros: row.get(4)?,
+            commitment: row.get(5)?,
+            pauses: PauseStats {
+                count: row.get(6)?,
+                secs: row.get(7)?,
+            },
+            project_id: row.get(8)?,
+            overtime_secs: row.get(9)?,
+            note: row.get(10)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// 记录列表中某天的小计（day 为北京时间 YYYY-MM-DD）
+#[derive(Clone, Debug, PartialEq)]
+pub struct DayTotalRow {
+    pub day: String,
+    pub sessions: u32,
+    pub total_secs: i64,
+}
+
+/// 按筛选条件按天汇总已完成的记录（日期倒序）；completed_at 为北京时区 RFC3339，前 10 个字符即日期。
+/// 包含归档时两库同一天的小计相加
+pub fn search_day_totals(conn: &Connection, query: &HistoryQuery) -> Result<Vec<DayTotalRow>, rusqlite::Error> {
+    let mut totals: std::collections::BTreeMap<String, (u32, i64)> = std::collections::BTreeMap::new();
+    let mut collect = |conn: &Connection| -> Result<(), rusqlite::Error> {
+        let (filter, params) = history_filter_sql(query);
+        let sql = format!(
+            "SELECT substr(completed_at, 1, 10) AS day, COUNT(*), COALESCE(SUM(duration_secs), 0) FROM focus_records WHERE {} GROUP BY day",
+            filter
+        );
+        let mut stmt = conn.prepare_cached(&sql)?;
+        let mut rows = stmt.query(rusqlite::params_from_iter(params))?;
+        while let Some(row) = rows.next()? {
+            let entry = totals.entry(row.get(0)?).or_default();
+            entry.0 += row.get::<_, u32>(1)?;
+            entry.1 += row.get::<_, i64>(2)?;
+        }
+        Ok(())
+    };
+    collect(conn)?;
+    if query.include_archive {
+        if let Some(archive) = open_archive()? {
+            collect(&archive)?;
+        }
+    }
+    Ok(totals
+        .into_iter()
+        .rev()
+        .map(|(day, (sessions, total_secs))| DayTotalRow { day, sessions, total_secs })
+        .collect())
+}
+
+/// 计划块 / 日历事件的一行（起止时间为 RFC3339 北京时区）
+pub struct SpanRow {
+    pub id: i64,
+    pub title: String,
+    pub start_at: String,
+    pub end_at: String,
+}
+
+/// 插入一个计划块
+pub fn insert_plan_block(conn: &Connection, title: &str, start_at: &str, end_at: &str) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "INSERT INTO plan_blocks (title, start_at, end_at) VALUES (?1, ?2, ?3)",
+        rusqlite::params![title, start_at, end_at],
+    )?;
+    Ok(())
+}
+
+/// 删除一个计划块
+pub fn delete_plan_block(conn: &Connection, id: i64) -> Result<(), rusqlite::Error> {
+    conn.execute("DELETE FROM plan_blocks WHERE id = ?1", rusqlite::params![id])?;
+    Ok(())
+}
+
+/// 插入或更新一条日历事件（按 uid 去重，重复导入同一日历不会产生重复）
+pub fn upsert_calendar_event(
+    conn: &Connection,
+    uid: &str,
+    summary: &str,
+    start_at: &str,
+    end_at: &str,
+) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "INSERT INTO calendar_events (uid, summary, start_at, end_at) VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(uid) DO UPDATE SET summary = excluded.summary, start_at = excluded.start_at, end_at = excluded.end_at",
+        rusqlite::params![uid, summary, start_at, end_at],
+    )?;
+    Ok(())
+}
+
+/// 加载与 [from, to) 有交集的计划块（按开始时间正序）
+pub fn load_plan_blocks(conn: &Connection, from: &str, to: &str) -> Result<Vec<SpanRow>, rusqlite::Error> {
+    load_spans(conn, "plan_blocks", "title", from, to)
+}
+
+/// 加载与 [from, to) 有交集的日历事件（按开始时间正序）
+pub fn load_calendar_events(conn: &Connection, from: &str, to: &str) -> Result<Vec<SpanRow>, rusqlite::Error> {
+    load_spans(conn, "calendar_events", "summary", from, to)
+}
+
+fn load_spans(
+    conn: &Connection,
+    table: &str,
+    title_col: &str,
+    from: &str,
+    to: &str,
+) -> Result<Vec<SpanRow>, rusqlite::Error> {
+    let sql = format!(
+        "SELECT id, {title_col}, start_at, end_at FROM {table} WHERE end_at > ?1 AND start_at < ?2 ORDER BY start_at ASC"
+    );
+    let mut stmt = conn.prepare_cached(&sql)?;
+    let rows = stmt.query_map(rusqlite::params![from, to], |row| {
+        Ok(SpanRow {
+            id: row.get(0)?,
+            title: row.get(1)?,
+            start_at: row.get(2)?,
+            end_at: row.get(3)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// 一条预定的专注（日程）：在 start_at（RFC3339 北京时区）开始做 task，计划 pomodoros 个番茄；
+/// task_completed_at 为标记任务完成的时间，空表示未完成
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScheduleRow {
+    pub id: i64,
+    pub start_at: String,
+    pub task: String,
+    pub pomodoros: u32,
+    pub project_id: i64,
+    pub task_completed_at: String,
+}
+
+/// 新增一条日程（created_at 为添加时间）
+pub fn insert_scheduled_session(
+    conn: &Connection,
+    start_at: &str,
+    task: &str,
+    pomodoros: u32,
+    project_id: i64,
+    created_at: &str,
+) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "INSERT INTO schedule (start_at, task, pomodoros, project_id, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+        rusqlite::params![start_at, protect(task)?, pomodoros as i64, project_id, created_at],
+    )?;
+    Ok(())
+}
+
+/// 把日程对应的任务标记为完成
+pub fn complete_scheduled_task(conn: &Connection, id: i64, completed_at: &str) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "UPDATE schedule SET task_completed_at = ?1 WHERE id = ?2",
+        rusqlite::params![completed_at, id],
+    )?;
+    Ok(())
+}
+
+/// 删除一条日程
+pub fn delete_scheduled_session(conn: &Connection, id: i64) -> Result<(), rusqlite::Error> {
+    conn.execute("DELETE FROM schedule WHERE id = ?1", rusqlite::params![id])?;
+    Ok(())
+}
+
+const SCHEDULE_COLUMNS: &str = "id, start_at, rt_text(task), pomodoros, project_id, task_completed_at";
+
+fn schedule_row(row: &rusqlite::Row) -> rusqlite::Result<ScheduleRow> {
+    Ok(ScheduleRow {
+        id: row.get(0)?,
+        start_at: row.get(1)?,
+        task: row.get(2)?,
+        pomodoros: row.get::<_, i64>(3)? as u32,
+        project_id: row.get(4)?,
+        task_completed_at: row.get(5)?,
+    })
+}
+
+/// [from, to) 内的日程（按开始时间正序）
+pub fn load_schedule(conn: &Connection, from: &str, to: &str) -> Result<Vec<ScheduleRow>, rusqlite::Error> {
+    let mut stmt = conn.prepare_cached(&format!(
+        "SELECT {SCHEDULE_COLUMNS} FROM schedule WHERE start_at >= ?1 AND start_at < ?2 ORDER BY start_at ASC"
+    ))?;
+    let rows = stmt.query_map(rusqlite::params![from, to], schedule_row)?;
+    rows.collect()
+}
+
+/// 取出 [since, now] 内到点且尚未提醒的日程，并标记为已提醒（每条只提醒一次）
+pub fn take_due_schedule(conn: &Connection, since: &str, now: &str) -> Result<Vec<ScheduleRow>, rusqlite::Error> {
+    let due: Vec<ScheduleRow> = conn
+        .prepare_cached(&format!(
+            "SELECT {SCHEDULE_COLUMNS} FROM schedule WHERE reminded = 0 AND start_at >= ?1 AND start_at <= ?2 ORDER BY start_at ASC"
+        ))?
+        .query_map(rusqlite::params![since, now], schedule_row)?
+        .collect::<Result<_, _>>()?;
+    let mut mark = conn.prepare_cached("UPDATE schedule SET reminded = 1 WHERE id = ?1")?;
+    for row in &due {
+        mark.execute(rusqlite::params![row.id])?;
+    }
+    Ok(due)
+}
+
+/// 日程的计划与完成：同一天内开始时间之后完成的同任务番茄数
+pub struct SchedulePlanRow {
+    pub id: i64,
+    pub start_at: String,
+    pub task: String,
+    pub planned: u32,
+    pub done: u32,
+    pub project_id: i64,
+    pub task_completed_at: String,
+}
+
+/// [from, to) 内各日程的计划番茄数与实际完成数（统计窗口「计划 vs 完成」）
+pub fn schedule_progress(conn: &Connection, from: &str, to: &str) -> Result<Vec<SchedulePlanRow>, rusqlite::Error> {
+    // 完成时间与开始时间同为北京时区 RFC3339，前 10 个字符即日期
+    let mut stmt = conn.prepare_cached(
+        "SELECT s.start_at, rt_text(s.task), s.pomodoros,
+                (SELECT COUNT(*) FROM focus_records r
+                 WHERE r.task = s.task AND r.status = ?3 AND r.completed_at >= s.start_at
+                   AND substr(r.completed_at, 1, 10) = substr(s.start_at, 1, 10)),
+                s.id, s.project_id, s.task_completed_at
+         FROM schedule s WHERE s.start_at >= ?1 AND s.start_at < ?2 ORDER BY s.start_at ASC",
+    )?;
+    let rows = stmt.query_map(rusqlite::params![from, to, STATUS_COMPLETED], |row| {
+        Ok(SchedulePlanRow {
+            id: row.get(4)?,
+            start_at: row.get(0)?,
+            task: row.get(1)?,
+            planned: row.get::<_, i64>(2)? as u32,
+            done: row.get::<_, i64>(3)? as u32,
+            project_id: row.get(5)?,
+            task_completed_at: row.get(6)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// 一个已完成任务的交付时长起止：日程添加时间（旧数据为预定开始时间）与标记完成时间
+pub struct TaskLeadRow {
+    pub created_at: String,
+    pub task_completed_at: String,
+}
+
+/// 完成时间在 [from, to) 内的日程任务（按完成时间正序）
+pub fn completed_tasks(conn: &Connection, from: &str, to: &str) -> Result<Vec<TaskLeadRow>, rusqlite::Error> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT CASE WHEN created_at = '' THEN start_at ELSE created_at END, task_completed_at
+         FROM schedule WHERE task_completed_at >= ?1 AND task_completed_at < ?2 ORDER BY task_completed_at ASC",
+    )?;
+    let rows = stmt.query_map(rusqlite::params![from, to], |row| {
+        Ok(TaskLeadRow {
+            created_at: row.get(0)?,
+            task_completed_at: row.get(1)?,
         })
     })?;
     rows.collect()
 }
+
+/// 专注中断类型：专注中暂停 / 专注未完成即重置
+pub const INTERRUPTION_PAUSE: &str = "pause";
+pub const INTERRUPTION_ABANDON: &str = "abandon";
+
+/// 记录一次专注中断
+pub fn insert_interruption(conn: &Connection, occurred_at: &str, kind: &str) -> Result<i64, rusqlite::Error> {
+    conn.execute(
+        "INSERT INTO interruptions (occurred_at, kind) VALUES (?1, ?2)",
+        rusqlite::params![occurred_at, kind],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// 撤销一次重置：按 id 删除当时写入的放弃中断与放弃记录（同一秒内由 HTTP 接口、任务栏按钮等写入的其他放弃不受影响）
+pub fn delete_abandon(conn: &Connection, interruption_id: Option<i64>, record_id: Option<i64>) -> Result<(), rusqlite::Error> {
+    if let Some(id) = interruption_id {
+        conn.execute(
+            "DELETE FROM interruptions WHERE id = ?1 AND kind = ?2",
+            rusqlite::params![id, INTERRUPTION_ABANDON],
+        )?;
+    }
+    if let Some(id) = record_id {
+        conn.execute(
+            "DELETE FROM focus_records WHERE id = ?1 AND status = ?2",
+            rusqlite::params![id, STATUS_ABANDONED],
+        )?;
+    }
+    Ok(())
+}
+
+/// 撤销一次「完成」：删除该时刻写入的不足一个番茄的专注记录
+pub fn delete_partial_focus(conn: &Connection, at: &str, duration_secs: i64) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "DELETE FROM focus_records WHERE completed_at = ?1 AND duration_secs = ?2 AND status = ?3",
+        rusqlite::params![at, duration_secs, STATUS_COMPLETED],
+    )?;
+    Ok(())
+}
+
+/// 记录一次任务完成（「完成」按钮）：任务名、所属项目与当时已完成的番茄数；返回新行的 id
+pub fn insert_task_completion(
+    conn: &Connection,
+    task: &str,
+    project_id: i64,
+    pomodoros: u32,
+    completed_at: &str,
+) -> Result<i64, rusqlite::Error> {
+    conn.execute(
+        "INSERT INTO task_completions (task, project_id, pomodoros, completed_at) VALUES (?1, ?2, ?3, ?4)",
+        rusqlite::params![protect(task)?, project_id, pomodoros as i64, completed_at],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// 撤销时按 id 删除那次任务完成（同一秒内其他途径记下的任务完成不受影响）
+pub fn delete_task_completion(conn: &Connection, id: i64) -> Result<(), rusqlite::Error> {
+    conn.execute("DELETE FROM task_completions WHERE id = ?1", rusqlite::params![id])?;
+    Ok(())
+}
+
+/// [from, to) 内完成的任务数
+pub fn count_task_completions(conn: &Connection, from: &str, to: &str) -> Result<u32, rusqlite::Error> {
+    conn.prepare_cached("SELECT COUNT(*) FROM task_completions WHERE completed_at >= ?1 AND completed_at < ?2")?
+        .query_row(rusqlite::params![from, to], |row| row.get(0))
+}
+
+/// 记录一次专注中的分心（前台窗口命中黑名单关键词）
+pub fn insert_distraction(
+    conn: &Connection,
+    keyword: &str,
+    title: &str,
+    started_at: &str,
+    duration_secs: i64,
+) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "INSERT INTO distractions (keyword, title, started_at, duration_secs) VALUES (?1, ?2, ?3, ?4)",
+        rusqlite::params![keyword, protect(title)?, started_at, duration_secs],
+    )?;
+    Ok(())
+}
+
+/// 按关键词聚合的分心
+pub struct DistractionRow {
+    pub keyword: String,
+    pub count: u32,
+    pub total_secs: i64,
+}
+
+/// [from, to) 内开始的分心按关键词汇总（按时长倒序）
+pub fn distraction_totals(conn: &Connection, from: &str, to: &str) -> Result<Vec<DistractionRow>, rusqlite::Error> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT keyword, COUNT(*), SUM(duration_secs) AS total FROM distractions
+         WHERE started_at >= ?1 AND started_at < ?2
+         GROUP BY keyword ORDER BY total DESC",
+    )?;
+    let rows = stmt.query_map(rusqlite::params![from, to], |row| {
+        Ok(DistractionRow {
+            keyword: row.get(0)?,
+            count: row.get(1)?,
+            total_secs: row.get(2)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// 记一条休息事件：phase 为 short_break / long_break，skipped 为是否跳过
+pub fn insert_break_event(conn: &Connection, occurred_at: &str, phase: &str, skipped: bool) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "INSERT INTO break_events (occurred_at, phase, skipped) VALUES (?1, ?2, ?3)",
+        rusqlite::params![occurred_at, phase, skipped],
+    )?;
+    Ok(())
+}
+
+/// since 之后最近连续跳过的休息次数
+pub fn consecutive_skipped_breaks(conn: &Connection, since: &str) -> Result<u32, rusqlite::Error> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT skipped FROM break_events WHERE occurred_at >= ?1 ORDER BY occurred_at DESC, id DESC",
+    )?;
+    let rows = stmt.query_map(rusqlite::params![since], |row| row.get::<_, bool>(0))?;
+    let mut count = 0;
+    for skipped in rows {
+        if !skipped? {
+            break;
+        }
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// 最近一次完成（未跳过）的长休息时间
+pub fn last_long_break(conn: &Connection) -> Result<Option<String>, rusqlite::Error> {
+    conn.prepare_cached("SELECT MAX(occurred_at) FROM break_events WHERE phase = 'long_break' AND skipped = 0")?
+        .query_row([], |row| row.get(0))
+}
+
+/// [from, to) 内的休息次数：(休息, 跳过)
+pub fn count_breaks(conn: &Connection, from: &str, to: &str) -> Result<(u32, u32), rusqlite::Error> {
+    conn.prepare_cached(
+        "SELECT COALESCE(SUM(skipped = 0), 0), COALESCE(SUM(skipped), 0) FROM break_events
+         WHERE occurred_at >= ?1 AND occurred_at < ?2",
+    )?
+    .query_row(rusqlite::params![from, to], |row| Ok((row.get(0)?, row.get(1)?)))
+}
+
+/// 收集箱中的一条：专注中随手记下的念头 / 待办；done_at 为空表示未处理
+pub struct InboxRow {
+    pub id: i64,
+    pub text: String,
+    pub captured_at: String,
+    pub done_at: String,
+}
+
+/// 放入收集箱
+pub fn insert_inbox_item(conn: &Connection, text: &str, captured_at: &str) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "INSERT INTO inbox (text, captured_at) VALUES (?1, ?2)",
+        rusqlite::params![protect(text)?, captured_at],
+    )?;
+    Ok(())
+}
+
+/// 收集箱（按记下时间倒序）；include_done 为 false 时只列未处理的
+pub fn load_inbox(conn: &Connection, include_done: bool) -> Result<Vec<InboxRow>, rusqlite::Error> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT id, rt_text(text), captured_at, done_at FROM inbox
+         WHERE ?1 OR done_at = '' ORDER BY captured_at DESC, id DESC",
+    )?;
+    let rows = stmt.query_map(rusqlite::params![include_done], |row| {
+        Ok(InboxRow {
+            id: row.get(0)?,
+            text: row.get(1)?,
+            captured_at: row.get(2)?,
+            done_at: row.get(3)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// 标记已处理；done_at 为空串表示恢复为未处理
+pub fn set_inbox_done(conn: &Connection, id: i64, done_at: &str) -> Result<(), rusqlite::Error> {
+    conn.execute("UPDATE inbox SET done_at = ?2 WHERE id = ?1", rusqlite::params![id, done_at])?;
+    Ok(())
+}
+
+/// 删除收集箱中的一条
+pub fn delete_inbox_item(conn: &Connection, id: i64) -> Result<(), rusqlite::Error> {
+    conn.execute("DELETE FROM inbox WHERE id = ?1", rusqlite::params![id])?;
+    Ok(())
+}
+
+/// 按天聚合的专注：日期（YYYY-MM-DD，取 completed_at 前 10 位即北京日期）、总秒数、番茄数
+pub struct DailyFocusRow {
+    pub day: String,
+    pub total_secs: i64,
+    pub sessions: u32,
+}
+
+/// [from, to) 内每天的专注总时长与番茄数（只返回有记录的日期，按日期正序）
+pub fn daily_focus(conn: &Connection, from: &str, to: &str) -> Result<Vec<DailyFocusRow>, rusqlite::Error> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT substr(completed_at, 1, 10) AS day, SUM(duration_secs), COUNT(*) FROM focus_records
+         WHERE completed_at >= ?1 AND completed_at < ?2 AND status = ?3 GROUP BY day ORDER BY day ASC",
+    )?;
+    let rows = stmt.query_map(rusqlite::params![from, to, STATUS_COMPLETED], |row| {
+        Ok(DailyFocusRow {
+            day: row.get(0)?,
+            total_secs: row.get(1)?,
+            sessions: row.get(2)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// 按任务聚合的专注
+pub struct TaskFocusRow {
+    pub task: String,
+    pub total_secs: i64,
+    pub sessions: u32,
+}
+
+/// [from, to) 内专注时长最多的任务（limit 0 表示全部）
+pub fn top_tasks(conn: &Connection, from: &str, to: &str, limit: u32) -> Result<Vec<TaskFocusRow>, rusqlite::Error> {
+    let limit_val = if limit > 0 { limit as i64 } else { 1_000_000 };
+    let mut stmt = conn.prepare_cached(
+        "SELECT rt_text(task), SUM(duration_secs) AS total, COUNT(*) FROM focus_records
+         WHERE completed_at >= ?1 AND completed_at < ?2 AND status = ?4 GROUP BY task ORDER BY total DESC LIMIT ?3",
+    )?;
+    let rows = stmt.query_map(rusqlite::params![from, to, limit_val, STATUS_COMPLETED], |row| {
+        Ok(TaskFocusRow {
+            task: row.get(0)?,
+            total_secs: row.get(1)?,
+            sessions: row.get(2)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// 做过的任务：任务名、完成次数、最近一次完成时间与当时所属项目
+pub struct RecentTaskRow {
+    pub task: String,
+    pub sessions: u32,
+    pub last_at: String,
+    pub project_id: i64,
+}
+
+/// 最近做过的任务（去重，按最近完成时间倒序，同时间按次数），供任务输入框快速选择
+pub fn recent_tasks(conn: &Connection, limit: u32) -> Result<Vec<RecentTaskRow>, rusqlite::Error> {
+    let mut stmt = conn.prepare_cached(
+        // SQLite 中与 MAX() 同查的裸列取自最大值所在行，即最近一次的项目
+        "SELECT rt_text(task), COUNT(*) AS n, MAX(completed_at) AS last, project_id FROM focus_records
+         WHERE task != '' AND status = ?2 GROUP BY task ORDER BY last DESC, n DESC LIMIT ?1",
+    )?;
+    let rows = stmt.query_map(rusqlite::params![limit as i64, STATUS_COMPLETED], |row| {
+        Ok(RecentTaskRow {
+            task: row.get(0)?,
+            sessions: row.get(1)?,
+            last_at: row.get(2)?,
+            project_id: row.get(3)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// [from, to) 内已完成专注的次数、总秒数、平均每次秒数
+pub fn session_summary(conn: &Connection, from: &str, to: &str) -> Result<(u32, i64, i64), rusqlite::Error> {
+    conn.prepare_cached(
+        "SELECT COUNT(*), COALESCE(SUM(duration_secs), 0), COALESCE(CAST(AVG(duration_secs) AS INTEGER), 0)
+         FROM focus_records WHERE completed_at >= ?1 AND completed_at < ?2 AND status = ?3",
+    )?
+    .query_row(
+        rusqlite::params![from, to, STATUS_COMPLETED],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+    )
+}
+
+/// 主界面「今日概览」：[from, to) 内已完成的番茄数、总秒数，以及其中任务名为 task 的番茄数；project_id 限定项目
+pub fn day_glance(
+    conn: &Connection,
+    from: &str,
+    to: &str,
+    task: &str,
+    project_id: Option<i64>,
+) -> Result<(u32, i64, u32), rusqlite::Error> {
+    conn.prepare_cached(
+        "SELECT COUNT(*), COALESCE(SUM(duration_secs), 0), COALESCE(SUM(rt_text(task) = ?4), 0)
+         FROM focus_records WHERE completed_at >= ?1 AND completed_at < ?2 AND status = ?3 AND (?5 IS NULL OR project_id = ?5)",
+    )?
+    .query_row(
+        rusqlite::params![from, to, STATUS_COMPLETED, task, project_id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+    )
+}
+
+/// [from, to) 内的专注次数：(完成, 放弃)
+pub fn count_by_status(conn: &Connection, from: &str, to: &str) -> Result<(u32, u32), rusqlite::Error> {
+    conn.prepare_cached(
+        "SELECT COALESCE(SUM(status = ?3), 0), COALESCE(SUM(status = ?4), 0) FROM focus_records
+         WHERE completed_at >= ?1 AND completed_at < ?2",
+    )?
+    .query_row(
+        rusqlite::params![from, to, STATUS_COMPLETED, STATUS_ABANDONED],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )
+}
+
+/// [from, to) 内有完成专注的天数（按北京时间日期）
+pub fn count_focus_days(conn: &Connection, from: &str, to: &str) -> Result<u32, rusqlite::Error> {
+    conn.prepare_cached(
+        "SELECT COUNT(DISTINCT substr(completed_at, 1, 10)) FROM focus_records
+         WHERE completed_at >= ?1 AND completed_at < ?2 AND status = ?3",
+    )?
+    .query_row(rusqlite::params![from, to, STATUS_COMPLETED], |row| row.get(0))
+}
+
+/// [from, to) 内每天最早完成的一条专注：(完成时间, 专注秒数)，用于推算每天第一个番茄的开始时刻
+pub fn first_focus_per_day(conn: &Connection, from: &str, to: &str) -> Result<Vec<(String, i64)>, rusqlite::Error> {
+    // 聚合 MIN 时 SQLite 的裸列取自最小值所在行
+    let mut stmt = conn.prepare_cached(
+        "SELECT MIN(completed_at), duration_secs FROM focus_records
+         WHERE completed_at >= ?1 AND completed_at < ?2 AND status = ?3
+         GROUP BY substr(completed_at, 1, 10)",
+    )?;
+    let rows = stmt.query_map(rusqlite::params![from, to, STATUS_COMPLETED], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect()
+}
+
+/// [from, to) 内每条已完成的专注：(完成时间, 专注秒数)，用于区分工作时间内外的专注
+pub fn focus_spans(conn: &Connection, from: &str, to: &str) -> Result<Vec<(String, i64)>, rusqlite::Error> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT completed_at, duration_secs FROM focus_records
+         WHERE completed_at >= ?1 AND completed_at < ?2 AND status = ?3",
+    )?;
+    let rows = stmt.query_map(rusqlite::params![from, to, STATUS_COMPLETED], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect()
+}
+
+/// [from, to) 内各类专注中断次数：(暂停, 放弃)
+pub fn count_interruptions(conn: &Connection, from: &str, to: &str) -> Result<(u32, u32), rusqlite::Error> {
+    conn.prepare_cached(
+        "SELECT COALESCE(SUM(kind = ?3), 0), COALESCE(SUM(kind = ?4), 0) FROM interruptions
+         WHERE occurred_at >= ?1 AND occurred_at < ?2",
+    )?
+    .query_row(
+        rusqlite::params![from, to, INTERRUPTION_PAUSE, INTERRUPTION_ABANDON],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )
+}
+
+/// 区间内的专注与暂停合计（含放弃的专注）
+#[derive(Clone, Copy, Default)]
+pub struct PauseTotals {
+    pub focus_secs: i64,
+    pub paused_secs: i64,
+    pub pauses: u32,
+}
+
+impl PauseTotals {
+    /// 纯专注率：实际专注 ÷（专注 + 暂停）的墙钟时间；没有专注时为 None
+    pub fn pure_focus_ratio(&self) -> Option<f64> {
+        let wall = self.focus_secs + self.paused_secs;
+        (wall > 0).then(|| self.focus_secs as f64 / wall as f64)
+    }
+}
+
+/// [from, to) 内的专注秒数、暂停秒数与暂停次数
+pub fn pause_totals(conn: &Connection, from: &str, to: &str) -> Result<PauseTotals, rusqlite::Error> {
+    conn.prepare_cached(
+        "SELECT COALESCE(SUM(duration_secs), 0), COALESCE(SUM(paused_secs), 0), COALESCE(SUM(pause_count), 0)
+         FROM focus_records WHERE completed_at >= ?1 AND completed_at < ?2",
+    )?
+    .query_row(
+        rusqlite::params![from, to],
+        |row| {
+            Ok(PauseTotals {
+                focus_secs: row.get(0)?,
+                paused_secs: row.get(1)?,
+                pauses: row.get(2)?,
+            })
+        },
+    )
+}
+
+/// 每日回顾日志一条：日期（YYYY-MM-DD）、提问、回答
+pub struct JournalRow {
+    pub id: i64,
+    pub day: String,
+    pub prompt: String,
+    pub answer: String,
+    pub created_at: String,
+}
+
+/// 保存一条回顾日志
+pub fn insert_journal_entry(
+    conn: &Connection,
+    day: &str,
+    prompt: &str,
+    answer: &str,
+    created_at: &str,
+) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "INSERT INTO journal_entries (day, prompt, answer, created_at) VALUES (?1, ?2, ?3, ?4)",
+        rusqlite::params![day, prompt, protect(answer)?, created_at],
+    )?;
+    Ok(())
+}
+
+/// 搜索回顾日志（提问或回答按字面包含关键字，% 与 _ 不作通配符；空关键字返回最近的），按日期倒序
+pub fn search_journal(conn: &Connection, keyword: &str, limit: u32) -> Result<Vec<JournalRow>, rusqlite::Error> {
+    let limit_val = if limit > 0 { limit as i64 } else { 1_000_000 };
+    let pattern = like_contains(keyword.trim());
+    let mut stmt = conn.prepare_cached(
+        "SELECT id, day, prompt, rt_text(answer), created_at FROM journal_entries
+         WHERE rt_text(answer) LIKE ?1 ESCAPE '\\' OR prompt LIKE ?1 ESCAPE '\\' ORDER BY day DESC, id DESC LIMIT ?2",
+    )?;
+    let rows = stmt.query_map(rusqlite::params![pattern, limit_val], |row| {
+        Ok(JournalRow {
+            id: row.get(0)?,
+            day: row.get(1)?,
+            prompt: row.get(2)?,
+            answer: row.get(3)?,
+            created_at: row.get(4)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// 通知摘要中的一条（路由到「摘要」渠道的事件，不即时打扰，在每日回顾中集中查看）
+pub struct DigestRow {
+    pub occurred_at: String,
+    pub event: String,
+    pub message: String,
+}
+
+pub fn insert_digest_entry(conn: &Connection, occurred_at: &str, event: &str, message: &str) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "INSERT INTO notification_digest (occurred_at, event, message) VALUES (?1, ?2, ?3)",
+        rusqlite::params![occurred_at, event, protect(message)?],
+    )?;
+    Ok(())
+}
+
+/// 读取 [from, to) 内的摘要条目，按时间正序
+pub fn load_digest(conn: &Connection, from: &str, to: &str) -> Result<Vec<DigestRow>, rusqlite::Error> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT occurred_at, event, rt_text(message) FROM notification_digest
+         WHERE occurred_at >= ?1 AND occurred_at < ?2 ORDER BY occurred_at ASC, id ASC",
+    )?;
+    let rows = stmt.query_map(rusqlite::params![from, to], |row| {
+        Ok(DigestRow {
+            occurred_at: row.get(0)?,
+            event: row.get(1)?,
+            message: row.get(2)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// 记录一次精力自评（1–5）
+pub fn insert_energy_rating(conn: &Connection, rated_at: &str, energy: u8) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "INSERT INTO energy_ratings (rated_at, energy) VALUES (?1, ?2)",
+        rusqlite::params![rated_at, energy as i64],
+    )?;
+    Ok(())
+}
+
+/// rated_at >= since 的精力自评（最新在前，最多 limit 条）：(时间, 精力)
+pub fn load_energy_ratings_since(conn: &Connection, since: &str, limit: u32) -> Result<Vec<(String, u8)>, rusqlite::Error> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT rated_at, energy FROM energy_ratings WHERE rated_at >= ?1 ORDER BY rated_at DESC, id DESC LIMIT ?2",
+    )?;
+    let rows = stmt.query_map(rusqlite::params![since, limit as i64], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect()
+}
+
+/// 默认项目「未分类」的 id（引入项目前的记录都属于它，不可删除）
+pub const UNCATEGORIZED_PROJECT_ID: i64 = 1;
+
+/// 项目
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProjectRow {
+    pub id: i64,
+    pub name: String,
+    /// "#rrggbb"，为空表示未指定
+    pub color: String,
+}
+
+/// 全部项目（「未分类」在前，其余按名称）
+pub fn load_projects(conn: &Connection) -> Result<Vec<ProjectRow>, rusqlite::Error> {
+    let mut stmt = conn.prepare_cached("SELECT id, name, color FROM projects ORDER BY id != ?1, name ASC")?;
+    let rows = stmt.query_map(rusqlite::params![UNCATEGORIZED_PROJECT_ID], |row| {
+        Ok(ProjectRow {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            color: row.get(2)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// 设置项目颜色（"#rrggbb"，空串为清除）
+pub fn set_project_color(conn: &Connection, id: i64, color: &str) -> Result<(), rusqlite::Error> {
+    conn.execute("UPDATE projects SET color = ?1 WHERE id = ?2", rusqlite::params![color, id])?;
+    Ok(())
+}
+
+/// 全部任务颜色：(任务名, "#rrggbb")，同名多行时后设置的在后
+pub fn load_task_colors(conn: &Connection) -> Result<Vec<(String, String)>, rusqlite::Error> {
+    let mut stmt = conn.prepare_cached("SELECT rt_text(task), color FROM task_colors ORDER BY id ASC")?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect()
+}
+
+/// 设置任务颜色（"#rrggbb"，空串为清除）；任务名可能是密文，先按 `rt_text` 删除旧值
+pub fn set_task_color(conn: &Connection, task: &str, color: &str) -> Result<(), rusqlite::Error> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute("DELETE FROM task_colors WHERE rt_text(task) = ?1", rusqlite::params![task])?;
+    if !color.is_empty() {
+        tx.execute(
+            "INSERT INTO task_colors (task, color) VALUES (?1, ?2)",
+            rusqlite::params![protect(task)?, color],
+        )?;
+    }
+    tx.commit()
+}
+
+/// 已解锁的成就：(成就键, 解锁时间)，按解锁时间正序
+pub fn load_achievements(conn: &Connection) -> Result<Vec<(String, String)>, rusqlite::Error> {
+    let mut stmt = conn.prepare_cached("SELECT key, unlocked_at FROM achievements ORDER BY unlocked_at ASC")?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect()
+}
+
+/// 记录成就解锁；已解锁过时不改动，返回 false
+pub fn unlock_achievement(conn: &Connection, key: &str, unlocked_at: &str) -> Result<bool, rusqlite::Error> {
+    let changed = conn.execute(
+        "INSERT OR IGNORE INTO achievements (key, unlocked_at) VALUES (?1, ?2)",
+        rusqlite::params![key, unlocked_at],
+    )?;
+    Ok(changed > 0)
+}
+
+/// 新建项目，返回 id（重名时报错）
+pub fn insert_project(conn: &Connection, name: &str) -> Result<i64, rusqlite::Error> {
+    conn.execute("INSERT INTO projects (name) VALUES (?1)", rusqlite::params![name])?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// 删除项目，其记录归入「未分类」
+pub fn delete_project(conn: &mut Connection, id: i64) -> Result<(), rusqlite::Error> {
+    if id == UNCATEGORIZED_PROJECT_ID {
+        return Ok(());
+    }
+    let tx = conn.transaction()?;
+    for table in ["focus_records", "schedule", "task_completions"] {
+        tx.execute(
+            &format!("UPDATE {table} SET project_id = ?1 WHERE project_id = ?2"),
+            rusqlite::params![UNCATEGORIZED_PROJECT_ID, id],
+        )?;
+    }
+    tx.execute("DELETE FROM projects WHERE id = ?1", rusqlite::params![id])?;
+    tx.commit()?;
+    if let Some(archive) = open_archive()? {
+        archive.execute(
+            "UPDATE focus_records SET project_id = ?1 WHERE project_id = ?2",
+            rusqlite::params![UNCATEGORIZED_PROJECT_ID, id],
+        )?;
+    }
+    Ok(())
+}
+
+/// 任务改名的结果
+pub struct RenameOutcome {
+    /// 改名的行数（专注记录含归档库，以及日程与任务完成）
+    pub renamed: usize,
+    /// 新名称此前已有专注记录，两者的统计合并为一个任务
+    pub merged: bool,
+}
+
+/// 把任务名 from 改为 to，见 `merge_tasks`
+pub fn rename_task(conn: &Connection, from: &str, to: &str) -> Result<RenameOutcome, rusqlite::Error> {
+    merge_tasks(conn, &[from.to_string()], to)
+}
+
+/// 把几个任务名（如「写周报」「周报」）统一改为 to：主库的专注记录、日程、任务完成与归档库中的专注记录在同一事务中改写。
+/// 统计按任务名聚合，改写后自然合并。任务名可能是密文，按 `rt_text` 匹配、按当前加密设置写入
+pub fn merge_tasks(conn: &Connection, from: &[String], to: &str) -> Result<RenameOutcome, rusqlite::Error> {
+    let from: Vec<&String> = from.iter().filter(|f| f.as_str() != to).collect();
+    if from.is_empty() {
+        return Ok(RenameOutcome { renamed: 0, merged: false });
+    }
+    let task = protect(to)?;
+    let archive = archive_path();
+    let attached = archive.exists();
+    if attached {
+        conn.execute("ATTACH DATABASE ?1 AS archive", rusqlite::params![archive.to_string_lossy()])?;
+    }
+    let mut records = vec!["main.focus_records"];
+    if attached {
+        records.push("archive.focus_records");
+    }
+    let placeholders = vec!["?"; from.len()].join(", ");
+    let outcome = (|| {
+        let tx = conn.unchecked_transaction()?;
+        let mut merged = false;
+        for table in &records {
+            merged |= tx.query_row(
+                &format!("SELECT EXISTS(SELECT 1 FROM {table} WHERE rt_text(task) = ?1)"),
+                rusqlite::params![to],
+                |row| row.get::<_, bool>(0),
+            )?;
+        }
+        let mut renamed = 0;
+        for table in records.iter().chain(&["main.schedule", "main.task_completions"]) {
+            let params = std::iter::once(&task).chain(from.iter().copied());
+            renamed += tx.execute(
+                &format!("UPDATE {table} SET task = ? WHERE rt_text(task) IN ({placeholders})"),
+                rusqlite::params_from_iter(params),
+            )?;
+        }
+        // 任务颜色随任务名一起改（不计入改写行数）；几种写法各有颜色时取最后设置的
+        let params = std::iter::once(&task).chain(from.iter().copied());
+        tx.execute(
+            &format!("UPDATE main.task_colors SET task = ? WHERE rt_text(task) IN ({placeholders})"),
+            rusqlite::params_from_iter(params),
+        )?;
+        tx.commit()?;
+        Ok(RenameOutcome { renamed, merged })
+    })();
+    if attached {
+        conn.execute_batch("DETACH DATABASE archive")?;
+    }
+    outcome
+}
+
+/// 一个任务名的用量（合并任务时选择与预览）
+pub struct TaskUsageRow {
+    pub task: String,
+    /// 专注记录条数（含放弃与归档）
+    pub records: u32,
+    /// 日程与任务完成的条数
+    pub other_rows: u32,
+    /// 已完成专注的总秒数
+    pub total_secs: i64,
+}
+
+impl TaskUsageRow {
+    /// 改名时会改写的行数
+    pub fn rows(&self) -> u32 {
+        self.records + self.other_rows
+    }
+}
+
+/// 全部非空任务名（含归档库）及其用量，按专注记录条数倒序。按 `rt_text(task)` 解密后的名称分组：开启或关闭加密的迁移过程中同一任务可能明文、密文两种行并存
+pub fn task_usage(conn: &Connection) -> Result<Vec<TaskUsageRow>, rusqlite::Error> {
+    let mut usage: std::collections::HashMap<String, TaskUsageRow> = std::collections::HashMap::new();
+    let mut collect = |conn: &Connection, sql: &str, params: &[&dyn rusqlite::ToSql], focus: bool| -> Result<(), rusqlite::Error> {
+        let mut stmt = conn.prepare(sql)?;
+        let rows = stmt.query_map(params, |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, u32>(1)?, row.get::<_, i64>(2)?))
+        })?;
+        for row in rows {
+            let (task, count, secs) = row?;
+            if task.is_empty() {
+                continue;
+            }
+            let entry = usage.entry(task.clone()).or_insert(TaskUsageRow {
+                task,
+                records: 0,
+                other_rows: 0,
+                total_secs: 0,
+            });
+            if focus {
+                entry.records += count;
+                entry.total_secs += secs;
+            } else {
+                entry.other_rows += count;
+            }
+        }
+        Ok(())
+    };
+    let focus_sql = "SELECT rt_text(task), COUNT(*), COALESCE(SUM(CASE WHEN status = ?1 THEN duration_secs END), 0)
+                     FROM focus_records GROUP BY rt_text(task)";
+    collect(conn, focus_sql, &[&STATUS_COMPLETED], true)?;
+    if let Some(archive) = open_archive()? {
+        collect(&archive, focus_sql, &[&STATUS_COMPLETED], true)?;
+    }
+    for table in ["schedule", "task_completions"] {
+        collect(conn, &format!("SELECT rt_text(task), COUNT(*), 0 FROM {table} GROUP BY rt_text(task)"), &[], false)?;
+    }
+    let mut rows: Vec<TaskUsageRow> = usage.into_values().collect();
+    rows.sort_by(|a, b| b.records.cmp(&a.records).then_with(|| a.task.cmp(&b.task)));
+    Ok(rows)
+}
+
+/// 按项目聚合的专注
+pub struct ProjectFocusRow {
+    pub project_id: i64,
+    pub name: String,
+    pub total_secs: i64,
+    pub sessions: u32,
+}
+
+/// [from, to) 内各项目的已完成专注时长与番茄数（按时长倒序，只含有记录的项目）
+pub fn project_totals(conn: &Connection, from: &str, to: &str) -> Result<Vec<ProjectFocusRow>, rusqlite::Error> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT COALESCE(p.name, '未分类'), SUM(f.duration_secs) AS total, COUNT(*), f.project_id FROM focus_records f
+         LEFT JOIN projects p ON p.id = f.project_id
+         WHERE f.completed_at >= ?1 AND f.completed_at < ?2 AND f.status = ?3
+         GROUP BY f.project_id ORDER BY total DESC",
+    )?;
+    let rows = stmt.query_map(rusqlite::params![from, to, STATUS_COMPLETED], |row| {
+        Ok(ProjectFocusRow {
+            project_id: row.get(3)?,
+            name: row.get(0)?,
+            total_secs: row.get(1)?,
+            sessions: row.get(2)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// [from, to) 内已完成专注按完成时间所在小时（北京时间 0–23）汇总的时长（秒）与番茄数；from 为 None 表示不限起点
+pub fn hourly_totals(conn: &Connection, from: Option<&str>, to: &str) -> Result<[(i64, u32); 24], rusqlite::Error> {
+    // 完成时间为北京时区 RFC3339，第 12–13 个字符即小时
+    let mut stmt = conn.prepare_cached(
+        "SELECT CAST(substr(completed_at, 12, 2) AS INTEGER) AS hour, SUM(duration_secs), COUNT(*) FROM focus_records
+         WHERE (?1 IS NULL OR completed_at >= ?1) AND completed_at < ?2 AND status = ?3
+         GROUP BY hour",
+    )?;
+    let mut totals = [(0, 0); 24];
+    let rows = stmt.query_map(rusqlite::params![from, to, STATUS_COMPLETED], |row| {
+        Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?, row.get::<_, i64>(2)?))
+    })?;
+    for row in rows {
+        let (hour, secs, sessions) = row?;
+        if let Some(slot) = totals.get_mut(hour as usize) {
+            *slot = (secs, sessions as u32);
+        }
+    }
+    Ok(totals)
+}
+
+/// 数据清除的记录范围：项目与 [from, to) 时间区间可组合，都为 None 时匹配全部记录（含放弃记录）
+pub struct RecordFilter<'a> {
+    pub project_id: Option<i64>,
+    pub range: Option<(&'a str, &'a str)>,
+}
+
+impl RecordFilter<'_> {
+    fn where_clause(&self) -> (String, Vec<rusqlite::types::Value>) {
+        let mut sql = String::from("WHERE 1 = 1");
+        let mut params = Vec::new();
+        if let Some(id) = self.project_id {
+            sql.push_str(" AND project_id = ?");
+            params.push(id.into());
+        }
+        if let Some((from, to)) = self.range {
+            sql.push_str(" AND completed_at >= ? AND completed_at < ?");
+            params.push(from.to_string().into());
+            params.push(to.to_string().into());
+        }
+        (sql, params)
+    }
+}
+
+/// 符合范围的专注记录条数（清除前预览）
+pub fn count_matching_records(conn: &Connection, filter: &RecordFilter) -> Result<u32, rusqlite::Error> {
+    let (clause, params) = filter.where_clause();
+    conn.query_row(
+        &format!("SELECT COUNT(*) FROM focus_records {clause}"),
+        rusqlite::params_from_iter(params),
+        |row| row.get(0),
+    )
+}
+
+/// 删除符合范围的专注记录，返回删除条数
+pub fn delete_matching_records(conn: &Connection, filter: &RecordFilter) -> Result<usize, rusqlite::Error> {
+    let (clause, params) = filter.where_clause();
+    conn.execute(&format!("DELETE FROM focus_records {clause}"), rusqlite::params_from_iter(params))
+}
+
+/// 删除 [from, to) 内与记录无关联的其余数据：中断、回顾日志（按 day）、计划块、日历事件、通知摘要、精力自评、日程、任务完成、分心、收集箱与休息事件。
+/// from_day / to_day 为对应的 `YYYY-MM-DD`（左闭右开）。返回删除总行数
+pub fn delete_activity_between(
+    conn: &Connection,
+    from: &str,
+    to: &str,
+    from_day: &str,
+    to_day: &str,
+) -> Result<usize, rusqlite::Error> {
+    let mut deleted = 0;
+    for (table, column) in [
+        ("interruptions", "occurred_at"),
+        ("plan_blocks", "start_at"),
+        ("calendar_events", "start_at"),
+        ("notification_digest", "occurred_at"),
+        ("energy_ratings", "rated_at"),
+        ("schedule", "start_at"),
+        ("task_completions", "completed_at"),
+        ("distractions", "started_at"),
+        ("inbox", "captured_at"),
+        ("break_events", "occurred_at"),
+    ] {
+        deleted += conn.execute(
+            &format!("DELETE FROM {table} WHERE {column} >= ?1 AND {column} < ?2"),
+            rusqlite::params![from, to],
+        )?;
+    }
+    deleted += conn.execute(
+        "DELETE FROM journal_entries WHERE day >= ?1 AND day < ?2",
+        rusqlite::params![from_day, to_day],
+    )?;
+    Ok(deleted)
+}
+
+/// 删除后整理数据库文件：清零已释放的页并重建，再把 WAL 检查点写回并截断，被删内容不再残留在文件中
+pub fn vacuum(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute_batch("PRAGMA secure_delete = ON; VACUUM;")?;
+    conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))
+}
+
+/// 可在电脑之间搬运的一条记录（合并、数据同步用）：项目以名称表示，两台电脑的项目 id 互不对应
+pub struct PortableRecord {
+    pub record: FocusRecord,
+    pub status: String,
+    /// 所属项目名；对方库没有项目表或项目已删除时为 None（归入「未分类」）
+    pub project_name: Option<String>,
+    /// Markdown 笔记，旧版本数据库为空
+    pub note: String,
+}
+
+/// 读取数据库文件（主库或归档库，可为另一台电脑的旧版本结构）中的全部专注记录（含放弃记录）。
+/// 不是本应用的数据库时返回 None；任务名原样读取，调用方需自行判断是否为密文
+pub fn load_portable_records(conn: &Connection) -> Result<Option<Vec<PortableRecord>>, rusqlite::Error> {
+    if !has_table(conn, "focus_records")? {
+        return Ok(None);
+    }
+    // 旧版本缺少的列取与 ensure_column 相同的默认值
+    let mut columns = Vec::new();
+    for (column, default) in [
+        ("commitment", "''"),
+        ("status", "'completed'"),
+        ("pause_count", "0"),
+        ("paused_secs", "0"),
+        ("overtime_secs", "0"),
+        ("note", "''"),
+    ] {
+        columns.push(if has_column(conn, "focus_records", column)? {
+            format!("r.{column}")
+        } else {
+            default.to_string()
+        });
+    }
+    let project_name = if has_table(conn, "projects")? && has_column(conn, "focus_records", "project_id")? {
+        "(SELECT name FROM projects p WHERE p.id = r.project_id)"
+    } else {
+        "NULL"
+    };
+    let mut stmt = conn.prepare(&format!(
+        "SELECT r.task, r.duration_secs, r.completed_at, r.completed_pomodoros, {}, {project_name}
+         FROM focus_records r ORDER BY r.completed_at ASC",
+        columns.join(", ")
+    ))?;
+    let rows = stmt.query_map([], |row| {
+        Ok(PortableRecord {
+            record: FocusRecord {
+                task: row.get(0)?,
+                duration_secs: row.get(1)?,
+                completed_at: row.get(2)?,
+                completed_pomodoros: row.get::<_, i64>(3)? as u32,
+                commitment: row.get(4)?,
+                pauses: PauseStats {
+                    count: row.get::<_, i64>(6)? as u32,
+                    secs: row.get(7)?,
+                },
+                project_id: UNCATEGORIZED_PROJECT_ID,
+                overtime_secs: row.get(8)?,
+            },
+            status: row.get(5)?,
+            project_name: row.get(10)?,
+            note: row.get(9)?,
+        })
+    })?;
+    rows.collect::<Result<_, _>>().map(Some)
+}
+
+/// 另一个数据库是否启用了加密（有加密参数，或任务名为密文，如加密库的归档库）
+pub fn foreign_is_encrypted(conn: &Connection) -> Result<bool, rusqlite::Error> {
+    if has_table(conn, "encryption")? && conn.query_row("SELECT EXISTS(SELECT 1 FROM encryption)", [], |row| row.get(0))? {
+        return Ok(true);
+    }
+    if !has_table(conn, "focus_records")? {
+        return Ok(false);
+    }
+    conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM focus_records WHERE task LIKE 'enc1:%')",
+        [],
+        |row| row.get(0),
+    )
+}
+
+/// 是否已有同任务、同完成时间、同时长的记录（合并两台电脑的数据时去重）
+pub fn focus_record_duplicate(
+    conn: &Connection,
+    task: &str,
+    completed_at: &str,
+    duration_secs: i64,
+) -> Result<bool, rusqlite::Error> {
+    conn.prepare_cached(
+        "SELECT EXISTS(SELECT 1 FROM focus_records WHERE task = ?1 AND completed_at = ?2 AND duration_secs = ?3)",
+    )?
+    .query_row(rusqlite::params![protect(task)?, completed_at, duration_secs], |row| row.get(0))
+}
+
+/// 把同任务、同完成时间、同时长的记录改到另一个项目，返回改动条数（数据同步中以对方为准时）
+pub fn reassign_record_project(
+    conn: &Connection,
+    task: &str,
+    completed_at: &str,
+    duration_secs: i64,
+    project_id: i64,
+) -> Result<usize, rusqlite::Error> {
+    conn.prepare_cached(
+        "UPDATE focus_records SET project_id = ?4 WHERE task = ?1 AND completed_at = ?2 AND duration_secs = ?3 AND project_id != ?4",
+    )?
+    .execute(rusqlite::params![protect(task)?, completed_at, duration_secs, project_id])
+}
+
+/// 推迟休息后再专注的秒数并入刚完成的那条记录：时长增加，完成时间改为 completed_at_new。返回改动条数
+pub fn extend_focus_record(
+    conn: &Connection,
+    task: &str,
+    completed_at: &str,
+    add_secs: i64,
+    completed_at_new: &str,
+) -> Result<usize, rusqlite::Error> {
+    conn.prepare_cached(
+        "UPDATE focus_records SET duration_secs = duration_secs + ?3, completed_at = ?4
+         WHERE task = ?1 AND completed_at = ?2 AND status = ?5",
+    )?
+    .execute(rusqlite::params![protect(task)?, completed_at, add_secs, completed_at_new, STATUS_COMPLETED])
+}
+
+/// 保存一条记录的 Markdown 笔记（空字符串为删除）；记录已归档时改写归档库。返回是否找到该记录
+pub fn set_record_note(conn: &Connection, id: i64, note: &str) -> Result<bool, rusqlite::Error> {
+    let note = protect(note.trim_end())?;
+    let sql = "UPDATE focus_records SET note = ?2 WHERE id = ?1";
+    if conn.execute(sql, rusqlite::params![id, note])? > 0 {
+        return Ok(true);
+    }
+    match open_archive()? {
+        Some(archive) => Ok(archive.execute(sql, rusqlite::params![id, note])? > 0),
+        None => Ok(false),
+    }
+}
+
+/// 按名称查找项目，不存在则新建；返回 (id, 是否新建)
+pub fn project_id_for_name(conn: &Connection, name: &str) -> Result<(i64, bool), rusqlite::Error> {
+    let existing = conn
+        .prepare_cached("SELECT id FROM projects WHERE name = ?1")?
+        .query_row(rusqlite::params![name], |row| row.get(0));
+    match existing {
+        Ok(id) => Ok((id, false)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok((insert_project(conn, name)?, true)),
+        Err(e) => Err(e),
+    }
+}
+
+/// 写入前按需加密；已启用加密但未解锁时报错，避免写入明文
+fn protect(plain: &str) -> Result<String, rusqlite::Error> {
+    crate::crypto::protect(plain)
+        .ok_or_else(|| rusqlite::Error::ToSqlConversionFailure("数据库已加密，尚未解锁".into()))
+}
+
+/// 加密参数：(盐, 口令校验密文)；未启用加密时为 None
+fn encryption_params(conn: &Connection) -> Result<Option<(String, String)>, rusqlite::Error> {
+    conn.prepare_cached("SELECT salt, verifier FROM encryption WHERE id = 1")?
+        .query_row([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map(Some)
+        .or_else(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => Ok(None),
+            e => Err(e),
+        })
+}
+
+/// 用口令解锁加密的数据库；口令正确返回 true 并在本次运行中记住密钥
+pub fn unlock_encryption(passphrase: &str) -> Result<bool, rusqlite::Error> {
+    let conn = open_and_init()?;
+    let Some((salt, verifier)) = encryption_params(&conn)? else {
+        return Ok(true);
+    };
+    let salt = crate::crypto::decode_salt(&salt).unwrap_or_default();
+    let key = crate::crypto::Key::derive(passphrase, &salt);
+    if key.open(&verifier).as_deref() != Some(crate::crypto::VERIFIER) {
+        return Ok(false);
+    }
+    crate::crypto::set_key(Some(key));
+    // 上次启用加密时归档库改写失败的，在这里补做；失败不影响解锁，下次解锁再试
+    if let Err(e) = seal_archive() {
+        tracing::warn!("加密归档库失败：{}", e);
+    }
+    Ok(true)
+}
+
+/// 启用加密：生成盐与口令校验值，把主库与归档库中已有的任务名、承诺改写为密文，再整理文件清除明文残留。
+/// 返回改写的记录条数
+///
+/// 主库的改写与加密参数在同一事务中提交，提交成功后才在内存中切换到加密状态：中途失败时库与内存都保持未加密，
+/// 不会用一把没有保存盐与校验值、重启后无法再派生的密钥加密之后的写入。归档库在主库提交之后改写，
+/// 失败时返回错误，已改写的部分与剩余明文可以共存，下次解锁时 `seal_archive` 补做。
+pub fn enable_encryption(passphrase: &str) -> Result<usize, rusqlite::Error> {
+    let mut conn = open_and_init()?;
+    if encryption_params(&conn)?.is_some() {
+        return Ok(0);
+    }
+    // 先补写预写日志，其中的明文记录随下面的改写一并加密
+    crate::wal::replay(&mut conn)?;
+    let salt = crate::crypto::new_salt();
+    let key = crate::crypto::Key::derive(passphrase, &salt);
+    let verifier = key.seal(crate::crypto::VERIFIER);
+    let tx = conn.transaction()?;
+    let mut rewritten = rewrite_text_columns(&tx, |s| Ok(key.protect(s)))?;
+    tx.execute(
+        "INSERT INTO encryption (id, salt, verifier) VALUES (1, ?1, ?2)",
+        rusqlite::params![crate::crypto::encode_salt(&salt), verifier],
+    )?;
+    tx.commit()?;
+    crate::crypto::set_key(Some(key));
+    crate::crypto::set_enabled(true);
+    vacuum(&conn)?;
+    rewritten += seal_archive()?;
+    Ok(rewritten)
+}
+
+/// 把归档库中仍为明文的内容用当前密钥加密（需已启用加密并解锁），再整理文件。返回改写的记录条数
+fn seal_archive() -> Result<usize, rusqlite::Error> {
+    let Some(mut archive) = open_archive()? else { return Ok(0) };
+    if crate::crypto::is_locked() || !has_plaintext(&archive)? {
+        return Ok(0);
+    }
+    let tx = archive.transaction()?;
+    let rewritten = rewrite_text_columns(&tx, protect)?;
+    tx.commit()?;
+    vacuum(&archive)?;
+    Ok(rewritten)
+}
+
+/// 需要加密的列中是否还有非空的明文
+fn has_plaintext(conn: &Connection) -> Result<bool, rusqlite::Error> {
+    for (table, columns) in PROTECTED_COLUMNS {
+        if !has_table(conn, table)? {
+            continue;
+        }
+        let plain: Vec<String> = columns
+            .iter()
+            .map(|c| format!("({c} != '' AND {c} NOT LIKE '{}%')", crate::crypto::PREFIX))
+            .collect();
+        let sql = format!("SELECT EXISTS (SELECT 1 FROM {table} WHERE {})", plain.join(" OR "));
+        if conn.query_row(&sql, [], |row| row.get::<_, bool>(0))? {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// 关闭加密（需已解锁）：把密文改写回明文并删除加密参数。返回改写的记录条数
+///
+/// 先补写预写日志（其中可能有加密的记录），再先改写归档库、后改写主库：主库提交前失败时加密参数仍在，
+/// 已解密的归档与密文共存，重启后仍能解锁读取；反过来则归档库中剩下的密文在删掉参数后再也无法解密。
+pub fn disable_encryption() -> Result<usize, rusqlite::Error> {
+    let mut conn = open_and_init()?;
+    if crate::crypto::is_locked() {
+        return Err(rusqlite::Error::ToSqlConversionFailure("数据库已加密，尚未解锁".into()));
+    }
+    crate::wal::replay(&mut conn)?;
+    let reveal = |s: &str| Ok(crate::crypto::reveal(s));
+    let mut rewritten = 0;
+    if let Some(mut archive) = open_archive()? {
+        let tx = archive.transaction()?;
+        rewritten += rewrite_text_columns(&tx, reveal)?;
+        tx.commit()?;
+    }
+    let tx = conn.transaction()?;
+    rewritten += rewrite_text_columns(&tx, reveal)?;
+    tx.execute("DELETE FROM encryption", [])?;
+    tx.commit()?;
+    crate::crypto::set_enabled(false);
+    crate::crypto::set_key(None);
+    Ok(rewritten)
+}
+
+/// 需要加密的文本列：专注记录的任务名、承诺与笔记、日程与任务完成的任务名、分心时的窗口标题、收集箱内容、任务颜色的任务名、
+/// 回顾日志的回答与通知摘要的内容（其中含任务名）
+const PROTECTED_COLUMNS: [(&str, &[&str]); 8] = [
+    ("focus_records", &["task", "commitment", "note"]),
+    ("schedule", &["task"]),
+    ("task_completions", &["task"]),
+    ("distractions", &["title"]),
+    ("inbox", &["text"]),
+    ("task_colors", &["task"]),
+    ("journal_entries", &["answer"]),
+    ("notification_digest", &["message"]),
+];
+
+/// 逐行改写需要加密的文本列（库中没有的表跳过，如归档库只有专注记录），返回有变化的行数
+fn rewrite_text_columns(
+    conn: &Connection,
+    convert: impl Fn(&str) -> Result<String, rusqlite::Error>,
+) -> Result<usize, rusqlite::Error> {
+    let mut changed = 0;
+    for (table, columns) in PROTECTED_COLUMNS {
+        if !has_table(conn, table)? {
+            continue;
+        }
+        let rows: Vec<(i64, Vec<String>)> = conn
+            .prepare(&format!("SELECT id, {} FROM {table}", columns.join(", ")))?
+            .query_map([], |row| {
+                let values = (0..columns.len()).map(|i| row.get(i + 1)).collect::<Result<_, _>>()?;
+                Ok((row.get(0)?, values))
+            })?
+            .collect::<Result<_, _>>()?;
+        let assignments: Vec<String> = columns.iter().enumerate().map(|(i, c)| format!("{c} = ?{}", i + 2)).collect();
+        let mut update = conn.prepare(&format!("UPDATE {table} SET {} WHERE id = ?1", assignments.join(", ")))?;
+        for (id, values) in rows {
+            let converted = values.iter().map(|v| convert(v)).collect::<Result<Vec<_>, _>>()?;
+            if converted != values {
+                let mut params: Vec<rusqlite::types::Value> = vec![id.into()];
+                params.extend(converted.into_iter().map(Into::into));
+                update.execute(rusqlite::params_from_iter(params))?;
+                changed += 1;
+            }
+        }
+    }
+    Ok(changed)
+}
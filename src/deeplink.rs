@@ -0,0 +1,327 @@
+//! `redtomato://` 链接：从启动器、笔记软件中一点即开始专注，如 `redtomato://start?task=写周报&mins=25`
+//!
+//! 支持 start（可带 task、mins）、pause、skip、show（只显示窗口）。系统把链接作为参数启动本程序：
+//! 已有实例在运行时经本机回环连接把链接转给它后退出，否则启动界面并执行。运行中的实例监听 127.0.0.1 的随机端口，
+//! 端口写在数据目录下的 `instance.port`；连接后先回一行标识，发送方据此确认对方确实是本程序。
+//!
+//! 协议在设置中注册：Windows 写入当前用户的注册表，Linux 写入 `.desktop` 文件并用 xdg-mime 设为默认处理程序。
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use eframe::egui;
+
+use crate::pomodoro::Command;
+
+pub const SCHEME: &str = "redtomato";
+const PORT_FILENAME: &str = "instance.port";
+/// 监听端连接后回复的标识
+const HELLO: &str = "red-tomato";
+/// 一条链接的长度上限
+const MAX_LINK_LEN: usize = 4096;
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(500);
+/// 专注时长上限（分钟）
+const MAX_MINUTES: u32 = 240;
+
+/// 解析后的链接
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeepLink {
+    /// None 为只显示窗口
+    pub command: Option<Command>,
+    pub task: Option<String>,
+    pub minutes: Option<u32>,
+}
+
+/// 参数是否为本程序的链接
+pub fn is_link(arg: &str) -> bool {
+    arg.get(..SCHEME.len() + 3).is_some_and(|p| p.eq_ignore_ascii_case(&format!("{}://", SCHEME)))
+}
+
+/// 解析 `redtomato://动作?参数`；未知动作或参数格式不对时返回原因
+pub fn parse(url: &str) -> Result<DeepLink, String> {
+    if !is_link(url) {
+        return Err(format!("不是 {}:// 链接", SCHEME));
+    }
+    let rest = &url[SCHEME.len() + 3..];
+    let (action, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let command = match action.trim_end_matches('/').to_ascii_lowercase().as_str() {
+        "start" => Some(Command::Start),
+        "pause" => Some(Command::Pause),
+        "skip" => Some(Command::Skip),
+        "show" | "" => None,
+        other => return Err(format!("未知的动作：{}", other)),
+    };
+    let mut link = DeepLink { command, task: None, minutes: None };
+    for pair in query.split('&').filter(|p| !p.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let value = percent_decode(value).ok_or_else(|| format!("参数 {} 的编码不正确", key))?;
+        match key {
+            "task" => link.task = Some(value.trim().to_string()).filter(|t| !t.is_empty()),
+            "mins" | "minutes" => {
+                let minutes = value.parse::<u32>().ok().filter(|m| (1..=MAX_MINUTES).contains(m));
+                link.minutes = Some(minutes.ok_or_else(|| format!("分钟数应为 1–{}：{}", MAX_MINUTES, value))?);
+            }
+            // 未知参数忽略，便于以后扩展
+            _ => {}
+        }
+    }
+    if link.command != Some(Command::Start) && (link.task.is_some() || link.minutes.is_some()) {
+        return Err("只有 start 可以带任务与时长".to_string());
+    }
+    Ok(link)
+}
+
+/// 百分号解码（`+` 为空格）；`%` 后不是两位十六进制数字或解码结果不是 UTF-8 时为 None
+fn percent_decode(s: &str) -> Option<String> {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                // 先逐个检查：from_str_radix 会接受前导 `+`，`%+1` 不能解成 0x01
+                let hex = bytes.get(i + 1..i + 3).filter(|h| h.iter().all(u8::is_ascii_hexdigit))?;
+                out.push(u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()?);
+                i += 3;
+            }
+            b'+' => {
+                out.push(b' ');
+                i += 1;
+            }
+            b => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+fn port_path() -> PathBuf {
+    crate::db::data_dir().join(PORT_FILENAME)
+}
+
+fn queue() -> &'static Mutex<Vec<DeepLink>> {
+    static QUEUE: Mutex<Vec<DeepLink>> = Mutex::new(Vec::new());
+    &QUEUE
+}
+
+/// 启动时命令行带的链接，界面首帧执行
+pub fn queue_startup(link: DeepLink) {
+    queue().lock().unwrap().push(link);
+}
+
+/// 取走待执行的链接
+pub fn take() -> Vec<DeepLink> {
+    std::mem::take(&mut *queue().lock().unwrap())
+}
+
+/// 把链接转给运行中的实例；没有实例在运行（或端口已被别的程序占用）时返回 false
+pub fn forward(url: &str) -> bool {
+    let Some(port) = std::fs::read_to_string(port_path()).ok().and_then(|s| s.trim().parse::<u16>().ok()) else {
+        return false;
+    };
+    let send = || -> std::io::Result<bool> {
+        let mut stream = TcpStream::connect_timeout(&(Ipv4Addr::LOCALHOST, port).into(), CONNECT_TIMEOUT)?;
+        stream.set_read_timeout(Some(CONNECT_TIMEOUT))?;
+        let mut hello = String::new();
+        BufReader::new(&stream).read_line(&mut hello)?;
+        if hello.trim_end() != HELLO {
+            return Ok(false);
+        }
+        writeln!(stream, "{}", url)?;
+        Ok(true)
+    };
+    send().unwrap_or(false)
+}
+
+/// 开始监听其他进程转来的链接（多次调用只启动一次）；收到后唤醒界面
+pub fn listen(ctx: &egui::Context) {
+    static STARTED: OnceLock<()> = OnceLock::new();
+    STARTED.get_or_init(|| {
+        let listener = match TcpListener::bind((Ipv4Addr::LOCALHOST, 0)) {
+            Ok(l) => l,
+            Err(e) => {
+                tracing::warn!("链接监听启动失败：{}", e);
+                return;
+            }
+        };
+        let port = listener.local_addr().map(|a| a.port()).unwrap_or(0);
+        if let Err(e) = std::fs::write(port_path(), port.to_string()) {
+            tracing::warn!("写入 {} 失败：{}", PORT_FILENAME, e);
+            return;
+        }
+        let ctx = ctx.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if let Some(url) = receive(stream) {
+                    match parse(&url) {
+                        Ok(link) => {
+                            queue().lock().unwrap().push(link);
+                            ctx.request_repaint();
+                        }
+                        Err(e) => tracing::warn!("忽略无法解析的链接 {}：{}", url, e),
+                    }
+                }
+            }
+        });
+    });
+}
+
+/// 回复标识后读一行链接
+fn receive(mut stream: TcpStream) -> Option<String> {
+    stream.set_read_timeout(Some(CONNECT_TIMEOUT)).ok()?;
+    writeln!(stream, "{}", HELLO).ok()?;
+    let mut line = String::new();
+    BufReader::new(stream.take(MAX_LINK_LEN as u64)).read_line(&mut line).ok()?;
+    Some(line.trim_end().to_string()).filter(|l| is_link(l))
+}
+
+/// 注册链接协议，指向当前可执行文件（便携模式下带上 --portable）；返回说明
+pub fn register() -> Result<String, String> {
+    let exe = std::env::current_exe().map_err(|e| format!("找不到程序路径：{}", e))?;
+    let portable = crate::db::portable_dir().is_some();
+    register_for(&exe.display().to_string(), portable)
+}
+
+#[cfg(windows)]
+fn register_for(exe: &str, portable: bool) -> Result<String, String> {
+    use windows_sys::Win32::System::Registry::{
+        HKEY, HKEY_CURRENT_USER, KEY_WRITE, REG_OPTION_NON_VOLATILE, REG_SZ, RegCloseKey, RegCreateKeyExW, RegSetValueExW,
+    };
+    let wide = |s: &str| -> Vec<u16> { s.encode_utf16().chain(Some(0)).collect() };
+    let set = |path: &str, name: Option<&str>, value: &str| -> Result<(), String> {
+        let mut key: HKEY = std::ptr::null_mut();
+        let path_w = wide(path);
+        let status = unsafe {
+            RegCreateKeyExW(
+                HKEY_CURRENT_USER,
+                path_w.as_ptr(),
+                0,
+                std::ptr::null(),
+                REG_OPTION_NON_VOLATILE,
+                KEY_WRITE,
+                std::ptr::null(),
+                &mut key,
+                std::ptr::null_mut(),
+            )
+        };
+        if status != 0 {
+            return Err(format!("创建注册表项 {} 失败（{}）", path, status));
+        }
+        let name_w = name.map(wide);
+        let value_w = wide(value);
+        let status = unsafe {
+            let status = RegSetValueExW(
+                key,
+                name_w.as_ref().map_or(std::ptr::null(), |n| n.as_ptr()),
+                0,
+                REG_SZ,
+                value_w.as_ptr().cast(),
+                (value_w.len() * 2) as u32,
+            );
+            RegCloseKey(key);
+            status
+        };
+        if status != 0 {
+            return Err(format!("写入注册表项 {} 失败（{}）", path, status));
+        }
+        Ok(())
+    };
+    let root = format!("Software\\Classes\\{}", SCHEME);
+    let flag = if portable { " --portable" } else { "" };
+    set(&root, None, "URL:红番茄")?;
+    set(&root, Some("URL Protocol"), "")?;
+    set(&format!("{}\\DefaultIcon", root), None, &format!("\"{}\",0", exe))?;
+    set(&format!("{}\\shell\\open\\command", root), None, &format!("\"{}\"{} \"%1\"", exe, flag))?;
+    Ok(format!("已注册 {}:// 链接（当前用户）", SCHEME))
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn register_for(exe: &str, portable: bool) -> Result<String, String> {
+    const DESKTOP_FILENAME: &str = "red-tomato-url.desktop";
+    let dir = dirs::data_dir().ok_or("找不到用户数据目录")?.join("applications");
+    std::fs::create_dir_all(&dir).map_err(|e| format!("创建 {} 失败：{}", dir.display(), e))?;
+    let flag = if portable { " --portable" } else { "" };
+    // Exec 中的路径加引号，内部的 \ " ` $ 需转义
+    let quoted: String = exe.chars().flat_map(|c| if "\\\"`$".contains(c) { vec!['\\', c] } else { vec![c] }).collect();
+    let entry = format!(
+        "[Desktop Entry]\nType=Application\nName=红番茄\nExec=\"{}\"{} %u\nNoDisplay=true\nMimeType=x-scheme-handler/{};\n",
+        quoted, flag, SCHEME
+    );
+    let path = dir.join(DESKTOP_FILENAME);
+    std::fs::write(&path, entry).map_err(|e| format!("写入 {} 失败：{}", path.display(), e))?;
+    let status = std::process::Command::new("xdg-mime")
+        .args(["default", DESKTOP_FILENAME, &format!("x-scheme-handler/{}", SCHEME)])
+        .status()
+        .map_err(|e| format!("已写入 {}，但运行 xdg-mime 失败：{}", path.display(), e))?;
+    if !status.success() {
+        return Err(format!("已写入 {}，但 xdg-mime 设为默认处理程序失败", path.display()));
+    }
+    Ok(format!("已注册 {}:// 链接：{}", SCHEME, path.display()))
+}
+
+#[cfg(not(any(windows, all(unix, not(target_os = "macos")))))]
+fn register_for(_exe: &str, _portable: bool) -> Result<String, String> {
+    Err("此平台需在应用包的 Info.plist 中声明链接协议，无法在程序内注册".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_start_with_task_and_minutes() {
+        let link = parse("redtomato://start?task=%E5%86%99%E5%91%A8%E6%8A%A5+v2&mins=25").unwrap();
+        assert_eq!(link.command, Some(Command::Start));
+        assert_eq!(link.task.as_deref(), Some("写周报 v2"));
+        assert_eq!(link.minutes, Some(25));
+    }
+
+    #[test]
+    fn parses_actions_case_insensitively() {
+        assert_eq!(parse("RedTomato://PAUSE").unwrap().command, Some(Command::Pause));
+        assert_eq!(parse("redtomato://skip/").unwrap().command, Some(Command::Skip));
+        assert_eq!(parse("redtomato://show").unwrap().command, None);
+        assert_eq!(parse("redtomato://").unwrap().command, None);
+    }
+
+    #[test]
+    fn ignores_unknown_parameters_and_empty_task() {
+        let link = parse("redtomato://start?task=+&from=launcher").unwrap();
+        assert_eq!(link.task, None);
+        assert_eq!(link.minutes, None);
+    }
+
+    #[test]
+    fn rejects_invalid_links() {
+        assert!(parse("https://example.com/start").is_err());
+        assert!(parse("redtomato://delete").is_err());
+        assert!(parse("redtomato://start?mins=0").is_err());
+        assert!(parse(&format!("redtomato://start?mins={}", MAX_MINUTES + 1)).is_err());
+        assert!(parse("redtomato://start?mins=abc").is_err());
+        assert!(parse("redtomato://pause?task=x").is_err());
+        assert!(parse("redtomato://start?task=%zz").is_err());
+    }
+
+    #[test]
+    fn percent_decode_handles_escapes() {
+        assert_eq!(percent_decode("a%20b+c").as_deref(), Some("a b c"));
+        assert_eq!(percent_decode("%e4%bd%a0").as_deref(), Some("你"));
+        assert_eq!(percent_decode("100%25").as_deref(), Some("100%"));
+    }
+
+    #[test]
+    fn percent_decode_rejects_malformed_escapes() {
+        assert_eq!(percent_decode("%+1"), None);
+        assert_eq!(percent_decode("%-1"), None);
+        assert_eq!(percent_decode("%1"), None);
+        assert_eq!(percent_decode("%"), None);
+        assert_eq!(percent_decode("%G0"), None);
+        // 不是 UTF-8
+        assert_eq!(percent_decode("%FF"), None);
+    }
+}
@@ -0,0 +1,134 @@
+//! 分心提醒：专注计时中每秒读取一次前台窗口标题，包含黑名单关键词（如「哔哩哔哩」「Twitter」）且持续超过设定秒数时
+//! 在屏幕顶部显示温和的提醒，离开该窗口或专注结束时把这次分心写入数据库供统计。
+//!
+//! 读取前台窗口目前仅支持 Windows（GetForegroundWindow + GetWindowTextW），其他平台不检测。
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// 当前平台能否读取前台窗口标题
+pub const SUPPORTED: bool = cfg!(windows);
+/// 读取前台窗口的间隔
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 分心提醒设置（仅本机）
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DistractionSettings {
+    pub enabled: bool,
+    /// 窗口标题关键词（不区分大小写）
+    pub blacklist: Vec<String>,
+    /// 停留超过多少秒才提醒并记录
+    pub grace_secs: u32,
+}
+
+impl Default for DistractionSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            blacklist: vec!["哔哩哔哩".to_string(), "Twitter".to_string(), "微博".to_string()],
+            grace_secs: 10,
+        }
+    }
+}
+
+impl DistractionSettings {
+    /// 标题命中的第一个关键词
+    pub fn matches(&self, title: &str) -> Option<&str> {
+        let title = title.to_lowercase();
+        self.blacklist
+            .iter()
+            .map(|k| k.trim())
+            .find(|k| !k.is_empty() && title.contains(&k.to_lowercase()))
+    }
+}
+
+/// 一次结束的分心：命中的关键词、窗口标题、开始时间与持续秒数
+pub struct Distraction {
+    pub keyword: String,
+    pub title: String,
+    pub started_at: DateTime<Utc>,
+    pub duration_secs: i64,
+}
+
+/// 正在进行的分心
+struct Current {
+    keyword: String,
+    title: String,
+    since: Instant,
+    started_at: DateTime<Utc>,
+}
+
+/// 前台窗口检测状态
+#[derive(Default)]
+pub struct DistractionMonitor {
+    last_poll: Option<Instant>,
+    current: Option<Current>,
+}
+
+impl DistractionMonitor {
+    /// 每帧调用；`focusing` 为专注计时正在运行。一次超过设定秒数的分心结束时返回它（供写入数据库）
+    pub fn tick(&mut self, settings: &DistractionSettings, focusing: bool, now: Instant) -> Option<Distraction> {
+        if !SUPPORTED || !settings.enabled || !focusing {
+            self.last_poll = None;
+            return self.finish(settings, now);
+        }
+        if self.last_poll.is_some_and(|t| now.duration_since(t) < POLL_INTERVAL) {
+            return None;
+        }
+        self.last_poll = Some(now);
+        let title = foreground_title().unwrap_or_default();
+        let keyword = settings.matches(&title).map(str::to_string);
+        if keyword.is_some() && self.current.as_ref().map(|c| &c.keyword) == keyword.as_ref() {
+            return None;
+        }
+        let ended = self.finish(settings, now);
+        self.current = keyword.map(|keyword| Current {
+            keyword,
+            title,
+            since: now,
+            started_at: Utc::now(),
+        });
+        ended
+    }
+
+    /// 当前分心已超过设定秒数时：(关键词, 已持续秒数)
+    pub fn nudge(&self, settings: &DistractionSettings, now: Instant) -> Option<(&str, u64)> {
+        let current = self.current.as_ref()?;
+        let secs = now.duration_since(current.since).as_secs();
+        (secs >= settings.grace_secs as u64).then_some((current.keyword.as_str(), secs))
+    }
+
+    /// 结束当前分心；未超过设定秒数的不记录
+    fn finish(&mut self, settings: &DistractionSettings, now: Instant) -> Option<Distraction> {
+        let current = self.current.take()?;
+        let secs = now.duration_since(current.since).as_secs();
+        (secs >= settings.grace_secs as u64).then(|| Distraction {
+            keyword: current.keyword,
+            title: current.title,
+            started_at: current.started_at,
+            duration_secs: secs as i64,
+        })
+    }
+}
+
+/// 前台窗口标题
+#[cfg(windows)]
+fn foreground_title() -> Option<String> {
+    use windows_sys::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowTextW};
+    let mut buf = [0u16; 512];
+    let len = unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd.is_null() {
+            return None;
+        }
+        GetWindowTextW(hwnd, buf.as_mut_ptr(), buf.len() as i32)
+    };
+    (len > 0).then(|| String::from_utf16_lossy(&buf[..len as usize]))
+}
+
+#[cfg(not(windows))]
+fn foreground_title() -> Option<String> {
+    None
+}
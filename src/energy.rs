@@ -0,0 +1,66 @@
+//! 按精力自评推荐专注时长：开始专注前可给当前精力打 1–5 分，
+//! 根据最近几次自评建议完整专注还是先做一段短的热身，并给出理由
+//!
+//! 规则刻意保持简单：只看最近 RECENT_HOURS 小时内最多 RECENT_COUNT 次自评。
+//! 平均分不高于 LOW_AVERAGE，或刚打的分不高于 LOW_RATING 时建议热身，否则建议完整专注。
+
+/// 精力评分范围
+pub const MIN_ENERGY: u8 = 1;
+pub const MAX_ENERGY: u8 = 5;
+/// 热身专注时长（秒）
+pub const WARMUP_SECS: i64 = 10 * 60;
+/// 参与推荐的自评时间范围与条数
+pub const RECENT_HOURS: i64 = 12;
+pub const RECENT_COUNT: u32 = 3;
+/// 平均分不高于此值建议热身
+const LOW_AVERAGE: f32 = 2.5;
+/// 最新一次不高于此值也建议热身（刚觉得累时不看旧分数）
+const LOW_RATING: u8 = 2;
+
+/// 推荐的专注方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Suggestion {
+    /// 按配置的时长完整专注
+    Full,
+    /// 先做 WARMUP_SECS 的短专注热身
+    WarmUp,
+}
+
+/// 推荐结果与展示给用户的理由
+#[derive(Clone, Debug, PartialEq)]
+pub struct Recommendation {
+    pub suggestion: Suggestion,
+    pub reason: String,
+}
+
+/// 根据最近的自评（最新在前）给出推荐；没有自评时为 None
+pub fn recommend(recent: &[u8], focus_secs: i64) -> Option<Recommendation> {
+    let latest = *recent.first()?;
+    let average = recent.iter().map(|&e| e as f32).sum::<f32>() / recent.len() as f32;
+    let full_mins = focus_secs / 60;
+    let warmup_mins = WARMUP_SECS / 60;
+    // 专注本身不长于热身时，没有缩短的必要
+    let can_shorten = focus_secs > WARMUP_SECS;
+    let (suggestion, reason) = if can_shorten && latest <= LOW_RATING {
+        (
+            Suggestion::WarmUp,
+            format!("刚记录的精力只有 {} 分，先做 {} 分钟热身更容易进入状态", latest, warmup_mins),
+        )
+    } else if can_shorten && average <= LOW_AVERAGE {
+        (
+            Suggestion::WarmUp,
+            format!(
+                "最近 {} 次精力平均 {:.1} 分，偏低：先做 {} 分钟热身",
+                recent.len(),
+                average,
+                warmup_mins
+            ),
+        )
+    } else {
+        (
+            Suggestion::Full,
+            format!("最近 {} 次精力平均 {:.1} 分，状态不错：完整专注 {} 分钟", recent.len(), average, full_mins),
+        )
+    };
+    Some(Recommendation { suggestion, reason })
+}
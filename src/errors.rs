@@ -0,0 +1,86 @@
+//! 错误提示条：数据库读写与集成失败时在窗口顶部提示，可重试或打开日志，关闭前一直显示
+//!
+//! 失败处（含后台线程）调用 `report` 放入全局队列并记入日志，界面每帧取回；同类错误只保留最新一条并累计次数。
+
+use std::sync::Mutex;
+
+/// 错误来源，决定「重试」做什么
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// 数据库打开 / 读写失败：重试时回放预写日志并重新加载数据
+    Database,
+    /// 专注记录同步失败：重试时立即同步
+    DataSync,
+    /// 设置同步失败：重试时重新拉取
+    SettingsSync,
+    /// 其他集成（推送、日记、HTTP 接口等），没有重试
+    Integration,
+}
+
+impl ErrorKind {
+    pub fn retryable(self) -> bool {
+        self != ErrorKind::Integration
+    }
+}
+
+/// 一条待显示的错误
+pub struct ErrorReport {
+    pub kind: ErrorKind,
+    pub message: String,
+    /// 关闭前同类错误发生的次数
+    pub count: u32,
+}
+
+fn pending() -> &'static Mutex<Vec<(ErrorKind, String)>> {
+    static PENDING: Mutex<Vec<(ErrorKind, String)>> = Mutex::new(Vec::new());
+    &PENDING
+}
+
+/// 报告一次错误（任意线程）：记入日志并交给界面显示
+pub fn report(kind: ErrorKind, message: String) {
+    tracing::warn!("{}", message);
+    pending().lock().unwrap().push((kind, message));
+}
+
+/// 数据库操作失败时报告（代替 `let _ =`）
+pub trait ReportErr<T> {
+    /// what 为操作名，如「写入专注记录」；失败时返回 None
+    fn db_err(self, what: &str) -> Option<T>;
+}
+
+impl<T, E: std::fmt::Display> ReportErr<T> for Result<T, E> {
+    fn db_err(self, what: &str) -> Option<T> {
+        self.map_err(|e| report(ErrorKind::Database, format!("{}失败：{}", what, e))).ok()
+    }
+}
+
+/// 界面上正在显示的错误
+#[derive(Default)]
+pub struct ErrorReports {
+    items: Vec<ErrorReport>,
+}
+
+impl ErrorReports {
+    /// 取回队列中的新错误；同类的替换旧消息
+    pub fn collect(&mut self) {
+        let new = std::mem::take(&mut *pending().lock().unwrap());
+        for (kind, message) in new {
+            match self.items.iter_mut().find(|r| r.kind == kind) {
+                Some(item) => {
+                    item.message = message;
+                    item.count += 1;
+                }
+                None => self.items.push(ErrorReport { kind, message, count: 1 }),
+            }
+        }
+    }
+
+    pub fn items(&self) -> &[ErrorReport] {
+        &self.items
+    }
+
+    /// 关闭某类错误（用户关闭、重试前或之后成功时）
+    pub fn dismiss(&mut self, kind: ErrorKind) {
+        self.items.retain(|r| r.kind != kind);
+    }
+}
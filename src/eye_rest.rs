@@ -0,0 +1,82 @@
+//! 护眼提醒（20-20-20 法则）：每累计专注一段时间（默认 20 分钟）提醒看远处片刻（默认 20 秒）
+//!
+//! 与番茄阶段相互独立：只在专注计时运行时累计，暂停或休息时停止累计但不清零，跨番茄继续计算。
+
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// 护眼提醒设置（仅本机）
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EyeRestSettings {
+    pub enabled: bool,
+    /// 每累计专注多少分钟提醒一次
+    pub interval_minutes: u32,
+    /// 看远处的秒数（浮层倒计时）
+    pub rest_secs: u32,
+    /// 在窗口上显示倒计时浮层（关闭时只按通知路由提醒）
+    pub overlay: bool,
+}
+
+impl Default for EyeRestSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_minutes: 20,
+            rest_secs: 20,
+            overlay: true,
+        }
+    }
+}
+
+impl EyeRestSettings {
+    pub fn message(&self) -> String {
+        format!("看远处 {} 秒", self.rest_secs)
+    }
+}
+
+/// 护眼计时：累计的专注时长与当前提醒的结束时间
+#[derive(Default)]
+pub struct EyeRestTimer {
+    focused: Duration,
+    last_tick: Option<Instant>,
+    rest_until: Option<Instant>,
+}
+
+impl EyeRestTimer {
+    /// 每帧调用；`focusing` 为专注计时正在运行。累计满间隔时开始一次提醒并返回 true
+    pub fn tick(&mut self, settings: &EyeRestSettings, focusing: bool, now: Instant) -> bool {
+        if self.rest_until.is_some_and(|until| now >= until) {
+            self.rest_until = None;
+        }
+        if !settings.enabled {
+            *self = Self::default();
+            return false;
+        }
+        let elapsed = self.last_tick.map_or(Duration::ZERO, |last| now - last);
+        self.last_tick = focusing.then_some(now);
+        if !focusing {
+            return false;
+        }
+        self.focused += elapsed;
+        let interval = Duration::from_secs(settings.interval_minutes.max(1) as u64 * 60);
+        if self.focused < interval {
+            return false;
+        }
+        self.focused = Duration::ZERO;
+        self.rest_until = Some(now + Duration::from_secs(settings.rest_secs as u64));
+        true
+    }
+
+    /// 正在提醒时剩余的秒数
+    pub fn resting_secs_left(&self, now: Instant) -> Option<u64> {
+        self.rest_until
+            .filter(|until| *until > now)
+            .map(|until| (until - now).as_secs_f32().ceil() as u64)
+    }
+
+    /// 提前结束本次提醒
+    pub fn dismiss(&mut self) {
+        self.rest_until = None;
+    }
+}
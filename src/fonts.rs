@@ -0,0 +1,205 @@
+//! 字体管理：查找 Windows / macOS / Linux 上的中文字体（数据由 egui 持有，不再 Box::leak），支持运行时切换与自定义字体文件
+
+use eframe::egui;
+use std::sync::Arc;
+
+/// egui 字体表中中文字体的键名
+const FONT_KEY: &str = "chinese";
+
+/// 一个可选字体：界面显示名 + 文件路径
+#[derive(Clone, Debug)]
+pub struct FontChoice {
+    pub name: String,
+    pub path: String,
+}
+
+/// 候选系统字体（按优先级），只有文件存在的才会出现在可选列表中
+#[cfg(windows)]
+const SYSTEM_FONT_CANDIDATES: &[(&str, &str)] = &[
+    ("微软雅黑", r"C:\Windows\Fonts\msyh.ttc"),
+    ("黑体", r"C:\Windows\Fonts\simhei.ttf"),
+    ("宋体", r"C:\Windows\Fonts\simsun.ttc"),
+];
+
+#[cfg(target_os = "macos")]
+const SYSTEM_FONT_CANDIDATES: &[(&str, &str)] = &[
+    ("苹方", "/System/Library/Fonts/PingFang.ttc"),
+    ("冬青黑体", "/System/Library/Fonts/Hiragino Sans GB.ttc"),
+    ("华文黑体", "/System/Library/Fonts/STHeiti Medium.ttc"),
+    ("Arial Unicode", "/Library/Fonts/Arial Unicode.ttf"),
+];
+
+#[cfg(all(unix, not(target_os = "macos")))]
+const SYSTEM_FONT_CANDIDATES: &[(&str, &str)] = &[
+    ("Noto Sans CJK", "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc"),
+    ("Noto Sans CJK", "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc"),
+    ("Noto Sans CJK", "/usr/share/fonts/google-noto-cjk/NotoSansCJK-Regular.ttc"),
+    ("思源黑体", "/usr/share/fonts/adobe-source-han-sans/SourceHanSansCN-Regular.otf"),
+    ("文泉驿微米黑", "/usr/share/fonts/truetype/wqy/wqy-microhei.ttc"),
+    ("文泉驿正黑", "/usr/share/fonts/truetype/wqy/wqy-zenhei.ttc"),
+    ("文泉驿微米黑", "/usr/share/fonts/wenquanyi/wqy-microhei/wqy-microhei.ttc"),
+    ("Droid Sans Fallback", "/usr/share/fonts/truetype/droid/DroidSansFallbackFull.ttf"),
+];
+
+#[cfg(not(any(windows, unix)))]
+const SYSTEM_FONT_CANDIDATES: &[(&str, &str)] = &[];
+
+/// 固定路径都没有时，扫描这些字体目录（类似 fontconfig 的查找），按文件名识别常见中文字体
+fn font_search_dirs() -> Vec<std::path::PathBuf> {
+    let mut dirs_list = Vec::new();
+    if let Some(home) = dirs::home_dir() {
+        dirs_list.push(home.join(".local/share/fonts"));
+        dirs_list.push(home.join(".fonts"));
+        dirs_list.push(home.join("Library/Fonts"));
+    }
+    for d in ["/usr/share/fonts", "/usr/local/share/fonts", "/Library/Fonts", "/System/Library/Fonts"] {
+        dirs_list.push(std::path::PathBuf::from(d));
+    }
+    dirs_list
+}
+
+/// 文件名中含这些片段（小写）的视为中文字体
+const CJK_NAME_HINTS: &[&str] = &["notosanscjk", "notosanssc", "sourcehansans", "wqy", "droidsansfallback", "pingfang", "msyh"];
+
+/// 递归扫描字体目录（限制深度），返回找到的中文字体
+fn discover_cjk_fonts() -> Vec<FontChoice> {
+    fn walk(dir: &std::path::Path, depth: u32, out: &mut Vec<FontChoice>) {
+        let Ok(entries) = std::fs::read_dir(dir) else { return };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                if depth > 0 {
+                    walk(&path, depth - 1, out);
+                }
+                continue;
+            }
+            let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else { continue };
+            let lower = file_name.to_lowercase();
+            let is_font = [".ttf", ".ttc", ".otf"].iter().any(|ext| lower.ends_with(ext));
+            if is_font && CJK_NAME_HINTS.iter().any(|h| lower.contains(h)) {
+                let name = file_name.rsplit_once('.').map(|(stem, _)| stem).unwrap_or(file_name);
+                out.push(FontChoice {
+                    name: name.to_string(),
+                    path: path.to_string_lossy().into_owned(),
+                });
+            }
+        }
+    }
+    let mut found = Vec::new();
+    for dir in font_search_dirs() {
+        walk(&dir, 4, &mut found);
+    }
+    found.sort_by(|a, b| a.name.cmp(&b.name));
+    found
+}
+
+/// 设置中「自定义字体文件」对应的字体名
+pub const CUSTOM_FONT_NAME: &str = "自定义文件";
+
+/// 字体管理器：记录可选字体与当前选中项，切换时重建 FontDefinitions
+///
+/// 字体数据以 `FontData::from_owned` 交给 egui，旧字体在 `set_fonts` 替换后随 Arc 引用计数释放。
+pub struct FontManager {
+    choices: Vec<FontChoice>,
+    current: Option<String>,
+}
+
+impl FontManager {
+    /// 扫描候选字体：系统固定路径 → 字体目录扫描 → 编译期 RED_TOMATO_FONT_PATH → 用户指定的字体文件
+    pub fn new(custom_path: Option<&str>) -> Self {
+        let mut choices: Vec<FontChoice> = Vec::new();
+        for (name, path) in SYSTEM_FONT_CANDIDATES {
+            // 同名字体（不同发行版路径）只保留第一个存在的
+            if std::path::Path::new(path).exists() && !choices.iter().any(|c| c.name == *name) {
+                choices.push(FontChoice {
+                    name: (*name).to_string(),
+                    path: (*path).to_string(),
+                });
+            }
+        }
+        for found in discover_cjk_fonts() {
+            if !choices.iter().any(|c| c.path == found.path || c.name == found.name) {
+                choices.push(found);
+            }
+        }
+        if let Some(custom) = option_env!("RED_TOMATO_FONT_PATH") {
+            if std::path::Path::new(custom).exists() {
+                choices.push(FontChoice {
+                    name: "编译期指定".to_string(),
+                    path: custom.to_string(),
+                });
+            }
+        }
+        let mut manager = Self {
+            choices,
+            current: None,
+        };
+        manager.set_custom_path(custom_path);
+        manager
+    }
+
+    /// 设置 / 清除用户指定的字体文件（出现在可选列表末尾，名为「自定义文件」）
+    pub fn set_custom_path(&mut self, path: Option<&str>) {
+        self.choices.retain(|c| c.name != CUSTOM_FONT_NAME);
+        if let Some(path) = path.map(str::trim).filter(|p| !p.is_empty()) {
+            self.choices.push(FontChoice {
+                name: CUSTOM_FONT_NAME.to_string(),
+                path: path.to_string(),
+            });
+        }
+    }
+
+    pub fn choices(&self) -> &[FontChoice] {
+        &self.choices
+    }
+
+    /// 当前生效的字体名（None 表示 egui 内置字体）
+    pub fn current(&self) -> Option<&str> {
+        self.current.as_deref()
+    }
+
+    /// 当前生效字体的文件路径（生成分享图时用同一字体绘制文字）
+    pub fn current_path(&self) -> Option<&str> {
+        let name = self.current.as_deref()?;
+        self.choices.iter().find(|c| c.name == name).map(|c| c.path.as_str())
+    }
+
+    /// 应用指定字体；name 为 None 或找不到/读取失败时依次尝试其余可选字体，
+    /// 全部失败则恢复 egui 内置字体。返回最终生效的字体名
+    pub fn apply(&mut self, ctx: &egui::Context, name: Option<&str>) -> Option<&str> {
+        let preferred = name.and_then(|n| self.choices.iter().position(|c| c.name == n));
+        let order = preferred
+            .into_iter()
+            .chain((0..self.choices.len()).filter(|&i| Some(i) != preferred));
+        for i in order {
+            let choice = &self.choices[i];
+            if let Ok(bytes) = std::fs::read(&choice.path) {
+                ctx.set_fonts(font_definitions_with(bytes));
+                self.current = Some(choice.name.clone());
+                return self.current();
+            }
+        }
+        ctx.set_fonts(egui::FontDefinitions::default());
+        self.current = None;
+        None
+    }
+}
+
+/// 以默认字体表为基础，把中文字体插到比例/等宽字体族的首位
+fn font_definitions_with(bytes: Vec<u8>) -> egui::FontDefinitions {
+    let mut fonts = egui::FontDefinitions::default();
+    fonts
+        .font_data
+        .insert(FONT_KEY.to_owned(), Arc::new(egui::FontData::from_owned(bytes)));
+    fonts
+        .families
+        .entry(egui::FontFamily::Proportional)
+        .or_default()
+        .insert(0, FONT_KEY.to_owned());
+    fonts
+        .families
+        .entry(egui::FontFamily::Monospace)
+        .or_default()
+        .insert(0, FONT_KEY.to_owned());
+    fonts
+}
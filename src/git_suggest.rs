@@ -0,0 +1,90 @@
+//! 按版本库活动推荐任务名：读取配置的 git 仓库当前分支名与最近一次提交说明，
+//! 开始专注前在任务输入框下方列出，点一下即可填入，省去重复输入工单号
+//!
+//! 调用系统 `git` 命令读取，结果缓存一段时间，仅在需要显示建议时刷新。
+
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// 建议缓存时长（切换分支或提交后最迟这么久更新）
+const REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+/// 主干分支名不含任务信息，不作为建议
+const TRUNK_BRANCHES: [&str; 5] = ["main", "master", "develop", "dev", "trunk"];
+
+/// 任务建议设置（仅本机）
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct GitSuggestSettings {
+    pub enabled: bool,
+    /// 仓库目录（工作区内任意路径均可）
+    pub repo_path: String,
+}
+
+impl GitSuggestSettings {
+    pub fn is_active(&self) -> bool {
+        self.enabled && !self.repo_path.trim().is_empty()
+    }
+}
+
+/// 建议缓存：记住上次读取的仓库、时间与结果
+#[derive(Default)]
+pub struct GitSuggester {
+    cached: Option<(String, Instant, Vec<String>)>,
+}
+
+impl GitSuggester {
+    /// 当前建议（分支名在前、最近提交说明在后，已去重）；未开启时为空
+    pub fn suggestions(&mut self, settings: &GitSuggestSettings) -> &[String] {
+        if !settings.is_active() {
+            self.cached = None;
+            return &[];
+        }
+        let repo = settings.repo_path.trim();
+        let stale = self
+            .cached
+            .as_ref()
+            .is_none_or(|(path, at, _)| path != repo || at.elapsed() >= REFRESH_INTERVAL);
+        if stale {
+            self.cached = Some((repo.to_string(), Instant::now(), read_suggestions(repo)));
+        }
+        self.cached.as_ref().map(|(_, _, s)| s.as_slice()).unwrap_or(&[])
+    }
+}
+
+/// 读取分支名与最近一次提交说明（第一行）
+fn read_suggestions(repo: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    // 分离 HEAD 时 symbolic-ref 失败，只给提交说明
+    if let Some(branch) = git(repo, &["symbolic-ref", "--short", "-q", "HEAD"]) {
+        if !TRUNK_BRANCHES.contains(&branch.as_str()) {
+            out.push(branch);
+        }
+    }
+    if let Some(subject) = git(repo, &["log", "-1", "--format=%s"]) {
+        if !out.contains(&subject) {
+            out.push(subject);
+        }
+    }
+    out
+}
+
+fn git(repo: &str, args: &[&str]) -> Option<String> {
+    let mut cmd = std::process::Command::new("git");
+    // 与日记路径一致，开头的 ~ 展开为用户主目录
+    let dir = match repo.strip_prefix("~/").zip(dirs::home_dir()) {
+        Some((rest, home)) => home.join(rest),
+        None => std::path::PathBuf::from(repo),
+    };
+    cmd.arg("-C").arg(dir).args(args);
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    }
+    let out = cmd.output().ok()?;
+    if !out.status.success() {
+        return None;
+    }
+    let text = String::from_utf8_lossy(&out.stdout).trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
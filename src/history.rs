@@ -0,0 +1,135 @@
+//! 专注历史内存缓存：按天窗口 + 条数上限淘汰，避免长期运行（钉住数月）时历史无限增长
+
+use chrono::{DateTime, Duration, FixedOffset};
+use serde::{Deserialize, Serialize};
+
+/// 内存中保留最近多少天的记录（更早的仍在 SQLite，不受影响）
+pub const HISTORY_WINDOW_DAYS: i64 = 90;
+/// 内存中最多保留的记录条数（窗口内记录过多时按时间淘汰最旧的）
+pub const HISTORY_MAX_RECORDS: usize = 5000;
+
+/// 单条专注记录：用于按时间统计做了哪些任务（与 SQLite focus_records 表一致）
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FocusRecord {
+    pub task: String,
+    pub duration_secs: i64,
+    /// 完成时间 ISO 8601
+    pub completed_at: String,
+    /// 完成时的番茄数（本周期内）
+    pub completed_pomodoros: u32,
+    /// 开始专注时写下的承诺（专注契约），旧记录为空
+    #[serde(default)]
+    pub commitment: String,
+    /// 本次专注的暂停次数与累计暂停秒数，旧记录为 0
+    #[serde(default)]
+    pub pauses: crate::pomodoro::PauseStats,
+    /// 所属项目 id，旧记录为「未分类」
+    #[serde(default = "default_project_id")]
+    pub project_id: i64,
+    /// 加时模式下专注时间到后继续的秒数（已计入 duration_secs），旧记录为 0
+    #[serde(default)]
+    pub overtime_secs: i64,
+}
+
+fn default_project_id() -> i64 {
+    crate::db::UNCATEGORIZED_PROJECT_ID
+}
+
+impl From<crate::db::FocusRow> for FocusRecord {
+    fn from(r: crate::db::FocusRow) -> Self {
+        Self {
+            task: r.task,
+            duration_secs: r.duration_secs,
+            completed_at: r.completed_at,
+            completed_pomodoros: r.completed_pomodoros,
+            commitment: r.commitment,
+            pauses: r.pauses,
+            project_id: r.project_id,
+            overtime_secs: r.overtime_secs,
+        }
+    }
+}
+
+/// 专注历史缓存：记录按完成时间倒序（最新在前），超出窗口或上限的从尾部淘汰
+pub struct HistoryCache {
+    records: Vec<FocusRecord>,
+    window_days: i64,
+    max_records: usize,
+}
+
+impl Default for HistoryCache {
+    fn default() -> Self {
+        Self::new(HISTORY_WINDOW_DAYS, HISTORY_MAX_RECORDS)
+    }
+}
+
+impl HistoryCache {
+    pub fn new(window_days: i64, max_records: usize) -> Self {
+        Self {
+            records: Vec::new(),
+            window_days,
+            max_records,
+        }
+    }
+
+    pub fn window_days(&self) -> i64 {
+        self.window_days
+    }
+
+    pub fn max_records(&self) -> usize {
+        self.max_records
+    }
+
+    pub fn records(&self) -> &[FocusRecord] {
+        &self.records
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// 窗口起点（RFC3339，与 completed_at 同为北京时区，可直接按字符串比较）
+    pub fn window_start(&self, now: DateTime<FixedOffset>) -> String {
+        (now - Duration::days(self.window_days)).to_rfc3339()
+    }
+
+    /// 整体替换（从 SQLite 重新加载时用），替换后立即按窗口与上限淘汰
+    pub fn replace(&mut self, mut records: Vec<FocusRecord>, now: DateTime<FixedOffset>) {
+        records.sort_by(|a, b| b.completed_at.cmp(&a.completed_at));
+        self.records = records;
+        self.evict(now);
+    }
+
+    /// 新完成一条记录：插到最前并淘汰
+    pub fn push_front(&mut self, record: FocusRecord, now: DateTime<FixedOffset>) {
+        self.records.insert(0, record);
+        self.evict(now);
+    }
+
+    /// 推迟休息后再专注的秒数并入某条记录（按任务 + 完成时间查找），完成时间改为新的时间
+    pub fn extend(&mut self, task: &str, completed_at: &str, add_secs: i64, completed_at_new: &str) {
+        if let Some(r) = self.records.iter_mut().find(|r| r.task == task && r.completed_at == completed_at) {
+            r.duration_secs += add_secs;
+            r.completed_at = completed_at_new.to_string();
+        }
+        self.records.sort_by(|a, b| b.completed_at.cmp(&a.completed_at));
+    }
+
+    /// 显式淘汰：先去掉窗口外的旧记录，再截断到上限。返回淘汰条数
+    ///
+    /// 记录按时间倒序存放，只需从尾部弹出，常态下每帧调用也只比较一条。
+    pub fn evict(&mut self, now: DateTime<FixedOffset>) -> usize {
+        let before = self.records.len();
+        let cutoff = now - Duration::days(self.window_days);
+        while let Some(oldest) = self.records.last() {
+            match DateTime::parse_from_rfc3339(&oldest.completed_at) {
+                Ok(t) if t < cutoff => {
+                    self.records.pop();
+                }
+                _ => break,
+            }
+        }
+        self.records.truncate(self.max_records);
+        before - self.records.len()
+    }
+}
@@ -0,0 +1,338 @@
+//! 命令行批量导入：`red-tomato import --csv file.csv --map task=2,duration=3,time=1`
+//!
+//! 把其他工具导出的历史记录写入 focus_records。逐行校验（列是否存在、时长与时间能否解析、是否已导入过），
+//! 无效行跳过并报告；`--dry-run` 只校验不写入。全部写入在一个事务中完成。
+
+use chrono::{DateTime, FixedOffset, NaiveDateTime, TimeZone};
+
+use crate::history::FocusRecord;
+
+/// 每处理多少行输出一次进度
+const PROGRESS_EVERY: usize = 500;
+/// 最多逐条打印多少个错误（其余只计数）
+const MAX_REPORTED_ERRORS: usize = 20;
+/// 数据库已加密时提供口令的环境变量
+const PASSPHRASE_ENV: &str = "RED_TOMATO_PASSPHRASE";
+/// 单条记录时长上限（超过视为数据错误）
+const MAX_DURATION_SECS: i64 = 24 * 3600;
+
+const USAGE: &str = "用法：red-tomato import --csv <文件> --map task=<列>,duration=<列>,time=<列>[,pomodoros=<列>]
+                    [--header] [--duration-unit m|s] [--dry-run]
+
+  --csv            CSV 文件路径（UTF-8，逗号分隔，支持双引号）
+  --map            字段对应的列号（从 1 开始）；time 为完成时间
+  --header         跳过第一行表头
+  --duration-unit  纯数字时长的单位：m 分钟（默认）/ s 秒；也可直接写 25m、1500s、0:25:00
+  --dry-run        只校验并统计，不写入数据库
+
+数据库已加密时，通过环境变量 RED_TOMATO_PASSPHRASE 提供口令。
+
+时间格式：RFC3339（如 2024-05-12T14:25:00+08:00），或 2024-05-12 14:25[:00] / 2024/05/12 14:25（按北京时间）";
+
+/// 列映射（0 起的下标）
+struct ColumnMap {
+    task: usize,
+    duration: usize,
+    time: usize,
+    pomodoros: Option<usize>,
+}
+
+struct ImportOptions {
+    csv_path: String,
+    map: ColumnMap,
+    has_header: bool,
+    duration_in_secs: bool,
+    dry_run: bool,
+}
+
+/// 入口：args 为 `import` 之后的参数，返回进程退出码
+pub fn run(args: &[String]) -> i32 {
+    if args.iter().any(|a| a == "--help" || a == "-h") {
+        println!("{}", USAGE);
+        return 0;
+    }
+    let options = match parse_args(args) {
+        Ok(o) => o,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            return 2;
+        }
+    };
+    match import(&options) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("导入失败：{}", e);
+            1
+        }
+    }
+}
+
+fn parse_args(args: &[String]) -> Result<ImportOptions, String> {
+    let mut csv_path = None;
+    let mut map = None;
+    let mut has_header = false;
+    let mut duration_in_secs = false;
+    let mut dry_run = false;
+    let mut it = args.iter();
+    while let Some(arg) = it.next() {
+        match arg.as_str() {
+            "--csv" => csv_path = Some(it.next().ok_or("--csv 缺少文件路径")?.clone()),
+            "--map" => map = Some(parse_map(it.next().ok_or("--map 缺少列映射")?)?),
+            "--header" => has_header = true,
+            "--dry-run" => dry_run = true,
+            "--duration-unit" => {
+                duration_in_secs = match it.next().map(String::as_str) {
+                    Some("m") => false,
+                    Some("s") => true,
+                    _ => return Err("--duration-unit 只能是 m 或 s".to_string()),
+                }
+            }
+            other => return Err(format!("未知参数：{}", other)),
+        }
+    }
+    Ok(ImportOptions {
+        csv_path: csv_path.ok_or("缺少 --csv")?,
+        map: map.ok_or("缺少 --map")?,
+        has_header,
+        duration_in_secs,
+        dry_run,
+    })
+}
+
+/// 解析 `task=2,duration=3,time=1`
+fn parse_map(s: &str) -> Result<ColumnMap, String> {
+    let (mut task, mut duration, mut time, mut pomodoros) = (None, None, None, None);
+    for pair in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let (key, col) = pair.split_once('=').ok_or_else(|| format!("列映射格式错误：{}", pair))?;
+        let col: usize = col
+            .trim()
+            .parse()
+            .ok()
+            .filter(|c| *c >= 1)
+            .ok_or_else(|| format!("列号须为从 1 开始的整数：{}", pair))?;
+        let slot = match key.trim() {
+            "task" => &mut task,
+            "duration" => &mut duration,
+            "time" => &mut time,
+            "pomodoros" => &mut pomodoros,
+            other => return Err(format!("未知字段：{}（可用 task / duration / time / pomodoros）", other)),
+        };
+        *slot = Some(col - 1);
+    }
+    Ok(ColumnMap {
+        task: task.ok_or("--map 缺少 task")?,
+        duration: duration.ok_or("--map 缺少 duration")?,
+        time: time.ok_or("--map 缺少 time")?,
+        pomodoros,
+    })
+}
+
+fn import(options: &ImportOptions) -> Result<(), String> {
+    let text = std::fs::read_to_string(&options.csv_path).map_err(|e| format!("读取 {} 失败：{}", options.csv_path, e))?;
+    let mut records = parse_csv(text.trim_start_matches('\u{feff}'));
+    let first_line = if options.has_header && !records.is_empty() {
+        records.remove(0);
+        2
+    } else {
+        1
+    };
+    let total = records.len();
+    eprintln!("共 {} 行，开始校验…", total);
+
+    let mut conn = crate::db::open_and_init().map_err(|e| e.to_string())?;
+    if crate::crypto::is_locked() {
+        // 加密的数据库：口令从环境变量读取（命令行不回显输入不便，也避免留在 shell 历史中）
+        let passphrase = std::env::var(PASSPHRASE_ENV)
+            .map_err(|_| format!("数据库已加密，请通过环境变量 {} 提供口令", PASSPHRASE_ENV))?;
+        if !crate::db::unlock_encryption(&passphrase).map_err(|e| e.to_string())? {
+            return Err("口令不正确".to_string());
+        }
+    }
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut imported = 0;
+    let mut duplicates = 0;
+    let mut errors = 0;
+    for (i, fields) in records.iter().enumerate() {
+        let line = first_line + i;
+        match validate_row(fields, options) {
+            Ok(row) => {
+                if crate::db::focus_record_exists(&tx, &row.task, &row.completed_at).map_err(|e| e.to_string())? {
+                    duplicates += 1;
+                } else {
+                    if !options.dry_run {
+                        crate::db::insert_focus_record(&tx, &row)
+                            .map_err(|e| format!("第 {} 行写入失败：{}", line, e))?;
+                    }
+                    imported += 1;
+                }
+            }
+            Err(e) => {
+                errors += 1;
+                if errors <= MAX_REPORTED_ERRORS {
+                    eprintln!("第 {} 行：{}", line, e);
+                }
+            }
+        }
+        if (i + 1) % PROGRESS_EVERY == 0 {
+            eprintln!("已处理 {}/{} 行", i + 1, total);
+        }
+    }
+    if errors > MAX_REPORTED_ERRORS {
+        eprintln!("……另有 {} 行错误未列出", errors - MAX_REPORTED_ERRORS);
+    }
+    if options.dry_run {
+        println!("试运行：可导入 {} 条，已存在 {} 条，无效 {} 条（未写入）", imported, duplicates, errors);
+        return Ok(());
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    println!("已导入 {} 条，跳过已存在 {} 条，无效 {} 条", imported, duplicates, errors);
+    Ok(())
+}
+
+fn validate_row(fields: &[String], options: &ImportOptions) -> Result<FocusRecord, String> {
+    let map = &options.map;
+    let field = |col: usize, name: &str| {
+        fields
+            .get(col)
+            .map(|s| s.trim())
+            .ok_or_else(|| format!("缺少第 {} 列（{}）", col + 1, name))
+    };
+    let task = field(map.task, "task")?.to_string();
+    let duration_text = field(map.duration, "duration")?;
+    let duration_secs = parse_duration(duration_text, options.duration_in_secs)
+        .ok_or_else(|| format!("无法解析时长：{:?}", duration_text))?;
+    if duration_secs <= 0 || duration_secs > MAX_DURATION_SECS {
+        return Err(format!("时长超出范围：{:?}", duration_text));
+    }
+    let time_text = field(map.time, "time")?;
+    let completed_at = parse_time(time_text).ok_or_else(|| format!("无法解析时间：{:?}", time_text))?;
+    let completed_pomodoros = match map.pomodoros {
+        Some(col) => {
+            let text = field(col, "pomodoros")?;
+            text.parse().map_err(|_| format!("番茄数不是整数：{:?}", text))?
+        }
+        None => 0,
+    };
+    // 导入的记录没有承诺、暂停与加时信息，归入「未分类」项目
+    Ok(FocusRecord {
+        task,
+        duration_secs,
+        completed_at: completed_at.to_rfc3339(),
+        completed_pomodoros,
+        commitment: String::new(),
+        pauses: Default::default(),
+        project_id: crate::db::UNCATEGORIZED_PROJECT_ID,
+        overtime_secs: 0,
+    })
+}
+
+/// 时长：`25m` / `1500s` / `H:MM:SS` / `MM:SS` / 纯数字（按 --duration-unit）
+fn parse_duration(s: &str, plain_in_secs: bool) -> Option<i64> {
+    let s = s.trim().to_lowercase();
+    if let Some(m) = s.strip_suffix("min").or_else(|| s.strip_suffix('m')) {
+        return Some((m.trim().parse::<f64>().ok()? * 60.0).round() as i64);
+    }
+    if let Some(sec) = s.strip_suffix('s') {
+        return sec.trim().parse().ok();
+    }
+    if s.contains(':') {
+        let parts: Vec<i64> = s.split(':').map(|p| p.trim().parse().ok()).collect::<Option<_>>()?;
+        return match parts.as_slice() {
+            [m, sec] => Some(m * 60 + sec),
+            [h, m, sec] => Some(h * 3600 + m * 60 + sec),
+            _ => None,
+        };
+    }
+    let n: f64 = s.parse().ok()?;
+    Some(if plain_in_secs { n.round() as i64 } else { (n * 60.0).round() as i64 })
+}
+
+/// 完成时间统一转成北京时间（与应用内记录一致，便于按字符串比较）
+fn parse_time(s: &str) -> Option<DateTime<FixedOffset>> {
+    let beijing = crate::app::beijing();
+    if let Ok(t) = DateTime::parse_from_rfc3339(s) {
+        return Some(t.with_timezone(&beijing));
+    }
+    const FORMATS: [&str; 4] = ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y/%m/%d %H:%M:%S", "%Y/%m/%d %H:%M"];
+    FORMATS
+        .iter()
+        .find_map(|f| NaiveDateTime::parse_from_str(s, f).ok())
+        .and_then(|naive| beijing.from_local_datetime(&naive).single())
+}
+
+/// 最小 CSV 解析：逗号分隔，双引号包裹的字段可含逗号、换行与 `""` 转义；跳过空行
+fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => row.push(std::mem::take(&mut field)),
+            '\r' if !in_quotes => {}
+            '\n' if !in_quotes => {
+                row.push(std::mem::take(&mut field));
+                if row.iter().any(|f| !f.trim().is_empty()) {
+                    rows.push(std::mem::take(&mut row));
+                } else {
+                    row.clear();
+                }
+            }
+            _ => field.push(c),
+        }
+    }
+    row.push(field);
+    if row.iter().any(|f| !f.trim().is_empty()) {
+        rows.push(row);
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("25m", false), Some(1500));
+        assert_eq!(parse_duration(" 1.5 min ", false), Some(90));
+        assert_eq!(parse_duration("1500S", false), Some(1500));
+        assert_eq!(parse_duration("0:25:00", false), Some(1500));
+        assert_eq!(parse_duration("25:30", false), Some(1530));
+        assert_eq!(parse_duration("25", false), Some(1500));
+        assert_eq!(parse_duration("25", true), Some(25));
+    }
+
+    #[test]
+    fn rejects_bad_durations() {
+        assert_eq!(parse_duration("", false), None);
+        assert_eq!(parse_duration("abc", false), None);
+        assert_eq!(parse_duration("xm", false), None);
+        assert_eq!(parse_duration("1:2:3:4", false), None);
+        assert_eq!(parse_duration("1::2", false), None);
+    }
+
+    #[test]
+    fn parses_plain_csv() {
+        let rows = parse_csv("a,b,c\r\n1,2,3\n");
+        assert_eq!(rows, vec![vec!["a", "b", "c"], vec!["1", "2", "3"]]);
+    }
+
+    #[test]
+    fn parses_quoted_fields() {
+        let rows = parse_csv("\"写周报, v2\",\"他说\"\"好\"\"\",\"两\n行\"\nx,,y");
+        assert_eq!(rows, vec![vec!["写周报, v2", "他说\"好\"", "两\n行"], vec!["x", "", "y"]]);
+    }
+
+    #[test]
+    fn skips_blank_lines() {
+        let rows = parse_csv("\n1,2\n\n , \n3,4");
+        assert_eq!(rows, vec![vec!["1", "2"], vec!["3", "4"]]);
+    }
+}
@@ -0,0 +1,21 @@
+//! 微日志：每日回顾时轮换的反思提问（回答存入 SQLite journal_entries，可搜索）
+
+use chrono::{Datelike, NaiveDate};
+
+/// 内置反思提问，按日期轮换
+pub const PROMPTS: &[&str] = &[
+    "今天最大的干扰是什么？",
+    "今天哪一个番茄最投入？为什么？",
+    "明天最重要的一件事是什么？",
+    "今天有什么事情比预想的更花时间？",
+    "如果重来一次，今天会先做哪件事？",
+    "今天学到了什么？",
+    "有什么可以交给别人或直接不做？",
+    "今天的精力在什么时候最好？",
+];
+
+/// 某天的提问：按一年中的第几天轮换，offset 用于「换一个」
+pub fn prompt_for(day: NaiveDate, offset: usize) -> &'static str {
+    let idx = (day.ordinal0() as usize + offset) % PROMPTS.len();
+    PROMPTS[idx]
+}
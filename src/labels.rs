@@ -0,0 +1,120 @@
+//! 任务与项目的颜色标签：主界面、钉住模式、历史记录与按任务的图表中用小色块标出任务
+//!
+//! 任务颜色优先取任务自己的，其次取所属项目的；都没有指定时按任务名生成一个固定的颜色，同一任务在各处颜色一致。
+
+use std::collections::HashMap;
+
+use eframe::egui;
+use egui::Color32;
+
+/// 可选的颜色（菜单中的色块）
+pub const PRESETS: [Color32; 10] = [
+    Color32::from_rgb(0xE5, 0x4B, 0x4B),
+    Color32::from_rgb(0xF0, 0x8A, 0x24),
+    Color32::from_rgb(0xE8, 0xC2, 0x2E),
+    Color32::from_rgb(0x6D, 0xB3, 0x3F),
+    Color32::from_rgb(0x2E, 0xA8, 0x8F),
+    Color32::from_rgb(0x3A, 0x9A, 0xD9),
+    Color32::from_rgb(0x4C, 0x5F, 0xD6),
+    Color32::from_rgb(0x8E, 0x5C, 0xD9),
+    Color32::from_rgb(0xD9, 0x5C, 0xA8),
+    Color32::from_rgb(0x8A, 0x8A, 0x8A),
+];
+
+/// "#rrggbb"
+pub fn to_hex(color: Color32) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r(), color.g(), color.b())
+}
+
+/// 解析 "#rrggbb"；格式不对时为 None
+pub fn from_hex(s: &str) -> Option<Color32> {
+    let hex = s.strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+    Some(Color32::from_rgb(channel(0)?, channel(2)?, channel(4)?))
+}
+
+/// 未指定颜色时按名称生成：名称哈希决定色相，饱和度与亮度固定，深浅主题下都看得清
+pub fn generated(name: &str) -> Color32 {
+    // FNV-1a，不随运行变化（std 的 HashMap 哈希带随机种子）
+    let hash = name.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |h, b| (h ^ b as u64).wrapping_mul(0x0100_0000_01b3));
+    let hue = (hash % 360) as f32 / 360.0;
+    egui::ecolor::Hsva::new(hue, 0.55, 0.85, 1.0).into()
+}
+
+/// 已指定的任务与项目颜色
+#[derive(Default)]
+pub struct TaskColors {
+    tasks: HashMap<String, Color32>,
+    projects: HashMap<i64, Color32>,
+}
+
+impl TaskColors {
+    pub fn load(conn: &rusqlite::Connection) -> Result<Self, rusqlite::Error> {
+        let tasks = crate::db::load_task_colors(conn)?
+            .into_iter()
+            .filter_map(|(task, color)| Some((task, from_hex(&color)?)))
+            .collect();
+        let projects = crate::db::load_projects(conn)?
+            .into_iter()
+            .filter_map(|p| Some((p.id, from_hex(&p.color)?)))
+            .collect();
+        Ok(Self { tasks, projects })
+    }
+
+    pub fn task(&self, task: &str) -> Option<Color32> {
+        self.tasks.get(task).copied()
+    }
+
+    pub fn project(&self, id: i64) -> Option<Color32> {
+        self.projects.get(&id).copied()
+    }
+
+    /// 任务显示用的颜色：任务的 → 项目的（已知所属项目时）→ 按任务名生成
+    pub fn color(&self, task: &str, project_id: Option<i64>) -> Color32 {
+        self.task(task)
+            .or_else(|| project_id.and_then(|id| self.project(id)))
+            .unwrap_or_else(|| generated(task))
+    }
+
+    /// 记下新设置的任务颜色（None 为清除），与写入数据库同时调用，免得重新加载
+    pub fn set_task(&mut self, task: &str, color: Option<Color32>) {
+        match color {
+            Some(color) => self.tasks.insert(task.to_string(), color),
+            None => self.tasks.remove(task),
+        };
+    }
+}
+
+/// 画一个小色块
+pub fn swatch(ui: &mut egui::Ui, color: Color32) -> egui::Response {
+    let (rect, response) = ui.allocate_exact_size(egui::vec2(10.0, 10.0), egui::Sense::hover());
+    ui.painter().rect_filled(rect, 2.0, color);
+    response
+}
+
+/// 色块按钮，点开后选颜色；返回 Some(Some(颜色)) 为选中，Some(None) 为「自动」（清除指定）
+pub fn color_menu(ui: &mut egui::Ui, current: Color32, hover: &str) -> Option<Option<Color32>> {
+    let mut picked = None;
+    ui.menu_button(egui::RichText::new("■").color(current), |ui| {
+        ui.horizontal(|ui| {
+            for color in PRESETS {
+                let button = egui::Button::new(egui::RichText::new("■").color(color).size(18.0)).frame(false);
+                if ui.add(button).on_hover_text(to_hex(color)).clicked() {
+                    picked = Some(Some(color));
+                }
+            }
+        });
+        if ui.button("自动").on_hover_text("按名称生成颜色").clicked() {
+            picked = Some(None);
+        }
+        if picked.is_some() {
+            ui.close();
+        }
+    })
+    .response
+    .on_hover_text(hover);
+    picked
+}
@@ -0,0 +1,189 @@
+//! Linux 上的钉住小窗：让紧凑模式在各桌面环境下都能固定在右上角并置顶
+//!
+//! X11：通过 EWMH 设置置顶、所有工作区可见、不占任务栏（`_NET_WM_STATE`），右上角按 RandR 主显示器与
+//! `_NET_WORKAREA` 的交集计算，避开顶部面板；不依赖窗口库给出的显示器尺寸。
+//!
+//! Wayland：xdg-shell 不允许程序摆放窗口或置顶，也拿不到显示器尺寸。wlr-layer-shell 需要另建一个图层表面，
+//! 而 eframe（winit）创建的窗口已是普通顶层窗口，无法改成图层表面，所以没有实现图层表面。
+//! Wayland 会话下只要有 XWayland（`DISPLAY` 可用）就在启动前自动改用 XWayland 运行，钉住走 X11 的路径；
+//! config.toml 中设 `[linux] x11 = false` 可保持原生 Wayland，此时位置与层级交给合成器（可用合成器的窗口规则固定）。
+
+use eframe::egui;
+
+/// 窗口所在的显示会话
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Session {
+    /// X11（含 XWayland），附窗口 id
+    X11(u32),
+    Wayland,
+    /// 其他平台
+    Other,
+}
+
+pub fn session(frame: &eframe::Frame) -> Session {
+    use raw_window_handle::{HasWindowHandle, RawWindowHandle};
+    let Ok(handle) = frame.window_handle() else { return Session::Other };
+    match handle.as_raw() {
+        RawWindowHandle::Xlib(h) => Session::X11(h.window as u32),
+        RawWindowHandle::Xcb(h) => Session::X11(h.window.get()),
+        RawWindowHandle::Wayland(_) => Session::Wayland,
+        _ => Session::Other,
+    }
+}
+
+/// 钉住状态同步到窗口管理器（仅 X11 有效）
+#[derive(Default)]
+pub struct LinuxPin {
+    /// 已设置到窗口上的钉住状态
+    applied: Option<bool>,
+}
+
+impl LinuxPin {
+    /// 每帧调用；钉住状态变化时设置或清除窗口的 EWMH 状态，失败时记日志且不再重试
+    pub fn sync(&mut self, frame: &eframe::Frame, pinned: bool) {
+        if self.applied == Some(pinned) {
+            return;
+        }
+        match session(frame) {
+            Session::X11(window) => {
+                if let Err(e) = x11::set_pinned(window, pinned) {
+                    tracing::warn!("设置 X11 窗口置顶状态失败：{}", e);
+                }
+                self.applied = Some(pinned);
+            }
+            Session::Wayland | Session::Other => self.applied = Some(pinned),
+        }
+    }
+}
+
+/// X11 下主显示器去掉面板后的可用区域（逻辑点）；非 X11 或取不到时为 None
+pub fn work_area(frame: &eframe::Frame, pixels_per_point: f32) -> Option<egui::Rect> {
+    let Session::X11(_) = session(frame) else { return None };
+    match x11::work_area() {
+        Ok(area) => Some(egui::Rect::from_min_max(
+            (area.min.to_vec2() / pixels_per_point).to_pos2(),
+            (area.max.to_vec2() / pixels_per_point).to_pos2(),
+        )),
+        Err(e) => {
+            tracing::warn!("读取 X11 显示器可用区域失败：{}", e);
+            None
+        }
+    }
+}
+
+/// Wayland 会话下有 XWayland 时，在创建窗口前去掉 WAYLAND_DISPLAY，winit 随之选择 X11。
+/// `setting` 为 config.toml 的 `[linux] x11`：未设置时自动改用，`false` 时保持 Wayland
+pub fn prefer_x11(setting: Option<bool>) {
+    if setting == Some(false) || std::env::var_os("WAYLAND_DISPLAY").is_none() {
+        return;
+    }
+    if std::env::var_os("DISPLAY").is_none() {
+        if setting == Some(true) {
+            tracing::warn!("config.toml 要求改用 XWayland，但没有可用的 X 显示（DISPLAY 未设置），仍以 Wayland 运行");
+        } else {
+            tracing::info!("没有可用的 XWayland（DISPLAY 未设置），以 Wayland 运行，钉住的小窗位置交给合成器");
+        }
+        return;
+    }
+    tracing::info!("Wayland 会话下改用 XWayland 运行，使钉住的小窗能固定在右上角并置顶");
+    // SAFETY: 在 main 中创建窗口之前调用，此时只有日志写入线程，它不读写环境变量
+    unsafe { std::env::remove_var("WAYLAND_DISPLAY") };
+}
+
+#[cfg(target_os = "linux")]
+mod x11 {
+    use eframe::egui;
+    use x11rb::connection::Connection;
+    use x11rb::protocol::randr::ConnectionExt as _;
+    use x11rb::protocol::xproto::{AtomEnum, ClientMessageEvent, ConnectionExt as _, EventMask, Window};
+
+    type Error = Box<dyn std::error::Error>;
+
+    /// `_NET_WM_STATE` 客户端消息的动作
+    const STATE_REMOVE: u32 = 0;
+    const STATE_ADD: u32 = 1;
+    /// 来源为普通应用
+    const SOURCE_APPLICATION: u32 = 1;
+
+    fn atom(conn: &impl Connection, name: &str) -> Result<u32, Error> {
+        Ok(conn.intern_atom(false, name.as_bytes())?.reply()?.atom)
+    }
+
+    /// 请求窗口管理器添加或去掉置顶、所有工作区可见与不占任务栏
+    pub fn set_pinned(window: Window, pinned: bool) -> Result<(), Error> {
+        let (conn, screen) = x11rb::connect(None)?;
+        let root = conn.setup().roots[screen].root;
+        let wm_state = atom(&conn, "_NET_WM_STATE")?;
+        let action = if pinned { STATE_ADD } else { STATE_REMOVE };
+        // 每条消息最多带两个状态
+        let pairs = [("_NET_WM_STATE_ABOVE", "_NET_WM_STATE_STICKY"), ("_NET_WM_STATE_SKIP_TASKBAR", "_NET_WM_STATE_SKIP_PAGER")];
+        for (first, second) in pairs {
+            let data = [action, atom(&conn, first)?, atom(&conn, second)?, SOURCE_APPLICATION, 0];
+            let event = ClientMessageEvent::new(32, window, wm_state, data);
+            conn.send_event(false, root, EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY, event)?;
+        }
+        conn.flush()?;
+        Ok(())
+    }
+
+    /// 主显示器（RandR；没有时为整个屏幕）与当前工作区 `_NET_WORKAREA` 的交集，物理像素
+    pub fn work_area() -> Result<egui::Rect, Error> {
+        let (conn, screen) = x11rb::connect(None)?;
+        let info = &conn.setup().roots[screen];
+        let root = info.root;
+        let full = egui::Rect::from_min_size(
+            egui::Pos2::ZERO,
+            egui::vec2(info.width_in_pixels as f32, info.height_in_pixels as f32),
+        );
+        let monitor = conn
+            .randr_get_monitors(root, true)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .and_then(|reply| {
+                let monitors = reply.monitors;
+                let primary = monitors.iter().find(|m| m.primary).or(monitors.first())?;
+                Some(egui::Rect::from_min_size(
+                    egui::pos2(primary.x as f32, primary.y as f32),
+                    egui::vec2(primary.width as f32, primary.height as f32),
+                ))
+            })
+            .unwrap_or(full);
+        // _NET_WORKAREA 每个工作区 4 个值（x, y, 宽, 高），跨所有显示器；取当前工作区
+        let desktop = conn
+            .get_property(false, root, atom(&conn, "_NET_CURRENT_DESKTOP")?, AtomEnum::CARDINAL, 0, 1)?
+            .reply()?
+            .value32()
+            .and_then(|mut v| v.next())
+            .unwrap_or(0);
+        let workarea: Vec<u32> = conn
+            .get_property(false, root, atom(&conn, "_NET_WORKAREA")?, AtomEnum::CARDINAL, desktop * 4, 4)?
+            .reply()?
+            .value32()
+            .map(|v| v.collect())
+            .unwrap_or_default();
+        let area = match workarea[..] {
+            [x, y, w, h] => monitor.intersect(egui::Rect::from_min_size(
+                egui::pos2(x as f32, y as f32),
+                egui::vec2(w as f32, h as f32),
+            )),
+            _ => monitor,
+        };
+        if area.width() <= 1.0 || area.height() <= 1.0 {
+            return Err("可用区域为空".into());
+        }
+        Ok(area)
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod x11 {
+    use eframe::egui;
+
+    pub fn set_pinned(_window: u32, _pinned: bool) -> Result<(), String> {
+        Ok(())
+    }
+
+    pub fn work_area() -> Result<egui::Rect, String> {
+        Err("非 X11 平台".to_string())
+    }
+}
@@ -0,0 +1,80 @@
+//! 运行日志：数据目录下 `logs/` 中按天滚动的文本日志，保留最近几天
+//!
+//! 记录阶段切换、窗口钉住 / 恢复位置、数据库写入失败（不中断界面，原先静默忽略）与集成失败，
+//! 便于排查无法接触到的用户电脑上的问题。「关于」中可打开日志文件夹。
+//! 默认 info 级别，环境变量 `RED_TOMATO_LOG=debug` 时记录更多细节。
+
+use std::path::PathBuf;
+
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{Builder, Rotation};
+
+pub const LOG_DIRNAME: &str = "logs";
+const LOG_PREFIX: &str = "red-tomato";
+const LOG_SUFFIX: &str = "log";
+/// 保留的日志文件数（每天一个）
+const MAX_LOG_FILES: usize = 7;
+const LEVEL_ENV: &str = "RED_TOMATO_LOG";
+
+pub fn log_dir() -> PathBuf {
+    crate::db::data_dir().join(LOG_DIRNAME)
+}
+
+/// 安装全局日志；返回的 guard 需保持到退出（后台线程写文件，drop 时写完剩余内容）。
+/// 日志目录无法创建时不记录日志，不影响启动
+pub fn init() -> Option<WorkerGuard> {
+    let appender = Builder::new()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_PREFIX)
+        .filename_suffix(LOG_SUFFIX)
+        .max_log_files(MAX_LOG_FILES)
+        .build(log_dir())
+        .ok()?;
+    let (writer, guard) = tracing_appender::non_blocking(appender);
+    let level = match std::env::var(LEVEL_ENV).as_deref() {
+        Ok("debug") => tracing::Level::DEBUG,
+        Ok("trace") => tracing::Level::TRACE,
+        _ => tracing::Level::INFO,
+    };
+    tracing_subscriber::fmt()
+        .with_writer(writer)
+        .with_ansi(false)
+        .with_target(false)
+        .with_max_level(level)
+        .try_init()
+        .ok()?;
+    tracing::info!("启动 {}，数据目录 {}", env!("CARGO_PKG_VERSION"), crate::db::data_dir().display());
+    Some(guard)
+}
+
+/// 不中断界面的操作失败时记入日志（代替 `let _ =`）
+pub trait LogErr<T> {
+    /// what 为操作名，如「写入专注记录」；失败时返回 None
+    fn log_err(self, what: &str) -> Option<T>;
+}
+
+impl<T, E: std::fmt::Display> LogErr<T> for Result<T, E> {
+    fn log_err(self, what: &str) -> Option<T> {
+        match self {
+            Ok(v) => Some(v),
+            Err(e) => {
+                tracing::warn!("{}失败：{}", what, e);
+                None
+            }
+        }
+    }
+}
+
+/// 用系统文件管理器打开日志文件夹
+pub fn open_log_dir() -> std::io::Result<()> {
+    let dir = log_dir();
+    std::fs::create_dir_all(&dir)?;
+    #[cfg(windows)]
+    let program = "explorer";
+    #[cfg(target_os = "macos")]
+    let program = "open";
+    #[cfg(not(any(windows, target_os = "macos")))]
+    let program = "xdg-open";
+    std::process::Command::new(program).arg(&dir).spawn()?;
+    Ok(())
+}
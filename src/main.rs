@@ -3,9 +3,67 @@
 // 使用 Windows 图形子系统，运行时不弹出黑色控制台窗口
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod a11y;
+mod achievements;
 mod app;
+mod audit;
+mod bar;
+mod breathing;
+mod config;
+mod crypto;
+mod daily_note;
+mod data_sync;
 mod db;
+mod deeplink;
+mod distraction;
+mod energy;
+mod errors;
+mod eye_rest;
+mod fonts;
+mod git_suggest;
+mod history;
+mod import;
+mod journal;
+mod labels;
+mod linux_pin;
+mod logging;
+mod media;
+mod merge;
+mod metronome;
+mod motion;
+mod mqtt;
+mod notify;
+mod obs;
+mod planner;
+mod policy;
 mod pomodoro;
+mod presence;
+mod profile;
+mod purge;
+mod push_actions;
+mod quotes;
+mod relocate;
+mod rest_guard;
+mod review;
+mod ring;
+#[cfg(feature = "http-api")]
+mod server;
+mod session;
+mod side_timer;
+mod share_card;
+mod slack;
+mod stats;
+mod suspend;
+mod sync;
+mod taskbar;
+mod theme;
+mod tray;
+mod undo;
+mod wal;
+mod wellness;
+
+/// 便携模式下 eframe 界面状态文件名（与 eframe 默认的文件名一致）
+const PORTABLE_STORAGE_FILENAME: &str = "app.ron";
 
 /// 生成应用图标：番茄红圆形，透明背景（48×48，任务栏/窗口更清晰）
 fn make_app_icon() -> egui::IconData {
@@ -39,14 +97,55 @@ fn make_app_icon() -> egui::IconData {
     }
 }
 
+/// Windows 发布版使用图形子系统，命令行子命令需挂到父进程控制台才能看到输出
+#[cfg(windows)]
+fn attach_parent_console() {
+    use windows_sys::Win32::System::Console::{ATTACH_PARENT_PROCESS, AttachConsole};
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(windows))]
+fn attach_parent_console() {}
+
 fn main() -> eframe::Result<()> {
+    // 便携模式：数据库与界面状态都放在可执行文件旁，需在任何读写数据之前确定
+    let mut args: Vec<String> = std::env::args().collect();
+    let portable = args.iter().any(|a| a == "--portable");
+    args.retain(|a| a != "--portable");
+    db::init_portable(portable);
+    // 命令行子命令：red-tomato import ...（不启动界面）
+    if args.get(1).map(String::as_str) == Some("import") {
+        attach_parent_console();
+        std::process::exit(import::run(&args[2..]));
+    }
+    // 状态栏输出：red-tomato bar ...（不启动界面，读取界面写出的状态）
+    if args.get(1).map(String::as_str) == Some("bar") {
+        attach_parent_console();
+        std::process::exit(bar::run(&args[2..]));
+    }
+    // 日志写在数据目录下，guard 保持到退出
+    let _log_guard = logging::init();
+    // redtomato:// 链接：转给运行中的实例后退出；没有实例在运行时启动界面，首帧执行
+    if let Some(url) = args.get(1).filter(|a| deeplink::is_link(a)) {
+        match deeplink::parse(url) {
+            Ok(_) if deeplink::forward(url) => return Ok(()),
+            Ok(link) => deeplink::queue_startup(link),
+            Err(e) => tracing::warn!("忽略无法解析的链接 {}：{}", url, e),
+        }
+    }
+    // Linux：Wayland 会话下默认改用 XWayland（config.toml 可关闭），需在创建窗口前
+    linux_pin::prefer_x11(config::load_at_startup().and_then(|c| c.linux.x11));
     let icon = make_app_icon();
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
-            .with_inner_size([380.0, 540.0])
+            .with_inner_size([380.0, 572.0])
             .with_title("红番茄")
             .with_decorations(false) // 无系统标题栏，与钉住模式一致，仅保留自定义顶栏
             .with_icon(icon),
+        // 便携模式下 eframe 的界面状态也存到可执行文件旁，不在本机用户目录留下数据
+        persistence_path: db::portable_dir().map(|dir| dir.join(PORTABLE_STORAGE_FILENAME)),
         ..Default::default()
     };
     eframe::run_native(
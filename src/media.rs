@@ -0,0 +1,136 @@
+//! 媒体自动暂停：专注开始时暂停正在播放的音乐 / 播客，进入休息时可自动恢复
+//!
+//! 只暂停开始专注时正在播放的播放器，恢复时也只恢复这些（用户在专注中自己暂停或关掉的不受影响）。
+//! 平台后端：Windows 用系统媒体传输控制（SMTC，`GlobalSystemMediaTransportControlsSessionManager`），
+//! Linux 用 `playerctl`（MPRIS）。控制在后台线程进行，不阻塞界面；其他平台上开启后不起作用。
+
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+
+/// 当前平台是否能控制媒体播放
+pub const SUPPORTED: bool = cfg!(any(windows, target_os = "linux"));
+
+/// 媒体自动暂停设置（仅本机）
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MediaSettings {
+    /// 专注开始时暂停正在播放的媒体
+    pub pause_on_focus: bool,
+    /// 进入休息时恢复专注开始时暂停的媒体
+    pub resume_on_break: bool,
+}
+
+/// 媒体控制：记住上一帧是否在专注，专注开始 / 进入休息时在后台线程暂停或恢复
+#[derive(Default)]
+pub struct MediaControl {
+    focusing: bool,
+    /// 由本程序暂停的播放器标识（SMTC 的应用 id / MPRIS 播放器名）
+    paused: Arc<Mutex<Vec<String>>>,
+}
+
+impl MediaControl {
+    /// 每帧调用；`focusing` 为专注计时正在运行，`on_break` 为当前处于休息阶段
+    pub fn sync(&mut self, settings: &MediaSettings, focusing: bool, on_break: bool) {
+        let started = focusing && !self.focusing;
+        self.focusing = focusing;
+        if !SUPPORTED {
+            return;
+        }
+        if started && settings.pause_on_focus {
+            let paused = Arc::clone(&self.paused);
+            std::thread::spawn(move || match backend::pause_playing() {
+                Ok(players) => paused.lock().unwrap().extend(players),
+                Err(e) => tracing::warn!("暂停媒体播放失败：{}", e),
+            });
+        } else if on_break && settings.resume_on_break && !self.paused.lock().unwrap().is_empty() {
+            let players = std::mem::take(&mut *self.paused.lock().unwrap());
+            std::thread::spawn(move || {
+                if let Err(e) = backend::resume(&players) {
+                    tracing::warn!("恢复媒体播放失败：{}", e);
+                }
+            });
+        }
+    }
+}
+
+#[cfg(windows)]
+mod backend {
+    use windows::Media::Control::{
+        GlobalSystemMediaTransportControlsSessionManager as SessionManager,
+        GlobalSystemMediaTransportControlsSessionPlaybackStatus as PlaybackStatus,
+    };
+
+    /// 暂停所有正在播放的会话，返回其应用 id
+    pub fn pause_playing() -> windows::core::Result<Vec<String>> {
+        let manager = SessionManager::RequestAsync()?.get()?;
+        let mut paused = Vec::new();
+        for session in manager.GetSessions()? {
+            if session.GetPlaybackInfo()?.PlaybackStatus()? != PlaybackStatus::Playing {
+                continue;
+            }
+            if session.TryPauseAsync()?.get()? {
+                paused.push(session.SourceAppUserModelId()?.to_string());
+            }
+        }
+        Ok(paused)
+    }
+
+    /// 恢复仍处于暂停的这些应用的会话
+    pub fn resume(players: &[String]) -> windows::core::Result<()> {
+        let manager = SessionManager::RequestAsync()?.get()?;
+        for session in manager.GetSessions()? {
+            let id = session.SourceAppUserModelId()?.to_string();
+            if players.contains(&id) && session.GetPlaybackInfo()?.PlaybackStatus()? == PlaybackStatus::Paused {
+                session.TryPlayAsync()?.get()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod backend {
+    use std::process::Command;
+
+    /// 运行 playerctl 并取标准输出
+    fn playerctl(args: &[&str]) -> Result<String, String> {
+        let output = Command::new("playerctl")
+            .args(args)
+            .output()
+            .map_err(|e| format!("无法运行 playerctl：{}", e))?;
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    /// 暂停所有正在播放的 MPRIS 播放器，返回其名称
+    pub fn pause_playing() -> Result<Vec<String>, String> {
+        let mut paused = Vec::new();
+        for player in playerctl(&["--list-all"])?.lines().map(str::trim).filter(|p| !p.is_empty()) {
+            if playerctl(&["--player", player, "status"])?.trim() == "Playing" {
+                playerctl(&["--player", player, "pause"])?;
+                paused.push(player.to_string());
+            }
+        }
+        Ok(paused)
+    }
+
+    /// 恢复仍处于暂停的这些播放器
+    pub fn resume(players: &[String]) -> Result<(), String> {
+        for player in players {
+            if playerctl(&["--player", player, "status"])?.trim() == "Paused" {
+                playerctl(&["--player", player, "play"])?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+mod backend {
+    pub fn pause_playing() -> Result<Vec<String>, String> {
+        Ok(Vec::new())
+    }
+
+    pub fn resume(_players: &[String]) -> Result<(), String> {
+        Ok(())
+    }
+}
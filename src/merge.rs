@@ -0,0 +1,98 @@
+//! 合并数据库：把另一台电脑（如办公室电脑）的 `red_tomato.db` 中的专注记录并入本机
+//!
+//! 按 (任务, 完成时间, 时长) 去重，本机主库与归档库中已有的记录跳过，重复合并同一个文件不会产生重复记录。
+//! 项目按名称对应，本机没有的项目自动新建。对方的归档库 `red_tomato_archive.db` 也可直接选择合并。
+//! 只合并专注记录（含放弃记录与记录笔记）；回顾日志、计划、日历等按天的数据不合并。
+
+use std::path::Path;
+
+use rusqlite::{Connection, OpenFlags};
+
+use crate::db::{self, PortableRecord};
+
+/// 合并结果：新增记录数、跳过的重复记录数、改了项目归属的记录数、新建的项目数
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MergeOutcome {
+    pub merged: usize,
+    pub duplicates: usize,
+    pub reassigned: usize,
+    pub projects: usize,
+}
+
+impl MergeOutcome {
+    pub fn describe(&self) -> String {
+        let mut text = format!("已合并 {} 条专注记录，跳过已存在 {} 条", self.merged, self.duplicates);
+        if self.reassigned > 0 {
+            text.push_str(&format!("，{} 条改为对方的项目", self.reassigned));
+        }
+        if self.projects > 0 {
+            text.push_str(&format!("，新建 {} 个项目", self.projects));
+        }
+        text
+    }
+}
+
+/// 合并另一个数据库文件（以只读方式打开，不修改对方文件）
+pub fn merge_database(path: &Path) -> Result<MergeOutcome, String> {
+    if !path.is_file() {
+        return Err(format!("找不到文件：{}", path.display()));
+    }
+    let same = |own: std::path::PathBuf| {
+        matches!((path.canonicalize(), own.canonicalize()), (Ok(a), Ok(b)) if a == b)
+    };
+    if same(db::db_path()) || same(db::archive_path()) {
+        return Err("不能合并本机自己的数据库".to_string());
+    }
+    let foreign = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| format!("无法打开 {}：{}", path.display(), e))?;
+    let not_ours = |e: rusqlite::Error| format!("不是 red-tomato 数据库：{}", e);
+    if db::foreign_is_encrypted(&foreign).map_err(not_ours)? {
+        return Err("该数据库已加密，请先在原电脑上关闭加密后再合并".to_string());
+    }
+    let records = db::load_portable_records(&foreign)
+        .map_err(not_ours)?
+        .ok_or("不是 red-tomato 数据库（缺少专注记录表）")?;
+    merge_records(records, false)
+}
+
+/// 把记录并入本机（去重规则见模块说明）。`prefer_incoming` 为 true 时，已存在的记录若项目不同改为传入记录的项目
+pub fn merge_records(records: Vec<PortableRecord>, prefer_incoming: bool) -> Result<MergeOutcome, String> {
+    let mut conn = db::open_and_init().map_err(|e| e.to_string())?;
+    crate::wal::replay(&mut conn).map_err(|e| format!("回放预写日志失败：{}", e))?;
+    let archive = db::open_archive().map_err(|e| e.to_string())?;
+    let mut outcome = MergeOutcome::default();
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    for mut incoming in records {
+        let r = &incoming.record;
+        let exists = |c: &Connection| db::focus_record_duplicate(c, &r.task, &r.completed_at, r.duration_secs);
+        let duplicate = exists(&tx).map_err(|e| e.to_string())?
+            || match &archive {
+                Some(archive) => exists(archive).map_err(|e| e.to_string())?,
+                None => false,
+            };
+        if duplicate && !prefer_incoming {
+            outcome.duplicates += 1;
+            continue;
+        }
+        if let Some(name) = &incoming.project_name {
+            let (id, created) = db::project_id_for_name(&tx, name).map_err(|e| e.to_string())?;
+            incoming.record.project_id = id;
+            outcome.projects += created as usize;
+        }
+        if duplicate {
+            let r = &incoming.record;
+            let changed = db::reassign_record_project(&tx, &r.task, &r.completed_at, r.duration_secs, r.project_id)
+                .map_err(|e| e.to_string())?;
+            if changed > 0 {
+                outcome.reassigned += 1;
+            } else {
+                outcome.duplicates += 1;
+            }
+            continue;
+        }
+        db::insert_portable_record(&tx, &incoming).map_err(|e| e.to_string())?;
+        outcome.merged += 1;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(outcome)
+}
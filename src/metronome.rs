@@ -0,0 +1,116 @@
+//! 专注滴答声（节拍器）：专注计时运行时每秒一声轻柔的滴答，暂停与休息时自动静音
+//!
+//! 滴答声在内存中合成为一小段 WAV，由独立的音频线程播放，不启动外部进程。
+//! 目前只有 Windows 有播放后端（winmm `PlaySoundW`），其他平台上开启后不出声。
+
+use serde::{Deserialize, Serialize};
+use std::sync::mpsc::{self, Sender};
+
+/// 采样率与单声滴答的时长
+const SAMPLE_RATE: u32 = 22_050;
+const TICK_MILLIS: u32 = 35;
+
+/// 当前平台是否能播放滴答声
+pub const SUPPORTED: bool = cfg!(windows);
+
+/// 滴答声设置（仅本机）
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MetronomeSettings {
+    pub enabled: bool,
+    /// 音量 0–100
+    pub volume: u8,
+}
+
+impl Default for MetronomeSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            volume: 30,
+        }
+    }
+}
+
+/// 节拍器：首次发声时启动音频线程，之后每秒把音量发给它
+#[derive(Default)]
+pub struct Metronome {
+    audio: Option<Sender<u8>>,
+    /// 上次发声时计时器显示的秒（剩余秒数与加时秒数），显示变化时才响一声
+    last_second: Option<(i64, i64)>,
+}
+
+impl Metronome {
+    /// 每帧调用；`focusing` 为专注计时正在运行，`second` 为计时器当前显示的秒
+    pub fn sync(&mut self, settings: &MetronomeSettings, focusing: bool, second: (i64, i64)) {
+        if !SUPPORTED || !settings.enabled || !focusing || settings.volume == 0 {
+            self.last_second = None;
+            return;
+        }
+        if self.last_second == Some(second) {
+            return;
+        }
+        self.last_second = Some(second);
+        let audio = self.audio.get_or_insert_with(spawn_audio_thread);
+        if audio.send(settings.volume).is_err() {
+            self.audio = None;
+        }
+    }
+}
+
+/// 音频线程：按收到的音量播放滴答声；积压时只播最新一次，音量不变时复用合成好的 WAV
+fn spawn_audio_thread() -> Sender<u8> {
+    let (tx, rx) = mpsc::channel::<u8>();
+    std::thread::spawn(move || {
+        let mut cached: Option<(u8, Vec<u8>)> = None;
+        while let Ok(volume) = rx.recv() {
+            let volume = rx.try_iter().last().unwrap_or(volume);
+            if cached.as_ref().is_none_or(|(v, _)| *v != volume) {
+                cached = Some((volume, tick_wav(volume)));
+            }
+            if let Some((_, wav)) = &cached {
+                play_wav(wav);
+            }
+        }
+    });
+    tx
+}
+
+/// 合成一声滴答（16 位单声道 PCM 的 WAV）：两个快速衰减的正弦叠加，听起来像机械表的轻响
+fn tick_wav(volume: u8) -> Vec<u8> {
+    let samples = SAMPLE_RATE * TICK_MILLIS / 1000;
+    let peak = i16::MAX as f32 * 0.6 * volume.min(100) as f32 / 100.0;
+    let data_len = samples * 2;
+    let mut wav = Vec::with_capacity(44 + data_len as usize);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    wav.extend_from_slice(&1u16.to_le_bytes()); // 单声道
+    wav.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    wav.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    wav.extend_from_slice(&2u16.to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+    for i in 0..samples {
+        let t = i as f32 / SAMPLE_RATE as f32;
+        let tau = std::f32::consts::TAU;
+        let wave = 0.7 * (tau * 1_800.0 * t).sin() + 0.3 * (tau * 3_600.0 * t).sin();
+        let envelope = (-t / 0.004).exp();
+        wav.extend_from_slice(&((wave * envelope * peak) as i16).to_le_bytes());
+    }
+    wav
+}
+
+/// 同步播放内存中的 WAV（在音频线程上调用，阻塞到播放结束）
+#[cfg(windows)]
+fn play_wav(wav: &[u8]) {
+    use windows_sys::Win32::Media::Audio::{PlaySoundW, SND_MEMORY, SND_NODEFAULT};
+    unsafe {
+        PlaySoundW(wav.as_ptr() as *const u16, std::ptr::null_mut(), SND_MEMORY | SND_NODEFAULT);
+    }
+}
+
+#[cfg(not(windows))]
+fn play_wav(_wav: &[u8]) {}
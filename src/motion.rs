@@ -0,0 +1,50 @@
+//! 计时器动效：阶段切换时强调色平滑过渡、计时中大计时器背后随呼吸节奏起伏的光晕（点光源）
+//!
+//! 强调色用 egui 的 `animate_value_with_time` 逐通道过渡，进度条、阶段文案与光晕一起变色；
+//! 光晕以强调色画若干由外到内叠加的半透明同心圆，近似径向渐变。
+//! 减少动态效果（设置 → 无障碍）开启时颜色立即切换、光晕不起伏；降低透明度时不画光晕。
+
+use eframe::egui;
+use egui::{Color32, Pos2, Shape};
+
+/// 阶段切换时强调色的过渡时长（秒）
+pub const ACCENT_TRANSITION_SECS: f32 = 0.6;
+/// 光晕一次起伏的周期（秒），接近放松时的呼吸节奏
+pub const GLOW_PERIOD_SECS: f64 = 4.0;
+/// 光晕的同心圆层数
+const GLOW_RINGS: usize = 10;
+/// 每层圆的不透明度（乘以光晕强度）
+const GLOW_RING_ALPHA: f32 = 0.05;
+
+/// 带过渡的颜色：target 变化时在 ACCENT_TRANSITION_SECS 内从当前显示的颜色渐变过去；reduce_motion 时立即切换
+pub fn animated_color(ctx: &egui::Context, id: egui::Id, target: Color32, reduce_motion: bool) -> Color32 {
+    let time = if reduce_motion { 0.0 } else { ACCENT_TRANSITION_SECS };
+    let [r, g, b, a] = target.to_array();
+    let channel = |i: usize, v: u8| ctx.animate_value_with_time(id.with(i), v as f32, time).round() as u8;
+    Color32::from_rgba_premultiplied(channel(0, r), channel(1, g), channel(2, b), channel(3, a))
+}
+
+/// 光晕强度 0.0..=1.0：计时运行中随时间正弦起伏，减少动态效果时保持中等亮度不动；未运行时不亮
+pub fn glow_level(running: bool, reduce_motion: bool, time: f64) -> f32 {
+    if !running {
+        return 0.0;
+    }
+    if reduce_motion {
+        return 0.6;
+    }
+    let phase = (time / GLOW_PERIOD_SECS * std::f64::consts::TAU) as f32;
+    0.35 + 0.65 * 0.5 * (1.0 - phase.cos())
+}
+
+/// 以 center 为圆心、radius 为半径的径向光晕
+pub fn glow_shape(center: Pos2, radius: f32, color: Color32, level: f32) -> Shape {
+    if level <= 0.0 {
+        return Shape::Noop;
+    }
+    let ring = color.gamma_multiply(GLOW_RING_ALPHA * level);
+    Shape::Vec(
+        (0..GLOW_RINGS)
+            .map(|i| Shape::circle_filled(center, radius * (1.0 - i as f32 / GLOW_RINGS as f32), ring))
+            .collect(),
+    )
+}
@@ -0,0 +1,364 @@
+//! MQTT 发布：把计时状态发到家里的 MQTT 服务器，供 Home Assistant 等联动（如专注时台灯变红、休息时变绿）
+//!
+//! 主题前缀下发布三个保留消息：
+//! - `<前缀>/phase`：`focus` / `short_break` / `long_break`，未开始时为 `idle`，阶段或状态变化时更新
+//! - `<前缀>/status`：JSON（阶段、状态、剩余分钟与秒数、任务、阶段颜色），变化时及计时中每分钟更新
+//! - `<前缀>/availability`：`online` / `offline`（遗嘱消息，程序退出或断线时由服务器发出 `offline`）
+//!
+//! 手写的 MQTT 3.1.1 客户端，只发布（QoS 0），支持用户名密码与 TLS（rustls，随 ureq 引入）。
+//! 连接与发送都在后台线程，断线后每隔一段时间重连并补发最新状态。
+
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::pomodoro::{Phase, TimerState};
+
+/// 默认端口：明文 1883，TLS 8883
+pub const DEFAULT_PORT: u16 = 1883;
+pub const DEFAULT_TLS_PORT: u16 = 8883;
+const DEFAULT_TOPIC: &str = "red-tomato";
+/// 连接失败或断线后的重连间隔
+const RETRY_INTERVAL: Duration = Duration::from_secs(15);
+/// 保活间隔（秒），空闲时每半个间隔发一次心跳
+const KEEP_ALIVE_SECS: u16 = 60;
+const IO_TIMEOUT: Duration = Duration::from_secs(10);
+/// 建立 TCP 连接的超时（不用系统默认的数十秒，设置改动后旧线程能及时退出）
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// 设置改动后保持这么久不再变化才按新设置重连，输入主机名时不会每个按键都起一个连接线程
+const RESTART_DEBOUNCE: Duration = Duration::from_secs(2);
+
+/// MQTT 设置（默认关闭；仅本机）
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MqttSettings {
+    pub enabled: bool,
+    pub host: String,
+    pub port: u16,
+    pub tls: bool,
+    pub username: String,
+    pub password: String,
+    /// 主题前缀，空为 red-tomato
+    pub topic: String,
+}
+
+impl Default for MqttSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: String::new(),
+            port: DEFAULT_PORT,
+            tls: false,
+            username: String::new(),
+            password: String::new(),
+            topic: String::new(),
+        }
+    }
+}
+
+impl MqttSettings {
+    pub fn is_active(&self) -> bool {
+        self.enabled && !self.host.trim().is_empty()
+    }
+
+    fn topic_prefix(&self) -> String {
+        let topic = self.topic.trim().trim_end_matches('/');
+        if topic.is_empty() { DEFAULT_TOPIC.to_string() } else { topic.to_string() }
+    }
+}
+
+/// 要发布的计时状态
+#[derive(Clone, Debug, PartialEq)]
+pub struct MqttStatus {
+    pub phase: Phase,
+    pub state: TimerState,
+    pub remaining_secs: i64,
+    pub task: String,
+    pub color: [u8; 3],
+}
+
+impl MqttStatus {
+    /// 剩余分钟数（不足一分钟按一分钟计），每分钟变化一次
+    fn remaining_minutes(&self) -> i64 {
+        (self.remaining_secs.max(0) + 59) / 60
+    }
+
+    /// 除秒数外相同（秒数变化不重发，分钟变化才重发）
+    fn same_as(&self, other: &MqttStatus) -> bool {
+        self.phase == other.phase
+            && self.state == other.state
+            && self.task == other.task
+            && self.color == other.color
+            && self.remaining_minutes() == other.remaining_minutes()
+    }
+
+    fn phase_key(&self) -> &'static str {
+        match (self.state, self.phase) {
+            (TimerState::Idle, _) => "idle",
+            (_, Phase::Focus) => "focus",
+            (_, Phase::ShortBreak) => "short_break",
+            (_, Phase::LongBreak) => "long_break",
+        }
+    }
+
+    fn to_json(&self) -> String {
+        let state = match self.state {
+            TimerState::Idle => "idle",
+            TimerState::Running => "running",
+            TimerState::Paused => "paused",
+        };
+        let [r, g, b] = self.color;
+        serde_json::json!({
+            "phase": self.phase_key(),
+            "state": state,
+            "remaining_minutes": self.remaining_minutes(),
+            "remaining_secs": self.remaining_secs.max(0),
+            "task": self.task,
+            "color": format!("#{:02x}{:02x}{:02x}", r, g, b),
+            "rgb": [r, g, b],
+        })
+        .to_string()
+    }
+}
+
+/// 发布句柄：持有后台线程的发送端，丢弃时线程发布 offline 后断开
+#[derive(Default)]
+pub struct MqttPublisher {
+    worker: Option<(MqttSettings, Sender<MqttStatus>)>,
+    /// 与后台线程不同的新设置及其首次出现的时刻（等待 RESTART_DEBOUNCE）
+    pending: Option<(MqttSettings, Instant)>,
+    last: Option<MqttStatus>,
+    /// 后台线程报告的连接状态，设置中显示
+    state: Arc<Mutex<String>>,
+}
+
+impl MqttPublisher {
+    /// 每帧调用：按设置启停后台线程（设置改动并稳定后重连），状态变化或跨分钟时发送
+    pub fn sync(&mut self, settings: &MqttSettings, status: MqttStatus) {
+        if !settings.is_active() {
+            self.worker = None;
+            self.pending = None;
+            self.last = None;
+            return;
+        }
+        if self.worker.as_ref().is_none_or(|(s, _)| s != settings) && self.settled(settings) {
+            // 替换发送端后旧线程收到断开，发布 offline 后退出
+            let (tx, rx) = mpsc::channel();
+            let worker_settings = settings.clone();
+            let state = Arc::clone(&self.state);
+            *state.lock().unwrap() = "正在连接…".to_string();
+            std::thread::spawn(move || run_worker(&worker_settings, rx, &state));
+            self.worker = Some((settings.clone(), tx));
+            self.last = None;
+        }
+        if self.last.as_ref().is_some_and(|last| last.same_as(&status)) {
+            return;
+        }
+        if let Some((_, tx)) = &self.worker {
+            let _ = tx.send(status.clone());
+        }
+        self.last = Some(status);
+    }
+
+    /// 设置与之前几帧相同且已保持 RESTART_DEBOUNCE 时为 true；设置变了则重新计时
+    fn settled(&mut self, settings: &MqttSettings) -> bool {
+        match &self.pending {
+            Some((s, since)) if s == settings => since.elapsed() >= RESTART_DEBOUNCE,
+            _ => {
+                self.pending = Some((settings.clone(), Instant::now()));
+                false
+            }
+        }
+    }
+
+    /// 连接状态说明（未开启时为空）
+    pub fn state(&self) -> String {
+        if self.worker.is_none() {
+            return String::new();
+        }
+        self.state.lock().unwrap().clone()
+    }
+}
+
+trait Stream: Read + Write + Send {}
+impl<T: Read + Write + Send> Stream for T {}
+
+/// 后台线程：维持连接、发布最新状态；发送端断开时发布 offline 并断开后退出
+fn run_worker(settings: &MqttSettings, rx: mpsc::Receiver<MqttStatus>, state: &Mutex<String>) {
+    let prefix = settings.topic_prefix();
+    let availability = format!("{}/availability", prefix);
+    let mut conn: Option<Box<dyn Stream>> = None;
+    let mut latest: Option<MqttStatus> = None;
+    let mut sent = false;
+    let mut last_attempt: Option<Instant> = None;
+    let mut last_packet = Instant::now();
+    loop {
+        let wait = Duration::from_secs(KEEP_ALIVE_SECS as u64 / 2);
+        match rx.recv_timeout(wait) {
+            Ok(status) => {
+                latest = Some(status);
+                sent = false;
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => {
+                if let Some(mut stream) = conn.take() {
+                    let _ = publish(&mut stream, &availability, b"offline", true);
+                    let _ = stream.write_all(&[0xE0, 0x00]);
+                }
+                return;
+            }
+        }
+        if conn.is_none() && last_attempt.is_none_or(|t| t.elapsed() >= RETRY_INTERVAL) {
+            last_attempt = Some(Instant::now());
+            match connect(settings, &availability) {
+                Ok(stream) => {
+                    tracing::info!("MQTT 已连接：{}:{}", settings.host.trim(), settings.port);
+                    *state.lock().unwrap() = format!("已连接：{}:{}", settings.host.trim(), settings.port);
+                    conn = Some(stream);
+                    sent = false;
+                    last_packet = Instant::now();
+                }
+                Err(e) => {
+                    tracing::warn!("MQTT 连接失败：{}", e);
+                    *state.lock().unwrap() = format!("连接失败：{}（{} 秒后重试）", e, RETRY_INTERVAL.as_secs());
+                }
+            }
+        }
+        let Some(stream) = conn.as_mut() else { continue };
+        let result = if let Some(status) = latest.as_ref().filter(|_| !sent) {
+            publish(stream, &format!("{}/phase", prefix), status.phase_key().as_bytes(), true)
+                .and_then(|_| publish(stream, &format!("{}/status", prefix), status.to_json().as_bytes(), true))
+                .map(|_| sent = true)
+        } else if last_packet.elapsed() >= wait {
+            ping(stream)
+        } else {
+            Ok(())
+        };
+        match result {
+            Ok(()) => last_packet = Instant::now(),
+            Err(e) => {
+                tracing::warn!("MQTT 连接断开：{}", e);
+                *state.lock().unwrap() = format!("连接断开：{}（{} 秒后重连）", e, RETRY_INTERVAL.as_secs());
+                conn = None;
+                sent = false;
+            }
+        }
+    }
+}
+
+/// 建立连接（可选 TLS）并完成 CONNECT / CONNACK；遗嘱为 availability = offline，连上后发布 online
+fn connect(settings: &MqttSettings, availability: &str) -> Result<Box<dyn Stream>, String> {
+    let host = settings.host.trim();
+    // 逐个尝试解析出的地址，都失败时报最后一个错误
+    let mut tcp = Err(format!("无法解析主机名 {}", host));
+    for addr in (host, settings.port).to_socket_addrs().map_err(|e| e.to_string())? {
+        tcp = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT).map_err(|e| e.to_string());
+        if tcp.is_ok() {
+            break;
+        }
+    }
+    let tcp = tcp?;
+    tcp.set_read_timeout(Some(IO_TIMEOUT)).map_err(|e| e.to_string())?;
+    tcp.set_write_timeout(Some(IO_TIMEOUT)).map_err(|e| e.to_string())?;
+    let mut stream: Box<dyn Stream> = if settings.tls { Box::new(tls_stream(host, tcp)?) } else { Box::new(tcp) };
+
+    let username = settings.username.trim();
+    let mut flags = 0x02 | 0x04 | 0x20; // 清除会话、遗嘱、遗嘱保留
+    if !username.is_empty() {
+        flags |= 0x80;
+        if !settings.password.is_empty() {
+            flags |= 0x40;
+        }
+    }
+    let mut body = Vec::new();
+    put_bytes(&mut body, b"MQTT");
+    body.push(4); // 协议级别 3.1.1
+    body.push(flags);
+    body.extend_from_slice(&KEEP_ALIVE_SECS.to_be_bytes());
+    put_bytes(&mut body, format!("red-tomato-{}", std::process::id()).as_bytes());
+    put_bytes(&mut body, availability.as_bytes());
+    put_bytes(&mut body, b"offline");
+    if !username.is_empty() {
+        put_bytes(&mut body, username.as_bytes());
+        if !settings.password.is_empty() {
+            put_bytes(&mut body, settings.password.as_bytes());
+        }
+    }
+    write_packet(&mut stream, 0x10, &body).map_err(|e| e.to_string())?;
+
+    let mut connack = [0u8; 4];
+    stream.read_exact(&mut connack).map_err(|e| format!("未收到服务器应答：{}", e))?;
+    if connack[0] != 0x20 {
+        return Err("服务器应答不是 CONNACK".to_string());
+    }
+    match connack[3] {
+        0 => {}
+        4 | 5 => return Err("用户名或密码不正确".to_string()),
+        code => return Err(format!("服务器拒绝连接（代码 {}）", code)),
+    }
+    publish(&mut stream, availability, b"online", true).map_err(|e| e.to_string())?;
+    Ok(stream)
+}
+
+fn tls_stream(host: &str, tcp: TcpStream) -> Result<rustls::StreamOwned<rustls::ClientConnection, TcpStream>, String> {
+    let mut roots = rustls::RootCertStore::empty();
+    roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    let config = rustls::ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .map_err(|e| e.to_string())?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    let name = rustls::pki_types::ServerName::try_from(host.to_string()).map_err(|e| e.to_string())?;
+    let conn = rustls::ClientConnection::new(Arc::new(config), name).map_err(|e| e.to_string())?;
+    Ok(rustls::StreamOwned::new(conn, tcp))
+}
+
+/// 发布（QoS 0）
+fn publish(stream: &mut Box<dyn Stream>, topic: &str, payload: &[u8], retain: bool) -> std::io::Result<()> {
+    let mut body = Vec::new();
+    put_bytes(&mut body, topic.as_bytes());
+    body.extend_from_slice(payload);
+    write_packet(stream, 0x30 | retain as u8, &body)
+}
+
+/// 心跳：PINGREQ 并等待 PINGRESP
+fn ping(stream: &mut Box<dyn Stream>) -> std::io::Result<()> {
+    stream.write_all(&[0xC0, 0x00])?;
+    stream.flush()?;
+    let mut resp = [0u8; 2];
+    stream.read_exact(&mut resp)?;
+    if resp[0] != 0xD0 {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "心跳应答不正确"));
+    }
+    Ok(())
+}
+
+/// 带两字节长度前缀的字符串 / 二进制
+fn put_bytes(buf: &mut Vec<u8>, bytes: &[u8]) {
+    buf.extend_from_slice(&(bytes.len() as u16).to_be_bytes());
+    buf.extend_from_slice(bytes);
+}
+
+/// 固定头（类型 + 变长的剩余长度）加正文
+fn write_packet(stream: &mut Box<dyn Stream>, header: u8, body: &[u8]) -> std::io::Result<()> {
+    let mut packet = vec![header];
+    let mut len = body.len();
+    loop {
+        let mut byte = (len % 128) as u8;
+        len /= 128;
+        if len > 0 {
+            byte |= 0x80;
+        }
+        packet.push(byte);
+        if len == 0 {
+            break;
+        }
+    }
+    packet.extend_from_slice(body);
+    stream.write_all(&packet)?;
+    stream.flush()
+}
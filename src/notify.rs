@@ -0,0 +1,453 @@
+//! 通知路由：按事件决定发往哪些渠道（应用内提示、提示音、系统通知气泡、Webhook、手机推送、摘要）
+//!
+//! 路由矩阵以「事件键 → 渠道键列表」保存，新增事件/渠道时旧配置仍可读取，缺省的事件使用默认路由。
+//! 网络类渠道（Webhook、手机推送）在后台线程发送，失败时记入日志并在界面顶部提示，不影响计时。
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use crate::errors::{ErrorKind, ReportErr};
+
+/// 应用内提示停留时长
+pub const TOAST_DURATION: Duration = Duration::from_secs(4);
+/// 网络请求超时（秒）
+const TIMEOUT_SECS: u64 = 10;
+/// 视觉提醒中背景脉冲的总时长与单次脉冲周期
+pub const PULSE_DURATION: Duration = Duration::from_secs(3);
+const PULSE_PERIOD_SECS: f32 = 1.0;
+/// Telegram 每日摘要的默认发送时刻
+pub const DEFAULT_DIGEST_TIME: &str = "21:30";
+
+/// 可路由的事件
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NotifyEvent {
+    /// 完成一个番茄
+    FocusFinished,
+    /// 短/长休息结束
+    BreakFinished,
+    /// 专注未完成即被重置
+    FocusAbandoned,
+    /// 设置同步失败
+    SyncFailed,
+    /// 日程中预定的专注到点
+    ScheduledSession,
+    /// 工作时间内长时间没有计时
+    IdleNudge,
+    /// 累计专注满护眼间隔（20-20-20）
+    EyeRest,
+    /// 起来活动、喝水等健康提醒
+    Wellness,
+    /// 连续跳过休息或太久没有长休息
+    RestWarning,
+    /// 副计时到点
+    SideTimer,
+}
+
+impl NotifyEvent {
+    pub const ALL: [NotifyEvent; 10] = [
+        NotifyEvent::FocusFinished,
+        NotifyEvent::BreakFinished,
+        NotifyEvent::FocusAbandoned,
+        NotifyEvent::SyncFailed,
+        NotifyEvent::ScheduledSession,
+        NotifyEvent::IdleNudge,
+        NotifyEvent::EyeRest,
+        NotifyEvent::Wellness,
+        NotifyEvent::RestWarning,
+        NotifyEvent::SideTimer,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            NotifyEvent::FocusFinished => "番茄完成",
+            NotifyEvent::BreakFinished => "休息结束",
+            NotifyEvent::FocusAbandoned => "专注放弃",
+            NotifyEvent::SyncFailed => "同步失败",
+            NotifyEvent::ScheduledSession => "日程提醒",
+            NotifyEvent::IdleNudge => "空闲提醒",
+            NotifyEvent::EyeRest => "护眼提醒",
+            NotifyEvent::Wellness => "健康提醒",
+            NotifyEvent::RestWarning => "休息警示",
+            NotifyEvent::SideTimer => "副计时到点",
+        }
+    }
+
+    /// 持久化 / 摘要表中使用的键
+    pub fn key(self) -> &'static str {
+        match self {
+            NotifyEvent::FocusFinished => "focus_finished",
+            NotifyEvent::BreakFinished => "break_finished",
+            NotifyEvent::FocusAbandoned => "focus_abandoned",
+            NotifyEvent::SyncFailed => "sync_failed",
+            NotifyEvent::ScheduledSession => "scheduled_session",
+            NotifyEvent::IdleNudge => "idle_nudge",
+            NotifyEvent::EyeRest => "eye_rest",
+            NotifyEvent::Wellness => "wellness",
+            NotifyEvent::RestWarning => "rest_warning",
+            NotifyEvent::SideTimer => "side_timer",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|e| e.key() == key)
+    }
+
+    /// 是否为提醒类事件（工作时间外可压下；阶段结束、同步失败等不算）
+    pub fn is_reminder(self) -> bool {
+        matches!(
+            self,
+            NotifyEvent::ScheduledSession
+                | NotifyEvent::IdleNudge
+                | NotifyEvent::EyeRest
+                | NotifyEvent::Wellness
+                | NotifyEvent::RestWarning
+        )
+    }
+
+    /// 未配置时的默认渠道（番茄完成仍响铃，与引入路由前一致）
+    fn default_channels(self) -> &'static [Channel] {
+        match self {
+            NotifyEvent::FocusFinished => &[Channel::Toast, Channel::Sound],
+            NotifyEvent::BreakFinished => &[Channel::Toast],
+            NotifyEvent::FocusAbandoned => &[Channel::Digest],
+            NotifyEvent::SyncFailed => &[Channel::Toast],
+            NotifyEvent::ScheduledSession => &[Channel::Toast, Channel::Sound, Channel::TrayBalloon],
+            NotifyEvent::IdleNudge => &[Channel::Toast, Channel::TrayBalloon],
+            // 窗口在其他程序后面时看不到浮层，默认同时发系统通知
+            NotifyEvent::EyeRest => &[Channel::TrayBalloon],
+            NotifyEvent::Wellness => &[Channel::Toast, Channel::TrayBalloon],
+            NotifyEvent::RestWarning => &[Channel::TrayBalloon],
+            NotifyEvent::SideTimer => &[Channel::Toast, Channel::Sound, Channel::TrayBalloon],
+        }
+    }
+}
+
+/// 通知渠道
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Channel {
+    /// 应用内提示（窗口底部浮层）
+    Toast,
+    /// 系统提示音
+    Sound,
+    /// 系统通知气泡
+    TrayBalloon,
+    /// POST JSON 到自定义地址
+    Webhook,
+    /// 手机推送（ntfy 兼容：POST 到主题地址；或 Telegram 机器人消息）
+    PhonePush,
+    /// 记入摘要，在每日回顾中集中查看
+    Digest,
+}
+
+impl Channel {
+    pub const ALL: [Channel; 6] = [
+        Channel::Toast,
+        Channel::Sound,
+        Channel::TrayBalloon,
+        Channel::Webhook,
+        Channel::PhonePush,
+        Channel::Digest,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Channel::Toast => "提示",
+            Channel::Sound => "声音",
+            Channel::TrayBalloon => "气泡",
+            Channel::Webhook => "Webhook",
+            Channel::PhonePush => "手机",
+            Channel::Digest => "摘要",
+        }
+    }
+
+    fn key(self) -> &'static str {
+        match self {
+            Channel::Toast => "toast",
+            Channel::Sound => "sound",
+            Channel::TrayBalloon => "tray_balloon",
+            Channel::Webhook => "webhook",
+            Channel::PhonePush => "phone_push",
+            Channel::Digest => "digest",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.key() == key)
+    }
+}
+
+/// 通知路由配置（持久化在本机，不参与设置同步）
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct NotificationRouting {
+    /// 事件键 → 渠道键列表；没有的事件使用默认路由
+    #[serde(default)]
+    pub routes: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    pub webhook_url: String,
+    /// 手机推送地址，如 https://ntfy.sh/my-topic
+    #[serde(default)]
+    pub push_url: String,
+    /// 推送访问令牌：ntfy 受保护主题的 access token，或自建推送接口要求的令牌；以 Bearer 认证头发送，可留空
+    #[serde(default)]
+    pub push_token: String,
+    /// Telegram 机器人 token 与接收消息的 chat_id（两者都填才发送）
+    #[serde(default)]
+    pub telegram_token: String,
+    #[serde(default)]
+    pub telegram_chat_id: String,
+    /// 每晚把当天的番茄数与任务排行发到 Telegram
+    #[serde(default)]
+    pub telegram_digest: bool,
+    /// 每日摘要的发送时刻（HH:MM，北京时间）；空或格式不对时用 `DEFAULT_DIGEST_TIME`
+    #[serde(default)]
+    pub telegram_digest_time: String,
+    /// 上次发送每日摘要的日期（当天只发一次，到点时程序未运行则当天稍后启动时补发）
+    #[serde(default)]
+    pub telegram_digest_sent_on: Option<chrono::NaiveDate>,
+    /// 阶段结束的手机推送附带「开始下一阶段」「+5 分钟」按钮，并接收点按回调
+    #[serde(default)]
+    pub push_actions: bool,
+    /// 阶段结束的视觉提醒（静音工作时用）
+    #[serde(default)]
+    pub visual: VisualAlarm,
+}
+
+/// 阶段结束的视觉提醒：任务栏闪烁（Windows 上为 FlashWindowEx）、窗口背景脉冲、窗口弹到最前
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VisualAlarm {
+    pub flash_taskbar: bool,
+    pub pulse: bool,
+    pub raise_window: bool,
+}
+
+/// 背景脉冲强度 0.0..=1.0（提醒开始后经过 elapsed），结束后为 None。
+/// `steady` 为减少动态效果时：不闪动，整段时间保持淡淡的着色
+pub fn pulse_intensity(elapsed: Duration, steady: bool) -> Option<f32> {
+    if elapsed >= PULSE_DURATION {
+        return None;
+    }
+    if steady {
+        return Some(0.25);
+    }
+    let phase = elapsed.as_secs_f32() / PULSE_PERIOD_SECS * std::f32::consts::TAU;
+    Some(0.5 * (1.0 - phase.cos()) * 0.6)
+}
+
+impl NotificationRouting {
+    /// 事件当前路由到的渠道
+    pub fn channels(&self, event: NotifyEvent) -> Vec<Channel> {
+        match self.routes.get(event.key()) {
+            Some(keys) => keys.iter().filter_map(|k| Channel::from_key(k)).collect(),
+            None => event.default_channels().to_vec(),
+        }
+    }
+
+    /// 填了推送令牌时的认证头
+    pub fn push_auth_header(&self) -> Option<(&'static str, String)> {
+        let token = self.push_token.trim();
+        (!token.is_empty()).then(|| ("Authorization", format!("Bearer {}", token)))
+    }
+
+    pub fn telegram_active(&self) -> bool {
+        !self.telegram_token.trim().is_empty() && !self.telegram_chat_id.trim().is_empty()
+    }
+
+    /// 每日摘要的发送时刻
+    pub fn digest_time(&self) -> chrono::NaiveTime {
+        chrono::NaiveTime::parse_from_str(self.telegram_digest_time.trim(), "%H:%M")
+            .unwrap_or_else(|_| chrono::NaiveTime::parse_from_str(DEFAULT_DIGEST_TIME, "%H:%M").unwrap())
+    }
+
+    /// 此刻是否该发送当天的每日摘要
+    pub fn digest_due(&self, now: chrono::NaiveDateTime) -> bool {
+        self.telegram_digest
+            && self.telegram_active()
+            && now.time() >= self.digest_time()
+            && self.telegram_digest_sent_on != Some(now.date())
+    }
+
+    pub fn is_routed(&self, event: NotifyEvent, channel: Channel) -> bool {
+        self.channels(event).contains(&channel)
+    }
+
+    /// 打开 / 关闭某个事件到某个渠道的路由
+    pub fn set_routed(&mut self, event: NotifyEvent, channel: Channel, on: bool) {
+        let mut channels = self.channels(event);
+        channels.retain(|c| *c != channel);
+        if on {
+            channels.push(channel);
+        }
+        let keys = Channel::ALL
+            .into_iter()
+            .filter(|c| channels.contains(c))
+            .map(|c| c.key().to_string())
+            .collect();
+        self.routes.insert(event.key().to_string(), keys);
+    }
+}
+
+/// 通知分发器：持有应用内提示队列，其余渠道即发即忘
+#[derive(Default)]
+pub struct Notifier {
+    toasts: Vec<(String, Instant)>,
+}
+
+impl Notifier {
+    /// 按路由把一条通知发往各渠道；occurred_at 为北京时间 RFC3339（摘要与 Webhook 使用）
+    pub fn dispatch(&mut self, routing: &NotificationRouting, event: NotifyEvent, message: &str, occurred_at: &str) {
+        for channel in routing.channels(event) {
+            match channel {
+                Channel::Toast => self.toasts.push((message.to_string(), Instant::now())),
+                Channel::Sound => play_sound(),
+                Channel::TrayBalloon => show_system_notification(event.label(), message),
+                Channel::Webhook => {
+                    if !routing.webhook_url.trim().is_empty() {
+                        let body = serde_json::json!({
+                            "event": event.key(),
+                            "message": message,
+                            "occurred_at": occurred_at,
+                        })
+                        .to_string();
+                        post_in_background(routing.webhook_url.trim().to_string(), body, "application/json", Vec::new());
+                    }
+                }
+                Channel::PhonePush => {
+                    let text = format!("{}：{}", event.label(), message);
+                    // 只有阶段结束的推送带操作按钮
+                    let with_actions = routing.push_actions
+                        && matches!(event, NotifyEvent::FocusFinished | NotifyEvent::BreakFinished);
+                    let push_url = routing.push_url.trim();
+                    let auth = routing.push_auth_header();
+                    match crate::push_actions::split_ntfy_url(push_url).filter(|_| with_actions) {
+                        // 带按钮时用 ntfy 的 JSON 发布接口（POST 到服务器根地址）
+                        Some((base, topic)) => post_in_background(
+                            base.to_string(),
+                            crate::push_actions::ntfy_publish_body(push_url, topic, &text),
+                            "application/json",
+                            auth.into_iter().collect(),
+                        ),
+                        None if !push_url.is_empty() => post_in_background(
+                            push_url.to_string(),
+                            text.clone(),
+                            "text/plain; charset=utf-8",
+                            // ntfy 用 Title 头作为推送标题（HTTP 头只放 ASCII，事件名写在正文里）
+                            [("Title", "Red Tomato".to_string())].into_iter().chain(auth).collect(),
+                        ),
+                        None => {}
+                    }
+                    if routing.telegram_active() {
+                        post_in_background(
+                            crate::push_actions::telegram_api_url(&routing.telegram_token, "sendMessage"),
+                            crate::push_actions::telegram_message_body(&routing.telegram_chat_id, &text, with_actions),
+                            "application/json",
+                            Vec::new(),
+                        );
+                    }
+                }
+                Channel::Digest => {
+                    if let Some(conn) = crate::db::open_and_init().db_err("打开数据库") {
+                        crate::db::insert_digest_entry(&conn, occurred_at, event.key(), message).db_err("写入通知摘要");
+                    }
+                }
+            }
+        }
+    }
+
+    /// 直接显示一条应用内提示（不经路由，用于成就解锁等界面内的轻提示）
+    pub fn toast(&mut self, message: &str) {
+        self.toasts.push((message.to_string(), Instant::now()));
+    }
+
+    /// 未过期的应用内提示（顺带清理过期的）
+    pub fn active_toasts(&mut self) -> impl Iterator<Item = &str> {
+        self.toasts.retain(|(_, at)| at.elapsed() < TOAST_DURATION);
+        self.toasts.iter().map(|(m, _)| m.as_str())
+    }
+}
+
+/// 在后台把一段文字发到配置的 Telegram 会话（未配置时不发送）
+pub fn send_telegram(routing: &NotificationRouting, text: &str) {
+    if routing.telegram_active() {
+        post_in_background(
+            crate::push_actions::telegram_api_url(&routing.telegram_token, "sendMessage"),
+            crate::push_actions::telegram_message_body(&routing.telegram_chat_id, text, false),
+            "application/json",
+            Vec::new(),
+        );
+    }
+}
+
+/// 系统提示音
+fn play_sound() {
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        let _ = std::process::Command::new("powershell")
+            .args(["-NoProfile", "-NonInteractive", "-Command", "[Console]::Beep(800, 300)"])
+            .creation_flags(CREATE_NO_WINDOW)
+            .spawn();
+    }
+    #[cfg(not(windows))]
+    {
+        let _ = std::process::Command::new("echo").arg("\x07").status();
+    }
+}
+
+/// 系统通知气泡：Windows 用托盘 BalloonTip，macOS 用 osascript，其余用 notify-send
+fn show_system_notification(title: &str, message: &str) {
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        let esc = |s: &str| s.replace('\'', "''");
+        let script = format!(
+            "Add-Type -AssemblyName System.Windows.Forms; \
+             $n = New-Object System.Windows.Forms.NotifyIcon; \
+             $n.Icon = [System.Drawing.SystemIcons]::Information; $n.Visible = $true; \
+             $n.ShowBalloonTip(5000, '{}', '{}', 'Info'); Start-Sleep -Seconds 6; $n.Dispose()",
+            esc(title),
+            esc(message)
+        );
+        let _ = std::process::Command::new("powershell")
+            .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+            .creation_flags(CREATE_NO_WINDOW)
+            .spawn();
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let esc = |s: &str| s.replace('\\', "\\\\").replace('"', "\\\"");
+        let script = format!("display notification \"{}\" with title \"{}\"", esc(message), esc(title));
+        let _ = std::process::Command::new("osascript").args(["-e", &script]).spawn();
+    }
+    #[cfg(all(unix, not(target_os = "macos")))]
+    {
+        let _ = std::process::Command::new("notify-send").args([title, message]).spawn();
+    }
+}
+
+/// 后台线程 POST，结果忽略
+fn post_in_background(
+    url: String,
+    body: String,
+    content_type: &'static str,
+    extra_headers: Vec<(&'static str, String)>,
+) {
+    std::thread::spawn(move || {
+        let agent: ureq::Agent = ureq::Agent::config_builder()
+            .timeout_global(Some(Duration::from_secs(TIMEOUT_SECS)))
+            .build()
+            .into();
+        let mut req = agent.post(&url).header("Content-Type", content_type);
+        for (name, value) in &extra_headers {
+            req = req.header(*name, value);
+        }
+        if let Err(e) = req.send(&body) {
+            // 只记主机名：Telegram 等地址中含 token
+            let host = url.split('/').nth(2).unwrap_or_default();
+            crate::errors::report(ErrorKind::Integration, format!("推送到 {} 失败：{}", host, e));
+        }
+    });
+}
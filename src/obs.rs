@@ -0,0 +1,62 @@
+//! OBS 文本源输出：把当前计时状态持续写入一个小文本文件（如 `🍅 24:59 · 任务名`），
+//! 直播时在 OBS「文本」源中勾选「从文件读取」即可显示，无需插件
+//!
+//! 内容变化时才写入（通常每秒一次），先写临时文件再改名，OBS 不会读到半截内容。
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+const DEFAULT_FILENAME: &str = "obs.txt";
+
+/// OBS 输出设置（仅本机）
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ObsSettings {
+    pub enabled: bool,
+    /// 输出文件路径，空表示数据目录下的 obs.txt
+    pub path: String,
+}
+
+impl ObsSettings {
+    pub fn output_path(&self) -> PathBuf {
+        let path = self.path.trim();
+        if path.is_empty() {
+            default_path()
+        } else {
+            PathBuf::from(path)
+        }
+    }
+}
+
+/// 默认输出路径（Windows 上即 `%LOCALAPPDATA%/red-tomato/obs.txt`）
+pub fn default_path() -> PathBuf {
+    crate::db::data_dir().join(DEFAULT_FILENAME)
+}
+
+/// 输出器：记住上次写入的内容与路径，避免每帧重复写盘
+#[derive(Default)]
+pub struct ObsOutput {
+    last: Option<(PathBuf, String)>,
+}
+
+impl ObsOutput {
+    /// 内容或路径变化时写入；返回写入错误（未变化时为 Ok）
+    pub fn update(&mut self, settings: &ObsSettings, text: &str) -> std::io::Result<()> {
+        let path = settings.output_path();
+        if self.last.as_ref().is_some_and(|(p, t)| *p == path && t == text) {
+            return Ok(());
+        }
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, text)?;
+        std::fs::rename(&tmp, &path)?;
+        self.last = Some((path, text.to_string()));
+        Ok(())
+    }
+
+    /// 关闭输出后清掉缓存，下次开启时立即重写
+    pub fn reset(&mut self) {
+        self.last = None;
+    }
+}
@@ -0,0 +1,236 @@
+//! 专注日程与提醒：为某天预定番茄块（如 `09:00 写代码 ×2`），到点提醒并可一键开始（日程存入 SQLite schedule 表）；
+//! 工作时间内长时间没有计时则温和提醒开始专注（空闲提醒，每天可设工作时间与免打扰时段）；
+//! 按星期设定的工作时间：时间外压下提醒、空闲时显示「下班了」，统计区分工作时间内外的专注；
+//! 日程的番茄数即任务的预计番茄数，做满时庆祝并可标记任务完成，统计任务从添加到完成的交付时长
+
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use serde::{Deserialize, Serialize};
+
+use crate::db::{SchedulePlanRow, TaskLeadRow};
+
+/// 提醒检查间隔（秒）：到点后最多延迟这么久提醒
+pub const CHECK_INTERVAL_SECS: u64 = 20;
+/// 错过的日程在开始后多久内仍会提醒（如开始时间时应用未运行）
+pub const REMIND_GRACE_MINUTES: i64 = 30;
+
+/// 解析后的一条日程输入
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entry {
+    /// 北京时区 RFC3339
+    pub start_at: String,
+    pub task: String,
+    pub pomodoros: u32,
+}
+
+/// 解析 `09:00 写代码 ×2`：时间 + 任务名，可选以 `×N` / `xN` / `*N` 结尾的番茄数（默认 1）
+pub fn parse_entry(day: NaiveDate, text: &str) -> Result<Entry, String> {
+    let text = text.trim();
+    let (time, rest) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+    let start_at = crate::audit::day_time_rfc3339(day, time).ok_or("开头应为时间 HH:MM，如 09:00 写代码 ×2")?;
+    let mut task = rest.trim();
+    let mut pomodoros = 1;
+    // `×` 可紧跟任务名；x / * 需与任务名隔开空格，避免把 `box2` 之类的任务名拆开
+    if let Some(at) = task.rfind(['×', 'x', 'X', '*']) {
+        let (head, marker) = task.split_at(at);
+        let count = marker.chars().skip(1).collect::<String>();
+        let separated = marker.starts_with('×') || head.ends_with(char::is_whitespace);
+        if let (true, Ok(n)) = (separated, count.trim().parse::<u32>()) {
+            pomodoros = n;
+            task = head.trim();
+        }
+    }
+    if task.is_empty() {
+        return Err("请填写任务名".to_string());
+    }
+    if !(1..=12).contains(&pomodoros) {
+        return Err("番茄数应为 1–12".to_string());
+    }
+    Ok(Entry {
+        start_at,
+        task: task.to_string(),
+        pomodoros,
+    })
+}
+
+
+/// 刚做满预计番茄数的日程：同任务、未标记完成、完成数恰好等于计划数（之后多做的番茄不再重复庆祝）
+pub fn target_reached<'a>(rows: &'a [SchedulePlanRow], task: &str) -> Option<&'a SchedulePlanRow> {
+    rows.iter()
+        .find(|r| r.task == task && r.task_completed_at.is_empty() && r.done == r.planned)
+}
+
+/// 接下来可做的日程：未标记完成且番茄数未做满，排除刚完成的任务
+pub fn next_candidates<'a>(rows: &'a [SchedulePlanRow], done_task: &str) -> Vec<&'a SchedulePlanRow> {
+    rows.iter()
+        .filter(|r| r.task != done_task && r.task_completed_at.is_empty() && r.done < r.planned)
+        .collect()
+}
+
+/// 已完成任务的平均交付时长（秒，从添加日程到标记完成）；没有可计算的任务时为 None
+pub fn average_lead_secs(rows: &[TaskLeadRow]) -> Option<i64> {
+    let leads: Vec<i64> = rows
+        .iter()
+        .filter_map(|r| {
+            let created = DateTime::parse_from_rfc3339(&r.created_at).ok()?;
+            let completed = DateTime::parse_from_rfc3339(&r.task_completed_at).ok()?;
+            Some((completed - created).num_seconds().max(0))
+        })
+        .collect();
+    (!leads.is_empty()).then(|| leads.iter().sum::<i64>() / leads.len() as i64)
+}
+
+/// 空闲提醒：某一天的工作时间与免打扰时段（`HH:MM-HH:MM`，免打扰为空表示没有）
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NudgeDay {
+    pub enabled: bool,
+    pub work: String,
+    pub quiet: String,
+}
+
+/// 空闲提醒设置（仅本机）
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IdleNudgeSettings {
+    pub enabled: bool,
+    /// 超过多少分钟没有计时则提醒
+    pub idle_minutes: u32,
+    /// 周一到周日
+    pub days: [NudgeDay; 7],
+}
+
+impl Default for IdleNudgeSettings {
+    fn default() -> Self {
+        let day = |enabled| NudgeDay {
+            enabled,
+            work: "09:00-18:00".to_string(),
+            quiet: "12:00-13:30".to_string(),
+        };
+        Self {
+            enabled: false,
+            idle_minutes: 90,
+            days: [day(true), day(true), day(true), day(true), day(true), day(false), day(false)],
+        }
+    }
+}
+
+/// 工作时间：某一天是否上班与上班时段（`HH:MM-HH:MM`）
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WorkDay {
+    pub enabled: bool,
+    pub hours: String,
+}
+
+/// 按星期的工作时间设置（仅本机）
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkingHours {
+    pub enabled: bool,
+    /// 工作时间外不发提醒（日程、空闲、护眼、健康提醒与休息警示；阶段结束的通知照常）
+    pub quiet_after_hours: bool,
+    /// 工作时间外计时空闲时，主界面计时器处显示「下班了」
+    pub show_off_duty: bool,
+    /// 周一到周日
+    pub days: [WorkDay; 7],
+}
+
+impl Default for WorkingHours {
+    fn default() -> Self {
+        let day = |enabled| WorkDay {
+            enabled,
+            hours: "09:00-18:00".to_string(),
+        };
+        Self {
+            enabled: false,
+            quiet_after_hours: true,
+            show_off_duty: true,
+            days: [day(true), day(true), day(true), day(true), day(true), day(false), day(false)],
+        }
+    }
+}
+
+impl WorkingHours {
+    /// 某天的上班时段；不上班或时段格式错误为 None
+    fn hours_on(&self, date: NaiveDate) -> Option<(NaiveTime, NaiveTime)> {
+        let day = &self.days[date.weekday().num_days_from_monday() as usize];
+        day.enabled.then(|| parse_range(&day.hours)).flatten()
+    }
+
+    /// 该时刻（北京时间）是否在工作时间内；未开启时总是 true
+    pub fn contains(&self, at: NaiveDateTime) -> bool {
+        if !self.enabled {
+            return true;
+        }
+        self.hours_on(at.date())
+            .is_some_and(|(start, end)| at.time() >= start && at.time() < end)
+    }
+
+    /// 此刻是否应压下提醒
+    pub fn suppresses_reminders(&self, at: NaiveDateTime) -> bool {
+        self.quiet_after_hours && !self.contains(at)
+    }
+
+    /// 此刻是否显示「下班了」
+    pub fn off_duty(&self, at: NaiveDateTime) -> bool {
+        self.show_off_duty && !self.contains(at)
+    }
+
+    /// [start, end) 中落在工作时间内的秒数（可跨天）
+    pub fn secs_within(&self, start: NaiveDateTime, end: NaiveDateTime) -> i64 {
+        let mut total = 0;
+        let mut date = start.date();
+        while date <= end.date() {
+            if let Some((from, to)) = self.hours_on(date) {
+                let lo = start.max(date.and_time(from));
+                let hi = end.min(date.and_time(to));
+                total += (hi - lo).num_seconds().max(0);
+            }
+            let Some(next) = date.succ_opt() else { break };
+            date = next;
+        }
+        total
+    }
+}
+
+/// 周一到周日的简称（设置界面用）
+pub const WEEKDAY_LABELS: [&str; 7] = ["周一", "周二", "周三", "周四", "周五", "周六", "周日"];
+
+/// 解析 `09:00-18:00`；空串或格式错误为 None
+pub fn parse_range(text: &str) -> Option<(NaiveTime, NaiveTime)> {
+    let (start, end) = text.split_once(['-', '–'])?;
+    let start = NaiveTime::parse_from_str(start.trim(), "%H:%M").ok()?;
+    let end = NaiveTime::parse_from_str(end.trim(), "%H:%M").ok()?;
+    (start < end).then_some((start, end))
+}
+
+impl IdleNudgeSettings {
+    /// 现在是否应提醒：当天启用、处于工作时间且不在免打扰时段，且从 `since`（上次计时、上次提醒或启动时间中最晚者）
+    /// 起已空闲超过设定分钟数。空闲从当天上班时间与免打扰结束时间起算，不会一上班就因昨晚没有计时而提醒。
+    /// 返回空闲的分钟数
+    pub fn due(&self, now: DateTime<FixedOffset>, since: DateTime<FixedOffset>) -> Option<i64> {
+        if !self.enabled || self.idle_minutes == 0 {
+            return None;
+        }
+        let day = &self.days[now.weekday().num_days_from_monday() as usize];
+        if !day.enabled {
+            return None;
+        }
+        let (work_start, work_end) = parse_range(&day.work)?;
+        let time = now.time();
+        if time < work_start || time >= work_end {
+            return None;
+        }
+        let at = |t: NaiveTime| now.timezone().from_local_datetime(&now.date_naive().and_time(t)).single();
+        let mut since = since.max(at(work_start)?);
+        if let Some((quiet_start, quiet_end)) = parse_range(&day.quiet) {
+            if time >= quiet_start && time < quiet_end {
+                return None;
+            }
+            if time >= quiet_end {
+                since = since.max(at(quiet_end)?);
+            }
+        }
+        let idle = (now - since).num_minutes();
+        (idle >= self.idle_minutes as i64).then_some(idle)
+    }
+}
+
@@ -0,0 +1,155 @@
+//! 机构部署策略：启动时读取可选的全机策略文件，供 IT 部门统一预设
+//!
+//! 位置（存在才读取）：
+//! - Windows：`%ProgramData%\red-tomato\policy.json`
+//! - macOS：`/Library/Application Support/red-tomato/policy.json`
+//! - 其他：`/etc/red-tomato/policy.json`
+//!
+//! 可设置默认时长（用户未自行保存过时长时使用，用户设置叠加其上）、禁用的集成（强制关闭，用户无法开启）
+//! 以及固定的数据库路径、专注中电脑休眠的处理方式。文件格式错误时忽略策略并在「关于」中显示原因。
+
+use serde::Deserialize;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use crate::pomodoro::PomodoroConfig;
+use crate::suspend::SleepAction;
+
+const POLICY_FILENAME: &str = "policy.json";
+
+/// 可被策略禁用的集成
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Integration {
+    /// 设置云同步
+    Sync,
+    /// 专注记录同步（WebDAV / 同步文件夹）
+    DataSync,
+    /// Webhook 通知
+    Webhook,
+    /// 手机推送（ntfy / Telegram，含按钮回调）
+    PhonePush,
+    /// Discord Rich Presence
+    Discord,
+    /// OBS 文本输出
+    Obs,
+    /// 本机 HTTP 接口
+    HttpApi,
+    /// 日记笔记追加
+    DailyNote,
+    /// 读取 git 仓库推荐任务名
+    GitSuggest,
+    /// MQTT 发布
+    Mqtt,
+    /// Slack 专注状态与勿扰
+    Slack,
+}
+
+impl Integration {
+    pub fn label(self) -> &'static str {
+        match self {
+            Integration::Sync => "设置同步",
+            Integration::DataSync => "数据同步",
+            Integration::Webhook => "Webhook",
+            Integration::PhonePush => "手机推送",
+            Integration::Discord => "Discord",
+            Integration::Obs => "OBS 输出",
+            Integration::HttpApi => "HTTP 接口",
+            Integration::DailyNote => "日记笔记",
+            Integration::GitSuggest => "任务名建议",
+            Integration::Mqtt => "MQTT",
+            Integration::Slack => "Slack",
+        }
+    }
+}
+
+/// 策略文件内容（各项均可省略）
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Policy {
+    pub focus_minutes: Option<i64>,
+    pub short_break_minutes: Option<i64>,
+    pub long_break_minutes: Option<i64>,
+    pub pomodoros_before_long: Option<u32>,
+    pub disabled_integrations: Vec<Integration>,
+    /// 固定的 SQLite 文件路径（如网络盘上的个人目录）
+    pub db_path: Option<PathBuf>,
+    /// 固定专注中电脑休眠的处理方式："ask" / "count" / "discard" / "pause"
+    pub sleep_action: Option<SleepAction>,
+}
+
+impl Policy {
+    /// 策略给出的默认时长（未指定的项用内置默认值）
+    pub fn default_config(&self) -> PomodoroConfig {
+        let mut config = PomodoroConfig::default();
+        if let Some(m) = self.focus_minutes.filter(|m| *m > 0) {
+            config.focus_secs = m * 60;
+        }
+        if let Some(m) = self.short_break_minutes.filter(|m| *m > 0) {
+            config.short_break_secs = m * 60;
+        }
+        if let Some(m) = self.long_break_minutes.filter(|m| *m > 0) {
+            config.long_break_secs = m * 60;
+        }
+        if let Some(n) = self.pomodoros_before_long.filter(|n| *n > 0) {
+            config.pomodoros_before_long = n;
+        }
+        config
+    }
+
+    pub fn allows(&self, integration: Integration) -> bool {
+        !self.disabled_integrations.contains(&integration)
+    }
+}
+
+/// 启动时加载的策略及其来源
+#[derive(Debug, Default)]
+pub struct LoadedPolicy {
+    pub policy: Policy,
+    /// 成功读取的策略文件路径；None 表示未部署策略
+    pub path: Option<PathBuf>,
+    /// 策略文件存在但无法解析时的原因
+    pub error: Option<String>,
+}
+
+/// 全机策略文件路径
+pub fn policy_path() -> PathBuf {
+    #[cfg(windows)]
+    let dir = std::env::var_os("ProgramData")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(r"C:\ProgramData"))
+        .join("red-tomato");
+    #[cfg(target_os = "macos")]
+    let dir = PathBuf::from("/Library/Application Support/red-tomato");
+    #[cfg(not(any(windows, target_os = "macos")))]
+    let dir = PathBuf::from("/etc/red-tomato");
+    dir.join(POLICY_FILENAME)
+}
+
+/// 当前生效的策略（首次调用时读取，之后不变）
+pub fn loaded() -> &'static LoadedPolicy {
+    static POLICY: OnceLock<LoadedPolicy> = OnceLock::new();
+    POLICY.get_or_init(load)
+}
+
+pub fn get() -> &'static Policy {
+    &loaded().policy
+}
+
+fn load() -> LoadedPolicy {
+    let path = policy_path();
+    let Ok(text) = std::fs::read_to_string(&path) else {
+        return LoadedPolicy::default();
+    };
+    match serde_json::from_str::<Policy>(&text) {
+        Ok(policy) => LoadedPolicy {
+            policy,
+            path: Some(path),
+            error: None,
+        },
+        Err(e) => LoadedPolicy {
+            error: Some(format!("{} 格式错误，已忽略：{}", path.display(), e)),
+            ..Default::default()
+        },
+    }
+}
@@ -1,9 +1,10 @@
 //! 番茄工作法状态与计时逻辑
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, FixedOffset, NaiveTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
 
 /// 番茄钟阶段
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Phase {
     /// 专注工作（默认 25 分钟）
     Focus,
@@ -21,13 +22,32 @@ pub enum TimerState {
     Idle,
 }
 
+/// 手动调整后单个阶段剩余时间的上限（秒）
+pub const MAX_ADJUSTED_SECS: i64 = 4 * 3600;
+/// 推迟休息（「再专注 5 分钟」）一次延长的秒数
+pub const SNOOZE_SECS: i64 = 5 * 60;
+/// 专注结束前 / 休息开始后多少秒内可以推迟休息
+pub const SNOOZE_WINDOW_SECS: i64 = 60;
+/// 「专注到某个时刻」距现在至少多少秒（上限同 MAX_ADJUSTED_SECS）
+pub const MIN_UNTIL_SECS: i64 = 60;
+
 /// 番茄工作法配置（单位：秒）
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PomodoroConfig {
     pub focus_secs: i64,
     pub short_break_secs: i64,
     pub long_break_secs: i64,
     pub pomodoros_before_long: u32,
+    /// 加时模式：专注到 00:00 后不自动进入休息，继续正计时直到手动结束，超出部分计入本次专注
+    #[serde(default)]
+    pub overtime: bool,
+    /// 连续推迟休息的次数上限（0 表示不允许推迟）
+    #[serde(default = "default_max_snoozes")]
+    pub max_snoozes: u32,
+}
+
+fn default_max_snoozes() -> u32 {
+    2
 }
 
 impl Default for PomodoroConfig {
@@ -38,6 +58,8 @@ impl Default for PomodoroConfig {
             short_break_secs: 60*5,
             long_break_secs: 15*60,
             pomodoros_before_long: 4,
+            overtime: false,
+            max_snoozes: default_max_snoozes(),
 
             // focus_secs: 20,
             // short_break_secs: 5,
@@ -47,9 +69,124 @@ impl Default for PomodoroConfig {
     }
 }
 
+/// 例程中的一步：阶段类型与时长
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoutineStep {
+    pub phase: Phase,
+    pub minutes: u32,
+}
+
+/// 例程：命名的阶段序列（如 50/10 深度工作、90 分钟超昼夜节律块），走完最后一步后从头循环。
+/// 不选例程时按经典番茄（专注/短休息交替，每 N 个番茄一次长休息）
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Routine {
+    pub name: String,
+    pub steps: Vec<RoutineStep>,
+}
+
+/// 单个例程的步数上限
+pub const MAX_ROUTINE_STEPS: usize = 24;
+
+impl Routine {
+    /// 内置预设
+    pub fn presets() -> Vec<Routine> {
+        let step = |phase, minutes| RoutineStep { phase, minutes };
+        vec![
+            Routine {
+                name: "深度工作 50/10".to_string(),
+                steps: vec![step(Phase::Focus, 50), step(Phase::ShortBreak, 10)],
+            },
+            Routine {
+                name: "超昼夜节律 90/20".to_string(),
+                steps: vec![step(Phase::Focus, 90), step(Phase::LongBreak, 20)],
+            },
+            Routine {
+                name: "晨间例程".to_string(),
+                steps: vec![
+                    step(Phase::Focus, 25),
+                    step(Phase::ShortBreak, 5),
+                    step(Phase::Focus, 25),
+                    step(Phase::ShortBreak, 5),
+                    step(Phase::Focus, 50),
+                    step(Phase::LongBreak, 15),
+                ],
+            },
+        ]
+    }
+
+    /// 一轮中的专注步数（番茄数圆圈用）
+    pub fn focus_steps(&self) -> u32 {
+        self.steps.iter().filter(|s| s.phase == Phase::Focus).count() as u32
+    }
+
+    /// 序列摘要，如 `专注 50 → 短休息 10`
+    pub fn summary(&self) -> String {
+        self.steps
+            .iter()
+            .map(|s| format!("{} {}", phase_name(s.phase), s.minutes))
+            .collect::<Vec<_>>()
+            .join(" → ")
+    }
+}
+
+/// 阶段的中文名
+pub fn phase_name(phase: Phase) -> &'static str {
+    match phase {
+        Phase::Focus => "专注",
+        Phase::ShortBreak => "短休息",
+        Phase::LongBreak => "长休息",
+    }
+}
+
+/// 解析「专注到」的时刻（`HH:MM`，北京时间）为截止时间：已过的时刻视为明天；
+/// 距现在不足 MIN_UNTIL_SECS 或超过 MAX_ADJUSTED_SECS 时返回原因。
+/// 北京时间为固定偏移、没有夏令时，本地时刻换算成绝对时间不会遇到不存在或重复的时刻
+pub fn focus_deadline(text: &str, now: DateTime<FixedOffset>) -> Result<DateTime<Utc>, String> {
+    let time = NaiveTime::parse_from_str(text.trim(), "%H:%M").map_err(|_| "时刻格式应为 HH:MM，如 11:30".to_string())?;
+    let mut target = now.date_naive().and_time(time);
+    if target <= now.naive_local() {
+        target += Duration::days(1);
+    }
+    let deadline = now
+        .offset()
+        .from_local_datetime(&target)
+        .single()
+        .ok_or_else(|| "无法换算该时刻".to_string())?;
+    let secs = (deadline - now).num_seconds();
+    if secs < MIN_UNTIL_SECS {
+        return Err("距离该时刻不足 1 分钟".to_string());
+    }
+    if secs > MAX_ADJUSTED_SECS {
+        return Err(format!("最多专注到 {} 小时之后", MAX_ADJUSTED_SECS / 3600));
+    }
+    Ok(deadline.with_timezone(&Utc))
+}
+
+/// 外部控制命令（HTTP 接口、任务栏缩略图按钮），由界面按当前状态执行
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Command {
+    /// 开始（空闲时）或继续（暂停时）
+    Start,
+    Pause,
+    /// 跳过当前阶段
+    Skip,
+}
+
+/// 一个阶段内的暂停统计：次数与累计暂停秒数
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PauseStats {
+    pub count: u32,
+    pub secs: i64,
+}
+
 /// 番茄钟核心状态
+#[derive(Clone)]
 pub struct PomodoroState {
     pub config: PomodoroConfig,
+    /// 当前例程（None 为经典番茄）
+    pub routine: Option<Routine>,
+    /// 例程中当前步的下标
+    pub step: usize,
     pub phase: Phase,
     pub state: TimerState,
     pub remaining_secs: i64,
@@ -60,12 +197,30 @@ pub struct PomodoroState {
     pub finished_phase: Option<Phase>,
     /// 刚完成的一次专注的时长（秒），供记录历史用，取走后清空
     pub last_completed_focus_duration_secs: Option<i64>,
+    /// 当前阶段的暂停统计（开始新阶段时清零，阶段结束后保留到下次开始，供记录历史用）
+    pub pauses: PauseStats,
+    /// 本次暂停开始时间（暂停中才有）
+    pub paused_at: Option<DateTime<Utc>>,
+    /// 专注时间到后是否正在加时（加时模式下）
+    pub in_overtime: bool,
+    /// 本次专注的加时秒数（开始新阶段时清零，阶段结束后保留到下次开始，供记录历史用）
+    pub overtime_secs: i64,
+    /// 连续推迟休息的次数（走完一次休息或重置时清零）
+    pub snoozes: u32,
+    /// 推迟休息而进入的专注结束后要回到的休息阶段
+    pub snoozed_break: Option<Phase>,
+    /// 刚结束的推迟专注的时长（秒），供延长上一条专注记录用，取走后清空
+    pub last_snooze_secs: Option<i64>,
+    /// 「专注到 11:30」的截止时间：计时按它与当前时间之差推进；暂停、手动调整后变为普通倒计时
+    pub deadline: Option<DateTime<Utc>>,
 }
 
 impl Default for PomodoroState {
     fn default() -> Self {
         Self {
             config: PomodoroConfig::default(),
+            routine: None,
+            step: 0,
             phase: Phase::Focus,
             state: TimerState::Idle,
             remaining_secs: 0,
@@ -74,6 +229,14 @@ impl Default for PomodoroState {
             last_tick_at: None,
             finished_phase: None,
             last_completed_focus_duration_secs: None,
+            pauses: PauseStats::default(),
+            paused_at: None,
+            in_overtime: false,
+            overtime_secs: 0,
+            snoozes: 0,
+            snoozed_break: None,
+            last_snooze_secs: None,
+            deadline: None,
         }
     }
 }
@@ -86,55 +249,251 @@ impl PomodoroState {
         }
     }
 
+    /// 例程中的当前步（经典番茄或例程为空时为 None）
+    pub fn current_step(&self) -> Option<RoutineStep> {
+        self.routine.as_ref()?.steps.get(self.step).copied()
+    }
+
+    /// 一轮的番茄数：经典番茄为长休息间隔，例程为其中的专注步数
+    pub fn cycle_length(&self) -> u32 {
+        match &self.routine {
+            Some(routine) => routine.focus_steps(),
+            None => self.config.pomodoros_before_long,
+        }
+    }
+
+    /// 切换例程（None 回到经典番茄），从第一步开始并进入 Idle
+    pub fn set_routine(&mut self, routine: Option<Routine>) {
+        self.routine = routine.filter(|r| !r.steps.is_empty());
+        self.step = 0;
+        self.completed_pomodoros = 0;
+        self.snoozes = 0;
+        self.phase = self.current_step().map_or(Phase::Focus, |s| s.phase);
+        self.stop();
+    }
+
+    /// 当前例程的内容被修改后同步进来（同名例程）；步数变少时下标收回范围内，Idle 时阶段随之更新
+    pub fn refresh_routine(&mut self, routine: &Routine) {
+        let Some(current) = self.routine.as_mut() else { return };
+        if current.name != routine.name || current == routine || routine.steps.is_empty() {
+            return;
+        }
+        *current = routine.clone();
+        if self.step >= current.steps.len() {
+            self.step = 0;
+        }
+        if self.state == TimerState::Idle {
+            self.phase = current.steps[self.step].phase;
+        }
+    }
+
     /// 开始当前阶段
     pub fn start(&mut self) {
-        let total = match self.phase {
-            Phase::Focus => self.config.focus_secs,
-            Phase::ShortBreak => self.config.short_break_secs,
-            Phase::LongBreak => self.config.long_break_secs,
-        };
+        if let Some(step) = self.current_step() {
+            self.phase = step.phase;
+        }
+        self.begin(self.phase_secs());
+    }
+
+    /// 当前阶段（例程中为当前步）的设定时长（秒）
+    pub fn phase_secs(&self) -> i64 {
+        match self.current_step() {
+            Some(step) => step.minutes.max(1) as i64 * 60,
+            None => match self.phase {
+                Phase::Focus => self.config.focus_secs,
+                Phase::ShortBreak => self.config.short_break_secs,
+                Phase::LongBreak => self.config.long_break_secs,
+            },
+        }
+    }
+
+    /// 专注到 deadline：以与 now 的时间差为时长开始一次专注（不在专注阶段时先切到专注）
+    pub fn start_until(&mut self, deadline: DateTime<Utc>, now: DateTime<Utc>) {
+        if self.phase != Phase::Focus {
+            self.set_phase(Phase::Focus);
+        }
+        self.begin((deadline - now).num_seconds().clamp(1, MAX_ADJUSTED_SECS));
+        self.deadline = Some(deadline);
+    }
+
+    /// 以给定总时长开始当前阶段
+    fn begin(&mut self, total: i64) {
         self.phase_total_secs = total;
         self.remaining_secs = total;
         self.state = TimerState::Running;
         self.last_tick_at = Some(Utc::now());
+        self.pauses = PauseStats::default();
+        self.paused_at = None;
+        self.in_overtime = false;
+        self.overtime_secs = 0;
+        self.deadline = None;
     }
 
     /// 暂停 / 继续
     pub fn toggle_pause(&mut self) {
+        // 暂停后不再追赶截止时间，按剩余时间继续倒计时
+        self.deadline = None;
         match self.state {
             TimerState::Running => {
                 self.state = TimerState::Paused;
                 self.last_tick_at = None;
+                self.pauses.count += 1;
+                self.paused_at = Some(Utc::now());
             }
             TimerState::Paused => {
+                let now = Utc::now();
                 self.state = TimerState::Running;
-                self.last_tick_at = Some(Utc::now());
+                self.last_tick_at = Some(now);
+                if let Some(at) = self.paused_at.take() {
+                    self.pauses.secs += (now - at).num_seconds().max(0);
+                }
             }
             TimerState::Idle => {}
         }
     }
 
+    /// 系统休眠：计到休眠开始的时刻后暂停（不计暂停次数），等待决定休眠的时间如何计算。
+    /// 休眠前阶段已结束或不在运行时返回 false
+    pub fn hold_at(&mut self, at: DateTime<Utc>) -> bool {
+        if self.state != TimerState::Running {
+            return false;
+        }
+        self.tick(at);
+        if self.state != TimerState::Running {
+            return false;
+        }
+        self.state = TimerState::Paused;
+        self.last_tick_at = None;
+        self.paused_at = Some(at);
+        true
+    }
+
+    /// 结束休眠暂停：count_gap 时从暂停的时刻接着计时（休眠的时间计入本阶段），否则从现在开始
+    pub fn release_hold(&mut self, count_gap: bool) {
+        let Some(at) = self.paused_at.take() else { return };
+        if !count_gap {
+            self.deadline = None;
+        }
+        self.state = TimerState::Running;
+        self.last_tick_at = Some(if count_gap { at } else { Utc::now() });
+    }
+
     /// 停止当前阶段，回到 Idle
     pub fn stop(&mut self) {
         self.state = TimerState::Idle;
         self.remaining_secs = 0;
         self.phase_total_secs = 0;
         self.last_tick_at = None;
+        self.pauses = PauseStats::default();
+        self.paused_at = None;
+        self.in_overtime = false;
+        self.overtime_secs = 0;
+        self.snoozed_break = None;
+        self.deadline = None;
+    }
+
+    /// 现在能否推迟休息：未达连续上限，且专注还剩不到一分钟，或休息待开始 / 刚开始不到一分钟（加时中不需要）
+    pub fn can_snooze(&self) -> bool {
+        if self.snoozes >= self.config.max_snoozes || self.in_overtime {
+            return false;
+        }
+        match (self.phase, self.state) {
+            (Phase::Focus, TimerState::Idle) => false,
+            (Phase::Focus, _) => self.remaining_secs <= SNOOZE_WINDOW_SECS,
+            (_, TimerState::Idle) => true,
+            _ => self.phase_total_secs - self.remaining_secs < SNOOZE_WINDOW_SECS,
+        }
+    }
+
+    /// 推迟休息（「再专注 5 分钟」），不计番茄数：专注快结束时延长本次专注；
+    /// 休息待开始或刚开始时回到专注 SNOOZE_SECS，结束后回到这次休息。返回是否推迟成功
+    pub fn snooze(&mut self) -> bool {
+        if !self.can_snooze() {
+            return false;
+        }
+        self.snoozes += 1;
+        if self.phase == Phase::Focus {
+            self.adjust_remaining(SNOOZE_SECS);
+            return true;
+        }
+        let break_phase = self.phase;
+        self.phase = Phase::Focus;
+        self.begin(SNOOZE_SECS);
+        self.snoozed_break = Some(break_phase);
+        true
+    }
+
+    /// 取走刚结束的推迟专注的时长（秒），取走后清空
+    pub fn take_snooze_finished(&mut self) -> Option<i64> {
+        self.last_snooze_secs.take()
+    }
+
+    /// 结束加时：本次专注按完成处理，时长含加时部分（不在加时中时不做任何事）
+    pub fn finish_overtime(&mut self) {
+        if !self.in_overtime {
+            return;
+        }
+        self.in_overtime = false;
+        self.phase_total_secs += self.overtime_secs;
+        self.on_phase_finished();
+    }
+
+    /// 当前阶段的暂停统计（暂停中时计入截至 now 的这次暂停）
+    pub fn pause_stats(&self, now: DateTime<Utc>) -> PauseStats {
+        let open = self.paused_at.map_or(0, |at| (now - at).num_seconds().max(0));
+        PauseStats {
+            count: self.pauses.count,
+            secs: self.pauses.secs + open,
+        }
     }
 
-    /// 重置番茄数、阶段回到专注，并停止（用于「重置」/「完成」按钮）
+    /// 重置番茄数、阶段回到专注（例程回到第一步），并停止（用于「重置」/「完成」按钮）
     pub fn reset_pomodoros_and_stop(&mut self) {
         self.completed_pomodoros = 0;
-        self.phase = Phase::Focus;
+        self.snoozes = 0;
+        self.step = 0;
+        self.phase = self.current_step().map_or(Phase::Focus, |s| s.phase);
         self.stop();
     }
 
-    /// 选择阶段并进入 Idle（用户可再点开始）
+    /// 选择阶段并进入 Idle（用户可再点开始）。
+    /// 例程中跳到从当前步起下一个该类型的步；例程里没有该阶段时退出例程
     pub fn set_phase(&mut self, phase: Phase) {
+        if let Some(routine) = &self.routine {
+            let len = routine.steps.len();
+            match (0..len).map(|i| (self.step + i) % len).find(|&i| routine.steps[i].phase == phase) {
+                Some(i) => self.step = i,
+                None => self.routine = None,
+            }
+        }
         self.phase = phase;
         self.stop();
     }
 
+    /// 跳到例程中的某一步并进入 Idle
+    pub fn set_step(&mut self, step: usize) {
+        let Some(s) = self.routine.as_ref().and_then(|r| r.steps.get(step)).copied() else { return };
+        self.step = step;
+        self.phase = s.phase;
+        self.stop();
+    }
+
+    /// 运行中/暂停时调整当前阶段剩余时间（如会议超时需延长）。
+    /// 剩余时间限制在 1 秒..=MAX_ADJUSTED_SECS，总时长同步增减以保持已用时间不变，进度随之重算。
+    /// 返回实际调整的秒数（Idle 或加时中不调整，返回 0）
+    pub fn adjust_remaining(&mut self, delta_secs: i64) -> i64 {
+        if self.state == TimerState::Idle || self.in_overtime {
+            return 0;
+        }
+        let old_remaining = self.remaining_secs.max(0);
+        let new_remaining = (old_remaining + delta_secs).clamp(1, MAX_ADJUSTED_SECS);
+        let applied = new_remaining - old_remaining;
+        self.deadline = None;
+        self.remaining_secs = new_remaining;
+        self.phase_total_secs = (self.phase_total_secs + applied).max(new_remaining);
+        applied
+    }
+
     /// 每秒由 UI 调用，推进计时并处理阶段结束
     pub fn tick(&mut self, now: DateTime<Utc>) {
         if self.state != TimerState::Running {
@@ -146,25 +505,61 @@ impl PomodoroState {
             return;
         }
         self.last_tick_at = Some(now);
-        self.remaining_secs = (self.remaining_secs - elapsed).max(0);
+        if self.in_overtime {
+            self.overtime_secs += elapsed;
+            return;
+        }
+        let remaining = match self.deadline {
+            // 按截止时间对齐；系统时间被往回调时不超过本阶段总时长
+            Some(deadline) => (deadline - now).num_seconds().min(self.phase_total_secs),
+            None => self.remaining_secs - elapsed,
+        };
+        self.remaining_secs = remaining.max(0);
 
-        if self.remaining_secs <= 0 {
-            self.on_phase_finished();
+        if remaining <= 0 {
+            if self.phase == Phase::Focus && self.config.overtime {
+                // 加时模式：继续正计时，超出的秒数计入加时
+                self.in_overtime = true;
+                self.overtime_secs = -remaining;
+            } else {
+                self.on_phase_finished();
+            }
         }
     }
 
     fn on_phase_finished(&mut self) {
+        self.deadline = None;
         let just_finished = self.phase;
         let total_secs = self.phase_total_secs;
-        self.finished_phase = Some(just_finished);
         self.state = TimerState::Idle;
         self.remaining_secs = 0;
         self.phase_total_secs = 0;
         self.last_tick_at = None;
+        if let Some(break_phase) = self.snoozed_break.take() {
+            // 推迟休息的专注：不计番茄数，回到原来的休息
+            self.last_snooze_secs = Some(total_secs);
+            self.phase = break_phase;
+            return;
+        }
+        self.finished_phase = Some(just_finished);
         if just_finished == Phase::Focus {
             self.last_completed_focus_duration_secs = Some(total_secs);
+        } else {
+            self.snoozes = 0;
         }
 
+        if let Some(routine) = &self.routine {
+            // 例程：进入下一步，走完一轮后从头开始，番茄数随之清零
+            if just_finished == Phase::Focus {
+                self.completed_pomodoros += 1;
+            }
+            self.step = (self.step + 1) % routine.steps.len();
+            if self.step == 0 {
+                self.completed_pomodoros = 0;
+            }
+            self.phase = routine.steps[self.step].phase;
+            return;
+        }
         match self.phase {
             Phase::Focus => {
                 self.completed_pomodoros += 1;
@@ -181,8 +576,11 @@ impl PomodoroState {
         }
     }
 
-    /// 剩余时间格式化为 "MM:SS"
+    /// 剩余时间格式化为 "MM:SS"；加时中为已加时的 "+MM:SS"
     pub fn remaining_display(&self) -> String {
+        if self.in_overtime {
+            return format!("+{:02}:{:02}", self.overtime_secs / 60, self.overtime_secs % 60);
+        }
         let s = self.remaining_secs.max(0);
         let m = s / 60;
         let s = s % 60;
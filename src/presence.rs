@@ -0,0 +1,233 @@
+//! Discord Rich Presence：专注时在 Discord 个人状态显示「Focusing: <任务>」与剩余时间，休息/空闲时清除
+//!
+//! 通过本机 Discord 客户端的 IPC（Unix 套接字 / Windows 命名管道 `discord-ipc-N`）通信，
+//! 帧格式为 `[opcode u32 LE][长度 u32 LE][JSON]`。连接、握手与发送都在后台线程，Discord 未运行时静默重试。
+//! 剩余时间用 `timestamps.end` 交给 Discord 自己倒计时，界面只在任务/阶段/结束时间变化时才发送更新。
+
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::time::Duration;
+
+/// Discord 未运行或连接断开时的重试间隔
+const RETRY_INTERVAL: Duration = Duration::from_secs(15);
+/// 结束时间变化超过此秒数才重新发送（避免每帧取整误差导致频繁更新）
+const END_TOLERANCE_SECS: i64 = 2;
+/// Discord 应用中上传的大图资源键
+const LARGE_IMAGE_KEY: &str = "tomato";
+
+const OP_HANDSHAKE: u32 = 0;
+const OP_FRAME: u32 = 1;
+
+/// Rich Presence 设置（隐私考虑默认关闭；仅本机）
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DiscordSettings {
+    pub enabled: bool,
+    /// Discord 开发者后台创建的应用 ID（需在该应用的 Rich Presence 资源中上传键名为 tomato 的图标）
+    pub client_id: String,
+}
+
+impl DiscordSettings {
+    pub fn is_active(&self) -> bool {
+        self.enabled && !self.client_id.trim().is_empty()
+    }
+}
+
+/// 要显示的状态
+#[derive(Clone, Debug, PartialEq)]
+pub struct Activity {
+    pub details: String,
+    pub state: Option<String>,
+    /// 倒计时结束时间（Unix 秒），None 表示不显示倒计时（如暂停）
+    pub end: Option<i64>,
+}
+
+impl Activity {
+    /// 与已发送的状态是否等价（结束时间允许少量误差）
+    fn same_as(&self, other: &Activity) -> bool {
+        let end_close = match (self.end, other.end) {
+            (Some(a), Some(b)) => (a - b).abs() <= END_TOLERANCE_SECS,
+            (None, None) => true,
+            _ => false,
+        };
+        self.details == other.details && self.state == other.state && end_close
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let mut activity = serde_json::json!({
+            "details": self.details,
+            "assets": { "large_image": LARGE_IMAGE_KEY, "large_text": "红番茄" },
+        });
+        if let Some(state) = &self.state {
+            activity["state"] = state.clone().into();
+        }
+        if let Some(end) = self.end {
+            activity["timestamps"] = serde_json::json!({ "end": end });
+        }
+        activity
+    }
+}
+
+/// Rich Presence 句柄：持有后台线程的发送端，丢弃时线程清除状态后退出
+#[derive(Default)]
+pub struct DiscordPresence {
+    worker: Option<(String, Sender<Option<Activity>>)>,
+    /// 最近一次交给后台线程的状态
+    last: Option<Option<Activity>>,
+}
+
+impl DiscordPresence {
+    /// 每帧调用：按设置启停后台线程，状态变化时发送
+    pub fn sync(&mut self, settings: &DiscordSettings, desired: Option<Activity>) {
+        if !settings.is_active() {
+            self.stop();
+            return;
+        }
+        let client_id = settings.client_id.trim();
+        if self.worker.as_ref().is_some_and(|(id, _)| id != client_id) {
+            self.stop();
+        }
+        if self.worker.is_none() {
+            let (tx, rx) = mpsc::channel();
+            let id = client_id.to_string();
+            std::thread::spawn(move || run_worker(&id, rx));
+            self.worker = Some((client_id.to_string(), tx));
+            self.last = None;
+        }
+        let changed = match (&self.last, &desired) {
+            (Some(Some(a)), Some(b)) => !a.same_as(b),
+            (Some(None), None) => false,
+            _ => true,
+        };
+        if changed {
+            if let Some((_, tx)) = &self.worker {
+                let _ = tx.send(desired.clone());
+            }
+            self.last = Some(desired);
+        }
+    }
+
+    /// 丢弃发送端：后台线程收到断开后清除状态并退出
+    fn stop(&mut self) {
+        self.worker = None;
+        self.last = None;
+    }
+}
+
+/// 后台线程：维持连接，把最新状态发给 Discord；发送端断开时清除状态并退出
+fn run_worker(client_id: &str, rx: mpsc::Receiver<Option<Activity>>) {
+    let mut conn: Option<Box<dyn IpcStream>> = None;
+    let mut desired: Option<Activity> = None;
+    let mut sent = false;
+    let mut nonce: u64 = 0;
+    loop {
+        let closed = match rx.recv_timeout(RETRY_INTERVAL) {
+            Ok(activity) => {
+                desired = activity;
+                sent = false;
+                false
+            }
+            Err(RecvTimeoutError::Timeout) => false,
+            Err(RecvTimeoutError::Disconnected) => {
+                desired = None;
+                sent = false;
+                true
+            }
+        };
+        if !sent {
+            if conn.is_none() {
+                conn = connect(client_id);
+            }
+            if let Some(stream) = conn.as_mut() {
+                nonce += 1;
+                let payload = serde_json::json!({
+                    "cmd": "SET_ACTIVITY",
+                    "args": {
+                        "pid": std::process::id(),
+                        "activity": desired.as_ref().map(Activity::to_json),
+                    },
+                    "nonce": nonce.to_string(),
+                });
+                if send_frame(stream, OP_FRAME, &payload).and_then(|_| read_frame(stream)).is_ok() {
+                    sent = true;
+                } else {
+                    conn = None;
+                }
+            }
+        }
+        if closed {
+            return;
+        }
+    }
+}
+
+trait IpcStream: Read + Write + Send {}
+impl<T: Read + Write + Send> IpcStream for T {}
+
+/// 依次尝试 discord-ipc-0..9 并握手
+fn connect(client_id: &str) -> Option<Box<dyn IpcStream>> {
+    for i in 0..10 {
+        if let Some(mut stream) = open_ipc(i) {
+            let handshake = serde_json::json!({ "v": 1, "client_id": client_id });
+            if send_frame(&mut stream, OP_HANDSHAKE, &handshake).and_then(|_| read_frame(&mut stream)).is_ok() {
+                return Some(stream);
+            }
+        }
+    }
+    None
+}
+
+#[cfg(windows)]
+fn open_ipc(i: u32) -> Option<Box<dyn IpcStream>> {
+    let pipe = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(format!(r"\\?\pipe\discord-ipc-{}", i))
+        .ok()?;
+    Some(Box::new(pipe))
+}
+
+#[cfg(unix)]
+fn open_ipc(i: u32) -> Option<Box<dyn IpcStream>> {
+    let mut bases: Vec<std::path::PathBuf> = ["XDG_RUNTIME_DIR", "TMPDIR", "TMP", "TEMP"]
+        .iter()
+        .filter_map(|v| std::env::var_os(v).map(std::path::PathBuf::from))
+        .collect();
+    bases.push(std::path::PathBuf::from("/tmp"));
+    // Flatpak / Snap 版 Discord 的套接字在子目录中
+    let subdirs = ["", "app/com.discordapp.Discord", "snap.discord"];
+    for base in &bases {
+        for sub in subdirs {
+            let path = base.join(sub).join(format!("discord-ipc-{}", i));
+            if let Ok(stream) = std::os::unix::net::UnixStream::connect(&path) {
+                let _ = stream.set_read_timeout(Some(Duration::from_secs(5)));
+                return Some(Box::new(stream));
+            }
+        }
+    }
+    None
+}
+
+#[cfg(not(any(windows, unix)))]
+fn open_ipc(_i: u32) -> Option<Box<dyn IpcStream>> {
+    None
+}
+
+fn send_frame(stream: &mut Box<dyn IpcStream>, op: u32, payload: &serde_json::Value) -> std::io::Result<()> {
+    let body = payload.to_string();
+    let mut frame = Vec::with_capacity(8 + body.len());
+    frame.extend_from_slice(&op.to_le_bytes());
+    frame.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    frame.extend_from_slice(body.as_bytes());
+    stream.write_all(&frame)?;
+    stream.flush()
+}
+
+/// 读取并丢弃一帧回复（不读会让 Discord 端缓冲区堆积）
+fn read_frame(stream: &mut Box<dyn IpcStream>) -> std::io::Result<()> {
+    let mut header = [0u8; 8];
+    stream.read_exact(&mut header)?;
+    let len = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as usize;
+    let mut body = vec![0u8; len];
+    stream.read_exact(&mut body)
+}
@@ -0,0 +1,90 @@
+//! 配置档：「工作」「学习」等命名的设置组合（主题、字体、时长、强调色、每日目标），可选只统计某个项目
+//!
+//! 当前生效的设置始终是界面上的那一份；切换时先把它存回当前配置档，再应用目标配置档。
+//! 外观与时长沿用设置同步的 `SyncedSettings`，两处保存的是同一组设置。
+
+use serde::{Deserialize, Serialize};
+
+use crate::sync::SyncedSettings;
+
+/// 配置档名称的最大字符数
+pub const MAX_NAME_CHARS: usize = 16;
+
+/// 一个配置档
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    pub settings: SyncedSettings,
+    /// 每日番茄目标（0 表示不设目标）
+    #[serde(default)]
+    pub daily_goal: u32,
+    /// 统计范围：Some 时切换到此配置档即选中该项目，统计窗口与每日目标只计该项目的专注
+    #[serde(default)]
+    pub stats_project: Option<i64>,
+}
+
+/// 全部配置档与当前生效的下标
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profiles {
+    pub list: Vec<Profile>,
+    pub active: usize,
+}
+
+impl Profiles {
+    /// 还没有配置档时以当前设置建立「默认」
+    pub fn ensure_default(&mut self, settings: SyncedSettings, daily_goal: u32) {
+        if self.list.is_empty() {
+            self.list.push(Profile {
+                name: "默认".to_string(),
+                settings,
+                daily_goal,
+                stats_project: None,
+            });
+        }
+        self.active = self.active.min(self.list.len() - 1);
+    }
+
+    pub fn active(&self) -> Option<&Profile> {
+        self.list.get(self.active)
+    }
+
+    pub fn active_mut(&mut self) -> Option<&mut Profile> {
+        self.list.get_mut(self.active)
+    }
+
+    /// 以当前配置档为模板新建一个（名称去空白后不能为空、过长或重名），返回其下标
+    pub fn add(&mut self, name: &str) -> Result<usize, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("名称不能为空".to_string());
+        }
+        if name.chars().count() > MAX_NAME_CHARS {
+            return Err(format!("名称不能超过 {} 个字", MAX_NAME_CHARS));
+        }
+        if self.list.iter().any(|p| p.name == name) {
+            return Err("已有同名配置档".to_string());
+        }
+        let template = self.active().cloned().ok_or("没有可复制的配置档")?;
+        self.list.push(Profile {
+            name: name.to_string(),
+            ..template
+        });
+        Ok(self.list.len() - 1)
+    }
+
+    /// 删除一个非当前的配置档（至少保留一个）
+    pub fn remove(&mut self, index: usize) -> Result<(), String> {
+        if index == self.active || self.list.len() <= 1 {
+            return Err("不能删除正在使用的配置档".to_string());
+        }
+        if index >= self.list.len() {
+            return Ok(());
+        }
+        self.list.remove(index);
+        if index < self.active {
+            self.active -= 1;
+        }
+        Ok(())
+    }
+}
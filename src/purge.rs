@@ -0,0 +1,160 @@
+//! 数据清除：按项目 / 日期区间彻底删除记录，或清空全部本地数据，供需要清理敏感客户数据的用户使用
+//!
+//! 清除前先回放预写日志，保证尚未写入 SQLite 的记录不会在下次启动时被补写回来；删除后整理数据库文件，
+//! 被删内容不残留在空闲页中；归档库中的记录按同样的范围一并删除。可选同时删除导出的报告（报告按周期汇总，无法只删其中部分内容）。
+//! 写入外部日记笔记的内容属于用户自己的文件，不在清除范围内。
+
+use chrono::{Duration, NaiveDate};
+
+use crate::db::{self, RecordFilter};
+use crate::app::beijing_day_start_rfc3339;
+
+/// 执行清除前需输入的确认短语
+pub const CONFIRM_PHRASE: &str = "永久删除";
+
+/// 按范围清除：项目与日期区间（北京时间，含首尾两天）至少指定一项
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PurgeScope {
+    pub project_id: Option<i64>,
+    pub days: Option<(NaiveDate, NaiveDate)>,
+    /// 同时删除报告目录中导出的全部报告
+    pub include_reports: bool,
+}
+
+impl PurgeScope {
+    pub fn is_empty(&self) -> bool {
+        self.project_id.is_none() && self.days.is_none()
+    }
+
+    /// 日期区间对应的 [from, to) 时间戳字符串与 `YYYY-MM-DD` 边界
+    fn bounds(&self) -> Option<(String, String, String, String)> {
+        let (first, last) = self.days?;
+        let end = last + Duration::days(1);
+        Some((
+            beijing_day_start_rfc3339(first),
+            beijing_day_start_rfc3339(end),
+            first.format("%Y-%m-%d").to_string(),
+            end.format("%Y-%m-%d").to_string(),
+        ))
+    }
+}
+
+/// 清除结果：专注记录条数、其余数据行数、删除的文件数
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PurgeOutcome {
+    pub records: usize,
+    pub other_rows: usize,
+    pub files: usize,
+}
+
+impl PurgeOutcome {
+    pub fn describe(&self) -> String {
+        format!(
+            "已删除 {} 条专注记录、{} 条其他数据、{} 个文件",
+            self.records, self.other_rows, self.files
+        )
+    }
+}
+
+/// 符合范围的专注记录条数（含预写日志中尚未写入的记录与归档库中的记录）
+pub fn preview(scope: &PurgeScope) -> Result<u32, rusqlite::Error> {
+    let mut conn = db::open_and_init()?;
+    crate::wal::replay(&mut conn)?;
+    let bounds = scope.bounds();
+    let filter = RecordFilter {
+        project_id: scope.project_id,
+        range: bounds.as_ref().map(|(from, to, _, _)| (from.as_str(), to.as_str())),
+    };
+    let mut count = db::count_matching_records(&conn, &filter)?;
+    if let Some(archive) = db::open_archive()? {
+        count += db::count_matching_records(&archive, &filter)?;
+    }
+    Ok(count)
+}
+
+/// 按范围清除。只按日期时同时删除区间内的中断、回顾日志、计划、日历、通知摘要与精力自评；
+/// 只按项目时删除该项目本身（「未分类」除外）
+pub fn purge(scope: &PurgeScope) -> Result<PurgeOutcome, String> {
+    if scope.is_empty() {
+        return Err("请选择项目或日期区间".to_string());
+    }
+    let mut conn = db::open_and_init().map_err(|e| e.to_string())?;
+    crate::wal::replay(&mut conn).map_err(|e| format!("回放预写日志失败：{}", e))?;
+    let bounds = scope.bounds();
+    let filter = RecordFilter {
+        project_id: scope.project_id,
+        range: bounds.as_ref().map(|(from, to, _, _)| (from.as_str(), to.as_str())),
+    };
+    let mut outcome = PurgeOutcome::default();
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    outcome.records = db::delete_matching_records(&tx, &filter).map_err(|e| e.to_string())?;
+    match (scope.project_id, &bounds) {
+        (None, Some((from, to, from_day, to_day))) => {
+            outcome.other_rows =
+                db::delete_activity_between(&tx, from, to, from_day, to_day).map_err(|e| e.to_string())?;
+        }
+        (Some(id), None) if id != db::UNCATEGORIZED_PROJECT_ID => {
+            outcome.other_rows = tx
+                .execute("DELETE FROM projects WHERE id = ?1", rusqlite::params![id])
+                .map_err(|e| e.to_string())?;
+        }
+        _ => {}
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    db::vacuum(&conn).map_err(|e| format!("整理数据库失败：{}", e))?;
+    if let Some(archive) = db::open_archive().map_err(|e| e.to_string())? {
+        outcome.records += db::delete_matching_records(&archive, &filter).map_err(|e| e.to_string())?;
+        db::vacuum(&archive).map_err(|e| format!("整理归档库失败：{}", e))?;
+    }
+    if scope.include_reports {
+        outcome.files = remove_dir_files(&crate::review::reports_dir())?;
+    }
+    Ok(outcome)
+}
+
+/// 清空全部本地数据：数据库与归档库文件（含 SQLite 临时日志）、预写日志、进行中会话的日志、导出的报告与 OBS 输出文件。
+/// 下次访问数据库时会重新建一个空库
+pub fn purge_everything() -> Result<PurgeOutcome, String> {
+    let mut outcome = PurgeOutcome::default();
+    let all = RecordFilter { project_id: None, range: None };
+    if let Ok(conn) = db::open_and_init() {
+        outcome.records = db::count_matching_records(&conn, &all).unwrap_or(0) as usize;
+    }
+    if let Ok(Some(archive)) = db::open_archive() {
+        outcome.records += db::count_matching_records(&archive, &all).unwrap_or(0) as usize;
+    }
+    let mut files = vec![crate::wal::journal_path(), crate::session::session_path(), crate::obs::default_path()];
+    for db_file in [db::db_path(), db::archive_path()] {
+        for suffix in ["", "-journal", "-wal", "-shm"] {
+            let mut name = db_file.clone().into_os_string();
+            name.push(suffix);
+            files.push(name.into());
+        }
+    }
+    for path in files {
+        match std::fs::remove_file(&path) {
+            Ok(()) => outcome.files += 1,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(format!("无法删除 {}：{}", path.display(), e)),
+        }
+    }
+    outcome.files += remove_dir_files(&crate::review::reports_dir())?;
+    // 加密参数随数据库一起删除，新库为明文
+    crate::crypto::set_enabled(false);
+    crate::crypto::set_key(None);
+    Ok(outcome)
+}
+
+/// 删除目录下的全部文件，返回删除数量；目录不存在时为 0
+fn remove_dir_files(dir: &std::path::Path) -> Result<usize, String> {
+    let Ok(entries) = std::fs::read_dir(dir) else { return Ok(0) };
+    let mut removed = 0;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_file() {
+            std::fs::remove_file(&path).map_err(|e| format!("无法删除 {}：{}", path.display(), e))?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
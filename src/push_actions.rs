@@ -0,0 +1,270 @@
+//! 手机推送上的操作按钮：在手机 / 手表上点「开始下一阶段」「+5 分钟」遥控计时
+//!
+//! - ntfy：推送附带 `http` 动作，点按后由 ntfy 应用把 `red-tomato:<动作>` 发回同一主题；
+//!   后台线程轮询该主题（`/json?poll=1&since=…`）取回这些消息。
+//! - Telegram：消息附带内联键盘，点按产生 callback_query；后台线程用 `getUpdates` 长轮询取回并应答。
+//!
+//! 两种方式都只需出站请求，无需本机对外开放端口。取回的动作经通道交给 UI，由 UI 每帧执行。
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::notify::NotificationRouting;
+
+/// ntfy 主题轮询间隔
+const NTFY_POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// Telegram getUpdates 长轮询等待秒数
+const TELEGRAM_LONG_POLL_SECS: u64 = 20;
+/// 请求失败后的重试间隔
+const RETRY_INTERVAL: Duration = Duration::from_secs(30);
+/// 发回 ntfy 主题的消息前缀，用来与普通推送区分
+const NTFY_ACTION_PREFIX: &str = "red-tomato:";
+
+/// 推送按钮对应的动作
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PushAction {
+    /// 开始下一阶段（阶段结束后处于空闲时）
+    StartNext,
+    /// 再给 5 分钟：进行中则延长，空闲则 5 分钟后再开始下一阶段
+    AddFiveMinutes,
+}
+
+impl PushAction {
+    pub const ALL: [PushAction; 2] = [PushAction::StartNext, PushAction::AddFiveMinutes];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PushAction::StartNext => "▶ 开始下一阶段",
+            PushAction::AddFiveMinutes => "+5 分钟",
+        }
+    }
+
+    /// 回传数据（ntfy 消息正文后缀 / Telegram callback_data）
+    fn key(self) -> &'static str {
+        match self {
+            PushAction::StartNext => "start_next",
+            PushAction::AddFiveMinutes => "add_5min",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|a| a.key() == key)
+    }
+}
+
+/// 把 ntfy 主题地址拆成 (服务器地址, 主题名)，如 `https://ntfy.sh/abc` → (`https://ntfy.sh`, `abc`)
+pub fn split_ntfy_url(url: &str) -> Option<(&str, &str)> {
+    let (base, topic) = url.trim().trim_end_matches('/').rsplit_once('/')?;
+    (!topic.is_empty() && base.contains("://")).then_some((base, topic))
+}
+
+/// ntfy JSON 发布体：正文 + 操作按钮（点按后向同一主题 POST `red-tomato:<动作>`）
+pub fn ntfy_publish_body(topic_url: &str, topic: &str, message: &str) -> String {
+    let actions: Vec<serde_json::Value> = PushAction::ALL
+        .into_iter()
+        .map(|a| {
+            serde_json::json!({
+                "action": "http",
+                "label": a.label(),
+                "url": topic_url.trim(),
+                "method": "POST",
+                "body": format!("{}{}", NTFY_ACTION_PREFIX, a.key()),
+                "clear": true,
+            })
+        })
+        .collect();
+    serde_json::json!({
+        "topic": topic,
+        "title": "Red Tomato",
+        "message": message,
+        "actions": actions,
+    })
+    .to_string()
+}
+
+/// Telegram sendMessage 请求体；with_actions 时附带内联键盘
+pub fn telegram_message_body(chat_id: &str, message: &str, with_actions: bool) -> String {
+    let mut body = serde_json::json!({ "chat_id": chat_id.trim(), "text": message });
+    if with_actions {
+        let buttons: Vec<serde_json::Value> = PushAction::ALL
+            .into_iter()
+            .map(|a| serde_json::json!({ "text": a.label(), "callback_data": a.key() }))
+            .collect();
+        body["reply_markup"] = serde_json::json!({ "inline_keyboard": [buttons] });
+    }
+    body.to_string()
+}
+
+/// Telegram Bot API 地址
+pub fn telegram_api_url(token: &str, method: &str) -> String {
+    format!("https://api.telegram.org/bot{}/{}", token.trim(), method)
+}
+
+/// 决定要运行哪些轮询线程的配置（变化时重启线程）
+#[derive(Clone, Debug, PartialEq)]
+struct ListenConfig {
+    /// ntfy 主题地址与访问令牌（可为空）
+    ntfy: Option<(String, String)>,
+    telegram: Option<(String, String)>,
+}
+
+impl ListenConfig {
+    fn from_routing(routing: &NotificationRouting) -> Option<Self> {
+        if !routing.push_actions {
+            return None;
+        }
+        let ntfy = Some(routing.push_url.trim().to_string())
+            .filter(|u| split_ntfy_url(u).is_some())
+            .map(|u| (u, routing.push_token.trim().to_string()));
+        let telegram = routing
+            .telegram_active()
+            .then(|| (routing.telegram_token.trim().to_string(), routing.telegram_chat_id.trim().to_string()));
+        (ntfy.is_some() || telegram.is_some()).then_some(Self { ntfy, telegram })
+    }
+}
+
+/// 按钮回调接收器：持有轮询线程的停止标志与动作通道，丢弃时线程在下一轮退出
+#[derive(Default)]
+pub struct PushActionListener {
+    running: Option<(ListenConfig, Receiver<PushAction>, Arc<AtomicBool>)>,
+}
+
+impl PushActionListener {
+    /// 每帧调用：按设置启停轮询线程，取回自上次以来收到的动作
+    pub fn sync(&mut self, routing: &NotificationRouting) -> Vec<PushAction> {
+        let desired = ListenConfig::from_routing(routing);
+        if self.running.as_ref().map(|(c, _, _)| c) != desired.as_ref() {
+            self.stop();
+            if let Some(config) = desired {
+                let (tx, rx) = mpsc::channel();
+                let stop = Arc::new(AtomicBool::new(false));
+                if let Some((url, token)) = config.ntfy.clone() {
+                    let (tx, stop) = (tx.clone(), Arc::clone(&stop));
+                    std::thread::spawn(move || poll_ntfy(&url, &token, &tx, &stop));
+                }
+                if let Some((token, chat_id)) = config.telegram.clone() {
+                    let (tx, stop) = (tx.clone(), Arc::clone(&stop));
+                    std::thread::spawn(move || poll_telegram(&token, &chat_id, &tx, &stop));
+                }
+                self.running = Some((config, rx, stop));
+            }
+        }
+        match &self.running {
+            Some((_, rx, _)) => rx.try_iter().collect(),
+            None => Vec::new(),
+        }
+    }
+
+    fn stop(&mut self) {
+        if let Some((_, _, stop)) = self.running.take() {
+            stop.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+impl Drop for PushActionListener {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn agent(timeout: Duration) -> ureq::Agent {
+    ureq::Agent::config_builder()
+        .timeout_global(Some(timeout))
+        .build()
+        .into()
+}
+
+/// 轮询 ntfy 主题（受保护的主题带访问令牌）；从线程启动时刻开始，之后按消息 id 续读，只处理带前缀的动作消息
+fn poll_ntfy(topic_url: &str, token: &str, tx: &Sender<PushAction>, stop: &AtomicBool) {
+    let agent = agent(Duration::from_secs(15));
+    let url = format!("{}/json", topic_url.trim().trim_end_matches('/'));
+    let mut since = chrono::Utc::now().timestamp().to_string();
+    while !stop.load(Ordering::Relaxed) {
+        let mut req = agent.get(&url).query("poll", "1").query("since", &since);
+        if !token.is_empty() {
+            req = req.header("Authorization", format!("Bearer {}", token));
+        }
+        let body = req.call().and_then(|mut r| r.body_mut().read_to_string());
+        let Ok(body) = body else {
+            std::thread::sleep(RETRY_INTERVAL);
+            continue;
+        };
+        for line in body.lines() {
+            let Ok(msg) = serde_json::from_str::<serde_json::Value>(line) else { continue };
+            if msg["event"] != "message" {
+                continue;
+            }
+            if let Some(id) = msg["id"].as_str() {
+                since = id.to_string();
+            }
+            let action = msg["message"]
+                .as_str()
+                .and_then(|m| m.trim().strip_prefix(NTFY_ACTION_PREFIX))
+                .and_then(PushAction::from_key);
+            if let Some(action) = action {
+                if tx.send(action).is_err() {
+                    return;
+                }
+            }
+        }
+        std::thread::sleep(NTFY_POLL_INTERVAL);
+    }
+}
+
+/// 长轮询 Telegram 的按钮回调；只接受配置的 chat_id，启动前积压的回调丢弃
+fn poll_telegram(token: &str, chat_id: &str, tx: &Sender<PushAction>, stop: &AtomicBool) {
+    let agent = agent(Duration::from_secs(TELEGRAM_LONG_POLL_SECS + 10));
+    let updates_url = telegram_api_url(token, "getUpdates");
+    let answer_url = telegram_api_url(token, "answerCallbackQuery");
+    let mut offset: Option<i64> = None;
+    while !stop.load(Ordering::Relaxed) {
+        // 首轮 offset=-1 只取最新一条以定位，不处理
+        let (offset_param, timeout) = match offset {
+            Some(o) => (o.to_string(), TELEGRAM_LONG_POLL_SECS),
+            None => ("-1".to_string(), 0),
+        };
+        let response = agent
+            .get(&updates_url)
+            .query("offset", &offset_param)
+            .query("timeout", timeout.to_string())
+            .query("allowed_updates", r#"["callback_query"]"#)
+            .call()
+            .and_then(|mut r| r.body_mut().read_to_string());
+        let Some(response) = response.ok().and_then(|b| serde_json::from_str::<serde_json::Value>(&b).ok()) else {
+            std::thread::sleep(RETRY_INTERVAL);
+            continue;
+        };
+        let updates = response["result"].as_array().cloned().unwrap_or_default();
+        let first_round = offset.is_none();
+        offset = Some(offset.unwrap_or(0));
+        for update in &updates {
+            if let Some(id) = update["update_id"].as_i64() {
+                offset = offset.max(Some(id + 1));
+            }
+            if first_round {
+                continue;
+            }
+            let query = &update["callback_query"];
+            let from_chat = query["message"]["chat"]["id"].as_i64().map(|id| id.to_string());
+            if from_chat.as_deref() != Some(chat_id) {
+                continue;
+            }
+            let action = query["data"].as_str().and_then(PushAction::from_key);
+            if let Some(query_id) = query["id"].as_str() {
+                let text = if action.is_some() { "已收到" } else { "未知操作" };
+                let _ = agent
+                    .post(&answer_url)
+                    .header("Content-Type", "application/json")
+                    .send(serde_json::json!({ "callback_query_id": query_id, "text": text }).to_string());
+            }
+            if let Some(action) = action {
+                if tx.send(action).is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,73 @@
+//! 专注语录：专注计时中在计时器下方轮换显示一句激励语或自定义的座右铭
+//!
+//! 不另存状态：按已完成番茄数选起始句、按本阶段已专注时长轮换，同一次专注内切换主题或重绘都保持一致。
+
+use serde::{Deserialize, Serialize};
+
+/// 语录设置（仅本机）
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QuoteSettings {
+    pub enabled: bool,
+    /// 每隔多少分钟换一句（0 表示整个专注只显示一句）
+    pub rotate_minutes: u32,
+    /// 语录列表，可在设置中增删
+    pub quotes: Vec<String>,
+}
+
+impl Default for QuoteSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            rotate_minutes: 5,
+            quotes: DEFAULT_QUOTES.iter().map(|q| q.to_string()).collect(),
+        }
+    }
+}
+
+/// 首次使用时的默认语录
+pub const DEFAULT_QUOTES: &[&str] = &[
+    "一次只做一件事。",
+    "先完成，再完美。",
+    "专注当下这一个番茄。",
+    "不积跬步，无以至千里。",
+    "行百里者半九十，坚持到铃响。",
+    "分心了也没关系，回到任务上来。",
+    "慢慢来，比较快。",
+];
+
+/// 允许的单句最大字符数（过长的在列表中无法完整显示）
+pub const MAX_QUOTE_CHARS: usize = 60;
+
+impl QuoteSettings {
+    /// 当前应显示的语录：`seed` 决定本次专注的起始句（传入已完成番茄数），`elapsed_secs` 为本阶段已专注秒数
+    pub fn pick(&self, seed: u32, elapsed_secs: i64) -> Option<&str> {
+        let quotes: Vec<&str> = self.quotes.iter().map(|q| q.trim()).filter(|q| !q.is_empty()).collect();
+        if !self.enabled || quotes.is_empty() {
+            return None;
+        }
+        let turns = if self.rotate_minutes == 0 {
+            0
+        } else {
+            elapsed_secs.max(0) as u64 / (self.rotate_minutes as u64 * 60)
+        };
+        let index = (seed as u64 + turns) % quotes.len() as u64;
+        Some(quotes[index as usize])
+    }
+
+    /// 添加一句（去掉首尾空白；空、过长或重复时返回错误说明）
+    pub fn add(&mut self, text: &str) -> Result<(), String> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Err("语录不能为空".to_string());
+        }
+        if text.chars().count() > MAX_QUOTE_CHARS {
+            return Err(format!("语录不能超过 {} 个字", MAX_QUOTE_CHARS));
+        }
+        if self.quotes.iter().any(|q| q == text) {
+            return Err("已有相同的语录".to_string());
+        }
+        self.quotes.push(text.to_string());
+        Ok(())
+    }
+}
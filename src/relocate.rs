@@ -0,0 +1,106 @@
+//! 移动数据库：把数据库（及归档库）复制到用户选择的文件夹（如 Dropbox 同步目录）并记住新位置，
+//! 或直接改用某个文件夹中已有的数据库（另一台电脑已移过去的）
+//!
+//! 复制前把 WAL 检查点写回主文件，复制后把副本改为回滚日志（同步盘上不用 WAL，见 `db::configure`）并做完整性检查，
+//! 通过后才切换位置。原文件保留在原处，确认无误后可自行删除。部署策略或环境变量指定了路径时不能移动。
+
+use std::path::{Path, PathBuf};
+
+use rusqlite::Connection;
+
+use crate::db::{self, DbLocation};
+
+/// 设置中能否更改位置；不能时返回原因
+pub fn locked_reason() -> Option<String> {
+    match db::db_location().1 {
+        DbLocation::Policy => Some("数据库位置已由部署策略固定".to_string()),
+        DbLocation::Env => Some(format!("数据库位置由环境变量 {} 指定", db::DB_PATH_ENV)),
+        DbLocation::Custom | DbLocation::Default => None,
+    }
+}
+
+/// 目标文件夹中的数据库文件路径；文件夹须已存在
+fn target_in(dir: &str) -> Result<PathBuf, String> {
+    let dir = Path::new(dir.trim());
+    if dir.as_os_str().is_empty() {
+        return Err("请填写目标文件夹".to_string());
+    }
+    if !dir.is_dir() {
+        return Err(format!("文件夹不存在：{}", dir.display()));
+    }
+    let target = dir.join(db::DB_FILENAME);
+    if same_file(&target, &db::db_path()) {
+        return Err("数据库已在该文件夹中".to_string());
+    }
+    Ok(target)
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// 把当前数据库（及归档库）复制到 dir 并改用该位置，返回新路径
+pub fn move_to(dir: &str) -> Result<PathBuf, String> {
+    if let Some(reason) = locked_reason() {
+        return Err(reason);
+    }
+    let target = target_in(dir)?;
+    if target.exists() {
+        return Err("该文件夹中已有数据库；如是其他电脑移过去的，请选择「改用该位置的数据库」".to_string());
+    }
+    let source = db::db_path();
+    {
+        let conn = db::open_and_init().map_err(|e| format!("打开数据库失败：{}", e))?;
+        conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))
+            .map_err(|e| format!("写回数据库失败：{}", e))?;
+    }
+    let archive = db::archive_path();
+    let archive_target = target.with_file_name(db::ARCHIVE_FILENAME);
+    let mut copies = vec![(source, target.clone())];
+    if archive.exists() && !archive_target.exists() {
+        copies.push((archive, archive_target));
+    }
+    for (from, to) in &copies {
+        std::fs::copy(from, to).map_err(|e| format!("复制 {} 失败：{}", from.display(), e))?;
+        if let Err(e) = prepare_copy(to) {
+            let _ = std::fs::remove_file(to);
+            return Err(format!("新位置的数据库不可用：{}", e));
+        }
+    }
+    db::set_custom_db_path(Some(&target)).map_err(|e| format!("保存位置失败：{}", e))?;
+    Ok(target)
+}
+
+/// 副本改为回滚日志并检查完整性
+fn prepare_copy(path: &Path) -> Result<(), String> {
+    let conn = Connection::open(path).map_err(|e| e.to_string())?;
+    conn.pragma_update_and_check(None, "journal_mode", "DELETE", |row| row.get::<_, String>(0))
+        .map_err(|e| e.to_string())?;
+    let check: String = conn
+        .query_row("PRAGMA quick_check", [], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    if check != "ok" {
+        return Err(check);
+    }
+    Ok(())
+}
+
+/// 改用 dir 中已有的数据库（不复制），返回其路径
+pub fn use_existing(dir: &str) -> Result<PathBuf, String> {
+    if let Some(reason) = locked_reason() {
+        return Err(reason);
+    }
+    let target = target_in(dir)?;
+    if !target.exists() {
+        return Err(format!("该文件夹中没有 {}", db::DB_FILENAME));
+    }
+    Connection::open(&target)
+        .and_then(|conn| conn.query_row("PRAGMA quick_check", [], |row| row.get::<_, String>(0)))
+        .map_err(|e| format!("无法打开该数据库：{}", e))
+        .and_then(|check| if check == "ok" { Ok(()) } else { Err(format!("数据库已损坏：{}", check)) })?;
+    db::set_custom_db_path(Some(&target)).map_err(|e| format!("保存位置失败：{}", e))?;
+    Ok(target)
+}
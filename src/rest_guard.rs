@@ -0,0 +1,105 @@
+//! 休息守护：连续跳过太多次休息，或距上次长休息累计专注超过设定小时数时，醒目地建议休息
+//!
+//! 每次专注结束后的那次休息，在下一次开始专注时记一条休息事件（break_events 表）：休息计时走完，
+//! 或离开专注后已过了该休息的时长（期间没有计时也算休过），记为休息；否则记为跳过。推迟休息不算跳过。
+//! 警示与统计窗口都以这张表为准：连续跳过次数只数今天最近连续的跳过，
+//! 「未长休息的专注时长」从今天最后一次完成的长休息（没有则从今天零点）起累计。
+
+use chrono::{DateTime, FixedOffset, Utc};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use crate::pomodoro::Phase;
+
+/// 休息守护设置（仅本机）
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RestGuardSettings {
+    pub enabled: bool,
+    /// 连续跳过多少次休息时提醒（0 表示不检查）
+    pub max_skipped_breaks: u32,
+    /// 没有长休息时累计专注多少小时提醒（0 表示不检查）
+    pub max_hours_without_long_break: u32,
+}
+
+impl Default for RestGuardSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_skipped_breaks: 2,
+            max_hours_without_long_break: 3,
+        }
+    }
+}
+
+/// 专注结束后待进行的休息
+#[derive(Clone, Copy, Debug)]
+pub struct PendingBreak {
+    pub phase: Phase,
+    /// 该休息的设定时长（秒）
+    pub secs: i64,
+    /// 专注结束的时间
+    pub since: DateTime<Utc>,
+}
+
+impl PendingBreak {
+    /// 此刻开始专注时这次休息是否算跳过：离开专注不到休息时长即为跳过
+    pub fn skipped_at(&self, now: DateTime<Utc>) -> bool {
+        (now - self.since).num_seconds() < self.secs
+    }
+}
+
+/// 休息警示
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RestWarning {
+    /// 今天连续跳过的休息次数
+    SkippedBreaks(u32),
+    /// 距上次长休息累计的专注秒数
+    NoLongBreak(i64),
+}
+
+impl RestWarning {
+    pub fn message(&self) -> String {
+        match self {
+            RestWarning::SkippedBreaks(n) => format!("已连续跳过 {} 次休息，这次好好休息一下吧", n),
+            RestWarning::NoLongBreak(secs) => format!(
+                "已经连续专注 {} 没有长休息了，起来走走、休息一会儿吧",
+                crate::review::format_hm(*secs)
+            ),
+        }
+    }
+}
+
+/// 休息事件表中的阶段键
+pub fn phase_key(phase: Phase) -> &'static str {
+    match phase {
+        Phase::LongBreak => "long_break",
+        _ => "short_break",
+    }
+}
+
+/// 按设置检查是否需要警示；now 为北京时间
+pub fn evaluate(
+    settings: &RestGuardSettings,
+    conn: &Connection,
+    now: DateTime<FixedOffset>,
+) -> Result<Option<RestWarning>, rusqlite::Error> {
+    if !settings.enabled {
+        return Ok(None);
+    }
+    let today = crate::app::beijing_day_start_rfc3339(now.date_naive());
+    if settings.max_skipped_breaks > 0 {
+        let skipped = crate::db::consecutive_skipped_breaks(conn, &today)?;
+        if skipped >= settings.max_skipped_breaks {
+            return Ok(Some(RestWarning::SkippedBreaks(skipped)));
+        }
+    }
+    if settings.max_hours_without_long_break > 0 {
+        let since = crate::db::last_long_break(conn)?.filter(|at| *at > today).unwrap_or(today);
+        let (_, secs, _) = crate::db::session_summary(conn, &since, "9")?;
+        if secs >= settings.max_hours_without_long_break as i64 * 3600 {
+            return Ok(Some(RestWarning::NoLongBreak(secs)));
+        }
+    }
+    Ok(None)
+}
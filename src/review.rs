@@ -0,0 +1,285 @@
+//! 周报与报告：汇总最近 7 天的专注数据（聚合查询在 db.rs），按日/周/月导出 Markdown
+
+use chrono::{Datelike, Duration, Months, NaiveDate};
+use rusqlite::Connection;
+
+use crate::app::beijing_day_start_rfc3339;
+use crate::db;
+use crate::history::FocusRecord;
+use crate::stats;
+
+/// 周报中列出的任务数
+pub const TOP_TASKS: u32 = 5;
+
+/// 一周的汇总数据
+pub struct WeeklyReview {
+    /// 第一天与最后一天（含）
+    pub first_day: NaiveDate,
+    pub last_day: NaiveDate,
+    /// 每天（共 7 天，按日期正序，无记录的天为 0）：日期、专注秒数、番茄数
+    pub days: Vec<(NaiveDate, i64, u32)>,
+    pub sessions: u32,
+    pub total_secs: i64,
+    pub avg_session_secs: i64,
+    pub top_tasks: Vec<db::TaskFocusRow>,
+    /// 最长深度块：番茄数与专注秒数
+    pub longest_block_sessions: u32,
+    pub longest_block_secs: i64,
+    /// 最长连续有专注的天数
+    pub longest_day_streak: u32,
+    /// 中断：专注中暂停次数、未完成即重置次数
+    pub pauses: u32,
+    pub abandons: u32,
+}
+
+/// 生成以 last_day 结尾（含）的 7 天周报；max_gap_secs 为深度块判定的最大间隔
+pub fn build_weekly_review(
+    conn: &Connection,
+    last_day: NaiveDate,
+    max_gap_secs: i64,
+) -> Result<WeeklyReview, rusqlite::Error> {
+    let first_day = last_day - Duration::days(6);
+    let from = beijing_day_start_rfc3339(first_day);
+    let to = beijing_day_start_rfc3339(last_day + Duration::days(1));
+
+    let daily = db::daily_focus(conn, &from, &to)?;
+    let days: Vec<(NaiveDate, i64, u32)> = (0..7)
+        .map(|i| {
+            let d = first_day + Duration::days(i);
+            let key = d.format("%Y-%m-%d").to_string();
+            daily
+                .iter()
+                .find(|r| r.day == key)
+                .map(|r| (d, r.total_secs, r.sessions))
+                .unwrap_or((d, 0, 0))
+        })
+        .collect();
+
+    let mut longest_day_streak = 0;
+    let mut streak = 0;
+    for (_, _, n) in &days {
+        streak = if *n > 0 { streak + 1 } else { 0 };
+        longest_day_streak = longest_day_streak.max(streak);
+    }
+
+    let (sessions, total_secs, avg_session_secs) = db::session_summary(conn, &from, &to)?;
+    let top_tasks = db::top_tasks(conn, &from, &to, TOP_TASKS)?;
+    let (pauses, abandons) = db::count_interruptions(conn, &from, &to)?;
+
+    let records: Vec<FocusRecord> = db::load_focus_records_since(conn, &from, 0)?
+        .into_iter()
+        .filter(|r| r.completed_at < to)
+        .map(FocusRecord::from)
+        .collect();
+    let blocks = stats::deep_blocks(&records, max_gap_secs);
+    let longest = blocks
+        .iter()
+        .max_by_key(|b| (b.sessions, b.focus_secs));
+
+    Ok(WeeklyReview {
+        first_day,
+        last_day,
+        days,
+        sessions,
+        total_secs,
+        avg_session_secs,
+        top_tasks,
+        longest_block_sessions: longest.map(|b| b.sessions).unwrap_or(0),
+        longest_block_secs: longest.map(|b| b.focus_secs).unwrap_or(0),
+        longest_day_streak,
+        pauses,
+        abandons,
+    })
+}
+
+/// 秒数 → 「X 小时 Y 分钟」/「Y 分钟」
+pub fn format_hm(secs: i64) -> String {
+    let mins = secs.max(0) / 60;
+    if mins >= 60 {
+        format!("{} 小时 {} 分钟", mins / 60, mins % 60)
+    } else {
+        format!("{} 分钟", mins)
+    }
+}
+
+const WEEKDAYS: [&str; 7] = ["一", "二", "三", "四", "五", "六", "日"];
+
+/// 星期几（中文，周一为「一」）
+pub fn weekday_label(day: NaiveDate) -> &'static str {
+    WEEKDAYS[day.weekday().num_days_from_monday() as usize]
+}
+
+impl WeeklyReview {
+    /// 渲染为 Markdown（标题 + 概览 + 每日表格 + 任务排行）
+    pub fn to_markdown(&self) -> String {
+        let mut md = String::new();
+        md.push_str(&format!(
+            "# 红番茄周报 {} ~ {}\n\n",
+            self.first_day.format("%Y-%m-%d"),
+            self.last_day.format("%Y-%m-%d")
+        ));
+        md.push_str("## 概览\n\n");
+        md.push_str(&format!("- 专注总时长：{}\n", format_hm(self.total_secs)));
+        md.push_str(&format!("- 番茄数：{}\n", self.sessions));
+        md.push_str(&format!("- 平均每次专注：{}\n", format_hm(self.avg_session_secs)));
+        md.push_str(&format!(
+            "- 最长深度块：{} 个番茄（{}）\n",
+            self.longest_block_sessions,
+            format_hm(self.longest_block_secs)
+        ));
+        md.push_str(&format!("- 最长连续专注天数：{} 天\n", self.longest_day_streak));
+        md.push_str(&format!("- 中断：暂停 {} 次，未完成即重置 {} 次\n\n", self.pauses, self.abandons));
+
+        md.push_str("## 每日\n\n| 日期 | 星期 | 番茄数 | 专注时长 |\n|---|---|---|---|\n");
+        for (day, secs, n) in &self.days {
+            md.push_str(&format!(
+                "| {} | {} | {} | {} |\n",
+                day.format("%m-%d"),
+                weekday_label(*day),
+                n,
+                format_hm(*secs)
+            ));
+        }
+
+        md.push_str("\n## 任务 Top 5\n\n");
+        if self.top_tasks.is_empty() {
+            md.push_str("（无记录）\n");
+        }
+        for (i, t) in self.top_tasks.iter().enumerate() {
+            let name = if t.task.is_empty() { "(无任务)" } else { t.task.as_str() };
+            md.push_str(&format!("{}. {} — {}，{} 个番茄\n", i + 1, name, format_hm(t.total_secs), t.sessions));
+        }
+        md
+    }
+}
+
+/// 报告导出目录（数据目录下的 reports）
+pub fn reports_dir() -> std::path::PathBuf {
+    db::data_dir().join("reports")
+}
+
+/// 把报告（Markdown 或分享图 PNG）写入报告目录，返回文件路径
+pub fn write_report(file_name: &str, contents: impl AsRef<[u8]>) -> std::io::Result<std::path::PathBuf> {
+    let dir = reports_dir();
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(file_name);
+    std::fs::write(&path, contents)?;
+    Ok(path)
+}
+
+/// 报告范围：某天 / 某周（周一至周日）/ 某月
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReportRange {
+    Day,
+    Week,
+    Month,
+}
+
+impl ReportRange {
+    pub const ALL: [ReportRange; 3] = [ReportRange::Day, ReportRange::Week, ReportRange::Month];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ReportRange::Day => "日",
+            ReportRange::Week => "周",
+            ReportRange::Month => "月",
+        }
+    }
+
+    /// 以 today 为基准、向前/后偏移 offset 个周期后的起止日期（含）
+    pub fn period(self, today: NaiveDate, offset: i32) -> (NaiveDate, NaiveDate) {
+        match self {
+            ReportRange::Day => {
+                let d = today + Duration::days(offset as i64);
+                (d, d)
+            }
+            ReportRange::Week => {
+                let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64)
+                    + Duration::weeks(offset as i64);
+                (monday, monday + Duration::days(6))
+            }
+            ReportRange::Month => {
+                let first = today.with_day(1).unwrap();
+                let first = if offset >= 0 {
+                    first + Months::new(offset as u32)
+                } else {
+                    first - Months::new(offset.unsigned_abs())
+                };
+                (first, first + Months::new(1) - Duration::days(1))
+            }
+        }
+    }
+}
+
+/// 当天的简报（Telegram 每日摘要）：合计加上与日报 Markdown 相同的任务清单，只列前 `TOP_TASKS` 项
+pub fn daily_digest(conn: &Connection, day: NaiveDate) -> Result<String, rusqlite::Error> {
+    let from = beijing_day_start_rfc3339(day);
+    let to = beijing_day_start_rfc3339(day + Duration::days(1));
+    let (sessions, total_secs, _) = db::session_summary(conn, &from, &to)?;
+    let mut text = format!(
+        "红番茄 {}（周{}）\n🍅{}，专注 {}\n",
+        day.format("%Y-%m-%d"),
+        weekday_label(day),
+        sessions,
+        format_hm(total_secs)
+    );
+    if sessions == 0 {
+        return Ok(text);
+    }
+    // 日报每个任务一行「- 任务 — 🍅n，时长」，按时长降序
+    let report = period_markdown(conn, day, day)?;
+    let tasks: Vec<&str> = report.lines().filter(|l| l.starts_with("- ")).collect();
+    text.push('\n');
+    for line in tasks.iter().take(TOP_TASKS as usize) {
+        text.push_str(line);
+        text.push('\n');
+    }
+    if tasks.len() > TOP_TASKS as usize {
+        text.push_str(&format!("……另有 {} 项\n", tasks.len() - TOP_TASKS as usize));
+    }
+    Ok(text)
+}
+
+/// 生成 [first, last] 的 Markdown 报告：每个有记录的日期一节 `## YYYY-MM-DD`，列出任务、番茄数与时长；
+/// 多天时在最前面加总标题与合计
+pub fn period_markdown(conn: &Connection, first: NaiveDate, last: NaiveDate) -> Result<String, rusqlite::Error> {
+    let mut md = String::new();
+    let mut total_secs = 0;
+    let mut total_sessions = 0;
+    let mut sections = String::new();
+    let mut day = first;
+    while day <= last {
+        let from = beijing_day_start_rfc3339(day);
+        let to = beijing_day_start_rfc3339(day + Duration::days(1));
+        let tasks = db::top_tasks(conn, &from, &to, 0)?;
+        if !tasks.is_empty() {
+            sections.push_str(&format!("## {}\n\n", day.format("%Y-%m-%d")));
+            for t in &tasks {
+                let name = if t.task.is_empty() { "(无任务)" } else { t.task.as_str() };
+                sections.push_str(&format!("- {} — 🍅{}，{}\n", name, t.sessions, format_hm(t.total_secs)));
+                total_secs += t.total_secs;
+                total_sessions += t.sessions;
+            }
+            sections.push('\n');
+        }
+        day += Duration::days(1);
+    }
+    if first != last {
+        md.push_str(&format!(
+            "# 红番茄 {} ~ {}\n\n合计：🍅{}，{}\n\n",
+            first.format("%Y-%m-%d"),
+            last.format("%Y-%m-%d"),
+            total_sessions,
+            format_hm(total_secs)
+        ));
+    }
+    if sections.is_empty() {
+        if first == last {
+            md.push_str(&format!("## {}\n\n", first.format("%Y-%m-%d")));
+        }
+        md.push_str("（无专注记录）\n");
+    } else {
+        md.push_str(&sections);
+    }
+    Ok(md)
+}
@@ -0,0 +1,116 @@
+//! 环形进度：可选的主界面布局，倒计时放在一个大圆环中间，圆环按阶段进度以强调色从正上方顺时针填满
+//!
+//! `ProgressRing` 用法与 `egui::ProgressBar` 相近（`new(progress)` 后链式设置），`show` 画出圆环并在环内
+//! 居中放置传入的内容；完整模式与紧凑模式共用，只是直径与环宽不同。
+
+use egui::{Color32, InnerResponse, Sense, Stroke};
+use serde::{Deserialize, Serialize};
+
+/// 主界面计时器的布局
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimerLayout {
+    /// 计时数字下方一条进度条
+    #[default]
+    Bar,
+    /// 计时数字放在环形进度中间
+    Ring,
+}
+
+impl TimerLayout {
+    pub const ALL: [TimerLayout; 2] = [TimerLayout::Bar, TimerLayout::Ring];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TimerLayout::Bar => "进度条",
+            TimerLayout::Ring => "圆环",
+        }
+    }
+}
+
+/// 圆弧按每圈多少段折线近似
+const ARC_SEGMENTS: f32 = 96.0;
+
+/// 环形进度控件
+pub struct ProgressRing {
+    progress: f32,
+    diameter: f32,
+    thickness: f32,
+    fill: Option<Color32>,
+    track: Option<Color32>,
+}
+
+impl ProgressRing {
+    /// progress 为 0.0..=1.0
+    pub fn new(progress: f32) -> Self {
+        Self {
+            progress: progress.clamp(0.0, 1.0),
+            diameter: 240.0,
+            thickness: 10.0,
+            fill: None,
+            track: None,
+        }
+    }
+
+    /// 圆环外径
+    pub fn diameter(mut self, diameter: f32) -> Self {
+        self.diameter = diameter;
+        self
+    }
+
+    /// 环宽
+    pub fn thickness(mut self, thickness: f32) -> Self {
+        self.thickness = thickness;
+        self
+    }
+
+    /// 已完成部分的颜色（默认为选中色，与 ProgressBar 一致）
+    pub fn fill(mut self, color: Color32) -> Self {
+        self.fill = Some(color);
+        self
+    }
+
+    /// 未完成部分（底环）的颜色（默认为 extreme_bg_color）
+    pub fn track(mut self, color: Color32) -> Self {
+        self.track = Some(color);
+        self
+    }
+
+    /// 画出圆环，并在环内居中放置 add_contents 添加的内容；返回的 response 为整个圆环的区域
+    pub fn show<R>(self, ui: &mut egui::Ui, add_contents: impl FnOnce(&mut egui::Ui) -> R) -> InnerResponse<R> {
+        let (rect, response) = ui.allocate_exact_size(egui::vec2(self.diameter, self.diameter), Sense::hover());
+        if ui.is_rect_visible(rect) {
+            let visuals = ui.visuals();
+            let fill = self.fill.unwrap_or(visuals.selection.bg_fill);
+            let track = self.track.unwrap_or(visuals.extreme_bg_color);
+            let center = rect.center();
+            let radius = (self.diameter - self.thickness) * 0.5;
+            let painter = ui.painter();
+            painter.circle_stroke(center, radius, Stroke::new(self.thickness, track));
+            if self.progress > 0.0 {
+                // 从正上方顺时针（屏幕坐标 y 向下，角度增大即顺时针）
+                let start = -std::f32::consts::FRAC_PI_2;
+                let sweep = std::f32::consts::TAU * self.progress;
+                let n = (ARC_SEGMENTS * self.progress).ceil().max(2.0) as usize;
+                let points: Vec<egui::Pos2> = (0..=n)
+                    .map(|i| {
+                        let angle = start + sweep * i as f32 / n as f32;
+                        center + radius * egui::vec2(angle.cos(), angle.sin())
+                    })
+                    .collect();
+                painter.add(egui::Shape::line(points, Stroke::new(self.thickness, fill)));
+                // 两端补圆头
+                for end in [0.0, sweep] {
+                    let angle = start + end;
+                    painter.circle_filled(center + radius * egui::vec2(angle.cos(), angle.sin()), self.thickness * 0.5, fill);
+                }
+            }
+        }
+        // 环内的子界面不再占用父界面的位置（圆环已占好）
+        let mut child = ui.new_child(
+            egui::UiBuilder::new()
+                .max_rect(rect.shrink(self.thickness * 1.5))
+                .layout(egui::Layout::centered_and_justified(egui::Direction::TopDown)),
+        );
+        InnerResponse::new(add_contents(&mut child), response)
+    }
+}
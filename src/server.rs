@@ -0,0 +1,505 @@
+//! 本机 HTTP 状态接口（feature `http-api`）：供 OBS 叠加层、Stream Deck 插件、脚本读取与控制计时
+//!
+//! - `GET /status`：当前阶段、状态、剩余时间、任务、番茄数（JSON）
+//! - `POST /start`：开始（空闲时）或继续（暂停时）
+//! - `POST /pause`：暂停
+//! - `POST /skip`：跳过当前阶段
+//! - `GET /ws`：WebSocket，供 Stream Deck 插件、硬件按键盒低延迟控制并实时收到倒计时
+//!
+//! 只监听 127.0.0.1。服务在后台线程运行：UI 每帧发布状态快照、取回控制命令，线程本身不接触计时状态。
+//! 来自其他网站的浏览器请求（带非本机 Origin）一律拒绝，也只对本机 Origin 返回 CORS 头，避免网页借用户浏览器读取任务或操控计时；
+//! Host 必须是 `127.0.0.1:端口` 或 `localhost:端口`，防止 DNS 重绑定绕过 Origin 检查。
+//!
+//! WebSocket 消息均为文本帧中的 JSON：
+//! - 客户端发 `{"action":"start"}` / `"pause"` / `"skip"` / `"status"`（也可直接发 `start` 等单词）；
+//!   控制命令回 `{"type":"ack","action":"start"}`，无法识别的回 `{"type":"error","message":"..."}`
+//! - 服务端在连接后、状态变化时（计时中每秒）及收到 `status` 时推送 `{"type":"status", ...}`，字段同 `GET /status`
+
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::pomodoro::Command;
+
+/// 默认端口
+pub const DEFAULT_PORT: u16 = 24025;
+/// 后台线程检查退出标志的间隔
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// 单个连接的读写超时
+const IO_TIMEOUT: Duration = Duration::from_secs(2);
+/// 请求头最大字节数
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+/// WebSocket 连接检查状态变化与收消息的间隔
+const WS_POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// 同时保持的 WebSocket 连接上限
+const MAX_WS_CLIENTS: usize = 16;
+/// 客户端单条消息的最大字节数
+const MAX_WS_MESSAGE_BYTES: usize = 4 * 1024;
+/// 握手应答中与客户端密钥拼接的固定串（RFC 6455）
+const WS_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC11B65";
+
+/// 接口设置（仅本机，默认关闭）
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ServerSettings {
+    pub enabled: bool,
+    pub port: u16,
+}
+
+impl Default for ServerSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_PORT,
+        }
+    }
+}
+
+/// `GET /status` 返回的状态快照
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct StatusSnapshot {
+    /// "Focus" / "ShortBreak" / "LongBreak"
+    pub phase: String,
+    /// "Idle" / "Running" / "Paused"
+    pub state: String,
+    pub remaining_secs: i64,
+    /// "MM:SS"
+    pub remaining: String,
+    pub task: String,
+    /// 本轮已完成番茄数与长休息间隔
+    pub tomatoes: u32,
+    pub pomodoros_before_long: u32,
+}
+
+/// 运行中的服务；丢弃时后台线程退出并释放端口
+pub struct StatusServer {
+    port: u16,
+    snapshot: Arc<Mutex<StatusSnapshot>>,
+    commands: Receiver<Command>,
+    stop: Arc<AtomicBool>,
+}
+
+impl StatusServer {
+    /// 绑定 127.0.0.1:port 并启动后台线程
+    pub fn start(port: u16) -> std::io::Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", port))?;
+        listener.set_nonblocking(true)?;
+        let snapshot = Arc::new(Mutex::new(StatusSnapshot::default()));
+        let stop = Arc::new(AtomicBool::new(false));
+        let (tx, rx) = mpsc::channel();
+        {
+            let shared = Shared {
+                port: listener.local_addr()?.port(),
+                snapshot: Arc::clone(&snapshot),
+                commands: tx,
+                stop: Arc::clone(&stop),
+                ws_clients: Arc::new(AtomicUsize::new(0)),
+            };
+            std::thread::spawn(move || {
+                while !shared.stop.load(Ordering::Relaxed) {
+                    match listener.accept() {
+                        Ok((stream, _)) => handle_connection(stream, &shared),
+                        Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                            std::thread::sleep(ACCEPT_POLL_INTERVAL)
+                        }
+                        Err(_) => std::thread::sleep(ACCEPT_POLL_INTERVAL),
+                    }
+                }
+            });
+        }
+        Ok(Self {
+            port,
+            snapshot,
+            commands: rx,
+            stop,
+        })
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// 每帧调用：更新对外的状态快照
+    pub fn publish(&self, snapshot: StatusSnapshot) {
+        if let Ok(mut s) = self.snapshot.lock() {
+            *s = snapshot;
+        }
+    }
+
+    /// 每帧调用：取回自上次以来收到的控制命令
+    pub fn take_commands(&self) -> Vec<Command> {
+        self.commands.try_iter().collect()
+    }
+}
+
+impl Drop for StatusServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// 后台线程与各 WebSocket 连接共用的状态
+struct Shared {
+    /// 实际监听的端口，用于校验 Host
+    port: u16,
+    snapshot: Arc<Mutex<StatusSnapshot>>,
+    commands: Sender<Command>,
+    stop: Arc<AtomicBool>,
+    ws_clients: Arc<AtomicUsize>,
+}
+
+fn handle_connection(mut stream: TcpStream, shared: &Shared) {
+    let _ = stream.set_nonblocking(false);
+    let _ = stream.set_read_timeout(Some(IO_TIMEOUT));
+    let _ = stream.set_write_timeout(Some(IO_TIMEOUT));
+    let Some(head) = read_head(&mut stream) else { return };
+    let mut lines = head.lines();
+    let mut request_line = lines.next().unwrap_or("").split_whitespace();
+    let method = request_line.next().unwrap_or("");
+    let path = request_line.next().unwrap_or("").split('?').next().unwrap_or("");
+    let headers: Vec<(&str, &str)> = lines.filter_map(|l| l.split_once(':')).map(|(n, v)| (n.trim(), v.trim())).collect();
+    let header = |name: &str| headers.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)).map(|(_, v)| *v);
+    let origin = header("origin");
+    let local_origin = origin.is_none_or(is_local_origin);
+    let local_host = header("host").is_some_and(|h| is_local_host(h, shared.port));
+
+    let (status, body) = match (method, path) {
+        _ if !local_host => ("403 Forbidden", r#"{"error":"forbidden host"}"#.to_string()),
+        _ if !local_origin => ("403 Forbidden", r#"{"error":"forbidden origin"}"#.to_string()),
+        ("OPTIONS", _) => ("204 No Content", String::new()),
+        ("GET", "/status") => {
+            let json = shared
+                .snapshot
+                .lock()
+                .ok()
+                .and_then(|s| serde_json::to_string(&*s).ok())
+                .unwrap_or_else(|| "{}".to_string());
+            ("200 OK", json)
+        }
+        ("GET", "/ws") => match header("sec-websocket-key") {
+            Some(key) if header("upgrade").is_some_and(|u| u.eq_ignore_ascii_case("websocket")) => {
+                if shared.ws_clients.load(Ordering::Relaxed) >= MAX_WS_CLIENTS {
+                    ("503 Service Unavailable", r#"{"error":"too many clients"}"#.to_string())
+                } else {
+                    let accept = ws_accept_key(key);
+                    let response = format!(
+                        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+                         Sec-WebSocket-Accept: {}\r\n\r\n",
+                        accept
+                    );
+                    if stream.write_all(response.as_bytes()).is_ok() {
+                        spawn_ws_client(stream, shared);
+                    }
+                    return;
+                }
+            }
+            _ => ("400 Bad Request", r#"{"error":"websocket upgrade required"}"#.to_string()),
+        },
+        ("POST", "/start") => send_command(&shared.commands, Command::Start),
+        ("POST", "/pause") => send_command(&shared.commands, Command::Pause),
+        ("POST", "/skip") => send_command(&shared.commands, Command::Skip),
+        (_, "/status" | "/start" | "/pause" | "/skip" | "/ws") => {
+            ("405 Method Not Allowed", r#"{"error":"method not allowed"}"#.to_string())
+        }
+        _ => ("404 Not Found", r#"{"error":"not found"}"#.to_string()),
+    };
+    // 只对本机页面回 CORS 头，其他网站的脚本读不到应答
+    let cors = match origin.filter(|o| local_host && is_local_origin(o)) {
+        Some(o) => format!(
+            "Access-Control-Allow-Origin: {}\r\nVary: Origin\r\nAccess-Control-Allow-Methods: GET, POST, OPTIONS\r\n",
+            o
+        ),
+        None => String::new(),
+    };
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json; charset=utf-8\r\nContent-Length: {}\r\n\
+         {}Cache-Control: no-store\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        cors,
+        body
+    );
+    let _ = stream.write_all(response.as_bytes());
+}
+
+fn send_command(commands: &Sender<Command>, command: Command) -> (&'static str, String) {
+    match commands.send(command) {
+        Ok(()) => ("202 Accepted", r#"{"ok":true}"#.to_string()),
+        Err(_) => ("503 Service Unavailable", r#"{"error":"app closing"}"#.to_string()),
+    }
+}
+
+/// 本机页面（OBS 浏览器源等）的 Origin。`null` 不算：任何网站都能在沙箱 iframe 或 data: 页面中发出该 Origin
+fn is_local_origin(origin: &str) -> bool {
+    ["http://localhost", "http://127.0.0.1", "https://localhost", "https://127.0.0.1"]
+        .iter()
+        .any(|prefix| {
+            origin
+                .strip_prefix(prefix)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with(':'))
+        })
+}
+
+/// Host 头是否指向本服务：`127.0.0.1:端口` 或 `localhost:端口`（DNS 重绑定时为攻击者的域名）
+fn is_local_host(host: &str, port: u16) -> bool {
+    let port = port.to_string();
+    host.rsplit_once(':').is_some_and(|(name, p)| {
+        p == port && (name == "127.0.0.1" || name.eq_ignore_ascii_case("localhost"))
+    })
+}
+
+/// 读取到空行为止的请求头（请求体不需要，忽略）
+fn read_head(stream: &mut TcpStream) -> Option<String> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 1024];
+    while !buf.windows(4).any(|w| w == b"\r\n\r\n") {
+        let n = stream.read(&mut chunk).ok()?;
+        if n == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        if buf.len() > MAX_REQUEST_BYTES {
+            return None;
+        }
+    }
+    String::from_utf8(buf).ok()
+}
+
+/// 握手应答的 Sec-WebSocket-Accept：客户端密钥拼接固定串后的 SHA-1，Base64 编码
+fn ws_accept_key(key: &str) -> String {
+    use base64::Engine;
+    let digest = ring::digest::digest(&ring::digest::SHA1_FOR_LEGACY_USE_ONLY, format!("{}{}", key, WS_GUID).as_bytes());
+    base64::engine::general_purpose::STANDARD.encode(digest.as_ref())
+}
+
+/// 为一个 WebSocket 连接启动线程：推送状态变化，执行收到的命令；服务停止或连接断开时结束
+fn spawn_ws_client(stream: TcpStream, shared: &Shared) {
+    let snapshot = Arc::clone(&shared.snapshot);
+    let commands = shared.commands.clone();
+    let stop = Arc::clone(&shared.stop);
+    let clients = Arc::clone(&shared.ws_clients);
+    clients.fetch_add(1, Ordering::Relaxed);
+    std::thread::spawn(move || {
+        let _ = run_ws_client(stream, &snapshot, &commands, &stop);
+        clients.fetch_sub(1, Ordering::Relaxed);
+    });
+}
+
+fn run_ws_client(
+    mut stream: TcpStream,
+    snapshot: &Mutex<StatusSnapshot>,
+    commands: &Sender<Command>,
+    stop: &AtomicBool,
+) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(WS_POLL_INTERVAL))?;
+    let mut sent: Option<StatusSnapshot> = None;
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 1024];
+    while !stop.load(Ordering::Relaxed) {
+        let current = snapshot.lock().map(|s| s.clone()).unwrap_or_default();
+        if sent.as_ref() != Some(&current) {
+            write_ws_text(&mut stream, &status_message(&current))?;
+            sent = Some(current);
+        }
+        match stream.read(&mut chunk) {
+            Ok(0) => return Ok(()),
+            Ok(n) => buf.extend_from_slice(&chunk[..n]),
+            Err(e) if matches!(e.kind(), std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut) => continue,
+            Err(e) => return Err(e),
+        }
+        while let Some((opcode, payload, used)) = parse_ws_frame(&buf)? {
+            buf.drain(..used);
+            match opcode {
+                // 文本
+                0x1 => {
+                    let reply = match parse_ws_action(&String::from_utf8_lossy(&payload)) {
+                        Some(WsAction::Status) => status_message(&snapshot.lock().map(|s| s.clone()).unwrap_or_default()),
+                        Some(WsAction::Command(command, name)) => match commands.send(command) {
+                            Ok(()) => serde_json::json!({ "type": "ack", "action": name }).to_string(),
+                            Err(_) => return Ok(()),
+                        },
+                        None => serde_json::json!({ "type": "error", "message": "unknown action" }).to_string(),
+                    };
+                    write_ws_text(&mut stream, &reply)?;
+                }
+                // 关闭：回一个关闭帧后断开
+                0x8 => {
+                    let _ = write_ws_frame(&mut stream, 0x8, &[]);
+                    return Ok(());
+                }
+                // ping → pong
+                0x9 => write_ws_frame(&mut stream, 0xA, &payload)?,
+                // pong、二进制、分片等忽略
+                _ => {}
+            }
+        }
+    }
+    // 服务停止：通知客户端关闭
+    let _ = write_ws_frame(&mut stream, 0x8, &[]);
+    Ok(())
+}
+
+enum WsAction {
+    Status,
+    Command(Command, &'static str),
+}
+
+/// 客户端消息：`{"action":"start"}` 或单词 `start`
+fn parse_ws_action(text: &str) -> Option<WsAction> {
+    let value: Option<serde_json::Value> = serde_json::from_str(text).ok();
+    let action = match &value {
+        Some(v) => v.get("action")?.as_str()?,
+        None => text.trim(),
+    };
+    match action.to_ascii_lowercase().as_str() {
+        "status" => Some(WsAction::Status),
+        "start" => Some(WsAction::Command(Command::Start, "start")),
+        "pause" => Some(WsAction::Command(Command::Pause, "pause")),
+        "skip" => Some(WsAction::Command(Command::Skip, "skip")),
+        _ => None,
+    }
+}
+
+/// `{"type":"status", ...快照字段}`
+fn status_message(snapshot: &StatusSnapshot) -> String {
+    let mut value = serde_json::to_value(snapshot).unwrap_or_else(|_| serde_json::json!({}));
+    if let Some(map) = value.as_object_mut() {
+        map.insert("type".to_string(), "status".into());
+    }
+    value.to_string()
+}
+
+/// 从缓冲区开头解析一个完整的客户端帧：返回操作码、解除掩码后的内容与占用的字节数；不完整时为 None。
+/// 客户端帧必须带掩码，过大的帧视为错误断开
+fn parse_ws_frame(buf: &[u8]) -> std::io::Result<Option<(u8, Vec<u8>, usize)>> {
+    let invalid = |msg: &str| std::io::Error::new(std::io::ErrorKind::InvalidData, msg.to_string());
+    if buf.len() < 2 {
+        return Ok(None);
+    }
+    let opcode = buf[0] & 0x0F;
+    if buf[1] & 0x80 == 0 {
+        return Err(invalid("unmasked client frame"));
+    }
+    let (len, mut pos) = match buf[1] & 0x7F {
+        126 if buf.len() >= 4 => (u16::from_be_bytes([buf[2], buf[3]]) as usize, 4),
+        127 if buf.len() >= 10 => {
+            let len = u64::from_be_bytes(buf[2..10].try_into().unwrap());
+            (usize::try_from(len).unwrap_or(usize::MAX), 10)
+        }
+        126 | 127 => return Ok(None),
+        n => (n as usize, 2),
+    };
+    if len > MAX_WS_MESSAGE_BYTES {
+        return Err(invalid("frame too large"));
+    }
+    if buf.len() < pos + 4 + len {
+        return Ok(None);
+    }
+    let mask = [buf[pos], buf[pos + 1], buf[pos + 2], buf[pos + 3]];
+    pos += 4;
+    let payload = buf[pos..pos + len].iter().enumerate().map(|(i, b)| b ^ mask[i % 4]).collect();
+    Ok(Some((opcode, payload, pos + len)))
+}
+
+fn write_ws_text(stream: &mut TcpStream, text: &str) -> std::io::Result<()> {
+    write_ws_frame(stream, 0x1, text.as_bytes())
+}
+
+/// 写一个不分片、不带掩码的服务端帧
+fn write_ws_frame(stream: &mut TcpStream, opcode: u8, payload: &[u8]) -> std::io::Result<()> {
+    let mut frame = vec![0x80 | opcode];
+    match payload.len() {
+        n if n < 126 => frame.push(n as u8),
+        n if n <= u16::MAX as usize => {
+            frame.push(126);
+            frame.extend_from_slice(&(n as u16).to_be_bytes());
+        }
+        n => {
+            frame.push(127);
+            frame.extend_from_slice(&(n as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(payload);
+    stream.write_all(&frame)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_local_origins() {
+        assert!(is_local_origin("http://localhost"));
+        assert!(is_local_origin("http://127.0.0.1:8080"));
+        assert!(is_local_origin("https://localhost:3000"));
+    }
+
+    #[test]
+    fn rejects_foreign_origins() {
+        assert!(!is_local_origin("null"));
+        assert!(!is_local_origin("file://"));
+        assert!(!is_local_origin("http://localhost.evil.com"));
+        assert!(!is_local_origin("http://127.0.0.1.evil.com:80"));
+        assert!(!is_local_origin("https://example.com"));
+    }
+
+    #[test]
+    fn checks_host_and_port() {
+        assert!(is_local_host("127.0.0.1:7878", 7878));
+        assert!(is_local_host("LocalHost:7878", 7878));
+        assert!(!is_local_host("127.0.0.1:7879", 7878));
+        assert!(!is_local_host("127.0.0.1", 7878));
+        assert!(!is_local_host("evil.com:7878", 7878));
+    }
+
+    /// 按客户端的方式给内容加掩码组帧
+    fn masked_frame(opcode: u8, payload: &[u8]) -> Vec<u8> {
+        let mask = [0x12, 0x34, 0x56, 0x78];
+        let mut frame = vec![0x80 | opcode];
+        match payload.len() {
+            n if n < 126 => frame.push(0x80 | n as u8),
+            n => {
+                frame.push(0x80 | 126);
+                frame.extend_from_slice(&(n as u16).to_be_bytes());
+            }
+        }
+        frame.extend_from_slice(&mask);
+        frame.extend(payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+        frame
+    }
+
+    #[test]
+    fn parses_masked_frames() {
+        let frame = masked_frame(0x1, b"{\"action\":\"pause\"}");
+        let (opcode, payload, used) = parse_ws_frame(&frame).unwrap().unwrap();
+        assert_eq!(opcode, 0x1);
+        assert_eq!(payload, b"{\"action\":\"pause\"}");
+        assert_eq!(used, frame.len());
+
+        let long = vec![b'a'; 300];
+        let mut buf = masked_frame(0x1, &long);
+        buf.extend(masked_frame(0x8, b""));
+        let (_, payload, used) = parse_ws_frame(&buf).unwrap().unwrap();
+        assert_eq!(payload, long);
+        assert_eq!(parse_ws_frame(&buf[used..]).unwrap().unwrap().0, 0x8);
+    }
+
+    #[test]
+    fn waits_for_incomplete_frames() {
+        let frame = masked_frame(0x1, &[b'a'; 300]);
+        for end in [0, 1, 3, frame.len() - 1] {
+            assert!(parse_ws_frame(&frame[..end]).unwrap().is_none());
+        }
+    }
+
+    #[test]
+    fn rejects_unmasked_and_oversized_frames() {
+        assert!(parse_ws_frame(&[0x81, 0x02, b'h', b'i']).is_err());
+        let mut huge = vec![0x81, 0x80 | 127];
+        huge.extend_from_slice(&((MAX_WS_MESSAGE_BYTES as u64) + 1).to_be_bytes());
+        assert!(parse_ws_frame(&huge).is_err());
+    }
+}
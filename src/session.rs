@@ -0,0 +1,94 @@
+//! 进行中专注的会话日志：专注开始时在数据目录写一个小文件，进行中定时更新已专注秒数，
+//! 专注结束（完成 / 重置 / 切换阶段）或正常退出时删除
+//!
+//! 崩溃或断电后下次启动时文件仍在，界面提示把已专注的部分记为专注记录，或恢复为暂停状态继续。
+//! 最多丢失一个更新间隔内的专注时间。
+
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+const SESSION_FILENAME: &str = "active_session.json";
+/// 进行中更新已专注秒数的间隔
+const HEARTBEAT: Duration = Duration::from_secs(15);
+
+/// 进行中的专注
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ActiveSession {
+    pub task: String,
+    #[serde(default)]
+    pub commitment: String,
+    pub project_id: i64,
+    pub completed_pomodoros: u32,
+    pub phase_total_secs: i64,
+    /// 已专注秒数（含加时，不含暂停）
+    pub focused_secs: i64,
+    /// 最近一次更新的时间（北京时间 RFC3339），恢复为记录时作为完成时间
+    pub updated_at: String,
+}
+
+pub fn session_path() -> PathBuf {
+    crate::db::data_dir().join(SESSION_FILENAME)
+}
+
+/// 上次运行留下的会话（启动时、开始写入前读取）
+pub fn load_stale() -> Option<ActiveSession> {
+    let text = std::fs::read_to_string(session_path()).ok()?;
+    let session: ActiveSession = serde_json::from_str(&text).ok()?;
+    // 任务名与承诺按当前加密设置保存，读取时还原
+    Some(ActiveSession {
+        task: crate::crypto::reveal(&session.task),
+        commitment: crate::crypto::reveal(&session.commitment),
+        ..session
+    })
+}
+
+pub fn clear() {
+    let path = session_path();
+    if path.exists() {
+        std::fs::remove_file(&path).map_err(|e| tracing::warn!("删除会话日志失败：{}", e)).ok();
+    }
+}
+
+/// 先写临时文件再改名，写入途中断电不会留下半个文件
+fn write(session: &ActiveSession) -> std::io::Result<()> {
+    let path = session_path();
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let protected = ActiveSession {
+        task: crate::crypto::protect(&session.task).unwrap_or_else(|| session.task.clone()),
+        commitment: crate::crypto::protect(&session.commitment).unwrap_or_else(|| session.commitment.clone()),
+        ..session.clone()
+    };
+    let json = serde_json::to_string(&protected).map_err(std::io::Error::other)?;
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, json)?;
+    std::fs::rename(&tmp, &path)
+}
+
+/// 会话日志写入器：专注进行中每隔 `HEARTBEAT` 写一次，不在专注时删除
+#[derive(Default)]
+pub struct SessionJournal {
+    last_write: Option<Instant>,
+}
+
+impl SessionJournal {
+    /// 每帧调用；focusing 为专注进行中（含暂停），session 只在需要写入时构造
+    pub fn sync(&mut self, focusing: bool, session: impl FnOnce() -> ActiveSession) {
+        if !focusing {
+            if self.last_write.take().is_some() {
+                clear();
+            }
+            return;
+        }
+        if self.last_write.is_some_and(|t| t.elapsed() < HEARTBEAT) {
+            return;
+        }
+        self.last_write = Some(Instant::now());
+        if let Err(e) = write(&session()) {
+            tracing::warn!("写入会话日志失败：{}", e);
+        }
+    }
+}
@@ -0,0 +1,210 @@
+//! 分享图：把一段时间的汇总（番茄数、专注总时长、专注最多的任务）画成 PNG 卡片，保存到报告目录或复制到剪贴板
+//!
+//! 不截取界面：直接在内存中合成像素，文字用当前选中的中文字体栅格化（ab_glyph），配色取自当前主题。
+
+use ab_glyph::{Font, FontVec, PxScale, ScaleFont};
+use chrono::{Duration, NaiveDate};
+use egui::{Color32, ColorImage};
+use rusqlite::Connection;
+
+use crate::theme::Palette;
+
+/// 卡片宽度与边距（像素）
+const WIDTH: usize = 640;
+const MARGIN: f32 = 40.0;
+/// 最多列出的任务数
+const MAX_TASKS: usize = 5;
+const TASK_ROW_H: f32 = 44.0;
+
+/// 分享图的内容
+pub struct ShareCard {
+    /// 如「今日专注」「本周专注」
+    pub title: String,
+    /// 日期或日期区间
+    pub period: String,
+    pub tomatoes: u32,
+    pub focus_secs: i64,
+    /// 专注最多的任务：(任务名, 时长秒, 番茄数)
+    pub top_tasks: Vec<(String, i64, u32)>,
+}
+
+impl ShareCard {
+    /// 汇总 [first, last] 的已完成专注
+    pub fn load(conn: &Connection, title: &str, first: NaiveDate, last: NaiveDate) -> Result<Self, rusqlite::Error> {
+        let from = crate::app::beijing_day_start_rfc3339(first);
+        let to = crate::app::beijing_day_start_rfc3339(last + Duration::days(1));
+        let tasks = crate::db::top_tasks(conn, &from, &to, 0)?;
+        let period = if first == last {
+            first.format("%Y-%m-%d").to_string()
+        } else {
+            format!("{} ~ {}", first.format("%Y-%m-%d"), last.format("%Y-%m-%d"))
+        };
+        Ok(Self {
+            title: title.to_string(),
+            period,
+            tomatoes: tasks.iter().map(|t| t.sessions).sum(),
+            focus_secs: tasks.iter().map(|t| t.total_secs).sum(),
+            top_tasks: tasks
+                .into_iter()
+                .take(MAX_TASKS)
+                .map(|t| {
+                    let name = if t.task.is_empty() { "(无任务)".to_string() } else { t.task };
+                    (name, t.total_secs, t.sessions)
+                })
+                .collect(),
+        })
+    }
+
+    /// 合成卡片图像；font_path 为当前界面使用的中文字体（None 表示只有 egui 内置字体，无法绘制中文）
+    pub fn render(&self, font_path: Option<&str>, palette: &Palette) -> Result<ColorImage, String> {
+        let path = font_path.ok_or("未找到中文字体，请先在设置中选择字体")?;
+        let bytes = std::fs::read(path).map_err(|e| format!("读取字体失败：{}", e))?;
+        let font = FontVec::try_from_vec_and_index(bytes, 0).map_err(|e| format!("字体无法解析：{}", e))?;
+        let rows = self.top_tasks.len().max(1);
+        let height = (250.0 + rows as f32 * TASK_ROW_H + 50.0) as usize;
+        let mut canvas = Canvas {
+            image: ColorImage::filled([WIDTH, height], palette.bg),
+            font,
+        };
+        let right = WIDTH as f32 - MARGIN;
+        // 顶部番茄红色条
+        canvas.fill_rect(0.0, 0.0, WIDTH as f32, 8.0, palette.long_break);
+        canvas.text(MARGIN, 64.0, 30.0, &self.title, palette.text);
+        let w = canvas.measure(16.0, &self.period);
+        canvas.text(right - w, 60.0, 16.0, &self.period, palette.text_dim);
+        // 番茄数：红圆点 + 大号数字
+        canvas.fill_circle(MARGIN + 22.0, 132.0, 22.0, palette.long_break);
+        canvas.text(MARGIN + 60.0, 150.0, 48.0, &self.tomatoes.to_string(), palette.text);
+        let w = canvas.measure(48.0, &self.tomatoes.to_string());
+        canvas.text(MARGIN + 68.0 + w, 150.0, 18.0, "个番茄", palette.text_dim);
+        canvas.text(MARGIN, 196.0, 20.0, &format!("专注 {}", crate::review::format_hm(self.focus_secs)), palette.text);
+        canvas.fill_rect(MARGIN, 220.0, right - MARGIN, 1.0, palette.grid);
+        let mut y = 260.0;
+        if self.top_tasks.is_empty() {
+            canvas.text(MARGIN, y, 16.0, "这段时间还没有完成的专注", palette.text_dim);
+        }
+        let max_secs = self.top_tasks.first().map(|t| t.1).unwrap_or(0).max(1);
+        for (name, secs, n) in &self.top_tasks {
+            let stat = format!("{} · {} 个", crate::review::format_hm(*secs), n);
+            let stat_w = canvas.measure(14.0, &stat);
+            canvas.text(right - stat_w, y, 14.0, &stat, palette.text_dim);
+            let name = canvas.truncate(16.0, name, right - MARGIN - stat_w - 16.0);
+            canvas.text(MARGIN, y, 16.0, &name, palette.text);
+            let bar_w = (right - MARGIN) * (*secs as f32 / max_secs as f32);
+            canvas.fill_rect(MARGIN, y + 10.0, right - MARGIN, 6.0, palette.lane_bg);
+            canvas.fill_rect(MARGIN, y + 10.0, bar_w.max(2.0), 6.0, palette.focus);
+            y += TASK_ROW_H;
+        }
+        canvas.text(MARGIN, height as f32 - 20.0, 12.0, "红番茄 · red-tomato", palette.text_dim);
+        Ok(canvas.image)
+    }
+}
+
+/// 编码为 PNG（RGBA 8 位）
+pub fn encode_png(image: &ColorImage) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    let mut encoder = png::Encoder::new(&mut out, image.size[0] as u32, image.size[1] as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let rgba: Vec<u8> = image.pixels.iter().flat_map(|c| c.to_srgba_unmultiplied()).collect();
+    let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+    writer.write_image_data(&rgba).map_err(|e| e.to_string())?;
+    writer.finish().map_err(|e| e.to_string())?;
+    Ok(out)
+}
+
+/// 绘制目标：不透明背景上的像素缓冲与字体
+struct Canvas {
+    image: ColorImage,
+    font: FontVec,
+}
+
+impl Canvas {
+    /// 以 coverage（0–1）把 color 叠加到 (x, y)
+    fn blend(&mut self, x: i32, y: i32, color: Color32, coverage: f32) {
+        let [w, h] = self.image.size;
+        if x < 0 || y < 0 || x as usize >= w || y as usize >= h || coverage <= 0.0 {
+            return;
+        }
+        let a = coverage.min(1.0) * color.a() as f32 / 255.0;
+        let dst = &mut self.image.pixels[y as usize * w + x as usize];
+        let mix = |s: u8, d: u8| (s as f32 * a + d as f32 * (1.0 - a)).round() as u8;
+        *dst = Color32::from_rgb(mix(color.r(), dst.r()), mix(color.g(), dst.g()), mix(color.b(), dst.b()));
+    }
+
+    fn fill_rect(&mut self, x: f32, y: f32, w: f32, h: f32, color: Color32) {
+        for py in y.round() as i32..(y + h).round() as i32 {
+            for px in x.round() as i32..(x + w).round() as i32 {
+                self.blend(px, py, color, 1.0);
+            }
+        }
+    }
+
+    /// 抗锯齿实心圆
+    fn fill_circle(&mut self, cx: f32, cy: f32, r: f32, color: Color32) {
+        for py in (cy - r - 1.0) as i32..=(cy + r + 1.0) as i32 {
+            for px in (cx - r - 1.0) as i32..=(cx + r + 1.0) as i32 {
+                let d = ((px as f32 + 0.5 - cx).powi(2) + (py as f32 + 0.5 - cy).powi(2)).sqrt();
+                self.blend(px, py, color, (r - d + 0.5).clamp(0.0, 1.0));
+            }
+        }
+    }
+
+    /// 文字宽度（像素）
+    fn measure(&self, px: f32, text: &str) -> f32 {
+        let font = self.font.as_scaled(PxScale::from(px));
+        let mut width = 0.0;
+        let mut prev = None;
+        for c in text.chars() {
+            let id = font.glyph_id(c);
+            if let Some(p) = prev {
+                width += font.kern(p, id);
+            }
+            width += font.h_advance(id);
+            prev = Some(id);
+        }
+        width
+    }
+
+    /// 超出 max_width 时截断并加「…」
+    fn truncate(&self, px: f32, text: &str, max_width: f32) -> String {
+        if self.measure(px, text) <= max_width {
+            return text.to_string();
+        }
+        let mut out = String::new();
+        for c in text.chars() {
+            out.push(c);
+            if self.measure(px, &format!("{}…", out)) > max_width {
+                out.pop();
+                break;
+            }
+        }
+        format!("{}…", out)
+    }
+
+    /// 以 (x, baseline) 为起点绘制一行文字
+    fn text(&mut self, x: f32, baseline: f32, px: f32, text: &str, color: Color32) {
+        let scale = PxScale::from(px);
+        let mut caret = x;
+        let mut prev = None;
+        for c in text.chars() {
+            let (id, advance, kern) = {
+                let font = self.font.as_scaled(scale);
+                let id = font.glyph_id(c);
+                (id, font.h_advance(id), prev.map_or(0.0, |p| font.kern(p, id)))
+            };
+            caret += kern;
+            let glyph = id.with_scale_and_position(scale, ab_glyph::point(caret, baseline));
+            if let Some(outlined) = self.font.outline_glyph(glyph) {
+                let bounds = outlined.px_bounds();
+                let mut coverage = Vec::new();
+                outlined.draw(|gx, gy, c| coverage.push((gx, gy, c)));
+                for (gx, gy, c) in coverage {
+                    self.blend(bounds.min.x as i32 + gx as i32, bounds.min.y as i32 + gy as i32, color, c);
+                }
+            }
+            caret += advance;
+            prev = Some(id);
+        }
+    }
+}
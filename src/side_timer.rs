@@ -0,0 +1,62 @@
+//! 副计时：与番茄钟并行的一个倒计时（如「烤箱 12 分钟」、会议前提醒），到点后单独通知
+//!
+//! 按墙钟计时，不随番茄钟暂停；截止时间随界面状态保存，重启后继续。两种模式下都以一行小字显示在计时器下方。
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 副计时时长上限（分钟）
+pub const MAX_MINUTES: u32 = 24 * 60;
+
+/// 一个副计时
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SideTimer {
+    /// 名称，如「烤箱」；可为空
+    pub label: String,
+    pub ends_at: DateTime<Utc>,
+    /// 已到点并发出通知（界面上保留「到点了」直到关闭）
+    #[serde(default)]
+    pub notified: bool,
+}
+
+impl SideTimer {
+    pub fn start(label: &str, minutes: u32, now: DateTime<Utc>) -> Self {
+        Self {
+            label: label.trim().to_string(),
+            ends_at: now + chrono::Duration::minutes(minutes.clamp(1, MAX_MINUTES) as i64),
+            notified: false,
+        }
+    }
+
+    /// 剩余秒数（到点后为 0）
+    pub fn remaining_secs(&self, now: DateTime<Utc>) -> i64 {
+        (self.ends_at - now).num_seconds().max(0)
+    }
+
+    /// 刚到点时返回 true（只返回一次）
+    pub fn take_due(&mut self, now: DateTime<Utc>) -> bool {
+        if self.notified || now < self.ends_at {
+            return false;
+        }
+        self.notified = true;
+        true
+    }
+
+    /// 名称，未填时为「副计时」
+    pub fn name(&self) -> &str {
+        if self.label.is_empty() { "副计时" } else { &self.label }
+    }
+
+    /// 显示的一行：`⏲ 烤箱 08:42`，到点后为 `⏲ 烤箱 到点了`
+    pub fn display(&self, now: DateTime<Utc>) -> String {
+        if self.notified {
+            return format!("⏲ {} 到点了", self.name());
+        }
+        let secs = self.remaining_secs(now);
+        if secs >= 3600 {
+            format!("⏲ {} {}:{:02}:{:02}", self.name(), secs / 3600, secs % 3600 / 60, secs % 60)
+        } else {
+            format!("⏲ {} {:02}:{:02}", self.name(), secs / 60, secs % 60)
+        }
+    }
+}
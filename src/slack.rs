@@ -0,0 +1,314 @@
+//! Slack 状态：专注时把 Slack 个人状态设为「🍅 Focusing until 14:25」并开启勿扰，专注结束后恢复原来的状态
+//!
+//! 使用用户 OAuth 令牌（xoxp-，需 `users.profile:read`、`users.profile:write`、`dnd:write` 权限）调用 Web API：
+//! 进入专注时先读出原状态保存在内存中，再设置专注状态与勿扰（`dnd.setSnooze`，时长到专注结束）；
+//! 结束时写回原状态并结束勿扰。专注状态带过期时间（暂停中按 1 小时），程序中途退出或崩溃时 Slack 也会到时自动清除。
+//!
+//! 请求都在后台线程；网络错误与限流时隔一段时间重试，令牌无效或缺少权限时停止请求，直到改动设置。
+
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+const API_BASE: &str = "https://slack.com/api";
+const STATUS_EMOJI: &str = ":tomato:";
+/// 失败后的重试间隔
+const RETRY_INTERVAL: Duration = Duration::from_secs(30);
+const TIMEOUT: Duration = Duration::from_secs(10);
+/// 结束时间变化超过此秒数才重新设置（状态只显示到分钟）
+const END_TOLERANCE_SECS: i64 = 30;
+/// 专注状态在结束后多留的秒数再由 Slack 自动清除（正常情况下由程序先行恢复）
+const EXPIRATION_GRACE_SECS: i64 = 60;
+/// 暂停或加时中结束时间未知，专注状态与勿扰先按这么久设置，恢复计时后按剩余时间重设
+const UNKNOWN_END_SECS: i64 = 3600;
+/// 结束勿扰时勿扰已不在进行（用户手动结束），视为成功
+const SNOOZE_NOT_ACTIVE: &str = "snooze_not_active";
+/// 这些错误重试也不会成功，需用户改动设置
+const FATAL_ERRORS: [&str; 6] =
+    ["invalid_auth", "not_authed", "token_revoked", "token_expired", "account_inactive", "missing_scope"];
+
+/// Slack 设置（默认关闭；仅本机）
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SlackSettings {
+    pub enabled: bool,
+    /// 用户 OAuth 令牌（xoxp-…）
+    pub token: String,
+    /// 专注时同时开启勿扰
+    pub dnd: bool,
+}
+
+impl SlackSettings {
+    pub fn is_active(&self) -> bool {
+        self.enabled && !self.token.trim().is_empty()
+    }
+}
+
+/// 专注中要显示的状态
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SlackFocus {
+    /// 专注结束时刻（Unix 秒）；暂停或加时中为 None，只显示「Focusing」
+    pub until: Option<i64>,
+}
+
+impl SlackFocus {
+    fn same_as(&self, other: &SlackFocus) -> bool {
+        match (self.until, other.until) {
+            (Some(a), Some(b)) => (a - b).abs() <= END_TOLERANCE_SECS,
+            (a, b) => a == b,
+        }
+    }
+
+    /// 状态文字，结束时刻按北京时间显示
+    fn text(&self) -> String {
+        let beijing = crate::app::beijing();
+        match self.until.and_then(|t| beijing.timestamp_opt(t, 0).single()) {
+            Some(end) => format!("Focusing until {}", end.format("%H:%M")),
+            None => "Focusing".to_string(),
+        }
+    }
+}
+
+/// 进入专注前的个人状态
+#[derive(Clone, Debug, Default)]
+struct SavedStatus {
+    text: String,
+    emoji: String,
+    /// 0 为不过期
+    expiration: i64,
+}
+
+/// Slack 状态句柄：持有后台线程的发送端，丢弃时线程恢复原状态后退出
+#[derive(Default)]
+pub struct SlackStatus {
+    worker: Option<(SlackSettings, Sender<Option<SlackFocus>>)>,
+    /// 最近一次交给后台线程的状态
+    last: Option<Option<SlackFocus>>,
+    /// 后台线程报告的状态，设置中显示
+    state: Arc<Mutex<String>>,
+}
+
+impl SlackStatus {
+    /// 每帧调用：按设置启停后台线程（设置改动后重启），专注状态变化时发送
+    pub fn sync(&mut self, settings: &SlackSettings, focus: Option<SlackFocus>) {
+        if !settings.is_active() {
+            self.worker = None;
+            self.last = None;
+            return;
+        }
+        if self.worker.as_ref().is_some_and(|(s, _)| s != settings) {
+            self.worker = None;
+        }
+        if self.worker.is_none() {
+            let (tx, rx) = mpsc::channel();
+            let worker_settings = settings.clone();
+            let state = Arc::clone(&self.state);
+            *state.lock().unwrap() = "等待专注开始".to_string();
+            std::thread::spawn(move || run_worker(&worker_settings, rx, &state));
+            self.worker = Some((settings.clone(), tx));
+            self.last = None;
+        }
+        let changed = match (&self.last, &focus) {
+            (Some(Some(a)), Some(b)) => !a.same_as(b),
+            (Some(None), None) => false,
+            _ => true,
+        };
+        if changed {
+            if let Some((_, tx)) = &self.worker {
+                let _ = tx.send(focus);
+            }
+            self.last = Some(focus);
+        }
+    }
+
+    /// 状态说明（未开启时为空）
+    pub fn state(&self) -> String {
+        if self.worker.is_none() {
+            return String::new();
+        }
+        self.state.lock().unwrap().clone()
+    }
+}
+
+/// 请求失败：可重试（网络、限流、服务端错误）或需用户处理
+enum ApiError {
+    Retry(String),
+    Fatal(String),
+}
+
+impl ApiError {
+    fn message(&self) -> &str {
+        match self {
+            ApiError::Retry(m) | ApiError::Fatal(m) => m,
+        }
+    }
+}
+
+/// 后台线程：把 Slack 状态同步为最新的期望状态；发送端断开时恢复原状态后退出
+fn run_worker(settings: &SlackSettings, rx: mpsc::Receiver<Option<SlackFocus>>, state: &Mutex<String>) {
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(TIMEOUT))
+        .http_status_as_error(false)
+        .build()
+        .into();
+    let client = Client { agent, token: settings.token.trim().to_string() };
+    let mut desired: Option<SlackFocus> = None;
+    // 已设置到 Slack 上的专注状态；Some 时 saved 为进入专注前的状态
+    let mut applied: Option<SlackFocus> = None;
+    let mut saved: Option<SavedStatus> = None;
+    let mut fatal = false;
+    loop {
+        let closed = match rx.recv_timeout(RETRY_INTERVAL) {
+            Ok(focus) => {
+                desired = focus;
+                false
+            }
+            Err(RecvTimeoutError::Timeout) => false,
+            Err(RecvTimeoutError::Disconnected) => {
+                desired = None;
+                true
+            }
+        };
+        let in_sync = match (&applied, &desired) {
+            (Some(a), Some(b)) => a.same_as(b),
+            // 设置专注状态中途失败时也写回原状态
+            (None, None) => saved.is_none(),
+            _ => false,
+        };
+        if !in_sync && !fatal {
+            let result = match desired {
+                Some(focus) => client.apply(settings.dnd, focus, &mut saved),
+                None => client.restore(settings.dnd, saved.as_ref()),
+            };
+            match result {
+                Ok(()) => {
+                    applied = desired;
+                    if desired.is_none() {
+                        saved = None;
+                    }
+                    *state.lock().unwrap() = match desired {
+                        Some(focus) => format!("已设置：{}", focus.text()),
+                        None => "已恢复原状态".to_string(),
+                    };
+                }
+                Err(e) => {
+                    tracing::warn!("Slack 状态更新失败：{}", e.message());
+                    crate::errors::report(
+                        crate::errors::ErrorKind::Integration,
+                        format!("Slack 状态更新失败：{}", e.message()),
+                    );
+                    *state.lock().unwrap() = match &e {
+                        ApiError::Retry(m) => format!("更新失败：{}（{} 秒后重试）", m, RETRY_INTERVAL.as_secs()),
+                        ApiError::Fatal(m) => format!("更新失败：{}（请检查令牌与权限）", m),
+                    };
+                    fatal = matches!(e, ApiError::Fatal(_));
+                }
+            }
+        }
+        if closed {
+            return;
+        }
+    }
+}
+
+struct Client {
+    agent: ureq::Agent,
+    token: String,
+}
+
+impl Client {
+    /// 设置专注状态（首次进入时先保存原状态）与勿扰
+    fn apply(&self, dnd: bool, focus: SlackFocus, saved: &mut Option<SavedStatus>) -> Result<(), ApiError> {
+        if saved.is_none() {
+            let reply = self.call_get("users.profile.get")?;
+            let profile = &reply["profile"];
+            *saved = Some(SavedStatus {
+                text: profile["status_text"].as_str().unwrap_or_default().to_string(),
+                emoji: profile["status_emoji"].as_str().unwrap_or_default().to_string(),
+                expiration: profile["status_expiration"].as_i64().unwrap_or(0),
+            });
+        }
+        let now = Utc::now().timestamp();
+        let until = focus.until.unwrap_or(now + UNKNOWN_END_SECS);
+        self.set_status(&focus.text(), STATUS_EMOJI, until + EXPIRATION_GRACE_SECS)?;
+        if dnd {
+            let minutes = ((until - now).max(60) + 59) / 60;
+            self.call_post("dnd.setSnooze", serde_json::json!({ "num_minutes": minutes }))?;
+        }
+        Ok(())
+    }
+
+    /// 写回进入专注前的状态（原状态已过期则清空）并结束勿扰
+    fn restore(&self, dnd: bool, saved: Option<&SavedStatus>) -> Result<(), ApiError> {
+        let now = Utc::now().timestamp();
+        let saved = saved.filter(|s| s.expiration == 0 || s.expiration > now).cloned().unwrap_or_default();
+        self.set_status(&saved.text, &saved.emoji, saved.expiration)?;
+        if dnd {
+            match self.call_post("dnd.endSnooze", serde_json::json!({})) {
+                Ok(_) => {}
+                // 勿扰已被用户手动结束
+                Err(ApiError::Fatal(e)) if e == SNOOZE_NOT_ACTIVE => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    fn set_status(&self, text: &str, emoji: &str, expiration: i64) -> Result<(), ApiError> {
+        let profile = serde_json::json!({
+            "status_text": text,
+            "status_emoji": emoji,
+            "status_expiration": expiration,
+        });
+        self.call_post("users.profile.set", serde_json::json!({ "profile": profile })).map(|_| ())
+    }
+
+    fn call_get(&self, method: &str) -> Result<serde_json::Value, ApiError> {
+        let response = self
+            .agent
+            .get(&format!("{}/{}", API_BASE, method))
+            .header("Authorization", format!("Bearer {}", self.token))
+            .call();
+        Self::check(response)
+    }
+
+    fn call_post(&self, method: &str, body: serde_json::Value) -> Result<serde_json::Value, ApiError> {
+        let response = self
+            .agent
+            .post(&format!("{}/{}", API_BASE, method))
+            .header("Authorization", format!("Bearer {}", self.token))
+            .header("Content-Type", "application/json; charset=utf-8")
+            .send(body.to_string());
+        Self::check(response)
+    }
+
+    /// Slack 的错误多在 HTTP 200 的 `{"ok": false, "error": ...}` 中返回；限流为 HTTP 429
+    fn check(response: Result<ureq::http::Response<ureq::Body>, ureq::Error>) -> Result<serde_json::Value, ApiError> {
+        let mut response = response.map_err(|e| ApiError::Retry(e.to_string()))?;
+        let status = response.status();
+        if status.as_u16() == 429 {
+            return Err(ApiError::Retry("请求过于频繁".to_string()));
+        }
+        if !status.is_success() {
+            return Err(ApiError::Retry(format!("HTTP {}", status.as_u16())));
+        }
+        let reply: serde_json::Value = response
+            .body_mut()
+            .read_to_string()
+            .map_err(|e| e.to_string())
+            .and_then(|body| serde_json::from_str(&body).map_err(|e| e.to_string()))
+            .map_err(|e| ApiError::Retry(format!("无法解析回复：{}", e)))?;
+        if reply["ok"].as_bool() == Some(true) {
+            return Ok(reply);
+        }
+        let error = reply["error"].as_str().unwrap_or("未知错误").to_string();
+        if FATAL_ERRORS.contains(&error.as_str()) || error == SNOOZE_NOT_ACTIVE {
+            Err(ApiError::Fatal(error))
+        } else {
+            Err(ApiError::Retry(error))
+        }
+    }
+}
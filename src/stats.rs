@@ -0,0 +1,168 @@
+//! 统计计算：由专注记录派生的汇总指标（与 UI 无关）
+
+use chrono::{DateTime, Datelike, Duration, FixedOffset, Timelike};
+
+use crate::app::beijing_day_start;
+use crate::history::FocusRecord;
+
+/// 深度块：相邻两次专注之间的间隔（上一次结束 → 下一次开始）不超过「短休息 + 此宽限」即视为连续
+pub const DEEP_BLOCK_SLACK_SECS: i64 = 5 * 60;
+/// 至少连续多少个番茄才算一个深度块
+pub const DEEP_BLOCK_MIN_SESSIONS: u32 = 2;
+
+/// 一段连续专注（深度块）
+#[derive(Clone, Debug)]
+pub struct DeepBlock {
+    /// 第一次专注开始 / 最后一次专注结束（秒级时间戳）
+    pub start: i64,
+    pub end: i64,
+    pub sessions: u32,
+    pub focus_secs: i64,
+}
+
+/// 某时间范围内的深度块汇总
+#[derive(Clone, Debug, Default)]
+pub struct DeepBlockSummary {
+    pub count: usize,
+    /// 最长深度块的番茄数与专注秒数（按番茄数比较）
+    pub longest_sessions: u32,
+    pub longest_focus_secs: i64,
+}
+
+/// 专注记录 → (开始, 结束, 时长) 并按开始时间排序
+fn sessions(records: &[FocusRecord]) -> Vec<(i64, i64, i64)> {
+    let mut v: Vec<(i64, i64, i64)> = records
+        .iter()
+        .filter_map(|r| {
+            let end = DateTime::parse_from_rfc3339(&r.completed_at).ok()?.timestamp();
+            let d = r.duration_secs.max(0);
+            Some((end - d, end, d))
+        })
+        .collect();
+    v.sort();
+    v
+}
+
+/// 检测连续专注：间隔不超过 max_gap_secs 的相邻专注串成一块，仅保留至少 DEEP_BLOCK_MIN_SESSIONS 个番茄的块
+pub fn deep_blocks(records: &[FocusRecord], max_gap_secs: i64) -> Vec<DeepBlock> {
+    let mut blocks: Vec<DeepBlock> = Vec::new();
+    let mut cur: Option<DeepBlock> = None;
+    for (start, end, secs) in sessions(records) {
+        match cur.as_mut() {
+            Some(b) if start - b.end <= max_gap_secs => {
+                b.end = b.end.max(end);
+                b.sessions += 1;
+                b.focus_secs += secs;
+            }
+            _ => {
+                if let Some(b) = cur.take() {
+                    blocks.push(b);
+                }
+                cur = Some(DeepBlock {
+                    start,
+                    end,
+                    sessions: 1,
+                    focus_secs: secs,
+                });
+            }
+        }
+    }
+    blocks.extend(cur);
+    blocks.retain(|b| b.sessions >= DEEP_BLOCK_MIN_SESSIONS);
+    blocks
+}
+
+/// 汇总开始时间落在 [from, to) 内的深度块
+pub fn summarize_deep_blocks(blocks: &[DeepBlock], from: i64, to: i64) -> DeepBlockSummary {
+    let mut summary = DeepBlockSummary::default();
+    for b in blocks.iter().filter(|b| b.start >= from && b.start < to) {
+        summary.count += 1;
+        if b.sessions > summary.longest_sessions
+            || (b.sessions == summary.longest_sessions && b.focus_secs > summary.longest_focus_secs)
+        {
+            summary.longest_sessions = b.sessions;
+            summary.longest_focus_secs = b.focus_secs;
+        }
+    }
+    summary
+}
+
+/// 一段时间内的平均指标（统计窗口「平均指标」）
+#[derive(Clone, Debug, Default)]
+pub struct SessionMetrics {
+    /// 有完成专注的天数
+    pub working_days: u32,
+    pub sessions: u32,
+    pub avg_session_secs: i64,
+    /// 每天第一个番茄开始时刻的中位数（当天 0 点起的分钟数）
+    pub median_first_start: Option<u32>,
+    pub completed: u32,
+    pub abandoned: u32,
+    /// 休息事件：完成的休息与跳过的休息次数
+    pub breaks_taken: u32,
+    pub breaks_skipped: u32,
+    /// 设定了工作时间时：工作时间内 / 外的专注秒数
+    pub hours_split: Option<(i64, i64)>,
+}
+
+impl SessionMetrics {
+    /// 每个工作日（有专注的天）平均番茄数
+    pub fn pomodoros_per_day(&self) -> Option<f64> {
+        (self.working_days > 0).then(|| self.sessions as f64 / self.working_days as f64)
+    }
+
+    /// 完成率 = 完成 / (完成 + 放弃)
+    pub fn completion_rate(&self) -> Option<f64> {
+        let total = self.completed + self.abandoned;
+        (total > 0).then(|| self.completed as f64 / total as f64)
+    }
+
+    /// 跳过率 = 跳过 / (休息 + 跳过)
+    pub fn break_skip_rate(&self) -> Option<f64> {
+        let total = self.breaks_taken + self.breaks_skipped;
+        (total > 0).then(|| self.breaks_skipped as f64 / total as f64)
+    }
+}
+
+/// 各条专注 (完成时间, 时长) 按工作时间拆分：(工作时间内秒数, 工作时间外秒数)
+pub fn split_by_working_hours(spans: &[(String, i64)], hours: &crate::planner::WorkingHours) -> (i64, i64) {
+    spans.iter().fold((0, 0), |(inside, outside), (completed_at, secs)| {
+        let Ok(end) = DateTime::parse_from_rfc3339(completed_at) else { return (inside, outside) };
+        let secs = (*secs).max(0);
+        let end = end.naive_local();
+        let within = hours.secs_within(end - Duration::seconds(secs), end);
+        (inside + within, outside + secs - within)
+    })
+}
+
+/// 每天第一个番茄的 (完成时间, 时长) → 开始时刻（当天分钟数）的中位数；偶数个时取中间两个的平均
+pub fn median_start_minute(firsts: &[(String, i64)]) -> Option<u32> {
+    let mut minutes: Vec<u32> = firsts
+        .iter()
+        .filter_map(|(completed_at, secs)| {
+            let end = DateTime::parse_from_rfc3339(completed_at).ok()?;
+            let start = end - Duration::seconds((*secs).max(0));
+            // 跨过午夜开始的专注按 0 点计
+            let start = if start.date_naive() == end.date_naive() { start.time() } else { chrono::NaiveTime::MIN };
+            Some((start.num_seconds_from_midnight() / 60) as u32)
+        })
+        .collect();
+    if minutes.is_empty() {
+        return None;
+    }
+    minutes.sort_unstable();
+    let mid = minutes.len() / 2;
+    Some(if minutes.len() % 2 == 0 { (minutes[mid - 1] + minutes[mid]) / 2 } else { minutes[mid] })
+}
+
+/// 今天与本周（周一起）的时间范围 [from, to)，按北京时区的日界划分
+pub fn today_and_week_ranges(now: DateTime<FixedOffset>) -> ((i64, i64), (i64, i64)) {
+    let today = now.date_naive();
+    let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+    let today_start = beijing_day_start(today);
+    let week_start = beijing_day_start(monday);
+    (
+        (today_start, today_start + 24 * 3600),
+        (week_start, week_start + 7 * 24 * 3600),
+    )
+}
@@ -0,0 +1,99 @@
+//! 系统休眠检测：后台线程每秒记一次墙钟时间，两次之间相隔过久说明电脑睡眠 / 休眠过
+//!
+//! 不依赖界面刷新（最小化时界面可能很久不刷新），也不依赖各平台的电源通知。
+//! 检测到的间隔由界面取走，按设置（可被部署策略固定）决定专注中睡眠的时间如何计算。
+
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 心跳间隔
+const HEARTBEAT: Duration = Duration::from_secs(1);
+/// 两次心跳相隔超过这个秒数视为休眠过
+const GAP_THRESHOLD_SECS: i64 = 60;
+
+/// 专注中系统休眠的时间如何处理
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SleepAction {
+    /// 醒来后暂停并询问
+    #[default]
+    Ask,
+    /// 计入专注（如开会时合上了笔记本）
+    Count,
+    /// 不计入，从醒来时继续计时
+    Discard,
+    /// 暂停，休眠时间记为暂停
+    Pause,
+}
+
+impl SleepAction {
+    pub const ALL: [SleepAction; 4] = [SleepAction::Ask, SleepAction::Count, SleepAction::Discard, SleepAction::Pause];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SleepAction::Ask => "询问",
+            SleepAction::Count => "计入专注",
+            SleepAction::Discard => "不计入",
+            SleepAction::Pause => "记为暂停",
+        }
+    }
+}
+
+/// 一次休眠：最后一次心跳到醒来后第一次心跳
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SleepGap {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+}
+
+impl SleepGap {
+    pub fn secs(&self) -> i64 {
+        (self.to - self.from).num_seconds().max(0)
+    }
+}
+
+fn detected() -> &'static Mutex<Option<SleepGap>> {
+    static GAP: Mutex<Option<SleepGap>> = Mutex::new(None);
+    &GAP
+}
+
+/// 最近一次心跳的时间（毫秒时间戳）
+static LAST_BEAT: AtomicI64 = AtomicI64::new(0);
+
+/// 记一次心跳；与上次相隔过久时记下这段休眠。心跳线程与界面都会调用，先到的一方记录
+fn beat() {
+    let now = Utc::now();
+    let last = LAST_BEAT.swap(now.timestamp_millis(), Ordering::SeqCst);
+    let Some(last) = DateTime::from_timestamp_millis(last).filter(|_| last > 0) else { return };
+    if (now - last).num_seconds() > GAP_THRESHOLD_SECS {
+        tracing::info!("检测到系统休眠：{} 至 {}", last, now);
+        let mut gap = detected().lock().unwrap();
+        // 界面还没取走上一次时合并为一段
+        let from = gap.map_or(last, |g| g.from);
+        *gap = Some(SleepGap { from, to: now });
+    }
+}
+
+/// 启动心跳线程（多次调用只启动一次）
+pub fn start() {
+    static STARTED: OnceLock<()> = OnceLock::new();
+    STARTED.get_or_init(|| {
+        beat();
+        std::thread::spawn(|| {
+            loop {
+                std::thread::sleep(HEARTBEAT);
+                beat();
+            }
+        });
+    });
+}
+
+/// 取走检测到的休眠。醒来后界面可能先于心跳线程运行，这里也检查一次，保证在计时推进前发现
+pub fn take_gap() -> Option<SleepGap> {
+    beat();
+    detected().lock().unwrap().take()
+}
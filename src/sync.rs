@@ -0,0 +1,160 @@
+//! 设置同步：把主题、字体、时长等轻量设置通过简单的云端键值接口同步（不含专注历史）
+//!
+//! 接口约定：`GET {url}/{key}` 返回值，`PUT {url}/{key}` 写入值，请求头 `Authorization: Bearer {token}`。
+//! 网络请求在后台线程执行，结果通过 channel 交回 UI 线程，不阻塞界面。
+
+use serde::{Deserialize, Serialize};
+use std::sync::mpsc::{self, Receiver};
+
+use crate::theme::AccentColors;
+
+/// 云端存放设置的键名
+pub const SETTINGS_KEY: &str = "red-tomato-settings";
+
+/// 请求超时（秒）
+const TIMEOUT_SECS: u64 = 10;
+
+/// 参与同步的设置（外观与时长偏好，不含任务与历史）
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SyncedSettings {
+    pub theme: String,
+    pub font: Option<String>,
+    pub focus_secs: i64,
+    pub short_break_secs: i64,
+    pub long_break_secs: i64,
+    pub pomodoros_before_long: u32,
+    #[serde(default)]
+    pub accents: AccentColors,
+}
+
+/// 同步端点配置（仅保存在本机，不参与同步）
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SyncEndpoint {
+    pub url: String,
+    pub token: String,
+}
+
+impl SyncEndpoint {
+    pub fn is_configured(&self) -> bool {
+        !self.url.trim().is_empty()
+    }
+
+    fn key_url(&self) -> String {
+        format!("{}/{}", self.url.trim().trim_end_matches('/'), SETTINGS_KEY)
+    }
+}
+
+/// 后台请求结果
+pub enum SyncResult {
+    /// 拉取成功；None 表示云端还没有设置
+    Pulled(Option<SyncedSettings>),
+    Pushed(SyncedSettings),
+    Failed(String),
+}
+
+/// 设置同步器：同一时间只有一个请求在途
+#[derive(Default)]
+pub struct SettingsSync {
+    pending: Option<Receiver<SyncResult>>,
+}
+
+impl SettingsSync {
+    pub fn is_busy(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// 后台拉取云端设置
+    pub fn pull(&mut self, endpoint: &SyncEndpoint) {
+        let endpoint = endpoint.clone();
+        self.spawn(move || match get(&endpoint) {
+            Ok(None) => SyncResult::Pulled(None),
+            Ok(Some(body)) => match serde_json::from_str(&body) {
+                Ok(settings) => SyncResult::Pulled(Some(settings)),
+                Err(e) => SyncResult::Failed(format!("云端设置格式错误：{}", e)),
+            },
+            Err(e) => SyncResult::Failed(e),
+        });
+    }
+
+    /// 后台推送本机设置
+    pub fn push(&mut self, endpoint: &SyncEndpoint, settings: SyncedSettings) {
+        let endpoint = endpoint.clone();
+        self.spawn(move || {
+            let body = match serde_json::to_string(&settings) {
+                Ok(b) => b,
+                Err(e) => return SyncResult::Failed(e.to_string()),
+            };
+            match put(&endpoint, &body) {
+                Ok(()) => SyncResult::Pushed(settings),
+                Err(e) => SyncResult::Failed(e),
+            }
+        });
+    }
+
+    /// 每帧调用：取回已完成的请求结果
+    pub fn poll(&mut self) -> Option<SyncResult> {
+        let rx = self.pending.as_ref()?;
+        match rx.try_recv() {
+            Ok(result) => {
+                self.pending = None;
+                Some(result)
+            }
+            Err(mpsc::TryRecvError::Empty) => None,
+            Err(mpsc::TryRecvError::Disconnected) => {
+                self.pending = None;
+                Some(SyncResult::Failed("同步线程异常退出".to_string()))
+            }
+        }
+    }
+
+    fn spawn(&mut self, job: impl FnOnce() -> SyncResult + Send + 'static) {
+        if self.pending.is_some() {
+            return;
+        }
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = tx.send(job());
+        });
+        self.pending = Some(rx);
+    }
+}
+
+fn agent() -> ureq::Agent {
+    ureq::Agent::config_builder()
+        .timeout_global(Some(std::time::Duration::from_secs(TIMEOUT_SECS)))
+        .http_status_as_error(false)
+        .build()
+        .into()
+}
+
+/// GET 键值；404 视为云端尚无该键
+fn get(endpoint: &SyncEndpoint) -> Result<Option<String>, String> {
+    let mut resp = agent()
+        .get(&endpoint.key_url())
+        .header("Authorization", &format!("Bearer {}", endpoint.token))
+        .call()
+        .map_err(|e| format!("拉取失败：{}", e))?;
+    match resp.status().as_u16() {
+        404 => Ok(None),
+        200..=299 => resp
+            .body_mut()
+            .read_to_string()
+            .map(Some)
+            .map_err(|e| format!("读取响应失败：{}", e)),
+        code => Err(format!("拉取失败：HTTP {}", code)),
+    }
+}
+
+/// PUT 键值
+fn put(endpoint: &SyncEndpoint, body: &str) -> Result<(), String> {
+    let resp = agent()
+        .put(&endpoint.key_url())
+        .header("Authorization", &format!("Bearer {}", endpoint.token))
+        .header("Content-Type", "application/json")
+        .send(body)
+        .map_err(|e| format!("推送失败：{}", e))?;
+    match resp.status().as_u16() {
+        200..=299 => Ok(()),
+        code => Err(format!("推送失败：HTTP {}", code)),
+    }
+}
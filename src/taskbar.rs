@@ -0,0 +1,273 @@
+//! 任务栏缩略图按钮（仅 Windows）：鼠标悬停任务栏图标时，预览图下方显示「开始 / 暂停」与「跳过」按钮，
+//! 像播放器一样不切回窗口就能控制计时
+//!
+//! 通过 ITaskbarList3 添加按钮，子类化主窗口接收点击（WM_COMMAND / THBN_CLICKED）放入队列；
+//! 界面每帧取走，换成与 HTTP 接口相同的 `Command` 执行。资源管理器重启后收到 TaskbarButtonCreated 时重新添加。
+
+use crate::pomodoro::Command;
+
+/// 按钮图标边长（像素）
+#[cfg(windows)]
+const ICON_SIZE: usize = 16;
+
+/// 按钮图标的白色图形：开始（三角）、暂停（双竖条）、跳过（三角加竖条），返回 RGBA 像素
+#[cfg(windows)]
+fn render_glyph(glyph: Glyph) -> Vec<u8> {
+    let mut rgba = vec![0u8; ICON_SIZE * ICON_SIZE * 4];
+    for y in 0..ICON_SIZE {
+        for x in 0..ICON_SIZE {
+            let (fx, fy) = (x as f32 + 0.5, y as f32 + 0.5);
+            // 左端在 x0、宽 w 的向右三角，高度随 x 线性收窄
+            let triangle = |x0: f32, w: f32| fx >= x0 && fx <= x0 + w && (fy - 8.0).abs() <= (x0 + w - fx) * 0.6;
+            let bar = |x0: usize, x1: usize| (x0..x1).contains(&x) && (3..13).contains(&y);
+            let on = match glyph {
+                Glyph::Play => triangle(4.0, 9.0),
+                Glyph::Pause => bar(4, 7) || bar(9, 12),
+                Glyph::Skip => triangle(3.0, 8.0) || bar(11, 13),
+            };
+            if on {
+                let i = (y * ICON_SIZE + x) * 4;
+                rgba[i..i + 4].copy_from_slice(&[255, 255, 255, 255]);
+            }
+        }
+    }
+    rgba
+}
+
+#[cfg(windows)]
+#[derive(Clone, Copy)]
+enum Glyph {
+    Play,
+    Pause,
+    Skip,
+}
+
+/// 按钮 id
+#[cfg(windows)]
+const BUTTON_TOGGLE: u32 = 0;
+#[cfg(windows)]
+const BUTTON_SKIP: u32 = 1;
+
+/// 点击的按钮 id 转成命令；running 为点击时「开始 / 暂停」按钮显示的是暂停
+#[cfg(windows)]
+fn command_for(button: u32, running: bool) -> Option<Command> {
+    match button {
+        BUTTON_TOGGLE if running => Some(Command::Pause),
+        BUTTON_TOGGLE => Some(Command::Start),
+        BUTTON_SKIP => Some(Command::Skip),
+        _ => None,
+    }
+}
+
+#[cfg(windows)]
+pub use sys::ThumbBar;
+
+/// 非 Windows 平台没有缩略图按钮
+#[cfg(not(windows))]
+#[derive(Default)]
+pub struct ThumbBar;
+
+#[cfg(not(windows))]
+impl ThumbBar {
+    pub fn sync(&mut self, _ctx: &eframe::egui::Context, _frame: &eframe::Frame, _running: bool) -> Vec<Command> {
+        Vec::new()
+    }
+}
+
+#[cfg(windows)]
+mod sys {
+    use std::ffi::c_void;
+    use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+    use std::sync::{Mutex, OnceLock};
+
+    use eframe::egui;
+    use windows_sys::Win32::Foundation::{HWND, LPARAM, LRESULT, WPARAM};
+    use windows_sys::Win32::System::Com::{CLSCTX_INPROC_SERVER, COINIT_APARTMENTTHREADED, CoCreateInstance, CoInitializeEx};
+    use windows_sys::Win32::UI::Shell::{
+        DefSubclassProc, SetWindowSubclass, THB_FLAGS, THB_ICON, THB_TOOLTIP, THBF_ENABLED, THBN_CLICKED, THUMBBUTTON,
+    };
+    use windows_sys::Win32::UI::WindowsAndMessaging::{RegisterWindowMessageW, WM_COMMAND};
+    use windows_sys::core::GUID;
+
+    use super::{BUTTON_SKIP, BUTTON_TOGGLE, Glyph, ICON_SIZE, command_for, render_glyph};
+    use crate::pomodoro::Command;
+
+    const CLSID_TASKBAR_LIST: GUID = GUID::from_u128(0x56fdf344_fd6d_11d0_958a_006097c9a090);
+    const IID_ITASKBAR_LIST3: GUID = GUID::from_u128(0xea1afb91_9e28_4b86_90e9_9e9f8a5eefaf);
+    /// 窗口子类 id
+    const SUBCLASS_ID: usize = 0x7274;
+
+    /// ITaskbarList3 的虚表（只声明用到的方法，其余占位）
+    #[repr(C)]
+    struct Vtbl {
+        _query_interface: usize,
+        _add_ref: usize,
+        release: unsafe extern "system" fn(*mut c_void) -> u32,
+        hr_init: unsafe extern "system" fn(*mut c_void) -> i32,
+        /// ITaskbarList 的 AddTab … SetActiveAlt、ITaskbarList2 的 MarkFullscreenWindow、
+        /// ITaskbarList3 的 SetProgressValue … SetTabActive
+        _other: [usize; 11],
+        thumb_bar_add_buttons: unsafe extern "system" fn(*mut c_void, HWND, u32, *const THUMBBUTTON) -> i32,
+        thumb_bar_update_buttons: unsafe extern "system" fn(*mut c_void, HWND, u32, *const THUMBBUTTON) -> i32,
+    }
+
+    struct TaskbarList(*mut c_void);
+
+    impl TaskbarList {
+        fn new() -> Option<Self> {
+            let mut ptr = std::ptr::null_mut();
+            unsafe {
+                // 界面线程通常已由窗口库初始化 COM，重复初始化的返回值无需处理
+                CoInitializeEx(std::ptr::null(), COINIT_APARTMENTTHREADED);
+                let hr = CoCreateInstance(&CLSID_TASKBAR_LIST, std::ptr::null_mut(), CLSCTX_INPROC_SERVER, &IID_ITASKBAR_LIST3, &mut ptr);
+                if hr < 0 || ptr.is_null() {
+                    return None;
+                }
+            }
+            let list = Self(ptr);
+            (unsafe { (list.vtbl().hr_init)(list.0) } >= 0).then_some(list)
+        }
+
+        fn vtbl(&self) -> &Vtbl {
+            unsafe { &**(self.0 as *const *const Vtbl) }
+        }
+
+        /// 首次添加按钮（每个窗口只能添加一次）或更新已有按钮
+        fn set_buttons(&self, hwnd: isize, buttons: &[THUMBBUTTON], add: bool) -> bool {
+            let f = if add { self.vtbl().thumb_bar_add_buttons } else { self.vtbl().thumb_bar_update_buttons };
+            unsafe { f(self.0, hwnd as HWND, buttons.len() as u32, buttons.as_ptr()) >= 0 }
+        }
+    }
+
+    impl Drop for TaskbarList {
+        fn drop(&mut self) {
+            unsafe { (self.vtbl().release)(self.0) };
+        }
+    }
+
+    fn clicks() -> &'static Mutex<Vec<u32>> {
+        static CLICKS: Mutex<Vec<u32>> = Mutex::new(Vec::new());
+        &CLICKS
+    }
+
+    /// 点击后唤醒界面（窗口在后台时可能不会自行重绘）
+    static CONTEXT: OnceLock<egui::Context> = OnceLock::new();
+    /// TaskbarButtonCreated 消息号
+    static BUTTON_CREATED: AtomicU32 = AtomicU32::new(0);
+    /// 任务栏按钮（重新）创建，需重新添加缩略图按钮
+    static READD: AtomicBool = AtomicBool::new(false);
+
+    unsafe extern "system" fn subclass_proc(
+        hwnd: HWND,
+        msg: u32,
+        wparam: WPARAM,
+        lparam: LPARAM,
+        _id: usize,
+        _data: usize,
+    ) -> LRESULT {
+        if msg == WM_COMMAND && (wparam >> 16) as u32 & 0xffff == THBN_CLICKED {
+            clicks().lock().unwrap().push((wparam & 0xffff) as u32);
+            if let Some(ctx) = CONTEXT.get() {
+                ctx.request_repaint();
+            }
+        } else if msg != 0 && msg == BUTTON_CREATED.load(Ordering::Relaxed) {
+            READD.store(true, Ordering::Relaxed);
+            if let Some(ctx) = CONTEXT.get() {
+                ctx.request_repaint();
+            }
+        }
+        unsafe { DefSubclassProc(hwnd, msg, wparam, lparam) }
+    }
+
+    /// 缩略图按钮：按计时状态切换「开始 / 暂停」图标，取回点击
+    #[derive(Default)]
+    pub struct ThumbBar {
+        list: Option<TaskbarList>,
+        hwnd: isize,
+        /// 开始、暂停、跳过图标句柄
+        icons: Vec<isize>,
+        added: bool,
+        /// 按钮当前显示的是否为暂停（计时进行中）
+        shown: Option<bool>,
+        /// 初始化失败后不再尝试
+        failed: bool,
+    }
+
+    impl ThumbBar {
+        /// 每帧调用；返回自上一帧以来点击按钮对应的命令
+        pub fn sync(&mut self, ctx: &egui::Context, frame: &eframe::Frame, running: bool) -> Vec<Command> {
+            if self.list.is_none() && !self.failed {
+                self.install(ctx, frame);
+            }
+            if let Some(list) = &self.list {
+                if READD.swap(false, Ordering::Relaxed) {
+                    self.added = false;
+                    self.shown = None;
+                }
+                // 任务栏按钮尚未创建时添加会失败，等 TaskbarButtonCreated 后再添加
+                let add = !self.added && self.shown.is_none();
+                let update = self.added && self.shown != Some(running);
+                if add || update {
+                    let ok = list.set_buttons(self.hwnd, &self.buttons(running), add);
+                    self.added |= add && ok;
+                }
+                self.shown = Some(running);
+            }
+            // 点击时按钮显示的状态即上一帧同步的状态
+            let shown = self.shown.unwrap_or(running);
+            std::mem::take(&mut *clicks().lock().unwrap())
+                .into_iter()
+                .filter_map(|button| command_for(button, shown))
+                .collect()
+        }
+
+        fn install(&mut self, ctx: &egui::Context, frame: &eframe::Frame) {
+            let Some(hwnd) = crate::tray::window_hwnd(frame) else { return };
+            let _ = CONTEXT.set(ctx.clone());
+            let name: Vec<u16> = "TaskbarButtonCreated\0".encode_utf16().collect();
+            BUTTON_CREATED.store(unsafe { RegisterWindowMessageW(name.as_ptr()) }, Ordering::Relaxed);
+            let subclassed = unsafe { SetWindowSubclass(hwnd as HWND, Some(subclass_proc), SUBCLASS_ID, 0) } != 0;
+            match TaskbarList::new().filter(|_| subclassed) {
+                Some(list) => {
+                    self.hwnd = hwnd;
+                    self.icons = [Glyph::Play, Glyph::Pause, Glyph::Skip]
+                        .into_iter()
+                        .map(|g| crate::tray::create_icon(&render_glyph(g), ICON_SIZE))
+                        .collect();
+                    self.list = Some(list);
+                }
+                None => {
+                    tracing::warn!("任务栏缩略图按钮初始化失败");
+                    self.failed = true;
+                }
+            }
+        }
+
+        fn buttons(&self, running: bool) -> [THUMBBUTTON; 2] {
+            let button = |id: u32, icon: isize, tip: &str| {
+                let mut sz_tip = [0u16; 260];
+                for (dst, src) in sz_tip.iter_mut().zip(tip.encode_utf16().take(259)) {
+                    *dst = src;
+                }
+                THUMBBUTTON {
+                    dwMask: THB_ICON | THB_TOOLTIP | THB_FLAGS,
+                    iId: id,
+                    iBitmap: 0,
+                    hIcon: icon as _,
+                    szTip: sz_tip,
+                    dwFlags: THBF_ENABLED,
+                }
+            };
+            let (icon, tip) = if running { (self.icons[1], "暂停") } else { (self.icons[0], "开始") };
+            [button(BUTTON_TOGGLE, icon, tip), button(BUTTON_SKIP, self.icons[2], "跳过当前阶段")]
+        }
+    }
+
+    impl Drop for ThumbBar {
+        fn drop(&mut self) {
+            for icon in self.icons.drain(..) {
+                crate::tray::destroy_icon(icon);
+            }
+        }
+    }
+}
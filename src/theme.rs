@@ -0,0 +1,181 @@
+//! 主题：White Text 深色 / 浅色两套配色，可跟随系统深浅色或手动指定
+
+use eframe::egui;
+use egui::Color32;
+use serde::{Deserialize, Serialize};
+
+use crate::pomodoro::Phase;
+
+/// 主题模式（设置中选择）
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ThemeMode {
+    /// 跟随系统深色/浅色
+    System,
+    Dark,
+    Light,
+}
+
+impl ThemeMode {
+    pub const ALL: [ThemeMode; 3] = [ThemeMode::System, ThemeMode::Dark, ThemeMode::Light];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ThemeMode::System => "跟随系统",
+            ThemeMode::Dark => "深色",
+            ThemeMode::Light => "浅色",
+        }
+    }
+
+    fn preference(self) -> egui::ThemePreference {
+        match self {
+            ThemeMode::System => egui::ThemePreference::System,
+            ThemeMode::Dark => egui::ThemePreference::Dark,
+            ThemeMode::Light => egui::ThemePreference::Light,
+        }
+    }
+}
+
+pub fn mode_to_str(m: ThemeMode) -> &'static str {
+    match m {
+        ThemeMode::System => "System",
+        ThemeMode::Dark => "Dark",
+        ThemeMode::Light => "Light",
+    }
+}
+
+pub fn mode_from_str(s: &str) -> ThemeMode {
+    match s {
+        "Dark" => ThemeMode::Dark,
+        "Light" => ThemeMode::Light,
+        _ => ThemeMode::System,
+    }
+}
+
+/// 一套界面配色（所有 UI 颜色都从这里取）
+#[derive(Clone, Copy, Debug)]
+pub struct Palette {
+    /// 窗口背景
+    pub bg: Color32,
+    /// 背景点阵图案
+    pub pattern: Color32,
+    /// 主文字（计时器等）
+    pub text: Color32,
+    /// 次要文字
+    pub text_dim: Color32,
+    /// 阶段强调色：专注 / 短休息 / 长休息
+    pub focus: Color32,
+    pub short_break: Color32,
+    pub long_break: Color32,
+    /// 加时（专注到点后继续正计时）的计时与文案颜色
+    pub overtime: Color32,
+    /// 番茄数圆圈描边
+    pub circle_stroke: Color32,
+    /// 时间轴底色与刻度线
+    pub lane_bg: Color32,
+    pub grid: Color32,
+    /// 时间审计：计划块 / 日历事件 / 计划内未执行 / 计划外专注
+    pub plan: Color32,
+    pub calendar: Color32,
+    pub untracked: Color32,
+    pub unplanned: Color32,
+}
+
+/// White Text 深色主题（参考 OnePomodoro WhiteTextView.xaml.cs）
+pub const DARK: Palette = Palette {
+    bg: Color32::from_rgb(18, 18, 24),
+    pattern: Color32::from_rgba_premultiplied(12, 12, 12, 12),
+    text: Color32::from_rgb(255, 255, 255),
+    text_dim: Color32::from_rgb(200, 200, 210),
+    focus: Color32::from_rgb(100, 220, 130),
+    short_break: Color32::from_rgb(255, 193, 7),
+    long_break: Color32::from_rgb(217, 17, 83),
+    overtime: Color32::from_rgb(90, 200, 250),
+    circle_stroke: Color32::from_rgb(80, 80, 90),
+    lane_bg: Color32::from_rgb(30, 30, 38),
+    grid: Color32::from_rgb(50, 50, 60),
+    plan: Color32::from_rgb(90, 120, 170),
+    calendar: Color32::from_rgb(150, 110, 200),
+    untracked: Color32::from_rgb(217, 17, 83),
+    unplanned: Color32::from_rgb(255, 152, 0),
+};
+
+/// White Text 浅色变体：暖白背景 + 深色文字，强调色略加深以保证对比度
+pub const LIGHT: Palette = Palette {
+    bg: Color32::from_rgb(246, 244, 240),
+    pattern: Color32::from_rgba_premultiplied(0, 0, 0, 12),
+    text: Color32::from_rgb(24, 24, 30),
+    text_dim: Color32::from_rgb(90, 90, 100),
+    focus: Color32::from_rgb(46, 160, 80),
+    short_break: Color32::from_rgb(220, 150, 0),
+    long_break: Color32::from_rgb(200, 16, 76),
+    overtime: Color32::from_rgb(0, 120, 200),
+    circle_stroke: Color32::from_rgb(160, 160, 170),
+    lane_bg: Color32::from_rgb(228, 226, 222),
+    grid: Color32::from_rgb(200, 200, 205),
+    plan: Color32::from_rgb(80, 120, 190),
+    calendar: Color32::from_rgb(140, 100, 200),
+    untracked: Color32::from_rgb(200, 16, 76),
+    unplanned: Color32::from_rgb(235, 130, 0),
+};
+
+impl Palette {
+    /// 阶段强调色（进度条、阶段文案）
+    pub fn phase_accent(&self, phase: Phase) -> Color32 {
+        match phase {
+            Phase::Focus => self.focus,
+            Phase::ShortBreak => self.short_break,
+            Phase::LongBreak => self.long_break,
+        }
+    }
+
+    /// 番茄数圆圈填充色：与长休息同色（满一轮番茄即进入长休息）
+    pub fn circle_fill(&self) -> Color32 {
+        self.long_break
+    }
+}
+
+/// 用户自定义的阶段强调色（RGB），None 表示沿用主题默认色
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AccentColors {
+    pub focus: Option<[u8; 3]>,
+    pub short_break: Option<[u8; 3]>,
+    pub long_break: Option<[u8; 3]>,
+}
+
+impl AccentColors {
+    /// 用自定义强调色覆盖配色中的阶段色
+    pub fn apply_to(&self, mut palette: Palette) -> Palette {
+        let rgb = |c: [u8; 3]| Color32::from_rgb(c[0], c[1], c[2]);
+        if let Some(c) = self.focus {
+            palette.focus = rgb(c);
+        }
+        if let Some(c) = self.short_break {
+            palette.short_break = rgb(c);
+        }
+        if let Some(c) = self.long_break {
+            palette.long_break = rgb(c);
+        }
+        palette
+    }
+
+    pub fn get_mut(&mut self, phase: Phase) -> &mut Option<[u8; 3]> {
+        match phase {
+            Phase::Focus => &mut self.focus,
+            Phase::ShortBreak => &mut self.short_break,
+            Phase::LongBreak => &mut self.long_break,
+        }
+    }
+}
+
+/// 把主题模式交给 egui（System 时由 egui 跟随系统深浅色），返回当前生效的配色（已套用自定义强调色）
+pub fn apply(ctx: &egui::Context, mode: ThemeMode, accents: &AccentColors) -> Palette {
+    let pref = mode.preference();
+    if ctx.options(|o| o.theme_preference) != pref {
+        ctx.set_theme(pref);
+    }
+    let base = match ctx.theme() {
+        egui::Theme::Dark => DARK,
+        egui::Theme::Light => LIGHT,
+    };
+    accents.apply_to(base)
+}
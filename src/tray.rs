@@ -0,0 +1,245 @@
+//! 托盘计时图标（仅 Windows）：系统托盘中一个按阶段着色的饼图，画出剩余进度与剩余分钟数，
+//! 窗口最小化或被挡住时也能瞄一眼计时；悬停提示显示阶段与剩余时间
+//!
+//! 图标只在分钟数、阶段颜色或饼图进度格变化时重画（专注中约每分钟一次）。图标没有菜单，点击不做处理。
+
+/// 图标边长（像素），系统按托盘尺寸缩放
+const ICON_SIZE: usize = 32;
+/// 饼图进度分格数，进度只在跨格时重画
+pub const PIE_STEPS: f32 = 32.0;
+/// 同一进程中的托盘图标 id
+#[cfg(windows)]
+const TRAY_ID: u32 = 1;
+
+/// 托盘图标上显示的内容
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrayState {
+    /// 剩余分钟数（加时中为已加时分钟数），超过 99 显示 99
+    pub minutes: u32,
+    /// 饼图中剩余部分的比例 0..=1，按 `PIE_STEPS` 取整
+    pub remaining: f32,
+    pub color: [u8; 3],
+    /// 悬停提示
+    pub tip: String,
+}
+
+/// 3×5 点阵数字，每个数字 15 位，自上而下、自左而右
+const DIGITS: [u16; 10] = [
+    0b111_101_101_101_111,
+    0b010_110_010_010_111,
+    0b111_001_111_100_111,
+    0b111_001_111_001_111,
+    0b101_101_111_001_001,
+    0b111_100_111_001_111,
+    0b111_100_111_101_111,
+    0b111_001_001_001_001,
+    0b111_101_111_101_111,
+    0b111_101_111_001_111,
+];
+/// 点阵数字放大倍数
+const DIGIT_SCALE: usize = 3;
+
+/// 画图标，返回 RGBA 像素（逐行）：阶段色饼图从 12 点方向顺时针画剩余部分，已过部分为暗色，中间白色分钟数
+pub fn render(state: &TrayState) -> Vec<u8> {
+    let size = ICON_SIZE as f32;
+    let center = size / 2.0;
+    let radius = center - 0.5;
+    let [r, g, b] = state.color;
+    let dim = [r / 3 + 24, g / 3 + 24, b / 3 + 24];
+    let text = digit_mask(state.minutes.min(99));
+    let mut rgba = vec![0u8; ICON_SIZE * ICON_SIZE * 4];
+    for y in 0..ICON_SIZE {
+        for x in 0..ICON_SIZE {
+            let (dx, dy) = (x as f32 + 0.5 - center, y as f32 + 0.5 - center);
+            let dist = (dx * dx + dy * dy).sqrt();
+            // 边缘一像素内渐隐，避免锯齿
+            let alpha = (radius - dist + 0.5).clamp(0.0, 1.0);
+            if alpha <= 0.0 {
+                continue;
+            }
+            // 12 点方向为 0，顺时针增加
+            let turn = dx.atan2(-dy).rem_euclid(std::f32::consts::TAU) / std::f32::consts::TAU;
+            let mut pixel = if turn < state.remaining { [r, g, b] } else { dim };
+            if text[y][x] {
+                pixel = [255, 255, 255];
+            } else if near(&text, x, y) {
+                // 数字描一圈深色边，浅色阶段色上也看得清
+                pixel = [32, 32, 32];
+            }
+            let i = (y * ICON_SIZE + x) * 4;
+            rgba[i..i + 4].copy_from_slice(&[pixel[0], pixel[1], pixel[2], (alpha * 255.0) as u8]);
+        }
+    }
+    rgba
+}
+
+/// 分钟数的点阵，居中放在图标上
+fn digit_mask(minutes: u32) -> [[bool; ICON_SIZE]; ICON_SIZE] {
+    let mut mask = [[false; ICON_SIZE]; ICON_SIZE];
+    let digits: Vec<usize> = minutes.to_string().bytes().map(|b| (b - b'0') as usize).collect();
+    let (w, h, gap) = (3 * DIGIT_SCALE, 5 * DIGIT_SCALE, 2);
+    let total_w = digits.len() * w + (digits.len() - 1) * gap;
+    let (left, top) = ((ICON_SIZE - total_w) / 2, (ICON_SIZE - h) / 2);
+    for (n, digit) in digits.into_iter().enumerate() {
+        let x0 = left + n * (w + gap);
+        for y in 0..h {
+            for x in 0..w {
+                let bit = 14 - ((y / DIGIT_SCALE) * 3 + x / DIGIT_SCALE);
+                mask[top + y][x0 + x] = (DIGITS[digit] >> bit) & 1 == 1;
+            }
+        }
+    }
+    mask
+}
+
+/// 周围 8 格中是否有数字像素
+fn near(mask: &[[bool; ICON_SIZE]; ICON_SIZE], x: usize, y: usize) -> bool {
+    let range = |v: usize| v.saturating_sub(1)..=(v + 1).min(ICON_SIZE - 1);
+    range(y).any(|yy| range(x).any(|xx| mask[yy][xx]))
+}
+
+/// 托盘图标：记住当前显示的内容，变化时才重画
+#[derive(Default)]
+pub struct TrayIcon {
+    shown: Option<TrayState>,
+    /// 已添加图标时所属的窗口
+    hwnd: Option<isize>,
+    /// 当前图标句柄（0 为无）
+    icon: isize,
+}
+
+impl TrayIcon {
+    /// 每帧调用；state 为 None 时移除图标
+    pub fn sync(&mut self, frame: &eframe::Frame, state: Option<TrayState>) {
+        if self.shown == state {
+            return;
+        }
+        let Some(state) = state else {
+            self.remove();
+            return;
+        };
+        let Some(hwnd) = window_hwnd(frame) else { return };
+        let icon = create_icon(&render(&state), ICON_SIZE);
+        if show(hwnd, self.hwnd.is_some(), icon, &state.tip) {
+            destroy_icon(self.icon);
+            self.icon = icon;
+            self.hwnd = Some(hwnd);
+        } else {
+            tracing::warn!("更新托盘图标失败");
+            destroy_icon(icon);
+        }
+        self.shown = Some(state);
+    }
+
+    fn remove(&mut self) {
+        if let Some(hwnd) = self.hwnd.take() {
+            hide(hwnd);
+        }
+        destroy_icon(std::mem::take(&mut self.icon));
+        self.shown = None;
+    }
+}
+
+impl Drop for TrayIcon {
+    fn drop(&mut self) {
+        self.remove();
+    }
+}
+
+/// 主窗口句柄（任务栏缩略图按钮也用）
+#[cfg(windows)]
+pub fn window_hwnd(frame: &eframe::Frame) -> Option<isize> {
+    use raw_window_handle::{HasWindowHandle, RawWindowHandle};
+    match frame.window_handle().ok()?.as_raw() {
+        RawWindowHandle::Win32(w) => Some(w.hwnd.get()),
+        _ => None,
+    }
+}
+
+/// 由 size×size 的 RGBA 像素生成图标：32 位带透明度的彩色位图加全零掩码；失败时为 0
+#[cfg(windows)]
+pub fn create_icon(rgba: &[u8], size: usize) -> isize {
+    use windows_sys::Win32::Graphics::Gdi::{CreateBitmap, DeleteObject};
+    use windows_sys::Win32::UI::WindowsAndMessaging::{CreateIconIndirect, ICONINFO};
+    // GDI 位图按 BGRA 排列
+    let bgra: Vec<u8> = rgba.chunks_exact(4).flat_map(|p| [p[2], p[1], p[0], p[3]]).collect();
+    let mask_bits = vec![0u8; size * size / 8];
+    let side = size as i32;
+    unsafe {
+        let color = CreateBitmap(side, side, 1, 32, bgra.as_ptr().cast());
+        let mask = CreateBitmap(side, side, 1, 1, mask_bits.as_ptr().cast());
+        let info = ICONINFO {
+            fIcon: 1,
+            xHotspot: 0,
+            yHotspot: 0,
+            hbmMask: mask,
+            hbmColor: color,
+        };
+        let icon = CreateIconIndirect(&info);
+        // 图标持有位图的副本
+        DeleteObject(color);
+        DeleteObject(mask);
+        icon as isize
+    }
+}
+
+#[cfg(windows)]
+pub fn destroy_icon(icon: isize) {
+    use windows_sys::Win32::UI::WindowsAndMessaging::DestroyIcon;
+    if icon != 0 {
+        unsafe { DestroyIcon(icon as _) };
+    }
+}
+
+/// 添加或更新托盘图标
+#[cfg(windows)]
+fn show(hwnd: isize, added: bool, icon: isize, tip: &str) -> bool {
+    use windows_sys::Win32::UI::Shell::{NIF_ICON, NIF_TIP, NIM_ADD, NIM_MODIFY, Shell_NotifyIconW};
+    let mut data = notify_data(hwnd);
+    data.uFlags = NIF_ICON | NIF_TIP;
+    data.hIcon = icon as _;
+    // 提示最长 127 个 UTF-16 单元，末尾留 0
+    for (dst, src) in data.szTip.iter_mut().zip(tip.encode_utf16().take(127)) {
+        *dst = src;
+    }
+    let message = if added { NIM_MODIFY } else { NIM_ADD };
+    unsafe { Shell_NotifyIconW(message, &data) != 0 }
+}
+
+#[cfg(windows)]
+fn hide(hwnd: isize) {
+    use windows_sys::Win32::UI::Shell::{NIM_DELETE, Shell_NotifyIconW};
+    let data = notify_data(hwnd);
+    unsafe { Shell_NotifyIconW(NIM_DELETE, &data) };
+}
+
+#[cfg(windows)]
+fn notify_data(hwnd: isize) -> windows_sys::Win32::UI::Shell::NOTIFYICONDATAW {
+    use windows_sys::Win32::UI::Shell::NOTIFYICONDATAW;
+    let mut data: NOTIFYICONDATAW = unsafe { std::mem::zeroed() };
+    data.cbSize = std::mem::size_of::<NOTIFYICONDATAW>() as u32;
+    data.hWnd = hwnd as _;
+    data.uID = TRAY_ID;
+    data
+}
+
+#[cfg(not(windows))]
+pub fn window_hwnd(_frame: &eframe::Frame) -> Option<isize> {
+    None
+}
+
+#[cfg(not(windows))]
+pub fn create_icon(_rgba: &[u8], _size: usize) -> isize {
+    0
+}
+
+#[cfg(not(windows))]
+pub fn destroy_icon(_icon: isize) {}
+
+#[cfg(not(windows))]
+fn show(_hwnd: isize, _added: bool, _icon: isize, _tip: &str) -> bool {
+    false
+}
+
+#[cfg(not(windows))]
+fn hide(_hwnd: isize) {}
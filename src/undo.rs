@@ -0,0 +1,43 @@
+//! 撤销：重置 / 完成等清空操作前保存状态快照，短时间内可在底部提示条上一键恢复（快照栈，最新的在栈顶）
+
+use std::time::{Duration, Instant};
+
+/// 操作后可撤销的时长
+pub const UNDO_WINDOW: Duration = Duration::from_secs(10);
+/// 最多保留的快照数
+const MAX_DEPTH: usize = 5;
+
+/// 快照栈：每条带操作名与保存时间，过期的在读取时丢弃
+pub struct UndoStack<T> {
+    entries: Vec<(String, T, Instant)>,
+}
+
+impl<T> Default for UndoStack<T> {
+    fn default() -> Self {
+        Self { entries: Vec::new() }
+    }
+}
+
+impl<T> UndoStack<T> {
+    /// 保存操作前的快照；超过上限时丢弃最旧的
+    pub fn push(&mut self, label: &str, snapshot: T) {
+        if self.entries.len() == MAX_DEPTH {
+            self.entries.remove(0);
+        }
+        self.entries.push((label.to_string(), snapshot, Instant::now()));
+    }
+
+    /// 栈顶仍可撤销的操作名与剩余时间（顺带丢弃过期的快照）
+    pub fn latest(&mut self) -> Option<(&str, Duration)> {
+        self.entries.retain(|(_, _, at)| at.elapsed() < UNDO_WINDOW);
+        self.entries
+            .last()
+            .map(|(label, _, at)| (label.as_str(), UNDO_WINDOW.saturating_sub(at.elapsed())))
+    }
+
+    /// 取出栈顶快照（已过期则为 None）
+    pub fn pop(&mut self) -> Option<T> {
+        let (_, snapshot, at) = self.entries.pop()?;
+        (at.elapsed() < UNDO_WINDOW).then_some(snapshot)
+    }
+}
@@ -0,0 +1,103 @@
+//! 专注记录预写日志：阶段结束时先把记录追加到日志文件并落盘，再写 SQLite
+//!
+//! 写入成功后回放并删除日志；若插入失败或程序在写入途中退出，下次启动时回放。
+//! 回放按「任务 + 完成时间」去重，重复回放不会产生重复记录，因此任何已完成的番茄都不会丢失。
+
+use std::io::Write;
+use std::path::PathBuf;
+
+use rusqlite::Connection;
+
+use crate::history::FocusRecord;
+
+const JOURNAL_FILENAME: &str = "pending_records.jsonl";
+
+/// 日志文件路径（与数据库同目录）
+pub fn journal_path() -> PathBuf {
+    crate::db::data_dir().join(JOURNAL_FILENAME)
+}
+
+/// 追加一条待写入记录（每行一个 JSON），并 fsync 确保落盘
+pub fn append(record: &FocusRecord) -> std::io::Result<()> {
+    let path = journal_path();
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    // 启用加密时日志中的任务名与承诺同样写密文
+    let protected = FocusRecord {
+        task: crate::crypto::protect(&record.task).unwrap_or_else(|| record.task.clone()),
+        commitment: crate::crypto::protect(&record.commitment).unwrap_or_else(|| record.commitment.clone()),
+        ..record.clone()
+    };
+    let line = serde_json::to_string(&protected).map_err(std::io::Error::other)?;
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(&path)?;
+    writeln!(file, "{}", line)?;
+    file.sync_all()
+}
+
+/// 把日志中的记录写入 SQLite（已存在的跳过），成功提交后删除日志。返回新写入条数
+///
+/// 程序中途退出时最后一行可能不完整，无法解析的行直接丢弃（它对应的 append 尚未返回，记录仍在内存中）。
+pub fn replay(conn: &mut Connection) -> Result<usize, rusqlite::Error> {
+    let path = journal_path();
+    let Ok(text) = std::fs::read_to_string(&path) else { return Ok(0) };
+    let inserted = replay_text(conn, &text)?;
+    let _ = std::fs::remove_file(&path);
+    Ok(inserted)
+}
+
+/// 在一个事务中写入日志内容里尚不存在的记录，返回新写入条数
+fn replay_text(conn: &mut Connection, text: &str) -> Result<usize, rusqlite::Error> {
+    let records: Vec<FocusRecord> = text
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    let tx = conn.transaction()?;
+    let mut inserted = 0;
+    for r in &records {
+        if !crate::db::focus_record_exists(&tx, &r.task, &r.completed_at)? {
+            crate::db::insert_focus_record(&tx, r)?;
+            inserted += 1;
+        }
+    }
+    tx.commit()?;
+    Ok(inserted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 日志中的一行（旧版本写入的行没有后来新增的字段，同样能回放）
+    fn line(task: &str, completed_at: &str) -> String {
+        format!(
+            "{{\"task\":\"{}\",\"duration_secs\":1500,\"completed_at\":\"{}\",\"completed_pomodoros\":1}}\n",
+            task, completed_at
+        )
+    }
+
+    fn count(conn: &Connection) -> i64 {
+        conn.query_row("SELECT COUNT(*) FROM focus_records", [], |row| row.get(0)).unwrap()
+    }
+
+    #[test]
+    fn replay_skips_existing_records() {
+        let mut conn = crate::db::open_in_memory().unwrap();
+        let first = line("写周报", "2024-05-12T14:25:00+08:00");
+        assert_eq!(replay_text(&mut conn, &first).unwrap(), 1);
+        let text = [first.clone(), line("写周报", "2024-05-12T15:00:00+08:00"), first].concat();
+        assert_eq!(replay_text(&mut conn, &text).unwrap(), 1);
+        assert_eq!(count(&conn), 2);
+        // 重复回放不产生重复记录
+        assert_eq!(replay_text(&mut conn, &text).unwrap(), 0);
+        assert_eq!(count(&conn), 2);
+    }
+
+    #[test]
+    fn replay_drops_truncated_last_line() {
+        let mut conn = crate::db::open_in_memory().unwrap();
+        let text = line("读书", "2024-05-12T09:00:00+08:00") + "{\"task\":\"写";
+        assert_eq!(replay_text(&mut conn, &text).unwrap(), 1);
+        assert_eq!(count(&conn), 1);
+    }
+}
@@ -0,0 +1,119 @@
+//! 健康提醒：「起来活动一下」「喝水」等，可在每次休息开始时提醒，也可按各自的间隔提醒
+//!
+//! 每条提醒单独开关，各有免打扰时段（可跨午夜，如 `22:00-08:00`）；免打扰期间间隔计时从头算起，
+//! 时段结束后不会立刻补发一次。按通知路由中的「健康提醒」发送。
+
+use chrono::NaiveTime;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// 一条健康提醒
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WellnessReminder {
+    pub enabled: bool,
+    /// 提醒内容
+    pub message: String,
+    /// 每次进入短 / 长休息时提醒
+    pub at_break_start: bool,
+    /// 每隔多少分钟提醒（0 表示不按间隔）
+    pub interval_minutes: u32,
+    /// 免打扰时段 `HH:MM-HH:MM`，为空表示没有
+    pub quiet: String,
+}
+
+impl WellnessReminder {
+    fn new(message: &str, at_break_start: bool, interval_minutes: u32) -> Self {
+        Self {
+            enabled: false,
+            message: message.to_string(),
+            at_break_start,
+            interval_minutes,
+            quiet: "22:00-08:00".to_string(),
+        }
+    }
+
+    /// 已开启、内容不为空且 `time` 不在免打扰时段内
+    fn active(&self, time: NaiveTime) -> bool {
+        self.enabled && !self.message.trim().is_empty() && !self.is_quiet(time)
+    }
+
+    /// `time` 是否在免打扰时段内（格式错误视为没有免打扰）
+    pub fn is_quiet(&self, time: NaiveTime) -> bool {
+        let Some((start, end)) = parse_quiet(&self.quiet) else { return false };
+        if start <= end {
+            time >= start && time < end
+        } else {
+            time >= start || time < end
+        }
+    }
+}
+
+/// 解析免打扰时段；允许结束早于开始（跨午夜），空串或格式错误为 None
+pub fn parse_quiet(text: &str) -> Option<(NaiveTime, NaiveTime)> {
+    let (start, end) = text.split_once(['-', '–'])?;
+    let start = NaiveTime::parse_from_str(start.trim(), "%H:%M").ok()?;
+    let end = NaiveTime::parse_from_str(end.trim(), "%H:%M").ok()?;
+    (start != end).then_some((start, end))
+}
+
+/// 健康提醒设置（仅本机）
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WellnessSettings {
+    pub reminders: Vec<WellnessReminder>,
+}
+
+impl Default for WellnessSettings {
+    fn default() -> Self {
+        Self {
+            reminders: vec![
+                WellnessReminder::new("起来活动一下", true, 0),
+                WellnessReminder::new("喝水", false, 45),
+            ],
+        }
+    }
+}
+
+impl WellnessSettings {
+    /// 进入休息时应发出的提醒内容
+    pub fn at_break_start(&self, time: NaiveTime) -> Vec<&str> {
+        self.reminders
+            .iter()
+            .filter(|r| r.at_break_start && r.active(time))
+            .map(|r| r.message.as_str())
+            .collect()
+    }
+}
+
+/// 按间隔提醒的计时：每条提醒上次提醒（或开始计时）的时间，与设置中的下标对应
+#[derive(Default)]
+pub struct WellnessTimer {
+    since: Vec<Option<Instant>>,
+}
+
+impl WellnessTimer {
+    /// 到了间隔的提醒内容（同时重新计时）；未开启或免打扰中的提醒重新计时
+    pub fn due<'a>(&mut self, settings: &'a WellnessSettings, time: NaiveTime, now: Instant) -> Vec<&'a str> {
+        self.since.resize(settings.reminders.len(), None);
+        let mut due = Vec::new();
+        for (reminder, since) in settings.reminders.iter().zip(&mut self.since) {
+            if reminder.interval_minutes == 0 || !reminder.active(time) {
+                *since = None;
+                continue;
+            }
+            let start = *since.get_or_insert(now);
+            if now.duration_since(start) >= Duration::from_secs(reminder.interval_minutes as u64 * 60) {
+                due.push(reminder.message.as_str());
+                *since = Some(now);
+            }
+        }
+        due
+    }
+
+    /// 删除设置中的第 i 条提醒后同步删除其计时
+    pub fn remove(&mut self, i: usize) {
+        if i < self.since.len() {
+            self.since.remove(i);
+        }
+    }
+}